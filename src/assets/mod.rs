@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Base URL of Mojang's asset object store.
+pub const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
+
+/// Error returned when an asset index cannot be loaded.
+#[derive(Debug, Error)]
+pub enum AssetError {
+    #[error("failed to read asset index: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to parse asset index: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// An asset index, stored as `assets/indexes/<id>.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetIndex {
+    /// Assets keyed by their logical path, e.g. `minecraft/sounds/ambient/cave/cave1.ogg`.
+    pub objects: BTreeMap<String, AssetObject>,
+    /// Set by pre-1.6 indexes: assets must be copied into the instance's `resources/`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub map_to_resources: bool,
+    /// Set by 1.6 indexes: assets must be laid out by name in `assets/virtual/<id>/`.
+    #[serde(
+        default,
+        rename = "virtual",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub is_virtual: bool,
+}
+
+/// A single asset of an index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetObject {
+    /// Hex sha1 of the file, which is also its name in the object store.
+    pub hash: String,
+    /// Size in bytes.
+    pub size: u64,
+}
+
+impl AssetObject {
+    /// Returns the path below `assets/objects`, e.g. `ab/abcdef...`.
+    pub fn object_path(&self) -> String {
+        format!("{}/{}", &self.hash[..2.min(self.hash.len())], self.hash)
+    }
+
+    /// Returns the download URL in Mojang's object store.
+    pub fn url(&self) -> String {
+        format!("{}/{}", RESOURCES_URL, self.object_path())
+    }
+}
+
+impl AssetIndex {
+    /// Parses an asset index document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a valid asset index.
+    pub fn from_json(json: &str) -> Result<Self, AssetError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads an asset index file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Returns `true` if the assets have to be materialized by name before launch.
+    pub fn is_legacy(&self) -> bool {
+        self.map_to_resources || self.is_virtual
+    }
+
+    /// Returns the directory the game reads assets from by name, if the index
+    /// needs one.
+    ///
+    /// Pass the result as `${game_assets}` (and `${assets_root}` for 1.6) when
+    /// launching.
+    ///
+    /// # Arguments
+    ///
+    /// * `assets_root` - The shared `assets` directory.
+    /// * `index_id` - The id of this index, e.g. `legacy` or `pre-1.6`.
+    /// * `instance_dir` - The game directory of the instance.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PathBuf>` - `instance_dir/resources` for `map_to_resources`
+    ///   indexes, `assets_root/virtual/<index_id>` for virtual ones, and `None`
+    ///   for modern indexes.
+    pub fn legacy_dir(
+        &self,
+        assets_root: &Path,
+        index_id: &str,
+        instance_dir: &Path,
+    ) -> Option<PathBuf> {
+        if self.map_to_resources {
+            Some(instance_dir.join("resources"))
+        } else if self.is_virtual {
+            Some(assets_root.join("virtual").join(index_id))
+        } else {
+            None
+        }
+    }
+}
+
+/// Files written by [`materialize_assets`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaterializeReport {
+    /// The directory assets were laid out in, `None` for modern indexes.
+    pub dir: Option<PathBuf>,
+    /// Number of files created or replaced.
+    pub written: usize,
+    /// Number of files that were already up to date.
+    pub unchanged: usize,
+}
+
+/// Lays out the assets of a legacy index by name so pre-1.7 versions find their
+/// sounds and language files.
+///
+/// Objects must already be present in `assets_root/objects`. Files are hard-linked
+/// where possible and copied otherwise; files that already have the right size
+/// are left alone. Modern indexes are a no-op.
+///
+/// # Arguments
+///
+/// * `index` - The asset index.
+/// * `assets_root` - The shared `assets` directory.
+/// * `index_id` - The id of the index.
+/// * `instance_dir` - The game directory of the instance.
+///
+/// # Returns
+///
+/// * `io::Result<MaterializeReport>` - The target directory and file counts.
+///
+/// # Errors
+///
+/// Returns an error if an object is missing or a file cannot be written. Asset
+/// names escaping the target directory are rejected as `InvalidData`.
+pub fn materialize_assets(
+    index: &AssetIndex,
+    assets_root: &Path,
+    index_id: &str,
+    instance_dir: &Path,
+) -> io::Result<MaterializeReport> {
+    let Some(dir) = index.legacy_dir(assets_root, index_id, instance_dir) else {
+        return Ok(MaterializeReport::default());
+    };
+    let objects = assets_root.join("objects");
+    let mut report = MaterializeReport {
+        dir: Some(dir.clone()),
+        ..Default::default()
+    };
+
+    for (name, object) in &index.objects {
+        let relative = Path::new(name);
+        if relative.is_absolute()
+            || relative
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsafe asset name {:?}", name),
+            ));
+        }
+        let target = dir.join(relative);
+        if fs::metadata(&target).is_ok_and(|m| m.len() == object.size) {
+            report.unchanged += 1;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let source = objects.join(object.object_path());
+        let _ = fs::remove_file(&target);
+        if fs::hard_link(&source, &target).is_err() {
+            fs::copy(&source, &target)?;
+        }
+        report.written += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn store_object(assets_root: &Path, data: &[u8]) -> AssetObject {
+        use sha1::{Digest, Sha1};
+        let object = AssetObject {
+            hash: hex::encode(Sha1::digest(data)),
+            size: data.len() as u64,
+        };
+        let path = assets_root.join("objects").join(object.object_path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+        object
+    }
+
+    #[test]
+    fn parses_legacy_flags() {
+        let index = AssetIndex::from_json(
+            r#"{"map_to_resources": true, "objects": {"sound/step/grass1.ogg": {"hash": "abcdef", "size": 3}}}"#,
+        )
+        .unwrap();
+        assert!(index.map_to_resources && index.is_legacy());
+        assert_eq!(
+            index.objects["sound/step/grass1.ogg"].url(),
+            "https://resources.download.minecraft.net/ab/abcdef"
+        );
+        assert!(
+            !AssetIndex::from_json(r#"{"objects": {}}"#)
+                .unwrap()
+                .is_legacy()
+        );
+    }
+
+    #[test]
+    fn materializes_virtual_and_resources_layouts() {
+        let dir = tempdir().unwrap();
+        let assets = dir.path().join("assets");
+        let instance = dir.path().join("instance");
+        let object = store_object(&assets, b"lang");
+        let mut index = AssetIndex {
+            is_virtual: true,
+            ..Default::default()
+        };
+        index.objects.insert("lang/en_US.lang".to_string(), object);
+
+        let report = materialize_assets(&index, &assets, "legacy", &instance).unwrap();
+        let virtual_file = assets.join("virtual/legacy/lang/en_US.lang");
+        assert_eq!(report.dir, Some(assets.join("virtual/legacy")));
+        assert_eq!(report.written, 1);
+        assert_eq!(fs::read(&virtual_file).unwrap(), b"lang");
+
+        let again = materialize_assets(&index, &assets, "legacy", &instance).unwrap();
+        assert_eq!((again.written, again.unchanged), (0, 1));
+
+        index.is_virtual = false;
+        index.map_to_resources = true;
+        materialize_assets(&index, &assets, "pre-1.6", &instance).unwrap();
+        assert_eq!(
+            fs::read(instance.join("resources/lang/en_US.lang")).unwrap(),
+            b"lang"
+        );
+    }
+
+    #[test]
+    fn rejects_asset_names_escaping_the_target() {
+        let dir = tempdir().unwrap();
+        let mut index = AssetIndex {
+            is_virtual: true,
+            ..Default::default()
+        };
+        index.objects.insert(
+            "../../evil".to_string(),
+            AssetObject {
+                hash: "00".repeat(20),
+                size: 1,
+            },
+        );
+        let result = materialize_assets(&index, dir.path(), "legacy", dir.path());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
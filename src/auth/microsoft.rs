@@ -0,0 +1,359 @@
+use super::AuthError;
+use crate::http::{HttpClient, HttpError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// OAuth 2.0 endpoints of the Microsoft identity platform for personal accounts.
+pub const MICROSOFT_AUTHORITY_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0";
+
+/// Scopes needed to sign in to Xbox Live and to receive a refresh token.
+pub const XBOX_LIVE_SCOPE: &str = "XboxLive.signin offline_access";
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// The Azure application a launcher signs in as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicrosoftOAuth {
+    /// Application (client) id registered in Azure for the launcher.
+    pub client_id: String,
+    /// Base URL of the OAuth endpoints.
+    pub base_url: String,
+    /// Space-separated scopes to request.
+    pub scope: String,
+}
+
+impl MicrosoftOAuth {
+    /// Creates a configuration for the given Azure application id.
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            base_url: MICROSOFT_AUTHORITY_URL.to_string(),
+            scope: XBOX_LIVE_SCOPE.to_string(),
+        }
+    }
+
+    /// Sets the base URL, e.g. to use another tenant or a test server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Sets the scopes to request.
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = scope.into();
+        self
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), endpoint)
+    }
+}
+
+/// A code the user enters on another device to sign in.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DeviceCode {
+    /// Opaque code used to poll for the tokens.
+    pub device_code: String,
+    /// Code shown to the user.
+    pub user_code: String,
+    /// Page where the user enters the code, e.g. `https://www.microsoft.com/link`.
+    pub verification_uri: String,
+    /// Seconds until the codes expire.
+    pub expires_in: u64,
+    /// Seconds to wait between polls.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    /// Localized instructions for the user.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// Microsoft account tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MsaTokens {
+    /// Token for signing in to Xbox Live.
+    pub access_token: String,
+    /// Token for getting new tokens without user interaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// When `access_token` expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+impl MsaTokens {
+    /// Returns whether the access token expires within `margin`.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        unix_now() + margin.as_secs() >= self.expires_at
+    }
+}
+
+/// Result of polling for the tokens of a device code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DevicePoll {
+    /// The user has not finished signing in.
+    Pending,
+    /// Polling too fast; the interval must grow by five seconds.
+    SlowDown,
+    /// The user signed in.
+    Complete(MsaTokens),
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+impl From<TokenResponse> for MsaTokens {
+    fn from(response: TokenResponse) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at: unix_now() + response.expires_in,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OAuthErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+impl HttpClient {
+    /// Starts the device-code flow.
+    ///
+    /// Show [`DeviceCode::user_code`] and [`DeviceCode::verification_uri`] to
+    /// the user, then call [`HttpClient::wait_for_device_tokens`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the application is rejected.
+    pub async fn request_device_code(
+        &self,
+        oauth: &MicrosoftOAuth,
+    ) -> Result<DeviceCode, AuthError> {
+        self.post_oauth_form(
+            &oauth.url("devicecode"),
+            &[("client_id", &oauth.client_id), ("scope", &oauth.scope)],
+        )
+        .await
+    }
+
+    /// Polls the token endpoint once for a device code.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::Declined`] if the user declined,
+    /// [`AuthError::DeviceCodeExpired`] if the code expired, or another error if
+    /// the request fails.
+    pub async fn poll_device_tokens(
+        &self,
+        oauth: &MicrosoftOAuth,
+        code: &DeviceCode,
+    ) -> Result<DevicePoll, AuthError> {
+        let result = self
+            .post_oauth_form::<TokenResponse>(
+                &oauth.url("token"),
+                &[
+                    ("grant_type", DEVICE_CODE_GRANT),
+                    ("client_id", &oauth.client_id),
+                    ("device_code", &code.device_code),
+                ],
+            )
+            .await;
+        match result {
+            Ok(response) => Ok(DevicePoll::Complete(response.into())),
+            Err(AuthError::OAuth { error, .. }) if error == "authorization_pending" => {
+                Ok(DevicePoll::Pending)
+            }
+            Err(AuthError::OAuth { error, .. }) if error == "slow_down" => Ok(DevicePoll::SlowDown),
+            Err(AuthError::OAuth { error, .. }) if error == "authorization_declined" => {
+                Err(AuthError::Declined)
+            }
+            Err(AuthError::OAuth { error, .. }) if error == "expired_token" => {
+                Err(AuthError::DeviceCodeExpired)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Polls until the user has signed in with a device code.
+    ///
+    /// Waits the interval the server asked for between polls and gives up when
+    /// the code expires.
+    ///
+    /// # Errors
+    ///
+    /// See [`HttpClient::poll_device_tokens`].
+    pub async fn wait_for_device_tokens(
+        &self,
+        oauth: &MicrosoftOAuth,
+        code: &DeviceCode,
+    ) -> Result<MsaTokens, AuthError> {
+        let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+        let mut interval = Duration::from_secs(code.interval);
+        loop {
+            if Instant::now() >= deadline {
+                return Err(AuthError::DeviceCodeExpired);
+            }
+            tokio::time::sleep(interval).await;
+            match self.poll_device_tokens(oauth, code).await? {
+                DevicePoll::Complete(tokens) => return Ok(tokens),
+                DevicePoll::SlowDown => interval += Duration::from_secs(5),
+                DevicePoll::Pending => {}
+            }
+        }
+    }
+
+    /// Gets new tokens with a refresh token.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::OAuth`] with `invalid_grant` if the refresh token
+    /// was revoked or expired, in which case the user must sign in again.
+    pub async fn refresh_msa_tokens(
+        &self,
+        oauth: &MicrosoftOAuth,
+        refresh_token: &str,
+    ) -> Result<MsaTokens, AuthError> {
+        let response: TokenResponse = self
+            .post_oauth_form(
+                &oauth.url("token"),
+                &[
+                    ("grant_type", "refresh_token"),
+                    ("client_id", &oauth.client_id),
+                    ("refresh_token", refresh_token),
+                    ("scope", &oauth.scope),
+                ],
+            )
+            .await?;
+        let mut tokens = MsaTokens::from(response);
+        // The server may keep the refresh token, in which case it omits it.
+        tokens
+            .refresh_token
+            .get_or_insert_with(|| refresh_token.to_string());
+        Ok(tokens)
+    }
+
+    async fn post_oauth_form<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+    ) -> Result<T, AuthError> {
+        let builder = self.request(reqwest::Method::POST, url, &[]).form(form);
+        let (status, body) = self.send_raw(url, builder).await?;
+        if (200..300).contains(&status) {
+            return Ok(serde_json::from_slice(&body)?);
+        }
+        match serde_json::from_slice::<OAuthErrorResponse>(&body) {
+            Ok(error) => Err(AuthError::OAuth {
+                error: error.error,
+                description: error.error_description,
+            }),
+            Err(_) => Err(HttpError::Status {
+                url: url.to_string(),
+                status,
+                retry_after: None,
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn runs_device_code_flow() {
+        let server = MockServer::start();
+        let oauth = MicrosoftOAuth::new("client").with_base_url(server.base_url());
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/devicecode")
+                .body_contains("client_id=client");
+            then.status(200).json_body(serde_json::json!({
+                "device_code": "device", "user_code": "ABCD-EFGH",
+                "verification_uri": "https://www.microsoft.com/link",
+                "expires_in": 900, "interval": 0
+            }));
+        });
+        let code = client.request_device_code(&oauth).await.unwrap();
+        assert_eq!(code.user_code, "ABCD-EFGH");
+
+        let mut pending = server.mock(|when, then| {
+            when.method(POST)
+                .path("/token")
+                .body_contains("device_code=device");
+            then.status(400).json_body(serde_json::json!({
+                "error": "authorization_pending", "error_description": "waiting"
+            }));
+        });
+        assert_eq!(
+            client.poll_device_tokens(&oauth, &code).await.unwrap(),
+            DevicePoll::Pending
+        );
+        pending.delete();
+
+        server.mock(|when, then| {
+            when.method(POST).path("/token");
+            then.status(200).json_body(serde_json::json!({
+                "token_type": "Bearer", "expires_in": 3600,
+                "access_token": "access", "refresh_token": "refresh"
+            }));
+        });
+        let tokens = client.wait_for_device_tokens(&oauth, &code).await.unwrap();
+        assert_eq!(tokens.access_token, "access");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+        assert!(!tokens.expires_within(Duration::from_secs(60)));
+        assert!(tokens.expires_within(Duration::from_secs(7200)));
+    }
+
+    #[tokio::test]
+    async fn reports_declined_and_expired_codes() {
+        let server = MockServer::start();
+        let oauth = MicrosoftOAuth::new("client").with_base_url(server.base_url());
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+        let code = DeviceCode {
+            device_code: "device".to_string(),
+            user_code: "ABCD".to_string(),
+            verification_uri: String::new(),
+            expires_in: 0,
+            interval: 0,
+            message: None,
+        };
+        assert!(matches!(
+            client.wait_for_device_tokens(&oauth, &code).await,
+            Err(AuthError::DeviceCodeExpired)
+        ));
+
+        server.mock(|when, then| {
+            when.method(POST).path("/token");
+            then.status(400)
+                .json_body(serde_json::json!({"error": "authorization_declined"}));
+        });
+        assert!(matches!(
+            client.poll_device_tokens(&oauth, &code).await,
+            Err(AuthError::Declined)
+        ));
+    }
+}
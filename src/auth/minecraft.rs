@@ -0,0 +1,362 @@
+use super::microsoft::unix_now;
+use super::xbox::{MINECRAFT_RELYING_PARTY, XboxLiveApi, XboxToken};
+use super::{AuthError, MsaTokens};
+use crate::http::{HttpClient, HttpError, bearer};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+/// Base URL of the Minecraft services API.
+pub const MINECRAFT_SERVICES_URL: &str = "https://api.minecraftservices.com";
+
+/// Entitlements granting the Java Edition: a purchase, or a PC Game Pass or
+/// Game Pass Ultimate subscription, whose accounts may not list the game
+/// itself until it has been started once.
+const GAME_ENTITLEMENTS: [&str; 4] = [
+    "product_minecraft",
+    "game_minecraft",
+    "product_game_pass_pc",
+    "product_game_pass_ultimate",
+];
+
+/// The Minecraft services API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftServicesApi {
+    /// Base URL, without trailing slash.
+    pub base_url: String,
+}
+
+impl Default for MinecraftServicesApi {
+    fn default() -> Self {
+        Self {
+            base_url: MINECRAFT_SERVICES_URL.to_string(),
+        }
+    }
+}
+
+impl MinecraftServicesApi {
+    /// Creates the configuration of the official API.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base URL, e.g. of a test server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path)
+    }
+}
+
+/// A Minecraft services access token, passed to the game as `${auth_access_token}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinecraftToken {
+    pub access_token: String,
+    /// When the token expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+impl MinecraftToken {
+    /// Returns whether the token expires within `margin`.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        unix_now() + margin.as_secs() >= self.expires_at
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// The products owned by an account.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Entitlements {
+    #[serde(default)]
+    pub items: Vec<Entitlement>,
+}
+
+impl Entitlements {
+    /// Returns whether the account may play the Java Edition, including
+    /// through Game Pass.
+    pub fn owns_minecraft(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| GAME_ENTITLEMENTS.contains(&item.name.as_str()))
+    }
+}
+
+/// An owned product.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Entitlement {
+    /// Product name, e.g. `product_minecraft`.
+    pub name: String,
+}
+
+/// The Java Edition profile of an account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinecraftProfile {
+    /// UUID without hyphens.
+    pub id: String,
+    /// Player name.
+    pub name: String,
+    #[serde(default)]
+    pub skins: Vec<ProfileSkin>,
+    #[serde(default)]
+    pub capes: Vec<ProfileCape>,
+}
+
+impl MinecraftProfile {
+    /// Returns the skin in use.
+    pub fn active_skin(&self) -> Option<&ProfileSkin> {
+        self.skins.iter().find(|s| s.state == "ACTIVE")
+    }
+
+    /// Returns the cape in use.
+    pub fn active_cape(&self) -> Option<&ProfileCape> {
+        self.capes.iter().find(|c| c.state == "ACTIVE")
+    }
+}
+
+/// A skin of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSkin {
+    pub id: String,
+    /// `ACTIVE` or `INACTIVE`.
+    pub state: String,
+    /// Texture URL.
+    pub url: String,
+    /// `CLASSIC` or `SLIM`.
+    pub variant: String,
+}
+
+/// A cape of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileCape {
+    pub id: String,
+    /// `ACTIVE` or `INACTIVE`.
+    pub state: String,
+    /// Texture URL.
+    pub url: String,
+    /// Cape name, e.g. `Migrator`.
+    #[serde(default)]
+    pub alias: String,
+}
+
+/// Everything needed to launch the game as a signed-in player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinecraftSession {
+    pub profile: MinecraftProfile,
+    pub token: MinecraftToken,
+    /// Xbox user id, passed to the game as `${auth_xuid}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xuid: Option<String>,
+}
+
+impl HttpClient {
+    /// Signs in to Minecraft services with an XSTS token for
+    /// [`MINECRAFT_RELYING_PARTY`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token has no user hash or is rejected.
+    pub async fn login_with_xbox(
+        &self,
+        api: &MinecraftServicesApi,
+        xsts: &XboxToken,
+    ) -> Result<MinecraftToken, AuthError> {
+        let user_hash = xsts.user_hash().ok_or(AuthError::MissingUserHash)?;
+        let body = json!({
+            "identityToken": format!("XBL3.0 x={};{}", user_hash, xsts.token),
+        });
+        let response: LoginResponse = self
+            .post_json(&api.url("authentication/login_with_xbox"), &body)
+            .await?;
+        Ok(MinecraftToken {
+            access_token: response.access_token,
+            expires_at: unix_now() + response.expires_in,
+        })
+    }
+
+    /// Lists the products owned by the account of `token`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    pub async fn minecraft_entitlements(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+    ) -> Result<Entitlements, AuthError> {
+        self.get_authorized(&api.url("entitlements/mcstore"), &token.access_token)
+            .await
+    }
+
+    /// Fetches the Java Edition profile of the account of `token`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::NoProfile`] if the account has not chosen a player
+    /// name yet, or another error if the request fails.
+    pub async fn minecraft_profile(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+    ) -> Result<MinecraftProfile, AuthError> {
+        match self
+            .get_authorized(&api.url("minecraft/profile"), &token.access_token)
+            .await
+        {
+            Err(AuthError::Http(HttpError::Status { status: 404, .. })) => {
+                Err(AuthError::NoProfile)
+            }
+            result => result,
+        }
+    }
+
+    /// Runs the whole chain from Microsoft account tokens to a game session:
+    /// Xbox Live, XSTS, Minecraft services, ownership and profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::Xsts`] if Xbox Live refuses the account,
+    /// [`AuthError::NotEntitled`] if it does not own the game,
+    /// [`AuthError::NoProfile`] if it has no player name yet, or another error
+    /// if a request fails.
+    pub async fn sign_in_minecraft(
+        &self,
+        xbox: &XboxLiveApi,
+        services: &MinecraftServicesApi,
+        msa: &MsaTokens,
+    ) -> Result<MinecraftSession, AuthError> {
+        let user = self.xbox_user_token(xbox, &msa.access_token).await?;
+        let xsts = self
+            .xsts_token(xbox, &user, MINECRAFT_RELYING_PARTY)
+            .await?;
+        let token = self.login_with_xbox(services, &xsts).await?;
+        if !self
+            .minecraft_entitlements(services, &token)
+            .await?
+            .owns_minecraft()
+        {
+            return Err(AuthError::NotEntitled);
+        }
+        let profile = self.minecraft_profile(services, &token).await?;
+        Ok(MinecraftSession {
+            profile,
+            token,
+            xuid: xsts.xuid().map(str::to_string),
+        })
+    }
+
+    /// Fetches JSON with a bearer token, bypassing the response cache so that
+    /// one account's data is never served to another.
+    pub(crate) async fn get_authorized<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        token: &str,
+    ) -> Result<T, AuthError> {
+        let builder = self
+            .request(reqwest::Method::GET, url, &[])
+            .header("Authorization", bearer(token));
+        let (status, body) = self.send_raw(url, builder).await?;
+        if !(200..300).contains(&status) {
+            return Err(HttpError::Status {
+                url: url.to_string(),
+                status,
+                retry_after: None,
+            }
+            .into());
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn signs_in_through_the_whole_chain() {
+        let server = MockServer::start();
+        let xbox = XboxLiveApi::new().with_base_url(&server.base_url());
+        let services = MinecraftServicesApi::new().with_base_url(server.base_url());
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+        let msa = MsaTokens {
+            access_token: "msa".to_string(),
+            refresh_token: None,
+            expires_at: 0,
+        };
+
+        server.mock(|when, then| {
+            when.method(POST).path("/user/authenticate");
+            then.status(200).json_body(serde_json::json!({
+                "NotAfter": "", "Token": "xbl", "DisplayClaims": {"xui": [{"uhs": "hash"}]}
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/xsts/authorize")
+                .body_contains("\"UserTokens\":[\"xbl\"]");
+            then.status(200).json_body(serde_json::json!({
+                "NotAfter": "", "Token": "xsts",
+                "DisplayClaims": {"xui": [{"uhs": "hash", "xid": "2535"}]}
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/authentication/login_with_xbox")
+                .json_body(serde_json::json!({"identityToken": "XBL3.0 x=hash;xsts"}));
+            then.status(200).json_body(serde_json::json!({
+                "username": "id", "access_token": "mc", "token_type": "Bearer", "expires_in": 86400
+            }));
+        });
+        let mut entitlements = server.mock(|when, then| {
+            when.method(GET)
+                .path("/entitlements/mcstore")
+                .header("Authorization", "Bearer mc");
+            then.status(200).json_body(serde_json::json!({"items": []}));
+        });
+        assert!(matches!(
+            client.sign_in_minecraft(&xbox, &services, &msa).await,
+            Err(AuthError::NotEntitled)
+        ));
+        entitlements.delete();
+        let game_pass = Entitlements {
+            items: vec![Entitlement {
+                name: "product_game_pass_ultimate".to_string(),
+            }],
+        };
+        assert!(game_pass.owns_minecraft());
+
+        server.mock(|when, then| {
+            when.method(GET).path("/entitlements/mcstore");
+            then.status(200).json_body(serde_json::json!({
+                "items": [{"name": "product_minecraft", "signature": "x"}]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/minecraft/profile");
+            then.status(200).json_body(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch",
+                "skins": [{"id": "s", "state": "ACTIVE", "url": "https://textures/s", "variant": "CLASSIC"}],
+                "capes": []
+            }));
+        });
+        let session = client
+            .sign_in_minecraft(&xbox, &services, &msa)
+            .await
+            .unwrap();
+        assert_eq!(session.profile.name, "Notch");
+        assert_eq!(session.token.access_token, "mc");
+        assert_eq!(session.xuid.as_deref(), Some("2535"));
+        assert_eq!(session.profile.active_skin().unwrap().variant, "CLASSIC");
+        assert!(session.profile.active_cape().is_none());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::http::HttpError;
+use thiserror::Error;
+
+/// Microsoft account sign-in with the OAuth device-code flow.
+pub mod microsoft;
+/// Minecraft services sign-in, ownership and profile.
+pub mod minecraft;
+/// Persistent refresh tokens and launch-time token renewal.
+pub mod store;
+/// Xbox Live user and XSTS tokens.
+pub mod xbox;
+
+pub use microsoft::{
+    DeviceCode, DevicePoll, MICROSOFT_AUTHORITY_URL, MicrosoftOAuth, MsaTokens, XBOX_LIVE_SCOPE,
+};
+pub use minecraft::{
+    Entitlement, Entitlements, MINECRAFT_SERVICES_URL, MinecraftProfile, MinecraftServicesApi,
+    MinecraftSession, MinecraftToken, ProfileCape, ProfileSkin,
+};
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+pub use store::KeyringStore;
+pub use store::{
+    CredentialManager, EncryptedFileStore, KEYRING_SERVICE, SecretStore, SecretStoreError,
+    default_secret_store,
+};
+pub use xbox::{
+    DisplayClaims, MINECRAFT_RELYING_PARTY, UserClaims, XBOX_USER_AUTH_URL, XSTS_AUTHORIZE_URL,
+    XboxLiveApi, XboxToken, XstsError,
+};
+
+/// Error returned when signing in fails.
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error("invalid authentication response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{error}: {description}")]
+    OAuth { error: String, description: String },
+    #[error("the sign-in was declined")]
+    Declined,
+    #[error("the device code expired before the sign-in was completed")]
+    DeviceCodeExpired,
+    #[error("{0}")]
+    Xsts(XstsError),
+    #[error("the Xbox token has no user hash")]
+    MissingUserHash,
+    #[error("the account does not own Minecraft: Java Edition")]
+    NotEntitled,
+    #[error("the account has no Minecraft profile; choose a player name at minecraft.net")]
+    NoProfile,
+    #[error("the Microsoft tokens have no refresh token")]
+    MissingRefreshToken,
+    #[error("no stored credentials for account {0}")]
+    UnknownAccount(String),
+    #[error(transparent)]
+    SecretStore(#[from] SecretStoreError),
+}
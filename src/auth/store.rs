@@ -0,0 +1,491 @@
+use super::{AuthError, MicrosoftOAuth, MinecraftServicesApi, MinecraftSession, XboxLiveApi};
+use crate::filesystem::{FilesystemError, write_atomic};
+use crate::http::HttpClient;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Service name under which secrets are stored in the OS keyring.
+pub const KEYRING_SERVICE: &str = "junco-launcher";
+
+/// Access tokens expiring sooner than this are refreshed before use, so they
+/// stay valid while the game starts.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+const NONCE_SIZE: usize = 12;
+
+/// Error returned when a secret cannot be stored or retrieved.
+#[derive(Debug, Error)]
+pub enum SecretStoreError {
+    #[error("failed to access secret store: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write secret store: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid secret store: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("secret store could not be decrypted; the key file may have changed")]
+    Decrypt,
+    #[error("OS keyring error: {0}")]
+    Keyring(String),
+}
+
+/// Persistent storage for refresh tokens, keyed by account id.
+pub trait SecretStore: Send + Sync {
+    /// Returns the secret of an account, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read.
+    fn get(&self, account_id: &str) -> Result<Option<String>, SecretStoreError>;
+
+    /// Stores the secret of an account, replacing any previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    fn set(&self, account_id: &str, secret: &str) -> Result<(), SecretStoreError>;
+
+    /// Removes the secret of an account. Removing a missing secret succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    fn delete(&self, account_id: &str) -> Result<(), SecretStoreError>;
+}
+
+/// Secrets in the macOS Keychain, the Windows Credential Manager or the
+/// Secret Service of Linux desktops (GNOME Keyring, KWallet).
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+#[derive(Debug, Clone)]
+pub struct KeyringStore {
+    service: String,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+impl KeyringStore {
+    /// Creates a store using `service` as the keyring service name.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// Returns whether the keyring can be reached, which on Linux needs a
+    /// running Secret Service.
+    pub fn is_available(&self) -> bool {
+        self.entry("availability-probe")
+            .is_ok_and(|entry| matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)))
+    }
+
+    fn entry(&self, account_id: &str) -> Result<keyring::Entry, SecretStoreError> {
+        keyring::Entry::new(&self.service, account_id)
+            .map_err(|e| SecretStoreError::Keyring(e.to_string()))
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+impl SecretStore for KeyringStore {
+    fn get(&self, account_id: &str) -> Result<Option<String>, SecretStoreError> {
+        match self.entry(account_id)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(SecretStoreError::Keyring(e.to_string())),
+        }
+    }
+
+    fn set(&self, account_id: &str, secret: &str) -> Result<(), SecretStoreError> {
+        self.entry(account_id)?
+            .set_password(secret)
+            .map_err(|e| SecretStoreError::Keyring(e.to_string()))
+    }
+
+    fn delete(&self, account_id: &str) -> Result<(), SecretStoreError> {
+        match self.entry(account_id)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(SecretStoreError::Keyring(e.to_string())),
+        }
+    }
+}
+
+/// Secrets in an AES-256-GCM encrypted file, for systems without a usable keyring.
+///
+/// The key is kept in a separate file readable only by the user. This keeps
+/// tokens out of backups and shared instance folders that include only the
+/// secrets file, but does not protect them from other programs of the same user.
+#[derive(Debug)]
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileStore {
+    /// Creates a store in `path`, with its key in `path` plus `.key`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut key_path = path.clone().into_os_string();
+        key_path.push(".key");
+        Self {
+            path,
+            key_path: key_path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn cipher(&self) -> Result<Aes256Gcm, SecretStoreError> {
+        match fs::read(&self.key_path) {
+            Ok(key) if key.len() == 32 => Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))),
+            Ok(_) => Err(SecretStoreError::Decrypt),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let key = Aes256Gcm::generate_key(OsRng);
+                // Temp files are created readable only by the user.
+                write_atomic(&self.key_path, key.as_slice())?;
+                Ok(Aes256Gcm::new(&key))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load(&self, cipher: &Aes256Gcm) -> Result<BTreeMap<String, String>, SecretStoreError> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+        if data.len() < NONCE_SIZE {
+            return Err(SecretStoreError::Decrypt);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretStoreError::Decrypt)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn save(
+        &self,
+        cipher: &Aes256Gcm,
+        secrets: &BTreeMap<String, String>,
+    ) -> Result<(), SecretStoreError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, serde_json::to_vec(secrets)?.as_slice())
+            .map_err(|_| SecretStoreError::Decrypt)?;
+        write_atomic(&self.path, &[nonce.as_slice(), &ciphertext].concat())?;
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        edit: impl FnOnce(&mut BTreeMap<String, String>),
+    ) -> Result<(), SecretStoreError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let cipher = self.cipher()?;
+        let mut secrets = self.load(&cipher)?;
+        edit(&mut secrets);
+        self.save(&cipher, &secrets)
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn get(&self, account_id: &str) -> Result<Option<String>, SecretStoreError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        Ok(self.load(&self.cipher()?)?.remove(account_id))
+    }
+
+    fn set(&self, account_id: &str, secret: &str) -> Result<(), SecretStoreError> {
+        self.update(|secrets| {
+            secrets.insert(account_id.to_string(), secret.to_string());
+        })
+    }
+
+    fn delete(&self, account_id: &str) -> Result<(), SecretStoreError> {
+        self.update(|secrets| {
+            secrets.remove(account_id);
+        })
+    }
+}
+
+/// Returns the OS keyring where one is supported, and otherwise an
+/// [`EncryptedFileStore`] named `credentials.bin` in `data_dir`.
+///
+/// On Linux the keyring is the Secret Service, which is only used if it
+/// answers: headless systems and desktops without one fall back to the file.
+/// Secrets are not moved between the two.
+pub fn default_secret_store(data_dir: &Path) -> Box<dyn SecretStore> {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        let _ = data_dir;
+        Box::new(KeyringStore::new(KEYRING_SERVICE))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let keyring = KeyringStore::new(KEYRING_SERVICE);
+        if keyring.is_available() {
+            return Box::new(keyring);
+        }
+        Box::new(EncryptedFileStore::new(data_dir.join("credentials.bin")))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    Box::new(EncryptedFileStore::new(data_dir.join("credentials.bin")))
+}
+
+/// Keeps the accounts of a launcher signed in.
+///
+/// Refresh tokens are persisted in a [`SecretStore`]; game sessions are kept in
+/// memory and renewed from the refresh token when they are about to expire.
+pub struct CredentialManager {
+    client: HttpClient,
+    oauth: MicrosoftOAuth,
+    xbox: XboxLiveApi,
+    services: MinecraftServicesApi,
+    store: Box<dyn SecretStore>,
+    sessions: Mutex<HashMap<String, MinecraftSession>>,
+    /// Serializes refreshes so an account is never refreshed twice at once;
+    /// Microsoft may rotate the refresh token on every use.
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl CredentialManager {
+    /// Creates a manager using the official endpoints.
+    pub fn new(client: HttpClient, oauth: MicrosoftOAuth, store: Box<dyn SecretStore>) -> Self {
+        Self {
+            client,
+            oauth,
+            xbox: XboxLiveApi::default(),
+            services: MinecraftServicesApi::default(),
+            store,
+            sessions: Mutex::new(HashMap::new()),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Sets the Xbox Live endpoints.
+    pub fn with_xbox_api(mut self, xbox: XboxLiveApi) -> Self {
+        self.xbox = xbox;
+        self
+    }
+
+    /// Sets the Minecraft services endpoints.
+    pub fn with_services_api(mut self, services: MinecraftServicesApi) -> Self {
+        self.services = services;
+        self
+    }
+
+    /// Signs in to Minecraft with freshly obtained Microsoft tokens and stores
+    /// the account.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MinecraftSession, AuthError>` - The session. Its profile id is
+    ///   the account id for the other methods.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing in fails, the tokens carry no refresh token,
+    /// or the refresh token cannot be stored.
+    pub async fn add_account(&self, msa: &super::MsaTokens) -> Result<MinecraftSession, AuthError> {
+        let refresh_token = msa
+            .refresh_token
+            .as_deref()
+            .ok_or(AuthError::MissingRefreshToken)?;
+        let session = self
+            .client
+            .sign_in_minecraft(&self.xbox, &self.services, msa)
+            .await?;
+        self.store.set(&session.profile.id, refresh_token)?;
+        self.cache(&session);
+        Ok(session)
+    }
+
+    /// Returns a game session for an account that is valid for at least a few
+    /// more minutes, refreshing it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::UnknownAccount`] if no refresh token is stored for
+    /// the account, or an error if refreshing fails. An `invalid_grant`
+    /// [`AuthError::OAuth`] means the user must sign in again.
+    pub async fn get_valid_session(&self, account_id: &str) -> Result<MinecraftSession, AuthError> {
+        if let Some(session) = self.cached(account_id) {
+            return Ok(session);
+        }
+        let _refreshing = self.refreshing.lock().await;
+        // Another caller may have refreshed while this one waited.
+        if let Some(session) = self.cached(account_id) {
+            return Ok(session);
+        }
+        let refresh_token = self
+            .store
+            .get(account_id)?
+            .ok_or_else(|| AuthError::UnknownAccount(account_id.to_string()))?;
+        let msa = self
+            .client
+            .refresh_msa_tokens(&self.oauth, &refresh_token)
+            .await?;
+        if let Some(rotated) = msa.refresh_token.as_deref()
+            && rotated != refresh_token
+        {
+            self.store.set(account_id, rotated)?;
+        }
+        let session = self
+            .client
+            .sign_in_minecraft(&self.xbox, &self.services, &msa)
+            .await?;
+        self.cache(&session);
+        Ok(session)
+    }
+
+    /// Returns an access token for launching the game as an account.
+    ///
+    /// # Errors
+    ///
+    /// See [`CredentialManager::get_valid_session`].
+    pub async fn get_valid_token(&self, account_id: &str) -> Result<String, AuthError> {
+        Ok(self.get_valid_session(account_id).await?.token.access_token)
+    }
+
+    /// Forgets an account and deletes its refresh token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the refresh token cannot be deleted.
+    pub fn remove_account(&self, account_id: &str) -> Result<(), AuthError> {
+        self.sessions_lock().remove(account_id);
+        self.store.delete(account_id)?;
+        Ok(())
+    }
+
+    fn cached(&self, account_id: &str) -> Option<MinecraftSession> {
+        self.sessions_lock()
+            .get(account_id)
+            .filter(|s| !s.token.expires_within(EXPIRY_MARGIN))
+            .cloned()
+    }
+
+    fn cache(&self, session: &MinecraftSession) {
+        self.sessions_lock()
+            .insert(session.profile.id.clone(), session.clone());
+    }
+
+    fn sessions_lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, MinecraftSession>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::MsaTokens;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn encrypted_file_store_round_trips_secrets() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("credentials.bin");
+        let store = EncryptedFileStore::new(&path);
+        assert_eq!(store.get("a").unwrap(), None);
+        store.set("a", "refresh-a").unwrap();
+        store.set("b", "refresh-b").unwrap();
+        store.delete("b").unwrap();
+
+        let reopened = EncryptedFileStore::new(&path);
+        assert_eq!(reopened.get("a").unwrap().as_deref(), Some("refresh-a"));
+        assert_eq!(reopened.get("b").unwrap(), None);
+        assert!(
+            !fs::read(&path)
+                .unwrap()
+                .windows(9)
+                .any(|w| w == b"refresh-a")
+        );
+
+        fs::write(dir.path().join("credentials.bin.key"), [7u8; 32]).unwrap();
+        assert!(matches!(reopened.get("a"), Err(SecretStoreError::Decrypt)));
+    }
+
+    #[tokio::test]
+    async fn refreshes_expired_sessions_from_stored_token() {
+        let server = MockServer::start();
+        let dir = tempdir().unwrap();
+        let manager = CredentialManager::new(
+            HttpClient::new(ClientOptions::default()).unwrap(),
+            MicrosoftOAuth::new("client").with_base_url(server.base_url()),
+            Box::new(EncryptedFileStore::new(dir.path().join("credentials.bin"))),
+        )
+        .with_xbox_api(XboxLiveApi::new().with_base_url(&server.base_url()))
+        .with_services_api(MinecraftServicesApi::new().with_base_url(server.base_url()));
+
+        let refresh = server.mock(|when, then| {
+            when.method(POST)
+                .path("/token")
+                .body_contains("refresh_token=old");
+            then.status(200).json_body(serde_json::json!({
+                "access_token": "msa2", "refresh_token": "new", "expires_in": 3600
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/user/authenticate");
+            then.status(200).json_body(serde_json::json!({
+                "NotAfter": "", "Token": "xbl", "DisplayClaims": {"xui": [{"uhs": "hash"}]}
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/xsts/authorize");
+            then.status(200).json_body(serde_json::json!({
+                "NotAfter": "", "Token": "xsts", "DisplayClaims": {"xui": [{"uhs": "hash"}]}
+            }));
+        });
+        // The first token expires within the margin and forces a refresh.
+        let mut login = server.mock(|when, then| {
+            when.method(POST).path("/authentication/login_with_xbox");
+            then.status(200)
+                .json_body(serde_json::json!({"access_token": "mc1", "expires_in": 60}));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/entitlements/mcstore");
+            then.status(200)
+                .json_body(serde_json::json!({"items": [{"name": "game_minecraft"}]}));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/minecraft/profile");
+            then.status(200)
+                .json_body(serde_json::json!({"id": "uuid", "name": "Notch"}));
+        });
+
+        let msa = MsaTokens {
+            access_token: "msa".to_string(),
+            refresh_token: Some("old".to_string()),
+            expires_at: 0,
+        };
+        let session = manager.add_account(&msa).await.unwrap();
+        assert_eq!(session.token.access_token, "mc1");
+        login.delete();
+        server.mock(|when, then| {
+            when.method(POST).path("/authentication/login_with_xbox");
+            then.status(200)
+                .json_body(serde_json::json!({"access_token": "mc2", "expires_in": 86400}));
+        });
+
+        assert_eq!(manager.get_valid_token("uuid").await.unwrap(), "mc2");
+        assert_eq!(manager.get_valid_token("uuid").await.unwrap(), "mc2");
+        refresh.assert_hits(1);
+        assert_eq!(manager.store.get("uuid").unwrap().as_deref(), Some("new"));
+
+        manager.remove_account("uuid").unwrap();
+        assert!(matches!(
+            manager.get_valid_token("uuid").await,
+            Err(AuthError::UnknownAccount(_))
+        ));
+    }
+}
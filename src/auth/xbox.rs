@@ -0,0 +1,249 @@
+use super::AuthError;
+use crate::http::{HttpClient, HttpError};
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt;
+
+/// Endpoint issuing Xbox Live user tokens.
+pub const XBOX_USER_AUTH_URL: &str = "https://user.auth.xboxlive.com/user/authenticate";
+
+/// Endpoint issuing XSTS tokens.
+pub const XSTS_AUTHORIZE_URL: &str = "https://xsts.auth.xboxlive.com/xsts/authorize";
+
+/// Relying party of XSTS tokens accepted by Minecraft services.
+pub const MINECRAFT_RELYING_PARTY: &str = "rp://api.minecraftservices.com/";
+
+/// The Xbox Live endpoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XboxLiveApi {
+    /// URL of the user authentication endpoint.
+    pub user_auth_url: String,
+    /// URL of the XSTS authorization endpoint.
+    pub xsts_url: String,
+}
+
+impl Default for XboxLiveApi {
+    fn default() -> Self {
+        Self {
+            user_auth_url: XBOX_USER_AUTH_URL.to_string(),
+            xsts_url: XSTS_AUTHORIZE_URL.to_string(),
+        }
+    }
+}
+
+impl XboxLiveApi {
+    /// Creates the configuration of the official endpoints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets both endpoints relative to one base URL, e.g. of a test server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        self.user_auth_url = format!("{}/user/authenticate", base_url);
+        self.xsts_url = format!("{}/xsts/authorize", base_url);
+        self
+    }
+}
+
+/// An Xbox Live user or XSTS token.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct XboxToken {
+    /// The token.
+    pub token: String,
+    /// Expiry as an ISO 8601 timestamp.
+    pub not_after: String,
+    pub display_claims: DisplayClaims,
+}
+
+impl XboxToken {
+    /// Returns the user hash, needed with the XSTS token to sign in to Minecraft.
+    pub fn user_hash(&self) -> Option<&str> {
+        self.display_claims.xui.first().map(|c| c.uhs.as_str())
+    }
+
+    /// Returns the Xbox user id, present in XSTS tokens.
+    pub fn xuid(&self) -> Option<&str> {
+        self.display_claims.xui.first()?.xid.as_deref()
+    }
+}
+
+/// The `DisplayClaims` of an Xbox token.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct DisplayClaims {
+    #[serde(default)]
+    pub xui: Vec<UserClaims>,
+}
+
+/// Claims about the signed-in user.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UserClaims {
+    /// User hash.
+    pub uhs: String,
+    /// Xbox user id.
+    #[serde(default)]
+    pub xid: Option<String>,
+}
+
+/// Why XSTS refused to authorize an account, from its `XErr` code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XstsError {
+    /// The Microsoft account has no Xbox profile yet.
+    NoXboxAccount,
+    /// Xbox Live is not available in the account's country.
+    RegionUnavailable,
+    /// The account needs adult verification (South Korea).
+    AdultVerificationRequired,
+    /// The account belongs to a child and must be added to a family.
+    ChildAccount,
+    /// Another code.
+    Other(u64),
+}
+
+impl XstsError {
+    /// Maps an `XErr` code.
+    pub fn from_code(code: u64) -> Self {
+        match code {
+            2148916233 => XstsError::NoXboxAccount,
+            2148916235 => XstsError::RegionUnavailable,
+            2148916236 | 2148916237 => XstsError::AdultVerificationRequired,
+            2148916238 => XstsError::ChildAccount,
+            other => XstsError::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for XstsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XstsError::NoXboxAccount => {
+                f.write_str("the account has no Xbox profile; sign in at xbox.com to create one")
+            }
+            XstsError::RegionUnavailable => {
+                f.write_str("Xbox Live is not available in the account's country")
+            }
+            XstsError::AdultVerificationRequired => {
+                f.write_str("the account must complete adult verification on xbox.com")
+            }
+            XstsError::ChildAccount => f.write_str(
+                "the account belongs to a child and must be added to a family by an adult",
+            ),
+            XstsError::Other(code) => write!(f, "Xbox Live refused the account (XErr {})", code),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct XstsErrorResponse {
+    #[serde(rename = "XErr")]
+    xerr: u64,
+}
+
+impl HttpClient {
+    /// Exchanges a Microsoft account access token for an Xbox Live user token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the token is rejected.
+    pub async fn xbox_user_token(
+        &self,
+        api: &XboxLiveApi,
+        msa_access_token: &str,
+    ) -> Result<XboxToken, AuthError> {
+        let body = json!({
+            "Properties": {
+                "AuthMethod": "RPS",
+                "SiteName": "user.auth.xboxlive.com",
+                "RpsTicket": format!("d={}", msa_access_token),
+            },
+            "RelyingParty": "http://auth.xboxlive.com",
+            "TokenType": "JWT",
+        });
+        self.post_xbox(&api.user_auth_url, &body).await
+    }
+
+    /// Exchanges an Xbox Live user token for an XSTS token for `relying_party`,
+    /// usually [`MINECRAFT_RELYING_PARTY`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::Xsts`] if the account cannot be authorized, or
+    /// another error if the request fails.
+    pub async fn xsts_token(
+        &self,
+        api: &XboxLiveApi,
+        user_token: &XboxToken,
+        relying_party: &str,
+    ) -> Result<XboxToken, AuthError> {
+        let body = json!({
+            "Properties": {"SandboxId": "RETAIL", "UserTokens": [user_token.token]},
+            "RelyingParty": relying_party,
+            "TokenType": "JWT",
+        });
+        self.post_xbox(&api.xsts_url, &body).await
+    }
+
+    async fn post_xbox(&self, url: &str, body: &serde_json::Value) -> Result<XboxToken, AuthError> {
+        let builder = self
+            .request(reqwest::Method::POST, url, &[])
+            .header("Accept", "application/json")
+            .header("x-xbl-contract-version", "1")
+            .json(body);
+        let (status, body) = self.send_raw(url, builder).await?;
+        if (200..300).contains(&status) {
+            return Ok(serde_json::from_slice(&body)?);
+        }
+        if status == 401
+            && let Ok(error) = serde_json::from_slice::<XstsErrorResponse>(&body)
+        {
+            return Err(AuthError::Xsts(XstsError::from_code(error.xerr)));
+        }
+        Err(HttpError::Status {
+            url: url.to_string(),
+            status,
+            retry_after: None,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn exchanges_tokens_and_maps_xsts_errors() {
+        let server = MockServer::start();
+        let api = XboxLiveApi::new().with_base_url(&server.base_url());
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/user/authenticate")
+                .body_contains("\"RpsTicket\":\"d=msa\"");
+            then.status(200).json_body(serde_json::json!({
+                "IssueInstant": "2024-03-01T00:00:00Z", "NotAfter": "2024-03-15T00:00:00Z",
+                "Token": "xbl", "DisplayClaims": {"xui": [{"uhs": "hash"}]}
+            }));
+        });
+        let user = client.xbox_user_token(&api, "msa").await.unwrap();
+        assert_eq!(user.user_hash(), Some("hash"));
+
+        server.mock(|when, then| {
+            when.method(POST).path("/xsts/authorize");
+            then.status(401).json_body(serde_json::json!({
+                "Identity": "0", "XErr": 2148916238u64, "Message": "",
+                "Redirect": "https://start.ui.xboxlive.com/AddChildToFamily"
+            }));
+        });
+        let error = client
+            .xsts_token(&api, &user, MINECRAFT_RELYING_PARTY)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AuthError::Xsts(XstsError::ChildAccount)));
+        assert!(error.to_string().contains("family"));
+    }
+}
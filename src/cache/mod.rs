@@ -0,0 +1,493 @@
+use crate::filesystem::{FilesystemError, write_atomic};
+use crate::http::{HashAlgorithm, hash_file};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Folder of the store holding the objects, named by their SHA-1.
+const OBJECTS_DIR: &str = "objects";
+/// Folder of the store holding one reference file per instance.
+const REFS_DIR: &str = "refs";
+
+/// Error returned when the shared store cannot be read or written.
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("failed to access shared store: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write shared store: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid reference file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid SHA-1 {0:?}")]
+    InvalidHash(String),
+    #[error("invalid instance id {0:?}")]
+    InvalidInstance(String),
+    #[error("object {0} is not in the shared store")]
+    MissingObject(String),
+    #[error("hash mismatch: expected {expected}, got {actual}")]
+    HashMismatch { expected: String, actual: String },
+}
+
+/// How an object was put into an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkMethod {
+    /// A hard link, sharing the disk space with the store.
+    HardLink,
+    /// A copy, where hard links are not supported, e.g. across drives.
+    Copy,
+}
+
+/// What [`SharedStore::collect_garbage`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Number of objects removed.
+    pub removed: usize,
+    /// Bytes freed by removing them.
+    pub freed_bytes: u64,
+    /// Number of objects still referenced.
+    pub kept: usize,
+    /// Number of references dropped because their file is gone from the
+    /// instance.
+    pub stale_refs: usize,
+}
+
+/// One store of libraries, asset objects and client jars shared by all
+/// instances.
+///
+/// Files are stored once under `objects/<first two hex digits>/<sha1>`, the
+/// layout of Mojang's asset store, and linked into instances on demand. Each
+/// instance records the files it links in `refs/<instance>.json`, so objects
+/// no instance uses anymore can be removed with
+/// [`SharedStore::collect_garbage`].
+///
+/// Garbage collection must not run while files are being linked, or objects
+/// inserted but not yet linked may be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedStore {
+    root: PathBuf,
+}
+
+impl SharedStore {
+    /// Creates a store in `root`. Folders are created when needed.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Returns the folder of the store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path of an object, whether or not it is stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CacheError::InvalidHash`] if `sha1` is not 40 hex digits.
+    pub fn object_path(&self, sha1: &str) -> Result<PathBuf, CacheError> {
+        let sha1 = normalize_hash(sha1)?;
+        Ok(self.root.join(OBJECTS_DIR).join(&sha1[..2]).join(sha1))
+    }
+
+    /// Returns whether an object is stored.
+    pub fn contains(&self, sha1: &str) -> bool {
+        self.object_path(sha1).is_ok_and(|path| path.is_file())
+    }
+
+    /// Stores a file, e.g. a freshly downloaded library.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to store; it is copied, not moved.
+    /// * `expected` - The SHA-1 the file must have, if known.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, CacheError>` - The SHA-1 of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CacheError::HashMismatch`] if the file does not have the
+    /// expected hash, or an error if it cannot be copied.
+    pub fn insert_file(&self, path: &Path, expected: Option<&str>) -> Result<String, CacheError> {
+        let sha1 = hash_file(path, HashAlgorithm::Sha1)?;
+        check_expected(&sha1, expected)?;
+        let dest = self.object_path(&sha1)?;
+        if !dest.is_file() {
+            let dir = dest.parent().expect("objects are in a folder");
+            fs::create_dir_all(dir)?;
+            let tmp = tempfile::NamedTempFile::new_in(dir)?;
+            fs::copy(path, tmp.path())?;
+            tmp.persist(&dest).map_err(|e| e.error)?;
+        }
+        Ok(sha1)
+    }
+
+    /// Stores data, e.g. an asset object downloaded into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CacheError::HashMismatch`] if the data does not have the
+    /// expected hash, or an error if it cannot be written.
+    pub fn insert_bytes(&self, data: &[u8], expected: Option<&str>) -> Result<String, CacheError> {
+        let sha1 = hex::encode(Sha1::digest(data));
+        check_expected(&sha1, expected)?;
+        let dest = self.object_path(&sha1)?;
+        if !dest.is_file() {
+            fs::create_dir_all(dest.parent().expect("objects are in a folder"))?;
+            write_atomic(&dest, data)?;
+        }
+        Ok(sha1)
+    }
+
+    /// Puts a stored object at `target` in an instance and records the
+    /// reference.
+    ///
+    /// The object is hard-linked where possible and copied otherwise. An
+    /// existing file at `target` is replaced. Instances must not modify linked
+    /// files, since a hard link changes the stored object for everyone.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance` - The id of the instance, used as file name.
+    /// * `sha1` - The object.
+    /// * `target` - Where the instance expects the file, e.g. its client jar.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LinkMethod, CacheError>` - Whether the file was linked or
+    ///   copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CacheError::MissingObject`] if the object is not stored, or
+    /// an error if the file or the reference cannot be written.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(target = %target.display()), err)
+    )]
+    pub fn link(
+        &self,
+        instance: &str,
+        sha1: &str,
+        target: &Path,
+    ) -> Result<LinkMethod, CacheError> {
+        let mut refs = self.references(instance)?;
+        let sha1 = normalize_hash(sha1)?;
+        let source = self.object_path(&sha1)?;
+        if !source.is_file() {
+            return Err(CacheError::MissingObject(sha1));
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::remove_file(target) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let method = if fs::hard_link(&source, target).is_ok() {
+            LinkMethod::HardLink
+        } else {
+            fs::copy(&source, target)?;
+            LinkMethod::Copy
+        };
+        refs.insert(target.to_path_buf(), sha1);
+        self.write_references(instance, &refs)?;
+        Ok(method)
+    }
+
+    /// Returns the files an instance has linked and their objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference file cannot be read.
+    pub fn references(&self, instance: &str) -> Result<BTreeMap<PathBuf, String>, CacheError> {
+        match fs::read_to_string(self.refs_path(instance)?) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Moves a reference of an instance to a new path after its file was
+    /// renamed, e.g. when a mod is disabled, so the object is not collected.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, CacheError>` - Whether `from` was referenced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference file cannot be read or written.
+    pub fn move_reference(
+        &self,
+        instance: &str,
+        from: &Path,
+        to: &Path,
+    ) -> Result<bool, CacheError> {
+        let mut refs = self.references(instance)?;
+        let Some(sha1) = refs.remove(from) else {
+            return Ok(false);
+        };
+        refs.insert(to.to_path_buf(), sha1);
+        self.write_references(instance, &refs)?;
+        Ok(true)
+    }
+
+    /// Drops all references of an instance, e.g. after deleting it. Its
+    /// objects are removed by the next garbage collection unless another
+    /// instance uses them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference file cannot be removed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err))]
+    pub fn release_instance(&self, instance: &str) -> Result<(), CacheError> {
+        match fs::remove_file(self.refs_path(instance)?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the objects no instance references.
+    ///
+    /// References whose file is gone from the instance or no longer holds the
+    /// object are dropped first, so objects of deleted files and of files
+    /// replaced in place, e.g. by an updating download, are removed too. A
+    /// file still holds its object if it is a hard link to it, or otherwise
+    /// if its size and SHA-1 match.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GcReport, CacheError>` - The removed objects and freed bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read or an object cannot be
+    /// removed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(root = %self.root.display()), err)
+    )]
+    pub fn collect_garbage(&self) -> Result<GcReport, CacheError> {
+        let mut report = GcReport::default();
+        let mut used = BTreeSet::new();
+        for instance in self.instances()? {
+            let all = self.references(&instance)?;
+            let before = all.len();
+            let mut refs = BTreeMap::new();
+            for (target, sha1) in all {
+                if self.holds_object(&target, &sha1)? {
+                    refs.insert(target, sha1);
+                }
+            }
+            if refs.len() != before {
+                report.stale_refs += before - refs.len();
+                self.write_references(&instance, &refs)?;
+            }
+            used.extend(refs.into_values());
+        }
+
+        let objects = self.root.join(OBJECTS_DIR);
+        let prefixes = match fs::read_dir(&objects) {
+            Ok(prefixes) => prefixes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(report),
+            Err(e) => return Err(e.into()),
+        };
+        for prefix in prefixes {
+            let prefix = prefix?.path();
+            if !prefix.is_dir() {
+                continue;
+            }
+            for object in fs::read_dir(&prefix)? {
+                let object = object?;
+                let name = object.file_name().to_string_lossy().into_owned();
+                if used.contains(&name) {
+                    report.kept += 1;
+                    continue;
+                }
+                report.freed_bytes += object.metadata()?.len();
+                fs::remove_file(object.path())?;
+                report.removed += 1;
+            }
+            // Only succeeds once the folder is empty.
+            let _ = fs::remove_dir(&prefix);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            removed = report.removed,
+            freed_bytes = report.freed_bytes,
+            kept = report.kept,
+            "collected garbage"
+        );
+        Ok(report)
+    }
+
+    /// Returns whether `target` still holds the object `sha1`.
+    fn holds_object(&self, target: &Path, sha1: &str) -> Result<bool, CacheError> {
+        let Ok(target_meta) = fs::metadata(target) else {
+            return Ok(false);
+        };
+        let Ok(object_meta) = fs::metadata(self.object_path(sha1)?) else {
+            return Ok(false);
+        };
+        if !target_meta.is_file() || target_meta.len() != object_meta.len() {
+            return Ok(false);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if (target_meta.dev(), target_meta.ino()) == (object_meta.dev(), object_meta.ino()) {
+                return Ok(true);
+            }
+        }
+        Ok(hash_file(target, HashAlgorithm::Sha1)?.eq_ignore_ascii_case(sha1))
+    }
+
+    /// Returns the ids of the instances with a reference file.
+    fn instances(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(self.root.join(REFS_DIR)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut instances = Vec::new();
+        for entry in entries {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if let Some(instance) = name.strip_suffix(".json") {
+                instances.push(instance.to_string());
+            }
+        }
+        Ok(instances)
+    }
+
+    fn refs_path(&self, instance: &str) -> Result<PathBuf, CacheError> {
+        let valid = !instance.is_empty()
+            && !instance.starts_with('.')
+            && !instance.contains(['/', '\\', ':']);
+        if !valid {
+            return Err(CacheError::InvalidInstance(instance.to_string()));
+        }
+        Ok(self.root.join(REFS_DIR).join(format!("{}.json", instance)))
+    }
+
+    fn write_references(
+        &self,
+        instance: &str,
+        refs: &BTreeMap<PathBuf, String>,
+    ) -> Result<(), CacheError> {
+        let path = self.refs_path(instance)?;
+        fs::create_dir_all(self.root.join(REFS_DIR))?;
+        write_atomic(&path, serde_json::to_string_pretty(refs)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Checks that a hash is 40 hex digits and returns it in lower case.
+fn normalize_hash(sha1: &str) -> Result<String, CacheError> {
+    if sha1.len() == 40 && sha1.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(sha1.to_ascii_lowercase())
+    } else {
+        Err(CacheError::InvalidHash(sha1.to_string()))
+    }
+}
+
+fn check_expected(actual: &str, expected: Option<&str>) -> Result<(), CacheError> {
+    match expected {
+        Some(expected) if !expected.eq_ignore_ascii_case(actual) => Err(CacheError::HashMismatch {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn stores_and_links_objects() {
+        let dir = tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        let jar = dir.path().join("download.jar");
+        fs::write(&jar, b"client").unwrap();
+
+        let sha1 = store.insert_file(&jar, None).unwrap();
+        assert_eq!(sha1, hex::encode(Sha1::digest(b"client")));
+        assert!(store.contains(&sha1));
+        assert!(matches!(
+            store.insert_bytes(b"other", Some(&sha1)),
+            Err(CacheError::HashMismatch { .. })
+        ));
+
+        let target = dir.path().join("a/versions/1.21/1.21.jar");
+        store.link("a", &sha1, &target).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"client");
+        assert_eq!(store.references("a").unwrap()[&target], sha1);
+        assert!(matches!(
+            store.link("a", &"0".repeat(40), &target),
+            Err(CacheError::MissingObject(_))
+        ));
+        assert!(matches!(
+            store.link("../a", &sha1, &target),
+            Err(CacheError::InvalidInstance(_))
+        ));
+    }
+
+    #[test]
+    fn collects_unreferenced_objects() {
+        let dir = tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        let shared = store.insert_bytes(b"library", None).unwrap();
+        let only_b = store.insert_bytes(b"asset", None).unwrap();
+        let unused = store.insert_bytes(b"old jar", None).unwrap();
+        store
+            .link("a", &shared, &dir.path().join("a/lib.jar"))
+            .unwrap();
+        store
+            .link("b", &shared, &dir.path().join("b/lib.jar"))
+            .unwrap();
+        store
+            .link("b", &only_b, &dir.path().join("b/asset"))
+            .unwrap();
+
+        let report = store.collect_garbage().unwrap();
+        assert_eq!((report.removed, report.kept), (1, 2));
+        assert_eq!(report.freed_bytes, 7);
+        assert!(!store.contains(&unused));
+
+        fs::remove_file(dir.path().join("b/asset")).unwrap();
+        store.release_instance("a").unwrap();
+        let report = store.collect_garbage().unwrap();
+        assert_eq!((report.removed, report.kept, report.stale_refs), (1, 1, 1));
+        assert!(store.contains(&shared) && !store.contains(&only_b));
+    }
+
+    #[test]
+    fn drops_references_of_files_replaced_in_place() {
+        let dir = tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        let old = store.insert_bytes(b"mod 1.0", None).unwrap();
+        let jar = dir.path().join("a/mods/mod.jar");
+        let copy = dir.path().join("a/mods/copy.jar");
+        store.link("a", &old, &jar).unwrap();
+        store.link("a", &old, &copy).unwrap();
+        // A copy of the object that is not a hard link still holds it.
+        fs::remove_file(&copy).unwrap();
+        fs::write(&copy, b"mod 1.0").unwrap();
+
+        assert_eq!(store.collect_garbage().unwrap().stale_refs, 0);
+        // Downloads write a new file and rename it over the old one.
+        fs::remove_file(&copy).unwrap();
+        fs::remove_file(&jar).unwrap();
+        fs::write(&jar, b"mod 2.0").unwrap();
+        let report = store.collect_garbage().unwrap();
+        assert_eq!((report.removed, report.stale_refs), (1, 2));
+        assert!(!store.contains(&old));
+        assert!(store.references("a").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,538 @@
+use super::value::{ConfigTable, ConfigValue};
+use std::fmt::{self, Write};
+use thiserror::Error;
+
+/// Error returned when a Forge `.cfg` file cannot be parsed.
+#[derive(Debug, Error)]
+#[error("invalid Forge config at line {line}: {message}")]
+pub struct ForgeCfgError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// The type of a property, written as a letter before its name, e.g. `I:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CfgType {
+    String,
+    Integer,
+    Boolean,
+    Double,
+    /// A color, stored as text.
+    Color,
+    /// A mod id, stored as text.
+    ModId,
+}
+
+impl CfgType {
+    /// Returns the type of a type letter, or `None` for unknown letters.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'S' => Some(CfgType::String),
+            'I' => Some(CfgType::Integer),
+            'B' => Some(CfgType::Boolean),
+            'D' => Some(CfgType::Double),
+            'C' => Some(CfgType::Color),
+            'M' => Some(CfgType::ModId),
+            _ => None,
+        }
+    }
+
+    /// Returns the type letter.
+    pub fn as_char(&self) -> char {
+        match self {
+            CfgType::String => 'S',
+            CfgType::Integer => 'I',
+            CfgType::Boolean => 'B',
+            CfgType::Double => 'D',
+            CfgType::Color => 'C',
+            CfgType::ModId => 'M',
+        }
+    }
+
+    /// Parses a raw value as a value of this type. Values that do not match
+    /// the type, which Forge replaces with the default, are kept as strings.
+    fn to_value(self, raw: &str) -> ConfigValue {
+        let parsed = match self {
+            CfgType::Integer => raw.parse().ok().map(ConfigValue::Integer),
+            CfgType::Boolean => raw.parse().ok().map(ConfigValue::Bool),
+            CfgType::Double => raw.parse().ok().map(ConfigValue::Float),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| ConfigValue::String(raw.to_string()))
+    }
+
+    /// Returns the type of a property created for `value`.
+    fn for_value(value: &ConfigValue) -> Self {
+        match value {
+            ConfigValue::Bool(_) => CfgType::Boolean,
+            ConfigValue::Integer(_) => CfgType::Integer,
+            ConfigValue::Float(_) => CfgType::Double,
+            ConfigValue::List(values) => values.first().map_or(CfgType::String, Self::for_value),
+            _ => CfgType::String,
+        }
+    }
+}
+
+/// A `T:name=value` property, or a `T:name < ... >` list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CfgProperty {
+    pub name: String,
+    pub kind: CfgType,
+    /// The values as written; a single value unless the property is a list.
+    pub values: Vec<String>,
+    pub is_list: bool,
+    /// The comment lines above the property, including their `#`.
+    pub comments: Vec<String>,
+}
+
+impl CfgProperty {
+    /// Returns the value as a typed config value.
+    pub fn value(&self) -> ConfigValue {
+        if self.is_list {
+            ConfigValue::List(self.values.iter().map(|v| self.kind.to_value(v)).collect())
+        } else {
+            self.kind
+                .to_value(self.values.first().map_or("", String::as_str))
+        }
+    }
+
+    fn set_value(&mut self, value: &ConfigValue) {
+        match value {
+            ConfigValue::List(values) => {
+                self.is_list = true;
+                self.values = values.iter().map(raw_value).collect();
+            }
+            value => {
+                self.is_list = false;
+                self.values = vec![raw_value(value)];
+            }
+        }
+    }
+}
+
+/// A `name { ... }` category.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CfgCategory {
+    pub name: String,
+    /// The comment lines above the category, including their `#`.
+    pub comments: Vec<String>,
+    pub properties: Vec<CfgProperty>,
+    pub children: Vec<CfgCategory>,
+}
+
+impl CfgCategory {
+    /// Returns the property `name` of this category.
+    pub fn property(&self, name: &str) -> Option<&CfgProperty> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the subcategory `name`.
+    pub fn child(&self, name: &str) -> Option<&CfgCategory> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn to_table(&self) -> ConfigTable {
+        let properties = self.properties.iter().map(|p| (p.name.clone(), p.value()));
+        let children = self
+            .children
+            .iter()
+            .map(|c| (c.name.clone(), ConfigValue::Table(c.to_table())));
+        properties.chain(children).collect()
+    }
+
+    /// Updates the category to hold `values`, keeping the raw text and the
+    /// comments of unchanged properties.
+    fn apply(&mut self, values: &ConfigTable) {
+        let present = |name: &str| values.get(name).is_some_and(|v| *v != ConfigValue::Null);
+        self.properties.retain(|p| present(&p.name));
+        self.children.retain(|c| present(&c.name));
+        for (name, value) in values.iter() {
+            match value {
+                ConfigValue::Null => {}
+                ConfigValue::Table(table) => {
+                    let index = match self.children.iter().position(|c| c.name == name) {
+                        Some(index) => index,
+                        None => {
+                            self.children.push(CfgCategory {
+                                name: name.to_string(),
+                                ..CfgCategory::default()
+                            });
+                            self.children.len() - 1
+                        }
+                    };
+                    self.children[index].apply(table);
+                }
+                value => match self.properties.iter_mut().find(|p| p.name == name) {
+                    Some(property) if property.value() == *value => {}
+                    Some(property) => property.set_value(value),
+                    None => {
+                        let mut property = CfgProperty {
+                            name: name.to_string(),
+                            kind: CfgType::for_value(value),
+                            values: Vec::new(),
+                            is_list: false,
+                            comments: Vec::new(),
+                        };
+                        property.set_value(value);
+                        self.properties.push(property);
+                    }
+                },
+            }
+        }
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let inner = "    ".repeat(depth + 1);
+        for comment in &self.comments {
+            let _ = writeln!(out, "{}{}", indent, comment);
+        }
+        let _ = writeln!(out, "{}{} {{", indent, quote(&self.name));
+        for (i, property) in self.properties.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            for comment in &property.comments {
+                let _ = writeln!(out, "{}{}", inner, comment);
+            }
+            let key = format!("{}:{}", property.kind.as_char(), quote(&property.name));
+            if property.is_list {
+                let _ = writeln!(out, "{}{} <", inner, key);
+                for value in &property.values {
+                    let _ = writeln!(out, "{}    {}", inner, value);
+                }
+                let _ = writeln!(out, "{} >", inner);
+            } else {
+                let value = property.values.first().map_or("", String::as_str);
+                let _ = writeln!(out, "{}{}={}", inner, key, value);
+            }
+        }
+        for child in &self.children {
+            out.push('\n');
+            child.write(out, depth + 1);
+        }
+        let _ = writeln!(out, "{}}}", indent);
+    }
+}
+
+/// A config file in the format of Forge before 1.13, still used by many
+/// 1.12 modpacks.
+///
+/// ```text
+/// # Configuration file
+///
+/// general {
+///     # Ticks between updates [range: 1 ~ 100, default: 20]
+///     I:updateInterval=20
+///
+///     S:blacklist <
+///         minecraft:bedrock
+///      >
+/// }
+/// ```
+///
+/// Writing the file keeps comments, and properties whose value did not change
+/// keep their text. Everything else follows the layout Forge writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForgeCfg {
+    /// The comment lines before the first category.
+    pub header: Vec<String>,
+    /// The `~CONFIG_VERSION` some mods use to migrate their config.
+    pub version: Option<String>,
+    pub categories: Vec<CfgCategory>,
+}
+
+impl ForgeCfg {
+    /// Parses the content of a `.cfg` file.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The text of the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ForgeCfg, ForgeCfgError>` - The categories of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for properties outside a category, unknown type
+    /// letters and unbalanced braces or lists.
+    pub fn parse(content: &str) -> Result<Self, ForgeCfgError> {
+        let mut cfg = ForgeCfg::default();
+        // The categories being read, innermost last.
+        let mut open: Vec<CfgCategory> = Vec::new();
+        let mut comments = Vec::new();
+        let mut list: Option<CfgProperty> = None;
+
+        for (index, line) in content.lines().enumerate() {
+            let error = |message: &str| ForgeCfgError {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            let line = line.trim();
+            if let Some(property) = &mut list {
+                if line == ">" {
+                    let property = list.take().expect("list is open");
+                    open.last_mut()
+                        .expect("lists are only opened in a category")
+                        .properties
+                        .push(property);
+                } else if !line.is_empty() {
+                    property.values.push(line.to_string());
+                }
+                continue;
+            }
+            if line.is_empty() {
+                // Comments set apart from the first category describe the file.
+                if open.is_empty() && cfg.categories.is_empty() {
+                    cfg.header.append(&mut comments);
+                }
+                continue;
+            }
+            if line.starts_with('#') {
+                comments.push(line.to_string());
+            } else if let Some(version) = line.strip_prefix("~CONFIG_VERSION:") {
+                cfg.version = Some(version.trim().to_string());
+                cfg.header.append(&mut comments);
+            } else if line == "}" {
+                let category = open.pop().ok_or_else(|| error("unexpected `}`"))?;
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(category),
+                    None => cfg.categories.push(category),
+                }
+            } else if let Some(name) = line.strip_suffix('{') {
+                open.push(CfgCategory {
+                    name: unquote(name.trim()).to_string(),
+                    comments: std::mem::take(&mut comments),
+                    ..CfgCategory::default()
+                });
+            } else {
+                let category = open
+                    .last_mut()
+                    .ok_or_else(|| error("property outside of a category"))?;
+                let (kind, rest) = line
+                    .split_once(':')
+                    .filter(|(kind, _)| kind.chars().count() == 1)
+                    .ok_or_else(|| error("expected a `T:name=value` property"))?;
+                let kind = kind
+                    .chars()
+                    .next()
+                    .and_then(CfgType::from_char)
+                    .ok_or_else(|| error("unknown property type"))?;
+                let (name, rest) = split_name(rest);
+                let mut property = CfgProperty {
+                    name: name.to_string(),
+                    kind,
+                    values: Vec::new(),
+                    is_list: false,
+                    comments: std::mem::take(&mut comments),
+                };
+                if let Some(value) = rest.strip_prefix('=') {
+                    property.values.push(value.to_string());
+                    category.properties.push(property);
+                } else if rest.trim() == "<" {
+                    property.is_list = true;
+                    list = Some(property);
+                } else {
+                    return Err(error("expected `=` or `<` after the property name"));
+                }
+            }
+        }
+        let last = content.lines().count();
+        if list.is_some() {
+            return Err(ForgeCfgError {
+                line: last,
+                message: "unclosed list".to_string(),
+            });
+        }
+        if !open.is_empty() {
+            return Err(ForgeCfgError {
+                line: last,
+                message: "unclosed category".to_string(),
+            });
+        }
+        Ok(cfg)
+    }
+
+    /// Returns the category at a dot-separated path such as `general.client`.
+    pub fn category(&self, path: &str) -> Option<&CfgCategory> {
+        let mut names = path.split('.');
+        let first = self
+            .categories
+            .iter()
+            .find(|c| Some(c.name.as_str()) == names.next())?;
+        names.try_fold(first, |category, name| category.child(name))
+    }
+
+    /// Returns the property at a dot-separated path such as `general.enabled`.
+    pub fn property(&self, path: &str) -> Option<&CfgProperty> {
+        let (category, name) = path.rsplit_once('.')?;
+        self.category(category)?.property(name)
+    }
+
+    /// Returns the categories as a table of tables.
+    pub fn to_value(&self) -> ConfigValue {
+        ConfigValue::Table(
+            self.categories
+                .iter()
+                .map(|c| (c.name.clone(), ConfigValue::Table(c.to_table())))
+                .collect(),
+        )
+    }
+
+    /// Updates the file to hold the values of a table returned by
+    /// [`ForgeCfg::to_value`] and then edited.
+    ///
+    /// Changed properties keep their type letter and comments, new entries
+    /// are appended, and entries missing from `values` are removed. Values
+    /// at the top level that are not tables are ignored, as Forge only
+    /// allows categories there.
+    pub fn apply(&mut self, values: &ConfigTable) {
+        let categories: ConfigTable = values
+            .iter()
+            .filter(|(_, value)| value.as_table().is_some())
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        let mut root = CfgCategory {
+            children: std::mem::take(&mut self.categories),
+            ..CfgCategory::default()
+        };
+        root.apply(&categories);
+        self.categories = root.children;
+    }
+}
+
+impl fmt::Display for ForgeCfg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        for comment in &self.header {
+            let _ = writeln!(out, "{}", comment);
+        }
+        if let Some(version) = &self.version {
+            let _ = writeln!(out, "~CONFIG_VERSION: {}", version);
+        }
+        for category in &self.categories {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            category.write(&mut out, 0);
+        }
+        f.write_str(&out)
+    }
+}
+
+/// Splits a property line after the type letter into the name and the rest,
+/// starting at `=` or `<`.
+fn split_name(rest: &str) -> (&str, &str) {
+    if let Some(quoted) = rest.strip_prefix('"')
+        && let Some((name, rest)) = quoted.split_once('"')
+    {
+        return (name, rest.trim_start());
+    }
+    let end = rest.find(['=', '<']).unwrap_or(rest.len());
+    (rest[..end].trim(), &rest[end..])
+}
+
+fn unquote(name: &str) -> &str {
+    name.strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+        .unwrap_or(name)
+}
+
+/// Quotes names with characters Forge does not allow unquoted.
+fn quote(name: &str) -> String {
+    let plain = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name)
+    }
+}
+
+/// Writes a value the way Forge does; doubles always have a decimal point.
+fn raw_value(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::Null => String::new(),
+        ConfigValue::Bool(b) => b.to_string(),
+        ConfigValue::Integer(i) => i.to_string(),
+        ConfigValue::Float(f) => format!("{:?}", f),
+        ConfigValue::String(s) => s.clone(),
+        ConfigValue::List(_) | ConfigValue::Table(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# Configuration file
+
+general {
+    # Ticks between updates [range: 1 ~ 100, default: 20]
+    I:updateInterval=20
+
+    B:enabled=true
+
+    S:blacklist <
+        minecraft:bedrock
+        minecraft:barrier
+     >
+
+    \"world gen\" {
+        D:oreChance=0.50
+    }
+
+}
+";
+
+    #[test]
+    fn parses_typed_properties_and_nested_categories() {
+        let cfg = ForgeCfg::parse(SAMPLE).unwrap();
+        assert_eq!(cfg.header, ["# Configuration file"]);
+        let interval = cfg.property("general.updateInterval").unwrap();
+        assert_eq!(interval.kind, CfgType::Integer);
+        assert_eq!(interval.comments.len(), 1);
+        assert_eq!(
+            cfg.property("general.blacklist").unwrap().value(),
+            ConfigValue::List(vec![
+                ConfigValue::String("minecraft:bedrock".to_string()),
+                ConfigValue::String("minecraft:barrier".to_string()),
+            ])
+        );
+        let value = cfg.to_value();
+        assert_eq!(
+            value.get_path("general.world gen.oreChance"),
+            Some(&ConfigValue::Float(0.5))
+        );
+
+        assert_eq!(ForgeCfg::parse("I:x=1\n").unwrap_err().line, 1);
+        assert_eq!(
+            ForgeCfg::parse("general {\n    X:x=1\n}\n")
+                .unwrap_err()
+                .line,
+            2
+        );
+        assert!(ForgeCfg::parse("general {\n").is_err());
+    }
+
+    #[test]
+    fn writes_back_changed_values_only() {
+        let mut cfg = ForgeCfg::parse(SAMPLE).unwrap();
+        let mut value = cfg.to_value();
+        *value.get_path_mut("general.updateInterval").unwrap() = ConfigValue::Integer(40);
+        if let ConfigValue::Table(general) = value.get_path_mut("general").unwrap() {
+            general.remove("enabled");
+            general.insert("ratio", ConfigValue::Float(2.0));
+        }
+        cfg.apply(value.as_table().unwrap());
+
+        let expected = SAMPLE
+            .replace("updateInterval=20", "updateInterval=40")
+            .replace("    B:enabled=true\n\n", "")
+            .replace("     >\n", "     >\n\n    D:ratio=2.0\n");
+        assert_eq!(cfg.to_string(), expected.replace("}\n\n}", "}\n}"));
+    }
+}
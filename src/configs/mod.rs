@@ -0,0 +1,387 @@
+/// The value tree shared by all config formats.
+pub mod value;
+
+/// The `.cfg` format of Forge before 1.13.
+pub mod forge_cfg;
+
+/// TOML reading and comment-preserving write-back.
+mod toml_doc;
+
+pub use forge_cfg::{CfgCategory, CfgProperty, CfgType, ForgeCfg, ForgeCfgError};
+pub use value::{ConfigTable, ConfigValue};
+
+use crate::filesystem::{FilesystemError, write_atomic};
+use crate::json::parse_json_lenient;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Error returned when a config file cannot be read, edited or written.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to access config file: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write config file: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid TOML: {0}")]
+    Toml(#[from] toml_edit::TomlError),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid YAML: {0}")]
+    Yaml(#[from] serde_yaml_ng::Error),
+    #[error(transparent)]
+    ForgeCfg(#[from] ForgeCfgError),
+    #[error("unknown config format: {0}")]
+    UnknownFormat(PathBuf),
+    #[error("no config value at {0}")]
+    NotFound(String),
+    #[error("{path} is a {found}, not a {expected}")]
+    TypeMismatch {
+        path: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("invalid value for {path}: {value}")]
+    InvalidValue { path: String, value: String },
+}
+
+/// The format of a mod config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigFormat {
+    /// Forge and NeoForge configs, and most Fabric mods using a TOML library.
+    Toml,
+    Json,
+    /// JSON with comments and trailing commas, also used for `.jsonc` files.
+    Json5,
+    Yaml,
+    /// The `.cfg` format of Forge before 1.13.
+    ForgeCfg,
+}
+
+impl ConfigFormat {
+    /// Detects the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            "json5" | "jsonc" => Some(ConfigFormat::Json5),
+            "yml" | "yaml" => Some(ConfigFormat::Yaml),
+            "cfg" => Some(ConfigFormat::ForgeCfg),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Json5 => "JSON5",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::ForgeCfg => "Forge .cfg",
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A parsed config file that can be edited and written back.
+///
+/// Values keep the type they were read with: [`ConfigFile::set`] refuses to
+/// store a string where the file had a number, so the mod can still read
+/// the file after an edit. TOML and Forge `.cfg` files keep
+/// their comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigFile {
+    pub format: ConfigFormat,
+    /// The parsed content, a table for every supported format.
+    pub root: ConfigValue,
+    /// The text the file was parsed from, used to keep its formatting.
+    source: String,
+}
+
+impl ConfigFile {
+    /// Parses the content of a config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The text of the file.
+    /// * `format` - The format of the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ConfigFile, ConfigError>` - The parsed file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content is invalid.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+        let root = match format {
+            ConfigFormat::Toml => toml_doc::parse(content)?,
+            ConfigFormat::Json => serde_json::from_str(content)?,
+            ConfigFormat::Json5 => parse_json_lenient(content)?,
+            ConfigFormat::Yaml => serde_yaml_ng::from_str(content)?,
+            ConfigFormat::ForgeCfg => ForgeCfg::parse(content)?.to_value(),
+        };
+        Ok(Self {
+            format,
+            root,
+            source: content.to_string(),
+        })
+    }
+
+    /// Reads and parses a config file, detecting its format from the extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::UnknownFormat`] for unknown extensions, or an
+    /// error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let format = ConfigFormat::from_path(path)
+            .ok_or_else(|| ConfigError::UnknownFormat(path.to_path_buf()))?;
+        Self::parse(&fs::read_to_string(path)?, format)
+    }
+
+    /// Returns the content of the file with the current values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the values cannot be written in the file's format.
+    pub fn to_text(&self) -> Result<String, ConfigError> {
+        match self.format {
+            ConfigFormat::Toml => {
+                let empty = ConfigTable::new();
+                let root = self.root.as_table().unwrap_or(&empty);
+                Ok(toml_doc::write(&self.source, root)?)
+            }
+            // JSON is valid JSON5, but comments of JSON5 files are lost.
+            ConfigFormat::Json | ConfigFormat::Json5 => {
+                Ok(serde_json::to_string_pretty(&self.root)? + "\n")
+            }
+            ConfigFormat::Yaml => Ok(serde_yaml_ng::to_string(&self.root)?),
+            ConfigFormat::ForgeCfg => {
+                let mut cfg = ForgeCfg::parse(&self.source)?;
+                if let Some(root) = self.root.as_table() {
+                    cfg.apply(root);
+                }
+                Ok(cfg.to_string())
+            }
+        }
+    }
+
+    /// Writes the file atomically, so the game never reads a partial config.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the values cannot be serialized or the file
+    /// cannot be written.
+    pub fn save(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let text = self.to_text()?;
+        write_atomic(path, text.as_bytes())?;
+        self.source = text;
+        Ok(())
+    }
+
+    /// Returns the value at a dot-separated path such as `client.fov`.
+    pub fn get(&self, path: &str) -> Option<&ConfigValue> {
+        self.root.get_path(path)
+    }
+
+    /// Replaces the value at a path, keeping the type it had in the file.
+    ///
+    /// Integers are accepted where the file has a float, and `null` values
+    /// of JSON and YAML can take any value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::NotFound`] if the path does not exist, or
+    /// [`ConfigError::TypeMismatch`] if the value has another type.
+    pub fn set(&mut self, path: &str, value: ConfigValue) -> Result<(), ConfigError> {
+        let current = self
+            .root
+            .get_path_mut(path)
+            .ok_or_else(|| ConfigError::NotFound(path.to_string()))?;
+        let value = match (&*current, value) {
+            (ConfigValue::Float(_), ConfigValue::Integer(i)) => ConfigValue::Float(i as f64),
+            (ConfigValue::Null, value) => value,
+            (current, value) if current.kind() == value.kind() => value,
+            (current, value) => {
+                return Err(ConfigError::TypeMismatch {
+                    path: path.to_string(),
+                    expected: current.kind(),
+                    found: value.kind(),
+                });
+            }
+        };
+        *current = value;
+        Ok(())
+    }
+
+    /// Replaces the value at a path with text typed by the user, parsed as
+    /// the type the value has in the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::NotFound`] if the path does not exist, or
+    /// [`ConfigError::InvalidValue`] if the text is not a valid value.
+    pub fn set_str(&mut self, path: &str, text: &str) -> Result<(), ConfigError> {
+        let current = self
+            .root
+            .get_path_mut(path)
+            .ok_or_else(|| ConfigError::NotFound(path.to_string()))?;
+        *current = current
+            .parse_like(text)
+            .ok_or_else(|| ConfigError::InvalidValue {
+                path: path.to_string(),
+                value: text.to_string(),
+            })?;
+        Ok(())
+    }
+}
+
+/// A config file found by [`scan_config_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    /// Path relative to the config directory, with `/` separators.
+    pub path: String,
+    pub format: ConfigFormat,
+}
+
+/// Lists the config files of an instance, including those in subfolders.
+///
+/// # Arguments
+///
+/// * `config_dir` - The `config` directory of a game directory.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<ConfigEntry>>` - The files with a known format, sorted
+///   by path, or an empty list if the directory does not exist.
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be read.
+pub fn scan_config_dir(config_dir: &Path) -> io::Result<Vec<ConfigEntry>> {
+    let mut entries = Vec::new();
+    let mut pending = vec![String::new()];
+    while let Some(relative) = pending.pop() {
+        let dir = match fs::read_dir(config_dir.join(&relative)) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in dir {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if let Some(format) = ConfigFormat::from_path(Path::new(&path)) {
+                entries.push(ConfigEntry { path, format });
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn toml_edits_keep_comments_and_types() {
+        let source = "# Client settings\n[client]\n\t# Field of view\n\tfov = 70.0 # degrees\n\
+                      \tshowHud = true\n\n[server]\nmaxPlayers = 20\n";
+        let mut config = ConfigFile::parse(source, ConfigFormat::Toml).unwrap();
+        assert_eq!(config.get("client.fov"), Some(&ConfigValue::Float(70.0)));
+
+        config.set("client.fov", ConfigValue::Integer(90)).unwrap();
+        config.set_str("server.maxPlayers", "8").unwrap();
+        assert!(matches!(
+            config.set("client.showHud", ConfigValue::String("yes".to_string())),
+            Err(ConfigError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            config.set_str("server.maxPlayers", "many"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+
+        assert_eq!(
+            config.to_text().unwrap(),
+            "# Client settings\n[client]\n\t# Field of view\n\tfov = 90.0 # degrees\n\
+             \tshowHud = true\n\n[server]\nmaxPlayers = 8\n"
+        );
+    }
+
+    #[test]
+    fn json_and_yaml_keep_key_order() {
+        let mut json = ConfigFile::parse(
+            r#"{"zoom": 3, "alpha": [1, 2.5], "extra": null}"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+        json.set("alpha.1", ConfigValue::Integer(4)).unwrap();
+        json.set("extra", ConfigValue::Bool(true)).unwrap();
+        assert_eq!(
+            json.to_text().unwrap(),
+            "{\n  \"zoom\": 3,\n  \"alpha\": [\n    1,\n    4.0\n  ],\n  \"extra\": true\n}\n"
+        );
+
+        let yaml = ConfigFile::parse(
+            "zoom: 3\nalpha:\n  enabled: yes\n  1: one\n",
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        let keys: Vec<&str> = yaml
+            .root
+            .as_table()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, ["zoom", "alpha"]);
+        assert_eq!(
+            yaml.get("alpha.1"),
+            Some(&ConfigValue::String("one".to_string()))
+        );
+        assert_eq!(
+            yaml.to_text().unwrap(),
+            "zoom: 3\nalpha:\n  enabled: yes\n  '1': one\n"
+        );
+    }
+
+    #[test]
+    fn scans_configs_recursively() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sodium")).unwrap();
+        fs::write(dir.path().join("sodium/options.json"), "{}").unwrap();
+        fs::write(dir.path().join("create-client.toml"), "").unwrap();
+        fs::write(dir.path().join("forge.cfg"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let entries = scan_config_dir(dir.path()).unwrap();
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["create-client.toml", "forge.cfg", "sodium/options.json"]
+        );
+        assert_eq!(entries[1].format, ConfigFormat::ForgeCfg);
+        assert!(
+            scan_config_dir(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use super::value::{ConfigTable, ConfigValue};
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// Parses a TOML document into a table value.
+pub(super) fn parse(content: &str) -> Result<ConfigValue, toml_edit::TomlError> {
+    let document: DocumentMut = content.parse()?;
+    Ok(ConfigValue::Table(from_table(document.as_table())))
+}
+
+/// Writes `root` as TOML, updating `source` in place so that only changed
+/// values lose their original formatting.
+pub(super) fn write(source: &str, root: &ConfigTable) -> Result<String, toml_edit::TomlError> {
+    let mut document: DocumentMut = source.parse()?;
+    apply_table(document.as_table_mut(), root);
+    Ok(document.to_string())
+}
+
+fn from_table(table: &dyn TableLike) -> ConfigTable {
+    table
+        .iter()
+        .filter_map(|(key, item)| Some((key.to_string(), from_item(item)?)))
+        .collect()
+}
+
+fn from_item(item: &Item) -> Option<ConfigValue> {
+    match item {
+        Item::None => None,
+        Item::Value(value) => Some(from_value(value)),
+        Item::Table(table) => Some(ConfigValue::Table(from_table(table))),
+        Item::ArrayOfTables(tables) => Some(ConfigValue::List(
+            tables
+                .iter()
+                .map(|table| ConfigValue::Table(from_table(table)))
+                .collect(),
+        )),
+    }
+}
+
+fn from_value(value: &Value) -> ConfigValue {
+    match value {
+        Value::String(s) => ConfigValue::String(s.value().clone()),
+        Value::Integer(i) => ConfigValue::Integer(*i.value()),
+        Value::Float(f) => ConfigValue::Float(*f.value()),
+        Value::Boolean(b) => ConfigValue::Bool(*b.value()),
+        // Dates are rare in configs; editing them as text keeps them intact.
+        Value::Datetime(d) => ConfigValue::String(d.value().to_string()),
+        Value::Array(array) => ConfigValue::List(array.iter().map(from_value).collect()),
+        Value::InlineTable(table) => ConfigValue::Table(from_table(table)),
+    }
+}
+
+/// Updates `table` to hold `values`, leaving unchanged entries untouched.
+fn apply_table(table: &mut dyn TableLike, values: &ConfigTable) {
+    let removed: Vec<String> = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| values.get(key).is_none_or(|v| *v == ConfigValue::Null))
+        .collect();
+    for key in removed {
+        table.remove(&key);
+    }
+    for (key, value) in values.iter() {
+        if *value == ConfigValue::Null {
+            continue;
+        }
+        match table.get_mut(key) {
+            Some(item) => apply_item(item, value),
+            None => {
+                table.insert(key, to_item(value));
+            }
+        }
+    }
+}
+
+fn apply_item(item: &mut Item, value: &ConfigValue) {
+    if from_item(item).as_ref() == Some(value) {
+        return;
+    }
+    match (&mut *item, value) {
+        (Item::Table(table), ConfigValue::Table(values)) => apply_table(table, values),
+        (Item::Value(Value::InlineTable(table)), ConfigValue::Table(values)) => {
+            apply_table(table, values)
+        }
+        (Item::Value(existing), _) => {
+            let decor = existing.decor().clone();
+            *existing = to_value(value);
+            *existing.decor_mut() = decor;
+        }
+        _ => *item = to_item(value),
+    }
+}
+
+/// Converts a value to a TOML item: tables become `[table]` sections and lists
+/// of tables `[[table]]` sections.
+fn to_item(value: &ConfigValue) -> Item {
+    match value {
+        ConfigValue::Table(values) => {
+            let mut table = Table::new();
+            apply_table(&mut table, values);
+            Item::Table(table)
+        }
+        ConfigValue::List(values)
+            if !values.is_empty() && values.iter().all(|v| v.as_table().is_some()) =>
+        {
+            let mut tables = ArrayOfTables::new();
+            for values in values.iter().filter_map(ConfigValue::as_table) {
+                let mut table = Table::new();
+                apply_table(&mut table, values);
+                tables.push(table);
+            }
+            Item::ArrayOfTables(tables)
+        }
+        _ => Item::Value(to_value(value)),
+    }
+}
+
+fn to_value(value: &ConfigValue) -> Value {
+    match value {
+        // TOML has no null; an empty string is the closest.
+        ConfigValue::Null => Value::from(""),
+        ConfigValue::Bool(b) => Value::from(*b),
+        ConfigValue::Integer(i) => Value::from(*i),
+        ConfigValue::Float(f) => Value::from(*f),
+        ConfigValue::String(s) => Value::from(s.as_str()),
+        ConfigValue::List(values) => Value::Array(values.iter().map(to_value).collect::<Array>()),
+        ConfigValue::Table(values) => {
+            let mut table = InlineTable::new();
+            for (key, value) in values.iter() {
+                table.insert(key, to_value(value));
+            }
+            Value::InlineTable(table)
+        }
+    }
+}
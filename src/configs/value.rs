@@ -0,0 +1,301 @@
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::fmt;
+
+/// A value of a config file, in any of the supported formats.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    /// JSON and YAML `null`.
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<ConfigValue>),
+    Table(ConfigTable),
+}
+
+impl ConfigValue {
+    /// Returns the name of the value's type, e.g. `integer`, for messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConfigValue::Null => "null",
+            ConfigValue::Bool(_) => "boolean",
+            ConfigValue::Integer(_) => "integer",
+            ConfigValue::Float(_) => "float",
+            ConfigValue::String(_) => "string",
+            ConfigValue::List(_) => "list",
+            ConfigValue::Table(_) => "table",
+        }
+    }
+
+    /// Returns the boolean, if the value is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConfigValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if the value is one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ConfigValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns floats, and integers converted to floats.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ConfigValue::Float(value) => Some(*value),
+            ConfigValue::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if the value is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ConfigValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if the value is a list.
+    pub fn as_list(&self) -> Option<&[ConfigValue]> {
+        match self {
+            ConfigValue::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the table, if the value is one.
+    pub fn as_table(&self) -> Option<&ConfigTable> {
+        match self {
+            ConfigValue::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    /// Returns the value at a dot-separated path such as `client.fov`, where
+    /// numbers index lists.
+    pub fn get_path(&self, path: &str) -> Option<&ConfigValue> {
+        path.split('.').try_fold(self, |value, key| match value {
+            ConfigValue::Table(table) => table.get(key),
+            ConfigValue::List(values) => values.get(key.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Returns the value at a dot-separated path for changing it.
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut ConfigValue> {
+        path.split('.').try_fold(self, |value, key| match value {
+            ConfigValue::Table(table) => table.get_mut(key),
+            ConfigValue::List(values) => values.get_mut(key.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Parses `text` as a value of the same type as `self`, e.g. for a value
+    /// typed into an editor. Strings are taken as is.
+    pub fn parse_like(&self, text: &str) -> Option<ConfigValue> {
+        let text = text.trim();
+        match self {
+            ConfigValue::Bool(_) => text.parse().ok().map(ConfigValue::Bool),
+            ConfigValue::Integer(_) => text.parse().ok().map(ConfigValue::Integer),
+            ConfigValue::Float(_) => text.parse().ok().map(ConfigValue::Float),
+            ConfigValue::String(_) | ConfigValue::Null => {
+                Some(ConfigValue::String(text.to_string()))
+            }
+            ConfigValue::List(_) | ConfigValue::Table(_) => None,
+        }
+    }
+}
+
+/// The entries of a table, in file order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigTable {
+    entries: Vec<(String, ConfigValue)>,
+}
+
+impl ConfigTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of `key`.
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the value of `key` for changing it.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut ConfigValue> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Sets an entry, keeping its position if it exists and appending it
+    /// otherwise. Returns the previous value.
+    pub fn insert(&mut self, key: impl Into<String>, value: ConfigValue) -> Option<ConfigValue> {
+        let key = key.into();
+        match self.get_mut(&key) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes an entry and returns its value.
+    pub fn remove(&mut self, key: &str) -> Option<ConfigValue> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Returns the entries in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ConfigValue)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl FromIterator<(String, ConfigValue)> for ConfigTable {
+    fn from_iter<I: IntoIterator<Item = (String, ConfigValue)>>(iter: I) -> Self {
+        let mut table = ConfigTable::new();
+        for (key, value) in iter {
+            table.insert(key, value);
+        }
+        table
+    }
+}
+
+impl Serialize for ConfigValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ConfigValue::Null => serializer.serialize_unit(),
+            ConfigValue::Bool(value) => serializer.serialize_bool(*value),
+            ConfigValue::Integer(value) => serializer.serialize_i64(*value),
+            ConfigValue::Float(value) => serializer.serialize_f64(*value),
+            ConfigValue::String(value) => serializer.serialize_str(value),
+            ConfigValue::List(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            ConfigValue::Table(table) => table.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for ConfigTable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = ConfigValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a config value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<ConfigValue, D::Error> {
+        ConfigValue::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<ConfigValue, E> {
+        // Only integers beyond i64 lose precision.
+        Ok(i64::try_from(value).map_or(ConfigValue::Float(value as f64), ConfigValue::Integer))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::Float(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<ConfigValue, E> {
+        Ok(ConfigValue::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ConfigValue, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(ConfigValue::List(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ConfigValue, A::Error> {
+        let mut table = ConfigTable::new();
+        while let Some((KeyString(key), value)) = map.next_entry()? {
+            table.insert(key, value);
+        }
+        Ok(ConfigValue::Table(table))
+    }
+}
+
+/// A map key, which YAML also allows to be a number or boolean.
+struct KeyString(String);
+
+impl<'de> Deserialize<'de> for KeyString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match ConfigValue::deserialize(deserializer)? {
+            ConfigValue::String(key) => Ok(KeyString(key)),
+            ConfigValue::Bool(key) => Ok(KeyString(key.to_string())),
+            ConfigValue::Integer(key) => Ok(KeyString(key.to_string())),
+            ConfigValue::Float(key) => Ok(KeyString(key.to_string())),
+            other => Err(de::Error::custom(format!(
+                "unsupported {} map key",
+                other.kind()
+            ))),
+        }
+    }
+}
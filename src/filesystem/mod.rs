@@ -33,17 +33,12 @@ impl Default for WriteOptions {
 }
 
 /// Options for removing files or directories, such as recursive removal.
+#[derive(Default)]
 pub struct RemoveOptions {
     /// If true, removes directories recursively.
     pub recursive: bool,
 }
 
-impl Default for RemoveOptions {
-    fn default() -> Self {
-        Self { recursive: false }
-    }
-}
-
 /// Creates a directory if it does not exist.
 ///
 /// # Arguments
@@ -107,6 +102,7 @@ pub fn file_exists<P: AsRef<Path>>(file: P) -> bool {
 /// # Errors
 ///
 /// Returns `FilesystemError` if the move operation fails.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(src = %src.as_ref().display(), dst = %dst.as_ref().display()), err(level = "warn")))]
 pub fn move_if_exists<P: AsRef<Path>>(src: P, dst: P) -> Result<(), FilesystemError> {
     fs::rename(src, dst)?;
     Ok(())
@@ -127,6 +123,7 @@ pub fn move_if_exists<P: AsRef<Path>>(src: P, dst: P) -> Result<(), FilesystemEr
 /// # Returns
 ///
 /// The number of bytes copied.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(src = %src.as_ref().display(), dst = %dst.as_ref().display()), err(level = "warn")))]
 pub fn copy_if_exists<P: AsRef<Path>>(src: P, dst: P, overwrite: bool) -> Result<u64, FilesystemError> {
     let dst_path = dst.as_ref();
     if dst_path.exists() && !overwrite {
@@ -148,6 +145,7 @@ pub fn copy_if_exists<P: AsRef<Path>>(src: P, dst: P, overwrite: bool) -> Result
 /// # Errors
 ///
 /// Returns `FilesystemError` if the removal fails.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(path = %path.as_ref().display()), err(level = "warn")))]
 pub fn remove_if_exists<P: AsRef<Path>>(path: P, options: RemoveOptions) -> Result<(), FilesystemError> {
     let p = path.as_ref();
     if p.is_dir() {
@@ -190,6 +188,7 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String, FilesystemError> {
 /// # Errors
 ///
 /// Returns `FilesystemError` if the write fails or overwrite is not allowed.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(path = %path.as_ref().display()), err(level = "warn")))]
 pub fn write_file<P: AsRef<Path>>(path: P, content: &str, options: WriteOptions) -> Result<(), FilesystemError> {
     let p = path.as_ref();
     if p.exists() && !options.overwrite {
@@ -203,6 +202,32 @@ pub fn write_file<P: AsRef<Path>>(path: P, content: &str, options: WriteOptions)
     Ok(())
 }
 
+/// Writes content to a file atomically.
+///
+/// The content is written to a temporary file next to `path`, which then
+/// replaces `path`, so readers never see a partially written file.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file.
+/// * `content` - Content to write.
+///
+/// # Errors
+///
+/// Returns `FilesystemError` if the temporary file cannot be written or renamed.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(path = %path.as_ref().display(), bytes = content.len()), err(level = "warn")))]
+pub fn write_atomic<P: AsRef<Path>>(path: P, content: &[u8]) -> Result<(), FilesystemError> {
+    let p = path.as_ref();
+    let dir = match p.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    tmp.write_all(content)?;
+    tmp.persist(p).map_err(|e| e.error)?;
+    Ok(())
+}
+
 /// Expands a path that starts with `~` to the user's home directory.
 ///
 /// # Arguments
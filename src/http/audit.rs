@@ -0,0 +1,226 @@
+use super::batch::DownloadRequest;
+use super::{FileCheck, verify_file};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A local file together with its expected size and hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathCheck {
+    /// The file to verify. `~` is expanded.
+    pub path: PathBuf,
+    /// The expected size and hash.
+    pub check: FileCheck,
+    /// Where the file can be downloaded from, used to build repair requests.
+    pub url: Option<String>,
+}
+
+impl PathCheck {
+    /// Creates a check without a download URL.
+    pub fn new(path: impl Into<PathBuf>, check: FileCheck) -> Self {
+        Self {
+            path: path.into(),
+            check,
+            url: None,
+        }
+    }
+
+    /// Sets the URL the file is downloaded from.
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+}
+
+/// Result of verifying a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditStatus {
+    /// The file matches.
+    Ok,
+    /// The file does not exist.
+    Missing,
+    /// The file has the wrong size. It was not hashed.
+    SizeMismatch {
+        /// The size of the file on disk.
+        actual: u64,
+    },
+    /// The file has the expected size but the wrong hash.
+    HashMismatch,
+    /// The file could not be read.
+    Error {
+        /// Human readable description of the failure.
+        reason: String,
+    },
+}
+
+/// The result of verifying one [`PathCheck`].
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// The check this entry belongs to.
+    pub check: PathCheck,
+    /// What was found on disk.
+    pub status: AuditStatus,
+}
+
+/// Summary of a [`verify_files`] run.
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    /// Per-file results, in the order the checks were given.
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditReport {
+    /// Returns `true` if every file matched.
+    pub fn is_clean(&self) -> bool {
+        self.failed().next().is_none()
+    }
+
+    /// Iterates over the files that are missing or do not match.
+    pub fn failed(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter().filter(|e| e.status != AuditStatus::Ok)
+    }
+
+    /// Returns download requests for every failed file that has a URL.
+    ///
+    /// The requests carry the expected size and hash, so they can be passed to
+    /// `download_all` with `overwrite` set to repair the files.
+    pub fn repair_requests(&self) -> Vec<DownloadRequest> {
+        self.failed()
+            .filter_map(|e| {
+                let url = e.check.url.as_ref()?;
+                Some(
+                    DownloadRequest::new(url.clone(), e.check.path.clone())
+                        .with_check(e.check.check.clone()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Verifies local files against their expected size and hash, in parallel.
+///
+/// No network access is performed. Sizes are compared first, so truncated files
+/// are reported without hashing them.
+///
+/// # Arguments
+///
+/// * `checks` - The files to verify.
+///
+/// # Returns
+///
+/// * `AuditReport` - Per-file status, in the order of `checks`.
+pub fn verify_files(checks: &[PathCheck]) -> AuditReport {
+    let workers = thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .min(checks.len());
+    let next = AtomicUsize::new(0);
+    let statuses = Mutex::new(vec![AuditStatus::Ok; checks.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(check) = checks.get(i) else {
+                        break;
+                    };
+                    let status = audit(check);
+                    statuses.lock().unwrap_or_else(|e| e.into_inner())[i] = status;
+                }
+            });
+        }
+    });
+
+    let statuses = statuses.into_inner().unwrap_or_else(|e| e.into_inner());
+    AuditReport {
+        entries: checks
+            .iter()
+            .cloned()
+            .zip(statuses)
+            .map(|(check, status)| AuditEntry { check, status })
+            .collect(),
+    }
+}
+
+fn audit(check: &PathCheck) -> AuditStatus {
+    let path = crate::filesystem::expand_home(&check.path.to_string_lossy());
+    let len = match fs::metadata(&path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return AuditStatus::Missing,
+        Err(e) => {
+            return AuditStatus::Error {
+                reason: e.to_string(),
+            };
+        }
+    };
+    if let Some(size) = check.check.size
+        && size != len
+    {
+        return AuditStatus::SizeMismatch { actual: len };
+    }
+    match &check.check.hash {
+        Some(spec) => match verify_file(&path, spec) {
+            Ok(true) => AuditStatus::Ok,
+            Ok(false) => AuditStatus::HashMismatch,
+            Err(e) => AuditStatus::Error {
+                reason: e.to_string(),
+            },
+        },
+        None => AuditStatus::Ok,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{HashAlgorithm, HashSpec};
+    use tempfile::tempdir;
+
+    #[test]
+    fn verify_files_reports_each_kind_of_failure() {
+        let dir = tempdir().unwrap();
+        let sha1 = HashSpec::new(
+            HashAlgorithm::Sha1,
+            "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed",
+        );
+        fs::write(dir.path().join("good"), b"hello world").unwrap();
+        fs::write(dir.path().join("corrupt"), b"hello worlD").unwrap();
+        fs::write(dir.path().join("short"), b"hello").unwrap();
+
+        let checks = vec![
+            PathCheck::new(dir.path().join("good"), FileCheck::new(11, sha1.clone())),
+            PathCheck::new(dir.path().join("short"), FileCheck::new(11, sha1.clone()))
+                .with_url("https://example.invalid/short"),
+            PathCheck::new(dir.path().join("corrupt"), FileCheck::new(11, sha1.clone())),
+            PathCheck::new(dir.path().join("missing"), FileCheck::new(11, sha1))
+                .with_url("https://example.invalid/missing"),
+        ];
+        let report = verify_files(&checks);
+
+        let statuses: Vec<&AuditStatus> = report.entries.iter().map(|e| &e.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                &AuditStatus::Ok,
+                &AuditStatus::SizeMismatch { actual: 5 },
+                &AuditStatus::HashMismatch,
+                &AuditStatus::Missing,
+            ]
+        );
+        assert!(!report.is_clean());
+
+        let repairs = report.repair_requests();
+        assert_eq!(repairs.len(), 2);
+        assert_eq!(repairs[0].url, "https://example.invalid/short");
+        assert_eq!(repairs[0].size, Some(11));
+        assert_eq!(repairs[0].hashes.len(), 1);
+    }
+
+    #[test]
+    fn verify_files_with_no_checks_is_clean() {
+        assert!(verify_files(&[]).is_clean());
+    }
+}
@@ -0,0 +1,334 @@
+use super::{FileCheck, HashSpec};
+use super::client::HttpClient;
+use super::manager::{DownloadManager, Priority};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A single file to download.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadRequest {
+    /// The URL to download from.
+    pub url: String,
+    /// The destination path. `~` is expanded.
+    pub path: PathBuf,
+    /// Acceptable hashes of the file. The file verifies if any of them matches;
+    /// an empty list disables verification.
+    #[serde(default)]
+    pub hashes: Vec<HashSpec>,
+    /// Expected size of the file in bytes. Checked before hashing.
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Extra headers sent with this request, in addition to the client's headers.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Scheduling priority when the request is queued with other downloads.
+    #[serde(default)]
+    pub priority: Priority,
+}
+
+impl DownloadRequest {
+    /// Creates a request without hash verification.
+    pub fn new(url: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            url: url.into(),
+            path: path.into(),
+            hashes: Vec::new(),
+            size: None,
+            headers: Vec::new(),
+            priority: Priority::default(),
+        }
+    }
+
+    /// Adds an acceptable hash of the file.
+    ///
+    /// Can be called several times when different sources publish different
+    /// algorithms; matching any one of them is sufficient.
+    pub fn with_hash(mut self, hash: HashSpec) -> Self {
+        self.hashes.push(hash);
+        self
+    }
+
+    /// Sets the expected size of the file.
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Applies the size and hash of `check`, leaving unset fields unchanged.
+    pub fn with_check(mut self, check: FileCheck) -> Self {
+        if let Some(size) = check.size {
+            self.size = Some(size);
+        }
+        if let Some(hash) = check.hash {
+            self.hashes.push(hash);
+        }
+        self
+    }
+
+    /// Adds a header to send with this request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the scheduling priority of this request.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Authenticates this request with a bearer token.
+    pub fn with_bearer_token(self, token: &str) -> Self {
+        self.with_header("Authorization", super::client::bearer(token))
+    }
+}
+
+/// Options for batch downloads.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Maximum number of concurrent downloads.
+    pub concurrency: usize,
+    /// If true, existing files are downloaded again even if they verify.
+    pub overwrite: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            overwrite: false,
+        }
+    }
+}
+
+/// Status of a single file in a batch download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadStatus {
+    /// The file was downloaded.
+    Downloaded {
+        /// Number of bytes written.
+        bytes: u64,
+    },
+    /// The file already existed and matched the expected hash, so it was kept, or
+    /// it was downloaded by an identical request running at the same time.
+    Skipped,
+    /// The download failed.
+    Failed {
+        /// Human readable description of the failure.
+        reason: String,
+    },
+}
+
+/// The result of downloading a single request.
+#[derive(Debug, Clone)]
+pub struct DownloadOutcome {
+    /// The request this outcome belongs to.
+    pub request: DownloadRequest,
+    /// What happened to the request.
+    pub status: DownloadStatus,
+}
+
+/// Summary of a batch download.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadReport {
+    /// Per-request outcomes, in the order the requests were given.
+    pub outcomes: Vec<DownloadOutcome>,
+    /// Total number of bytes downloaded.
+    pub total_bytes: u64,
+    /// Wall-clock time the batch took.
+    pub elapsed: Duration,
+}
+
+impl DownloadReport {
+    /// Returns `true` if no download failed.
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+
+    /// Iterates over the outcomes of downloaded files.
+    pub fn downloaded(&self) -> impl Iterator<Item = &DownloadOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| matches!(o.status, DownloadStatus::Downloaded { .. }))
+    }
+
+    /// Iterates over the outcomes of files that were already present.
+    pub fn skipped(&self) -> impl Iterator<Item = &DownloadOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| o.status == DownloadStatus::Skipped)
+    }
+
+    /// Iterates over the outcomes of failed downloads.
+    pub fn failed(&self) -> impl Iterator<Item = &DownloadOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|o| matches!(o.status, DownloadStatus::Failed { .. }))
+    }
+
+    /// Returns the requests that failed, ready to be retried.
+    pub fn failed_requests(&self) -> Vec<DownloadRequest> {
+        self.failed().map(|o| o.request.clone()).collect()
+    }
+}
+
+impl HttpClient {
+    /// Downloads all requests concurrently and reports the status of each one.
+    ///
+    /// Higher priority requests are started first. Failures do not abort the batch;
+    /// they are recorded in the report.
+    pub async fn download_all(
+        &self,
+        requests: Vec<DownloadRequest>,
+        options: &BatchOptions,
+    ) -> DownloadReport {
+        let manager = DownloadManager::new(self.clone(), options.clone());
+        for request in requests {
+            manager.push(request);
+        }
+        manager.run().await
+    }
+}
+
+/// Downloads all requests with a default client.
+///
+/// # Arguments
+///
+/// * `requests` - The files to download.
+/// * `options` - Concurrency and overwrite options.
+///
+/// # Returns
+///
+/// * `DownloadReport` - Per-file status, total bytes and elapsed time.
+pub async fn download_all(
+    requests: Vec<DownloadRequest>,
+    options: &BatchOptions,
+) -> DownloadReport {
+    HttpClient::default().download_all(requests, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HashAlgorithm;
+    use sha1::{Digest, Sha1};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn download_all_reports_downloaded_skipped_and_failed() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/a.jar");
+            then.status(200).body("aaaa");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/missing.jar");
+            then.status(404);
+        });
+        let cached_path = dir.path().join("cached.jar");
+        fs::write(&cached_path, b"cached").unwrap();
+        let cached_hash = HashSpec::new(HashAlgorithm::Sha1, hex::encode(Sha1::digest(b"cached")));
+
+        let requests = vec![
+            DownloadRequest::new(server.url("/a.jar"), dir.path().join("a.jar")),
+            DownloadRequest::new(server.url("/cached.jar"), &cached_path).with_hash(cached_hash),
+            DownloadRequest::new(server.url("/missing.jar"), dir.path().join("missing.jar")),
+        ];
+        let report = download_all(requests, &BatchOptions::default()).await;
+
+        assert_eq!(report.outcomes.len(), 3);
+        assert_eq!(
+            report.outcomes[0].status,
+            DownloadStatus::Downloaded { bytes: 4 }
+        );
+        assert_eq!(report.outcomes[1].status, DownloadStatus::Skipped);
+        assert!(matches!(
+            report.outcomes[2].status,
+            DownloadStatus::Failed { .. }
+        ));
+        assert_eq!(report.total_bytes, 4);
+        assert!(!report.is_success());
+        assert_eq!(report.failed_requests()[0].url, server.url("/missing.jar"));
+        assert_eq!(fs::read(dir.path().join("a.jar")).unwrap(), b"aaaa");
+    }
+
+    #[tokio::test]
+    async fn download_all_reports_hash_mismatch_as_failure() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar");
+            then.status(200).body("library");
+        });
+
+        let request = DownloadRequest::new(server.url("/lib.jar"), dir.path().join("lib.jar"))
+            .with_hash(HashSpec::new(HashAlgorithm::Sha1, "0".repeat(40)));
+        let report = download_all(vec![request], &BatchOptions::default()).await;
+
+        match &report.outcomes[0].status {
+            DownloadStatus::Failed { reason } => assert!(reason.contains("hash mismatch")),
+            other => panic!("expected failure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn download_all_accepts_any_matching_hash() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/mod.jar");
+            then.status(200).body("mod");
+        });
+        let sha1 = HashSpec::new(HashAlgorithm::Sha1, hex::encode(Sha1::digest(b"mod")));
+        let stale_sha512 = HashSpec::new(HashAlgorithm::Sha512, "0".repeat(128));
+
+        let ok = DownloadRequest::new(server.url("/mod.jar"), dir.path().join("ok.jar"))
+            .with_hash(stale_sha512.clone())
+            .with_hash(sha1);
+        let bad = DownloadRequest::new(server.url("/mod.jar"), dir.path().join("bad.jar"))
+            .with_hash(stale_sha512)
+            .with_hash(HashSpec::new(HashAlgorithm::Sha1, "0".repeat(40)));
+        let report = download_all(vec![ok, bad], &BatchOptions::default()).await;
+
+        assert_eq!(
+            report.outcomes[0].status,
+            DownloadStatus::Downloaded { bytes: 3 }
+        );
+        match &report.outcomes[1].status {
+            DownloadStatus::Failed { reason } => {
+                assert!(reason.contains("sha512:"));
+                assert!(reason.contains("sha1:"));
+            }
+            other => panic!("expected failure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn download_all_sends_per_request_headers() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET")
+                .path("/private.zip")
+                .header("Authorization", "Bearer abc");
+            then.status(200).body("zip");
+        });
+
+        let request = DownloadRequest::new(server.url("/private.zip"), dir.path().join("p.zip"))
+            .with_bearer_token("abc");
+        let report = download_all(vec![request], &BatchOptions::default()).await;
+
+        assert!(report.is_success());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn download_all_with_no_requests_is_success() {
+        let report = download_all(Vec::new(), &BatchOptions::default()).await;
+        assert!(report.is_success());
+        assert_eq!(report.total_bytes, 0);
+    }
+}
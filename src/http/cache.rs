@@ -0,0 +1,378 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Options controlling how long responses stay fresh and how large the cache may grow.
+#[derive(Debug, Clone)]
+pub struct CacheOptions {
+    /// How long a stored response is considered fresh.
+    pub ttl: Duration,
+    /// Maximum total size of all cached bodies in bytes. Least recently used
+    /// entries are evicted once this limit is exceeded.
+    pub max_size: u64,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60 * 60),
+            max_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// A response body read from the cache, together with its age information.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// The cached response body.
+    pub body: Vec<u8>,
+    /// When the response was stored.
+    pub stored_at: SystemTime,
+    /// Whether the entry is still within its time-to-live.
+    pub fresh: bool,
+}
+
+/// Metadata stored next to every cached body.
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryMeta {
+    url: String,
+    stored_at: u64,
+    last_access: u64,
+    size: u64,
+}
+
+/// An on-disk cache for HTTP responses, keyed by URL.
+///
+/// Every entry consists of a `<key>.body` file holding the raw response and a
+/// `<key>.json` file holding its metadata, where `<key>` is the SHA-256 of the URL.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    options: CacheOptions,
+}
+
+impl ResponseCache {
+    /// Opens (and creates if needed) a response cache in the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory holding the cache entries. `~` is expanded.
+    /// * `options` - TTL and size limits for the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn new<P: AsRef<Path>>(dir: P, options: CacheOptions) -> io::Result<Self> {
+        let dir = crate::filesystem::expand_home(&dir.as_ref().to_string_lossy());
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, options })
+    }
+
+    /// Returns the directory the cache lives in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the options the cache was opened with.
+    pub fn options(&self) -> &CacheOptions {
+        &self.options
+    }
+
+    /// Returns the cached body for `url` if it exists and is still fresh.
+    pub fn get(&self, url: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self
+            .get_stale(url)?
+            .filter(|entry| entry.fresh)
+            .map(|entry| entry.body))
+    }
+
+    /// Returns the cached body for `url` regardless of its age.
+    ///
+    /// Useful as a fallback when the network is unavailable.
+    pub fn get_stale(&self, url: &str) -> io::Result<Option<CachedResponse>> {
+        let (body_path, meta_path) = self.entry_paths(url);
+        let mut meta = match read_meta(&meta_path)? {
+            Some(meta) if meta.url == url => meta,
+            _ => return Ok(None),
+        };
+        let body = match fs::read(&body_path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let now = now_millis();
+        let age = Duration::from_millis(now.saturating_sub(meta.stored_at));
+        let stored_at = UNIX_EPOCH + Duration::from_millis(meta.stored_at);
+
+        meta.last_access = now;
+        write_atomic(&self.dir, &meta_path, &serde_json::to_vec(&meta)?)?;
+
+        Ok(Some(CachedResponse {
+            body,
+            stored_at,
+            fresh: age < self.options.ttl,
+        }))
+    }
+
+    /// Stores a response body for `url`, evicting older entries if the cache grows too large.
+    ///
+    /// Bodies larger than the configured maximum size are not stored.
+    pub fn put(&self, url: &str, body: &[u8]) -> io::Result<()> {
+        if body.len() as u64 > self.options.max_size {
+            return Ok(());
+        }
+        let (body_path, meta_path) = self.entry_paths(url);
+        let now = now_millis();
+        let meta = EntryMeta {
+            url: url.to_string(),
+            stored_at: now,
+            last_access: now,
+            size: body.len() as u64,
+        };
+        write_atomic(&self.dir, &body_path, body)?;
+        write_atomic(&self.dir, &meta_path, &serde_json::to_vec(&meta)?)?;
+        self.evict()
+    }
+
+    /// Removes the entry for `url`, if any.
+    pub fn remove(&self, url: &str) -> io::Result<()> {
+        let (body_path, meta_path) = self.entry_paths(url);
+        remove_if_present(&meta_path)?;
+        remove_if_present(&body_path)
+    }
+
+    /// Removes every entry from the cache.
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if is_cache_file(&path) {
+                remove_if_present(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the total size in bytes of all cached bodies.
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.entries()?.iter().map(|(_, meta)| meta.size).sum())
+    }
+
+    /// Evicts least recently used entries until the cache fits into `max_size`.
+    pub fn evict(&self) -> io::Result<()> {
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|(_, meta)| meta.size).sum();
+        if total <= self.options.max_size {
+            return Ok(());
+        }
+
+        entries.sort_by_key(|(_, meta)| meta.last_access);
+        for (meta_path, meta) in entries {
+            if total <= self.options.max_size {
+                break;
+            }
+            remove_if_present(&meta_path)?;
+            remove_if_present(&meta_path.with_extension("body"))?;
+            total = total.saturating_sub(meta.size);
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> io::Result<Vec<(PathBuf, EntryMeta)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(meta) = read_meta(&path)?
+            {
+                entries.push((path, meta));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn entry_paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        (
+            self.dir.join(format!("{}.body", key)),
+            self.dir.join(format!("{}.json", key)),
+        )
+    }
+}
+
+/// Fetches `url` through the cache.
+///
+/// A fresh cached response is returned without touching the network. Otherwise the
+/// URL is fetched and stored; if the request fails, a stale cached copy is returned
+/// when available so the launcher keeps working offline.
+///
+/// # Arguments
+///
+/// * `cache` - The response cache to consult and update.
+/// * `url` - The URL to fetch.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<u8>>` - The response body, or an error if neither the network nor the cache could provide it.
+pub async fn get_cached(cache: &ResponseCache, url: &str) -> io::Result<Vec<u8>> {
+    if let Some(body) = cache.get(url)? {
+        return Ok(body);
+    }
+
+    match super::download_bytes(url, None).await {
+        Ok(body) => {
+            cache.put(url, &body)?;
+            Ok(body)
+        }
+        Err(e) => match cache.get_stale(url)? {
+            Some(entry) => Ok(entry.body),
+            None => Err(e),
+        },
+    }
+}
+
+fn read_meta(path: &Path) -> io::Result<Option<EntryMeta>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes).ok()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn write_atomic(dir: &Path, path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    tmp.write_all(content)?;
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+fn remove_if_present(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn is_cache_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "json" || ext == "body")
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn put_and_get_roundtrip() {
+        let dir = tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path(), CacheOptions::default()).unwrap();
+        cache.put("https://example.com/a.json", b"{}").unwrap();
+        assert_eq!(
+            cache.get("https://example.com/a.json").unwrap().unwrap(),
+            b"{}"
+        );
+        assert!(cache.get("https://example.com/b.json").unwrap().is_none());
+    }
+
+    #[test]
+    fn expired_entries_are_only_returned_as_stale() {
+        let dir = tempdir().unwrap();
+        let options = CacheOptions {
+            ttl: Duration::ZERO,
+            ..CacheOptions::default()
+        };
+        let cache = ResponseCache::new(dir.path(), options).unwrap();
+        cache.put("https://example.com/a.json", b"old").unwrap();
+
+        assert!(cache.get("https://example.com/a.json").unwrap().is_none());
+        let stale = cache
+            .get_stale("https://example.com/a.json")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stale.body, b"old");
+        assert!(!stale.fresh);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entries_over_max_size() {
+        let dir = tempdir().unwrap();
+        let options = CacheOptions {
+            max_size: 10,
+            ..CacheOptions::default()
+        };
+        let cache = ResponseCache::new(dir.path(), options).unwrap();
+        cache.put("https://example.com/a", b"aaaaa").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        cache.put("https://example.com/b", b"bbbbb").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        cache.get("https://example.com/a").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        cache.put("https://example.com/c", b"ccccc").unwrap();
+
+        assert!(cache.get("https://example.com/a").unwrap().is_some());
+        assert!(cache.get("https://example.com/b").unwrap().is_none());
+        assert!(cache.get("https://example.com/c").unwrap().is_some());
+        assert!(cache.size().unwrap() <= 10);
+    }
+
+    #[test]
+    fn remove_and_clear_delete_entries() {
+        let dir = tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path(), CacheOptions::default()).unwrap();
+        cache.put("https://example.com/a", b"a").unwrap();
+        cache.put("https://example.com/b", b"b").unwrap();
+        cache.remove("https://example.com/a").unwrap();
+        assert!(cache.get("https://example.com/a").unwrap().is_none());
+        cache.clear().unwrap();
+        assert_eq!(cache.size().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn get_cached_only_hits_network_once() {
+        let dir = tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path(), CacheOptions::default()).unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/manifest.json");
+            then.status(200).body("{\"latest\":1}");
+        });
+
+        let url = server.url("/manifest.json");
+        let first = get_cached(&cache, &url).await.unwrap();
+        let second = get_cached(&cache, &url).await.unwrap();
+
+        assert_eq!(first, second);
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn get_cached_falls_back_to_stale_entry_on_failure() {
+        let dir = tempdir().unwrap();
+        let options = CacheOptions {
+            ttl: Duration::ZERO,
+            ..CacheOptions::default()
+        };
+        let cache = ResponseCache::new(dir.path(), options).unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/manifest.json");
+            then.status(500);
+        });
+
+        let url = server.url("/manifest.json");
+        assert!(get_cached(&cache, &url).await.is_err());
+        cache.put(&url, b"stale").unwrap();
+        assert_eq!(get_cached(&cache, &url).await.unwrap(), b"stale");
+    }
+}
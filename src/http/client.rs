@@ -0,0 +1,1661 @@
+use super::batch::{DownloadRequest, DownloadStatus};
+use super::cache::ResponseCache;
+use super::compression::{Compression, Decoder, HashingWriter};
+use super::error::HttpError;
+use super::events::{TransferEvent, TransferObserver};
+use super::metrics::{Metrics, host_of};
+use super::mirror::{MirrorRanking, apply_host_overrides};
+use super::tls::TlsOptions;
+use super::{HashSpec, MultiHasher, verify_file_any};
+use futures_util::StreamExt;
+use reqwest::multipart::Form;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tempfile::NamedTempFile;
+
+/// Controls how often and how quickly failed requests are repeated.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry. Doubles for every further attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between two attempts.
+    pub max_backoff: Duration,
+    /// Upper bound for honoring a server's `Retry-After` delay on 429 and 503 responses.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay to wait after the given (1-based) failed attempt.
+    ///
+    /// A `Retry-After` delay requested by the server takes precedence over the
+    /// exponential backoff, capped at `max_retry_after`.
+    pub fn delay_for(&self, attempt: u32, error: &HttpError) -> Duration {
+        match error.retry_after() {
+            Some(delay) => delay.min(self.max_retry_after),
+            None => self.backoff(attempt),
+        }
+    }
+
+    /// Returns the exponential backoff after the given (1-based) failed attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// User-Agent sent when none is configured explicitly.
+pub const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Builds a descriptive User-Agent such as `junco-launcher/1.2.3 (support@example.com)`.
+///
+/// API providers like Modrinth require a User-Agent that identifies the application
+/// and offers a way to contact its developers.
+pub fn user_agent(product: &str, version: &str, contact: &str) -> String {
+    format!("{}/{} ({})", product, version, contact)
+}
+
+/// Options for the shared HTTP client.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// User-Agent sent with every request. Must not be empty.
+    pub user_agent: String,
+    /// Retry behaviour for idempotent requests.
+    pub retry: RetryPolicy,
+    /// Optional response cache consulted by `get_json` and `get_bytes`.
+    pub cache: Option<ResponseCache>,
+    /// Headers attached to every request, e.g. API keys.
+    pub headers: Vec<(String, String)>,
+    /// If true, gzip, deflate and brotli are advertised in `Accept-Encoding` and
+    /// compressed responses are decoded transparently. If false, no
+    /// `Accept-Encoding` is sent unless set through `headers`.
+    pub decompress: bool,
+    /// Optional receiver for request, byte and cache counters.
+    pub metrics: Option<Arc<dyn Metrics>>,
+    /// If true, no requests are sent. Reads are served from the cache (even stale
+    /// entries) and downloads only succeed for files already present and verified;
+    /// everything else fails with `HttpError::Offline`.
+    pub offline: bool,
+    /// Extra trusted roots and certificate pinning.
+    pub tls: TlsOptions,
+    /// Host rewrites applied to every request, e.g. `libraries.minecraft.net` to a
+    /// regional mirror. See [`apply_host_overrides`] for the value format.
+    pub host_overrides: HashMap<String, String>,
+    /// Optional ranking of alternative sources. Requests to a host that is not in
+    /// `host_overrides` go to its fastest measured source, see
+    /// [`HttpClient::probe_mirrors`].
+    pub mirrors: Option<Arc<MirrorRanking>>,
+    /// If true, downloads are written to a `<file>.part` file that is kept when a
+    /// transfer fails, and later attempts continue it with a `Range` request.
+    pub resume_partial: bool,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+            cache: None,
+            headers: Vec::new(),
+            decompress: true,
+            metrics: None,
+            offline: false,
+            tls: TlsOptions::default(),
+            host_overrides: HashMap::new(),
+            mirrors: None,
+            resume_partial: false,
+        }
+    }
+}
+
+/// HTTP client shared by all launcher API interactions.
+///
+/// Wraps a `reqwest::Client` (which pools connections) together with retry and
+/// caching behaviour. Cloning is cheap.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    inner: reqwest::Client,
+    options: ClientOptions,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(ClientOptions::default()).expect("default HTTP client must build")
+    }
+}
+
+impl HttpClient {
+    /// Creates a new client with the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError::Config` if no User-Agent is set or a TLS certificate is
+    /// invalid, or `HttpError` if the underlying client cannot be built.
+    pub fn new(options: ClientOptions) -> Result<Self, HttpError> {
+        if options.user_agent.trim().is_empty() {
+            return Err(HttpError::Config("a User-Agent is required".to_string()));
+        }
+        let builder = reqwest::Client::builder()
+            .user_agent(options.user_agent.as_str())
+            .gzip(options.decompress)
+            .deflate(options.decompress)
+            .brotli(options.decompress);
+        let inner = options.tls.apply(builder)?.build()?;
+        Ok(Self { inner, options })
+    }
+
+    /// Returns the options the client was created with.
+    pub fn options(&self) -> &ClientOptions {
+        &self.options
+    }
+
+    /// Returns the underlying `reqwest` client.
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.inner
+    }
+
+    /// Returns a copy of this client that sends an additional header with every request.
+    ///
+    /// Useful for API keys such as CurseForge's `x-api-key`.
+    pub fn with_header(&self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let mut client = self.clone();
+        client.options.headers.push((name.into(), value.into()));
+        client
+    }
+
+    /// Returns a copy of this client with offline mode switched on or off.
+    pub fn with_offline(&self, offline: bool) -> Self {
+        let mut client = self.clone();
+        client.options.offline = offline;
+        client
+    }
+
+    /// Returns `true` if the client is in offline mode.
+    pub fn is_offline(&self) -> bool {
+        self.options.offline
+    }
+
+    /// Returns a copy of this client that authenticates every request with a bearer token.
+    pub fn with_bearer_token(&self, token: &str) -> Self {
+        self.with_header("Authorization", bearer(token))
+    }
+
+    /// Fetches the body of `url`, retrying transient failures and using the cache if configured.
+    ///
+    /// A fresh cached response is returned without touching the network. If all
+    /// attempts fail, a stale cached copy is returned when available.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if neither the network nor the cache could provide the body.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), err))]
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, HttpError> {
+        self.get_verified_bytes(url, None).await
+    }
+
+    /// [`HttpClient::get_bytes`], checking every body against `expected`.
+    ///
+    /// Cached bodies that do not match are ignored, and a downloaded body is
+    /// only cached once it matches.
+    async fn get_verified_bytes(
+        &self,
+        url: &str,
+        expected: Option<&HashSpec>,
+    ) -> Result<Vec<u8>, HttpError> {
+        let matches = |body: &[u8]| verify_body(body, expected).is_ok();
+        let cache = self.options.cache.as_ref();
+        if let Some(body) = cache.map(|c| c.get(url)).transpose()?.flatten()
+            && matches(&body)
+        {
+            if let Some(metrics) = &self.options.metrics {
+                metrics.cache_hit(url);
+            }
+            return Ok(body);
+        }
+        let stale = || -> Result<_, HttpError> {
+            Ok(cache
+                .map(|c| c.get_stale(url))
+                .transpose()?
+                .flatten()
+                .filter(|entry| matches(&entry.body)))
+        };
+        if self.options.offline {
+            return match stale()? {
+                Some(entry) => Ok(entry.body),
+                None => Err(offline_error(url)),
+            };
+        }
+
+        let fetched = self.with_retry(|| self.fetch_bytes(url)).await;
+        match fetched.and_then(|body| verify_body(&body, expected).map(|()| body)) {
+            Ok(body) => {
+                if let Some(cache) = cache {
+                    cache.put(url, &body)?;
+                }
+                Ok(body)
+            }
+            Err(e) => match stale()? {
+                Some(entry) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, "request failed, using stale cached response");
+                    Ok(entry.body)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Fetches `url` and deserializes the JSON body into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the body is not valid JSON for `T`.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, HttpError> {
+        let body = self.get_bytes(url).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Sends `body` as JSON with a `POST` request and deserializes the JSON response.
+    ///
+    /// `POST` is not idempotent, so the request is never retried. An empty response
+    /// body is deserialized as `null`, which allows `T = ()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is not valid JSON for `T`.
+    pub async fn post_json<B, T>(&self, url: &str, body: &B) -> Result<T, HttpError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let builder = self.request(reqwest::Method::POST, url, &[]).json(body);
+        self.send_for_json(url, builder).await
+    }
+
+    /// Sends `body` as JSON with a `PUT` request and deserializes the JSON response.
+    ///
+    /// `PUT` is idempotent, so transient failures are retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is not valid JSON for `T`.
+    pub async fn put_json<B, T>(&self, url: &str, body: &B) -> Result<T, HttpError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.with_retry(|| {
+            let builder = self.request(reqwest::Method::PUT, url, &[]).json(body);
+            self.send_for_json(url, builder)
+        })
+        .await
+    }
+
+    /// Uploads a multipart form with a `POST` request and deserializes the JSON response.
+    ///
+    /// Used for file uploads such as skins. The request is never retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is not valid JSON for `T`.
+    pub async fn post_multipart<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        form: Form,
+    ) -> Result<T, HttpError> {
+        let builder = self.request(reqwest::Method::POST, url, &[]).multipart(form);
+        self.send_for_json(url, builder).await
+    }
+
+    async fn send_for_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<T, HttpError> {
+        if self.options.offline {
+            return Err(offline_error(url));
+        }
+        let body = self
+            .observed(url, async {
+                let response = builder.send().await?;
+                if !response.status().is_success() {
+                    return Err(status_error(url, &response));
+                }
+                let body = response.bytes().await?;
+                self.record_bytes(url, body.len() as u64);
+                Ok(body)
+            })
+            .await?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(serde_json::from_slice(b"null")?);
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Sends a request once and returns the status code and body, whatever the status.
+    ///
+    /// For APIs such as OAuth that describe their errors in the response body.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request cannot be sent or the body cannot be read.
+    pub(crate) async fn send_raw(
+        &self,
+        url: &str,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<(u16, Vec<u8>), HttpError> {
+        if self.options.offline {
+            return Err(offline_error(url));
+        }
+        self.observed(url, async {
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let body = response.bytes().await?;
+            self.record_bytes(url, body.len() as u64);
+            Ok((status, body.to_vec()))
+        })
+        .await
+    }
+
+    /// Fetches the body of `url` and verifies it against an optional expected hash.
+    ///
+    /// Like [`HttpClient::get_bytes`], but a body that does not match is never
+    /// cached, and cached bodies that do not match are fetched again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the hash does not match.
+    pub async fn download_bytes(
+        &self,
+        url: &str,
+        expected: Option<&HashSpec>,
+    ) -> Result<Vec<u8>, HttpError> {
+        self.get_verified_bytes(url, expected).await
+    }
+
+    /// Downloads a single request to disk.
+    ///
+    /// Existing files are kept when `overwrite` is false and they match the expected
+    /// hash (or no hash is given). Parent directories are created as needed. In
+    /// offline mode a verified existing file is always kept and nothing is downloaded.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DownloadStatus::Downloaded)` with the number of bytes written, or
+    ///   `Ok(DownloadStatus::Skipped)` if the existing file was kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the download or verification fails.
+    pub async fn download(
+        &self,
+        request: &DownloadRequest,
+        overwrite: bool,
+    ) -> Result<DownloadStatus, HttpError> {
+        self.download_with(request, overwrite, None, &|_| {}).await
+    }
+
+    /// Downloads a compressed artifact (e.g. a `.json.gz` index) and decompresses it
+    /// to disk on the fly.
+    ///
+    /// The expected hash of the request, if any, is checked against the
+    /// decompressed output written to `request.path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the download, decompression or verification fails.
+    pub async fn download_decompressed(
+        &self,
+        request: &DownloadRequest,
+        compression: Compression,
+        overwrite: bool,
+    ) -> Result<DownloadStatus, HttpError> {
+        self.download_with(request, overwrite, Some(compression), &|_| {})
+            .await
+    }
+
+    /// Downloads a single request like [`HttpClient::download`], reporting progress,
+    /// retries and verification to `observer`.
+    pub(crate) async fn download_observed(
+        &self,
+        request: &DownloadRequest,
+        overwrite: bool,
+        observer: TransferObserver<'_>,
+    ) -> Result<DownloadStatus, HttpError> {
+        self.download_with(request, overwrite, None, observer).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "download",
+            level = "debug",
+            skip_all,
+            fields(url = %request.url, path = %request.path.display()),
+            err
+        )
+    )]
+    async fn download_with(
+        &self,
+        request: &DownloadRequest,
+        overwrite: bool,
+        compression: Option<Compression>,
+        observer: TransferObserver<'_>,
+    ) -> Result<DownloadStatus, HttpError> {
+        let path = crate::filesystem::expand_home(&request.path.to_string_lossy());
+
+        let keep_existing = path.exists() && (!overwrite || self.options.offline);
+        if keep_existing
+            && request
+                .size
+                .is_none_or(|size| fs::metadata(&path).is_ok_and(|m| m.len() == size))
+            && (request.hashes.is_empty() || verify_file_any(&path, &request.hashes)?)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("existing file verified, skipping download");
+            return Ok(DownloadStatus::Skipped);
+        }
+
+        if self.options.offline {
+            return Err(offline_error(&request.url));
+        }
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        fs::create_dir_all(&dir)?;
+
+        let bytes = self
+            .with_retry_notify(
+                || {
+                    self.observed(
+                        &request.url,
+                        self.download_attempt(request, &path, &dir, compression, observer),
+                    )
+                },
+                |attempt, error| {
+                    observer(TransferEvent::Retrying {
+                        attempt,
+                        error: error.to_string(),
+                    })
+                },
+            )
+            .await?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes, "downloaded");
+        Ok(DownloadStatus::Downloaded { bytes })
+    }
+
+    async fn download_attempt(
+        &self,
+        request: &DownloadRequest,
+        path: &Path,
+        dir: &Path,
+        compression: Option<Compression>,
+        observer: TransferObserver<'_>,
+    ) -> Result<u64, HttpError> {
+        let resumable = self.options.resume_partial && compression.is_none();
+        let part_path = partial_path(path);
+        let offset = if resumable {
+            fs::metadata(&part_path).map_or(0, |m| m.len())
+        } else {
+            0
+        };
+
+        let mut response = self.send_download(request, compression, offset).await?;
+        if offset > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            // The partial file does not belong to the current artifact.
+            fs::remove_file(&part_path)?;
+            response = self.send_download(request, compression, 0).await?;
+        }
+        if !response.status().is_success() {
+            return Err(status_error(&request.url, &response));
+        }
+        let resumed = offset > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        if resumed && content_range_start(&response) != Some(offset) {
+            fs::remove_file(&part_path)?;
+            return Err(HttpError::Truncated {
+                url: request.url.clone(),
+                expected: offset,
+                received: content_range_start(&response).unwrap_or(0),
+            });
+        }
+
+        let expected_len = response.content_length();
+        let resumed_from = if resumed { offset } else { 0 };
+        if let (Some(size), Some(len), None) = (request.size, expected_len, compression)
+            && resumed_from + len != size
+        {
+            if resumed {
+                fs::remove_file(&part_path)?;
+            }
+            return Err(size_mismatch(&request.url, size, resumed_from + len));
+        }
+        let mut hasher = MultiHasher::new(&request.hashes);
+        let staged = if !resumable {
+            // Write next to the destination so the final rename stays on one
+            // filesystem; the temp file is removed if anything below fails.
+            Staged::Temp(NamedTempFile::new_in(dir)?)
+        } else if resumed {
+            hasher.update_from(File::open(&part_path)?)?;
+            Staged::Part(fs::OpenOptions::new().append(true).open(&part_path)?)
+        } else {
+            Staged::Part(File::create(&part_path)?)
+        };
+        let mut decoder = Decoder::new(compression, HashingWriter::new(staged, hasher));
+        let mut received = 0u64;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| body_error(&request.url, expected_len, received, e))?;
+            decoder.write_all(&chunk)?;
+            received += chunk.len() as u64;
+            self.record_bytes(&request.url, chunk.len() as u64);
+            observer(TransferEvent::Progress {
+                received: resumed_from + received,
+                total: expected_len.map(|len| resumed_from + len),
+            });
+        }
+        check_length(&request.url, expected_len, received)?;
+
+        let (staged, hasher, written) = decoder.finish()?.into_parts();
+        let checked = match request.size {
+            Some(size) if resumed_from + written != size => {
+                Err(size_mismatch(&request.url, size, resumed_from + written))
+            }
+            _ => check_hashes(&request.hashes, hasher),
+        };
+        if let Err(e) = checked {
+            drop(staged);
+            if resumable {
+                let _ = fs::remove_file(&part_path);
+            }
+            return Err(e);
+        }
+        if !request.hashes.is_empty() {
+            observer(TransferEvent::Verified);
+        }
+        match staged {
+            Staged::Temp(file) => {
+                file.persist(path).map_err(|e| e.error)?;
+            }
+            Staged::Part(file) => {
+                drop(file);
+                fs::rename(&part_path, path)?;
+            }
+        }
+        Ok(resumed_from + written)
+    }
+
+    /// Sends a download request, asking for the bytes from `offset` on if it is non-zero.
+    async fn send_download(
+        &self,
+        request: &DownloadRequest,
+        compression: Option<Compression>,
+        offset: u64,
+    ) -> Result<reqwest::Response, HttpError> {
+        let mut builder = self.request(reqwest::Method::GET, &request.url, &request.headers);
+        if compression.is_some() || offset > 0 {
+            // Make sure the transport does not decode the artifact itself; byte
+            // ranges also refer to the unencoded representation.
+            builder = builder.header(reqwest::header::ACCEPT_ENCODING, "identity");
+        }
+        if offset > 0 {
+            builder = builder.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        Ok(builder.send().await?)
+    }
+
+    /// Runs `operation` until it succeeds, fails with a non-retryable error, or the
+    /// retry policy is exhausted.
+    pub(crate) async fn with_retry<T, F, Fut>(&self, operation: F) -> Result<T, HttpError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, HttpError>>,
+    {
+        self.with_retry_notify(operation, |_, _| {}).await
+    }
+
+    /// Like `with_retry`, calling `on_retry` with the failed attempt before each retry.
+    pub(crate) async fn with_retry_notify<T, F, Fut, R>(
+        &self,
+        mut operation: F,
+        on_retry: R,
+    ) -> Result<T, HttpError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, HttpError>>,
+        R: Fn(u32, &HttpError),
+    {
+        let policy = &self.options.retry;
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if e.is_retryable() && attempt < policy.max_attempts => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(attempt, error = %e, "request failed, retrying");
+                    on_retry(attempt, &e);
+                    tokio::time::sleep(policy.delay_for(attempt, &e)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Builds a request carrying the client-wide headers followed by `extra_headers`.
+    ///
+    /// Host overrides and mirror selection are applied here, so callers, caches and error messages keep
+    /// using the original URL.
+    pub(crate) fn request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        extra_headers: &[(String, String)],
+    ) -> reqwest::RequestBuilder {
+        let mut url = apply_host_overrides(url, &self.options.host_overrides);
+        if let Some(mirrors) = &self.options.mirrors {
+            url = mirrors.rewrite(&url);
+        }
+        self.request_unrouted(method, &url, extra_headers)
+    }
+
+    /// Builds a request to exactly `url`, without host overrides or mirror selection.
+    pub(crate) fn request_unrouted(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        extra_headers: &[(String, String)],
+    ) -> reqwest::RequestBuilder {
+        let mut builder = self.inner.request(method, url);
+        for (name, value) in self.options.headers.iter().chain(extra_headers) {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder
+    }
+
+    async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, HttpError> {
+        self.observed(url, async {
+            let response = self.request(reqwest::Method::GET, url, &[]).send().await?;
+            if !response.status().is_success() {
+                return Err(status_error(url, &response));
+            }
+            let expected_len = response.content_length();
+            // The length is untrusted, so grow the body as chunks arrive.
+            let mut body = Vec::with_capacity(expected_len.unwrap_or(0).min(64 * 1024) as usize);
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk =
+                    chunk.map_err(|e| body_error(url, expected_len, body.len() as u64, e))?;
+                body.extend_from_slice(&chunk);
+                self.record_bytes(url, chunk.len() as u64);
+            }
+            check_length(url, expected_len, body.len() as u64)?;
+            Ok(body)
+        })
+        .await
+    }
+
+    /// Runs a single request attempt, reporting its start, outcome and duration to
+    /// the configured metrics.
+    pub(crate) async fn observed<T, Fut>(&self, url: &str, attempt: Fut) -> Result<T, HttpError>
+    where
+        Fut: Future<Output = Result<T, HttpError>>,
+    {
+        let Some(metrics) = &self.options.metrics else {
+            return attempt.await;
+        };
+        let host = host_of(url);
+        metrics.request_started(&host);
+        let started = Instant::now();
+        let result = attempt.await;
+        match &result {
+            Ok(_) => metrics.request_finished(&host, started.elapsed()),
+            Err(e) => metrics.request_failed(&host, started.elapsed(), e),
+        }
+        result
+    }
+
+    pub(crate) fn record_bytes(&self, url: &str, bytes: u64) {
+        if let Some(metrics) = &self.options.metrics {
+            metrics.bytes_received(&host_of(url), bytes);
+        }
+    }
+}
+
+/// Where a download is written before it is verified and moved into place.
+enum Staged {
+    /// A temp file that is deleted if the download fails.
+    Temp(NamedTempFile),
+    /// A `.part` file that survives failures so the download can be resumed.
+    Part(File),
+}
+
+impl Write for Staged {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Staged::Temp(file) => file.write(buf),
+            Staged::Part(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Staged::Temp(file) => file.flush(),
+            Staged::Part(file) => file.flush(),
+        }
+    }
+}
+
+/// Returns the path of the partial download for `path`, e.g. `client.jar.part`.
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Returns the first byte position of a `Content-Range: bytes start-end/total` header.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?;
+    let range = value.trim().strip_prefix("bytes")?.trim_start();
+    range.split('-').next()?.trim().parse().ok()
+}
+
+/// Builds an `HttpError::Status` for a failed response, capturing `Retry-After`
+/// on rate-limited (429) and unavailable (503) responses.
+pub(crate) fn status_error(url: &str, response: &reqwest::Response) -> HttpError {
+    let status = response.status().as_u16();
+    let retry_after = if status == 429 || status == 503 {
+        response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after)
+    } else {
+        None
+    };
+    HttpError::Status {
+        url: url.to_string(),
+        status,
+        retry_after,
+    }
+}
+
+/// Parses a `Retry-After` header value given either in seconds or as an HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Fails with `HttpError::Truncated` if fewer bytes than announced were received.
+pub(crate) fn check_length(
+    url: &str,
+    expected: Option<u64>,
+    received: u64,
+) -> Result<(), HttpError> {
+    match expected {
+        Some(expected) if expected != received => Err(HttpError::Truncated {
+            url: url.to_string(),
+            expected,
+            received,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Classifies an error while reading a response body, reporting it as truncation
+/// when the connection broke before the announced length was reached.
+pub(crate) fn body_error(
+    url: &str,
+    expected: Option<u64>,
+    received: u64,
+    error: reqwest::Error,
+) -> HttpError {
+    match expected {
+        Some(expected) if received < expected => HttpError::Truncated {
+            url: url.to_string(),
+            expected,
+            received,
+        },
+        _ => HttpError::from(error),
+    }
+}
+
+pub(crate) fn offline_error(url: &str) -> HttpError {
+    HttpError::Offline {
+        url: url.to_string(),
+    }
+}
+
+/// Formats a bearer token as an `Authorization` header value.
+pub fn bearer(token: &str) -> String {
+    format!("Bearer {}", token)
+}
+
+pub(crate) fn size_mismatch(url: &str, expected: u64, actual: u64) -> HttpError {
+    HttpError::SizeMismatch {
+        url: url.to_string(),
+        expected,
+        actual,
+    }
+}
+
+/// Fails with `HttpError::HashMismatch` unless `body` matches `expected`, if given.
+fn verify_body(body: &[u8], expected: Option<&HashSpec>) -> Result<(), HttpError> {
+    let Some(spec) = expected else {
+        return Ok(());
+    };
+    let specs = std::slice::from_ref(spec);
+    let mut hasher = MultiHasher::new(specs);
+    hasher.update(body);
+    check_hashes(specs, hasher)
+}
+
+/// Fails with `HttpError::HashMismatch` unless one of `specs` matches.
+pub(crate) fn check_hashes(specs: &[HashSpec], hasher: MultiHasher) -> Result<(), HttpError> {
+    match hasher.matches_any(specs) {
+        Ok(_) => Ok(()),
+        Err(digests) if specs.len() == 1 => Err(HttpError::HashMismatch {
+            expected: specs[0].hex.clone(),
+            actual: hex::encode(&digests[0]),
+        }),
+        Err(digests) => Err(HttpError::HashMismatch {
+            expected: join_specs(specs.iter().cloned()),
+            actual: join_specs(
+                specs
+                    .iter()
+                    .zip(digests)
+                    .map(|(spec, digest)| HashSpec::new(spec.algorithm, hex::encode(digest))),
+            ),
+        }),
+    }
+}
+
+fn join_specs(specs: impl Iterator<Item = HashSpec>) -> String {
+    specs.map(|spec| spec.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Fetches `url` with a default client and deserializes the JSON body into `T`.
+///
+/// # Arguments
+///
+/// * `url` - The URL to fetch.
+///
+/// # Returns
+///
+/// * `Result<T, HttpError>` - The deserialized value, or an error if the request or parsing fails.
+pub async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, HttpError> {
+    HttpClient::default().get_json(url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::cache::CacheOptions;
+    use serde::Deserialize;
+    use tempfile::tempdir;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Latest {
+        release: String,
+    }
+
+    fn fast_retry(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            max_retry_after: Duration::from_millis(50),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_json_deserializes_response() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/latest.json");
+            then.status(200).body(r#"{"release":"1.21.1"}"#);
+        });
+
+        let latest: Latest = get_json(&server.url("/latest.json")).await.unwrap();
+        assert_eq!(latest.release, "1.21.1");
+    }
+
+    #[tokio::test]
+    async fn get_json_retries_server_errors() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/latest.json");
+            then.status(503);
+        });
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(3),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let result: Result<Latest, _> = client.get_json(&server.url("/latest.json")).await;
+        assert!(matches!(result, Err(HttpError::Status { status: 503, .. })));
+        mock.assert_hits(3);
+    }
+
+    #[tokio::test]
+    async fn get_json_does_not_retry_client_errors() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/latest.json");
+            then.status(404);
+        });
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(3),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let result: Result<Latest, _> = client.get_json(&server.url("/latest.json")).await;
+        assert!(result.is_err());
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn get_json_returns_parse_error_for_invalid_json() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/latest.json");
+            then.status(200).body("not json");
+        });
+
+        let result: Result<Latest, _> = get_json(&server.url("/latest.json")).await;
+        assert!(matches!(result, Err(HttpError::Json(_))));
+    }
+
+    #[tokio::test]
+    async fn get_json_uses_configured_cache() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/latest.json");
+            then.status(200).body(r#"{"release":"1.20.4"}"#);
+        });
+        let client = HttpClient::new(ClientOptions {
+            cache: Some(ResponseCache::new(dir.path(), CacheOptions::default()).unwrap()),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let url = server.url("/latest.json");
+        let first: Latest = client.get_json(&url).await.unwrap();
+        let second: Latest = client.get_json(&url).await.unwrap();
+        assert_eq!(first, second);
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn download_bytes_caches_only_matching_bodies() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/icon.png");
+            then.status(200).body(b"tampered");
+        });
+        let cache = ResponseCache::new(dir.path(), CacheOptions::default()).unwrap();
+        let client = HttpClient::new(ClientOptions {
+            cache: Some(cache.clone()),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+        let url = server.url("/icon.png");
+        let bad = HashSpec::new(crate::http::HashAlgorithm::Sha1, "0".repeat(40));
+
+        assert!(matches!(
+            client.download_bytes(&url, Some(&bad)).await,
+            Err(HttpError::HashMismatch { .. })
+        ));
+        assert!(cache.get(&url).unwrap().is_none());
+
+        assert_eq!(client.download_bytes(&url, None).await.unwrap(), b"tampered");
+        // The cached body does not match either, so it is fetched again.
+        assert!(client.download_bytes(&url, Some(&bad)).await.is_err());
+        mock.assert_hits(3);
+    }
+
+    #[tokio::test]
+    async fn client_headers_are_sent_with_every_request() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET")
+                .path("/v1/mods")
+                .header("x-api-key", "secret")
+                .header("Authorization", "Bearer token123");
+            then.status(200).body(r#"{"release":"ok"}"#);
+        });
+        let client = HttpClient::default()
+            .with_header("x-api-key", "secret")
+            .with_bearer_token("token123");
+
+        let latest: Latest = client.get_json(&server.url("/v1/mods")).await.unwrap();
+        assert_eq!(latest.release, "ok");
+        mock.assert();
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    struct Report<'a> {
+        summary: &'a str,
+    }
+
+    #[tokio::test]
+    async fn post_json_sends_body_and_parses_response() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("POST")
+                .path("/reports")
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({ "summary": "crash" }));
+            then.status(201).body(r#"{"release":"created"}"#);
+        });
+
+        let created: Latest = HttpClient::default()
+            .post_json(&server.url("/reports"), &Report { summary: "crash" })
+            .await
+            .unwrap();
+        assert_eq!(created.release, "created");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn post_json_is_not_retried() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("POST").path("/reports");
+            then.status(503);
+        });
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(3),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let result: Result<(), _> = client
+            .post_json(&server.url("/reports"), &Report { summary: "x" })
+            .await;
+        assert!(result.is_err());
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn put_json_accepts_empty_response_body() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("PUT").path("/profile/name");
+            then.status(204);
+        });
+
+        let result: () = HttpClient::default()
+            .put_json(&server.url("/profile/name"), &Report { summary: "name" })
+            .await
+            .unwrap();
+        assert_eq!(result, ());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn post_multipart_uploads_form() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("POST")
+                .path("/skins")
+                .body_contains("name=\"variant\"")
+                .body_contains("slim");
+            then.status(200).body(r#"{"release":"uploaded"}"#);
+        });
+        let form = Form::new().text("variant", "slim").part(
+            "file",
+            reqwest::multipart::Part::bytes(vec![1, 2, 3]).file_name("skin.png"),
+        );
+
+        let uploaded: Latest = HttpClient::default()
+            .post_multipart(&server.url("/skins"), form)
+            .await
+            .unwrap();
+        assert_eq!(uploaded.release, "uploaded");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn configured_user_agent_is_sent() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET")
+                .path("/project")
+                .header("user-agent", "junco-launcher/1.2.3 (dev@example.com)");
+            then.status(200).body(r#"{"release":"ok"}"#);
+        });
+        let client = HttpClient::new(ClientOptions {
+            user_agent: user_agent("junco-launcher", "1.2.3", "dev@example.com"),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let _: Latest = client.get_json(&server.url("/project")).await.unwrap();
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn default_user_agent_identifies_the_crate() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET")
+                .path("/project")
+                .header("user-agent", DEFAULT_USER_AGENT);
+            then.status(200).body(r#"{"release":"ok"}"#);
+        });
+
+        let _: Latest = get_json(&server.url("/project")).await.unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn empty_user_agent_is_rejected() {
+        let result = HttpClient::new(ClientOptions {
+            user_agent: " ".to_string(),
+            ..ClientOptions::default()
+        });
+        assert!(matches!(result, Err(HttpError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn with_header_does_not_modify_original_client() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/data").header_exists("x-api-key");
+            then.status(200).body(r#"{"release":"ok"}"#);
+        });
+        let client = HttpClient::default();
+        let _keyed = client.with_header("x-api-key", "secret");
+
+        let result: Result<Latest, _> = client.get_json(&server.url("/data")).await;
+        assert!(result.is_err());
+        mock.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_are_retried_with_retry_after() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/search");
+            then.status(429).header("Retry-After", "0");
+        });
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(4),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let result: Result<Latest, _> = client.get_json(&server.url("/search")).await;
+        match result {
+            Err(HttpError::Status {
+                status,
+                retry_after,
+                ..
+            }) => {
+                assert_eq!(status, 429);
+                assert_eq!(retry_after, Some(Duration::ZERO));
+            }
+            other => panic!("expected rate limit error, got {:?}", other),
+        }
+        mock.assert_hits(4);
+    }
+
+    /// Serves `connections` raw HTTP responses announcing more bytes than they send.
+    fn spawn_truncating_server(connections: usize) -> (String, std::thread::JoinHandle<usize>) {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut served = 0;
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\npartial",
+                );
+                served += 1;
+            }
+            served
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn truncated_download_fails_and_is_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, handle) = spawn_truncating_server(2);
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(2),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let request = DownloadRequest::new(url, dir.path().join("file.bin"));
+        let result = client.download(&request, true).await;
+
+        assert!(matches!(
+            result,
+            Err(HttpError::Truncated { expected: 100, .. })
+        ));
+        assert_eq!(handle.join().unwrap(), 2);
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn gzip_encoded_responses_are_decoded_transparently() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET")
+                .path("/meta.json")
+                .header_exists("accept-encoding");
+            then.status(200)
+                .header("content-encoding", "gzip")
+                .body(gzip(br#"{"release":"1.21"}"#));
+        });
+
+        let latest: Latest = get_json(&server.url("/meta.json")).await.unwrap();
+        assert_eq!(latest.release, "1.21");
+    }
+
+    #[tokio::test]
+    async fn disabled_decompression_returns_raw_body() {
+        let server = httpmock::MockServer::start();
+        let compressed = gzip(b"raw");
+        server.mock(|when, then| {
+            when.method("GET").path("/raw");
+            then.status(200)
+                .header("content-encoding", "gzip")
+                .body(gzip(b"raw"));
+        });
+        let client = HttpClient::new(ClientOptions {
+            decompress: false,
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            client.get_bytes(&server.url("/raw")).await.unwrap(),
+            compressed
+        );
+    }
+
+    #[tokio::test]
+    async fn download_hash_mismatch_keeps_previous_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/client.jar");
+            then.status(200).body("corrupt");
+        });
+        let path = dir.path().join("client.jar");
+        fs::write(&path, b"previous").unwrap();
+
+        let request = DownloadRequest::new(server.url("/client.jar"), &path)
+            .with_hash(HashSpec::new(crate::http::HashAlgorithm::Sha1, "0".repeat(40)));
+        let result = HttpClient::default().download(&request, true).await;
+
+        assert!(matches!(result, Err(HttpError::HashMismatch { .. })));
+        assert_eq!(fs::read(&path).unwrap(), b"previous");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn download_checks_expected_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/client.jar");
+            then.status(200).body("fresh");
+        });
+        let path = dir.path().join("client.jar");
+        fs::write(&path, b"stale data").unwrap();
+
+        let request = DownloadRequest::new(server.url("/client.jar"), &path).with_size(5);
+        let status = HttpClient::default().download(&request, false).await.unwrap();
+        assert_eq!(status, DownloadStatus::Downloaded { bytes: 5 });
+        assert_eq!(fs::read(&path).unwrap(), b"fresh");
+        let status = HttpClient::default().download(&request, false).await.unwrap();
+        assert_eq!(status, DownloadStatus::Skipped);
+        mock.assert_hits(1);
+
+        let wrong = DownloadRequest::new(server.url("/client.jar"), dir.path().join("wrong.jar"))
+            .with_size(6);
+        let result = HttpClient::default().download(&wrong, true).await;
+        assert!(matches!(
+            result,
+            Err(HttpError::SizeMismatch {
+                expected: 6,
+                actual: 5,
+                ..
+            })
+        ));
+        assert!(!dir.path().join("wrong.jar").exists());
+    }
+
+    #[tokio::test]
+    async fn download_into_relative_path_without_parent() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET");
+            then.status(200).body("data");
+        });
+        let name = format!("junco-relative-{}.bin", std::process::id());
+
+        let result = HttpClient::default()
+            .download(&DownloadRequest::new(server.url("/x"), &name), true)
+            .await;
+
+        let written = fs::read(&name);
+        let _ = fs::remove_file(&name);
+        assert_eq!(result.unwrap(), DownloadStatus::Downloaded { bytes: 4 });
+        assert_eq!(written.unwrap(), b"data");
+    }
+
+    #[tokio::test]
+    async fn download_decompressed_writes_and_verifies_decoded_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = br#"{"objects":{}}"#;
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/index.json.gz");
+            then.status(200).body(gzip(content));
+        });
+        let spec = HashSpec::new(
+            crate::http::HashAlgorithm::Sha1,
+            hex::encode(<sha1::Sha1 as sha1::Digest>::digest(content)),
+        );
+        let request =
+            DownloadRequest::new(server.url("/index.json.gz"), dir.path().join("index.json"))
+                .with_hash(spec);
+
+        let status = HttpClient::default()
+            .download_decompressed(&request, Compression::Gzip, true)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            status,
+            DownloadStatus::Downloaded {
+                bytes: content.len() as u64
+            }
+        );
+        assert_eq!(fs::read(dir.path().join("index.json")).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn download_decompressed_fails_on_corrupt_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/index.json.gz");
+            then.status(200).body("definitely not gzip");
+        });
+        let request =
+            DownloadRequest::new(server.url("/index.json.gz"), dir.path().join("index.json"));
+
+        let result = HttpClient::default()
+            .download_decompressed(&request, Compression::Gzip, true)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn check_length_detects_short_reads() {
+        assert!(check_length("u", Some(10), 10).is_ok());
+        assert!(check_length("u", None, 3).is_ok());
+        assert!(matches!(
+            check_length("u", Some(10), 4),
+            Err(HttpError::Truncated {
+                expected: 10,
+                received: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_retry_after_accepts_seconds_and_dates() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let future = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        let delay = parse_retry_after(&future).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn delay_for_prefers_capped_retry_after() {
+        let policy = RetryPolicy {
+            max_retry_after: Duration::from_secs(10),
+            ..RetryPolicy::default()
+        };
+        let limited = HttpError::Status {
+            url: String::new(),
+            status: 429,
+            retry_after: Some(Duration::from_secs(3600)),
+        };
+        let plain = HttpError::Status {
+            url: String::new(),
+            status: 500,
+            retry_after: None,
+        };
+        assert_eq!(policy.delay_for(1, &limited), Duration::from_secs(10));
+        assert_eq!(policy.delay_for(1, &plain), policy.initial_backoff);
+    }
+
+    #[test]
+    fn retry_policy_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn metrics_count_requests_bytes_failures_and_cache_hits() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/ok");
+            then.status(200).body("12345");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/broken");
+            then.status(500);
+        });
+        let cache_dir = tempfile::tempdir().unwrap();
+        let metrics = Arc::new(crate::http::CountingMetrics::new());
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(2),
+            cache: Some(ResponseCache::new(cache_dir.path(), Default::default()).unwrap()),
+            metrics: Some(metrics.clone()),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        client.get_bytes(&server.url("/ok")).await.unwrap();
+        client.get_bytes(&server.url("/ok")).await.unwrap();
+        assert!(client.get_bytes(&server.url("/broken")).await.is_err());
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.failures, 2);
+        assert_eq!(snapshot.bytes, 5);
+        assert_eq!(snapshot.cache_hits, 1);
+        assert_eq!(snapshot.hosts["127.0.0.1"].requests, 3);
+    }
+
+    #[tokio::test]
+    async fn offline_client_serves_cache_and_local_files_only() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET");
+            then.status(200).body("online");
+        });
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(cache_dir.path(), Default::default()).unwrap();
+        cache.put(&server.url("/cached"), b"cached").unwrap();
+        let client = HttpClient::new(ClientOptions {
+            cache: Some(cache),
+            offline: true,
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(client.get_bytes(&server.url("/cached")).await.unwrap(), b"cached");
+        assert!(matches!(
+            client.get_bytes(&server.url("/other")).await,
+            Err(HttpError::Offline { .. })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let present = dir.path().join("present.jar");
+        fs::write(&present, b"local").unwrap();
+        let request = DownloadRequest::new(server.url("/present.jar"), &present);
+        assert_eq!(
+            client.download(&request, true).await.unwrap(),
+            DownloadStatus::Skipped
+        );
+        let missing = DownloadRequest::new(server.url("/missing.jar"), dir.path().join("m.jar"));
+        assert!(matches!(
+            client.download(&missing, false).await,
+            Err(HttpError::Offline { .. })
+        ));
+        let post: Result<(), _> = client.post_json(&server.url("/post"), &()).await;
+        assert!(matches!(post, Err(HttpError::Offline { .. })));
+
+        mock.assert_hits(0);
+        assert!(!client.with_offline(false).is_offline());
+    }
+
+    #[tokio::test]
+    async fn host_overrides_redirect_requests_to_mirror() {
+        let mirror = httpmock::MockServer::start();
+        let mock = mirror.mock(|when, then| {
+            when.method("GET").path("/maven/com/example/lib.jar");
+            then.status(200).body("mirrored");
+        });
+        let client = HttpClient::new(ClientOptions {
+            host_overrides: HashMap::from([(
+                "libraries.minecraft.net".to_string(),
+                mirror.url("/maven"),
+            )]),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+
+        let body = client
+            .get_bytes("https://libraries.minecraft.net/com/example/lib.jar")
+            .await
+            .unwrap();
+
+        assert_eq!(body, b"mirrored");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn resume_partial_continues_with_range_request() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/client.jar", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            use std::io::Read;
+            let mut heads = Vec::new();
+            let responses: [&[u8]; 2] = [
+                b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\nhello",
+                b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 5-9/10\r\nContent-Length: 5\r\nConnection: close\r\n\r\nworld",
+            ];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 2048];
+                let n = stream.read(&mut buf).unwrap();
+                heads.push(String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase());
+                stream.write_all(response).unwrap();
+            }
+            heads
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("client.jar");
+        let client = HttpClient::new(ClientOptions {
+            retry: fast_retry(2),
+            resume_partial: true,
+            ..ClientOptions::default()
+        })
+        .unwrap();
+        let request = DownloadRequest::new(&url, &path).with_hash(HashSpec::new(
+            crate::http::HashAlgorithm::Sha1,
+            "6adfb183a4a2c94a2f92dab5ade762a47889a5a1",
+        ));
+
+        let status = client.download(&request, false).await.unwrap();
+
+        assert_eq!(status, DownloadStatus::Downloaded { bytes: 10 });
+        assert_eq!(fs::read(&path).unwrap(), b"helloworld");
+        assert!(!partial_path(&path).exists());
+        let heads = server.join().unwrap();
+        assert!(!heads[0].contains("range:"));
+        assert!(heads[1].contains("range: bytes=5-"));
+    }
+}
@@ -0,0 +1,221 @@
+use super::MultiHasher;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Compression formats that can be decoded while downloading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// gzip, as used by `.gz` files.
+    Gzip,
+    /// zlib-wrapped deflate, which is what HTTP calls `deflate`.
+    Zlib,
+    /// Raw deflate without any header.
+    Deflate,
+    /// Brotli, as used by `.br` files.
+    Brotli,
+    /// Legacy LZMA ("LZMA alone"), as used by `.lzma` files in Mojang's Java
+    /// runtime manifests.
+    Lzma,
+}
+
+impl Compression {
+    /// Guesses the compression from a file extension such as `.json.gz`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zz" | "zlib" => Some(Compression::Zlib),
+            "deflate" => Some(Compression::Deflate),
+            "br" => Some(Compression::Brotli),
+            "lzma" => Some(Compression::Lzma),
+            _ => None,
+        }
+    }
+
+    /// Parses a `Content-Encoding` header value.
+    pub fn from_content_encoding(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Compression::Gzip),
+            "deflate" => Some(Compression::Zlib),
+            "br" => Some(Compression::Brotli),
+            _ => None,
+        }
+    }
+}
+
+/// A writer that decompresses everything written to it into `W`.
+pub(crate) enum Decoder<W: Write> {
+    Identity(W),
+    Gzip(flate2::write::GzDecoder<W>),
+    Zlib(flate2::write::ZlibDecoder<W>),
+    Deflate(flate2::write::DeflateDecoder<W>),
+    Brotli(Box<brotli::DecompressorWriter<W>>),
+    Lzma(Box<lzma_rs::decompress::Stream<W>>),
+}
+
+impl<W: Write> Decoder<W> {
+    pub(crate) fn new(compression: Option<Compression>, inner: W) -> Self {
+        match compression {
+            None => Decoder::Identity(inner),
+            Some(Compression::Gzip) => Decoder::Gzip(flate2::write::GzDecoder::new(inner)),
+            Some(Compression::Zlib) => Decoder::Zlib(flate2::write::ZlibDecoder::new(inner)),
+            Some(Compression::Deflate) => {
+                Decoder::Deflate(flate2::write::DeflateDecoder::new(inner))
+            }
+            Some(Compression::Brotli) => {
+                Decoder::Brotli(Box::new(brotli::DecompressorWriter::new(inner, 8192)))
+            }
+            Some(Compression::Lzma) => {
+                Decoder::Lzma(Box::new(lzma_rs::decompress::Stream::new(inner)))
+            }
+        }
+    }
+
+    /// Flushes any buffered output and returns the inner writer.
+    pub(crate) fn finish(self) -> io::Result<W> {
+        match self {
+            Decoder::Identity(w) => Ok(w),
+            Decoder::Gzip(d) => d.finish(),
+            Decoder::Zlib(d) => d.finish(),
+            Decoder::Deflate(d) => d.finish(),
+            Decoder::Brotli(mut d) => {
+                d.close()?;
+                d.into_inner().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "incomplete brotli stream")
+                })
+            }
+            Decoder::Lzma(d) => d
+                .finish()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e))),
+        }
+    }
+}
+
+impl<W: Write> Write for Decoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Decoder::Identity(w) => w.write(buf),
+            Decoder::Gzip(d) => d.write(buf),
+            Decoder::Zlib(d) => d.write(buf),
+            Decoder::Deflate(d) => d.write(buf),
+            Decoder::Brotli(d) => d.write(buf),
+            Decoder::Lzma(d) => d.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Decoder::Identity(w) => w.flush(),
+            Decoder::Gzip(d) => d.flush(),
+            Decoder::Zlib(d) => d.flush(),
+            Decoder::Deflate(d) => d.flush(),
+            Decoder::Brotli(d) => d.flush(),
+            Decoder::Lzma(d) => d.flush(),
+        }
+    }
+}
+
+/// A writer that hashes and counts everything passing through it.
+pub(crate) struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: MultiHasher,
+    written: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub(crate) fn new(inner: W, hasher: MultiHasher) -> Self {
+        Self {
+            inner,
+            hasher,
+            written: 0,
+        }
+    }
+
+    /// Returns the inner writer, the hasher and the number of bytes written.
+    pub(crate) fn into_parts(self) -> (W, MultiHasher, u64) {
+        (self.inner, self.hasher, self.written)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn detects_compression_from_path_and_header() {
+        assert_eq!(
+            Compression::from_path("index.json.gz"),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::from_path("index.json.br"),
+            Some(Compression::Brotli)
+        );
+        assert_eq!(Compression::from_path("index.json"), None);
+        assert_eq!(
+            Compression::from_content_encoding("deflate"),
+            Some(Compression::Zlib)
+        );
+        assert_eq!(Compression::from_content_encoding("identity"), None);
+    }
+
+    #[test]
+    fn decoder_decompresses_gzip_in_chunks() {
+        let compressed = gzip(b"hello hello hello");
+        let mut decoder = Decoder::new(Some(Compression::Gzip), Vec::new());
+        for chunk in compressed.chunks(3) {
+            decoder.write_all(chunk).unwrap();
+        }
+        assert_eq!(decoder.finish().unwrap(), b"hello hello hello");
+    }
+
+    #[test]
+    fn decoder_decompresses_brotli() {
+        let mut compressed = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 5, 22);
+            writer.write_all(b"brotli payload").unwrap();
+        }
+        let mut decoder = Decoder::new(Some(Compression::Brotli), Vec::new());
+        decoder.write_all(&compressed).unwrap();
+        assert_eq!(decoder.finish().unwrap(), b"brotli payload");
+    }
+
+    #[test]
+    fn decoder_decompresses_lzma_in_chunks() {
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut &b"lzma payload"[..], &mut compressed).unwrap();
+        let mut decoder = Decoder::new(Compression::from_path("java.dll.lzma"), Vec::new());
+        for chunk in compressed.chunks(5) {
+            decoder.write_all(chunk).unwrap();
+        }
+        assert_eq!(decoder.finish().unwrap(), b"lzma payload");
+    }
+
+    #[test]
+    fn hashing_writer_counts_and_hashes() {
+        let mut writer = HashingWriter::new(Vec::new(), MultiHasher::new(&[]));
+        writer.write_all(b"abc").unwrap();
+        let (inner, _, written) = writer.into_parts();
+        assert_eq!(inner, b"abc");
+        assert_eq!(written, 3);
+    }
+}
@@ -0,0 +1,274 @@
+use std::error::Error as _;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The kind of a transport-level failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkErrorKind {
+    /// The request or the connection attempt timed out.
+    Timeout,
+    /// The server actively refused the connection.
+    ConnectionRefused,
+    /// The connection was reset or closed unexpectedly.
+    ConnectionReset,
+    /// The host name could not be resolved.
+    Dns,
+    /// The TLS handshake failed, e.g. because of an untrusted certificate.
+    Tls,
+    /// The connection could not be established for another reason.
+    Connect,
+}
+
+impl NetworkErrorKind {
+    /// Returns a short hint for the user on how to fix the problem.
+    pub fn hint(self) -> &'static str {
+        match self {
+            NetworkErrorKind::Timeout => "the server took too long to respond; try again later",
+            NetworkErrorKind::ConnectionRefused => {
+                "the server refused the connection; check your firewall or proxy settings"
+            }
+            NetworkErrorKind::ConnectionReset => {
+                "the connection was interrupted; check your network connection"
+            }
+            NetworkErrorKind::Dns => {
+                "the server name could not be resolved; check your internet connection and DNS settings"
+            }
+            NetworkErrorKind::Tls => {
+                "a secure connection could not be established; check your system clock, antivirus or proxy certificates"
+            }
+            NetworkErrorKind::Connect => {
+                "the server could not be reached; check your firewall or proxy settings"
+            }
+        }
+    }
+
+    /// Classifies a `reqwest` error by inspecting its source chain.
+    ///
+    /// Returns `None` for errors that are not transport failures, such as invalid URLs.
+    pub fn classify(error: &reqwest::Error) -> Option<Self> {
+        if error.is_timeout() {
+            return Some(NetworkErrorKind::Timeout);
+        }
+        let mut source = error.source();
+        while let Some(cause) = source {
+            if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+                match io_error.kind() {
+                    io::ErrorKind::TimedOut => return Some(NetworkErrorKind::Timeout),
+                    io::ErrorKind::ConnectionRefused => {
+                        return Some(NetworkErrorKind::ConnectionRefused);
+                    }
+                    io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof => {
+                        return Some(NetworkErrorKind::ConnectionReset);
+                    }
+                    _ => {}
+                }
+            }
+            let message = cause.to_string().to_ascii_lowercase();
+            if message.contains("dns error") || message.contains("failed to lookup address") {
+                return Some(NetworkErrorKind::Dns);
+            }
+            if ["certificate", "tls", "ssl", "handshake"]
+                .iter()
+                .any(|needle| message.contains(needle))
+            {
+                return Some(NetworkErrorKind::Tls);
+            }
+            source = cause.source();
+        }
+        error.is_connect().then_some(NetworkErrorKind::Connect)
+    }
+}
+
+impl fmt::Display for NetworkErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NetworkErrorKind::Timeout => "timed out",
+            NetworkErrorKind::ConnectionRefused => "connection refused",
+            NetworkErrorKind::ConnectionReset => "connection reset",
+            NetworkErrorKind::Dns => "DNS lookup failed",
+            NetworkErrorKind::Tls => "TLS handshake failed",
+            NetworkErrorKind::Connect => "connection failed",
+        })
+    }
+}
+
+/// Represents errors that can occur while talking to HTTP endpoints.
+#[derive(Debug, Error)]
+pub enum HttpError {
+    /// Wrapper for standard IO errors.
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    /// The request could not be sent or the response could not be read.
+    #[error("http error: {0}")]
+    Request(reqwest::Error),
+    /// A transport-level failure such as a timeout or a refused connection.
+    #[error("network error ({kind}): {source}")]
+    Network {
+        /// What went wrong.
+        kind: NetworkErrorKind,
+        /// The underlying error.
+        source: reqwest::Error,
+    },
+    /// The server answered with a non-success status code.
+    #[error("request to {url} failed: status code {status}")]
+    Status {
+        /// The requested URL.
+        url: String,
+        /// The HTTP status code returned by the server.
+        status: u16,
+        /// Delay requested by the server through a `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+    /// The response body could not be deserialized.
+    #[error("failed to parse JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The connection ended before the announced `Content-Length` was received.
+    #[error("truncated response from {url}: received {received} of {expected} bytes")]
+    Truncated {
+        /// The requested URL.
+        url: String,
+        /// The length announced by the server.
+        expected: u64,
+        /// The number of bytes actually received.
+        received: u64,
+    },
+    /// The client was configured incorrectly.
+    #[error("invalid client configuration: {0}")]
+    Config(String),
+    /// The downloaded content did not match the expected hash.
+    #[error("hash mismatch: got {actual}, want {expected}")]
+    HashMismatch {
+        /// The expected hex digest.
+        expected: String,
+        /// The hex digest of the received content.
+        actual: String,
+    },
+    /// The downloaded content did not have the expected size.
+    #[error("size mismatch for {url}: got {actual} bytes, want {expected}")]
+    SizeMismatch {
+        /// The requested URL.
+        url: String,
+        /// The expected size in bytes.
+        expected: u64,
+        /// The size of the received content.
+        actual: u64,
+    },
+    /// The client is in offline mode and the resource is neither cached nor present on disk.
+    #[error("offline: {url} is not available locally")]
+    Offline {
+        /// The requested URL.
+        url: String,
+    },
+}
+
+impl HttpError {
+    /// Returns `true` if repeating the request may succeed.
+    ///
+    /// Server errors, timeouts, rate limiting, truncated transfers and connection
+    /// failures are retryable; TLS failures, client errors, hash mismatches and parse
+    /// failures are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            HttpError::Request(e) => e.is_body(),
+            HttpError::Network { kind, .. } => *kind != NetworkErrorKind::Tls,
+            HttpError::Status { status, .. } => *status >= 500 || *status == 408 || *status == 429,
+            HttpError::Truncated { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the kind of network failure, if this is one.
+    pub fn network_kind(&self) -> Option<NetworkErrorKind> {
+        match self {
+            HttpError::Network { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Returns the delay the server asked for before retrying, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            HttpError::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(error: reqwest::Error) -> Self {
+        match NetworkErrorKind::classify(&error) {
+            Some(kind) => HttpError::Network {
+                kind,
+                source: error,
+            },
+            None => HttpError::Request(error),
+        }
+    }
+}
+
+impl From<HttpError> for io::Error {
+    fn from(error: HttpError) -> Self {
+        match error {
+            HttpError::Io(e) => e,
+            other => io::Error::other(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn fetch_error(url: &str) -> HttpError {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        HttpError::from(client.get(url).send().await.unwrap_err())
+    }
+
+    #[tokio::test]
+    async fn classifies_connection_refused() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let error = fetch_error(&format!("http://{}/", addr)).await;
+
+        assert_eq!(
+            error.network_kind(),
+            Some(NetworkErrorKind::ConnectionRefused)
+        );
+        assert!(error.is_retryable());
+        assert!(error.to_string().contains("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn classifies_tls_failures_as_not_retryable() {
+        let server = httpmock::MockServer::start();
+        let url = server.url("/").replace("http://", "https://");
+
+        let error = fetch_error(&url).await;
+
+        assert_eq!(error.network_kind(), Some(NetworkErrorKind::Tls));
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn classifies_dns_failures() {
+        let error = fetch_error("http://junco-launcher.invalid/").await;
+        assert_eq!(error.network_kind(), Some(NetworkErrorKind::Dns));
+    }
+
+    #[tokio::test]
+    async fn invalid_urls_are_not_network_errors() {
+        let error = fetch_error("http://").await;
+        assert!(matches!(error, HttpError::Request(_)));
+        assert!(error.network_kind().is_none());
+    }
+}
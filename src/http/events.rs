@@ -0,0 +1,103 @@
+use super::batch::DownloadStatus;
+use std::path::PathBuf;
+
+/// Progress of a single download, as reported by a `DownloadManager`.
+///
+/// Every event carries the id the manager assigned when the request was queued,
+/// so a GUI can keep one row per file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadEvent {
+    /// The request was added to the queue.
+    Queued {
+        /// Id of the download.
+        id: u64,
+        /// The requested URL.
+        url: String,
+        /// The destination path.
+        path: PathBuf,
+    },
+    /// A worker picked up the request.
+    Started {
+        /// Id of the download.
+        id: u64,
+    },
+    /// Bytes were received.
+    Progress {
+        /// Id of the download.
+        id: u64,
+        /// Bytes of the file received so far, including resumed data.
+        received: u64,
+        /// Total size of the file, if the server announced it.
+        total: Option<u64>,
+    },
+    /// An attempt failed and the download will be tried again.
+    Retrying {
+        /// Id of the download.
+        id: u64,
+        /// The 1-based attempt that failed.
+        attempt: u32,
+        /// Description of the failure.
+        error: String,
+    },
+    /// The downloaded data matched the expected hash.
+    Verified {
+        /// Id of the download.
+        id: u64,
+    },
+    /// The download finished, either by downloading or by keeping an existing file.
+    Completed {
+        /// Id of the download.
+        id: u64,
+        /// `Downloaded` or `Skipped`.
+        status: DownloadStatus,
+    },
+    /// The download failed for good.
+    Failed {
+        /// Id of the download.
+        id: u64,
+        /// Description of the failure.
+        reason: String,
+    },
+}
+
+impl DownloadEvent {
+    /// Returns the id of the download the event belongs to.
+    pub fn id(&self) -> u64 {
+        match self {
+            DownloadEvent::Queued { id, .. }
+            | DownloadEvent::Started { id }
+            | DownloadEvent::Progress { id, .. }
+            | DownloadEvent::Retrying { id, .. }
+            | DownloadEvent::Verified { id }
+            | DownloadEvent::Completed { id, .. }
+            | DownloadEvent::Failed { id, .. } => *id,
+        }
+    }
+}
+
+/// Events produced inside a single transfer, before the manager attaches an id.
+#[derive(Debug, Clone)]
+pub(crate) enum TransferEvent {
+    Progress { received: u64, total: Option<u64> },
+    Retrying { attempt: u32, error: String },
+    Verified,
+}
+
+impl TransferEvent {
+    pub(crate) fn with_id(self, id: u64) -> DownloadEvent {
+        match self {
+            TransferEvent::Progress { received, total } => DownloadEvent::Progress {
+                id,
+                received,
+                total,
+            },
+            TransferEvent::Retrying { attempt, error } => {
+                DownloadEvent::Retrying { id, attempt, error }
+            }
+            TransferEvent::Verified => DownloadEvent::Verified { id },
+        }
+    }
+}
+
+/// Receives transfer events; `&|_| {}` when nobody listens.
+pub(crate) type TransferObserver<'a> = &'a (dyn Fn(TransferEvent) + Send + Sync);
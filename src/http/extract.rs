@@ -0,0 +1,422 @@
+use super::client::{
+    HttpClient, body_error, check_hashes, check_length, offline_error, status_error,
+};
+use super::error::HttpError;
+use super::{HashSpec, MultiHasher};
+use futures_util::StreamExt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+
+/// Archive formats that can be extracted while they are downloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveFormat {
+    /// A zip archive, read entry by entry from the local file headers.
+    Zip,
+    /// An uncompressed tar archive.
+    Tar,
+    /// A gzip-compressed tar archive.
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Guesses the format from a file name or URL path such as `jdk.tar.gz`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".zip") || name.ends_with(".jar") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Summary of a streamed extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractReport {
+    /// Size of the downloaded archive.
+    pub archive_bytes: u64,
+    /// Number of files and directories extracted.
+    pub entries: usize,
+}
+
+impl HttpClient {
+    /// Downloads an archive and extracts it into `dest` without storing the archive.
+    ///
+    /// Entries are unpacked into a staging directory next to `dest` while the
+    /// archive is hashed. Only after the transfer and the hash check succeed is the
+    /// staging directory moved to `dest`, replacing any previous contents. Zip
+    /// archives are read sequentially, so Unix permissions stored in the central
+    /// directory are not applied; tar archives keep their modes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the download fails, the archive is malformed, an
+    /// entry would escape `dest`, or the archive does not match `expected`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, expected), fields(dest = %dest.display()), err)
+    )]
+    pub async fn download_and_extract(
+        &self,
+        url: &str,
+        expected: Option<&HashSpec>,
+        format: ArchiveFormat,
+        dest: &Path,
+    ) -> Result<ExtractReport, HttpError> {
+        if self.is_offline() {
+            return Err(offline_error(url));
+        }
+        let dest = crate::filesystem::expand_home(&dest.to_string_lossy());
+        let parent = match dest.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        fs::create_dir_all(&parent)?;
+
+        let (staging, report) = self
+            .with_retry(|| self.observed(url, self.extract_attempt(url, expected, format, &parent)))
+            .await?;
+
+        if dest.exists() {
+            fs::remove_dir_all(&dest)?;
+        }
+        let staging = staging.keep();
+        if let Err(e) = fs::rename(&staging, &dest) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e.into());
+        }
+        Ok(report)
+    }
+
+    async fn extract_attempt(
+        &self,
+        url: &str,
+        expected: Option<&HashSpec>,
+        format: ArchiveFormat,
+        parent: &Path,
+    ) -> Result<(TempDir, ExtractReport), HttpError> {
+        let staging = tempfile::Builder::new()
+            .prefix(".extract-")
+            .tempdir_in(parent)?;
+        let response = self
+            .request(reqwest::Method::GET, url, &[])
+            .header(reqwest::header::ACCEPT_ENCODING, "identity")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(status_error(url, &response));
+        }
+        let expected_len = response.content_length();
+
+        // The extractors are synchronous, so they run on the blocking pool and
+        // receive the body through a channel.
+        let (tx, rx) = mpsc::channel(16);
+        let specs = expected.map_or(&[][..], std::slice::from_ref);
+        let hasher = MultiHasher::new(specs);
+        let target = staging.path().to_path_buf();
+        let extractor = tokio::task::spawn_blocking(move || {
+            let mut reader = HashingReader {
+                inner: ChannelReader::new(rx),
+                hasher,
+            };
+            let entries = extract_archive(format, &mut reader, &target)?;
+            // Hash trailing data the extractor did not need, such as the zip central directory.
+            io::copy(&mut reader, &mut io::sink())?;
+            Ok::<_, io::Error>((entries, reader.hasher))
+        });
+
+        let mut received = 0u64;
+        let mut transfer = Ok(());
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    received += chunk.len() as u64;
+                    self.record_bytes(url, chunk.len() as u64);
+                    if tx.send(Ok(chunk)).await.is_err() {
+                        // The extractor stopped early; its error is reported below.
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(io::Error::other("download interrupted"))).await;
+                    transfer = Err(body_error(url, expected_len, received, e));
+                    break;
+                }
+            }
+        }
+        drop(tx);
+
+        let extracted = extractor.await.map_err(io::Error::other)?;
+        transfer?;
+        let (entries, hasher) = extracted?;
+        check_length(url, expected_len, received)?;
+        check_hashes(specs, hasher)?;
+
+        Ok((
+            staging,
+            ExtractReport {
+                archive_bytes: received,
+                entries,
+            },
+        ))
+    }
+}
+
+/// Extracts an archive read from `reader` into `target`.
+fn extract_archive<R: Read>(
+    format: ArchiveFormat,
+    reader: &mut R,
+    target: &Path,
+) -> io::Result<usize> {
+    match format {
+        ArchiveFormat::Tar => unpack_tar(tar::Archive::new(reader), target),
+        ArchiveFormat::TarGz => unpack_tar(
+            tar::Archive::new(flate2::read::GzDecoder::new(reader)),
+            target,
+        ),
+        ArchiveFormat::Zip => {
+            let mut entries = 0;
+            while let Some(mut file) = zip::read::read_zipfile_from_stream(reader)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            {
+                let name = file
+                    .enclosed_name()
+                    .ok_or_else(|| unsafe_entry(file.name()))?;
+                let path = target.join(name);
+                if file.is_dir() {
+                    fs::create_dir_all(&path)?;
+                } else {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    io::copy(&mut file, &mut File::create(&path)?)?;
+                }
+                entries += 1;
+            }
+            Ok(entries)
+        }
+    }
+}
+
+fn unpack_tar<R: Read>(mut archive: tar::Archive<R>, target: &Path) -> io::Result<usize> {
+    let mut entries = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.unpack_in(target)? {
+            return Err(unsafe_entry(&entry.path()?.to_string_lossy()));
+        }
+        entries += 1;
+    }
+    Ok(entries)
+}
+
+fn unsafe_entry(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("archive entry escapes the destination: {}", name),
+    )
+}
+
+/// A blocking reader over chunks sent from an async task.
+struct ChannelReader<B> {
+    rx: mpsc::Receiver<io::Result<B>>,
+    current: B,
+    pos: usize,
+}
+
+impl<B: AsRef<[u8]> + Default> ChannelReader<B> {
+    fn new(rx: mpsc::Receiver<io::Result<B>>) -> Self {
+        Self {
+            rx,
+            current: B::default(),
+            pos: 0,
+        }
+    }
+}
+
+impl<B: AsRef<[u8]> + Default> Read for ChannelReader<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.current.as_ref().len() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => {
+                    self.current = chunk?;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let available = &self.current.as_ref()[self.pos..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// A reader that hashes everything read through it.
+struct HashingReader<R> {
+    inner: R,
+    hasher: MultiHasher,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HashAlgorithm;
+    use sha1::{Digest, Sha1};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        for (name, data) in files {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn serve(body: Vec<u8>) -> httpmock::MockServer {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET");
+            then.status(200).body(body);
+        });
+        server
+    }
+
+    #[test]
+    fn detects_archive_format() {
+        assert_eq!(
+            ArchiveFormat::from_path("OpenJDK21-jre_x64_linux.tar.gz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path("natives.JAR"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(ArchiveFormat::from_path("index.json"), None);
+    }
+
+    #[tokio::test]
+    async fn extracts_tar_gz_and_verifies_archive_hash() {
+        let archive = tar_gz(&[("jre/bin/java", b"#!java"), ("jre/release", b"21")]);
+        let hash = HashSpec::new(HashAlgorithm::Sha1, hex::encode(Sha1::digest(&archive)));
+        let server = serve(archive.clone());
+        let dir = tempdir().unwrap();
+        let dest = dir.path().join("runtime");
+
+        let report = HttpClient::default()
+            .download_and_extract(
+                &server.url("/jre.tar.gz"),
+                Some(&hash),
+                ArchiveFormat::TarGz,
+                &dest,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.entries, 2);
+        assert_eq!(report.archive_bytes, archive.len() as u64);
+        assert_eq!(fs::read(dest.join("jre/bin/java")).unwrap(), b"#!java");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dest.join("jre/bin/java"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_ne!(mode & 0o111, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn extracts_zip_replacing_previous_contents() {
+        let archive = zip(&[("lwjgl.so", b"native"), ("META-INF/MANIFEST.MF", b"m")]);
+        let server = serve(archive);
+        let dir = tempdir().unwrap();
+        let dest = dir.path().join("natives");
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("stale.so"), b"old").unwrap();
+
+        let report = HttpClient::default()
+            .download_and_extract(&server.url("/natives.jar"), None, ArchiveFormat::Zip, &dest)
+            .await
+            .unwrap();
+
+        assert_eq!(report.entries, 2);
+        assert_eq!(fs::read(dest.join("lwjgl.so")).unwrap(), b"native");
+        assert!(!dest.join("stale.so").exists());
+    }
+
+    #[tokio::test]
+    async fn hash_mismatch_leaves_destination_untouched() {
+        let server = serve(tar_gz(&[("a.txt", b"a")]));
+        let dir = tempdir().unwrap();
+        let dest = dir.path().join("runtime");
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("keep.txt"), b"keep").unwrap();
+
+        let result = HttpClient::default()
+            .download_and_extract(
+                &server.url("/jre.tar.gz"),
+                Some(&HashSpec::new(HashAlgorithm::Sha1, "0".repeat(40))),
+                ArchiveFormat::TarGz,
+                &dest,
+            )
+            .await;
+
+        assert!(matches!(result, Err(HttpError::HashMismatch { .. })));
+        assert_eq!(fs::read(dest.join("keep.txt")).unwrap(), b"keep");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn malformed_archive_is_reported() {
+        let server = serve(b"definitely not a zip file".to_vec());
+        let dir = tempdir().unwrap();
+
+        let result = HttpClient::default()
+            .download_and_extract(
+                &server.url("/x.zip"),
+                None,
+                ArchiveFormat::Zip,
+                &dir.path().join("x"),
+            )
+            .await;
+
+        assert!(matches!(result, Err(HttpError::Io(_))));
+        assert!(!dir.path().join("x").exists());
+    }
+}
@@ -0,0 +1,683 @@
+use super::batch::{
+    BatchOptions, DownloadOutcome, DownloadReport, DownloadRequest, DownloadStatus,
+};
+use super::client::HttpClient;
+use super::events::{DownloadEvent, TransferEvent};
+use super::session::DownloadSession;
+use super::verify_file_any;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{OnceCell, mpsc};
+
+/// Priority of a download. Higher priorities are started first.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Speculative prefetching that nobody is waiting for.
+    Background,
+    /// Work that is needed eventually, such as assets.
+    Low,
+    /// The default priority.
+    #[default]
+    Normal,
+    /// Files the user is actively waiting for.
+    High,
+    /// Files required before the game can start, such as the client jar and libraries.
+    Critical,
+}
+
+#[derive(Debug)]
+struct Pending {
+    priority: Priority,
+    seq: u64,
+    request: DownloadRequest,
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+/// Downloads currently in progress, keyed by URL and destination.
+type InFlight = HashMap<(String, PathBuf), Arc<OnceCell<DownloadStatus>>>;
+
+/// Checks a file downloaded by an identical request against the size and
+/// hashes of `request`.
+async fn verify_shared(request: &DownloadRequest, path: PathBuf) -> DownloadStatus {
+    let size = request.size;
+    let hashes = request.hashes.clone();
+    let verified = tokio::task::spawn_blocking(move || -> io::Result<bool> {
+        let len = fs::metadata(&path)?.len();
+        if size.is_some_and(|size| size != len) {
+            return Ok(false);
+        }
+        Ok(hashes.is_empty() || verify_file_any(&path, &hashes)?)
+    })
+    .await
+    .map_err(io::Error::other)
+    .and_then(|verified| verified);
+    match verified {
+        Ok(true) => DownloadStatus::Skipped,
+        Ok(false) => DownloadStatus::Failed {
+            reason:
+                "file downloaded by a concurrent request does not match the expected size or hash"
+                    .to_string(),
+        },
+        Err(e) => DownloadStatus::Failed {
+            reason: e.to_string(),
+        },
+    }
+}
+
+/// A prioritized download queue.
+///
+/// Requests are started in priority order (FIFO within the same priority). Requests
+/// enqueued while [`DownloadManager::run`] is in progress are picked up by the
+/// running workers, so urgent work can jump ahead of queued background downloads.
+///
+/// Requests for the same URL and destination that run at the same time share a
+/// single transfer, verified against the hashes of the request that started it.
+/// The others check the finished file against their own size and hashes and
+/// report [`DownloadStatus::Skipped`], or a failure on a mismatch or when the
+/// transfer failed.
+#[derive(Debug)]
+pub struct DownloadManager {
+    client: HttpClient,
+    options: BatchOptions,
+    queue: Mutex<BinaryHeap<Pending>>,
+    next_seq: AtomicU64,
+    session: Option<DownloadSession>,
+    subscribers: Mutex<Vec<mpsc::UnboundedSender<DownloadEvent>>>,
+    in_flight: Mutex<InFlight>,
+}
+
+impl DownloadManager {
+    /// Creates an empty manager that downloads through `client`.
+    pub fn new(client: HttpClient, options: BatchOptions) -> Self {
+        Self {
+            client,
+            options,
+            queue: Mutex::new(BinaryHeap::new()),
+            next_seq: AtomicU64::new(0),
+            session: None,
+            subscribers: Mutex::new(Vec::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a manager whose queue is persisted in the session journal at `path`.
+    ///
+    /// Requests left unfinished by a previous run (including failed ones) are queued
+    /// again. Combined with `ClientOptions::resume_partial`, partially downloaded
+    /// files continue where they stopped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be opened.
+    pub fn with_session<P: AsRef<Path>>(
+        client: HttpClient,
+        options: BatchOptions,
+        path: P,
+    ) -> io::Result<Self> {
+        let (session, restored) = DownloadSession::open(path)?;
+        let manager = Self {
+            session: Some(session),
+            ..Self::new(client, options)
+        };
+        // The compacted journal already lists the restored requests under these ids.
+        for request in restored {
+            manager.push(request);
+        }
+        Ok(manager)
+    }
+
+    /// Returns the session journal, if the manager has one.
+    pub fn session(&self) -> Option<&DownloadSession> {
+        self.session.as_ref()
+    }
+
+    /// Subscribes to the events of every download handled by this manager.
+    ///
+    /// Events are only delivered for requests queued after subscribing, so subscribe
+    /// before calling [`DownloadManager::enqueue`]. Requests restored from a session
+    /// start with `Started` instead of `Queued`. Dropping the receiver unsubscribes.
+    ///
+    /// # Returns
+    ///
+    /// * `mpsc::UnboundedReceiver<DownloadEvent>` - The event stream. It ends when the
+    ///   manager is dropped.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<DownloadEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lock_subscribers().push(tx);
+        rx
+    }
+
+    /// Adds a request to the queue using the request's priority.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be recorded in the session journal.
+    /// The request is not queued in that case.
+    pub fn enqueue(&self, request: DownloadRequest) -> io::Result<()> {
+        let seq = self.next_seq.fetch_add(1, AtomicOrdering::Relaxed);
+        if let Some(session) = &self.session {
+            session.record_add(seq, &request)?;
+        }
+        self.push_with_seq(seq, request);
+        Ok(())
+    }
+
+    /// Adds several requests to the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request cannot be recorded in the session journal.
+    pub fn enqueue_all<I: IntoIterator<Item = DownloadRequest>>(
+        &self,
+        requests: I,
+    ) -> io::Result<()> {
+        for request in requests {
+            self.enqueue(request)?;
+        }
+        Ok(())
+    }
+
+    /// Queues a request without recording it in the session journal.
+    pub(crate) fn push(&self, request: DownloadRequest) {
+        let seq = self.next_seq.fetch_add(1, AtomicOrdering::Relaxed);
+        self.push_with_seq(seq, request);
+    }
+
+    fn push_with_seq(&self, seq: u64, request: DownloadRequest) {
+        self.emit(DownloadEvent::Queued {
+            id: seq,
+            url: request.url.clone(),
+            path: request.path.clone(),
+        });
+        let pending = Pending {
+            priority: request.priority,
+            seq,
+            request,
+        };
+        self.lock_queue().push(pending);
+    }
+
+    /// Returns the number of requests waiting to be started.
+    pub fn len(&self) -> usize {
+        self.lock_queue().len()
+    }
+
+    /// Returns `true` if no requests are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the waiting requests in the order they will be started.
+    pub fn pending(&self) -> Vec<DownloadRequest> {
+        let queue = self.lock_queue();
+        let mut pending: Vec<&Pending> = queue.iter().collect();
+        pending.sort_by(|a, b| b.cmp(a));
+        pending.into_iter().map(|p| p.request.clone()).collect()
+    }
+
+    /// Downloads queued requests until the queue is empty.
+    ///
+    /// Outcomes in the returned report are ordered by the time the requests were
+    /// enqueued. With a session, finished requests are removed from the journal and
+    /// it is emptied once everything succeeded; failed requests stay in it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "download_batch", skip_all, fields(queued = self.len()))
+    )]
+    pub async fn run(&self) -> DownloadReport {
+        let started = Instant::now();
+        let workers = (0..self.options.concurrency.max(1)).map(|_| self.worker());
+        let mut outcomes: Vec<(u64, DownloadOutcome)> = futures_util::future::join_all(workers)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        outcomes.sort_by_key(|(seq, _)| *seq);
+
+        let outcomes: Vec<DownloadOutcome> = outcomes.into_iter().map(|(_, o)| o).collect();
+        if let Some(session) = &self.session {
+            let all_done = self.is_empty()
+                && outcomes
+                    .iter()
+                    .all(|o| !matches!(o.status, DownloadStatus::Failed { .. }));
+            if all_done {
+                // Losing this only means finished entries are replayed and skipped.
+                let _ = session.clear();
+            }
+        }
+        let total_bytes = outcomes
+            .iter()
+            .map(|o| match o.status {
+                DownloadStatus::Downloaded { bytes } => bytes,
+                _ => 0,
+            })
+            .sum();
+
+        let report = DownloadReport {
+            outcomes,
+            total_bytes,
+            elapsed: started.elapsed(),
+        };
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            downloaded = report.downloaded().count(),
+            skipped = report.skipped().count(),
+            failed = report.failed().count(),
+            total_bytes,
+            "download batch finished"
+        );
+        report
+    }
+
+    async fn worker(&self) -> Vec<(u64, DownloadOutcome)> {
+        let mut outcomes = Vec::new();
+        while let Some(Pending { seq, request, .. }) = self.pop() {
+            self.emit(DownloadEvent::Started { id: seq });
+            let status = self.download_coalesced(seq, &request).await;
+            self.emit(match &status {
+                DownloadStatus::Failed { reason } => DownloadEvent::Failed {
+                    id: seq,
+                    reason: reason.clone(),
+                },
+                status => DownloadEvent::Completed {
+                    id: seq,
+                    status: status.clone(),
+                },
+            });
+            if let Some(session) = &self.session
+                && !matches!(status, DownloadStatus::Failed { .. })
+            {
+                // Losing this only means the file is verified and skipped next time.
+                let _ = session.record_done(seq);
+            }
+            outcomes.push((seq, DownloadOutcome { request, status }));
+        }
+        outcomes
+    }
+
+    /// Downloads `request`, or waits for an identical download that is already running.
+    async fn download_coalesced(&self, seq: u64, request: &DownloadRequest) -> DownloadStatus {
+        let key = (
+            request.url.clone(),
+            crate::filesystem::expand_home(&request.path.to_string_lossy()),
+        );
+        let cell = self
+            .lock_in_flight()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let mut leader = false;
+        let shared = cell
+            .get_or_init(|| {
+                leader = true;
+                self.download_one(seq, request)
+            })
+            .await;
+
+        {
+            let mut in_flight = self.lock_in_flight();
+            if in_flight.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+                in_flight.remove(&key);
+            }
+        }
+        match shared {
+            DownloadStatus::Failed { .. } => shared.clone(),
+            _ if leader => shared.clone(),
+            // The transfer was verified against the leader's expectations,
+            // which may differ from this request's.
+            _ => verify_shared(request, key.1).await,
+        }
+    }
+
+    async fn download_one(&self, seq: u64, request: &DownloadRequest) -> DownloadStatus {
+        let observer = |event: TransferEvent| self.emit(event.with_id(seq));
+        match self
+            .client
+            .download_observed(request, self.options.overwrite, &observer)
+            .await
+        {
+            Ok(status) => status,
+            Err(e) => DownloadStatus::Failed {
+                reason: e.to_string(),
+            },
+        }
+    }
+
+    fn pop(&self) -> Option<Pending> {
+        self.lock_queue().pop()
+    }
+
+    fn emit(&self, event: DownloadEvent) {
+        let mut subscribers = self.lock_subscribers();
+        if subscribers.is_empty() {
+            return;
+        }
+        subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    fn lock_subscribers(
+        &self,
+    ) -> std::sync::MutexGuard<'_, Vec<mpsc::UnboundedSender<DownloadEvent>>> {
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_in_flight(&self) -> std::sync::MutexGuard<'_, InFlight> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_queue(&self) -> std::sync::MutexGuard<'_, BinaryHeap<Pending>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use tempfile::tempdir;
+
+    /// Serves `connections` requests one at a time and records the requested paths.
+    fn spawn_recording_server(
+        connections: usize,
+    ) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut paths = Vec::new();
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 2048];
+                let n = stream.read(&mut buf).unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]);
+                let path = head.split_whitespace().nth(1).unwrap_or("").to_string();
+                paths.push(path);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                );
+            }
+            paths
+        });
+        (base, handle)
+    }
+
+    #[test]
+    fn pending_orders_by_priority_then_insertion() {
+        let manager = DownloadManager::new(HttpClient::default(), BatchOptions::default());
+        manager
+            .enqueue_all([
+                DownloadRequest::new("u/asset1", "a1").with_priority(Priority::Low),
+                DownloadRequest::new("u/client.jar", "c").with_priority(Priority::Critical),
+                DownloadRequest::new("u/asset2", "a2").with_priority(Priority::Low),
+                DownloadRequest::new("u/prefetch", "p").with_priority(Priority::Background),
+                DownloadRequest::new("u/lib.jar", "l").with_priority(Priority::High),
+            ])
+            .unwrap();
+
+        let order: Vec<String> = manager.pending().into_iter().map(|r| r.url).collect();
+        assert_eq!(
+            order,
+            vec![
+                "u/client.jar",
+                "u/lib.jar",
+                "u/asset1",
+                "u/asset2",
+                "u/prefetch"
+            ]
+        );
+        assert_eq!(manager.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn run_downloads_in_priority_order() {
+        let dir = tempdir().unwrap();
+        let (base, handle) = spawn_recording_server(3);
+        let manager = DownloadManager::new(
+            HttpClient::default(),
+            BatchOptions {
+                concurrency: 1,
+                ..BatchOptions::default()
+            },
+        );
+        manager
+            .enqueue_all([
+                DownloadRequest::new(format!("{}/asset", base), dir.path().join("asset"))
+                    .with_priority(Priority::Low),
+                DownloadRequest::new(format!("{}/lib", base), dir.path().join("lib")),
+                DownloadRequest::new(format!("{}/client", base), dir.path().join("client"))
+                    .with_priority(Priority::Critical),
+            ])
+            .unwrap();
+
+        let report = manager.run().await;
+
+        assert!(report.is_success());
+        assert_eq!(report.outcomes[0].request.url, format!("{}/asset", base));
+        assert_eq!(handle.join().unwrap(), vec!["/client", "/lib", "/asset"]);
+        assert!(manager.is_empty());
+    }
+
+    #[tokio::test]
+    async fn session_keeps_unfinished_requests_across_restarts() {
+        let dir = tempdir().unwrap();
+        let journal = dir.path().join("install.session");
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/ok.jar");
+            then.status(200).body("ok");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/flaky.jar");
+            then.status(404);
+        });
+        let client = HttpClient::new(crate::http::ClientOptions {
+            retry: crate::http::RetryPolicy::none(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let manager =
+            DownloadManager::with_session(client.clone(), BatchOptions::default(), &journal)
+                .unwrap();
+        manager
+            .enqueue_all([
+                DownloadRequest::new(server.url("/ok.jar"), dir.path().join("ok.jar")),
+                DownloadRequest::new(server.url("/flaky.jar"), dir.path().join("flaky.jar"))
+                    .with_priority(Priority::High),
+            ])
+            .unwrap();
+        assert!(!manager.run().await.is_success());
+        drop(manager);
+
+        let resumed =
+            DownloadManager::with_session(client, BatchOptions::default(), &journal).unwrap();
+        let pending = resumed.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].url, server.url("/flaky.jar"));
+        assert_eq!(pending[0].priority, Priority::High);
+    }
+
+    #[tokio::test]
+    async fn session_is_emptied_after_success() {
+        let dir = tempdir().unwrap();
+        let journal = dir.path().join("install.session");
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET");
+            then.status(200).body("x");
+        });
+
+        let manager =
+            DownloadManager::with_session(HttpClient::default(), BatchOptions::default(), &journal)
+                .unwrap();
+        manager
+            .enqueue(DownloadRequest::new(server.url("/a"), dir.path().join("a")))
+            .unwrap();
+        assert!(manager.run().await.is_success());
+
+        assert_eq!(std::fs::metadata(&journal).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_the_event_sequence_of_each_download() {
+        use crate::http::{HashAlgorithm, HashSpec};
+        use sha1::{Digest, Sha1};
+
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar");
+            then.status(200).body("library");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/broken.jar");
+            then.status(500);
+        });
+        let client = HttpClient::new(crate::http::ClientOptions {
+            retry: crate::http::RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: std::time::Duration::from_millis(1),
+                max_backoff: std::time::Duration::from_millis(5),
+                max_retry_after: std::time::Duration::from_millis(50),
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let manager = DownloadManager::new(
+            client,
+            BatchOptions {
+                concurrency: 1,
+                ..BatchOptions::default()
+            },
+        );
+        let mut events = manager.subscribe();
+
+        let hash = HashSpec::new(HashAlgorithm::Sha1, hex::encode(Sha1::digest(b"library")));
+        manager
+            .enqueue_all([
+                DownloadRequest::new(server.url("/lib.jar"), dir.path().join("lib.jar"))
+                    .with_hash(hash),
+                DownloadRequest::new(server.url("/broken.jar"), dir.path().join("broken.jar")),
+            ])
+            .unwrap();
+        manager.run().await;
+        drop(manager);
+
+        let mut received = Vec::new();
+        while let Some(event) = events.recv().await {
+            received.push(event);
+        }
+        let lib: Vec<&DownloadEvent> = received.iter().filter(|e| e.id() == 0).collect();
+        assert!(matches!(lib[0], DownloadEvent::Queued { url, .. } if url.ends_with("/lib.jar")));
+        assert_eq!(lib[1], &DownloadEvent::Started { id: 0 });
+        assert_eq!(
+            lib[2],
+            &DownloadEvent::Progress {
+                id: 0,
+                received: 7,
+                total: Some(7)
+            }
+        );
+        assert_eq!(lib[3], &DownloadEvent::Verified { id: 0 });
+        assert_eq!(
+            lib[4],
+            &DownloadEvent::Completed {
+                id: 0,
+                status: DownloadStatus::Downloaded { bytes: 7 }
+            }
+        );
+
+        let broken: Vec<&DownloadEvent> = received.iter().filter(|e| e.id() == 1).collect();
+        assert!(matches!(
+            broken[2],
+            DownloadEvent::Retrying { attempt: 1, .. }
+        ));
+        assert!(matches!(broken[3], DownloadEvent::Failed { .. }));
+        assert_eq!(broken.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn identical_concurrent_requests_share_one_transfer() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/shared.jar");
+            then.status(200)
+                .body("shared")
+                .delay(std::time::Duration::from_millis(200));
+        });
+        let request =
+            DownloadRequest::new(server.url("/shared.jar"), dir.path().join("shared.jar"));
+
+        let report = HttpClient::default()
+            .download_all(vec![request.clone(), request], &BatchOptions::default())
+            .await;
+
+        assert!(report.is_success());
+        mock.assert_hits(1);
+        assert_eq!(report.downloaded().count(), 1);
+        assert_eq!(report.skipped().count(), 1);
+        assert_eq!(report.total_bytes, 6);
+    }
+
+    #[tokio::test]
+    async fn shared_transfers_are_checked_against_each_request() {
+        use crate::http::{HashAlgorithm, HashSpec};
+
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/shared.jar");
+            then.status(200)
+                .body("hello world")
+                .delay(std::time::Duration::from_millis(200));
+        });
+        let request =
+            DownloadRequest::new(server.url("/shared.jar"), dir.path().join("shared.jar"));
+        let right = request.clone().with_hash(HashSpec::new(
+            HashAlgorithm::Sha1,
+            "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed",
+        ));
+        let wrong = request.with_hash(HashSpec::new(HashAlgorithm::Sha1, "0".repeat(40)));
+
+        let report = HttpClient::default()
+            .download_all(vec![right, wrong.clone()], &BatchOptions::default())
+            .await;
+
+        assert_eq!(report.downloaded().count(), 1);
+        assert_eq!(report.failed().count(), 1);
+        assert_eq!(report.failed().next().unwrap().request, wrong);
+        mock.assert_hits(1);
+    }
+}
@@ -0,0 +1,183 @@
+use super::error::HttpError;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Receives network activity from an `HttpClient`.
+///
+/// All methods have empty default implementations, so implementors only override
+/// the events they care about. Every attempt of a retried request is reported
+/// separately. Implementations are called from the download tasks and should
+/// return quickly.
+pub trait Metrics: fmt::Debug + Send + Sync {
+    /// A request to `host` is about to be sent.
+    fn request_started(&self, host: &str) {
+        let _ = host;
+    }
+
+    /// A chunk of `bytes` was received from `host`.
+    fn bytes_received(&self, host: &str, bytes: u64) {
+        let _ = (host, bytes);
+    }
+
+    /// A request to `host` completed successfully after `elapsed`.
+    fn request_finished(&self, host: &str, elapsed: Duration) {
+        let _ = (host, elapsed);
+    }
+
+    /// A request to `host` failed after `elapsed`.
+    fn request_failed(&self, host: &str, elapsed: Duration, error: &HttpError) {
+        let _ = (host, elapsed, error);
+    }
+
+    /// A response for `url` was served from the cache without touching the network.
+    fn cache_hit(&self, url: &str) {
+        let _ = url;
+    }
+}
+
+/// Counters for a single host.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostStats {
+    /// Number of requests sent, including retries.
+    pub requests: u64,
+    /// Number of requests that failed.
+    pub failures: u64,
+    /// Number of body bytes received.
+    pub bytes: u64,
+    /// Total time spent on completed and failed requests.
+    pub total_time: Duration,
+}
+
+impl HostStats {
+    /// Returns the average time per finished request, or `None` if nothing finished yet.
+    pub fn average_time(&self) -> Option<Duration> {
+        let finished = u32::try_from(self.requests).ok()?;
+        (finished > 0).then(|| self.total_time / finished)
+    }
+}
+
+/// A point-in-time copy of the counters collected by `CountingMetrics`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Number of requests sent, including retries.
+    pub requests: u64,
+    /// Number of requests that failed.
+    pub failures: u64,
+    /// Number of body bytes received.
+    pub bytes: u64,
+    /// Number of responses served from the cache.
+    pub cache_hits: u64,
+    /// Counters per host.
+    pub hosts: HashMap<String, HostStats>,
+}
+
+/// A `Metrics` implementation that keeps running totals, e.g. for a network activity panel.
+#[derive(Debug, Default)]
+pub struct CountingMetrics {
+    state: Mutex<MetricsSnapshot>,
+}
+
+impl CountingMetrics {
+    /// Creates a collector with all counters at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the current counters.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.lock().clone()
+    }
+
+    /// Resets all counters to zero.
+    pub fn reset(&self) {
+        *self.lock() = MetricsSnapshot::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MetricsSnapshot> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Metrics for CountingMetrics {
+    fn request_started(&self, host: &str) {
+        let mut state = self.lock();
+        state.requests += 1;
+        state.hosts.entry(host.to_string()).or_default().requests += 1;
+    }
+
+    fn bytes_received(&self, host: &str, bytes: u64) {
+        let mut state = self.lock();
+        state.bytes += bytes;
+        state.hosts.entry(host.to_string()).or_default().bytes += bytes;
+    }
+
+    fn request_finished(&self, host: &str, elapsed: Duration) {
+        let mut state = self.lock();
+        state.hosts.entry(host.to_string()).or_default().total_time += elapsed;
+    }
+
+    fn request_failed(&self, host: &str, elapsed: Duration, _error: &HttpError) {
+        let mut state = self.lock();
+        state.failures += 1;
+        let stats = state.hosts.entry(host.to_string()).or_default();
+        stats.failures += 1;
+        stats.total_time += elapsed;
+    }
+
+    fn cache_hit(&self, _url: &str) {
+        self.lock().cache_hits += 1;
+    }
+}
+
+/// Returns the host part of `url`, or the whole string if it cannot be parsed.
+pub(crate) fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_metrics_aggregates_per_host() {
+        let metrics = CountingMetrics::new();
+        metrics.request_started("a.example");
+        metrics.bytes_received("a.example", 10);
+        metrics.request_finished("a.example", Duration::from_millis(20));
+        metrics.request_started("b.example");
+        metrics.request_failed(
+            "b.example",
+            Duration::from_millis(5),
+            &HttpError::Config("x".to_string()),
+        );
+        metrics.cache_hit("https://a.example/x");
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 2);
+        assert_eq!(snapshot.failures, 1);
+        assert_eq!(snapshot.bytes, 10);
+        assert_eq!(snapshot.cache_hits, 1);
+        assert_eq!(
+            snapshot.hosts["a.example"].average_time(),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(snapshot.hosts["b.example"].failures, 1);
+
+        metrics.reset();
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+    }
+
+    #[test]
+    fn host_of_extracts_host() {
+        assert_eq!(
+            host_of("https://cdn.modrinth.com/data/x.jar"),
+            "cdn.modrinth.com"
+        );
+        assert_eq!(host_of("not a url"), "not a url");
+    }
+}
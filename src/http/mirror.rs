@@ -0,0 +1,438 @@
+use super::client::{HttpClient, offline_error, status_error};
+use super::error::HttpError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+
+/// Weight of a new sample in the moving averages of [`MirrorStats`].
+const SMOOTHING: f64 = 0.3;
+
+/// Rewrites `url` according to a host override table.
+///
+/// Keys are lowercase host names such as `libraries.minecraft.net`. A value is either a
+/// replacement host (optionally with a port), which keeps scheme and path, or a
+/// base URL such as `https://mirror.example/minecraft-libraries`, whose path is
+/// prepended to the original path. URLs whose host is not in the table are
+/// returned unchanged.
+///
+/// # Arguments
+///
+/// * `url` - The URL to rewrite.
+/// * `overrides` - Host to replacement mapping.
+///
+/// # Returns
+///
+/// * `String` - The rewritten URL.
+pub fn apply_host_overrides(url: &str, overrides: &HashMap<String, String>) -> String {
+    if overrides.is_empty() {
+        return url.to_string();
+    }
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(replacement) = parsed
+        .host_str()
+        .and_then(|host| overrides.get(&host.to_ascii_lowercase()))
+    else {
+        return url.to_string();
+    };
+
+    if replacement.contains("://") {
+        let Ok(mut base) = reqwest::Url::parse(replacement) else {
+            return url.to_string();
+        };
+        let path = format!("{}{}", base.path().trim_end_matches('/'), parsed.path());
+        base.set_path(&path);
+        base.set_query(parsed.query());
+        base.set_fragment(parsed.fragment());
+        return base.to_string();
+    }
+
+    let (host, port) = match replacement.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host, port.parse().ok()),
+        _ => (replacement.as_str(), None),
+    };
+    if parsed.set_host(Some(host)).is_err() || parsed.set_port(port).is_err() {
+        return url.to_string();
+    }
+    parsed.to_string()
+}
+
+/// Measured performance of one download source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MirrorStats {
+    /// Moving average of the time until response headers arrived.
+    pub latency: Duration,
+    /// Moving average of the transfer rate in bytes per second.
+    pub throughput: f64,
+    /// Number of successful measurements.
+    pub samples: u32,
+    /// Number of failures since the last success.
+    pub failures: u32,
+}
+
+impl MirrorStats {
+    fn record(&mut self, latency: Duration, throughput: f64) {
+        if self.samples == 0 {
+            self.latency = latency;
+            self.throughput = throughput;
+        } else {
+            self.latency = latency.mul_f64(SMOOTHING) + self.latency.mul_f64(1.0 - SMOOTHING);
+            self.throughput = throughput * SMOOTHING + self.throughput * (1.0 - SMOOTHING);
+        }
+        self.samples += 1;
+        self.failures = 0;
+    }
+}
+
+/// Alternative sources per host, ranked by measured speed.
+///
+/// A source is written like a value of [`apply_host_overrides`]. The original host
+/// is always a source itself, so requests keep going there until a mirror has
+/// been measured to be faster. Sources that failed their last measurement are
+/// only used if every source failed.
+#[derive(Debug, Default)]
+pub struct MirrorRanking {
+    sources: HashMap<String, Vec<String>>,
+    stats: Mutex<HashMap<String, MirrorStats>>,
+}
+
+impl MirrorRanking {
+    /// Creates a ranking without mirrors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `source` as an alternative for `host`.
+    pub fn add_mirror(&mut self, host: &str, source: impl Into<String>) {
+        let source = source.into();
+        let sources = self.sources.entry(host.to_ascii_lowercase()).or_default();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    /// Returns the sources of `host`: the host itself followed by its mirrors.
+    pub fn sources(&self, host: &str) -> Vec<String> {
+        let host = host.to_ascii_lowercase();
+        let mut sources = vec![host.clone()];
+        if let Some(mirrors) = self.sources.get(&host) {
+            sources.extend(mirrors.iter().cloned());
+        }
+        sources
+    }
+
+    /// Returns the measurements of `source`, if any.
+    pub fn stats(&self, source: &str) -> Option<MirrorStats> {
+        self.lock().get(source).copied()
+    }
+
+    /// Records a successful transfer of `bytes` from `source`.
+    ///
+    /// `latency` is the time until the response headers arrived and `elapsed` the
+    /// time the whole transfer took.
+    pub fn record_success(&self, source: &str, latency: Duration, bytes: u64, elapsed: Duration) {
+        let throughput = bytes as f64 / elapsed.as_secs_f64().max(0.001);
+        self.lock()
+            .entry(source.to_string())
+            .or_default()
+            .record(latency, throughput);
+    }
+
+    /// Records a failed transfer from `source`.
+    pub fn record_failure(&self, source: &str) {
+        self.lock().entry(source.to_string()).or_default().failures += 1;
+    }
+
+    /// Returns the sources of `host`, fastest first.
+    ///
+    /// Working sources are ordered by throughput, then latency. Unmeasured sources
+    /// follow in the order they were added, and sources that failed come last.
+    pub fn ranked(&self, host: &str) -> Vec<String> {
+        let stats = self.lock();
+        let mut sources = self.sources(host);
+        // Stable, so unmeasured sources keep the original host first.
+        sources.sort_by(|a, b| {
+            let (a, b) = (stats.get(a), stats.get(b));
+            rank(a).cmp(&rank(b)).then_with(|| match (a, b) {
+                (Some(a), Some(b)) => b
+                    .throughput
+                    .total_cmp(&a.throughput)
+                    .then_with(|| a.latency.cmp(&b.latency)),
+                _ => std::cmp::Ordering::Equal,
+            })
+        });
+        sources
+    }
+
+    /// Rewrites `url` to the fastest source of its host.
+    ///
+    /// URLs whose host has no mirrors, or whose fastest source is the host itself,
+    /// are returned unchanged.
+    pub fn rewrite(&self, url: &str) -> String {
+        let Some(host) = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        else {
+            return url.to_string();
+        };
+        if !self.sources.contains_key(&host) {
+            return url.to_string();
+        }
+        let fastest = self.ranked(&host).swap_remove(0);
+        source_url(url, &host, &fastest)
+    }
+
+    /// Loads measurements saved by [`MirrorRanking::save`], replacing the current ones.
+    ///
+    /// A missing file is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(&self, path: &Path) -> io::Result<()> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        *self.lock() = serde_json::from_slice(&data)?;
+        Ok(())
+    }
+
+    /// Saves the measurements to `path`, so later runs start with the known ranking.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(dir)?;
+        let mut file = NamedTempFile::new_in(dir)?;
+        serde_json::to_writer_pretty(file.as_file_mut(), &*self.lock())?;
+        file.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, MirrorStats>> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 0 for working, 1 for unmeasured and 2 for failing sources.
+fn rank(stats: Option<&MirrorStats>) -> u8 {
+    match stats {
+        Some(s) if s.failures > 0 => 2,
+        Some(s) if s.samples > 0 => 0,
+        _ => 1,
+    }
+}
+
+fn source_url(url: &str, host: &str, source: &str) -> String {
+    if source == host {
+        return url.to_string();
+    }
+    apply_host_overrides(
+        url,
+        &HashMap::from([(host.to_string(), source.to_string())]),
+    )
+}
+
+impl HttpClient {
+    /// Measures every source of the host of `probe_url` and records the results.
+    ///
+    /// `probe_url` should point to a small file that all sources serve, such as a
+    /// `maven-metadata.xml`. Sources are measured one after the other so they do not
+    /// compete for bandwidth. Host overrides are not applied to probes.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranking` - The sources to probe; measurements are recorded in it.
+    /// * `probe_url` - A URL on the original host.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, HttpError>` - The sources of the host, fastest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is offline or `probe_url` is not a valid URL.
+    /// Failing sources are recorded, not reported as errors.
+    pub async fn probe_mirrors(
+        &self,
+        ranking: &MirrorRanking,
+        probe_url: &str,
+    ) -> Result<Vec<String>, HttpError> {
+        if self.is_offline() {
+            return Err(offline_error(probe_url));
+        }
+        let host = reqwest::Url::parse(probe_url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .ok_or_else(|| HttpError::Config(format!("invalid probe URL {:?}", probe_url)))?;
+
+        for source in ranking.sources(&host) {
+            let url = source_url(probe_url, &host, &source);
+            match self.probe(&url).await {
+                Ok((latency, bytes, elapsed)) => {
+                    ranking.record_success(&source, latency, bytes, elapsed)
+                }
+                Err(_) => ranking.record_failure(&source),
+            }
+        }
+        Ok(ranking.ranked(&host))
+    }
+
+    async fn probe(&self, url: &str) -> Result<(Duration, u64, Duration), HttpError> {
+        let started = Instant::now();
+        let response = self
+            .request_unrouted(reqwest::Method::GET, url, &[])
+            .send()
+            .await?;
+        let latency = started.elapsed();
+        if !response.status().is_success() {
+            return Err(status_error(url, &response));
+        }
+        let bytes = response.bytes().await?.len() as u64;
+        Ok((latency, bytes, started.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn replaces_host_and_keeps_path() {
+        let overrides = table(&[("libraries.minecraft.net", "mirror.example")]);
+        assert_eq!(
+            apply_host_overrides(
+                "https://libraries.minecraft.net/com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar",
+                &overrides
+            ),
+            "https://mirror.example/com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar"
+        );
+    }
+
+    #[test]
+    fn replaces_with_base_url_and_port() {
+        let overrides = table(&[
+            (
+                "resources.download.minecraft.net",
+                "http://127.0.0.1:8080/assets/",
+            ),
+            ("piston-meta.mojang.com", "localhost:9000"),
+        ]);
+        assert_eq!(
+            apply_host_overrides(
+                "https://resources.download.minecraft.net/ab/abcdef?x=1",
+                &overrides
+            ),
+            "http://127.0.0.1:8080/assets/ab/abcdef?x=1"
+        );
+        assert_eq!(
+            apply_host_overrides("https://piston-meta.mojang.com/mc/game/v.json", &overrides),
+            "https://localhost:9000/mc/game/v.json"
+        );
+    }
+
+    #[test]
+    fn leaves_other_urls_alone() {
+        let overrides = table(&[("libraries.minecraft.net", "mirror.example")]);
+        assert_eq!(
+            apply_host_overrides("https://maven.fabricmc.net/a.jar", &overrides),
+            "https://maven.fabricmc.net/a.jar"
+        );
+        assert_eq!(apply_host_overrides("not a url", &overrides), "not a url");
+    }
+
+    #[test]
+    fn ranking_prefers_fast_working_sources_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ranking = MirrorRanking::new();
+        ranking.add_mirror("libraries.minecraft.net", "mirror-a.example");
+        ranking.add_mirror("libraries.minecraft.net", "https://mirror-b.example/libs");
+        let url = "https://libraries.minecraft.net/a/b.jar";
+        assert_eq!(ranking.rewrite(url), url);
+
+        let second = Duration::from_secs(1);
+        ranking.record_success("libraries.minecraft.net", second, 1_000, second);
+        ranking.record_success("https://mirror-b.example/libs", second, 5_000, second);
+        ranking.record_success("mirror-a.example", second, 9_000, second);
+        ranking.record_failure("mirror-a.example");
+        assert_eq!(
+            ranking.ranked("libraries.minecraft.net"),
+            vec![
+                "https://mirror-b.example/libs",
+                "libraries.minecraft.net",
+                "mirror-a.example"
+            ]
+        );
+        assert_eq!(
+            ranking.rewrite(url),
+            "https://mirror-b.example/libs/a/b.jar"
+        );
+
+        let path = dir.path().join("mirrors.json");
+        ranking.save(&path).unwrap();
+        let mut restored = MirrorRanking::new();
+        restored.add_mirror("libraries.minecraft.net", "https://mirror-b.example/libs");
+        restored.load(&path).unwrap();
+        assert_eq!(restored.stats("mirror-a.example").unwrap().failures, 1);
+        assert_eq!(
+            restored.rewrite(url),
+            "https://mirror-b.example/libs/a/b.jar"
+        );
+    }
+
+    #[tokio::test]
+    async fn probing_routes_requests_to_the_fastest_source() {
+        let origin = httpmock::MockServer::start();
+        let origin_mock = origin.mock(|when, then| {
+            when.method("GET");
+            then.status(200)
+                .body("slow")
+                .delay(Duration::from_millis(300));
+        });
+        let mirror = httpmock::MockServer::start();
+        let mirror_mock = mirror.mock(|when, then| {
+            when.method("GET");
+            then.status(200).body("fast");
+        });
+        let mut ranking = MirrorRanking::new();
+        ranking.add_mirror("localhost", mirror.address().to_string());
+        let ranking = std::sync::Arc::new(ranking);
+        let client = HttpClient::new(crate::http::ClientOptions {
+            mirrors: Some(ranking.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let origin_url = format!("http://localhost:{}/maven-metadata.xml", origin.port());
+        let order = client.probe_mirrors(&ranking, &origin_url).await.unwrap();
+        assert_eq!(
+            order,
+            vec![mirror.address().to_string(), "localhost".to_string()]
+        );
+
+        let body = client.get_bytes(&origin_url).await.unwrap();
+        assert_eq!(body, b"fast");
+        origin_mock.assert_hits(1);
+        mirror_mock.assert_hits(2);
+    }
+}
@@ -1,15 +1,77 @@
-use futures_util::StreamExt;
+use crc32fast::Hasher as Crc32;
+use md5::Md5;
 use sha1::{Digest as Sha1Digest, Sha1};
-use sha2::{Digest as Sha2Digest, Sha256, Sha512};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Sha512};
+use std::fmt;
 use std::fs::{self, File};
-use std::io::{self, BufReader, Read, Write};
+use std::io::{self, BufReader, Read};
 use std::path::Path;
+use std::str::FromStr;
+
+/// On-disk cache for HTTP responses with TTL and size-based eviction.
+pub mod cache;
+
+/// MurmurHash2 implementation used for CurseForge file fingerprints.
+pub mod murmur2;
+
+/// Error type for HTTP client operations.
+pub mod error;
+
+/// Shared HTTP client with retries and response caching.
+pub mod client;
+
+/// Offline verification of installed files.
+pub mod audit;
+/// Batch downloads with per-file status reporting.
+pub mod batch;
+
+/// Streaming decompression of downloaded artifacts.
+pub mod compression;
+/// Per-download progress events for user interfaces.
+pub mod events;
+/// Extraction of archives while they are downloaded.
+pub mod extract;
+/// Prioritized download queue.
+pub mod manager;
+/// Hooks for network activity counters.
+pub mod metrics;
+/// Host overrides and speed-ranked regional mirrors.
+pub mod mirror;
+/// Maven-style checksum sidecar files.
+pub mod sidecar;
+/// Persistent download queues.
+pub mod session;
+/// Custom root certificates and pinning.
+pub mod tls;
+
+pub use audit::{verify_files, AuditEntry, AuditReport, AuditStatus, PathCheck};
+pub use batch::{download_all, BatchOptions, DownloadReport, DownloadRequest, DownloadStatus};
+pub use client::{
+    bearer, get_json, parse_retry_after, partial_path, user_agent, ClientOptions, HttpClient,
+    RetryPolicy, DEFAULT_USER_AGENT,
+};
+pub use compression::Compression;
+pub use events::DownloadEvent;
+pub use extract::{ArchiveFormat, ExtractReport};
+pub use manager::{DownloadManager, Priority};
+pub use mirror::{apply_host_overrides, MirrorRanking, MirrorStats};
+pub use metrics::{CountingMetrics, HostStats, Metrics, MetricsSnapshot};
+pub use reqwest::multipart;
+pub use session::DownloadSession;
+pub use sidecar::{parse_sidecar, sidecar_url};
+pub use tls::TlsOptions;
+pub use error::{HttpError, NetworkErrorKind};
+use murmur2::Murmur2;
 
 /// Enum representing supported hashers for file integrity verification.
 pub enum HasherEnum {
     Sha1(Sha1),
     Sha256(Sha256),
     Sha512(Sha512),
+    Md5(Md5),
+    Crc32(Crc32),
+    Murmur2(Murmur2),
     None,
 }
 
@@ -25,6 +87,9 @@ impl HasherEnum {
             HasherEnum::Sha1(h) => h.update(data),
             HasherEnum::Sha256(h) => h.update(data),
             HasherEnum::Sha512(h) => h.update(data),
+            HasherEnum::Md5(h) => h.update(data),
+            HasherEnum::Crc32(h) => h.update(data),
+            HasherEnum::Murmur2(h) => h.update(data),
             HasherEnum::None => {}
         }
     }
@@ -36,11 +101,224 @@ impl HasherEnum {
             HasherEnum::Sha1(h) => h.finalize().to_vec(),
             HasherEnum::Sha256(h) => h.finalize().to_vec(),
             HasherEnum::Sha512(h) => h.finalize().to_vec(),
+            HasherEnum::Md5(h) => h.finalize().to_vec(),
+            HasherEnum::Crc32(h) => h.finalize().to_be_bytes().to_vec(),
+            HasherEnum::Murmur2(h) => h.finalize().to_be_bytes().to_vec(),
             HasherEnum::None => Vec::new(),
         }
     }
 }
 
+/// Hash algorithms supported for file integrity verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+    Md5,
+    Crc32,
+    /// CurseForge-style MurmurHash2 fingerprint, encoded as 8 big-endian hex digits.
+    Murmur2,
+}
+
+impl HashAlgorithm {
+    /// Creates a fresh hasher for this algorithm.
+    pub fn hasher(self) -> HasherEnum {
+        match self {
+            HashAlgorithm::Sha1 => HasherEnum::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => HasherEnum::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => HasherEnum::Sha512(Sha512::new()),
+            HashAlgorithm::Md5 => HasherEnum::Md5(Md5::new()),
+            HashAlgorithm::Crc32 => HasherEnum::Crc32(Crc32::new()),
+            HashAlgorithm::Murmur2 => HasherEnum::Murmur2(Murmur2::new()),
+        }
+    }
+
+    /// Returns the length of a hex-encoded digest produced by this algorithm.
+    pub fn hex_len(self) -> usize {
+        match self {
+            HashAlgorithm::Crc32 | HashAlgorithm::Murmur2 => 8,
+            HashAlgorithm::Md5 => 32,
+            HashAlgorithm::Sha1 => 40,
+            HashAlgorithm::Sha256 => 64,
+            HashAlgorithm::Sha512 => 128,
+        }
+    }
+
+    /// Returns the lowercase name of the algorithm, e.g. `sha256`.
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Crc32 => "crc32",
+            HashAlgorithm::Murmur2 => "murmur2",
+        }
+    }
+
+    /// Guesses the algorithm from the length of a hex-encoded digest.
+    ///
+    /// This is ambiguous (CRC32 and MurmurHash2 share a length) and only exists
+    /// for the legacy string-based APIs.
+    #[deprecated(note = "pass an explicit `HashSpec` instead of guessing from the hash length")]
+    pub fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            8 => Some(HashAlgorithm::Crc32),
+            32 => Some(HashAlgorithm::Md5),
+            40 => Some(HashAlgorithm::Sha1),
+            64 => Some(HashAlgorithm::Sha256),
+            128 => Some(HashAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "md5" => Ok(HashAlgorithm::Md5),
+            "crc32" => Ok(HashAlgorithm::Crc32),
+            "murmur2" => Ok(HashAlgorithm::Murmur2),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown hash algorithm: {}", s),
+            )),
+        }
+    }
+}
+
+/// An expected hash: the algorithm together with its hex-encoded digest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HashSpec {
+    /// The algorithm the digest was produced with.
+    pub algorithm: HashAlgorithm,
+    /// The hex-encoded digest.
+    pub hex: String,
+}
+
+impl HashSpec {
+    /// Creates a new hash spec. The hex digest is normalized to lowercase.
+    pub fn new(algorithm: HashAlgorithm, hex: impl Into<String>) -> Self {
+        Self {
+            algorithm,
+            hex: hex.into().to_ascii_lowercase(),
+        }
+    }
+
+    /// Guesses the algorithm from the length of `hex`.
+    #[deprecated(note = "construct the spec with an explicit `HashAlgorithm` instead")]
+    pub fn guess(hex: &str) -> Option<Self> {
+        #[allow(deprecated)]
+        HashAlgorithm::from_hex_len(hex.len()).map(|algorithm| Self::new(algorithm, hex))
+    }
+
+    /// Checks whether the hex digest has the length expected for its algorithm.
+    pub fn is_well_formed(&self) -> bool {
+        self.hex.len() == self.algorithm.hex_len()
+            && self.hex.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    /// Checks whether a finalized digest matches this spec.
+    pub fn matches(&self, digest: &[u8]) -> bool {
+        hex::encode(digest).eq_ignore_ascii_case(&self.hex)
+    }
+}
+
+impl fmt::Display for HashSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.hex)
+    }
+}
+
+/// The expected size and hash of a file, as listed in version manifests.
+///
+/// The size is checked first, so obviously wrong files are rejected without
+/// hashing them. Unset fields are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileCheck {
+    /// The expected size in bytes.
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// The expected hash.
+    #[serde(default)]
+    pub hash: Option<HashSpec>,
+}
+
+impl FileCheck {
+    /// Creates a check for both size and hash.
+    pub fn new(size: u64, hash: HashSpec) -> Self {
+        Self {
+            size: Some(size),
+            hash: Some(hash),
+        }
+    }
+
+    /// Returns `true` if the check does not constrain the file at all.
+    pub fn is_empty(&self) -> bool {
+        self.size.is_none() && self.hash.is_none()
+    }
+}
+
+/// Hashes data once for every spec in a set of acceptable hashes.
+pub(crate) struct MultiHasher {
+    hashers: Vec<HasherEnum>,
+}
+
+impl MultiHasher {
+    /// Creates one hasher per spec, in the same order.
+    pub(crate) fn new(specs: &[HashSpec]) -> Self {
+        Self {
+            hashers: specs.iter().map(|spec| spec.algorithm.hasher()).collect(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for hasher in &mut self.hashers {
+            hasher.update(data);
+        }
+    }
+
+    /// Feeds everything read from `reader` into the hashers.
+    pub(crate) fn update_from<R: Read>(&mut self, mut reader: R) -> io::Result<()> {
+        let mut buffer = [0u8; 8192];
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                return Ok(());
+            }
+            self.update(&buffer[..n]);
+        }
+    }
+
+    /// Returns `true` if at least one spec matches, or if `specs` is empty.
+    ///
+    /// `specs` must be the slice the hasher was created with.
+    pub(crate) fn matches_any(self, specs: &[HashSpec]) -> Result<bool, Vec<Vec<u8>>> {
+        if specs.is_empty() {
+            return Ok(true);
+        }
+        let digests: Vec<Vec<u8>> = self.hashers.into_iter().map(HasherEnum::finalize).collect();
+        if specs.iter().zip(&digests).any(|(spec, digest)| spec.matches(digest)) {
+            Ok(true)
+        } else {
+            Err(digests)
+        }
+    }
+}
+
 /// Downloads a file from the given URL and saves it to the specified path.
 ///
 /// Optionally verifies the file's hash and can override existing files.
@@ -50,79 +328,239 @@ impl HasherEnum {
 ///
 /// * `url` - The URL to download the file from.
 /// * `filepath` - The local file path to save the downloaded file.
+/// * `expected` - Optional expected hash for file verification.
+/// * `override_file` - Whether to overwrite the file if it already exists.
+///
+/// # Returns
+///
+/// * `io::Result<()>` - Returns `Ok(())` on success, or an error if the download or verification fails.
+pub async fn download_file(
+    url: &str,
+    filepath: &str,
+    expected: Option<&HashSpec>,
+    override_file: bool,
+) -> io::Result<()> {
+    let mut request = DownloadRequest::new(url, filepath);
+    request.hashes = expected.cloned().into_iter().collect();
+    HttpClient::default()
+        .download(&request, override_file)
+        .await?;
+    Ok(())
+}
+
+/// Downloads a file and verifies its size and hash.
+///
+/// An existing file that passes the check is kept unless `override_file` is set.
+/// A response whose `Content-Length` contradicts the expected size is rejected
+/// before its body is downloaded.
+///
+/// # Arguments
+///
+/// * `url` - The URL to download the file from.
+/// * `filepath` - The local file path to save the downloaded file.
+/// * `check` - The expected size and hash.
+/// * `override_file` - Whether to overwrite the file if it already exists.
+///
+/// # Returns
+///
+/// * `io::Result<()>` - Returns `Ok(())` on success, or an error if the download or verification fails.
+pub async fn download_file_checked(
+    url: &str,
+    filepath: &str,
+    check: &FileCheck,
+    override_file: bool,
+) -> io::Result<()> {
+    let request = DownloadRequest::new(url, filepath).with_check(check.clone());
+    HttpClient::default()
+        .download(&request, override_file)
+        .await?;
+    Ok(())
+}
+
+/// Downloads a file from the given URL and saves it to the specified path.
+///
+/// The hash algorithm is guessed from the length of `expected_hash`; an empty
+/// hash disables verification and an unrecognized length is rejected.
+///
+/// # Arguments
+///
+/// * `url` - The URL to download the file from.
+/// * `filepath` - The local file path to save the downloaded file.
 /// * `expected_hash` - Optional expected hash string for file verification.
 /// * `override_file` - Whether to overwrite the file if it already exists.
 ///
 /// # Returns
 ///
 /// * `io::Result<()>` - Returns `Ok(())` on success, or an error if the download or verification fails.
+#[deprecated(note = "use `download_file` with an explicit `HashSpec`")]
 pub async fn download_to_file(
     url: &str,
     filepath: &str,
     expected_hash: Option<&str>,
     override_file: bool,
 ) -> io::Result<()> {
-    let expanded_path = crate::filesystem::expand_home(filepath);
+    let spec = match expected_hash {
+        Some(hash) if !hash.is_empty() => Some(guess_spec(hash)?),
+        _ => None,
+    };
+    download_file(url, filepath, spec.as_ref(), override_file).await
+}
 
+/// Downloads the body of the given URL into memory.
+///
+/// Intended for small payloads such as manifests and icons where writing a
+/// temporary file first would be wasteful.
+///
+/// # Arguments
+///
+/// * `url` - The URL to download.
+/// * `expected` - Optional expected hash for verification of the body.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<u8>>` - The response body, or an error if the download or verification fails.
+pub async fn download_bytes(url: &str, expected: Option<&HashSpec>) -> io::Result<Vec<u8>> {
+    Ok(HttpClient::default().download_bytes(url, expected).await?)
+}
 
-    if expanded_path.exists() && !override_file {
-        if let Some(expected) = expected_hash {
-            if verify_hash(&expanded_path, expected)? {
-                return Ok(());
-            }
-        } else {
-            return Ok(());
-        }
-    }
+/// Downloads the body of the given URL as a UTF-8 string.
+///
+/// # Arguments
+///
+/// * `url` - The URL to download.
+/// * `expected` - Optional expected hash for verification of the body.
+///
+/// # Returns
+///
+/// * `io::Result<String>` - The response body, or an error if the download, verification, or UTF-8 decoding fails.
+pub async fn download_string(url: &str, expected: Option<&HashSpec>) -> io::Result<String> {
+    let body = download_bytes(url, expected).await?;
+    String::from_utf8(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
 
-    if let Some(parent) = expanded_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
+/// Computes the hex-encoded digest of a file.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to hash.
+/// * `algorithm` - The hash algorithm to use.
+///
+/// # Returns
+///
+/// * `io::Result<String>` - The lowercase hex digest, or an error if reading fails.
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+    hash_reader(BufReader::new(File::open(path)?), algorithm)
+}
 
-    let response = reqwest::get(url)
-        .await
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("http error: {}", e)))?;
+/// Computes the hex-encoded digest of everything read from `reader`.
+///
+/// Works for in-memory data (`&[u8]`, `Cursor`) as well as streams such as zip entries.
+///
+/// # Arguments
+///
+/// * `reader` - The data to hash. It is read until EOF.
+/// * `algorithm` - The hash algorithm to use.
+///
+/// # Returns
+///
+/// * `io::Result<String>` - The lowercase hex digest, or an error if reading fails.
+pub fn hash_reader<R: Read>(mut reader: R, algorithm: HashAlgorithm) -> io::Result<String> {
+    let mut hasher = algorithm.hasher();
 
-    if !response.status().is_success() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("download failed: status code {}", response.status()),
-        ));
+    let mut buffer = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
     }
 
-    let mut out_file = File::create(&expanded_path)?;
-
-    let mut hasher = match expected_hash {
-        Some(h) if h.len() == 40 => HasherEnum::Sha1(Sha1::new()),
-        Some(h) if h.len() == 64 => HasherEnum::Sha256(Sha256::new()),
-        Some(h) if h.len() == 128 => HasherEnum::Sha512(Sha512::new()),
-        _ => HasherEnum::None,
-    };
+    Ok(hex::encode(hasher.finalize()))
+}
 
-    let mut stream = response.bytes_stream();
+/// Verifies the hash of a file against an expected hash.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to verify.
+/// * `spec` - The expected hash.
+///
+/// # Returns
+///
+/// * `io::Result<bool>` - Returns `Ok(true)` if the hash matches, `Ok(false)` otherwise, or an error if reading fails.
+pub fn verify_file(path: &Path, spec: &HashSpec) -> io::Result<bool> {
+    Ok(hash_file(path, spec.algorithm)?.eq_ignore_ascii_case(&spec.hex))
+}
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
-        out_file.write_all(&chunk)?;
-        hasher.update(&chunk);
+/// Verifies a file against a set of acceptable hashes, reading it only once.
+///
+/// Useful when sources publish different algorithms for the same artifact, e.g.
+/// sha1 from Mojang and sha512 from Modrinth.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to verify.
+/// * `specs` - The acceptable hashes.
+///
+/// # Returns
+///
+/// * `io::Result<bool>` - Returns `Ok(true)` if any hash matches, `Ok(false)` if none does or `specs` is empty, or an error if reading fails.
+pub fn verify_file_any(path: &Path, specs: &[HashSpec]) -> io::Result<bool> {
+    if specs.is_empty() {
+        return Ok(false);
     }
+    let mut hasher = MultiHasher::new(specs);
+    hasher.update_from(File::open(path)?)?;
+    Ok(hasher.matches_any(specs).is_ok())
+}
 
-    if let Some(expected) = expected_hash {
-        let actual = hex::encode(hasher.finalize());
-        if actual != expected {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("hash mismatch: got {}, want {}", actual, expected),
-            ));
-        }
+/// Verifies a file against an expected size and hash.
+///
+/// The size is compared first; the file is only hashed if it has the expected size.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to verify.
+/// * `check` - The expected size and hash.
+///
+/// # Returns
+///
+/// * `io::Result<bool>` - Returns `Ok(true)` if every set field matches, `Ok(false)` otherwise, or an error if the file cannot be read.
+pub fn verify_file_check(path: &Path, check: &FileCheck) -> io::Result<bool> {
+    let len = fs::metadata(path)?.len();
+    if check.size.is_some_and(|size| size != len) {
+        return Ok(false);
+    }
+    match &check.hash {
+        Some(spec) => verify_file(path, spec),
+        None => Ok(true),
     }
+}
 
-    Ok(())
+/// Verifies the hash of a file on tokio's blocking thread pool.
+///
+/// Use this from async code so hashing large files does not stall the runtime.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to verify.
+/// * `spec` - The expected hash.
+///
+/// # Returns
+///
+/// * `io::Result<bool>` - Returns `Ok(true)` if the hash matches, `Ok(false)` otherwise, or an error if reading fails.
+pub async fn verify_hash_async(path: &Path, spec: &HashSpec) -> io::Result<bool> {
+    let path = path.to_path_buf();
+    let spec = spec.clone();
+    tokio::task::spawn_blocking(move || verify_file(&path, &spec))
+        .await
+        .map_err(io::Error::other)?
 }
 
 /// Verifies the hash of a file against an expected hash string.
 ///
-/// Supports SHA-1, SHA-256, and SHA-512 based on the length of the expected hash.
+/// Supports CRC32, MD5, SHA-1, SHA-256, and SHA-512 based on the length of the expected hash.
 ///
 /// # Arguments
 ///
@@ -132,17 +570,43 @@ pub async fn download_to_file(
 /// # Returns
 ///
 /// * `io::Result<bool>` - Returns `Ok(true)` if the hash matches, `Ok(false)` otherwise, or an error if reading fails.
+#[deprecated(note = "use `verify_file` with an explicit `HashSpec`")]
 pub fn verify_hash(path: &Path, expected: &str) -> io::Result<bool> {
-    let f = File::open(path)?;
-    let mut reader = BufReader::new(f);
-
-    let mut hasher = match expected.len() {
-        40 => HasherEnum::Sha1(Sha1::new()),
-        64 => HasherEnum::Sha256(Sha256::new()),
-        128 => HasherEnum::Sha512(Sha512::new()),
-        _ => HasherEnum::None,
-    };
+    #[allow(deprecated)]
+    match HashSpec::guess(expected) {
+        Some(spec) => verify_file(path, &spec),
+        None => {
+            File::open(path)?;
+            Ok(expected.is_empty())
+        }
+    }
+}
+
+fn guess_spec(hash: &str) -> io::Result<HashSpec> {
+    #[allow(deprecated)]
+    HashSpec::guess(hash).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot detect hash algorithm for {:?}", hash),
+        )
+    })
+}
 
+/// Computes the CurseForge fingerprint of a file.
+///
+/// The fingerprint is the MurmurHash2 (seed 1) of the file contents with all
+/// whitespace bytes removed, as expected by the CurseForge fingerprint API.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to fingerprint.
+///
+/// # Returns
+///
+/// * `io::Result<u32>` - The fingerprint, or an error if reading fails.
+pub fn curseforge_fingerprint(path: &Path) -> io::Result<u32> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Murmur2::new();
     let mut buffer = [0u8; 8192];
     loop {
         let n = reader.read(&mut buffer)?;
@@ -151,12 +615,54 @@ pub fn verify_hash(path: &Path, expected: &str) -> io::Result<bool> {
         }
         hasher.update(&buffer[..n]);
     }
+    Ok(hasher.finalize())
+}
 
-    let actual = hex::encode(hasher.finalize());
-    Ok(actual == expected)
+/// Verifies the stored CRC32 of every entry in a zip archive (such as a jar).
+///
+/// Each entry is decompressed into a sink and its CRC32 compared against the value
+/// recorded in the archive, so corrupt jars can be detected without extracting them.
+///
+/// # Arguments
+///
+/// * `path` - Path to the zip archive to check.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<String>>` - The names of all entries whose CRC32 does not match, or an error if the archive cannot be read.
+pub fn verify_zip_crcs(path: &Path) -> io::Result<Vec<String>> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut corrupt = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let expected = entry.crc32();
+        let mut hasher = Crc32::new();
+        let mut buffer = [0u8; 8192];
+        let intact = loop {
+            match entry.read(&mut buffer) {
+                Ok(0) => break hasher.finalize() == expected,
+                Ok(n) => hasher.update(&buffer[..n]),
+                Err(_) => break false,
+            }
+        };
+        if !intact {
+            corrupt.push(entry.name().to_string());
+        }
+    }
+
+    Ok(corrupt)
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
     use std::fs::{self, File};
@@ -325,6 +831,226 @@ mod tests {
         assert!(verify_hash(&file_path, &sha512).unwrap());
     }
 
+    #[test]
+    fn verify_hash_returns_true_for_md5() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        let content = b"forge-1.12.2.jar";
+        let mut f = File::create(&file_path).unwrap();
+        f.write_all(content).unwrap();
+
+        let md5 = hex::encode(md5::Md5::digest(content));
+        assert_eq!(md5.len(), 32);
+        assert!(verify_hash(&file_path, &md5).unwrap());
+        assert!(!verify_hash(&file_path, "00000000000000000000000000000000").unwrap());
+    }
+
+    #[tokio::test]
+    async fn download_to_file_verifies_md5_hash() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/file.txt");
+            then.status(200).body("maven artifact");
+        });
+
+        let result = download_to_file(
+            &format!("{}/file.txt", server.url("")),
+            file_path.to_str().unwrap(),
+            Some("00000000000000000000000000000000"),
+            true,
+        )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn verify_hash_returns_true_for_crc32() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        let mut f = File::create(&file_path).unwrap();
+        f.write_all(b"123456789").unwrap();
+
+        assert!(verify_hash(&file_path, "cbf43926").unwrap());
+        assert!(!verify_hash(&file_path, "00000000").unwrap());
+    }
+
+    #[test]
+    fn curseforge_fingerprint_hashes_file_without_whitespace() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("mod.jar");
+        fs::write(&file_path, b"jar -\ncontent\r\n").unwrap();
+
+        assert_eq!(curseforge_fingerprint(&file_path).unwrap(), 698878528);
+    }
+
+    #[test]
+    fn hasher_enum_murmur2_returns_big_endian_fingerprint() {
+        let mut hasher = HasherEnum::Murmur2(Murmur2::new());
+        hasher.update(b"jar-content");
+        assert_eq!(hasher.finalize(), 698878528u32.to_be_bytes().to_vec());
+    }
+
+    #[test]
+    fn verify_zip_crcs_reports_no_corruption_for_valid_archive() {
+        let dir = tempdir().unwrap();
+        let zip_path = dir.path().join("mod.jar");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("META-INF/MANIFEST.MF", options).unwrap();
+        writer.write_all(b"Manifest-Version: 1.0\n").unwrap();
+        writer.add_directory("assets/", options).unwrap();
+        writer.finish().unwrap();
+
+        assert!(verify_zip_crcs(&zip_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn verify_zip_crcs_detects_corrupted_entry() {
+        let dir = tempdir().unwrap();
+        let zip_path = dir.path().join("mod.jar");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.start_file("data.txt", options).unwrap();
+        writer.write_all(b"original content").unwrap();
+        writer.finish().unwrap();
+
+        let mut bytes = fs::read(&zip_path).unwrap();
+        let pos = bytes
+            .windows(8)
+            .position(|w| w == b"original")
+            .unwrap();
+        bytes[pos] = b'X';
+        fs::write(&zip_path, bytes).unwrap();
+
+        assert_eq!(verify_zip_crcs(&zip_path).unwrap(), vec!["data.txt"]);
+    }
+
+    #[test]
+    fn verify_zip_crcs_returns_error_for_non_zip_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        fs::write(&file_path, b"not a zip").unwrap();
+        assert!(verify_zip_crcs(&file_path).is_err());
+    }
+
+    #[tokio::test]
+    async fn download_file_verifies_explicit_hash_spec() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        let content = b"hello world";
+        let spec = HashSpec::new(
+            HashAlgorithm::Sha256,
+            hex::encode(sha2::Sha256::digest(content)).to_uppercase(),
+        );
+
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/file.txt");
+            then.status(200).body(content);
+        });
+
+        download_file(
+            &server.url("/file.txt"),
+            file_path.to_str().unwrap(),
+            Some(&spec),
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fs::read(&file_path).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn download_bytes_returns_body_and_verifies_hash() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/icon.png");
+            then.status(200).body(b"\x89PNG");
+        });
+        let url = server.url("/icon.png");
+        let good = HashSpec::new(HashAlgorithm::Sha1, hex::encode(sha1::Sha1::digest(b"\x89PNG")));
+        let bad = HashSpec::new(HashAlgorithm::Sha1, "0".repeat(40));
+
+        assert_eq!(download_bytes(&url, Some(&good)).await.unwrap(), b"\x89PNG");
+        assert!(download_bytes(&url, Some(&bad)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn download_string_decodes_utf8_and_fails_on_http_error() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/manifest.json");
+            then.status(200).body("{\"latest\":{}}");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/missing.json");
+            then.status(404);
+        });
+
+        let body = download_string(&server.url("/manifest.json"), None).await.unwrap();
+        assert_eq!(body, "{\"latest\":{}}");
+        assert!(download_string(&server.url("/missing.json"), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn download_to_file_rejects_unrecognized_hash_length_before_downloading() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method("GET").path("/file.txt");
+            then.status(200).body("content");
+        });
+
+        let result = download_to_file(
+            &server.url("/file.txt"),
+            file_path.to_str().unwrap(),
+            Some("abc"),
+            true,
+        )
+        .await;
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        mock.assert_hits(0);
+    }
+
+    #[test]
+    fn verify_file_distinguishes_crc32_and_murmur2() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        fs::write(&file_path, b"123456789").unwrap();
+
+        let crc = HashSpec::new(HashAlgorithm::Crc32, "cbf43926");
+        let murmur = HashSpec::new(HashAlgorithm::Murmur2, "cbf43926");
+        assert!(verify_file(&file_path, &crc).unwrap());
+        assert!(!verify_file(&file_path, &murmur).unwrap());
+    }
+
+    #[test]
+    fn hash_algorithm_parses_names_and_reports_hex_len() {
+        assert_eq!("SHA-256".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha256);
+        assert_eq!("md5".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Md5);
+        assert!("whirlpool".parse::<HashAlgorithm>().is_err());
+        assert_eq!(HashAlgorithm::Sha512.hex_len(), 128);
+        assert_eq!(HashAlgorithm::Murmur2.to_string(), "murmur2");
+    }
+
+    #[test]
+    fn hash_spec_checks_well_formedness() {
+        assert!(HashSpec::new(HashAlgorithm::Md5, "0".repeat(32)).is_well_formed());
+        assert!(!HashSpec::new(HashAlgorithm::Sha1, "0".repeat(32)).is_well_formed());
+        assert!(!HashSpec::new(HashAlgorithm::Crc32, "zzzzzzzz").is_well_formed());
+        assert_eq!(
+            HashSpec::new(HashAlgorithm::Sha1, "ABCD").to_string(),
+            "sha1:abcd"
+        );
+    }
+
     #[test]
     fn verify_hash_returns_true_when_expected_is_empty_and_file_is_empty() {
         let dir = tempdir().unwrap();
@@ -332,4 +1058,56 @@ mod tests {
         File::create(&file_path).unwrap();
         assert!(verify_hash(&file_path, "").unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn hash_reader_hashes_in_memory_data() {
+        let digest = hash_reader(&b"123456789"[..], HashAlgorithm::Crc32).unwrap();
+        assert_eq!(digest, "cbf43926");
+        let digest = hash_reader(io::Cursor::new(b"hello world"), HashAlgorithm::Sha1).unwrap();
+        assert_eq!(digest, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+    }
+
+    #[tokio::test]
+    async fn verify_hash_async_matches_verify_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        fs::write(&file_path, b"hello world").unwrap();
+
+        let good = HashSpec::new(HashAlgorithm::Sha1, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+        let bad = HashSpec::new(HashAlgorithm::Sha1, "0".repeat(40));
+        assert!(verify_hash_async(&file_path, &good).await.unwrap());
+        assert!(!verify_hash_async(&file_path, &bad).await.unwrap());
+        assert!(verify_hash_async(&dir.path().join("missing"), &good).await.is_err());
+    }
+
+    #[test]
+    fn verify_file_any_passes_if_one_hash_matches() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        fs::write(&file_path, b"hello world").unwrap();
+
+        let sha1 = HashSpec::new(HashAlgorithm::Sha1, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+        let wrong = HashSpec::new(HashAlgorithm::Sha512, "0".repeat(128));
+        assert!(verify_file_any(&file_path, &[wrong.clone(), sha1]).unwrap());
+        assert!(!verify_file_any(&file_path, &[wrong]).unwrap());
+        assert!(!verify_file_any(&file_path, &[]).unwrap());
+    }
+
+    #[test]
+    fn verify_file_check_compares_size_before_hash() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("file.txt");
+        fs::write(&file_path, b"hello world").unwrap();
+        let sha1 = HashSpec::new(HashAlgorithm::Sha1, "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+
+        assert!(verify_file_check(&file_path, &FileCheck::new(11, sha1.clone())).unwrap());
+        assert!(!verify_file_check(&file_path, &FileCheck::new(12, sha1)).unwrap());
+        let size_only = FileCheck {
+            size: Some(11),
+            hash: None,
+        };
+        assert!(verify_file_check(&file_path, &size_only).unwrap());
+        assert!(verify_file_check(&file_path, &FileCheck::default()).unwrap());
+        assert!(verify_file_check(&dir.path().join("missing"), &FileCheck::default()).is_err());
+    }
+}
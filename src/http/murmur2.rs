@@ -0,0 +1,103 @@
+/// Seed used by CurseForge when computing file fingerprints.
+pub const CURSEFORGE_SEED: u32 = 1;
+
+const M: u32 = 0x5bd1_e995;
+const R: u32 = 24;
+
+/// Incremental hasher producing CurseForge-style MurmurHash2 fingerprints.
+///
+/// CurseForge strips whitespace bytes (tab, line feed, carriage return and space)
+/// before hashing. Because MurmurHash2 mixes the input length into its initial
+/// state, the normalized bytes are buffered until [`Murmur2::finalize`] is called.
+#[derive(Debug, Clone, Default)]
+pub struct Murmur2 {
+    buffer: Vec<u8>,
+}
+
+impl Murmur2 {
+    /// Creates an empty hasher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds data into the hasher, dropping whitespace bytes.
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer
+            .extend(data.iter().copied().filter(|b| !is_whitespace(*b)));
+    }
+
+    /// Returns the fingerprint of all data fed so far.
+    pub fn finalize(self) -> u32 {
+        murmur2(&self.buffer, CURSEFORGE_SEED)
+    }
+}
+
+/// Computes the 32-bit MurmurHash2 of `data` with the given seed.
+pub fn murmur2(data: &[u8], seed: u32) -> u32 {
+    let mut h = seed ^ data.len() as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if tail.len() >= 3 {
+        h ^= (tail[2] as u32) << 16;
+    }
+    if tail.len() >= 2 {
+        h ^= (tail[1] as u32) << 8;
+    }
+    if !tail.is_empty() {
+        h ^= tail[0] as u32;
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, 9 | 10 | 13 | 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murmur2_matches_reference_values() {
+        assert_eq!(murmur2(b"", 0), 0);
+        assert_eq!(murmur2(b"", 1), 0x5bd1_5e36);
+        assert_eq!(murmur2(b"hello", 0), 0xe56129cb);
+    }
+
+    #[test]
+    fn fingerprint_ignores_whitespace() {
+        let mut a = Murmur2::new();
+        a.update(b"hello world\r\n\tfoo");
+        let mut b = Murmur2::new();
+        b.update(b"helloworld");
+        b.update(b"foo");
+        assert_eq!(a.finalize(), b.finalize());
+    }
+
+    #[test]
+    fn fingerprint_is_independent_of_chunking() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut whole = Murmur2::new();
+        whole.update(&data);
+        let mut chunked = Murmur2::new();
+        for chunk in data.chunks(7) {
+            chunked.update(chunk);
+        }
+        assert_eq!(whole.finalize(), chunked.finalize());
+    }
+}
@@ -0,0 +1,188 @@
+use super::batch::DownloadRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::NamedTempFile;
+
+/// A line of the session journal.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Entry {
+    /// A request was queued.
+    Add { id: u64, request: DownloadRequest },
+    /// A request finished and does not need to be resumed.
+    Done { id: u64 },
+}
+
+/// An on-disk journal of the requests a `DownloadManager` still has to finish.
+///
+/// The journal is an append-only file with one JSON object per line, so recording
+/// progress stays cheap even for thousands of assets. It is compacted when opened.
+/// Request headers, including `Authorization`, are stored in plain text.
+#[derive(Debug)]
+pub struct DownloadSession {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl DownloadSession {
+    /// Opens or creates the journal at `path`.
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<(DownloadSession, Vec<DownloadRequest>)>` - The session and the
+    ///   unfinished requests of a previous run, in the order they were queued. In the
+    ///   compacted journal they have the ids `0..n`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be read or rewritten. A malformed
+    /// trailing line, as left behind by a crash mid-write, is ignored.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<(Self, Vec<DownloadRequest>)> {
+        let path = path.as_ref().to_path_buf();
+        let pending = match File::open(&path) {
+            Ok(file) => read_pending(file)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        fs::create_dir_all(&dir)?;
+        let mut compacted = NamedTempFile::new_in(&dir)?;
+        for (id, request) in pending.iter().enumerate() {
+            write_entry(
+                compacted.as_file_mut(),
+                &Entry::Add {
+                    id: id as u64,
+                    request: request.clone(),
+                },
+            )?;
+        }
+        compacted.persist(&path).map_err(|e| e.error)?;
+
+        let file = OpenOptions::new().append(true).open(&path)?;
+        Ok((
+            Self {
+                path,
+                file: Mutex::new(file),
+            },
+            pending,
+        ))
+    }
+
+    /// Returns the path of the journal.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records that request `id` was queued.
+    pub(crate) fn record_add(&self, id: u64, request: &DownloadRequest) -> io::Result<()> {
+        write_entry(
+            &mut self.lock(),
+            &Entry::Add {
+                id,
+                request: request.clone(),
+            },
+        )
+    }
+
+    /// Records that request `id` finished.
+    pub(crate) fn record_done(&self, id: u64) -> io::Result<()> {
+        write_entry(&mut self.lock(), &Entry::Done { id })
+    }
+
+    /// Empties the journal, e.g. after everything was downloaded.
+    pub fn clear(&self) -> io::Result<()> {
+        self.lock().set_len(0)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, File> {
+        self.file.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn write_entry(file: &mut File, entry: &Entry) -> io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    // A single write keeps lines intact even if the process dies right after.
+    file.write_all(&line)
+}
+
+fn read_pending(file: File) -> io::Result<Vec<DownloadRequest>> {
+    let mut pending = BTreeMap::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(Entry::Add { id, request }) => {
+                pending.insert(id, request);
+            }
+            Ok(Entry::Done { id }) => {
+                pending.remove(&id);
+            }
+            Err(_) => continue,
+        }
+    }
+    Ok(pending.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reopening_returns_unfinished_requests_in_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        {
+            let (session, pending) = DownloadSession::open(&path).unwrap();
+            assert!(pending.is_empty());
+            session
+                .record_add(0, &DownloadRequest::new("https://a", "a"))
+                .unwrap();
+            session
+                .record_add(1, &DownloadRequest::new("https://b", "b"))
+                .unwrap();
+            session
+                .record_add(2, &DownloadRequest::new("https://c", "c"))
+                .unwrap();
+            session.record_done(1).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"op\":\"done\",\"i").unwrap();
+
+        let (_, pending) = DownloadSession::open(&path).unwrap();
+        let urls: Vec<&str> = pending.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://a", "https://c"]);
+
+        let compacted = fs::read_to_string(&path).unwrap();
+        assert_eq!(compacted.lines().count(), 2);
+    }
+
+    #[test]
+    fn clear_empties_the_journal() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let (session, _) = DownloadSession::open(&path).unwrap();
+        session
+            .record_add(0, &DownloadRequest::new("https://a", "a"))
+            .unwrap();
+        session.clear().unwrap();
+        session
+            .record_add(1, &DownloadRequest::new("https://b", "b"))
+            .unwrap();
+
+        let (_, pending) = DownloadSession::open(&path).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].url, "https://b");
+    }
+}
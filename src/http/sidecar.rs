@@ -0,0 +1,216 @@
+use super::batch::{DownloadRequest, DownloadStatus};
+use super::client::HttpClient;
+use super::error::HttpError;
+use super::{HashAlgorithm, HashSpec};
+use std::io;
+
+/// Sidecar algorithms tried by [`HttpClient::download_with_sidecar`], strongest first.
+pub const SIDECAR_ALGORITHMS: [HashAlgorithm; 4] = [
+    HashAlgorithm::Sha512,
+    HashAlgorithm::Sha256,
+    HashAlgorithm::Sha1,
+    HashAlgorithm::Md5,
+];
+
+/// Returns the URL of the checksum sidecar for `url`, e.g. `lib.jar.sha1`.
+///
+/// Only algorithms with a Maven sidecar convention are supported.
+pub fn sidecar_url(url: &str, algorithm: HashAlgorithm) -> Option<String> {
+    match algorithm {
+        HashAlgorithm::Crc32 | HashAlgorithm::Murmur2 => None,
+        _ => Some(format!("{}.{}", url, algorithm.name())),
+    }
+}
+
+/// Parses the contents of a checksum sidecar file.
+///
+/// Accepts a bare digest (the Maven convention), `sha1sum`-style `<digest>  <file>`
+/// lines and BSD-style `SHA1 (file) = <digest>` lines. Only the first line is used.
+///
+/// # Returns
+///
+/// * `Option<HashSpec>` - The digest, or `None` if the content is not a well formed digest.
+pub fn parse_sidecar(content: &str, algorithm: HashAlgorithm) -> Option<HashSpec> {
+    let line = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .next()?
+        .trim();
+    let digest = match line.rsplit_once(" = ") {
+        Some((_, digest)) => digest.trim(),
+        None => line.split_whitespace().next()?,
+    };
+    let spec = HashSpec::new(algorithm, digest);
+    spec.is_well_formed().then_some(spec)
+}
+
+impl HttpClient {
+    /// Fetches and parses the `algorithm` sidecar of `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the sidecar cannot be fetched, or an `InvalidData` IO
+    /// error if it does not contain a digest.
+    pub async fn fetch_sidecar(
+        &self,
+        url: &str,
+        algorithm: HashAlgorithm,
+    ) -> Result<HashSpec, HttpError> {
+        let sidecar = sidecar_url(url, algorithm).ok_or_else(|| {
+            HttpError::Config(format!("{} has no checksum sidecar convention", algorithm))
+        })?;
+        let body = self.get_bytes(&sidecar).await?;
+        parse_sidecar(&String::from_utf8_lossy(&body), algorithm).ok_or_else(|| {
+            HttpError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed checksum sidecar: {}", sidecar),
+            ))
+        })
+    }
+
+    /// Downloads `request`, verifying it against a checksum sidecar when it has no hash.
+    ///
+    /// Sidecars are tried in [`SIDECAR_ALGORITHMS`] order; a missing sidecar (404)
+    /// moves on to the next algorithm.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if no sidecar is available, or if the download or
+    /// verification fails.
+    pub async fn download_with_sidecar(
+        &self,
+        request: &DownloadRequest,
+        overwrite: bool,
+    ) -> Result<DownloadStatus, HttpError> {
+        // Offline, the sidecars cannot be fetched; fall back to keeping a present file.
+        if !request.hashes.is_empty() || self.is_offline() {
+            return self.download(request, overwrite).await;
+        }
+
+        let mut last_error = None;
+        for algorithm in SIDECAR_ALGORITHMS {
+            match self.fetch_sidecar(&request.url, algorithm).await {
+                Ok(spec) => {
+                    let request = request.clone().with_hash(spec);
+                    return self.download(&request, overwrite).await;
+                }
+                Err(e @ HttpError::Status { status: 404, .. }) => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.expect("SIDECAR_ALGORITHMS is not empty"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const HELLO_SHA1: &str = "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed";
+
+    #[test]
+    fn parses_common_sidecar_formats() {
+        let bare = parse_sidecar(&format!("{}\n", HELLO_SHA1), HashAlgorithm::Sha1).unwrap();
+        assert_eq!(bare.hex, HELLO_SHA1);
+        let gnu = parse_sidecar(
+            &format!("{}  forge-1.20.1.jar", HELLO_SHA1.to_uppercase()),
+            HashAlgorithm::Sha1,
+        )
+        .unwrap();
+        assert_eq!(gnu.hex, HELLO_SHA1);
+        let bsd = parse_sidecar(
+            &format!("SHA1 (forge.jar) = {}", HELLO_SHA1),
+            HashAlgorithm::Sha1,
+        )
+        .unwrap();
+        assert_eq!(bsd.hex, HELLO_SHA1);
+        assert!(parse_sidecar("<html>not found</html>", HashAlgorithm::Sha1).is_none());
+        assert!(parse_sidecar(HELLO_SHA1, HashAlgorithm::Md5).is_none());
+    }
+
+    #[test]
+    fn sidecar_url_appends_extension() {
+        assert_eq!(
+            sidecar_url("https://maven.example/a.jar", HashAlgorithm::Sha256).unwrap(),
+            "https://maven.example/a.jar.sha256"
+        );
+        assert!(sidecar_url("https://maven.example/a.jar", HashAlgorithm::Crc32).is_none());
+    }
+
+    #[tokio::test]
+    async fn download_with_sidecar_falls_back_to_sha1() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar");
+            then.status(200).body("hello world");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar.sha1");
+            then.status(200).body(HELLO_SHA1);
+        });
+        server.mock(|when, then| {
+            when.method("GET");
+            then.status(404);
+        });
+
+        let path = dir.path().join("lib.jar");
+        let status = HttpClient::default()
+            .download_with_sidecar(&DownloadRequest::new(server.url("/lib.jar"), &path), false)
+            .await
+            .unwrap();
+
+        assert_eq!(status, DownloadStatus::Downloaded { bytes: 11 });
+        assert_eq!(fs::read(&path).unwrap(), b"hello world");
+    }
+
+    #[tokio::test]
+    async fn download_with_sidecar_rejects_mismatching_artifact() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar");
+            then.status(200).body("tampered");
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar.sha512");
+            then.status(404);
+        });
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar.sha256");
+            then.status(200)
+                .body("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+        });
+
+        let path = dir.path().join("lib.jar");
+        let result = HttpClient::default()
+            .download_with_sidecar(&DownloadRequest::new(server.url("/lib.jar"), &path), false)
+            .await;
+
+        assert!(matches!(result, Err(HttpError::HashMismatch { .. })));
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn download_with_sidecar_fails_without_any_sidecar() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/lib.jar");
+            then.status(200).body("x");
+        });
+        server.mock(|when, then| {
+            when.method("GET");
+            then.status(404);
+        });
+
+        let path = dir.path().join("lib.jar");
+        let result = HttpClient::default()
+            .download_with_sidecar(&DownloadRequest::new(server.url("/lib.jar"), &path), false)
+            .await;
+
+        assert!(matches!(result, Err(HttpError::Status { status: 404, .. })));
+    }
+}
@@ -0,0 +1,136 @@
+use super::error::HttpError;
+use reqwest::Certificate;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// TLS settings for an `HttpClient`.
+///
+/// Certificates are given as PEM (a single certificate or a bundle) or DER bytes.
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    /// Root certificates trusted in addition to the system roots, e.g. the CA of a
+    /// TLS-intercepting corporate proxy.
+    pub extra_roots: Vec<Vec<u8>>,
+    /// If non-empty, only these certificates are trusted and the system roots are
+    /// ignored. Intended for a dedicated client used with authentication endpoints.
+    pub pinned_roots: Vec<Vec<u8>>,
+}
+
+impl TlsOptions {
+    /// Reads a PEM or DER certificate file and adds it to `extra_roots`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn add_root_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.extra_roots.push(fs::read(path)?);
+        Ok(())
+    }
+
+    /// Reads a PEM or DER certificate file and adds it to `pinned_roots`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn add_pinned_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.pinned_roots.push(fs::read(path)?);
+        Ok(())
+    }
+
+    /// Applies the settings to a client builder.
+    pub(crate) fn apply(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, HttpError> {
+        if !self.pinned_roots.is_empty() {
+            builder = builder.tls_built_in_root_certs(false);
+        }
+        for data in self.extra_roots.iter().chain(&self.pinned_roots) {
+            for certificate in parse_certificates(data)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(builder)
+    }
+}
+
+/// Parses PEM (possibly several certificates) or DER encoded certificate data.
+fn parse_certificates(data: &[u8]) -> Result<Vec<Certificate>, HttpError> {
+    let is_pem = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .is_some_and(|start| data[start..].starts_with(b"-----BEGIN"));
+    let certificates = if is_pem {
+        Certificate::from_pem_bundle(data)
+    } else {
+        Certificate::from_der(data).map(|c| vec![c])
+    };
+    match certificates {
+        Ok(certificates) if !certificates.is_empty() => Ok(certificates),
+        Ok(_) => Err(HttpError::Config("no certificate found".to_string())),
+        Err(e) => Err(HttpError::Config(format!("invalid certificate: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{ClientOptions, HttpClient};
+
+    const TEST_ROOT: &str = "-----BEGIN CERTIFICATE-----
+MIIBjDCCATGgAwIBAgIUNjG4e22XuPItu6ZpdAIYRojCzzwwCgYIKoZIzj0EAwIw
+GjEYMBYGA1UEAwwPanVuY28tdGVzdC1yb290MCAXDTI2MTAxNjA5MzAzMFoYDzIx
+MjYwOTIyMDkzMDMwWjAaMRgwFgYDVQQDDA9qdW5jby10ZXN0LXJvb3QwWTATBgcq
+hkjOPQIBBggqhkjOPQMBBwNCAATW0/0waQaq0wTprvRPyO60xFDpcKZWsm8bCMaB
+zmBcsbl0bkfaOEfyKDeI3bSSYinSRRaZux9l75yycQ39EvHmo1MwUTAdBgNVHQ4E
+FgQUDujJ41IYtrFnmFcK0S+l99P4FKkwHwYDVR0jBBgwFoAUDujJ41IYtrFnmFcK
+0S+l99P4FKkwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNJADBGAiEAnMWc
+iRKO9tUj4j837lLGjWdJczkZY5CM8bNZSB6Eu9kCIQCcen01BrZhvmhMc9UVt9YW
+za2q8KQRK7FNb9hX7X4w9w==
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn parses_pem_bundles() {
+        let bundle = format!("{}{}", TEST_ROOT, TEST_ROOT);
+        assert_eq!(parse_certificates(bundle.as_bytes()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_certificates() {
+        assert!(matches!(
+            parse_certificates(b"not a certificate"),
+            Err(HttpError::Config(_))
+        ));
+        assert!(matches!(
+            parse_certificates(b"-----BEGIN CERTIFICATE-----\n-----END CERTIFICATE-----\n"),
+            Err(HttpError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn client_builds_with_extra_and_pinned_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proxy-ca.pem");
+        fs::write(&path, TEST_ROOT).unwrap();
+        let mut tls = TlsOptions::default();
+        tls.add_root_file(&path).unwrap();
+        tls.add_pinned_file(&path).unwrap();
+
+        let client = HttpClient::new(ClientOptions {
+            tls,
+            ..ClientOptions::default()
+        });
+        assert!(client.is_ok());
+
+        let broken = HttpClient::new(ClientOptions {
+            tls: TlsOptions {
+                extra_roots: vec![b"garbage".to_vec()],
+                ..TlsOptions::default()
+            },
+            ..ClientOptions::default()
+        });
+        assert!(matches!(broken, Err(HttpError::Config(_))));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::filesystem::{FilesystemError, write_atomic};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// File name of the instance configuration inside an instance directory.
+pub const INSTANCE_CONFIG_FILE: &str = "instance.json";
+
+/// Schema version written by this crate.
+pub const INSTANCE_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a configuration from one schema version to the next.
+pub type Migration = fn(&mut Map<String, Value>);
+
+/// Migrations from each schema version to the next; entry `i` upgrades schema
+/// `i + 1` to `i + 2`.
+const MIGRATIONS: &[Migration] = &[];
+
+const _: () = assert!(MIGRATIONS.len() as u32 + 1 == INSTANCE_SCHEMA_VERSION);
+
+/// Error returned when an instance configuration cannot be read or written.
+#[derive(Debug, Error)]
+pub enum InstanceConfigError {
+    #[error("failed to read instance config: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write instance config: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid instance config: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("instance config schema {0} is newer than the supported schema")]
+    UnsupportedSchema(u32),
+}
+
+/// Mod loaders an instance can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceLoader {
+    /// Fabric.
+    Fabric,
+    /// Quilt.
+    Quilt,
+    /// Forge.
+    Forge,
+    /// NeoForge.
+    NeoForge,
+}
+
+/// The mod loader of an instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoaderConfig {
+    /// The loader.
+    pub kind: InstanceLoader,
+    /// The loader version, e.g. `0.16.9` or `47.3.0`.
+    pub version: String,
+}
+
+/// Java settings of an instance.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaSettings {
+    /// Java executable. `None` picks a matching installation automatically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<PathBuf>,
+    /// Additional JVM arguments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+}
+
+/// Heap sizes of an instance, in MiB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemorySettings {
+    /// Initial heap size (`-Xms`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_mb: Option<u32>,
+    /// Maximum heap size (`-Xmx`).
+    pub max_mb: u32,
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            min_mb: None,
+            max_mb: 2048,
+        }
+    }
+}
+
+/// Commands run around the game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchHooks {
+    /// Run before the game starts; a failure cancels the launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_launch: Option<String>,
+    /// Run after the game exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_exit: Option<String>,
+    /// Command the java invocation is passed to, e.g. `gamemoderun`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<String>,
+}
+
+/// The configuration of an instance, stored as `instance.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceConfig {
+    /// Schema version of the file.
+    pub schema_version: u32,
+    /// Display name.
+    pub name: String,
+    /// The Minecraft version.
+    pub game_version: String,
+    /// The mod loader, `None` for vanilla.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loader: Option<LoaderConfig>,
+    /// Java settings.
+    #[serde(default)]
+    pub java: JavaSettings,
+    /// Heap sizes.
+    #[serde(default)]
+    pub memory: MemorySettings,
+    /// Commands run around the game.
+    #[serde(default)]
+    pub hooks: LaunchHooks,
+    /// `options.txt` values applied before every launch, keyed by option name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub option_overrides: BTreeMap<String, String>,
+    /// Fields written by newer versions or other tools.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl InstanceConfig {
+    /// Creates a vanilla instance configuration with default settings.
+    pub fn new(name: impl Into<String>, game_version: impl Into<String>) -> Self {
+        Self {
+            schema_version: INSTANCE_SCHEMA_VERSION,
+            name: name.into(),
+            game_version: game_version.into(),
+            loader: None,
+            java: JavaSettings::default(),
+            memory: MemorySettings::default(),
+            hooks: LaunchHooks::default(),
+            option_overrides: BTreeMap::new(),
+            extra: Map::new(),
+        }
+    }
+
+    /// Sets the mod loader.
+    pub fn with_loader(mut self, kind: InstanceLoader, version: impl Into<String>) -> Self {
+        self.loader = Some(LoaderConfig {
+            kind,
+            version: version.into(),
+        });
+        self
+    }
+
+    /// Sets the maximum heap size in MiB.
+    pub fn with_max_memory(mut self, max_mb: u32) -> Self {
+        self.memory.max_mb = max_mb;
+        self
+    }
+
+    /// Parses a configuration, migrating older schema versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is malformed or uses a newer schema.
+    pub fn from_json(json: &str) -> Result<Self, InstanceConfigError> {
+        let value = migrate(serde_json::from_str(json)?, MIGRATIONS)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Reads the configuration from `path`, migrating older schema versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is malformed, or uses a
+    /// newer schema.
+    pub fn load(path: &Path) -> Result<Self, InstanceConfigError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Writes the configuration to `path` atomically, with the current schema
+    /// version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), InstanceConfigError> {
+        let mut config = self.clone();
+        config.schema_version = INSTANCE_SCHEMA_VERSION;
+        write_atomic(path, &serde_json::to_vec_pretty(&config)?)?;
+        Ok(())
+    }
+}
+
+/// Applies the migrations needed to bring `value` to the latest schema.
+///
+/// A missing `schemaVersion` counts as schema 1.
+fn migrate(mut value: Value, migrations: &[Migration]) -> Result<Value, InstanceConfigError> {
+    let latest = migrations.len() as u32 + 1;
+    let Some(object) = value.as_object_mut() else {
+        return Ok(value);
+    };
+    let version = object
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(1) as u32;
+    if version > latest {
+        return Err(InstanceConfigError::UnsupportedSchema(version));
+    }
+    for migration in &migrations[version.saturating_sub(1) as usize..] {
+        migration(object);
+    }
+    object.insert("schemaVersion".to_string(), latest.into());
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn saves_and_loads_config() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(INSTANCE_CONFIG_FILE);
+        let mut config = InstanceConfig::new("Survival", "1.20.1")
+            .with_loader(InstanceLoader::Fabric, "0.16.9")
+            .with_max_memory(6144);
+        config.hooks.wrapper = Some("gamemoderun".to_string());
+        config
+            .option_overrides
+            .insert("fov".to_string(), "0.5".to_string());
+
+        config.save(&path).unwrap();
+        let saved: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved["schemaVersion"], INSTANCE_SCHEMA_VERSION);
+        assert_eq!(saved["loader"]["kind"], "fabric");
+        assert_eq!(InstanceConfig::load(&path).unwrap(), config);
+
+        assert!(matches!(
+            InstanceConfig::from_json(
+                r#"{"schemaVersion": 99, "name": "x", "gameVersion": "1.0"}"#
+            ),
+            Err(InstanceConfigError::UnsupportedSchema(99))
+        ));
+    }
+
+    #[test]
+    fn migrates_older_schemas_in_order() {
+        fn rename_minecraft(config: &mut Map<String, Value>) {
+            if let Some(version) = config.remove("minecraft") {
+                config.insert("gameVersion".to_string(), version);
+            }
+        }
+        fn memory_object(config: &mut Map<String, Value>) {
+            if let Some(max) = config.remove("memory").filter(Value::is_u64) {
+                config.insert("memory".to_string(), serde_json::json!({ "maxMb": max }));
+            }
+        }
+        let migrations: &[Migration] = &[rename_minecraft, memory_object];
+
+        let value = migrate(
+            serde_json::json!({"name": "Old", "minecraft": "1.12.2", "memory": 4096}),
+            migrations,
+        )
+        .unwrap();
+        let config: InstanceConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(config.schema_version, 3);
+        assert_eq!(config.game_version, "1.12.2");
+        assert_eq!(config.memory.max_mb, 4096);
+
+        let current = migrate(
+            serde_json::json!({"schemaVersion": 3, "name": "New", "gameVersion": "1.21", "memory": 1}),
+            migrations,
+        )
+        .unwrap();
+        assert_eq!(current["memory"], 1);
+    }
+}
@@ -0,0 +1,473 @@
+use super::saves::{LEVEL_DAT, is_locked};
+use crate::json::parse_json_lenient;
+use crate::nbt::{NbtCompound, NbtError, NbtFile, NbtValue};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::ZipArchive;
+
+/// Folder of a world holding its datapacks.
+pub const DATAPACKS_DIR: &str = "datapacks";
+/// Prefix of the ids of packs in the world's `datapacks` folder.
+const FILE_PREFIX: &str = "file/";
+/// The pack with the game's own data, which cannot be disabled.
+const VANILLA: &str = "vanilla";
+
+/// Error returned when the datapacks of a world cannot be read or changed.
+#[derive(Debug, Error)]
+pub enum DatapackError {
+    #[error("failed to access world: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid level.dat: {0}")]
+    Nbt(#[from] NbtError),
+    #[error("level.dat has no Data compound")]
+    MissingData,
+    #[error("unknown datapack: {0}")]
+    UnknownPack(String),
+    #[error("the vanilla datapack cannot be disabled")]
+    Required,
+    #[error("the world is open in the game")]
+    WorldOpen,
+}
+
+/// Whether a datapack is used by the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DatapackState {
+    Enabled,
+    Disabled,
+    /// In the `datapacks` folder but not yet in `level.dat`. The game
+    /// enables such packs the next time the world is loaded.
+    New,
+}
+
+/// A problem with a datapack in the `datapacks` folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatapackProblem {
+    /// `level.dat` lists the pack but its file is gone.
+    Missing,
+    /// The zip cannot be opened.
+    InvalidZip,
+    /// There is no `pack.mcmeta` at the root, as when a folder holding the
+    /// pack was zipped instead of its content.
+    NoMcmeta,
+    /// `pack.mcmeta` has no `pack.pack_format`.
+    InvalidMcmeta,
+    /// There is no `data` folder, as in resource packs.
+    NoDataFolder,
+}
+
+/// A datapack of a world.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datapack {
+    /// The id in `level.dat`, e.g. `vanilla`, `fabric` or `file/Terralith.zip`.
+    pub id: String,
+    pub state: DatapackState,
+    /// The zip or folder, for packs in the `datapacks` folder.
+    pub path: Option<PathBuf>,
+    pub pack_format: Option<u32>,
+    /// The description, or the raw JSON of a text component.
+    pub description: Option<String>,
+    pub problems: Vec<DatapackProblem>,
+}
+
+impl Datapack {
+    /// Returns the name to display: the file name for packs in the
+    /// `datapacks` folder, the id otherwise.
+    pub fn display_name(&self) -> &str {
+        self.id.strip_prefix(FILE_PREFIX).unwrap_or(&self.id)
+    }
+}
+
+/// The datapack lists of a world's `level.dat`, for enabling and disabling
+/// packs.
+///
+/// Later entries of [`WorldDatapacks::enabled`] take precedence, the way the
+/// game orders them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldDatapacks {
+    pub world: PathBuf,
+    /// The ids of the enabled packs, lowest priority first.
+    pub enabled: Vec<String>,
+    /// The ids of the disabled packs.
+    pub disabled: Vec<String>,
+    level: NbtFile,
+}
+
+impl WorldDatapacks {
+    /// Reads the datapack lists of a world.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The folder of the world, holding its `level.dat`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<WorldDatapacks, DatapackError>` - The lists, empty for worlds
+    ///   from before 1.13.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `level.dat` cannot be read or has no `Data`.
+    pub fn load(world: &Path) -> Result<Self, DatapackError> {
+        let level = NbtFile::read(&world.join(LEVEL_DAT))?;
+        let packs = level
+            .root
+            .get("Data")
+            .ok_or(DatapackError::MissingData)?
+            .get("DataPacks");
+        let ids = |key: &str| -> Vec<String> {
+            packs
+                .and_then(|packs| packs.get(key))
+                .and_then(NbtValue::as_list)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|id| id.as_str().map(str::to_string))
+                .collect()
+        };
+        Ok(Self {
+            world: world.to_path_buf(),
+            enabled: ids("Enabled"),
+            disabled: ids("Disabled"),
+            level,
+        })
+    }
+
+    /// Lists the packs of `level.dat` and of the `datapacks` folder, enabled
+    /// packs first in priority order, and checks the files of the folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `datapacks` folder cannot be read.
+    pub fn list(&self) -> io::Result<Vec<Datapack>> {
+        let mut packs: Vec<Datapack> = self
+            .enabled
+            .iter()
+            .map(|id| (id, DatapackState::Enabled))
+            .chain(self.disabled.iter().map(|id| (id, DatapackState::Disabled)))
+            .map(|(id, state)| {
+                let path = id
+                    .strip_prefix(FILE_PREFIX)
+                    .map(|name| self.world.join(DATAPACKS_DIR).join(name));
+                let mut pack = Datapack {
+                    id: id.clone(),
+                    state,
+                    path,
+                    pack_format: None,
+                    description: None,
+                    problems: Vec::new(),
+                };
+                inspect(&mut pack);
+                pack
+            })
+            .collect();
+
+        let entries = match fs::read_dir(self.world.join(DATAPACKS_DIR)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(packs),
+            Err(e) => return Err(e),
+        };
+        let mut new = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_zip = name.to_ascii_lowercase().ends_with(".zip");
+            if !entry.file_type()?.is_dir() && !is_zip {
+                continue;
+            }
+            let id = format!("{}{}", FILE_PREFIX, name);
+            if self.enabled.contains(&id) || self.disabled.contains(&id) {
+                continue;
+            }
+            let mut pack = Datapack {
+                id,
+                state: DatapackState::New,
+                path: Some(entry.path()),
+                pack_format: None,
+                description: None,
+                problems: Vec::new(),
+            };
+            inspect(&mut pack);
+            new.push(pack);
+        }
+        new.sort_by(|a, b| a.id.cmp(&b.id));
+        packs.extend(new);
+        Ok(packs)
+    }
+
+    /// Enables a pack with the highest priority, or raises it to the highest
+    /// priority if it is enabled already.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatapackError::UnknownPack`] if the pack is neither in
+    /// `level.dat` nor in the `datapacks` folder.
+    pub fn enable(&mut self, id: &str) -> Result<(), DatapackError> {
+        if !self.is_known(id) {
+            return Err(DatapackError::UnknownPack(id.to_string()));
+        }
+        self.disabled.retain(|pack| pack != id);
+        self.enabled.retain(|pack| pack != id);
+        self.enabled.push(id.to_string());
+        Ok(())
+    }
+
+    /// Disables a pack.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatapackError::Required`] for the vanilla pack, or
+    /// [`DatapackError::UnknownPack`] if the pack does not exist.
+    pub fn disable(&mut self, id: &str) -> Result<(), DatapackError> {
+        if id == VANILLA {
+            return Err(DatapackError::Required);
+        }
+        if !self.is_known(id) {
+            return Err(DatapackError::UnknownPack(id.to_string()));
+        }
+        self.enabled.retain(|pack| pack != id);
+        if !self.disabled.iter().any(|pack| pack == id) {
+            self.disabled.push(id.to_string());
+        }
+        Ok(())
+    }
+
+    fn is_known(&self, id: &str) -> bool {
+        self.enabled
+            .iter()
+            .chain(&self.disabled)
+            .any(|pack| pack == id)
+            || id
+                .strip_prefix(FILE_PREFIX)
+                .is_some_and(|name| self.world.join(DATAPACKS_DIR).join(name).exists())
+    }
+
+    /// Writes the lists to `level.dat`, keeping the previous file as
+    /// `level.dat_old` as the game does.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatapackError::WorldOpen`] if the game has the world open,
+    /// since it would overwrite the change, or an error if `level.dat`
+    /// cannot be written.
+    pub fn save(&mut self) -> Result<(), DatapackError> {
+        if is_locked(&self.world.join("session.lock"))? {
+            return Err(DatapackError::WorldOpen);
+        }
+        let list = |ids: &[String]| {
+            NbtValue::List(ids.iter().map(|id| NbtValue::String(id.clone())).collect())
+        };
+        let data = self
+            .level
+            .root
+            .as_compound_mut()
+            .and_then(|root| root.get_mut("Data"))
+            .and_then(NbtValue::as_compound_mut)
+            .ok_or(DatapackError::MissingData)?;
+        let packs = data
+            .entry("DataPacks".to_string())
+            .or_insert_with(|| NbtValue::Compound(NbtCompound::new()));
+        if let Some(packs) = packs.as_compound_mut() {
+            packs.insert("Enabled".to_string(), list(&self.enabled));
+            packs.insert("Disabled".to_string(), list(&self.disabled));
+        }
+        let level_dat = self.world.join(LEVEL_DAT);
+        fs::copy(&level_dat, self.world.join("level.dat_old"))?;
+        self.level.write(&level_dat)?;
+        Ok(())
+    }
+}
+
+/// Reads the metadata of a pack in the `datapacks` folder and records its
+/// problems. Built-in and mod packs are left as they are.
+fn inspect(pack: &mut Datapack) {
+    let Some(path) = &pack.path else {
+        return;
+    };
+    let (mcmeta, has_data) = if path.is_dir() {
+        (
+            fs::read_to_string(path.join("pack.mcmeta")).ok(),
+            path.join("data").is_dir(),
+        )
+    } else if path.is_file() {
+        let Ok(mut archive) = File::open(path)
+            .map_err(zip::result::ZipError::from)
+            .and_then(|file| ZipArchive::new(BufReader::new(file)))
+        else {
+            pack.problems.push(DatapackProblem::InvalidZip);
+            return;
+        };
+        let has_data = archive.file_names().any(|name| name.starts_with("data/"));
+        let mut content = String::new();
+        let read = archive
+            .by_name("pack.mcmeta")
+            .ok()
+            .is_some_and(|mut file| file.read_to_string(&mut content).is_ok());
+        (read.then_some(content), has_data)
+    } else {
+        pack.problems.push(DatapackProblem::Missing);
+        return;
+    };
+
+    match mcmeta {
+        None => pack.problems.push(DatapackProblem::NoMcmeta),
+        Some(content) => {
+            let mcmeta: Value = parse_json_lenient(&content).unwrap_or_default();
+            pack.pack_format = mcmeta["pack"]["pack_format"]
+                .as_u64()
+                .and_then(|format| format.try_into().ok());
+            pack.description = match &mcmeta["pack"]["description"] {
+                Value::Null => None,
+                Value::String(text) => Some(text.clone()),
+                component => Some(component.to_string()),
+            };
+            if pack.pack_format.is_none() {
+                pack.problems.push(DatapackProblem::InvalidMcmeta);
+            }
+        }
+    }
+    if !has_data {
+        pack.problems.push(DatapackProblem::NoDataFolder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::NbtCompression;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_level(world: &Path, enabled: &[&str], disabled: &[&str]) {
+        let list = |ids: &[&str]| {
+            NbtValue::List(
+                ids.iter()
+                    .map(|id| NbtValue::String(id.to_string()))
+                    .collect(),
+            )
+        };
+        let mut packs = NbtCompound::new();
+        packs.insert("Enabled".to_string(), list(enabled));
+        packs.insert("Disabled".to_string(), list(disabled));
+        let mut data = NbtCompound::new();
+        data.insert("DataPacks".to_string(), NbtValue::Compound(packs));
+        data.insert(
+            "LevelName".to_string(),
+            NbtValue::String("Test".to_string()),
+        );
+        let mut root = NbtCompound::new();
+        root.insert("Data".to_string(), NbtValue::Compound(data));
+        NbtFile::new(root, NbtCompression::Gzip)
+            .write(&world.join(LEVEL_DAT))
+            .unwrap();
+    }
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn lists_and_validates_datapacks() {
+        let dir = tempdir().unwrap();
+        let world = dir.path();
+        let datapacks = world.join(DATAPACKS_DIR);
+        fs::create_dir(&datapacks).unwrap();
+        write_level(
+            world,
+            &["vanilla", "file/gone.zip", "file/Terralith.zip"],
+            &["bundle"],
+        );
+        write_zip(
+            &datapacks.join("Terralith.zip"),
+            &[
+                (
+                    "pack.mcmeta",
+                    r#"{"pack": {"pack_format": 48, "description": "Terrain"}}"#,
+                ),
+                ("data/terralith/worldgen/a.json", "{}"),
+            ],
+        );
+        write_zip(
+            &datapacks.join("wrapped.zip"),
+            &[("Pack/pack.mcmeta", "{}")],
+        );
+        fs::create_dir_all(datapacks.join("folder/data")).unwrap();
+        fs::write(
+            datapacks.join("folder/pack.mcmeta"),
+            r#"{"pack": {"pack_format": 15, "description": {"text": "Folder"}}}"#,
+        )
+        .unwrap();
+
+        let packs = WorldDatapacks::load(world).unwrap().list().unwrap();
+        let ids: Vec<(&str, DatapackState)> =
+            packs.iter().map(|p| (p.id.as_str(), p.state)).collect();
+        assert_eq!(
+            ids,
+            [
+                ("vanilla", DatapackState::Enabled),
+                ("file/gone.zip", DatapackState::Enabled),
+                ("file/Terralith.zip", DatapackState::Enabled),
+                ("bundle", DatapackState::Disabled),
+                ("file/folder", DatapackState::New),
+                ("file/wrapped.zip", DatapackState::New),
+            ]
+        );
+        assert!(packs[0].problems.is_empty() && packs[0].path.is_none());
+        assert_eq!(packs[1].problems, [DatapackProblem::Missing]);
+        assert_eq!(packs[2].pack_format, Some(48));
+        assert_eq!(packs[2].description.as_deref(), Some("Terrain"));
+        assert_eq!(
+            packs[4].description.as_deref(),
+            Some(r#"{"text":"Folder"}"#)
+        );
+        assert_eq!(
+            packs[5].problems,
+            [DatapackProblem::NoMcmeta, DatapackProblem::NoDataFolder]
+        );
+        assert_eq!(packs[5].display_name(), "wrapped.zip");
+    }
+
+    #[test]
+    fn enables_and_disables_packs_in_level_dat() {
+        let dir = tempdir().unwrap();
+        let world = dir.path();
+        fs::create_dir_all(world.join("datapacks/extra")).unwrap();
+        write_level(world, &["vanilla", "file/a.zip"], &["bundle"]);
+
+        let mut packs = WorldDatapacks::load(world).unwrap();
+        packs.disable("file/a.zip").unwrap();
+        packs.enable("bundle").unwrap();
+        packs.enable("file/extra").unwrap();
+        assert!(matches!(
+            packs.disable("vanilla"),
+            Err(DatapackError::Required)
+        ));
+        assert!(matches!(
+            packs.enable("file/none.zip"),
+            Err(DatapackError::UnknownPack(_))
+        ));
+        packs.save().unwrap();
+
+        let saved = WorldDatapacks::load(world).unwrap();
+        assert_eq!(saved.enabled, ["vanilla", "bundle", "file/extra"]);
+        assert_eq!(saved.disabled, ["file/a.zip"]);
+        assert!(world.join("level.dat_old").is_file());
+        let level = NbtFile::read(&world.join(LEVEL_DAT)).unwrap();
+        assert_eq!(
+            level
+                .root
+                .get_path("Data.LevelName")
+                .and_then(NbtValue::as_str),
+            Some("Test")
+        );
+    }
+}
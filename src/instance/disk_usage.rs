@@ -0,0 +1,249 @@
+use super::saves::dir_size;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A group of files in a game directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UsageCategory {
+    Mods,
+    Saves,
+    ResourcePacks,
+    ShaderPacks,
+    Screenshots,
+    Logs,
+    Config,
+    CrashReports,
+    /// Caches kept by the game and by mods, such as `.cache` or `.fabric`.
+    Cache,
+    /// Everything else.
+    Other,
+}
+
+impl UsageCategory {
+    /// All categories, in the order a launcher lists them.
+    pub const ALL: [UsageCategory; 10] = [
+        UsageCategory::Mods,
+        UsageCategory::Saves,
+        UsageCategory::ResourcePacks,
+        UsageCategory::ShaderPacks,
+        UsageCategory::Screenshots,
+        UsageCategory::Logs,
+        UsageCategory::Config,
+        UsageCategory::CrashReports,
+        UsageCategory::Cache,
+        UsageCategory::Other,
+    ];
+
+    /// Returns the category of a top-level entry of a game directory.
+    pub fn of(name: &str) -> Self {
+        match name {
+            "mods" | "coremods" => UsageCategory::Mods,
+            "saves" => UsageCategory::Saves,
+            "resourcepacks" | "texturepacks" => UsageCategory::ResourcePacks,
+            "shaderpacks" => UsageCategory::ShaderPacks,
+            "screenshots" => UsageCategory::Screenshots,
+            "logs" => UsageCategory::Logs,
+            "config" | "defaultconfigs" => UsageCategory::Config,
+            "crash-reports" => UsageCategory::CrashReports,
+            ".cache" | ".fabric" | ".quilt" | ".mixin.out" | "webcache" | "webcache2" => {
+                UsageCategory::Cache
+            }
+            _ => UsageCategory::Other,
+        }
+    }
+
+    /// Returns whether the files can be deleted without losing anything the
+    /// player made or configured, to suggest as cleanups.
+    pub fn is_disposable(self) -> bool {
+        matches!(
+            self,
+            UsageCategory::Logs | UsageCategory::CrashReports | UsageCategory::Cache
+        )
+    }
+}
+
+/// A file or folder and its size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageEntry {
+    pub category: UsageCategory,
+    pub path: PathBuf,
+    /// Size in bytes; for a folder, of all files below it.
+    pub size: u64,
+}
+
+/// The result of [`disk_usage_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskUsageReport {
+    /// Size of the whole game directory in bytes.
+    pub total: u64,
+    /// Size per category, in the order of [`UsageCategory::ALL`], without
+    /// empty categories.
+    pub categories: Vec<(UsageCategory, u64)>,
+    /// The entries of every category, largest first: each mod, world, pack,
+    /// log or screenshot, and each top-level entry of the other categories.
+    pub entries: Vec<UsageEntry>,
+}
+
+impl DiskUsageReport {
+    /// Returns the size of a category in bytes.
+    pub fn size(&self, category: UsageCategory) -> u64 {
+        self.categories
+            .iter()
+            .find(|(c, _)| *c == category)
+            .map_or(0, |(_, size)| *size)
+    }
+
+    /// Returns the `count` largest entries.
+    pub fn largest(&self, count: usize) -> &[UsageEntry] {
+        &self.entries[..count.min(self.entries.len())]
+    }
+
+    /// Returns the number of bytes freed by deleting the disposable
+    /// categories.
+    pub fn reclaimable(&self) -> u64 {
+        self.categories
+            .iter()
+            .filter(|(category, _)| category.is_disposable())
+            .map(|(_, size)| size)
+            .sum()
+    }
+}
+
+/// Measures the disk usage of a game directory by category.
+///
+/// Symbolic links are not followed, so files linked from a shared store are
+/// not counted.
+///
+/// # Arguments
+///
+/// * `instance_dir` - The game directory of the instance.
+///
+/// # Returns
+///
+/// * `DiskUsageReport` - The sizes per category and the largest entries. A
+///   missing directory gives an empty report.
+///
+/// # Errors
+///
+/// Returns an error if a folder cannot be read.
+pub fn disk_usage_report(instance_dir: &Path) -> io::Result<DiskUsageReport> {
+    let mut report = DiskUsageReport::default();
+    let top_level = match fs::read_dir(instance_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(report),
+        Err(e) => return Err(e),
+    };
+    let mut sizes = [0u64; UsageCategory::ALL.len()];
+    for entry in top_level {
+        let entry = entry?;
+        let category = UsageCategory::of(&entry.file_name().to_string_lossy());
+        let file_type = entry.file_type()?;
+        // Folders whose entries are listed one by one; other folders are
+        // listed as a whole.
+        let listed = file_type.is_dir() && category != UsageCategory::Other;
+        let children: Vec<(PathBuf, u64)> = if listed {
+            let mut children = Vec::new();
+            for child in fs::read_dir(entry.path())? {
+                let child = child?;
+                if let Some(size) = entry_size(&child.path(), child.file_type()?)? {
+                    children.push((child.path(), size));
+                }
+            }
+            children
+        } else {
+            entry_size(&entry.path(), file_type)?
+                .map(|size| (entry.path(), size))
+                .into_iter()
+                .collect()
+        };
+        for (path, size) in children {
+            sizes[category as usize] += size;
+            report.entries.push(UsageEntry {
+                category,
+                path,
+                size,
+            });
+        }
+    }
+
+    report.categories = UsageCategory::ALL
+        .into_iter()
+        .zip(sizes)
+        .filter(|(_, size)| *size > 0)
+        .collect();
+    report.total = sizes.iter().sum();
+    report
+        .entries
+        .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    Ok(report)
+}
+
+/// Returns the size of a file or folder, or `None` for links and other
+/// special files.
+fn entry_size(path: &Path, file_type: fs::FileType) -> io::Result<Option<u64>> {
+    if file_type.is_dir() {
+        dir_size(path).map(Some)
+    } else if file_type.is_file() {
+        Ok(Some(fs::metadata(path)?.len()))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn groups_sizes_by_category() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for (path, size) in [
+            ("mods/sodium.jar", 300),
+            ("mods/lithium.jar", 200),
+            ("saves/World/level.dat", 50),
+            ("saves/World/region/r.0.0.mca", 950),
+            ("logs/latest.log", 40),
+            ("logs/2024-01-01-1.log.gz", 10),
+            ("config/sodium-options.json", 5),
+            (".fabric/remappedJars/x.jar", 400),
+            ("options.txt", 3),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![0; size]).unwrap();
+        }
+
+        let report = disk_usage_report(root).unwrap();
+        assert_eq!(report.total, 1958);
+        assert_eq!(report.size(UsageCategory::Saves), 1000);
+        assert_eq!(report.size(UsageCategory::Mods), 500);
+        assert_eq!(report.size(UsageCategory::Other), 3);
+        assert_eq!(report.size(UsageCategory::Screenshots), 0);
+        assert_eq!(report.categories[0], (UsageCategory::Mods, 500));
+        assert_eq!(report.reclaimable(), 450);
+
+        let largest: Vec<_> = report
+            .largest(3)
+            .iter()
+            .map(|e| (e.category, e.size))
+            .collect();
+        assert_eq!(
+            largest,
+            [
+                (UsageCategory::Saves, 1000),
+                (UsageCategory::Cache, 400),
+                (UsageCategory::Mods, 300),
+            ]
+        );
+        assert_eq!(report.entries[0].path, root.join("saves/World"));
+        assert_eq!(report.largest(100).len(), 8);
+
+        assert_eq!(
+            disk_usage_report(&root.join("missing")).unwrap(),
+            DiskUsageReport::default()
+        );
+    }
+}
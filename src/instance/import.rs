@@ -0,0 +1,410 @@
+use super::saves::{dir_size, is_locked};
+use super::{SCREENSHOTS_DIR, SERVERS_DAT};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Error returned when a game directory cannot be imported.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("failed to import game directory: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a game directory: {0}")]
+    NotAGameDir(PathBuf),
+}
+
+/// The kinds of files taken over from a game directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ImportCategory {
+    /// The worlds in `saves`.
+    Saves,
+    /// The packs in `resourcepacks`.
+    ResourcePacks,
+    /// `options.txt`, and OptiFine's `optionsof.txt` if present.
+    Options,
+    /// The multiplayer server list, `servers.dat`.
+    Servers,
+    /// The pictures in `screenshots`.
+    Screenshots,
+}
+
+impl ImportCategory {
+    /// All categories.
+    pub const ALL: [ImportCategory; 5] = [
+        ImportCategory::Saves,
+        ImportCategory::ResourcePacks,
+        ImportCategory::Options,
+        ImportCategory::Servers,
+        ImportCategory::Screenshots,
+    ];
+
+    /// Returns the folder whose entries are imported one by one, or `None` for
+    /// single files.
+    fn folder(self) -> Option<&'static str> {
+        match self {
+            ImportCategory::Saves => Some("saves"),
+            ImportCategory::ResourcePacks => Some("resourcepacks"),
+            ImportCategory::Screenshots => Some(SCREENSHOTS_DIR),
+            ImportCategory::Options | ImportCategory::Servers => None,
+        }
+    }
+
+    fn files(self) -> &'static [&'static str] {
+        match self {
+            ImportCategory::Options => &["options.txt", "optionsof.txt"],
+            ImportCategory::Servers => &[SERVERS_DAT],
+            _ => &[],
+        }
+    }
+}
+
+/// How an import takes files over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ImportAction {
+    /// Files are copied and the game directory stays as it is.
+    #[default]
+    Copy,
+    /// Files are moved out of the game directory.
+    Move,
+}
+
+/// Options of [`plan_game_dir_import`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Whether files are copied or moved.
+    pub action: ImportAction,
+    /// The kinds of files to import.
+    pub categories: Vec<ImportCategory>,
+    /// Whether files already in the instance are replaced. If not, they are
+    /// left out of the plan.
+    pub overwrite: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            action: ImportAction::Copy,
+            categories: ImportCategory::ALL.to_vec(),
+            overwrite: false,
+        }
+    }
+}
+
+impl ImportOptions {
+    /// Sets whether files are copied or moved.
+    pub fn with_action(mut self, action: ImportAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Sets the kinds of files to import.
+    pub fn with_categories(mut self, categories: impl IntoIterator<Item = ImportCategory>) -> Self {
+        self.categories = categories.into_iter().collect();
+        self
+    }
+
+    /// Sets whether files already in the instance are replaced.
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+}
+
+/// A file or folder an import takes over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportItem {
+    pub category: ImportCategory,
+    pub source: PathBuf,
+    pub target: PathBuf,
+    /// Size in bytes, of all files for a folder.
+    pub size: u64,
+    /// Whether the target exists and is replaced.
+    pub replaces: bool,
+}
+
+/// Why an entry of the game directory is left out of an import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// The instance already has it and overwriting is off.
+    Exists,
+    /// The world is open in the game.
+    WorldInUse,
+}
+
+/// What [`plan_game_dir_import`] would do, shown to the user before
+/// [`ImportPlan::execute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportPlan {
+    pub action: ImportAction,
+    /// The entries to take over, sorted by category and source.
+    pub items: Vec<ImportItem>,
+    /// Entries left out, with the reason.
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+impl ImportPlan {
+    /// Returns the number of bytes the import copies or moves.
+    pub fn total_size(&self) -> u64 {
+        self.items.iter().map(|item| item.size).sum()
+    }
+
+    /// Copies or moves the planned entries into the instance.
+    ///
+    /// Replaced entries are removed first, so folders are not merged. Moves
+    /// across file systems fall back to copying and removing the source.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, ImportError>` - The number of entries taken over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry cannot be copied, moved or replaced.
+    /// Entries taken over before the error stay in the instance.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(action = ?self.action, items = self.items.len()), err)
+    )]
+    pub fn execute(&self) -> Result<usize, ImportError> {
+        for item in &self.items {
+            if let Some(parent) = item.target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if item.replaces {
+                remove_entry(&item.target)?;
+            }
+            match self.action {
+                ImportAction::Copy => copy_entry(&item.source, &item.target)?,
+                ImportAction::Move => {
+                    if fs::rename(&item.source, &item.target).is_err() {
+                        copy_entry(&item.source, &item.target)?;
+                        remove_entry(&item.source)?;
+                    }
+                }
+            }
+        }
+        Ok(self.items.len())
+    }
+}
+
+/// Plans taking worlds, resource packs, options, servers and screenshots
+/// over from a vanilla `.minecraft` folder into the game directory of an
+/// instance. Nothing is changed until [`ImportPlan::execute`].
+///
+/// # Arguments
+///
+/// * `game_dir` - The `.minecraft` folder.
+/// * `instance_dir` - The game directory of the new instance.
+/// * `options` - What to import and how.
+///
+/// # Returns
+///
+/// * `Result<ImportPlan, ImportError>` - The entries to copy or move and the
+///   ones left out.
+///
+/// # Errors
+///
+/// Returns [`ImportError::NotAGameDir`] if `game_dir` has none of the
+/// imported files, or an error if it cannot be read.
+pub fn plan_game_dir_import(
+    game_dir: &Path,
+    instance_dir: &Path,
+    options: &ImportOptions,
+) -> Result<ImportPlan, ImportError> {
+    let mut plan = ImportPlan {
+        action: options.action,
+        items: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut found = false;
+    let mut categories = options.categories.clone();
+    categories.sort();
+    categories.dedup();
+    for category in categories {
+        let mut sources = Vec::new();
+        match category.folder() {
+            Some(folder) => match fs::read_dir(game_dir.join(folder)) {
+                Ok(entries) => {
+                    found = true;
+                    for entry in entries {
+                        sources.push((entry?.path(), Path::new(folder).to_path_buf()));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            },
+            None => {
+                for file in category.files() {
+                    let path = game_dir.join(file);
+                    if path.is_file() {
+                        found = true;
+                        sources.push((path, PathBuf::new()));
+                    }
+                }
+            }
+        }
+        sources.sort();
+
+        for (source, folder) in sources {
+            let Some(name) = source.file_name() else {
+                continue;
+            };
+            let target = instance_dir.join(folder).join(name);
+            // Only world folders are imported; stray files such as
+            // `.DS_Store` are left behind.
+            if category == ImportCategory::Saves && !source.is_dir() {
+                continue;
+            }
+            if category == ImportCategory::Saves && is_locked(&source.join("session.lock"))? {
+                plan.skipped.push((source, SkipReason::WorldInUse));
+                continue;
+            }
+            let replaces = fs::symlink_metadata(&target).is_ok();
+            if replaces && !options.overwrite {
+                plan.skipped.push((source, SkipReason::Exists));
+                continue;
+            }
+            let metadata = fs::metadata(&source)?;
+            plan.items.push(ImportItem {
+                category,
+                size: if metadata.is_dir() {
+                    dir_size(&source)?
+                } else {
+                    metadata.len()
+                },
+                source,
+                target,
+                replaces,
+            });
+        }
+    }
+    if !found {
+        return Err(ImportError::NotAGameDir(game_dir.to_path_buf()));
+    }
+    Ok(plan)
+}
+
+fn copy_entry(source: &Path, target: &Path) -> io::Result<()> {
+    if !fs::metadata(source)?.is_dir() {
+        return fs::copy(source, target).map(|_| ());
+    }
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        copy_entry(&entry.path(), &target.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn game_dir(root: &Path) -> PathBuf {
+        let game = root.join(".minecraft");
+        fs::create_dir_all(game.join("saves/World/region")).unwrap();
+        fs::write(game.join("saves/World/level.dat"), "level").unwrap();
+        fs::write(game.join("saves/World/region/r.0.0.mca"), "region").unwrap();
+        fs::create_dir_all(game.join("resourcepacks")).unwrap();
+        fs::write(game.join("resourcepacks/Faithful.zip"), "zip").unwrap();
+        fs::create_dir_all(game.join("screenshots")).unwrap();
+        fs::write(game.join("screenshots/2024-01-15_18.30.45.png"), "png").unwrap();
+        fs::write(game.join("options.txt"), "fov:0.5").unwrap();
+        fs::write(game.join("servers.dat"), "nbt").unwrap();
+        fs::create_dir_all(game.join("versions/1.20.1")).unwrap();
+        game
+    }
+
+    #[test]
+    fn plans_and_copies_into_the_instance() {
+        let dir = tempdir().unwrap();
+        let game = game_dir(dir.path());
+        let instance = dir.path().join("instance");
+        fs::create_dir_all(&instance).unwrap();
+        fs::write(instance.join("options.txt"), "fov:0.0").unwrap();
+
+        let plan = plan_game_dir_import(&game, &instance, &ImportOptions::default()).unwrap();
+        let categories: Vec<_> = plan.items.iter().map(|item| item.category).collect();
+        assert_eq!(
+            categories,
+            [
+                ImportCategory::Saves,
+                ImportCategory::ResourcePacks,
+                ImportCategory::Servers,
+                ImportCategory::Screenshots,
+            ]
+        );
+        assert_eq!(plan.items[0].size, 11);
+        assert_eq!(plan.total_size(), 11 + 3 + 3 + 3);
+        assert_eq!(
+            plan.skipped,
+            [(game.join("options.txt"), SkipReason::Exists)]
+        );
+
+        assert_eq!(plan.execute().unwrap(), 4);
+        assert!(instance.join("saves/World/region/r.0.0.mca").is_file());
+        assert!(
+            instance
+                .join("screenshots/2024-01-15_18.30.45.png")
+                .is_file()
+        );
+        assert_eq!(
+            fs::read_to_string(instance.join("options.txt")).unwrap(),
+            "fov:0.0"
+        );
+        assert!(game.join("saves/World/level.dat").is_file());
+        assert!(!instance.join("versions").exists());
+    }
+
+    #[test]
+    fn moves_and_replaces() {
+        let dir = tempdir().unwrap();
+        let game = game_dir(dir.path());
+        let instance = dir.path().join("instance");
+        fs::create_dir_all(instance.join("saves/World")).unwrap();
+        fs::write(instance.join("saves/World/stale.dat"), "old").unwrap();
+
+        let options = ImportOptions::default()
+            .with_action(ImportAction::Move)
+            .with_categories([ImportCategory::Saves, ImportCategory::Options])
+            .with_overwrite(true);
+        let plan = plan_game_dir_import(&game, &instance, &options).unwrap();
+        assert!(plan.items[0].replaces);
+        assert_eq!(plan.execute().unwrap(), 2);
+        assert!(!instance.join("saves/World/stale.dat").exists());
+        assert!(instance.join("saves/World/level.dat").is_file());
+        assert!(!game.join("saves/World").exists());
+        assert!(!game.join("options.txt").exists());
+
+        let empty = dir.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert!(matches!(
+            plan_game_dir_import(&empty, &instance, &ImportOptions::default()),
+            Err(ImportError::NotAGameDir(_))
+        ));
+    }
+
+    #[test]
+    fn skips_stray_files_in_saves() {
+        let dir = tempdir().unwrap();
+        let game = game_dir(dir.path());
+        fs::write(game.join("saves/.DS_Store"), "finder").unwrap();
+        let instance = dir.path().join("instance");
+
+        let options = ImportOptions::default().with_categories([ImportCategory::Saves]);
+        let plan = plan_game_dir_import(&game, &instance, &options).unwrap();
+        assert_eq!(plan.items.len(), 1);
+        assert_eq!(plan.items[0].source, game.join("saves/World"));
+        assert!(plan.skipped.is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::assets::AssetIndex;
+use crate::http::{AuditStatus, DownloadRequest, FileCheck, PathCheck, verify_files};
+use crate::version::{InstallFileKind, InstallPaths, InstallPlan, VersionJson, plan_install};
+use std::path::PathBuf;
+
+/// A managed file that is missing or damaged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityProblem {
+    pub kind: InstallFileKind,
+    pub path: PathBuf,
+    pub status: AuditStatus,
+}
+
+/// The result of [`audit_instance`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Number of files checked.
+    pub checked: usize,
+    /// Files that are missing or do not match their manifest.
+    pub problems: Vec<IntegrityProblem>,
+    /// The downloads that repair the problems, one per damaged file.
+    pub repairs: Vec<DownloadRequest>,
+    /// Whether a native jar is repaired, so the natives must be extracted
+    /// again before launch.
+    pub reextract_natives: bool,
+}
+
+impl IntegrityReport {
+    /// Returns whether every managed file is intact.
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Returns the number of bytes the repairs download, as far as known.
+    pub fn repair_size(&self) -> u64 {
+        self.repairs.iter().filter_map(|request| request.size).sum()
+    }
+}
+
+/// Checks the client jar, libraries, loader libraries, natives, assets and log
+/// configuration of an instance against the hashes of their manifests.
+///
+/// See [`audit_install`].
+///
+/// # Arguments
+///
+/// * `version` - The resolved version JSON of the instance, loader included.
+/// * `index` - The asset index of the version.
+/// * `paths` - The shared folders the version is installed in.
+///
+/// # Returns
+///
+/// * `IntegrityReport` - The damaged files and the downloads repairing them.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(version = %version.id))
+)]
+pub fn audit_instance(
+    version: &VersionJson,
+    index: &AssetIndex,
+    paths: &InstallPaths,
+) -> IntegrityReport {
+    audit_install(&plan_install(version, index, paths))
+}
+
+/// Verifies every file of an install plan on disk, without network access.
+///
+/// Files are compared by size first and then by hash. Files whose manifest
+/// gives no hash, such as some loader libraries, are only checked to exist.
+/// Only damaged files are repaired, so the repairs are the smallest set of
+/// downloads that restores the install.
+pub fn audit_install(plan: &InstallPlan) -> IntegrityReport {
+    let checks: Vec<PathCheck> = plan
+        .files
+        .iter()
+        .map(|file| {
+            let check = FileCheck {
+                size: file.request.size,
+                hash: file.request.hashes.first().cloned(),
+            };
+            PathCheck::new(&file.request.path, check).with_url(&file.request.url)
+        })
+        .collect();
+    let audit = verify_files(&checks);
+
+    let mut report = IntegrityReport {
+        checked: plan.files.len(),
+        ..IntegrityReport::default()
+    };
+    for (file, entry) in plan.files.iter().zip(audit.entries) {
+        if entry.status == AuditStatus::Ok {
+            continue;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            kind = ?file.kind,
+            path = %file.request.path.display(),
+            status = ?entry.status,
+            "damaged file"
+        );
+        report.reextract_natives |= file.kind == InstallFileKind::Native;
+        report.repairs.push(file.request.clone());
+        report.problems.push(IntegrityProblem {
+            kind: file.kind,
+            path: file.request.path.clone(),
+            status: entry.status,
+        });
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{Arch, OsName, Platform};
+    use crate::version::plan_install_with;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn repairs_only_damaged_files() {
+        let dir = tempdir().unwrap();
+        let paths = InstallPaths::new(dir.path());
+        let version = VersionJson::from_json(
+            r#"{"id": "1.20.1",
+                "downloads": {"client": {"sha1": "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed", "size": 11,
+                    "url": "https://example.invalid/client.jar"}},
+                "libraries": [
+                    {"name": "org.example:good:1.0", "downloads": {"artifact": {
+                        "path": "org/example/good/1.0/good-1.0.jar", "size": 11,
+                        "sha1": "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed", "url": "https://example.invalid/good.jar"}}},
+                    {"name": "net.fabricmc:fabric-loader:0.16.9", "url": "https://maven.fabricmc.net/"}
+                ]}"#,
+        )
+        .unwrap();
+        let index = AssetIndex::from_json(
+            r#"{"objects": {"icons/icon_16x16.png": {"hash": "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed", "size": 11}}}"#,
+        )
+        .unwrap();
+        let linux = Platform::new(OsName::Linux, "6.1", Arch::X86_64);
+        let plan = plan_install_with(&version, &index, &paths, &linux);
+
+        fs::create_dir_all(paths.versions.join("1.20.1")).unwrap();
+        fs::write(paths.versions.join("1.20.1/1.20.1.jar"), b"hello worlD").unwrap();
+        let good = paths.libraries.join("org/example/good/1.0/good-1.0.jar");
+        fs::create_dir_all(good.parent().unwrap()).unwrap();
+        fs::write(&good, b"hello world").unwrap();
+        let object = paths
+            .assets
+            .join("objects/2a/2aae6c35c94fcfb415dbe95f408b9ce91ee846ed");
+        fs::create_dir_all(object.parent().unwrap()).unwrap();
+        fs::write(&object, b"hello world").unwrap();
+
+        let report = audit_install(&plan);
+        assert_eq!(report.checked, 4);
+        let problems: Vec<_> = report
+            .problems
+            .iter()
+            .map(|p| (p.kind, p.status.clone()))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                (InstallFileKind::ClientJar, AuditStatus::HashMismatch),
+                (InstallFileKind::Library, AuditStatus::Missing),
+            ]
+        );
+        assert_eq!(report.repairs.len(), 2);
+        assert_eq!(report.repair_size(), 11);
+        assert!(!report.reextract_natives && !report.is_clean());
+    }
+}
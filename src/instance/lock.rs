@@ -0,0 +1,93 @@
+use super::saves::is_locked;
+use std::fs::{self, File, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the file locked inside an instance directory while it runs.
+pub const INSTANCE_LOCK_FILE: &str = "instance.lock";
+
+/// Error returned when an instance cannot be locked.
+#[derive(Debug, Error)]
+pub enum InstanceLockError {
+    #[error("failed to lock instance: {0}")]
+    Io(#[from] io::Error),
+    #[error("instance {0} is running")]
+    Running(PathBuf),
+}
+
+/// An exclusive lock on [`INSTANCE_LOCK_FILE`] marking an instance as
+/// running, held by the launcher from launch until the game exits.
+///
+/// The lock is released when the value is dropped, and by the system if the
+/// launcher dies, so a stale lock file never blocks an instance.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Locks an instance, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InstanceLockError::Running`] if the instance is already
+    /// locked, by this or another launcher process.
+    pub fn acquire(instance_dir: &Path) -> Result<Self, InstanceLockError> {
+        fs::create_dir_all(instance_dir)?;
+        let path = instance_dir.join(INSTANCE_LOCK_FILE);
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self { _file: file, path }),
+            Err(TryLockError::WouldBlock) => {
+                Err(InstanceLockError::Running(instance_dir.to_path_buf()))
+            }
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// Returns the path of the locked file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Returns whether an instance is locked as running by an [`InstanceLock`].
+///
+/// # Errors
+///
+/// Returns an error if the lock file exists but cannot be checked.
+pub fn is_instance_running(instance_dir: &Path) -> io::Result<bool> {
+    is_locked(&instance_dir.join(INSTANCE_LOCK_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn locks_an_instance_until_dropped() {
+        let dir = tempdir().unwrap();
+        let instance = dir.path().join("instance");
+        assert!(!is_instance_running(&instance).unwrap());
+
+        let lock = InstanceLock::acquire(&instance).unwrap();
+        assert_eq!(lock.path(), instance.join(INSTANCE_LOCK_FILE));
+        assert!(is_instance_running(&instance).unwrap());
+        assert!(matches!(
+            InstanceLock::acquire(&instance),
+            Err(InstanceLockError::Running(path)) if path == instance
+        ));
+
+        drop(lock);
+        assert!(!is_instance_running(&instance).unwrap());
+        InstanceLock::acquire(&instance).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+/// The versioned `instance.json` configuration.
+pub mod config;
+/// Enabling and disabling the datapacks of a world.
+pub mod datapacks;
+/// Disk usage of a game directory by category.
+pub mod disk_usage;
+/// Taking worlds, packs and settings over from a vanilla `.minecraft` folder.
+pub mod import;
+/// Verifying the installed files of an instance and planning repairs.
+pub mod integrity;
+/// Marking an instance as running while its game is open.
+pub mod lock;
+/// Header statistics of the `.mca` region files of a world.
+pub mod region;
+/// Discovery of the worlds in a `saves` directory.
+pub mod saves;
+/// Screenshots: listing, thumbnails and duplicates.
+pub mod screenshots;
+/// The multiplayer server list stored in `servers.dat`.
+pub mod servers;
+/// Discovery of the shader packs in a `shaderpacks` directory.
+pub mod shaders;
+/// Data versions, and what opening a world in another version does to it.
+pub mod world_version;
+
+pub use config::{
+    INSTANCE_CONFIG_FILE, INSTANCE_SCHEMA_VERSION, InstanceConfig, InstanceConfigError,
+    InstanceLoader, JavaSettings, LaunchHooks, LoaderConfig, MemorySettings,
+};
+pub use datapacks::{
+    DATAPACKS_DIR, Datapack, DatapackError, DatapackProblem, DatapackState, WorldDatapacks,
+};
+pub use disk_usage::{DiskUsageReport, UsageCategory, UsageEntry, disk_usage_report};
+pub use import::{
+    ImportAction, ImportCategory, ImportError, ImportItem, ImportOptions, ImportPlan, SkipReason,
+    plan_game_dir_import,
+};
+pub use integrity::{IntegrityProblem, IntegrityReport, audit_install, audit_instance};
+pub use lock::{INSTANCE_LOCK_FILE, InstanceLock, InstanceLockError, is_instance_running};
+pub use region::{
+    ChunkLocation, REGION_HEADER_SIZE, RegionHeader, RegionStats, SECTOR_SIZE, world_region_stats,
+};
+pub use saves::{GameMode, LEVEL_DAT, LevelSummary, WorldEntry, scan_saves_dir};
+pub use screenshots::{
+    SCREENSHOTS_DIR, Screenshot, ScreenshotError, ScreenshotTime, find_duplicates,
+    parse_screenshot_time, scan_screenshots_dir, thumbnail,
+};
+pub use servers::{ResourcePackPolicy, SERVERS_DAT, ServerEntry, ServerList};
+pub use shaders::{ShaderHint, ShaderPack, scan_shaderpacks_dir};
+pub use world_version::{
+    CompatibilityStatus, WorldCompatibility, WorldVersionError, check_world_compatibility,
+    data_version, world_compatibility,
+};
@@ -0,0 +1,235 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Size of a sector, the allocation unit of region files.
+pub const SECTOR_SIZE: u64 = 4096;
+/// Size of the header: a location table and a timestamp table of one sector each.
+pub const REGION_HEADER_SIZE: usize = 2 * SECTOR_SIZE as usize;
+/// Number of chunks in a region (32 by 32).
+const CHUNKS_PER_REGION: usize = 1024;
+/// Folders holding the terrain regions of the vanilla dimensions, relative to
+/// the world folder.
+const REGION_DIRS: [&str; 3] = ["region", "DIM-1/region", "DIM1/region"];
+
+/// A chunk present in a region file, as described by the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkLocation {
+    /// Chunk x coordinate within the region, `0..32`.
+    pub x: u8,
+    /// Chunk z coordinate within the region, `0..32`.
+    pub z: u8,
+    /// First sector of the chunk, counted from the start of the file.
+    pub sector_offset: u32,
+    /// Number of sectors allocated to the chunk.
+    pub sector_count: u8,
+    /// Last time the chunk was saved, in seconds since the Unix epoch.
+    pub timestamp: u32,
+}
+
+/// The location and timestamp tables at the start of an `.mca` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegionHeader {
+    /// Chunks present in the region, in header order.
+    pub chunks: Vec<ChunkLocation>,
+}
+
+impl RegionHeader {
+    /// Parses a header from the first [`REGION_HEADER_SIZE`] bytes of a region.
+    pub fn parse(header: &[u8; REGION_HEADER_SIZE]) -> Self {
+        let (locations, timestamps) = header.split_at(REGION_HEADER_SIZE / 2);
+        let chunks = (0..CHUNKS_PER_REGION)
+            .filter_map(|i| {
+                let entry = &locations[i * 4..i * 4 + 4];
+                let sector_offset = u32::from_be_bytes([0, entry[0], entry[1], entry[2]]);
+                let sector_count = entry[3];
+                if sector_offset == 0 || sector_count == 0 {
+                    return None;
+                }
+                let timestamp =
+                    u32::from_be_bytes(timestamps[i * 4..i * 4 + 4].try_into().unwrap());
+                Some(ChunkLocation {
+                    x: (i % 32) as u8,
+                    z: (i / 32) as u8,
+                    sector_offset,
+                    sector_count,
+                    timestamp,
+                })
+            })
+            .collect();
+        Self { chunks }
+    }
+}
+
+/// Space and chunk statistics of one or more region files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegionStats {
+    /// Number of region files counted.
+    pub files: usize,
+    /// Number of chunks present.
+    pub chunks: usize,
+    /// Total size of the files in bytes.
+    pub file_size: u64,
+    /// Sectors allocated to chunks, headers excluded.
+    pub allocated_sectors: u64,
+    /// Bytes of chunk data actually stored, including each chunk's 5-byte prefix.
+    pub payload_bytes: u64,
+    /// Bytes holding neither headers nor chunk data: free sectors and the
+    /// unused tails of allocated ones.
+    pub wasted_bytes: u64,
+    /// Most recent chunk timestamp, in seconds since the Unix epoch.
+    pub last_saved: Option<u32>,
+}
+
+impl RegionStats {
+    /// Reads the statistics of a single `.mca` file.
+    ///
+    /// Only the header and the length prefix of each chunk are read. Files
+    /// shorter than a header, which the game leaves behind for regions it
+    /// never wrote to, count as empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let mut stats = Self {
+            files: 1,
+            file_size,
+            wasted_bytes: file_size,
+            ..Self::default()
+        };
+        if file_size < REGION_HEADER_SIZE as u64 {
+            return Ok(stats);
+        }
+        let mut header = [0u8; REGION_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        for chunk in RegionHeader::parse(&header).chunks {
+            let start = u64::from(chunk.sector_offset) * SECTOR_SIZE;
+            let allocated = u64::from(chunk.sector_count) * SECTOR_SIZE;
+            // Entries pointing past the end are corrupt; the game regenerates them.
+            if start + 5 > file_size {
+                continue;
+            }
+            file.seek(SeekFrom::Start(start))?;
+            let mut length = [0u8; 4];
+            file.read_exact(&mut length)?;
+            let stored = u64::from(u32::from_be_bytes(length)) + 4;
+            stats.chunks += 1;
+            stats.allocated_sectors += u64::from(chunk.sector_count);
+            stats.payload_bytes += stored.min(allocated);
+            stats.last_saved = stats.last_saved.max(Some(chunk.timestamp));
+        }
+        stats.wasted_bytes =
+            file_size.saturating_sub(REGION_HEADER_SIZE as u64 + stats.payload_bytes);
+        Ok(stats)
+    }
+
+    /// Sums the statistics of every `.mca` file in a directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or a region file cannot be read. A
+    /// missing directory counts as empty.
+    pub fn read_dir(dir: &Path) -> io::Result<Self> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let mut stats = Self::default();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "mca") && path.is_file() {
+                stats.add(&Self::read(&path)?);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Adds the statistics of `other` to these.
+    pub fn add(&mut self, other: &Self) {
+        self.files += other.files;
+        self.chunks += other.chunks;
+        self.file_size += other.file_size;
+        self.allocated_sectors += other.allocated_sectors;
+        self.payload_bytes += other.payload_bytes;
+        self.wasted_bytes += other.wasted_bytes;
+        self.last_saved = self.last_saved.max(other.last_saved);
+    }
+}
+
+/// Computes terrain statistics of a world across the overworld, the Nether and
+/// the End.
+///
+/// # Arguments
+///
+/// * `world` - The world folder, containing `level.dat`.
+///
+/// # Errors
+///
+/// Returns an error if a region directory or file cannot be read.
+pub fn world_region_stats(world: &Path) -> io::Result<RegionStats> {
+    let mut stats = RegionStats::default();
+    for dir in REGION_DIRS {
+        stats.add(&RegionStats::read_dir(&world.join(dir))?);
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn region_with_chunks(chunks: &[(usize, u32, u8, u32, u32)]) -> Vec<u8> {
+        let sectors = chunks
+            .iter()
+            .map(|(_, offset, count, _, _)| offset + u32::from(*count))
+            .max()
+            .unwrap_or(2);
+        let mut data = vec![0u8; sectors as usize * SECTOR_SIZE as usize];
+        for &(index, offset, count, timestamp, length) in chunks {
+            let location = (offset << 8) | u32::from(count);
+            data[index * 4..index * 4 + 4].copy_from_slice(&location.to_be_bytes());
+            let ts = SECTOR_SIZE as usize + index * 4;
+            data[ts..ts + 4].copy_from_slice(&timestamp.to_be_bytes());
+            let start = offset as usize * SECTOR_SIZE as usize;
+            data[start..start + 4].copy_from_slice(&length.to_be_bytes());
+            data[start + 4] = 2;
+        }
+        data
+    }
+
+    #[test]
+    fn reads_region_and_world_stats() {
+        let dir = tempdir().unwrap();
+        let region = dir.path().join("region");
+        fs::create_dir_all(&region).unwrap();
+        let data = region_with_chunks(&[(0, 2, 1, 100, 1000), (33, 3, 2, 300, 5000)]);
+        fs::write(region.join("r.0.0.mca"), &data).unwrap();
+        fs::write(region.join("r.1.0.mca"), []).unwrap();
+
+        let header = RegionHeader::parse(data[..REGION_HEADER_SIZE].try_into().unwrap());
+        assert_eq!(header.chunks.len(), 2);
+        assert_eq!((header.chunks[1].x, header.chunks[1].z), (1, 1));
+        assert_eq!(header.chunks[1].sector_count, 2);
+
+        let nether = dir.path().join("DIM-1/region");
+        fs::create_dir_all(&nether).unwrap();
+        let data = region_with_chunks(&[(5, 2, 1, 200, 100)]);
+        fs::write(nether.join("r.-1.0.mca"), &data).unwrap();
+
+        let stats = world_region_stats(dir.path()).unwrap();
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.chunks, 3);
+        assert_eq!(stats.allocated_sectors, 4);
+        assert_eq!(stats.payload_bytes, 1004 + 5004 + 104);
+        assert_eq!(stats.last_saved, Some(300));
+        assert_eq!(
+            stats.wasted_bytes,
+            stats.file_size - 2 * REGION_HEADER_SIZE as u64 - stats.payload_bytes
+        );
+    }
+}
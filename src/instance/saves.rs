@@ -0,0 +1,244 @@
+use crate::nbt::{NbtFile, NbtValue};
+use std::fs::{self, File, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the world metadata file inside a world folder.
+pub const LEVEL_DAT: &str = "level.dat";
+
+/// Game mode a world was created with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    fn from_id(id: i64) -> Option<Self> {
+        match id {
+            0 => Some(Self::Survival),
+            1 => Some(Self::Creative),
+            2 => Some(Self::Adventure),
+            3 => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of `level.dat` shown in a world list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelSummary {
+    /// World name set by the player, which may differ from the folder name.
+    pub name: Option<String>,
+    /// Last time the world was played, in milliseconds since the Unix epoch.
+    pub last_played: Option<i64>,
+    pub game_mode: Option<GameMode>,
+    pub hardcore: bool,
+    /// Whether cheats (commands) are allowed.
+    pub allow_commands: bool,
+    /// Name of the game version that last saved the world, e.g. `1.20.4`.
+    pub version_name: Option<String>,
+    /// Data version of the game that last saved the world.
+    pub data_version: Option<i32>,
+}
+
+impl LevelSummary {
+    /// Extracts the summary from a parsed `level.dat`.
+    pub fn from_level_dat(file: &NbtFile) -> Self {
+        let data = file.root.get("Data");
+        let get = |path: &str| data.and_then(|d| d.get_path(path));
+        Self {
+            name: get("LevelName")
+                .and_then(NbtValue::as_str)
+                .map(str::to_string),
+            last_played: get("LastPlayed").and_then(NbtValue::as_i64),
+            game_mode: get("GameType")
+                .and_then(NbtValue::as_i64)
+                .and_then(GameMode::from_id),
+            hardcore: get("hardcore").and_then(NbtValue::as_bool).unwrap_or(false),
+            allow_commands: get("allowCommands")
+                .and_then(NbtValue::as_bool)
+                .unwrap_or(false),
+            version_name: get("Version.Name")
+                .and_then(NbtValue::as_str)
+                .map(str::to_string),
+            data_version: get("DataVersion")
+                .and_then(NbtValue::as_i64)
+                .and_then(|v| i32::try_from(v).ok()),
+        }
+    }
+}
+
+/// A world found in a `saves` directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldEntry {
+    /// Name of the world folder.
+    pub folder: String,
+    pub path: PathBuf,
+    /// Metadata from `level.dat`, or `None` if it could not be read.
+    pub level: Option<LevelSummary>,
+    /// Path of `icon.png`, if the world has one.
+    pub icon: Option<PathBuf>,
+    /// Total size of the world folder in bytes.
+    pub size: u64,
+    /// Whether a running game holds the world's `session.lock`.
+    pub locked: bool,
+}
+
+impl WorldEntry {
+    /// Returns the name to display: the level name, falling back to the folder.
+    pub fn display_name(&self) -> &str {
+        self.level
+            .as_ref()
+            .and_then(|l| l.name.as_deref())
+            .unwrap_or(&self.folder)
+    }
+}
+
+/// Lists the worlds in a `saves` directory.
+///
+/// Every folder containing a `level.dat` is a world. A `level.dat` that cannot
+/// be parsed does not hide the world; its entry has no metadata instead.
+///
+/// # Arguments
+///
+/// * `path` - The `saves` directory of a game directory.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<WorldEntry>>` - The worlds, most recently played first, or
+///   an empty list if the directory does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the directory or a world folder cannot be read.
+pub fn scan_saves_dir(path: &Path) -> io::Result<Vec<WorldEntry>> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut worlds = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let world = entry.path();
+        let level_dat = world.join(LEVEL_DAT);
+        if !entry.file_type()?.is_dir() || !level_dat.is_file() {
+            continue;
+        }
+        let icon = world.join("icon.png");
+        worlds.push(WorldEntry {
+            folder: entry.file_name().to_string_lossy().into_owned(),
+            level: NbtFile::read(&level_dat)
+                .ok()
+                .map(|file| LevelSummary::from_level_dat(&file)),
+            icon: icon.is_file().then_some(icon),
+            size: dir_size(&world)?,
+            locked: is_locked(&world.join("session.lock"))?,
+            path: world,
+        });
+    }
+    worlds.sort_by_key(|w| {
+        std::cmp::Reverse(w.level.as_ref().and_then(|l| l.last_played).unwrap_or(0))
+    });
+    Ok(worlds)
+}
+
+pub(super) fn dir_size(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Since 1.16 the game keeps an exclusive lock on `session.lock` while the
+/// world is open; older versions only write a timestamp and are never reported.
+pub(super) fn is_locked(lock: &Path) -> io::Result<bool> {
+    let file = match File::options().read(true).write(true).open(lock) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        // A read-only file cannot be locked by the game either.
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    match file.try_lock() {
+        Ok(()) => Ok(false),
+        Err(TryLockError::WouldBlock) => Ok(true),
+        Err(TryLockError::Error(e)) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::{NbtCompound, NbtCompression};
+    use tempfile::tempdir;
+
+    fn write_world(saves: &Path, folder: &str, name: &str, last_played: i64) -> PathBuf {
+        let world = saves.join(folder);
+        fs::create_dir_all(&world).unwrap();
+        let data = NbtCompound::from([
+            ("LevelName".to_string(), NbtValue::String(name.to_string())),
+            ("LastPlayed".to_string(), NbtValue::Long(last_played)),
+            ("GameType".to_string(), NbtValue::Int(1)),
+            ("hardcore".to_string(), NbtValue::Byte(0)),
+            (
+                "Version".to_string(),
+                NbtValue::Compound(NbtCompound::from([(
+                    "Name".to_string(),
+                    NbtValue::String("1.20.4".to_string()),
+                )])),
+            ),
+        ]);
+        let root = NbtCompound::from([("Data".to_string(), NbtValue::Compound(data))]);
+        NbtFile::new(root, NbtCompression::Gzip)
+            .write(&world.join(LEVEL_DAT))
+            .unwrap();
+        world
+    }
+
+    #[test]
+    fn scans_worlds_with_metadata_and_lock() {
+        let dir = tempdir().unwrap();
+        let saves = dir.path().join("saves");
+        assert!(scan_saves_dir(&saves).unwrap().is_empty());
+
+        write_world(&saves, "old", "Old World", 1_000);
+        let new = write_world(&saves, "New World", "Shiny", 2_000);
+        fs::write(new.join("icon.png"), b"png").unwrap();
+        fs::create_dir_all(new.join("region")).unwrap();
+        fs::write(new.join("region/r.0.0.mca"), vec![0u8; 4096]).unwrap();
+        let lock = File::create(new.join("session.lock")).unwrap();
+        lock.lock().unwrap();
+        fs::create_dir_all(saves.join("not a world")).unwrap();
+
+        let worlds = scan_saves_dir(&saves).unwrap();
+        assert_eq!(worlds.len(), 2);
+        let shiny = &worlds[0];
+        assert_eq!(shiny.display_name(), "Shiny");
+        assert_eq!(shiny.folder, "New World");
+        assert!(shiny.locked);
+        assert!(shiny.icon.is_some());
+        assert!(shiny.size > 4096);
+        let level = shiny.level.as_ref().unwrap();
+        assert_eq!(level.game_mode, Some(GameMode::Creative));
+        assert_eq!(level.version_name.as_deref(), Some("1.20.4"));
+        assert!(!worlds[1].locked);
+        assert!(worlds[1].icon.is_none());
+
+        drop(lock);
+        fs::write(saves.join("old").join(LEVEL_DAT), b"garbage").unwrap();
+        let worlds = scan_saves_dir(&saves).unwrap();
+        assert!(!worlds[0].locked);
+        assert_eq!(worlds[1].display_name(), "old");
+    }
+}
@@ -0,0 +1,371 @@
+use crate::filesystem::{FilesystemError, write_atomic};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+
+/// Folder of a game directory holding the screenshots taken with F2.
+pub const SCREENSHOTS_DIR: &str = "screenshots";
+
+/// Error returned when a thumbnail cannot be created.
+#[derive(Debug, Error)]
+pub enum ScreenshotError {
+    #[error("failed to access screenshot: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write thumbnail: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid PNG: {0}")]
+    Png(#[from] png::DecodingError),
+    #[error("failed to encode thumbnail: {0}")]
+    Encode(#[from] png::EncodingError),
+}
+
+/// The local time a screenshot was taken, from its file name. Fields are
+/// ordered so that comparing two values compares the times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScreenshotTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+/// A screenshot found in a `screenshots` folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub file_name: String,
+    pub path: PathBuf,
+    /// File size in bytes.
+    pub size: u64,
+    /// Width and height in pixels, if the PNG header can be read.
+    pub dimensions: Option<(u32, u32)>,
+    /// When the screenshot was taken, if the file keeps the game's name.
+    pub taken: Option<ScreenshotTime>,
+    pub modified: Option<SystemTime>,
+}
+
+/// Parses the time from a screenshot name as the game writes it, e.g.
+/// `2024-01-15_18.30.45.png` or `2024-01-15_18.30.45_2.png` for the second
+/// screenshot of the same second.
+pub fn parse_screenshot_time(file_name: &str) -> Option<ScreenshotTime> {
+    let stem = file_name.strip_suffix(".png")?;
+    let (date, time) = stem.split_once('_')?;
+    let time = time.split('_').next()?;
+    let mut date = date.split('-').map(str::parse::<u16>);
+    let mut time = time.split('.').map(str::parse::<u8>);
+    let taken = ScreenshotTime {
+        year: date.next()?.ok()?,
+        month: u8::try_from(date.next()?.ok()?).ok()?,
+        day: u8::try_from(date.next()?.ok()?).ok()?,
+        hour: time.next()?.ok()?,
+        minute: time.next()?.ok()?,
+        second: time.next()?.ok()?,
+    };
+    let valid = date.next().is_none()
+        && time.next().is_none()
+        && (1..=12).contains(&taken.month)
+        && (1..=31).contains(&taken.day)
+        && taken.hour < 24
+        && taken.minute < 60
+        && taken.second < 60;
+    valid.then_some(taken)
+}
+
+/// Lists the screenshots in a `screenshots` folder.
+///
+/// Only the PNG header is read, so scanning stays fast for large folders.
+///
+/// # Arguments
+///
+/// * `path` - The `screenshots` folder of a game directory.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<Screenshot>>` - The screenshots, newest first, or an
+///   empty list if the folder does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the folder cannot be read.
+pub fn scan_screenshots_dir(path: &Path) -> io::Result<Vec<Screenshot>> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut screenshots = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || !file_name.to_ascii_lowercase().ends_with(".png") {
+            continue;
+        }
+        screenshots.push(Screenshot {
+            taken: parse_screenshot_time(&file_name),
+            dimensions: png_dimensions(&entry.path()),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            path: entry.path(),
+            file_name,
+        });
+    }
+    // Names of the same second end in `_2`, `_3` and so on, which sort after
+    // the first screenshot of that second.
+    screenshots.sort_by(|a, b| {
+        b.taken.cmp(&a.taken).then_with(|| match a.taken {
+            Some(_) => b.file_name.cmp(&a.file_name),
+            None => b.modified.cmp(&a.modified),
+        })
+    });
+    Ok(screenshots)
+}
+
+fn png_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let file = File::open(path).ok()?;
+    let reader = png::Decoder::new(BufReader::new(file)).read_info().ok()?;
+    Some(reader.info().size())
+}
+
+/// Returns a thumbnail of a screenshot, creating it in `cache_dir` if needed.
+///
+/// Thumbnails are named after the SHA-1 of the screenshot and the size, so a
+/// renamed screenshot keeps its thumbnail and an edited one gets a new one.
+///
+/// # Arguments
+///
+/// * `screenshot` - The PNG screenshot.
+/// * `cache_dir` - The folder to keep thumbnails in; created if missing.
+/// * `max_size` - The largest width or height of the thumbnail. Smaller
+///   screenshots are not enlarged.
+///
+/// # Returns
+///
+/// * `Result<PathBuf, ScreenshotError>` - The thumbnail PNG.
+///
+/// # Errors
+///
+/// Returns an error if the screenshot is not a valid PNG or the thumbnail
+/// cannot be written.
+pub fn thumbnail(
+    screenshot: &Path,
+    cache_dir: &Path,
+    max_size: u32,
+) -> Result<PathBuf, ScreenshotError> {
+    let data = fs::read(screenshot)?;
+    let max_size = max_size.max(1);
+    let dest = cache_dir.join(format!(
+        "{}_{}.png",
+        hex::encode(Sha1::digest(&data)),
+        max_size
+    ));
+    if dest.is_file() {
+        return Ok(dest);
+    }
+
+    let mut decoder = png::Decoder::new(io::Cursor::new(&data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let (width, height) = reader.info().size();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels)?;
+    let (color_type, _) = reader.output_color_type();
+    let channels = color_type.samples();
+
+    let scale = f64::from(max_size) / f64::from(width.max(height));
+    let (thumb_width, thumb_height) = if scale < 1.0 {
+        (
+            ((f64::from(width) * scale).round() as u32).max(1),
+            ((f64::from(height) * scale).round() as u32).max(1),
+        )
+    } else {
+        (width, height)
+    };
+    let thumb = downscale(
+        &pixels,
+        (width as usize, height as usize),
+        (thumb_width as usize, thumb_height as usize),
+        channels,
+    );
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, thumb_width, thumb_height);
+    encoder.set_color(color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&thumb)?;
+    writer.finish()?;
+    fs::create_dir_all(cache_dir)?;
+    write_atomic(&dest, &png)?;
+    Ok(dest)
+}
+
+/// Scales an image down by averaging the source pixels covered by each
+/// target pixel.
+pub(crate) fn downscale(
+    pixels: &[u8],
+    (width, height): (usize, usize),
+    (target_width, target_height): (usize, usize),
+    channels: usize,
+) -> Vec<u8> {
+    // The source pixels covered by target pixel `t`, at least one.
+    let span = |t: usize, size: usize, target: usize| {
+        let start = t * size / target;
+        (start, ((t + 1) * size / target).max(start + 1))
+    };
+    let mut out = Vec::with_capacity(target_width * target_height * channels);
+    for ty in 0..target_height {
+        let (y0, y1) = span(ty, height, target_height);
+        for tx in 0..target_width {
+            let (x0, x1) = span(tx, width, target_width);
+            let count = ((y1 - y0) * (x1 - x0)) as u32;
+            for c in 0..channels {
+                let mut sum = 0u32;
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        sum += u32::from(pixels[(y * width + x) * channels + c]);
+                    }
+                }
+                out.push((sum / count) as u8);
+            }
+        }
+    }
+    out
+}
+
+/// Groups screenshots with the same content.
+///
+/// Only files of equal size are hashed, so this is cheap when there are no
+/// duplicates.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<Vec<PathBuf>>>` - Groups of two or more identical files,
+///   in the order of `screenshots` within and across groups.
+///
+/// # Errors
+///
+/// Returns an error if a file of equal size cannot be read.
+pub fn find_duplicates(screenshots: &[Screenshot]) -> io::Result<Vec<Vec<PathBuf>>> {
+    let mut by_size: BTreeMap<u64, Vec<&Screenshot>> = BTreeMap::new();
+    for screenshot in screenshots {
+        by_size.entry(screenshot.size).or_default().push(screenshot);
+    }
+    // Keyed by the index of the first file, to keep the input order.
+    let mut groups: BTreeMap<usize, Vec<PathBuf>> = BTreeMap::new();
+    for candidates in by_size.values().filter(|c| c.len() > 1) {
+        let mut by_hash: BTreeMap<Vec<u8>, Vec<&Screenshot>> = BTreeMap::new();
+        for screenshot in candidates {
+            let hash = Sha1::digest(fs::read(&screenshot.path)?).to_vec();
+            by_hash.entry(hash).or_default().push(screenshot);
+        }
+        for group in by_hash.into_values().filter(|g| g.len() > 1) {
+            let first = screenshots
+                .iter()
+                .position(|s| s.path == group[0].path)
+                .unwrap_or(usize::MAX);
+            groups.insert(first, group.into_iter().map(|s| s.path.clone()).collect());
+        }
+    }
+    Ok(groups.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_png(path: &Path, width: u32, height: u32, shade: u8) {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        let pixels: Vec<u8> = (0..width * height)
+            .flat_map(|i| if i % 2 == 0 { [shade; 3] } else { [0; 3] })
+            .collect();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn scans_screenshots_and_finds_duplicates() {
+        let dir = tempdir().unwrap();
+        write_png(&dir.path().join("2024-01-15_18.30.45.png"), 40, 20, 200);
+        write_png(&dir.path().join("2024-01-15_18.30.45_2.png"), 40, 20, 200);
+        write_png(&dir.path().join("2023-12-31_23.59.59.png"), 40, 20, 100);
+        write_png(&dir.path().join("custom.png"), 8, 8, 100);
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let screenshots = scan_screenshots_dir(dir.path()).unwrap();
+        let names: Vec<&str> = screenshots.iter().map(|s| s.file_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "2024-01-15_18.30.45_2.png",
+                "2024-01-15_18.30.45.png",
+                "2023-12-31_23.59.59.png",
+                "custom.png",
+            ]
+        );
+        assert_eq!(screenshots[0].dimensions, Some((40, 20)));
+        assert_eq!(
+            screenshots[2].taken,
+            Some(ScreenshotTime {
+                year: 2023,
+                month: 12,
+                day: 31,
+                hour: 23,
+                minute: 59,
+                second: 59
+            })
+        );
+        assert_eq!(screenshots[3].taken, None);
+        assert_eq!(parse_screenshot_time("2024-13-01_00.00.00.png"), None);
+
+        let duplicates = find_duplicates(&screenshots).unwrap();
+        assert_eq!(
+            duplicates,
+            [vec![
+                screenshots[0].path.clone(),
+                screenshots[1].path.clone()
+            ]]
+        );
+        assert!(
+            scan_screenshots_dir(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn creates_and_reuses_thumbnails() {
+        let dir = tempdir().unwrap();
+        let screenshot = dir.path().join("shot.png");
+        write_png(&screenshot, 40, 20, 200);
+        let cache = dir.path().join("cache/thumbnails");
+
+        let thumb = thumbnail(&screenshot, &cache, 10).unwrap();
+        assert_eq!(png_dimensions(&thumb), Some((10, 5)));
+        let modified = fs::metadata(&thumb).unwrap().modified().unwrap();
+        assert_eq!(thumbnail(&screenshot, &cache, 10).unwrap(), thumb);
+        assert_eq!(fs::metadata(&thumb).unwrap().modified().unwrap(), modified);
+
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(&thumb).unwrap()));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        // Alternating 200 and 0 pixels average to 100.
+        assert_eq!(&pixels[..3], [100, 100, 100]);
+
+        let small = thumbnail(&screenshot, &cache, 100).unwrap();
+        assert_eq!(png_dimensions(&small), Some((40, 20)));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::nbt::{NbtCompound, NbtCompression, NbtError, NbtFile, NbtValue};
+use std::io;
+use std::path::Path;
+
+/// File name of the multiplayer server list inside the game directory.
+pub const SERVERS_DAT: &str = "servers.dat";
+
+/// Whether the client uses a server's resource pack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ResourcePackPolicy {
+    /// Ask when joining.
+    #[default]
+    Prompt,
+    /// Download and apply the pack.
+    Enabled,
+    /// Never use the pack.
+    Disabled,
+}
+
+/// An entry of the multiplayer server list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerEntry {
+    /// Display name.
+    pub name: String,
+    /// Address, e.g. `mc.example.com` or `127.0.0.1:25566`.
+    pub address: String,
+    /// Server icon as base64 PNG, cached from the last ping.
+    pub icon: Option<String>,
+    /// Resource pack handling.
+    pub resource_packs: ResourcePackPolicy,
+    /// Whether the entry is hidden from the list, as for direct connections.
+    pub hidden: bool,
+    /// Tags this crate does not know, written back unchanged.
+    pub extra: NbtCompound,
+}
+
+impl ServerEntry {
+    /// Creates a visible entry that asks before using resource packs.
+    pub fn new(name: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            address: address.into(),
+            icon: None,
+            resource_packs: ResourcePackPolicy::Prompt,
+            hidden: false,
+            extra: NbtCompound::new(),
+        }
+    }
+
+    fn from_nbt(value: &NbtValue) -> Result<Self, NbtError> {
+        let invalid = |what: &str| NbtError::Message(format!("server entry has no {}", what));
+        let mut extra = value
+            .as_compound()
+            .ok_or_else(|| invalid("compound"))?
+            .clone();
+        let mut take_string = |key: &str| match extra.remove(key) {
+            Some(NbtValue::String(s)) => Some(s),
+            _ => None,
+        };
+        let address = take_string("ip").ok_or_else(|| invalid("address"))?;
+        let name = take_string("name").unwrap_or_default();
+        let icon = take_string("icon");
+        let resource_packs = match extra.remove("acceptTextures").and_then(|v| v.as_bool()) {
+            Some(true) => ResourcePackPolicy::Enabled,
+            Some(false) => ResourcePackPolicy::Disabled,
+            None => ResourcePackPolicy::Prompt,
+        };
+        let hidden = extra
+            .remove("hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Ok(Self {
+            name,
+            address,
+            icon,
+            resource_packs,
+            hidden,
+            extra,
+        })
+    }
+
+    fn to_nbt(&self) -> NbtValue {
+        let mut compound = self.extra.clone();
+        compound.insert("name".to_string(), NbtValue::String(self.name.clone()));
+        compound.insert("ip".to_string(), NbtValue::String(self.address.clone()));
+        if let Some(icon) = &self.icon {
+            compound.insert("icon".to_string(), NbtValue::String(icon.clone()));
+        }
+        match self.resource_packs {
+            ResourcePackPolicy::Prompt => {}
+            ResourcePackPolicy::Enabled => {
+                compound.insert("acceptTextures".to_string(), NbtValue::Byte(1));
+            }
+            ResourcePackPolicy::Disabled => {
+                compound.insert("acceptTextures".to_string(), NbtValue::Byte(0));
+            }
+        }
+        if self.hidden {
+            compound.insert("hidden".to_string(), NbtValue::Byte(1));
+        }
+        NbtValue::Compound(compound)
+    }
+}
+
+/// The multiplayer server list of an instance, in display order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerList {
+    /// The entries, including hidden ones.
+    pub servers: Vec<ServerEntry>,
+}
+
+impl ServerList {
+    /// Reads `servers.dat`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ServerList, NbtError>` - The list, or an empty list if the file
+    ///   does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid server list.
+    pub fn load(path: &Path) -> Result<Self, NbtError> {
+        let file = match NbtFile::read(path) {
+            Ok(file) => file,
+            Err(NbtError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(e) => return Err(e),
+        };
+        let servers = match file.root.get("servers") {
+            Some(NbtValue::List(list)) => list
+                .iter()
+                .map(ServerEntry::from_nbt)
+                .collect::<Result<_, _>>()?,
+            _ => Vec::new(),
+        };
+        Ok(Self { servers })
+    }
+
+    /// Writes the list to `path` uncompressed, as the game does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), NbtError> {
+        let servers = self.servers.iter().map(ServerEntry::to_nbt).collect();
+        let root = NbtCompound::from([("servers".to_string(), NbtValue::List(servers))]);
+        NbtFile::new(root, NbtCompression::None).write(path)
+    }
+
+    /// Returns the entries shown in the multiplayer screen.
+    pub fn visible(&self) -> impl Iterator<Item = &ServerEntry> {
+        self.servers.iter().filter(|s| !s.hidden)
+    }
+
+    /// Returns the index of the first entry with the given address.
+    pub fn position(&self, address: &str) -> Option<usize> {
+        self.servers
+            .iter()
+            .position(|s| s.address.eq_ignore_ascii_case(address))
+    }
+
+    /// Appends an entry to the end of the list.
+    pub fn add(&mut self, entry: ServerEntry) {
+        self.servers.push(entry);
+    }
+
+    /// Removes the entry at `index`, returning it.
+    pub fn remove(&mut self, index: usize) -> Option<ServerEntry> {
+        (index < self.servers.len()).then(|| self.servers.remove(index))
+    }
+
+    /// Moves the entry at `from` to position `to`, shifting the entries between.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `false` if either index is out of range.
+    pub fn move_entry(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.servers.len() || to >= self.servers.len() {
+            return false;
+        }
+        let entry = self.servers.remove(from);
+        self.servers.insert(to, entry);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn edits_and_round_trips_server_list() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SERVERS_DAT);
+        assert!(ServerList::load(&path).unwrap().servers.is_empty());
+
+        let mut list = ServerList::default();
+        let mut hypixel = ServerEntry::new("Hypixel", "mc.hypixel.net");
+        hypixel.resource_packs = ResourcePackPolicy::Disabled;
+        hypixel
+            .extra
+            .insert("preventsChatReports".to_string(), NbtValue::Byte(1));
+        list.add(hypixel);
+        list.add(ServerEntry::new("Local", "127.0.0.1:25566"));
+        let mut direct = ServerEntry::new("Direct", "play.example.com");
+        direct.hidden = true;
+        list.add(direct);
+        assert!(list.move_entry(1, 0));
+        assert!(!list.move_entry(0, 3));
+        list.save(&path).unwrap();
+
+        let file = NbtFile::read(&path).unwrap();
+        assert_eq!(file.compression, NbtCompression::None);
+        let loaded = ServerList::load(&path).unwrap();
+        assert_eq!(loaded, list);
+        assert_eq!(loaded.servers[0].name, "Local");
+        assert_eq!(loaded.visible().count(), 2);
+        assert_eq!(loaded.position("MC.HYPIXEL.NET"), Some(1));
+
+        let mut loaded = loaded;
+        assert_eq!(loaded.remove(0).unwrap().address, "127.0.0.1:25566");
+        assert!(loaded.remove(5).is_none());
+        assert_eq!(
+            loaded.servers[0].extra.get("preventsChatReports"),
+            Some(&NbtValue::Byte(1))
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Name of the folder holding the shader programs of a pack.
+const SHADERS_FOLDER: &str = "shaders";
+/// Pack options, profiles and required loader features.
+const SHADERS_PROPERTIES: &str = "shaders/shaders.properties";
+
+/// A hint about where a shader pack can run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderHint {
+    /// The pack requires Iris features, so OptiFine cannot load it.
+    RequiresIris(Vec<String>),
+    /// The zip has no `shaders` folder, so neither Iris nor OptiFine lists it.
+    NotAShaderPack,
+    /// The zip wraps other zips, usually several editions of the pack that
+    /// must be extracted into `shaderpacks` first.
+    ContainsPacks(Vec<String>),
+}
+
+/// A shader pack found in a `shaderpacks` directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderPack {
+    /// File or folder name, which Iris and OptiFine store as the selected pack.
+    pub file_name: String,
+    pub path: PathBuf,
+    /// Whether the pack is an extracted folder rather than a zip.
+    pub is_folder: bool,
+    /// Names of the option profiles, e.g. `LOW` and `HIGH`.
+    pub profiles: Vec<String>,
+    /// OptiFine edition required per game version, from `version.<game version>`
+    /// keys, e.g. `1.20.1` to `H9`.
+    pub optifine_versions: BTreeMap<String, String>,
+    /// Features the pack needs from Iris, from `iris.features.required`.
+    pub required_features: Vec<String>,
+    pub hints: Vec<ShaderHint>,
+    /// File the shader mod saves the pack's options to, if it exists.
+    pub settings: Option<PathBuf>,
+}
+
+impl ShaderPack {
+    /// Returns the name to display: the file name without `.zip`.
+    pub fn display_name(&self) -> &str {
+        self.file_name
+            .strip_suffix(".zip")
+            .unwrap_or(&self.file_name)
+    }
+
+    /// Returns whether OptiFine can load the pack.
+    pub fn supports_optifine(&self) -> bool {
+        !self.hints.iter().any(|hint| {
+            matches!(
+                hint,
+                ShaderHint::RequiresIris(_) | ShaderHint::NotAShaderPack
+            )
+        })
+    }
+
+    fn apply_properties(&mut self, content: &str) {
+        let properties = parse_properties(content);
+        for (key, value) in &properties {
+            if let Some(profile) = key.strip_prefix("profile.") {
+                self.profiles.push(profile.to_string());
+            } else if let Some(game_version) = key.strip_prefix("version.") {
+                self.optifine_versions
+                    .insert(game_version.to_string(), value.clone());
+            }
+        }
+        if let Some(features) = properties.get("iris.features.required") {
+            self.required_features = features.split_whitespace().map(str::to_string).collect();
+        }
+        if !self.required_features.is_empty() {
+            self.hints
+                .push(ShaderHint::RequiresIris(self.required_features.clone()));
+        }
+    }
+}
+
+/// Lists the shader packs in a `shaderpacks` directory.
+///
+/// Every zip is listed, with a hint if it holds no pack, while folders count
+/// only if they contain a `shaders` folder. The `.txt` files the shader mods
+/// write next to the packs hold their settings. A pack whose
+/// `shaders.properties` cannot be read is still listed, without profiles.
+///
+/// # Arguments
+///
+/// * `path` - The `shaderpacks` directory of a game directory.
+///
+/// # Returns
+///
+/// * `io::Result<Vec<ShaderPack>>` - The packs sorted by file name, or an
+///   empty list if the directory does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn scan_shaderpacks_dir(path: &Path) -> io::Result<Vec<ShaderPack>> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut packs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let is_folder = entry.file_type()?.is_dir();
+        if !is_folder && !file_name.to_ascii_lowercase().ends_with(".zip") {
+            continue;
+        }
+        let settings = path.join(format!("{}.txt", file_name));
+        let mut pack = ShaderPack {
+            file_name,
+            path: entry.path(),
+            is_folder,
+            profiles: Vec::new(),
+            optifine_versions: BTreeMap::new(),
+            required_features: Vec::new(),
+            hints: Vec::new(),
+            settings: settings.is_file().then_some(settings),
+        };
+        if is_folder {
+            if !pack.path.join(SHADERS_FOLDER).is_dir() {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(pack.path.join(SHADERS_PROPERTIES)) {
+                pack.apply_properties(&content);
+            }
+        } else {
+            inspect_zip(&mut pack);
+        }
+        packs.push(pack);
+    }
+    packs.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(packs)
+}
+
+/// Reads the properties and hints of a zipped pack. A zip that cannot be
+/// opened is reported as no shader pack.
+fn inspect_zip(pack: &mut ShaderPack) {
+    let Ok(mut archive) = File::open(&pack.path)
+        .map_err(zip::result::ZipError::from)
+        .and_then(|file| ZipArchive::new(BufReader::new(file)))
+    else {
+        pack.hints.push(ShaderHint::NotAShaderPack);
+        return;
+    };
+    let shaders_prefix = format!("{}/", SHADERS_FOLDER);
+    if !archive
+        .file_names()
+        .any(|name| name.starts_with(&shaders_prefix))
+    {
+        let nested: Vec<String> = archive
+            .file_names()
+            .filter(|name| name.to_ascii_lowercase().ends_with(".zip"))
+            .map(str::to_string)
+            .collect();
+        pack.hints.push(if nested.is_empty() {
+            ShaderHint::NotAShaderPack
+        } else {
+            ShaderHint::ContainsPacks(nested)
+        });
+        return;
+    }
+    let mut content = String::new();
+    let read = archive
+        .by_name(SHADERS_PROPERTIES)
+        .ok()
+        .is_some_and(|mut file| file.read_to_string(&mut content).is_ok());
+    if read {
+        pack.apply_properties(&content);
+    }
+}
+
+/// Parses Java properties as written in `shaders.properties`: `key=value`
+/// lines, `#` and `!` comments, and values continued with a trailing `\`.
+/// Preprocessor lines such as `#ifdef` count as comments.
+fn parse_properties(content: &str) -> BTreeMap<String, String> {
+    let mut properties = BTreeMap::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let mut line = line.trim().to_string();
+        if line.is_empty() || line.starts_with(['#', '!']) {
+            continue;
+        }
+        while line.ends_with('\\') {
+            line.pop();
+            match lines.next() {
+                Some(next) => line.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+        let Some((key, value)) = line.split_once(['=', ':']) else {
+            continue;
+        };
+        properties.insert(key.trim().to_string(), value.trim().to_string());
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn reads_profiles_and_hints_of_packs() {
+        let dir = tempdir().unwrap();
+        write_zip(
+            &dir.path().join("Complementary.zip"),
+            &[(
+                SHADERS_PROPERTIES,
+                "# Profiles\nprofile.LOW=!SHADOWS\nprofile.HIGH=SHADOWS \\\n  BLOOM\n\
+                 version.1.20.1=H9\n#ifdef IRIS\niris.features.required=SSBO CUSTOM_IMAGES\n",
+            )],
+        );
+        write_zip(
+            &dir.path().join("Download.zip"),
+            &[("BSL_v8.2.zip", ""), ("readme.txt", "")],
+        );
+        fs::create_dir_all(dir.path().join("Extracted/shaders")).unwrap();
+        fs::create_dir(dir.path().join("empty")).unwrap();
+        fs::write(dir.path().join("Complementary.zip.txt"), "SHADOWS=true").unwrap();
+
+        let packs = scan_shaderpacks_dir(dir.path()).unwrap();
+        let names: Vec<&str> = packs.iter().map(ShaderPack::display_name).collect();
+        assert_eq!(names, ["Complementary", "Download", "Extracted"]);
+
+        let complementary = &packs[0];
+        assert_eq!(complementary.profiles, ["HIGH", "LOW"]);
+        assert_eq!(complementary.optifine_versions["1.20.1"], "H9");
+        assert_eq!(
+            complementary.hints,
+            vec![ShaderHint::RequiresIris(vec![
+                "SSBO".to_string(),
+                "CUSTOM_IMAGES".to_string()
+            ])]
+        );
+        assert!(!complementary.supports_optifine());
+        assert!(complementary.settings.is_some());
+
+        assert_eq!(
+            packs[1].hints,
+            vec![ShaderHint::ContainsPacks(vec!["BSL_v8.2.zip".to_string()])]
+        );
+        assert!(packs[2].is_folder && packs[2].supports_optifine());
+        assert!(
+            scan_shaderpacks_dir(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parses_continued_properties() {
+        let properties = parse_properties("a = 1\n! comment\nscreen=A B \\\n  C\nb:2\n");
+        assert_eq!(properties["a"], "1");
+        assert_eq!(properties["screen"], "A B C");
+        assert_eq!(properties["b"], "2");
+    }
+}
@@ -0,0 +1,274 @@
+use super::saves::LevelSummary;
+use crate::nbt::{NbtError, NbtFile};
+use crate::version::{McVersion, McVersionError};
+use std::path::Path;
+use thiserror::Error;
+
+/// Data versions of releases, oldest first. Worlds store the data version of
+/// the game that last saved them as `DataVersion` since 1.9.
+const DATA_VERSIONS: &[(&str, i32)] = &[
+    ("1.9", 169),
+    ("1.9.1", 175),
+    ("1.9.2", 176),
+    ("1.9.3", 183),
+    ("1.9.4", 184),
+    ("1.10", 510),
+    ("1.10.1", 511),
+    ("1.10.2", 512),
+    ("1.11", 819),
+    ("1.11.1", 921),
+    ("1.11.2", 922),
+    ("1.12", 1139),
+    ("1.12.1", 1241),
+    ("1.12.2", 1343),
+    ("1.13", 1519),
+    ("1.13.1", 1628),
+    ("1.13.2", 1631),
+    ("1.14", 1952),
+    ("1.14.1", 1957),
+    ("1.14.2", 1963),
+    ("1.14.3", 1968),
+    ("1.14.4", 1976),
+    ("1.15", 2225),
+    ("1.15.1", 2227),
+    ("1.15.2", 2230),
+    ("1.16", 2566),
+    ("1.16.1", 2567),
+    ("1.16.2", 2578),
+    ("1.16.3", 2580),
+    ("1.16.4", 2584),
+    ("1.16.5", 2586),
+    ("1.17", 2724),
+    ("1.17.1", 2730),
+    ("1.18", 2860),
+    ("1.18.1", 2865),
+    ("1.18.2", 2975),
+    ("1.19", 3105),
+    ("1.19.1", 3117),
+    ("1.19.2", 3120),
+    ("1.19.3", 3218),
+    ("1.19.4", 3337),
+    ("1.20", 3463),
+    ("1.20.1", 3465),
+    ("1.20.2", 3578),
+    ("1.20.3", 3698),
+    ("1.20.4", 3700),
+    ("1.20.5", 3837),
+    ("1.20.6", 3839),
+    ("1.21", 3953),
+    ("1.21.1", 3955),
+    ("1.21.2", 4080),
+    ("1.21.3", 4082),
+    ("1.21.4", 4189),
+    ("1.21.5", 4325),
+    ("1.21.6", 4435),
+    ("1.21.7", 4438),
+    ("1.21.8", 4440),
+    ("1.21.9", 4554),
+    ("1.21.10", 4556),
+];
+
+/// Error returned when the compatibility of a world cannot be checked.
+#[derive(Debug, Error)]
+pub enum WorldVersionError {
+    #[error("failed to read level.dat: {0}")]
+    Nbt(#[from] NbtError),
+    #[error(transparent)]
+    Version(#[from] McVersionError),
+}
+
+/// What opening a world in a game version does to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompatibilityStatus {
+    /// The world was last saved by the same data version.
+    Same,
+    /// The target is newer than the game that last saved the world. Opening
+    /// upgrades the world, which older versions cannot open safely afterwards.
+    Upgrade,
+    /// The world was last saved by a newer version. Opening it in the target
+    /// risks corrupting it or losing chunks and items.
+    Downgrade,
+    /// The data version of the target is not known, e.g. for a snapshot
+    /// close to the world's version, or neither side has one.
+    Unknown,
+}
+
+/// The result of [`check_world_compatibility`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldCompatibility {
+    pub status: CompatibilityStatus,
+    /// Name of the game version that last saved the world, e.g. `1.20.4`.
+    pub world_version: Option<String>,
+    /// `DataVersion` of the world; `None` for worlds from before 1.9.
+    pub world_data_version: Option<i32>,
+    /// Data version of the target, if it is a known release.
+    pub target_data_version: Option<i32>,
+}
+
+impl WorldCompatibility {
+    /// Returns whether the player should be warned and offered a backup
+    /// before the world is opened.
+    pub fn needs_warning(&self) -> bool {
+        matches!(
+            self.status,
+            CompatibilityStatus::Upgrade | CompatibilityStatus::Downgrade
+        )
+    }
+}
+
+/// Returns the data version of a release, e.g. `3465` for `1.20.1`, or `None`
+/// for snapshots, releases before 1.9 and releases newer than this crate.
+pub fn data_version(release: &str) -> Option<i32> {
+    let release = McVersion::parse(release).ok()?;
+    DATA_VERSIONS
+        .iter()
+        .find(|(id, _)| McVersion::parse(id).is_ok_and(|id| id == release))
+        .map(|(_, data_version)| *data_version)
+}
+
+/// Checks what opening a world in another game version does to it.
+///
+/// # Arguments
+///
+/// * `level_dat` - The `level.dat` of the world.
+/// * `target_mc_version` - The version the world is about to be opened in,
+///   e.g. `1.20.1` or `24w14a`.
+///
+/// # Returns
+///
+/// * `WorldCompatibility` - Whether the world would be upgraded or
+///   downgraded, with the versions compared.
+///
+/// # Errors
+///
+/// Returns an error if `level.dat` cannot be read or the target is not a
+/// version id.
+pub fn check_world_compatibility(
+    level_dat: &Path,
+    target_mc_version: &str,
+) -> Result<WorldCompatibility, WorldVersionError> {
+    let level = LevelSummary::from_level_dat(&NbtFile::read(level_dat)?);
+    let target = McVersion::parse(target_mc_version)?;
+    Ok(world_compatibility(&level, &target))
+}
+
+/// Compares the version that last saved a world with a target version.
+///
+/// Snapshots and releases missing from the table are placed between the
+/// known releases around them, so a world is still reported as upgraded or
+/// downgraded when it is older or newer than both.
+pub fn world_compatibility(level: &LevelSummary, target: &McVersion) -> WorldCompatibility {
+    let known: Vec<(McVersion, i32)> = DATA_VERSIONS
+        .iter()
+        .filter_map(|(id, data_version)| Some((McVersion::parse(id).ok()?, *data_version)))
+        .collect();
+    let exact = known
+        .iter()
+        .find(|(release, _)| release == target)
+        .map(|(_, data_version)| *data_version);
+    // The target's data version lies above `lower` and below `upper`.
+    let (lower, upper) = match exact {
+        Some(data_version) => (Some(data_version), Some(data_version)),
+        None => (
+            known
+                .iter()
+                .rev()
+                .find(|(release, _)| release < target)
+                .map(|(_, data_version)| *data_version),
+            known
+                .iter()
+                .find(|(release, _)| release > target)
+                .map(|(_, data_version)| *data_version),
+        ),
+    };
+
+    let world = level.data_version;
+    let status = match (world, exact) {
+        (Some(world), Some(target)) if world == target => CompatibilityStatus::Same,
+        (Some(world), _) if lower.is_some_and(|lower| world < lower) => {
+            CompatibilityStatus::Upgrade
+        }
+        (Some(world), None) if lower.is_some_and(|lower| world == lower) => {
+            CompatibilityStatus::Upgrade
+        }
+        (Some(world), _) if upper.is_some_and(|upper| world > upper) => {
+            CompatibilityStatus::Downgrade
+        }
+        (Some(world), None) if upper.is_some_and(|upper| world == upper) => {
+            CompatibilityStatus::Downgrade
+        }
+        // Worlds from before 1.9 are upgraded by any version with data
+        // versions.
+        (None, _) if lower.is_some() => CompatibilityStatus::Upgrade,
+        _ => CompatibilityStatus::Unknown,
+    };
+    WorldCompatibility {
+        status,
+        world_version: level.version_name.clone(),
+        world_data_version: world,
+        target_data_version: exact,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::{NbtCompound, NbtCompression, NbtValue};
+    use tempfile::tempdir;
+
+    fn level(data_version: Option<i32>) -> LevelSummary {
+        LevelSummary {
+            data_version,
+            ..LevelSummary::default()
+        }
+    }
+
+    fn check(data_version: Option<i32>, target: &str) -> CompatibilityStatus {
+        world_compatibility(&level(data_version), &McVersion::parse(target).unwrap()).status
+    }
+
+    #[test]
+    fn compares_data_versions() {
+        assert_eq!(data_version("1.20.1"), Some(3465));
+        assert_eq!(data_version("1.20.0"), Some(3463));
+        assert_eq!(data_version("23w31a"), None);
+
+        use CompatibilityStatus::*;
+        assert_eq!(check(Some(3465), "1.20.1"), Same);
+        assert_eq!(check(Some(3465), "1.21"), Upgrade);
+        assert_eq!(check(Some(3700), "1.20.1"), Downgrade);
+        assert_eq!(check(None, "1.12.2"), Upgrade);
+        assert_eq!(check(None, "1.8.9"), Unknown);
+        assert_eq!(check(Some(169), "1.8.9"), Downgrade);
+        // 23w31a leads to 1.20.2 (3578) and comes after 1.20.1 (3465).
+        assert_eq!(check(Some(3465), "23w31a"), Upgrade);
+        assert_eq!(check(Some(3578), "23w31a"), Downgrade);
+        assert_eq!(check(Some(3567), "23w31a"), Unknown);
+        // Releases newer than the table upgrade every known world.
+        assert_eq!(check(Some(4556), "1.30"), Upgrade);
+        assert_eq!(check(Some(9000), "1.30"), Unknown);
+    }
+
+    #[test]
+    fn checks_a_level_dat() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("level.dat");
+        let mut data = NbtCompound::new();
+        data.insert("DataVersion".to_string(), NbtValue::Int(3700));
+        let mut version = NbtCompound::new();
+        version.insert("Name".to_string(), NbtValue::String("1.20.4".to_string()));
+        data.insert("Version".to_string(), NbtValue::Compound(version));
+        let mut root = NbtCompound::new();
+        root.insert("Data".to_string(), NbtValue::Compound(data));
+        NbtFile::new(root, NbtCompression::Gzip)
+            .write(&path)
+            .unwrap();
+
+        let compatibility = check_world_compatibility(&path, "1.20.1").unwrap();
+        assert_eq!(compatibility.status, CompatibilityStatus::Downgrade);
+        assert_eq!(compatibility.world_version.as_deref(), Some("1.20.4"));
+        assert_eq!(compatibility.target_data_version, Some(3465));
+        assert!(compatibility.needs_warning());
+        assert!(check_world_compatibility(&path, "not a version").is_err());
+    }
+}
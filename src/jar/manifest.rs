@@ -0,0 +1,306 @@
+use super::JarError;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Path of the manifest inside a jar.
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+/// Folder holding the classes of a multi-release jar for each Java version.
+const VERSIONS_FOLDER: &str = "META-INF/versions/";
+
+/// The attributes of one section of a manifest, in file order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestSection {
+    pub attributes: Vec<(String, String)>,
+}
+
+impl ManifestSection {
+    /// Returns the value of an attribute. Names are case-insensitive.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A parsed `META-INF/MANIFEST.MF`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// The attributes of the whole jar.
+    pub main: ManifestSection,
+    /// The sections of single entries, keyed by their `Name`. Signed jars
+    /// list the digest of every entry here.
+    pub entries: BTreeMap<String, ManifestSection>,
+}
+
+impl Manifest {
+    /// Parses a manifest.
+    ///
+    /// Lines continued with a leading space are joined before decoding, so
+    /// characters split across lines survive. Lines without `:` are skipped.
+    pub fn parse(content: &[u8]) -> Self {
+        let mut manifest = Manifest::default();
+        let mut sections: Vec<ManifestSection> = vec![ManifestSection::default()];
+        let mut logical: Vec<Vec<u8>> = Vec::new();
+        for line in content.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            match (line.first(), logical.last_mut()) {
+                (Some(b' '), Some(last)) if !last.is_empty() => last.extend_from_slice(&line[1..]),
+                _ => logical.push(line.to_vec()),
+            }
+        }
+        for line in logical {
+            if line.is_empty() {
+                if !sections.last().is_some_and(|s| s.attributes.is_empty()) {
+                    sections.push(ManifestSection::default());
+                }
+                continue;
+            }
+            let line = String::from_utf8_lossy(&line);
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let section = sections.last_mut().expect("there is always a section");
+            section
+                .attributes
+                .push((key.trim().to_string(), value.trim().to_string()));
+        }
+        let mut sections = sections.into_iter().filter(|s| !s.attributes.is_empty());
+        if let Some(first) = sections.next() {
+            if first.get("Name").is_some() {
+                manifest.add_entry(first);
+            } else {
+                manifest.main = first;
+            }
+        }
+        for section in sections {
+            manifest.add_entry(section);
+        }
+        manifest
+    }
+
+    fn add_entry(&mut self, section: ManifestSection) {
+        if let Some(name) = section.get("Name") {
+            self.entries.insert(name.to_string(), section);
+        }
+    }
+
+    /// Returns an attribute of the main section.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.main.get(name)
+    }
+
+    /// Returns `Implementation-Version`.
+    pub fn implementation_version(&self) -> Option<&str> {
+        self.attribute("Implementation-Version")
+    }
+
+    /// Returns `Implementation-Title`.
+    pub fn implementation_title(&self) -> Option<&str> {
+        self.attribute("Implementation-Title")
+    }
+
+    /// Returns `Main-Class`, the class `java -jar` starts.
+    pub fn main_class(&self) -> Option<&str> {
+        self.attribute("Main-Class")
+    }
+
+    /// Returns `Automatic-Module-Name`, the module name of a jar without
+    /// `module-info.class`.
+    pub fn automatic_module_name(&self) -> Option<&str> {
+        self.attribute("Automatic-Module-Name")
+    }
+
+    /// Returns whether `Multi-Release` is `true`, which makes Java load the
+    /// classes under `META-INF/versions/<release>` on newer runtimes.
+    pub fn is_multi_release(&self) -> bool {
+        self.attribute("Multi-Release")
+            .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+    }
+
+    /// Returns the version of the jar: `Implementation-Version`, or else
+    /// `Specification-Version` or the OSGi `Bundle-Version`.
+    pub fn version(&self) -> Option<&str> {
+        self.implementation_version()
+            .or_else(|| self.attribute("Specification-Version"))
+            .or_else(|| self.attribute("Bundle-Version"))
+    }
+
+    /// Returns the name of the jar: `Automatic-Module-Name`, or else
+    /// `Implementation-Title` or the OSGi `Bundle-SymbolicName` without its
+    /// directives.
+    pub fn name(&self) -> Option<&str> {
+        self.automatic_module_name()
+            .or_else(|| self.implementation_title())
+            .or_else(|| {
+                self.attribute("Bundle-SymbolicName")
+                    .and_then(|name| name.split(';').next())
+                    .map(str::trim)
+            })
+    }
+}
+
+/// What the manifest and layout of a jar tell about it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JarInfo {
+    /// The manifest, if the jar has one.
+    pub manifest: Option<Manifest>,
+    /// Whether the jar declares itself multi-release.
+    pub multi_release: bool,
+    /// The Java releases with classes under `META-INF/versions`, ascending.
+    pub release_versions: Vec<u32>,
+    /// Whether the jar is a named module, with a `module-info.class`.
+    pub has_module_info: bool,
+    /// Coremod class of Forge before 1.13, from `FMLCorePlugin`. Old mods
+    /// without `mcmod.info` can only be recognized by it.
+    pub fml_core_plugin: Option<String>,
+    /// Tweaker class of LiteLoader and OptiFine era mods, from `TweakClass`.
+    pub tweak_class: Option<String>,
+}
+
+impl JarInfo {
+    /// Returns the version from the manifest, see [`Manifest::version`].
+    pub fn version(&self) -> Option<&str> {
+        self.manifest.as_ref()?.version()
+    }
+
+    /// Returns the name from the manifest, see [`Manifest::name`].
+    pub fn name(&self) -> Option<&str> {
+        self.manifest.as_ref()?.name()
+    }
+
+    /// Returns the main class from the manifest.
+    pub fn main_class(&self) -> Option<&str> {
+        self.manifest.as_ref()?.main_class()
+    }
+}
+
+/// Reads the manifest of a jar.
+///
+/// # Errors
+///
+/// Returns an error if the jar cannot be opened or read.
+pub fn read_manifest(jar: &Path) -> Result<Option<Manifest>, JarError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(jar)?))?;
+    manifest_of(&mut archive)
+}
+
+/// Reads the manifest and layout of a jar.
+///
+/// # Arguments
+///
+/// * `jar` - The library or mod jar.
+///
+/// # Returns
+///
+/// * `Result<JarInfo, JarError>` - The manifest attributes and the Java
+///   releases of a multi-release jar.
+///
+/// # Errors
+///
+/// Returns an error if the jar cannot be opened or read.
+pub fn read_jar_info(jar: &Path) -> Result<JarInfo, JarError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(jar)?))?;
+    let manifest = manifest_of(&mut archive)?;
+    let mut release_versions: Vec<u32> = archive
+        .file_names()
+        .filter_map(|name| {
+            name.strip_prefix(VERSIONS_FOLDER)?
+                .split('/')
+                .next()?
+                .parse()
+                .ok()
+        })
+        .collect();
+    release_versions.sort_unstable();
+    release_versions.dedup();
+    let attribute = |name| manifest.as_ref()?.attribute(name).map(str::to_string);
+    Ok(JarInfo {
+        multi_release: manifest.as_ref().is_some_and(Manifest::is_multi_release),
+        release_versions,
+        has_module_info: archive.index_for_name("module-info.class").is_some(),
+        fml_core_plugin: attribute("FMLCorePlugin"),
+        tweak_class: attribute("TweakClass"),
+        manifest,
+    })
+}
+
+/// Reads the manifest of an opened jar.
+fn manifest_of<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<Manifest>, JarError> {
+    let mut entry = match archive.by_name(MANIFEST_PATH) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = Vec::new();
+    entry.read_to_end(&mut content)?;
+    Ok(Some(Manifest::parse(&content)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn parses_sections_and_continued_lines() {
+        let manifest = Manifest::parse(
+            b"Manifest-Version: 1.0\r\nMain-Class: net.minecraft.client.main.Ma\r\n in\r\n\
+              implementation-version: 1.2.3\r\n\r\nName: a/B.class\r\nSHA-256-Digest: abc=\r\n\r\n",
+        );
+        assert_eq!(
+            manifest.main_class(),
+            Some("net.minecraft.client.main.Main")
+        );
+        assert_eq!(manifest.version(), Some("1.2.3"));
+        assert_eq!(
+            manifest.entries["a/B.class"].get("sha-256-digest"),
+            Some("abc=")
+        );
+        assert!(!manifest.is_multi_release());
+
+        let osgi = Manifest::parse(
+            b"Bundle-SymbolicName: org.lwjgl;singleton:=true\nBundle-Version: 3.3.1\n",
+        );
+        assert_eq!(osgi.name(), Some("org.lwjgl"));
+        assert_eq!(osgi.version(), Some("3.3.1"));
+    }
+
+    #[test]
+    fn reads_multi_release_jars() {
+        let dir = tempdir().unwrap();
+        let jar = dir.path().join("library.jar");
+        let mut writer = zip::ZipWriter::new(File::create(&jar).unwrap());
+        for (name, content) in [
+            (
+                MANIFEST_PATH,
+                "Manifest-Version: 1.0\nMulti-Release: true\nAutomatic-Module-Name: org.example\n\
+                 FMLCorePlugin: org.example.Core\n",
+            ),
+            ("META-INF/versions/17/org/example/A.class", ""),
+            ("META-INF/versions/9/module-info.class", ""),
+            ("org/example/A.class", ""),
+        ] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let info = read_jar_info(&jar).unwrap();
+        assert!(info.multi_release);
+        assert_eq!(info.release_versions, [9, 17]);
+        assert!(!info.has_module_info);
+        assert_eq!(info.name(), Some("org.example"));
+        assert_eq!(info.fml_core_plugin.as_deref(), Some("org.example.Core"));
+        assert_eq!(info.version(), None);
+    }
+}
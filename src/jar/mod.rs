@@ -0,0 +1,21 @@
+/// `META-INF/MANIFEST.MF` and what it tells about a jar.
+pub mod manifest;
+/// Verifying the signature entries of signed jars.
+pub mod signature;
+
+pub use manifest::{
+    JarInfo, MANIFEST_PATH, Manifest, ManifestSection, read_jar_info, read_manifest,
+};
+pub use signature::{JarSignature, JarSigner, SignatureProblem, check_jar_signature};
+
+use std::io;
+use thiserror::Error;
+
+/// Error returned when a jar cannot be read.
+#[derive(Debug, Error)]
+pub enum JarError {
+    #[error("failed to read jar: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid jar: {0}")]
+    Zip(#[from] zip::result::ZipError),
+}
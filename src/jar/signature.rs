@@ -0,0 +1,363 @@
+use super::JarError;
+use super::manifest::{MANIFEST_PATH, Manifest};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Extensions of the signature block files holding the signer certificates.
+const BLOCK_EXTENSIONS: [&str; 3] = ["RSA", "DSA", "EC"];
+
+/// A signer of a jar, from a `META-INF/<NAME>.SF` signature file.
+///
+/// The names are not authenticated: anyone can sign a jar with a signature
+/// file of any name and a certificate with any common name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarSigner {
+    /// The name of the signature file without extension, e.g. `MOJANGCS`.
+    pub name: String,
+    /// The signature block file, e.g. `META-INF/MOJANGCS.RSA`.
+    pub block: Option<String>,
+    /// Common names found in the certificates of the block, signer and
+    /// issuers in the order they appear.
+    pub certificate_names: Vec<String>,
+}
+
+/// A reason a signed jar does not match its signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureProblem {
+    /// The signature file has no signature block next to it.
+    MissingBlock(String),
+    /// The manifest changed after the signature file was written.
+    ManifestChanged(String),
+    /// The content of an entry does not match its digest in the manifest.
+    DigestMismatch(String),
+    /// An entry that is not listed in the manifest, added after signing.
+    UnsignedEntry(String),
+    /// An entry listed in the manifest is missing from the jar.
+    MissingEntry(String),
+}
+
+/// The result of [`check_jar_signature`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JarSignature {
+    pub signers: Vec<JarSigner>,
+    /// Number of entries whose content matched their digest.
+    pub verified_entries: usize,
+    pub problems: Vec<SignatureProblem>,
+}
+
+impl JarSignature {
+    /// Returns whether the jar has at least one signature file.
+    pub fn is_signed(&self) -> bool {
+        !self.signers.is_empty()
+    }
+
+    /// Returns whether the jar is signed and matches its signature.
+    pub fn is_intact(&self) -> bool {
+        self.is_signed() && self.problems.is_empty()
+    }
+
+    /// Returns whether `name` is among the signers or their certificate names.
+    ///
+    /// The signature block is not verified, so this only tells signers apart
+    /// for display and cannot be used to trust a jar: a tampered jar re-signed
+    /// as `META-INF/MOJANGCS.SF` matches `MOJANGCS`.
+    pub fn is_signed_by(&self, name: &str) -> bool {
+        self.signers.iter().any(|signer| {
+            signer.name.eq_ignore_ascii_case(name)
+                || signer.certificate_names.iter().any(|cn| cn == name)
+        })
+    }
+}
+
+/// Checks the signature of a jar.
+///
+/// The digests in the manifest are checked against the content of every
+/// entry, and the manifest digest of each signature file against the
+/// manifest. This detects jars damaged or changed after signing without
+/// being signed again.
+///
+/// The signature blocks are not verified cryptographically and the
+/// certificates are only read for their names, so the signers are
+/// unauthenticated: a changed jar signed again under any name is reported
+/// intact. To trust a jar, compare its hash with a known one instead.
+///
+/// # Arguments
+///
+/// * `jar` - The jar to check, e.g. a client jar or a library.
+///
+/// # Returns
+///
+/// * `Result<JarSignature, JarError>` - The signers and the problems found,
+///   or no signers if the jar is unsigned.
+///
+/// # Errors
+///
+/// Returns an error if the jar cannot be opened or read.
+pub fn check_jar_signature(jar: &Path) -> Result<JarSignature, JarError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(jar)?))?;
+    let mut signature = JarSignature::default();
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+
+    let signature_files: Vec<&String> = names
+        .iter()
+        .filter(|name| signature_file_name(name).is_some_and(|(_, ext)| ext == "SF"))
+        .collect();
+    if signature_files.is_empty() {
+        return Ok(signature);
+    }
+    let Some(manifest_bytes) = read_bytes(&mut archive, MANIFEST_PATH)? else {
+        signature
+            .problems
+            .push(SignatureProblem::MissingEntry(MANIFEST_PATH.to_string()));
+        return Ok(signature);
+    };
+    let manifest = Manifest::parse(&manifest_bytes);
+
+    for file in signature_files {
+        let (stem, _) = signature_file_name(file).expect("filtered above");
+        let block = BLOCK_EXTENSIONS
+            .iter()
+            .map(|ext| format!("META-INF/{}.{}", stem, ext))
+            .find(|block| names.contains(block));
+        let certificate_names = match &block {
+            Some(block) => read_bytes(&mut archive, block)?
+                .map(|der| common_names(&der))
+                .unwrap_or_default(),
+            None => {
+                signature
+                    .problems
+                    .push(SignatureProblem::MissingBlock(file.clone()));
+                Vec::new()
+            }
+        };
+        let sf = Manifest::parse(&read_bytes(&mut archive, file)?.unwrap_or_default());
+        let manifest_matches = sf.main.attributes.iter().all(|(key, expected)| {
+            key.strip_suffix("-Digest-Manifest")
+                .is_none_or(|algorithm| {
+                    digest_matches(algorithm, &manifest_bytes, expected) != Some(false)
+                })
+        });
+        if !manifest_matches {
+            signature
+                .problems
+                .push(SignatureProblem::ManifestChanged(file.clone()));
+        }
+        signature.signers.push(JarSigner {
+            name: stem.to_string(),
+            block,
+            certificate_names,
+        });
+    }
+
+    for (name, section) in &manifest.entries {
+        let digests: Vec<(&str, &str)> = section
+            .attributes
+            .iter()
+            .filter_map(|(key, value)| Some((key.strip_suffix("-Digest")?, value.as_str())))
+            .collect();
+        if digests.is_empty() {
+            continue;
+        }
+        let Some(content) = read_bytes(&mut archive, name)? else {
+            signature
+                .problems
+                .push(SignatureProblem::MissingEntry(name.clone()));
+            continue;
+        };
+        let matches = digests
+            .iter()
+            .filter_map(|(algorithm, expected)| digest_matches(algorithm, &content, expected))
+            .collect::<Vec<_>>();
+        if matches.contains(&false) {
+            signature
+                .problems
+                .push(SignatureProblem::DigestMismatch(name.clone()));
+        } else if !matches.is_empty() {
+            signature.verified_entries += 1;
+        }
+    }
+
+    for name in &names {
+        let exempt =
+            name.ends_with('/') || name == MANIFEST_PATH || signature_file_name(name).is_some();
+        if !exempt && !manifest.entries.contains_key(name) {
+            signature
+                .problems
+                .push(SignatureProblem::UnsignedEntry(name.clone()));
+        }
+    }
+    Ok(signature)
+}
+
+/// Splits `META-INF/NAME.EXT` into the name and the upper-case extension,
+/// for signature files and blocks directly in `META-INF`.
+fn signature_file_name(name: &str) -> Option<(&str, String)> {
+    let file = name.strip_prefix("META-INF/")?;
+    if file.contains('/') {
+        return None;
+    }
+    let (stem, extension) = file.rsplit_once('.')?;
+    let extension = extension.to_ascii_uppercase();
+    (extension == "SF"
+        || BLOCK_EXTENSIONS.contains(&extension.as_str())
+        || stem.starts_with("SIG-"))
+    .then_some((stem, extension))
+}
+
+fn read_bytes<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, JarError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = Vec::new();
+    entry.read_to_end(&mut content)?;
+    Ok(Some(content))
+}
+
+/// Compares the digest of `data` with a base64 digest. Returns `None` for
+/// algorithms that are not supported.
+fn digest_matches(algorithm: &str, data: &[u8], expected: &str) -> Option<bool> {
+    let actual = match algorithm.to_ascii_uppercase().as_str() {
+        "SHA1" | "SHA-1" => Sha1::digest(data).to_vec(),
+        "SHA-256" => Sha256::digest(data).to_vec(),
+        "SHA-384" => Sha384::digest(data).to_vec(),
+        "SHA-512" => Sha512::digest(data).to_vec(),
+        _ => return None,
+    };
+    Some(
+        STANDARD
+            .decode(expected.trim())
+            .is_ok_and(|expected| expected == actual),
+    )
+}
+
+/// Finds the common names (`CN`) in DER-encoded certificates.
+fn common_names(der: &[u8]) -> Vec<String> {
+    // The OID 2.5.4.3, followed by the name as a string.
+    const CN_OID: [u8; 5] = [0x06, 0x03, 0x55, 0x04, 0x03];
+    let mut names = Vec::new();
+    let mut rest = der;
+    while let Some(index) = rest.windows(CN_OID.len()).position(|w| w == CN_OID) {
+        rest = &rest[index + CN_OID.len()..];
+        let [tag, len, ..] = *rest else { break };
+        let len = usize::from(len);
+        // UTF8String, PrintableString, T61String and IA5String.
+        if matches!(tag, 0x0c | 0x13 | 0x14 | 0x16)
+            && len < 0x80
+            && let Some(value) = rest.get(2..2 + len)
+        {
+            let name = String::from_utf8_lossy(value).into_owned();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn digest(data: &[u8]) -> String {
+        STANDARD.encode(Sha256::digest(data))
+    }
+
+    fn write_jar(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// A fake certificate holding the DER encoding of `CN=Mojang AB`.
+    fn certificate() -> Vec<u8> {
+        let mut der = vec![0x30, 0x10];
+        der.extend_from_slice(&[0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x09]);
+        der.extend_from_slice(b"Mojang AB");
+        der
+    }
+
+    #[test]
+    fn verifies_signed_jars_and_detects_changes() {
+        let dir = tempdir().unwrap();
+        let class = b"class content".as_slice();
+        let manifest = format!(
+            "Manifest-Version: 1.0\r\n\r\nName: net/minecraft/A.class\r\nSHA-256-Digest: {}\r\n\r\n",
+            digest(class)
+        );
+        let sf = format!(
+            "Signature-Version: 1.0\r\nSHA-256-Digest-Manifest: {}\r\n",
+            digest(manifest.as_bytes())
+        );
+        let cert = certificate();
+        let jar = dir.path().join("client.jar");
+        write_jar(
+            &jar,
+            &[
+                (MANIFEST_PATH, manifest.as_bytes()),
+                ("META-INF/MOJANGCS.SF", sf.as_bytes()),
+                ("META-INF/MOJANGCS.RSA", &cert),
+                ("net/minecraft/A.class", class),
+            ],
+        );
+        let signature = check_jar_signature(&jar).unwrap();
+        assert!(signature.is_intact());
+        assert_eq!(signature.verified_entries, 1);
+        assert_eq!(signature.signers[0].certificate_names, ["Mojang AB"]);
+        assert!(signature.is_signed_by("Mojang AB") && signature.is_signed_by("mojangcs"));
+
+        write_jar(
+            &jar,
+            &[
+                (MANIFEST_PATH, manifest.as_bytes()),
+                ("META-INF/MOJANGCS.SF", sf.as_bytes()),
+                ("net/minecraft/A.class", b"patched"),
+                ("net/minecraft/B.class", b"added"),
+            ],
+        );
+        let signature = check_jar_signature(&jar).unwrap();
+        assert!(signature.is_signed() && !signature.is_intact());
+        assert_eq!(
+            signature.problems,
+            [
+                SignatureProblem::MissingBlock("META-INF/MOJANGCS.SF".to_string()),
+                SignatureProblem::DigestMismatch("net/minecraft/A.class".to_string()),
+                SignatureProblem::UnsignedEntry("net/minecraft/B.class".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unsigned_jars() {
+        let dir = tempdir().unwrap();
+        let jar = dir.path().join("library.jar");
+        write_jar(
+            &jar,
+            &[
+                (MANIFEST_PATH, b"Manifest-Version: 1.0\n"),
+                ("a/B.class", b""),
+            ],
+        );
+        let signature = check_jar_signature(&jar).unwrap();
+        assert!(!signature.is_signed() && !signature.is_intact());
+        assert!(signature.problems.is_empty());
+    }
+}
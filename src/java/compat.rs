@@ -0,0 +1,183 @@
+use super::JavaInstallation;
+use crate::version::VersionJson;
+use thiserror::Error;
+
+/// Main class of LaunchWrapper, which fails on Java 9 and later.
+const LAUNCHWRAPPER_MAIN_CLASS: &str = "net.minecraft.launchwrapper.Launch";
+
+/// The Java versions a Minecraft version can run on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRequirement {
+    /// The lowest supported major version.
+    pub min_major: u32,
+    /// The highest supported major version, if there is one.
+    pub max_major: Option<u32>,
+    /// The Mojang runtime component, e.g. `java-runtime-gamma`, if the version names one.
+    pub component: Option<String>,
+}
+
+impl JavaRequirement {
+    /// Returns `true` if a runtime of major version `major` satisfies the requirement.
+    pub fn accepts(&self, major: u32) -> bool {
+        major >= self.min_major && self.max_major.is_none_or(|max| major <= max)
+    }
+}
+
+/// Why a Java installation cannot run a Minecraft version.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JavaMismatch {
+    #[error("{version} requires Java {required} or newer, but {executable} is Java {found}")]
+    TooOld {
+        version: String,
+        required: u32,
+        found: u32,
+        executable: String,
+    },
+    #[error("{version} requires Java {maximum} or older, but {executable} is Java {found}")]
+    TooNew {
+        version: String,
+        maximum: u32,
+        found: u32,
+        executable: String,
+    },
+}
+
+/// Returns the Java versions a Minecraft version can run on.
+///
+/// The `javaVersion` field is used when present. Otherwise the requirement is
+/// derived from the release id (the `inheritsFrom` id for loader profiles):
+/// Java 8 before 1.17, 16 for 1.17, 17 up to 1.20.4 and 21 from 1.20.5. Versions
+/// launched through LaunchWrapper are limited to Java 8.
+///
+/// # Arguments
+///
+/// * `version` - The version JSON.
+///
+/// # Returns
+///
+/// * `JavaRequirement` - The supported major versions.
+pub fn required_java_for(version: &VersionJson) -> JavaRequirement {
+    let (min_major, component) = match &version.java_version {
+        Some(java) => (java.major_version, Some(java.component.clone())),
+        None => {
+            let id = version.inherits_from.as_deref().unwrap_or(&version.id);
+            (java_for_release(id), None)
+        }
+    };
+    let max_major = (version.main_class.as_deref() == Some(LAUNCHWRAPPER_MAIN_CLASS)).then_some(8);
+    JavaRequirement {
+        min_major,
+        max_major,
+        component,
+    }
+}
+
+/// Checks whether `java` can run `version`.
+///
+/// # Arguments
+///
+/// * `java` - The detected Java installation.
+/// * `version` - The version JSON.
+///
+/// # Errors
+///
+/// Returns a [`JavaMismatch`] describing why the installation is unsuitable.
+pub fn check_java(java: &JavaInstallation, version: &VersionJson) -> Result<(), JavaMismatch> {
+    let requirement = required_java_for(version);
+    let executable = java.executable.display().to_string();
+    if java.major < requirement.min_major {
+        return Err(JavaMismatch::TooOld {
+            version: version.id.clone(),
+            required: requirement.min_major,
+            found: java.major,
+            executable,
+        });
+    }
+    if let Some(maximum) = requirement.max_major
+        && java.major > maximum
+    {
+        return Err(JavaMismatch::TooNew {
+            version: version.id.clone(),
+            maximum,
+            found: java.major,
+            executable,
+        });
+    }
+    Ok(())
+}
+
+/// The minimum Java version of a release id such as `1.20.1`. Ids that are not
+/// releases, e.g. old snapshots, default to Java 8.
+fn java_for_release(id: &str) -> u32 {
+    let mut parts = id.split('.').map(|p| p.parse::<u32>().ok());
+    let (Some(Some(1)), Some(Some(minor))) = (parts.next(), parts.next()) else {
+        return 8;
+    };
+    let patch = parts.next().flatten().unwrap_or(0);
+    match (minor, patch) {
+        (21.., _) | (20, 5..) => 21,
+        (18.., _) => 17,
+        (17, _) => 16,
+        _ => 8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn java(major: u32) -> JavaInstallation {
+        JavaInstallation {
+            executable: PathBuf::from("/usr/bin/java"),
+            home: PathBuf::from("/usr"),
+            version: format!("{}.0.1", major),
+            major,
+            vendor: None,
+            arch: None,
+        }
+    }
+
+    fn version(json: &str) -> VersionJson {
+        VersionJson::from_json(json).unwrap()
+    }
+
+    #[test]
+    fn uses_java_version_field_and_release_history() {
+        let modern = version(
+            r#"{"id": "1.20.1", "javaVersion": {"component": "java-runtime-gamma", "majorVersion": 17}}"#,
+        );
+        let requirement = required_java_for(&modern);
+        assert_eq!(requirement.min_major, 17);
+        assert_eq!(requirement.component.as_deref(), Some("java-runtime-gamma"));
+
+        assert_eq!(java_for_release("1.12.2"), 8);
+        assert_eq!(java_for_release("1.17.1"), 16);
+        assert_eq!(java_for_release("1.20.4"), 17);
+        assert_eq!(java_for_release("1.20.5"), 21);
+        assert_eq!(java_for_release("1.21"), 21);
+        assert_eq!(java_for_release("b1.7.3"), 8);
+
+        let fabric = version(r#"{"id": "fabric-loader-0.16.9-1.18.2", "inheritsFrom": "1.18.2"}"#);
+        assert_eq!(required_java_for(&fabric).min_major, 17);
+    }
+
+    #[test]
+    fn reports_too_old_and_too_new_runtimes() {
+        let modern = version(r#"{"id": "1.20.5"}"#);
+        assert!(check_java(&java(21), &modern).is_ok());
+        let error = check_java(&java(17), &modern).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1.20.5 requires Java 21 or newer, but /usr/bin/java is Java 17"
+        );
+
+        let forge =
+            version(r#"{"id": "1.12.2-forge", "mainClass": "net.minecraft.launchwrapper.Launch"}"#);
+        assert!(check_java(&java(8), &forge).is_ok());
+        assert!(matches!(
+            check_java(&java(17), &forge),
+            Err(JavaMismatch::TooNew { maximum: 8, .. })
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+/// Heap size from which Aikar's flags switch to their large heap values, in
+/// MiB.
+const AIKAR_LARGE_HEAP_MB: u64 = 12 * 1024;
+
+/// A curated set of garbage collector flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JvmPreset {
+    /// The G1 flags the official launcher passes.
+    #[default]
+    Vanilla,
+    /// G1 tuned for short pauses in the client, for heavier modpacks.
+    Client,
+    /// Aikar's G1 flags, made for servers and large modpacks. The whole heap
+    /// is reserved on start.
+    Aikar,
+    /// The Z garbage collector, with pauses of a millisecond or less at the
+    /// cost of some throughput. Needs Java 11 or later; older runtimes get
+    /// [`JvmPreset::Vanilla`] instead.
+    Zgc,
+}
+
+impl JvmPreset {
+    /// All presets, in the order a launcher lists them.
+    pub const ALL: [JvmPreset; 4] = [
+        JvmPreset::Vanilla,
+        JvmPreset::Client,
+        JvmPreset::Aikar,
+        JvmPreset::Zgc,
+    ];
+
+    /// Returns the id the preset is stored under, e.g. `aikar`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JvmPreset::Vanilla => "vanilla",
+            JvmPreset::Client => "client",
+            JvmPreset::Aikar => "aikar",
+            JvmPreset::Zgc => "zgc",
+        }
+    }
+
+    /// Parses a preset id, ignoring case.
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.as_str().eq_ignore_ascii_case(id))
+    }
+
+    /// Returns the JVM arguments of the preset, heap size included.
+    ///
+    /// The arguments go before the main class, e.g. through
+    /// [`crate::process::LaunchCommand::with_jvm_args`], in place of
+    /// [`super::MemoryRecommendation::jvm_arguments`].
+    ///
+    /// # Arguments
+    ///
+    /// * `max_mb` - The maximum heap in MiB, passed as `-Xmx`.
+    /// * `java_major` - The major version of the Java runtime, e.g. `17`.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The heap and garbage collector arguments.
+    pub fn arguments(self, max_mb: u64, java_major: u32) -> Vec<String> {
+        let max_mb = max_mb.max(512);
+        let preset = if self == JvmPreset::Zgc && java_major < 11 {
+            JvmPreset::Vanilla
+        } else {
+            self
+        };
+        // Servers and ZGC do best with the whole heap from the start, the
+        // client starts small and grows as needed.
+        let min_mb = match preset {
+            JvmPreset::Vanilla | JvmPreset::Client => (max_mb / 2).max(512),
+            JvmPreset::Aikar | JvmPreset::Zgc => max_mb,
+        };
+        let mut args = vec![format!("-Xms{}M", min_mb), format!("-Xmx{}M", max_mb)];
+        let flags: &[&str] = match preset {
+            JvmPreset::Vanilla => &[
+                "-XX:+UnlockExperimentalVMOptions",
+                "-XX:+UseG1GC",
+                "-XX:G1NewSizePercent=20",
+                "-XX:G1ReservePercent=20",
+                "-XX:MaxGCPauseMillis=50",
+                "-XX:G1HeapRegionSize=32M",
+            ],
+            JvmPreset::Client => &[
+                "-XX:+UnlockExperimentalVMOptions",
+                "-XX:+UseG1GC",
+                "-XX:+ParallelRefProcEnabled",
+                "-XX:+DisableExplicitGC",
+                "-XX:MaxGCPauseMillis=37",
+                "-XX:G1NewSizePercent=23",
+                "-XX:G1ReservePercent=20",
+                "-XX:G1HeapRegionSize=16M",
+                "-XX:G1MixedGCCountTarget=3",
+                "-XX:InitiatingHeapOccupancyPercent=20",
+                "-XX:G1MixedGCLiveThresholdPercent=90",
+                "-XX:SurvivorRatio=32",
+                "-XX:MaxTenuringThreshold=1",
+            ],
+            JvmPreset::Aikar => {
+                let large = max_mb >= AIKAR_LARGE_HEAP_MB;
+                args.extend(
+                    [
+                        "-XX:+UseG1GC",
+                        "-XX:+ParallelRefProcEnabled",
+                        "-XX:MaxGCPauseMillis=200",
+                        "-XX:+UnlockExperimentalVMOptions",
+                        "-XX:+DisableExplicitGC",
+                        "-XX:+AlwaysPreTouch",
+                    ]
+                    .map(String::from),
+                );
+                let (new_size, max_new_size, region, reserve, occupancy) = if large {
+                    (40, 50, "16M", 15, 20)
+                } else {
+                    (30, 40, "8M", 20, 15)
+                };
+                args.extend([
+                    format!("-XX:G1NewSizePercent={}", new_size),
+                    format!("-XX:G1MaxNewSizePercent={}", max_new_size),
+                    format!("-XX:G1HeapRegionSize={}", region),
+                    format!("-XX:G1ReservePercent={}", reserve),
+                    "-XX:G1HeapWastePercent=5".to_string(),
+                    "-XX:G1MixedGCCountTarget=4".to_string(),
+                    format!("-XX:InitiatingHeapOccupancyPercent={}", occupancy),
+                ]);
+                &[
+                    "-XX:G1MixedGCLiveThresholdPercent=90",
+                    "-XX:G1RSetUpdatingPauseTimePercent=5",
+                    "-XX:SurvivorRatio=32",
+                    "-XX:+PerfDisableSharedMem",
+                    "-XX:MaxTenuringThreshold=1",
+                    "-Dusing.aikars.flags=https://mcflags.emc.gs",
+                    "-Daikars.new.flags=true",
+                ]
+            }
+            JvmPreset::Zgc => {
+                // ZGC is experimental before Java 15. Its generational mode
+                // is opt-in on Java 21 and 22, the default from 23 on.
+                if java_major < 15 {
+                    args.push("-XX:+UnlockExperimentalVMOptions".to_string());
+                }
+                args.push("-XX:+UseZGC".to_string());
+                if (21..23).contains(&java_major) {
+                    args.push("-XX:+ZGenerational".to_string());
+                }
+                &["-XX:+AlwaysPreTouch", "-XX:+DisableExplicitGC"]
+            }
+        };
+        args.extend(flags.iter().map(|flag| flag.to_string()));
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_heap_and_gc_arguments() {
+        let vanilla = JvmPreset::Vanilla.arguments(4096, 17);
+        assert_eq!(vanilla[..2], ["-Xms2048M", "-Xmx4096M"]);
+        assert!(vanilla.contains(&"-XX:G1HeapRegionSize=32M".to_string()));
+
+        let aikar = JvmPreset::Aikar.arguments(16384, 17);
+        assert_eq!(aikar[..2], ["-Xms16384M", "-Xmx16384M"]);
+        assert!(aikar.contains(&"-XX:G1HeapRegionSize=16M".to_string()));
+        assert!(
+            JvmPreset::Aikar
+                .arguments(8192, 17)
+                .contains(&"-XX:G1HeapRegionSize=8M".to_string())
+        );
+        assert_eq!(JvmPreset::from_id("AIKAR"), Some(JvmPreset::Aikar));
+    }
+
+    #[test]
+    fn adapts_zgc_to_the_java_version() {
+        let java21 = JvmPreset::Zgc.arguments(8192, 21);
+        assert!(java21.contains(&"-XX:+ZGenerational".to_string()));
+        assert!(!java21.contains(&"-XX:+UnlockExperimentalVMOptions".to_string()));
+
+        let java11 = JvmPreset::Zgc.arguments(8192, 11);
+        assert!(java11.contains(&"-XX:+UnlockExperimentalVMOptions".to_string()));
+        assert!(
+            !JvmPreset::Zgc
+                .arguments(8192, 25)
+                .contains(&"-XX:+ZGenerational".to_string())
+        );
+        assert_eq!(
+            JvmPreset::Zgc.arguments(4096, 8),
+            JvmPreset::Vanilla.arguments(4096, 8)
+        );
+    }
+}
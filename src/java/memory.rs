@@ -0,0 +1,194 @@
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+
+const MIB: u64 = 1024 * 1024;
+
+/// Heap of a vanilla game from 1.13 on, in MiB.
+const MODERN_BASE_MB: u64 = 2048;
+/// Heap of a vanilla game before 1.13, in MiB.
+const LEGACY_BASE_MB: u64 = 1024;
+/// Extra heap per mod from 1.13 on, in MiB.
+const MODERN_PER_MOD_MB: u64 = 24;
+/// Extra heap per mod before 1.13, in MiB. Old mods are usually smaller.
+const LEGACY_PER_MOD_MB: u64 = 16;
+/// Largest recommended heap, in MiB. Bigger heaps mostly lengthen GC pauses.
+const MAX_RECOMMENDED_MB: u64 = 12 * 1024;
+/// Smallest heap worth recommending, in MiB.
+const MIN_RECOMMENDED_MB: u64 = 1024;
+/// Memory always left to the system and the JVM's own overhead, in MiB.
+const MIN_RESERVED_MB: u64 = 2048;
+
+/// The physical memory of the computer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemMemory {
+    /// Installed memory in bytes.
+    pub total: u64,
+    /// Memory available to new processes without swapping, in bytes.
+    pub available: u64,
+}
+
+impl SystemMemory {
+    /// Reads the memory of the computer.
+    ///
+    /// On Linux, the limits of the launcher's cgroup are applied, so containers
+    /// report their own memory rather than the host's.
+    pub fn detect() -> Self {
+        let system = System::new_with_specifics(
+            RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()),
+        );
+        let mut memory = Self {
+            total: system.total_memory(),
+            available: system.available_memory(),
+        };
+        if let Some(limits) = system.cgroup_limits() {
+            memory.total = memory.total.min(limits.total_memory);
+            memory.available = memory.available.min(limits.free_memory);
+        }
+        memory
+    }
+
+    /// Returns the installed memory in MiB.
+    pub fn total_mb(&self) -> u64 {
+        self.total / MIB
+    }
+
+    /// Returns the available memory in MiB.
+    pub fn available_mb(&self) -> u64 {
+        self.available / MIB
+    }
+}
+
+/// Recommended heap sizes for the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRecommendation {
+    /// Maximum heap in MiB, passed as `-Xmx`.
+    pub max_mb: u64,
+    /// Initial heap in MiB, passed as `-Xms`.
+    pub min_mb: u64,
+    /// Whether the recommendation was lowered to fit the computer's memory,
+    /// in which case the game may run short of memory.
+    pub limited_by_system: bool,
+}
+
+impl MemoryRecommendation {
+    /// Returns the `-Xms` and `-Xmx` JVM arguments.
+    pub fn jvm_arguments(&self) -> [String; 2] {
+        [
+            format!("-Xms{}M", self.min_mb),
+            format!("-Xmx{}M", self.max_mb),
+        ]
+    }
+}
+
+/// Recommends heap sizes for a game version and number of mods on this
+/// computer.
+///
+/// See [`recommend_memory_with`].
+pub fn recommend_memory(mc_version: &str, mod_count: usize) -> MemoryRecommendation {
+    recommend_memory_with(mc_version, mod_count, &SystemMemory::detect())
+}
+
+/// Recommends heap sizes for a game version and number of mods.
+///
+/// The maximum heap starts at 2 GiB for 1.13 and later (1 GiB before) and grows
+/// with every mod, up to 12 GiB. It never takes more than what is left after
+/// reserving a quarter of the installed memory, and at least 2 GiB, for the
+/// system. The initial heap is half the maximum, so small games start lean
+/// while large packs avoid growing the heap step by step during loading. Sizes
+/// are multiples of 512 MiB.
+///
+/// # Arguments
+///
+/// * `mc_version` - The Minecraft version, e.g. `1.20.1`. Versions that are not
+///   `1.x` releases, such as snapshots, count as recent.
+/// * `mod_count` - The number of installed mods.
+/// * `memory` - The memory of the computer.
+///
+/// # Returns
+///
+/// * `MemoryRecommendation` - The `-Xmx` and `-Xms` values.
+pub fn recommend_memory_with(
+    mc_version: &str,
+    mod_count: usize,
+    memory: &SystemMemory,
+) -> MemoryRecommendation {
+    let (base, per_mod) = if is_legacy(mc_version) {
+        (LEGACY_BASE_MB, LEGACY_PER_MOD_MB)
+    } else {
+        (MODERN_BASE_MB, MODERN_PER_MOD_MB)
+    };
+    let wanted = round_up(base + per_mod * mod_count as u64).min(MAX_RECOMMENDED_MB);
+
+    let total = memory.total_mb();
+    let reserved = (total / 4).max(MIN_RESERVED_MB);
+    let affordable = round_down(total.saturating_sub(reserved)).max(MIN_RECOMMENDED_MB);
+    let max_mb = wanted.min(affordable);
+    MemoryRecommendation {
+        max_mb,
+        min_mb: round_down(max_mb / 2).max(512),
+        limited_by_system: max_mb < wanted,
+    }
+}
+
+/// Returns whether `version` is a release before 1.13.
+fn is_legacy(version: &str) -> bool {
+    let mut parts = version
+        .split(['.', '-', ' '])
+        .map(|p| p.parse::<u32>().ok());
+    matches!(
+        (parts.next(), parts.next()),
+        (Some(Some(1)), Some(Some(minor))) if minor < 13
+    )
+}
+
+fn round_up(mb: u64) -> u64 {
+    mb.div_ceil(512) * 512
+}
+
+fn round_down(mb: u64) -> u64 {
+    mb / 512 * 512
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(total_gb: u64) -> SystemMemory {
+        SystemMemory {
+            total: total_gb * 1024 * MIB,
+            available: total_gb * 512 * MIB,
+        }
+    }
+
+    #[test]
+    fn scales_with_version_and_mods() {
+        let vanilla = recommend_memory_with("1.20.1", 0, &memory(16));
+        assert_eq!((vanilla.max_mb, vanilla.min_mb), (2048, 1024));
+        assert_eq!(vanilla.jvm_arguments(), ["-Xms1024M", "-Xmx2048M"]);
+
+        let legacy = recommend_memory_with("1.7.10", 0, &memory(16));
+        assert_eq!(legacy.max_mb, 1024);
+        assert_eq!(recommend_memory_with("24w14a", 0, &memory(16)).max_mb, 2048);
+
+        let pack = recommend_memory_with("1.20.1", 200, &memory(16));
+        assert_eq!(pack.max_mb, 7168);
+        assert!(!pack.limited_by_system);
+        assert_eq!(
+            recommend_memory_with("1.20.1", 1000, &memory(64)).max_mb,
+            12 * 1024
+        );
+    }
+
+    #[test]
+    fn leaves_room_for_the_system() {
+        let pack = recommend_memory_with("1.20.1", 200, &memory(8));
+        assert_eq!(pack.max_mb, 6144);
+        assert!(pack.limited_by_system);
+
+        let tiny = recommend_memory_with("1.20.1", 0, &memory(2));
+        assert_eq!((tiny.max_mb, tiny.min_mb), (1024, 512));
+        assert!(tiny.limited_by_system);
+
+        let detected = SystemMemory::detect();
+        assert!(detected.total > 0);
+    }
+}
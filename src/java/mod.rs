@@ -0,0 +1,335 @@
+/// Java requirements of Minecraft versions.
+pub mod compat;
+/// Garbage collector flag presets such as Aikar's flags.
+pub mod flags;
+/// System memory and heap size recommendations.
+pub mod memory;
+/// Mojang's managed Java runtimes.
+pub mod runtime;
+
+pub use compat::{JavaMismatch, JavaRequirement, check_java, required_java_for};
+pub use flags::JvmPreset;
+pub use memory::{MemoryRecommendation, SystemMemory, recommend_memory, recommend_memory_with};
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Error returned when a Java installation cannot be inspected.
+#[derive(Debug, Error)]
+pub enum JavaError {
+    #[error("failed to run java: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not determine the Java version of {0}")]
+    UnknownVersion(PathBuf),
+}
+
+/// An installed Java runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaInstallation {
+    /// Path of the `java` executable.
+    pub executable: PathBuf,
+    /// The Java home directory (the parent of `bin`).
+    pub home: PathBuf,
+    /// Full version string, e.g. `17.0.9` or `1.8.0_381`.
+    pub version: String,
+    /// Major version, e.g. `17` or `8`.
+    pub major: u32,
+    /// Vendor, e.g. `Eclipse Adoptium`, if known.
+    pub vendor: Option<String>,
+    /// Architecture as reported by the runtime, e.g. `x86_64` or `aarch64`, if known.
+    pub arch: Option<String>,
+}
+
+/// Returns the file name of the `java` executable on this platform.
+pub fn java_executable_name() -> &'static str {
+    if cfg!(windows) { "java.exe" } else { "java" }
+}
+
+/// Extracts the major version from a Java version string.
+///
+/// Handles both the legacy `1.x` scheme (`1.8.0_381` is 8) and the modern one
+/// (`17.0.9` is 17).
+///
+/// # Arguments
+///
+/// * `version` - The version string.
+///
+/// # Returns
+///
+/// * `Option<u32>` - The major version, or `None` if the string is not a version.
+pub fn major_version(version: &str) -> Option<u32> {
+    let mut parts = version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|p| !p.is_empty());
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Parses the `release` file found in the home directory of most JDKs and JREs.
+///
+/// # Returns
+///
+/// * `Option<(String, Option<String>, Option<String>)>` - Version, vendor and
+///   architecture, or `None` if the file has no `JAVA_VERSION`.
+pub fn parse_release_file(content: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let value = |key: &str| {
+        content.lines().find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            (k.trim() == key).then(|| v.trim().trim_matches('"').to_string())
+        })
+    };
+    Some((
+        value("JAVA_VERSION")?,
+        value("IMPLEMENTOR"),
+        value("OS_ARCH"),
+    ))
+}
+
+/// Parses the output of `java -version`, which is written to stderr.
+///
+/// # Returns
+///
+/// * `Option<(String, Option<String>, Option<String>)>` - Version, vendor and
+///   architecture, or `None` if no version line was found. The architecture is
+///   only known as `64-bit` or `32-bit` from this output.
+pub fn parse_version_output(output: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let mut lines = output.lines().map(str::trim);
+    let version = lines.by_ref().find_map(|line| {
+        if !line.contains(" version ") {
+            return None;
+        }
+        let start = line.find('"')? + 1;
+        let end = start + line[start..].find('"')?;
+        Some(line[start..end].to_string())
+    })?;
+    let runtime = lines.next().unwrap_or("");
+    let vm = lines.next().unwrap_or("");
+    let vendor = [
+        "Temurin",
+        "Zulu",
+        "Corretto",
+        "GraalVM",
+        "Microsoft",
+        "JetBrains",
+        "Oracle",
+    ]
+    .into_iter()
+    .find(|vendor| runtime.contains(vendor) || vm.contains(vendor))
+    .map(str::to_string);
+    let arch = if vm.contains("64-Bit") {
+        Some("64-bit".to_string())
+    } else if vm.is_empty() {
+        None
+    } else {
+        Some("32-bit".to_string())
+    };
+    Some((version, vendor, arch))
+}
+
+/// Inspects the Java runtime whose executable is at `executable`.
+///
+/// The `release` file of the Java home is read if present; otherwise
+/// `java -version` is run.
+///
+/// # Errors
+///
+/// Returns an error if the runtime cannot be run or reports no version.
+pub fn inspect_java(executable: &Path) -> Result<JavaInstallation, JavaError> {
+    let home = executable
+        .parent()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let parsed = match fs::read_to_string(home.join("release")) {
+        Ok(release) if executable.is_file() => parse_release_file(&release),
+        _ => None,
+    };
+    let (version, vendor, arch) = match parsed {
+        Some(parsed) => parsed,
+        None => {
+            let output = Command::new(executable).arg("-version").output()?;
+            parse_version_output(&String::from_utf8_lossy(&output.stderr))
+                .ok_or_else(|| JavaError::UnknownVersion(executable.to_path_buf()))?
+        }
+    };
+    let major = major_version(&version)
+        .ok_or_else(|| JavaError::UnknownVersion(executable.to_path_buf()))?;
+    Ok(JavaInstallation {
+        executable: executable.to_path_buf(),
+        home,
+        version,
+        major,
+        vendor,
+        arch,
+    })
+}
+
+/// Lists the `java` executables that may exist on this system.
+///
+/// Looks at `JAVA_HOME`, `PATH`, common vendor directories, the Windows registry
+/// and macOS `java_home`. Paths are not checked beyond existing.
+pub fn candidate_executables() -> Vec<PathBuf> {
+    let exe = java_executable_name();
+    let mut homes = Vec::new();
+    if let Some(home) = env::var_os("JAVA_HOME") {
+        homes.push(PathBuf::from(home));
+    }
+    let mut candidates: Vec<PathBuf> = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).map(|dir| dir.join(exe)).collect())
+        .unwrap_or_default();
+
+    let roots: &[&str] = if cfg!(windows) {
+        &[
+            r"C:\Program Files\Java",
+            r"C:\Program Files\Eclipse Adoptium",
+            r"C:\Program Files\Microsoft",
+            r"C:\Program Files\Zulu",
+            r"C:\Program Files\Amazon Corretto",
+            r"C:\Program Files (x86)\Java",
+        ]
+    } else if cfg!(target_os = "macos") {
+        &["/Library/Java/JavaVirtualMachines"]
+    } else {
+        &["/usr/lib/jvm", "/usr/java", "/opt/java", "/opt"]
+    };
+    for root in roots {
+        for entry in fs::read_dir(root).into_iter().flatten().flatten() {
+            let path = entry.path();
+            homes.push(if cfg!(target_os = "macos") {
+                path.join("Contents").join("Home")
+            } else {
+                path
+            });
+        }
+    }
+    if cfg!(windows) {
+        homes.extend(registry_java_homes());
+    }
+    if cfg!(target_os = "macos") {
+        homes.extend(macos_java_homes());
+    }
+
+    candidates.extend(homes.into_iter().map(|home| home.join("bin").join(exe)));
+    candidates.retain(|path| path.is_file());
+    candidates
+}
+
+/// Finds and inspects all Java runtimes installed on this system.
+///
+/// Runtimes reachable through several paths (e.g. a `PATH` symlink and the
+/// vendor directory) are reported once.
+///
+/// # Returns
+///
+/// * `Vec<JavaInstallation>` - The runtimes that could be inspected, newest major
+///   version first.
+pub fn detect_java_installations() -> Vec<JavaInstallation> {
+    let mut seen = HashSet::new();
+    let mut installations: Vec<JavaInstallation> = candidate_executables()
+        .into_iter()
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .filter_map(|path| inspect_java(&path).ok())
+        .collect();
+    installations.sort_by_key(|java| std::cmp::Reverse(java.major));
+    installations
+}
+
+/// Reads `JavaHome` values below the JavaSoft registry keys.
+fn registry_java_homes() -> Vec<PathBuf> {
+    let mut homes = Vec::new();
+    for key in [
+        r"HKLM\SOFTWARE\JavaSoft\JDK",
+        r"HKLM\SOFTWARE\JavaSoft\Java Runtime Environment",
+        r"HKLM\SOFTWARE\JavaSoft\Java Development Kit",
+    ] {
+        let Ok(output) = Command::new("reg")
+            .args(["query", key, "/s", "/v", "JavaHome"])
+            .output()
+        else {
+            continue;
+        };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Some((_, value)) = line.split_once("REG_SZ") {
+                homes.push(PathBuf::from(value.trim()));
+            }
+        }
+    }
+    homes
+}
+
+/// Parses the homes listed by `/usr/libexec/java_home -V`.
+fn macos_java_homes() -> Vec<PathBuf> {
+    let Ok(output) = Command::new("/usr/libexec/java_home").arg("-V").output() else {
+        return Vec::new();
+    };
+    // The listing goes to stderr; each entry ends with the home path.
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(|line| {
+            line.rfind(" /")
+                .map(|i| PathBuf::from(line[i + 1..].trim()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn major_version_handles_both_schemes() {
+        assert_eq!(major_version("1.8.0_381"), Some(8));
+        assert_eq!(major_version("17.0.9"), Some(17));
+        assert_eq!(major_version("21"), Some(21));
+        assert_eq!(major_version("21-ea"), Some(21));
+        assert_eq!(major_version("abc"), None);
+    }
+
+    #[test]
+    fn parses_version_output() {
+        let output = "openjdk version \"17.0.9\" 2023-10-17\n\
+            OpenJDK Runtime Environment Temurin-17.0.9+9 (build 17.0.9+9)\n\
+            OpenJDK 64-Bit Server VM Temurin-17.0.9+9 (build 17.0.9+9, mixed mode, sharing)\n";
+        let (version, vendor, arch) = parse_version_output(output).unwrap();
+        assert_eq!(version, "17.0.9");
+        assert_eq!(vendor.as_deref(), Some("Temurin"));
+        assert_eq!(arch.as_deref(), Some("64-bit"));
+
+        let legacy = "Picked up _JAVA_OPTIONS: -Xmx1g\njava version \"1.8.0_381\"\n\
+            Java(TM) SE Runtime Environment (build 1.8.0_381-b09)\n\
+            Java HotSpot(TM) Client VM (build 25.381-b09, mixed mode)\n";
+        let (version, _, arch) = parse_version_output(legacy).unwrap();
+        assert_eq!(version, "1.8.0_381");
+        assert_eq!(arch.as_deref(), Some("32-bit"));
+        assert!(parse_version_output("command not found").is_none());
+    }
+
+    #[test]
+    fn inspects_a_java_home_with_release_file() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let exe = bin.join(java_executable_name());
+        fs::write(&exe, b"").unwrap();
+        fs::write(
+            dir.path().join("release"),
+            "IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"21.0.2\"\nOS_ARCH=\"aarch64\"\n",
+        )
+        .unwrap();
+
+        let java = inspect_java(&exe).unwrap();
+        assert_eq!(java.major, 21);
+        assert_eq!(java.version, "21.0.2");
+        assert_eq!(java.vendor.as_deref(), Some("Eclipse Adoptium"));
+        assert_eq!(java.arch.as_deref(), Some("aarch64"));
+        assert_eq!(java.home, dir.path());
+    }
+}
@@ -0,0 +1,524 @@
+use crate::http::batch::DownloadOutcome;
+use crate::http::{
+    BatchOptions, Compression, DownloadReport, DownloadRequest, DownloadStatus, FileCheck,
+    HashAlgorithm, HashSpec, HttpClient, HttpError,
+};
+use crate::platform::{Arch, OsName, Platform};
+use crate::version::{VersionJson, has_arm64_natives};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+
+/// Index of all Mojang Java runtimes, per platform and component.
+pub const RUNTIME_INDEX_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+/// A file of the runtime index or of a runtime manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeDownload {
+    /// Hex sha1 of the file.
+    pub sha1: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Download URL.
+    pub url: String,
+}
+
+impl RuntimeDownload {
+    /// Returns the size and sha1 as a [`FileCheck`].
+    pub fn check(&self) -> FileCheck {
+        FileCheck::new(self.size, HashSpec::new(HashAlgorithm::Sha1, &self.sha1))
+    }
+}
+
+/// Version information of a runtime component.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeVersion {
+    /// The Java version, e.g. `17.0.8`.
+    pub name: String,
+    /// Release timestamp.
+    #[serde(default)]
+    pub released: String,
+}
+
+/// A single runtime component release of the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeEntry {
+    /// The files manifest of the runtime.
+    pub manifest: RuntimeDownload,
+    /// The Java version of the runtime.
+    pub version: RuntimeVersion,
+}
+
+/// The `all.json` runtime index: platform, then component, then releases.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RuntimeIndex {
+    /// Releases keyed by platform (e.g. `linux`) and component (e.g. `java-runtime-gamma`).
+    pub platforms: BTreeMap<String, BTreeMap<String, Vec<RuntimeEntry>>>,
+}
+
+impl RuntimeIndex {
+    /// Returns the runtime of `component` for `platform`, if Mojang provides one.
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform key, see [`runtime_platform`].
+    /// * `component` - The component, e.g. `java-runtime-gamma` from a version's
+    ///   `javaVersion`.
+    pub fn find(&self, platform: &str, component: &str) -> Option<&RuntimeEntry> {
+        self.platforms.get(platform)?.get(component)?.first()
+    }
+}
+
+/// Returns the runtime index platform key of the hardware, e.g. `linux`,
+/// `mac-os-arm64` or `windows-x64`.
+///
+/// An x86_64 launcher running under Rosetta 2 gets `mac-os-arm64`, see
+/// [`Platform::native`].
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The platform key, or `None` on platforms Mojang does
+///   not ship runtimes for.
+pub fn runtime_platform() -> Option<&'static str> {
+    let platform = Platform::native();
+    runtime_platform_for(platform.os, platform.arch)
+}
+
+/// Returns the runtime index platform key of an OS and Java architecture.
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The platform key, or `None` if Mojang ships no
+///   runtimes for the combination.
+pub fn runtime_platform_for(os: OsName, arch: Arch) -> Option<&'static str> {
+    match (os, arch) {
+        (OsName::Linux, Arch::X86_64) => Some("linux"),
+        (OsName::Linux, Arch::X86) => Some("linux-i386"),
+        (OsName::Osx, Arch::X86_64) => Some("mac-os"),
+        (OsName::Osx, Arch::Arm64) => Some("mac-os-arm64"),
+        (OsName::Windows, Arch::X86_64) => Some("windows-x64"),
+        (OsName::Windows, Arch::X86) => Some("windows-x86"),
+        (OsName::Windows, Arch::Arm64) => Some("windows-arm64"),
+        _ => None,
+    }
+}
+
+/// Returns the architecture of the Java runtime a version must run on.
+///
+/// On arm64 macOS and Windows, versions without arm64 natives (before 1.19)
+/// fall back to an x86_64 runtime, which runs under Rosetta 2 or Windows'
+/// emulation. Version rules and natives classifiers must then be evaluated
+/// for x86_64 too.
+///
+/// # Arguments
+///
+/// * `version` - The resolved version JSON.
+/// * `platform` - The hardware platform, usually [`Platform::native`].
+///
+/// # Returns
+///
+/// * `Arch` - The architecture of the runtime to use.
+pub fn java_arch_for(version: &VersionJson, platform: &Platform) -> Arch {
+    let emulates_x64 = matches!(platform.os, OsName::Osx | OsName::Windows);
+    if platform.arch == Arch::Arm64 && emulates_x64 && !has_arm64_natives(version, platform) {
+        Arch::X86_64
+    } else {
+        platform.arch
+    }
+}
+
+/// Downloads of a runtime file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeFileDownloads {
+    /// The uncompressed file.
+    pub raw: RuntimeDownload,
+    /// The same file compressed with LZMA, if offered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lzma: Option<RuntimeDownload>,
+}
+
+/// An entry of a runtime files manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuntimeFile {
+    /// A regular file.
+    File {
+        /// Whether the file needs the executable bit.
+        #[serde(default)]
+        executable: bool,
+        /// Where to download the file.
+        downloads: RuntimeFileDownloads,
+    },
+    /// A directory.
+    Directory,
+    /// A symbolic link.
+    Link {
+        /// The link target, relative to the link's directory.
+        target: String,
+    },
+}
+
+/// A runtime files manifest, listing every file of a runtime by relative path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeManifest {
+    /// Entries keyed by path relative to the runtime root, e.g. `bin/java`.
+    pub files: BTreeMap<String, RuntimeFile>,
+}
+
+/// A file to download when installing a runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// The download request. Its size and hash are those of the uncompressed file.
+    pub request: DownloadRequest,
+    /// Whether `request.url` points to the LZMA-compressed file.
+    pub lzma: bool,
+    /// Whether the file needs the executable bit.
+    pub executable: bool,
+}
+
+/// Everything needed to lay out a runtime on disk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimePlan {
+    /// Directories to create, parents first.
+    pub directories: Vec<PathBuf>,
+    /// Files to download.
+    pub files: Vec<PlannedFile>,
+    /// Symbolic links to create, as link path and target.
+    pub links: Vec<(PathBuf, PathBuf)>,
+}
+
+/// Plans the installation of a runtime into `dest`.
+///
+/// LZMA downloads are preferred when offered; they are decompressed while
+/// downloading and verified against the size and sha1 of the raw file.
+///
+/// # Arguments
+///
+/// * `manifest` - The runtime files manifest.
+/// * `dest` - The runtime root, e.g. `runtime/java-runtime-gamma/linux/java-runtime-gamma`.
+///
+/// # Returns
+///
+/// * `io::Result<RuntimePlan>` - The directories, downloads and links.
+///
+/// # Errors
+///
+/// Returns `InvalidData` if an entry path is absolute or escapes `dest`.
+pub fn plan_runtime(manifest: &RuntimeManifest, dest: &Path) -> io::Result<RuntimePlan> {
+    let mut plan = RuntimePlan::default();
+    for (name, file) in &manifest.files {
+        let path = dest.join(safe_relative(name)?);
+        match file {
+            RuntimeFile::Directory => plan.directories.push(path),
+            RuntimeFile::File {
+                executable,
+                downloads,
+            } => {
+                let (url, lzma) = match &downloads.lzma {
+                    Some(lzma) => (&lzma.url, true),
+                    None => (&downloads.raw.url, false),
+                };
+                plan.files.push(PlannedFile {
+                    request: DownloadRequest::new(url, path).with_check(downloads.raw.check()),
+                    lzma,
+                    executable: *executable,
+                });
+            }
+            RuntimeFile::Link { target } => plan.links.push((path, PathBuf::from(target))),
+        }
+    }
+    plan.directories.sort();
+    Ok(plan)
+}
+
+fn safe_relative(name: &str) -> io::Result<&Path> {
+    let path = Path::new(name);
+    if path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(path)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsafe runtime path {:?}", name),
+        ))
+    }
+}
+
+impl HttpClient {
+    /// Installs a planned runtime.
+    ///
+    /// Directories are created first, then files are downloaded concurrently and
+    /// marked executable where required, and finally links are created. Files
+    /// that already exist and verify are kept unless `options.overwrite` is set.
+    /// Links are only created on Unix.
+    ///
+    /// # Returns
+    ///
+    /// * `DownloadReport` - The outcome of every file, in plan order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if a directory or link cannot be created. Failed
+    /// downloads are recorded in the report instead; links are not created when
+    /// any download failed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(files = plan.files.len()), err)
+    )]
+    pub async fn install_runtime(
+        &self,
+        plan: &RuntimePlan,
+        options: &BatchOptions,
+    ) -> Result<DownloadReport, HttpError> {
+        let start = Instant::now();
+        for directory in &plan.directories {
+            fs::create_dir_all(directory)?;
+        }
+
+        let outcomes: Vec<DownloadOutcome> = stream::iter(&plan.files)
+            .map(|file| async move {
+                let status = match self.install_file(file, options.overwrite).await {
+                    Ok(status) => status,
+                    Err(e) => DownloadStatus::Failed {
+                        reason: e.to_string(),
+                    },
+                };
+                DownloadOutcome {
+                    request: file.request.clone(),
+                    status,
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await;
+        let report = DownloadReport {
+            total_bytes: outcomes
+                .iter()
+                .map(|o| match o.status {
+                    DownloadStatus::Downloaded { bytes } => bytes,
+                    _ => 0,
+                })
+                .sum(),
+            outcomes,
+            elapsed: start.elapsed(),
+        };
+
+        if report.is_success() {
+            for (link, target) in &plan.links {
+                create_link(link, target)?;
+            }
+        }
+        Ok(report)
+    }
+
+    async fn install_file(
+        &self,
+        file: &PlannedFile,
+        overwrite: bool,
+    ) -> Result<DownloadStatus, HttpError> {
+        let status = if file.lzma {
+            self.download_decompressed(&file.request, Compression::Lzma, overwrite)
+                .await?
+        } else {
+            self.download(&file.request, overwrite).await?
+        };
+        if file.executable {
+            set_executable(&file.request.path)?;
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_link(link: &Path, target: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(link).is_ok() {
+        fs::remove_file(link)?;
+    }
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn create_link(_link: &Path, _target: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+    use tempfile::tempdir;
+
+    fn sha1_hex(data: &[u8]) -> String {
+        crate::http::hash_reader(data, HashAlgorithm::Sha1).unwrap()
+    }
+
+    #[test]
+    fn parses_index_and_finds_component() {
+        let index: RuntimeIndex = serde_json::from_str(
+            r#"{"linux": {"java-runtime-gamma": [{
+                "availability": {"group": 1, "progress": 100},
+                "manifest": {"sha1": "abc", "size": 10, "url": "https://example.com/manifest.json"},
+                "version": {"name": "17.0.8", "released": "2023-08-01T00:00:00+00:00"}
+            }], "jre-legacy": []}}"#,
+        )
+        .unwrap();
+
+        let entry = index.find("linux", "java-runtime-gamma").unwrap();
+        assert_eq!(entry.version.name, "17.0.8");
+        assert!(index.find("linux", "jre-legacy").is_none());
+        assert!(index.find("mac-os", "java-runtime-gamma").is_none());
+        assert_eq!(
+            runtime_platform_for(OsName::Osx, Arch::Arm64),
+            Some("mac-os-arm64")
+        );
+        assert_eq!(runtime_platform_for(OsName::Unknown, Arch::X86_64), None);
+    }
+
+    #[test]
+    fn falls_back_to_x64_java_without_arm64_natives() {
+        let legacy = VersionJson::from_json(
+            r#"{"id": "1.16.5", "libraries": [{
+                "name": "org.lwjgl:lwjgl:3.2.1",
+                "downloads": {"classifiers": {"natives-macos": {"url": ""}}}
+            }]}"#,
+        )
+        .unwrap();
+        let modern = VersionJson::from_json(
+            r#"{"id": "1.20.1", "libraries": [
+                {"name": "org.lwjgl:lwjgl:3.3.1:natives-windows-arm64"}
+            ]}"#,
+        )
+        .unwrap();
+        let mac = Platform::new(OsName::Osx, "14.4", Arch::Arm64);
+        let windows = Platform::new(OsName::Windows, "10.0", Arch::Arm64);
+        let linux = Platform::new(OsName::Linux, "6.1", Arch::Arm64);
+
+        assert_eq!(java_arch_for(&legacy, &mac), Arch::X86_64);
+        assert_eq!(java_arch_for(&modern, &windows), Arch::Arm64);
+        assert_eq!(java_arch_for(&legacy, &linux), Arch::Arm64);
+    }
+
+    #[test]
+    fn plans_directories_files_and_links() {
+        let manifest: RuntimeManifest = serde_json::from_str(
+            r#"{"files": {
+                "bin": {"type": "directory"},
+                "bin/java": {"type": "file", "executable": true, "downloads": {
+                    "raw": {"sha1": "aa", "size": 3, "url": "https://example.com/java"},
+                    "lzma": {"sha1": "bb", "size": 2, "url": "https://example.com/java.lzma"}}},
+                "lib/jvm.cfg": {"type": "file", "downloads": {
+                    "raw": {"sha1": "cc", "size": 4, "url": "https://example.com/jvm.cfg"}}},
+                "legal/java.base/LICENSE": {"type": "link", "target": "../LICENSE"}
+            }}"#,
+        )
+        .unwrap();
+        let dest = Path::new("/runtime");
+
+        let plan = plan_runtime(&manifest, dest).unwrap();
+
+        assert_eq!(plan.directories, vec![dest.join("bin")]);
+        assert_eq!(plan.files.len(), 2);
+        assert!(plan.files[0].lzma && plan.files[0].executable);
+        assert_eq!(plan.files[0].request.url, "https://example.com/java.lzma");
+        assert_eq!(plan.files[0].request.size, Some(3));
+        assert!(!plan.files[1].lzma);
+        assert_eq!(
+            plan.links,
+            vec![(
+                dest.join("legal/java.base/LICENSE"),
+                PathBuf::from("../LICENSE")
+            )]
+        );
+
+        let escaping = RuntimeManifest {
+            files: BTreeMap::from([("../evil".to_string(), RuntimeFile::Directory)]),
+        };
+        assert!(plan_runtime(&escaping, dest).is_err());
+    }
+
+    #[tokio::test]
+    async fn installs_lzma_files_and_marks_them_executable() {
+        let server = MockServer::start();
+        let content = b"#!/bin/sh\necho java\n";
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut &content[..], &mut compressed).unwrap();
+        server.mock(|when, then| {
+            when.method(GET).path("/bin/java.lzma");
+            then.status(200).body(&compressed);
+        });
+        let dir = tempdir().unwrap();
+        let manifest = RuntimeManifest {
+            files: BTreeMap::from([
+                ("bin".to_string(), RuntimeFile::Directory),
+                (
+                    "bin/java".to_string(),
+                    RuntimeFile::File {
+                        executable: true,
+                        downloads: RuntimeFileDownloads {
+                            raw: RuntimeDownload {
+                                sha1: sha1_hex(content),
+                                size: content.len() as u64,
+                                url: server.url("/bin/java"),
+                            },
+                            lzma: Some(RuntimeDownload {
+                                sha1: sha1_hex(&compressed),
+                                size: compressed.len() as u64,
+                                url: server.url("/bin/java.lzma"),
+                            }),
+                        },
+                    },
+                ),
+                (
+                    "bin/java-link".to_string(),
+                    RuntimeFile::Link {
+                        target: "java".to_string(),
+                    },
+                ),
+            ]),
+        };
+        let plan = plan_runtime(&manifest, dir.path()).unwrap();
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+
+        let report = client
+            .install_runtime(&plan, &BatchOptions::default())
+            .await
+            .unwrap();
+
+        assert!(report.is_success());
+        let java = dir.path().join("bin/java");
+        assert_eq!(fs::read(&java).unwrap(), content);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_ne!(fs::metadata(&java).unwrap().permissions().mode() & 0o111, 0);
+            assert_eq!(
+                fs::read_link(dir.path().join("bin/java-link")).unwrap(),
+                PathBuf::from("java")
+            );
+        }
+    }
+}
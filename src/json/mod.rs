@@ -0,0 +1,202 @@
+use serde::de::DeserializeOwned;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Parses JSON the way Gson's lenient mode and JSON5 accept it.
+///
+/// On top of strict JSON this accepts `//`, `/* */` and `#` comments,
+/// trailing commas, unquoted keys and string values, single-quoted strings,
+/// hexadecimal numbers, numbers with a leading `+` or a bare decimal point,
+/// and a byte order mark. Such text is common in mod configs and in launcher
+/// files edited by hand.
+///
+/// # Arguments
+///
+/// * `text` - The JSON text.
+///
+/// # Returns
+///
+/// * `Result<T, serde_json::Error>` - The deserialized value.
+///
+/// # Errors
+///
+/// Returns an error if the text is not valid even with these extensions,
+/// or does not match `T`. Line numbers in the error match the input; columns
+/// may be off on lines that use the extensions.
+pub fn parse_json_lenient<T: DeserializeOwned>(text: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str(&to_strict_json(text))
+}
+
+/// Rewrites lenient JSON as strict JSON, keeping the line breaks.
+fn to_strict_json(text: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    // A comma is only written once the next token shows it is not trailing.
+    let mut pending_comma = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => out.push(c),
+            '/' if chars.peek() == Some(&'/') => skip_line(&mut chars, &mut out),
+            '#' => skip_line(&mut chars, &mut out),
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                    }
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            ',' if !pending_comma => pending_comma = true,
+            c => {
+                if pending_comma && c != '}' && c != ']' {
+                    out.push(',');
+                }
+                pending_comma = false;
+                match c {
+                    '"' | '\'' => read_string(c, &mut chars, &mut out),
+                    '0'..='9' | '-' | '+' | '.' => {
+                        let mut token = String::from(c);
+                        while let Some(&c) = chars.peek() {
+                            if !(c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')) {
+                                break;
+                            }
+                            token.push(c);
+                            chars.next();
+                        }
+                        out.push_str(&normalize_number(&token));
+                    }
+                    c if c.is_alphabetic() || c == '_' || c == '$' => {
+                        let mut word = String::from(c);
+                        while let Some(&c) = chars.peek() {
+                            if !(c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | '-')) {
+                                break;
+                            }
+                            word.push(c);
+                            chars.next();
+                        }
+                        if matches!(word.as_str(), "true" | "false" | "null") {
+                            out.push_str(&word);
+                        } else {
+                            out.push_str(&serde_json::Value::String(word).to_string());
+                        }
+                    }
+                    c => out.push(c),
+                }
+            }
+        }
+    }
+    if pending_comma {
+        out.push(',');
+    }
+    out
+}
+
+/// Skips a comment up to the end of the line, keeping the line break.
+fn skip_line(chars: &mut Peekable<Chars>, out: &mut String) {
+    for c in chars.by_ref() {
+        if c == '\n' {
+            out.push('\n');
+            break;
+        }
+    }
+}
+
+/// Reads a string opened with `quote` and writes it double-quoted.
+fn read_string(quote: char, chars: &mut Peekable<Chars>, out: &mut String) {
+    out.push('"');
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => out.push('\''),
+                // A backslash before a line break continues the string.
+                Some('\n') => {}
+                Some(escaped) => {
+                    out.push('\\');
+                    out.push(escaped);
+                }
+                None => out.push('\\'),
+            },
+            c if c == quote => break,
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Rewrites a JSON5 number as a JSON number. Tokens that are no number are
+/// returned as is, for serde_json to report.
+fn normalize_number(token: &str) -> String {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    };
+    let sign = if negative { "-" } else { "" };
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        return match u64::from_str_radix(hex, 16) {
+            Ok(value) => format!("{}{}", sign, value),
+            Err(_) => token.to_string(),
+        };
+    }
+    let mut number = digits.to_string();
+    if number.starts_with('.') {
+        number.insert(0, '0');
+    }
+    if let Some(index) = number.find('.')
+        && !number[index + 1..].starts_with(|c: char| c.is_ascii_digit())
+    {
+        number.insert(index + 1, '0');
+    }
+    format!("{}{}", sign, number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn accepts_json5_extensions() {
+        let text = "\u{feff}{
+            // line comment
+            unquoted: 'single \"quoted\"', /* block
+            comment */ hex: 0x1F,
+            # hash comment
+            numbers: [+1, .5, 5., -0x10,],
+            'it\\'s': bare-value,
+            nested: {a: true, b: null,},
+        }";
+        let value: Value = parse_json_lenient(text).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "unquoted": "single \"quoted\"",
+                "hex": 31,
+                "numbers": [1, 0.5, 5.0, -16],
+                "it's": "bare-value",
+                "nested": {"a": true, "b": null},
+            })
+        );
+    }
+
+    #[test]
+    fn reports_errors_on_the_input_line() {
+        let error =
+            parse_json_lenient::<Value>("{\n  /* a\n  b */\n  \"a\": [1,, 2]\n}").unwrap_err();
+        assert_eq!(error.line(), 4);
+        assert!(parse_json_lenient::<Value>("{\"url\": \"http://x\"}").is_ok());
+    }
+}
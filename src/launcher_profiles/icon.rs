@@ -0,0 +1,192 @@
+use crate::instance::screenshots::downscale;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::io;
+use thiserror::Error;
+
+/// Prefix of the icons stored inline in `launcher_profiles.json`.
+pub const DATA_URI_PREFIX: &str = "data:image/png;base64,";
+
+/// Size in pixels of the custom icons the vanilla launcher creates.
+pub const PROFILE_ICON_SIZE: u32 = 128;
+
+/// Size in pixels of the built-in instance icons of MultiMC and Prism
+/// Launcher. Custom icons are PNG files in their `icons` folder, named after
+/// the `iconKey` of `instance.cfg`, which [`resize_icon`] prepares.
+pub const MULTIMC_ICON_SIZE: u32 = 48;
+
+/// Error returned when an icon cannot be converted.
+#[derive(Debug, Error)]
+pub enum IconError {
+    #[error("invalid PNG: {0}")]
+    Png(#[from] png::DecodingError),
+    #[error("failed to encode icon: {0}")]
+    Encode(#[from] png::EncodingError),
+    #[error("invalid base64 icon: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("icon is not a PNG data URI")]
+    NotADataUri,
+}
+
+/// Returns whether a profile icon is an inline image rather than the name of
+/// a built-in icon such as `Grass`.
+pub fn is_data_uri(icon: &str) -> bool {
+    icon.starts_with(DATA_URI_PREFIX)
+}
+
+/// Scales a PNG down to fit a square icon.
+///
+/// Images larger than `size` are scaled down keeping their aspect ratio,
+/// and images that are not square are centered on a transparent square, so
+/// launchers show them undistorted. Smaller square images are only
+/// re-encoded.
+///
+/// # Arguments
+///
+/// * `png` - The image, in any PNG color type.
+/// * `size` - The width and height of the icon, e.g. [`PROFILE_ICON_SIZE`].
+///
+/// # Returns
+///
+/// * `Vec<u8>` - The icon as an 8-bit RGBA PNG.
+///
+/// # Errors
+///
+/// Returns an error if `png` is not a valid PNG.
+pub fn resize_icon(png: &[u8], size: u32) -> Result<Vec<u8>, IconError> {
+    let mut decoder = png::Decoder::new(io::Cursor::new(png));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let (width, height) = reader.info().size();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels)?;
+    let (color_type, _) = reader.output_color_type();
+    let rgba = to_rgba(&pixels, color_type);
+
+    let size = size.max(1);
+    let scale = (f64::from(size) / f64::from(width.max(height))).min(1.0);
+    let scaled_width = ((f64::from(width) * scale).round() as u32).max(1);
+    let scaled_height = ((f64::from(height) * scale).round() as u32).max(1);
+    let scaled = downscale(
+        &rgba,
+        (width as usize, height as usize),
+        (scaled_width as usize, scaled_height as usize),
+        4,
+    );
+
+    let side = scaled_width.max(scaled_height);
+    let mut icon = vec![0; (side * side * 4) as usize];
+    let (left, top) = ((side - scaled_width) / 2, (side - scaled_height) / 2);
+    for (y, row) in scaled.chunks((scaled_width * 4) as usize).enumerate() {
+        let start = (((top + y as u32) * side + left) * 4) as usize;
+        icon[start..start + row.len()].copy_from_slice(row);
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, side, side);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&icon)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+/// Converts a PNG to a `data:image/png;base64,` icon for
+/// [`super::LauncherProfile::icon`], scaled down to `size` with
+/// [`resize_icon`].
+///
+/// # Errors
+///
+/// Returns an error if `png` is not a valid PNG.
+pub fn png_to_icon(png: &[u8], size: u32) -> Result<String, IconError> {
+    Ok(format!(
+        "{}{}",
+        DATA_URI_PREFIX,
+        STANDARD.encode(resize_icon(png, size)?)
+    ))
+}
+
+/// Returns the PNG of a `data:image/png;base64,` icon.
+///
+/// Plain base64, as in the `icon` of `servers.dat`, is accepted too.
+///
+/// # Errors
+///
+/// Returns [`IconError::NotADataUri`] for other data URIs, or an error if
+/// the data is not base64.
+pub fn icon_to_png(icon: &str) -> Result<Vec<u8>, IconError> {
+    let data = match icon.strip_prefix(DATA_URI_PREFIX) {
+        Some(data) => data,
+        None if icon.starts_with("data:") => return Err(IconError::NotADataUri),
+        None => icon,
+    };
+    Ok(STANDARD.decode(data.trim())?)
+}
+
+/// Expands 8-bit pixels of any color type to RGBA.
+fn to_rgba(pixels: &[u8], color_type: png::ColorType) -> Vec<u8> {
+    match color_type {
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Rgb => pixels
+            .chunks(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        // Indexed images are expanded by the decoder.
+        png::ColorType::Rgba | png::ColorType::Indexed => pixels.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32, color: png::ColorType, pixel: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&pixel.repeat((width * height) as usize))
+            .unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(io::Cursor::new(png)).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        (info.width, info.height, pixels)
+    }
+
+    #[test]
+    fn round_trips_icons_through_data_uris() {
+        let icon = png_to_icon(&png(512, 256, png::ColorType::Rgb, &[10, 20, 30]), 128).unwrap();
+        assert!(is_data_uri(&icon));
+        assert!(!is_data_uri("Grass"));
+
+        let (width, height, pixels) = decode(&icon_to_png(&icon).unwrap());
+        assert_eq!((width, height), (128, 128));
+        // The 128x64 image is centered, with transparent bars above and below.
+        assert_eq!(pixels[..4], [0, 0, 0, 0]);
+        let center = ((64 * 128 + 64) * 4) as usize;
+        assert_eq!(pixels[center..center + 4], [10, 20, 30, 255]);
+
+        let small = resize_icon(&png(16, 16, png::ColorType::Grayscale, &[99]), 128).unwrap();
+        assert_eq!(decode(&small).0, 16);
+
+        let plain = STANDARD.encode(&small);
+        assert_eq!(icon_to_png(&plain).unwrap(), small);
+        assert!(matches!(
+            icon_to_png("data:image/jpeg;base64,AAAA"),
+            Err(IconError::NotADataUri)
+        ));
+        assert!(resize_icon(b"not a png", 48).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+/// Conversion of PNG images to and from inline profile icons.
+pub mod icon;
+
+pub use icon::{
+    DATA_URI_PREFIX, IconError, MULTIMC_ICON_SIZE, PROFILE_ICON_SIZE, icon_to_png, is_data_uri,
+    png_to_icon, resize_icon,
+};
+
+use crate::filesystem::{FilesystemError, write_atomic};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// File name of the vanilla launcher's profile list inside `.minecraft`.
+pub const LAUNCHER_PROFILES: &str = "launcher_profiles.json";
+
+/// Profile type of profiles created by users.
+pub const PROFILE_TYPE_CUSTOM: &str = "custom";
+
+/// Error returned when `launcher_profiles.json` cannot be read or written.
+#[derive(Debug, Error)]
+pub enum LauncherProfilesError {
+    #[error("failed to read launcher profiles: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write launcher profiles: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid launcher profiles: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Game window size of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileResolution {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+/// A profile of the vanilla launcher.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherProfile {
+    /// Display name. Empty for the built-in latest release and snapshot profiles.
+    #[serde(default)]
+    pub name: String,
+    /// `custom`, `latest-release` or `latest-snapshot`.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub profile_type: Option<String>,
+    /// Creation time as an ISO 8601 timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Last launch time as an ISO 8601 timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<String>,
+    /// A built-in icon name such as `Grass`, or a `data:image/png;base64,` URI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// The version launched, e.g. `1.20.1` or `fabric-loader-0.16.9-1.20.1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_version_id: Option<String>,
+    /// Game directory, if not the default `.minecraft`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_dir: Option<String>,
+    /// Java executable, if not the bundled runtime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub java_dir: Option<String>,
+    /// JVM arguments as a single string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub java_args: Option<String>,
+    /// Initial window size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<ProfileResolution>,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl LauncherProfile {
+    /// Creates a custom profile launching `version_id`.
+    pub fn new(name: impl Into<String>, version_id: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            profile_type: Some(PROFILE_TYPE_CUSTOM.to_string()),
+            last_version_id: Some(version_id.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the game directory.
+    pub fn with_game_dir(mut self, game_dir: impl Into<String>) -> Self {
+        self.game_dir = Some(game_dir.into());
+        self
+    }
+
+    /// Sets the Java executable.
+    pub fn with_java_dir(mut self, java_dir: impl Into<String>) -> Self {
+        self.java_dir = Some(java_dir.into());
+        self
+    }
+
+    /// Sets the JVM arguments.
+    pub fn with_java_args(mut self, java_args: impl Into<String>) -> Self {
+        self.java_args = Some(java_args.into());
+        self
+    }
+
+    /// Sets the icon.
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Returns `true` for profiles created by users, as opposed to the built-in
+    /// latest release and snapshot profiles.
+    pub fn is_custom(&self) -> bool {
+        self.profile_type
+            .as_deref()
+            .is_none_or(|t| t == PROFILE_TYPE_CUSTOM)
+    }
+
+    /// Splits the JVM arguments at whitespace.
+    pub fn java_args_list(&self) -> Vec<&str> {
+        self.java_args
+            .as_deref()
+            .map(|args| args.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// The contents of `launcher_profiles.json`.
+///
+/// Fields this crate does not know, including settings of newer launcher
+/// versions, are kept and written back unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LauncherProfiles {
+    /// Profiles keyed by id.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, LauncherProfile>,
+    /// Launcher settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Map<String, Value>>,
+    /// Format version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl LauncherProfiles {
+    /// Reads `launcher_profiles.json`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LauncherProfiles, LauncherProfilesError>` - The profiles, or an
+    ///   empty list if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is malformed.
+    pub fn load(path: &Path) -> Result<Self, LauncherProfilesError> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the profiles to `path`, replacing the file atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), LauncherProfilesError> {
+        write_atomic(path, &serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns the profile with the given id.
+    pub fn get(&self, id: &str) -> Option<&LauncherProfile> {
+        self.profiles.get(id)
+    }
+
+    /// Adds or replaces a profile, returning the previous one.
+    pub fn insert(
+        &mut self,
+        id: impl Into<String>,
+        profile: LauncherProfile,
+    ) -> Option<LauncherProfile> {
+        self.profiles.insert(id.into(), profile)
+    }
+
+    /// Removes a profile, returning it.
+    pub fn remove(&mut self, id: &str) -> Option<LauncherProfile> {
+        self.profiles.remove(id)
+    }
+
+    /// Returns the profiles created by users, skipping the built-in ones.
+    pub fn custom_profiles(&self) -> impl Iterator<Item = (&str, &LauncherProfile)> {
+        self.profiles
+            .iter()
+            .filter(|(_, profile)| profile.is_custom())
+            .map(|(id, profile)| (id.as_str(), profile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const PROFILES: &str = r#"{
+        "profiles": {
+            "a1b2": {
+                "created": "2023-06-01T10:00:00.000Z",
+                "icon": "Furnace",
+                "lastUsed": "2024-01-01T10:00:00.000Z",
+                "lastVersionId": "fabric-loader-0.16.9-1.20.1",
+                "name": "Fabric",
+                "type": "custom",
+                "javaArgs": "-Xmx4G -XX:+UseG1GC",
+                "resolution": {"width": 1280, "height": 720},
+                "skipJreVersionCheck": true
+            },
+            "latest": {"icon": "Grass", "lastVersionId": "latest-release", "name": "", "type": "latest-release"}
+        },
+        "settings": {"crashAssistance": true, "enableAdvanced": false},
+        "version": 3,
+        "selectedUser": {"account": "x"}
+    }"#;
+
+    #[test]
+    fn parses_profiles_and_keeps_unknown_fields() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(LAUNCHER_PROFILES);
+        fs::write(&path, PROFILES).unwrap();
+
+        let mut profiles = LauncherProfiles::load(&path).unwrap();
+        let fabric = profiles.get("a1b2").unwrap();
+        assert_eq!(fabric.java_args_list(), vec!["-Xmx4G", "-XX:+UseG1GC"]);
+        assert_eq!(fabric.resolution.unwrap().width, 1280);
+        assert_eq!(
+            profiles
+                .custom_profiles()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            vec!["a1b2"]
+        );
+
+        profiles.insert(
+            "junco-test",
+            LauncherProfile::new("Junco Test", "1.21.1").with_game_dir("/instances/test"),
+        );
+        profiles.save(&path).unwrap();
+
+        let saved: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved["selectedUser"]["account"], "x");
+        assert_eq!(saved["profiles"]["a1b2"]["skipJreVersionCheck"], true);
+        assert_eq!(
+            saved["profiles"]["junco-test"]["gameDir"],
+            "/instances/test"
+        );
+        assert_eq!(saved["profiles"]["junco-test"]["type"], "custom");
+        assert_eq!(saved["settings"]["crashAssistance"], true);
+        assert!(saved["profiles"]["junco-test"].get("javaArgs").is_none());
+    }
+
+    #[test]
+    fn missing_file_loads_empty_profiles() {
+        let dir = tempdir().unwrap();
+        let profiles = LauncherProfiles::load(&dir.path().join(LAUNCHER_PROFILES)).unwrap();
+        assert!(profiles.profiles.is_empty());
+    }
+}
@@ -32,12 +32,90 @@ pub mod mcmeta_parser;
 /// Uses `Path`, `PathBuf`, and `dirs` for home dir expansion. Works on all platforms.
 ///
 /// # Example
-/// ```rust
+/// ```rust,no_run
 /// use crate::junco_launcher_utils::filesystem::{create_if_not_exists, write_file, WriteOptions};
 ///
+/// # fn main() -> Result<(), junco_launcher_utils::filesystem::FilesystemError> {
 /// create_if_not_exists("my_dir", true)?;
 /// write_file("my_dir/hello.txt", "hello", WriteOptions::default())?;
+/// # Ok(())
+/// # }
 /// ```
 pub mod filesystem;
 
-pub mod http;
\ No newline at end of file
+pub mod http;
+
+/// Types and helpers for Minecraft version JSON files (`versions/<id>/<id>.json`).
+///
+/// Includes the rule evaluator that decides which libraries and arguments apply
+/// on a given platform.
+pub mod version;
+
+/// Maven coordinates (`group:artifact:version[:classifier][@ext]`) and their
+/// repository paths, as used by library lists of vanilla, Fabric and Forge.
+pub mod maven;
+
+/// Asset indexes and the legacy `virtual`/`map_to_resources` asset layouts used
+/// by versions before 1.7.
+pub mod assets;
+
+/// One hash-keyed store of libraries, asset objects and client jars, linked into
+/// instances on demand.
+pub mod cache;
+
+/// Discovery and inspection of installed Java runtimes, and JVM flag presets.
+pub mod java;
+
+/// Mod loader metadata: the Fabric and Quilt meta APIs, Forge and NeoForge
+/// version listings and installers, and server installs.
+pub mod loader;
+
+/// Metadata of installed mods and checks across the mods of an instance.
+pub mod mods;
+
+/// Modpack formats and the plans that turn them into instances.
+pub mod modpack;
+
+/// Reading and writing the vanilla launcher's `launcher_profiles.json`.
+pub mod launcher_profiles;
+
+/// Instances managed by junco: their configuration and contents.
+pub mod instance;
+
+/// Reading and writing of NBT, the binary format of `level.dat`, `servers.dat`
+/// and other game files.
+pub mod nbt;
+
+/// Dedicated servers: their setup, files such as the player lists, and the
+/// Query protocol.
+pub mod server;
+
+/// Game log files.
+pub mod logs;
+
+/// Signing in with a Microsoft account to play Minecraft.
+pub mod auth;
+
+/// Public player profiles: UUIDs of player names, skins and capes.
+pub mod profiles;
+
+/// Starting the game and following its process.
+pub mod process;
+
+/// The operating system and architecture, named as version JSON files name them.
+pub mod platform;
+
+/// Resource packs: reading their contents, languages and sounds, checking their
+/// models and translations, and combining several into one.
+pub mod resourcepack;
+
+/// Mod config files in TOML, JSON, JSON5, YAML and Forge `.cfg` formats, read
+/// into one value tree for editing.
+pub mod configs;
+
+/// Reading JSON written by hand, with comments, trailing commas and other
+/// JSON5 extensions.
+pub mod json;
+
+/// Jar files of libraries and mods: their manifest, layout and signature.
+pub mod jar;
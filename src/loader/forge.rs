@@ -0,0 +1,468 @@
+use crate::http::{HttpClient, HttpError};
+use crate::maven::{MavenCoordinate, parse_metadata_versions};
+use crate::version::{Library, VersionError, VersionJson};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+/// The Forge Maven repository.
+pub const FORGE_MAVEN: &str = "https://maven.minecraftforge.net";
+/// Recommended and latest Forge builds per Minecraft version.
+pub const FORGE_PROMOTIONS_URL: &str =
+    "https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json";
+
+/// Error returned when an installer jar cannot be read.
+#[derive(Debug, Error)]
+pub enum InstallerError {
+    #[error("failed to read installer: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid installer archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("failed to parse installer metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid version JSON in installer: {0}")]
+    Version(#[from] VersionError),
+    #[error("installer does not contain {0}")]
+    MissingEntry(String),
+}
+
+/// A Forge build, e.g. `1.20.1-47.3.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ForgeVersion {
+    /// The full version as published on Maven.
+    pub full: String,
+    /// The Minecraft version, e.g. `1.20.1`.
+    pub minecraft: String,
+    /// The Forge version, e.g. `47.3.0`. Old builds keep their branch suffix,
+    /// e.g. `10.13.4.1614-1.7.10`.
+    pub forge: String,
+}
+
+impl ForgeVersion {
+    /// Splits a Maven version such as `1.20.1-47.3.0` into its parts.
+    pub fn parse(full: &str) -> Option<Self> {
+        let (minecraft, forge) = full.split_once('-')?;
+        if minecraft.is_empty() || forge.is_empty() {
+            return None;
+        }
+        Some(Self {
+            full: full.to_string(),
+            minecraft: minecraft.to_string(),
+            forge: forge.to_string(),
+        })
+    }
+
+    /// Returns the coordinate of the installer jar.
+    pub fn installer(&self) -> MavenCoordinate {
+        MavenCoordinate::new("net.minecraftforge", "forge", &self.full).with_classifier("installer")
+    }
+
+    fn is_promoted(&self, promoted: &str) -> bool {
+        self.forge == promoted
+            || self
+                .forge
+                .strip_prefix(promoted)
+                .is_some_and(|rest| rest.starts_with('-'))
+    }
+}
+
+/// The contents of `promotions_slim.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForgePromotions {
+    /// Forge versions keyed by `<minecraft>-recommended` or `<minecraft>-latest`.
+    #[serde(default)]
+    pub promos: BTreeMap<String, String>,
+}
+
+/// All Forge builds together with their promotions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForgeIndex {
+    /// Builds in Maven metadata order.
+    pub versions: Vec<ForgeVersion>,
+    /// The promoted builds.
+    pub promotions: ForgePromotions,
+}
+
+impl ForgeIndex {
+    /// Builds an index from `maven-metadata.xml` and `promotions_slim.json`.
+    pub fn new(metadata_xml: &str, promotions: ForgePromotions) -> Self {
+        Self {
+            versions: parse_metadata_versions(metadata_xml)
+                .iter()
+                .filter_map(|v| ForgeVersion::parse(v))
+                .collect(),
+            promotions,
+        }
+    }
+
+    /// Returns the builds for a Minecraft version, newest first.
+    pub fn for_minecraft(&self, minecraft: &str) -> Vec<&ForgeVersion> {
+        let mut versions: Vec<_> = self
+            .versions
+            .iter()
+            .filter(|v| v.minecraft == minecraft)
+            .collect();
+        versions.reverse();
+        versions
+    }
+
+    /// Returns the recommended build for a Minecraft version.
+    pub fn recommended(&self, minecraft: &str) -> Option<&ForgeVersion> {
+        self.promoted(minecraft, "recommended")
+    }
+
+    /// Returns the latest build for a Minecraft version.
+    pub fn latest(&self, minecraft: &str) -> Option<&ForgeVersion> {
+        self.promoted(minecraft, "latest")
+    }
+
+    fn promoted(&self, minecraft: &str, kind: &str) -> Option<&ForgeVersion> {
+        let promoted = self
+            .promotions
+            .promos
+            .get(&format!("{}-{}", minecraft, kind))?;
+        self.versions
+            .iter()
+            .find(|v| v.minecraft == minecraft && v.is_promoted(promoted))
+    }
+}
+
+/// A value of the `data` section of an install profile, per side.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataEntry {
+    /// Value used for client installs.
+    #[serde(default)]
+    pub client: String,
+    /// Value used for server installs.
+    #[serde(default)]
+    pub server: String,
+}
+
+/// A post-processing step run by the installer, e.g. binary patching.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Processor {
+    /// Sides the processor runs on. Empty means both.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sides: Vec<String>,
+    /// The jar containing the processor's main class.
+    pub jar: MavenCoordinate,
+    /// Additional classpath entries.
+    #[serde(default)]
+    pub classpath: Vec<MavenCoordinate>,
+    /// Arguments; `{KEY}` refers to `data` and `[coordinate]` to a library path.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Expected sha1 of produced files, keyed by path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outputs: BTreeMap<String, String>,
+}
+
+impl Processor {
+    /// Returns `true` if the processor runs for `side` (`client` or `server`).
+    pub fn runs_on(&self, side: &str) -> bool {
+        self.sides.is_empty() || self.sides.iter().any(|s| s == side)
+    }
+}
+
+/// `install_profile.json` of installers for Minecraft 1.13 and later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallProfile {
+    /// Profile format version.
+    #[serde(default)]
+    pub spec: u32,
+    /// The launcher profile name, e.g. `forge`.
+    pub profile: String,
+    /// The id of the installed version.
+    pub version: String,
+    /// The Minecraft version.
+    pub minecraft: String,
+    /// The main loader artifact, if the installer ships one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<MavenCoordinate>,
+    /// Path of the version JSON inside the installer, e.g. `/version.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json: Option<String>,
+    /// Values substituted into processor arguments.
+    #[serde(default)]
+    pub data: BTreeMap<String, DataEntry>,
+    /// Steps run after the libraries are downloaded.
+    #[serde(default)]
+    pub processors: Vec<Processor>,
+    /// Libraries needed by the processors.
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl InstallProfile {
+    /// Returns the processors that run for `side` (`client` or `server`), in order.
+    pub fn processors_for<'a>(&'a self, side: &'a str) -> impl Iterator<Item = &'a Processor> {
+        self.processors.iter().filter(move |p| p.runs_on(side))
+    }
+}
+
+/// The `install` section of installers for Minecraft 1.12.2 and earlier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyInstall {
+    /// The id of the installed version.
+    pub target: String,
+    /// The universal jar, installed as a library.
+    pub path: MavenCoordinate,
+    /// Name of the universal jar inside the installer.
+    pub file_path: String,
+    /// The Minecraft version.
+    pub minecraft: String,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyProfile {
+    install: LegacyInstall,
+    version_info: VersionJson,
+}
+
+/// Metadata read from an installer jar.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallerMetadata {
+    /// An installer with processors, for Minecraft 1.13 and later.
+    Modern {
+        /// The install profile.
+        profile: Box<InstallProfile>,
+        /// The version JSON to install.
+        version: Box<VersionJson>,
+    },
+    /// An installer that only copies the universal jar.
+    Legacy {
+        /// The install section.
+        install: Box<LegacyInstall>,
+        /// The version JSON to install.
+        version: Box<VersionJson>,
+    },
+}
+
+impl InstallerMetadata {
+    /// Returns the version JSON to install.
+    pub fn version(&self) -> &VersionJson {
+        match self {
+            InstallerMetadata::Modern { version, .. }
+            | InstallerMetadata::Legacy { version, .. } => version,
+        }
+    }
+
+    /// Returns the Minecraft version the installer targets.
+    pub fn minecraft(&self) -> &str {
+        match self {
+            InstallerMetadata::Modern { profile, .. } => &profile.minecraft,
+            InstallerMetadata::Legacy { install, .. } => &install.minecraft,
+        }
+    }
+}
+
+/// Reads `install_profile.json` and the version JSON from an installer jar.
+///
+/// Works for Forge installers of all eras and for NeoForge installers, which use
+/// the same format.
+///
+/// # Arguments
+///
+/// * `path` - The installer jar.
+///
+/// # Returns
+///
+/// * `Result<InstallerMetadata, InstallerError>` - The installer metadata.
+///
+/// # Errors
+///
+/// Returns an error if the jar cannot be read or lacks valid metadata.
+pub fn read_installer(path: &Path) -> Result<InstallerMetadata, InstallerError> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let profile = read_entry(&mut archive, "install_profile.json")?;
+    let value: serde_json::Value = serde_json::from_str(&profile)?;
+    if value.get("install").is_some() {
+        let legacy: LegacyProfile = serde_json::from_value(value)?;
+        return Ok(InstallerMetadata::Legacy {
+            install: Box::new(legacy.install),
+            version: Box::new(legacy.version_info),
+        });
+    }
+
+    let profile: InstallProfile = serde_json::from_value(value)?;
+    let json_path = profile.json.as_deref().unwrap_or("version.json");
+    let version = VersionJson::from_json(&read_entry(
+        &mut archive,
+        json_path.trim_start_matches('/'),
+    )?)?;
+    Ok(InstallerMetadata::Modern {
+        profile: Box::new(profile),
+        version: Box::new(version),
+    })
+}
+
+fn read_entry<R: io::Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String, InstallerError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(InstallerError::MissingEntry(name.to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+impl HttpClient {
+    /// Fetches all Forge builds and their promotions.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if either document cannot be fetched or parsed.
+    pub async fn forge_index(&self) -> Result<ForgeIndex, HttpError> {
+        let metadata = self
+            .get_bytes(&crate::maven::metadata_url(
+                FORGE_MAVEN,
+                "net.minecraftforge",
+                "forge",
+            ))
+            .await?;
+        let promotions = self.get_json(FORGE_PROMOTIONS_URL).await?;
+        Ok(ForgeIndex::new(
+            &String::from_utf8_lossy(&metadata),
+            promotions,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_jar(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn resolves_promotions_against_maven_versions() {
+        let xml = "<metadata><versioning><versions>\
+            <version>1.7.10-10.13.4.1614-1.7.10</version>\
+            <version>1.20.1-47.2.0</version>\
+            <version>1.20.1-47.3.0</version>\
+            </versions></versioning></metadata>";
+        let promotions: ForgePromotions = serde_json::from_str(
+            r#"{"homepage": "https://files.minecraftforge.net/", "promos": {
+                "1.7.10-recommended": "10.13.4.1614",
+                "1.20.1-recommended": "47.2.0",
+                "1.20.1-latest": "47.3.0"}}"#,
+        )
+        .unwrap();
+        let index = ForgeIndex::new(xml, promotions);
+
+        assert_eq!(index.recommended("1.20.1").unwrap().full, "1.20.1-47.2.0");
+        assert_eq!(index.latest("1.20.1").unwrap().forge, "47.3.0");
+        assert_eq!(
+            index.recommended("1.7.10").unwrap().full,
+            "1.7.10-10.13.4.1614-1.7.10"
+        );
+        assert!(index.latest("1.7.10").is_none());
+        assert_eq!(index.for_minecraft("1.20.1")[0].forge, "47.3.0");
+        assert_eq!(
+            index.versions[2].installer().file_name(),
+            "forge-1.20.1-47.3.0-installer.jar"
+        );
+    }
+
+    #[test]
+    fn reads_modern_installer() {
+        let dir = tempdir().unwrap();
+        let jar = dir.path().join("installer.jar");
+        write_jar(
+            &jar,
+            &[
+                (
+                    "install_profile.json",
+                    r#"{"spec": 1, "profile": "forge", "version": "1.20.1-forge-47.3.0",
+                        "minecraft": "1.20.1", "json": "/version.json",
+                        "path": "net.minecraftforge:forge:1.20.1-47.3.0",
+                        "data": {"MAPPINGS": {"client": "[de.oceanlabs.mcp:mcp_config:1.20.1@txt]", "server": "x"}},
+                        "processors": [
+                            {"sides": ["server"], "jar": "net.minecraftforge:installertools:1.3.0", "args": ["--task", "EXTRACT_FILES"]},
+                            {"jar": "net.minecraftforge:binarypatcher:1.1.1", "classpath": ["net.sf.jopt-simple:jopt-simple:5.0.4"],
+                             "args": ["--patch", "{BINPATCH}"], "outputs": {"{PATCHED}": "{PATCHED_SHA}"}}
+                        ],
+                        "libraries": [{"name": "net.minecraftforge:binarypatcher:1.1.1"}],
+                        "logo": "/big_logo.png"}"#,
+                ),
+                (
+                    "version.json",
+                    r#"{"id": "1.20.1-forge-47.3.0", "inheritsFrom": "1.20.1",
+                        "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher"}"#,
+                ),
+            ],
+        );
+
+        let metadata = read_installer(&jar).unwrap();
+
+        assert_eq!(metadata.version().inherits_from.as_deref(), Some("1.20.1"));
+        let InstallerMetadata::Modern { profile, .. } = metadata else {
+            panic!("expected a modern installer");
+        };
+        assert_eq!(profile.data["MAPPINGS"].server, "x");
+        let client: Vec<_> = profile.processors_for("client").collect();
+        assert_eq!(client.len(), 1);
+        assert_eq!(client[0].jar.artifact, "binarypatcher");
+        assert_eq!(profile.extra["logo"], "/big_logo.png");
+    }
+
+    #[test]
+    fn reads_legacy_installer_and_reports_missing_profile() {
+        let dir = tempdir().unwrap();
+        let jar = dir.path().join("installer.jar");
+        write_jar(
+            &jar,
+            &[(
+                "install_profile.json",
+                r#"{"install": {"profileName": "Forge", "target": "1.12.2-forge-14.23.5.2859",
+                    "path": "net.minecraftforge:forge:1.12.2-14.23.5.2859",
+                    "filePath": "forge-1.12.2-14.23.5.2859.jar", "minecraft": "1.12.2"},
+                    "versionInfo": {"id": "1.12.2-forge-14.23.5.2859", "inheritsFrom": "1.12.2",
+                    "mainClass": "net.minecraft.launchwrapper.Launch"}}"#,
+            )],
+        );
+
+        let metadata = read_installer(&jar).unwrap();
+        assert!(
+            matches!(&metadata, InstallerMetadata::Legacy { install, .. } if install.minecraft == "1.12.2")
+        );
+        assert_eq!(metadata.version().id, "1.12.2-forge-14.23.5.2859");
+
+        let empty = dir.path().join("empty.jar");
+        write_jar(&empty, &[]);
+        assert!(matches!(
+            read_installer(&empty),
+            Err(InstallerError::MissingEntry(name)) if name == "install_profile.json"
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+/// Forge builds, promotions and installer metadata.
+pub mod forge;
+/// NeoForge builds for Minecraft 1.20.1 and later.
+pub mod neoforge;
+/// Running loader installers headlessly to set up dedicated servers.
+pub mod server;
+
+use crate::http::{HttpClient, HttpError};
+use crate::maven::MavenCoordinate;
+use crate::version::VersionJson;
+use serde::{Deserialize, Serialize};
+
+/// Mod loaders that publish their versions through a Fabric-style meta API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoaderKind {
+    /// Fabric, served by `meta.fabricmc.net`.
+    Fabric,
+    /// Quilt, served by `meta.quiltmc.org`.
+    Quilt,
+}
+
+impl LoaderKind {
+    /// Returns the base URL of the loader's meta API.
+    pub fn meta_url(self) -> &'static str {
+        match self {
+            LoaderKind::Fabric => "https://meta.fabricmc.net/v2",
+            LoaderKind::Quilt => "https://meta.quiltmc.org/v3",
+        }
+    }
+
+    /// Returns the display name of the loader.
+    pub fn name(self) -> &'static str {
+        match self {
+            LoaderKind::Fabric => "Fabric",
+            LoaderKind::Quilt => "Quilt",
+        }
+    }
+}
+
+/// A meta API endpoint of a loader.
+///
+/// Fabric's v2 and Quilt's v3 APIs share the same layout, so the same client
+/// serves both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderMeta {
+    /// The loader served by this API.
+    pub kind: LoaderKind,
+    /// Base URL without a trailing slash, e.g. `https://meta.fabricmc.net/v2`.
+    pub base_url: String,
+}
+
+impl LoaderMeta {
+    /// Creates a client for the official meta API of `kind`.
+    pub fn new(kind: LoaderKind) -> Self {
+        Self {
+            kind,
+            base_url: kind.meta_url().to_string(),
+        }
+    }
+
+    /// Uses another base URL, e.g. a mirror.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    fn url(&self, segments: &[&str]) -> Result<String, HttpError> {
+        let mut url = reqwest::Url::parse(&self.base_url).map_err(|e| {
+            HttpError::Config(format!("invalid meta URL {:?}: {}", self.base_url, e))
+        })?;
+        url.path_segments_mut()
+            .map_err(|_| HttpError::Config(format!("invalid meta URL {:?}", self.base_url)))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url.into())
+    }
+}
+
+/// A Minecraft version supported by a loader.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameVersion {
+    /// The Minecraft version id.
+    pub version: String,
+    /// Whether the version is a release.
+    pub stable: bool,
+}
+
+/// A loader release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoaderVersion {
+    /// The loader version, e.g. `0.16.9`.
+    pub version: String,
+    /// The Maven coordinate of the loader jar.
+    pub maven: MavenCoordinate,
+    /// Build number, if the loader publishes one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<u32>,
+    /// Whether the release is stable. Quilt does not publish this field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stable: Option<bool>,
+}
+
+impl LoaderVersion {
+    /// Returns `true` if the release is stable.
+    ///
+    /// Without an explicit flag, pre-releases such as `0.20.0-beta.9` are unstable.
+    pub fn is_stable(&self) -> bool {
+        self.stable.unwrap_or_else(|| !self.version.contains('-'))
+    }
+}
+
+#[derive(Deserialize)]
+struct LoaderEntry {
+    loader: LoaderVersion,
+}
+
+impl HttpClient {
+    /// Lists the Minecraft versions supported by a loader.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is malformed.
+    pub async fn loader_game_versions(
+        &self,
+        meta: &LoaderMeta,
+    ) -> Result<Vec<GameVersion>, HttpError> {
+        self.get_json(&meta.url(&["versions", "game"])?).await
+    }
+
+    /// Lists loader releases, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `meta` - The loader meta API.
+    /// * `game_version` - If given, only releases compatible with this Minecraft
+    ///   version are listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is malformed.
+    pub async fn loader_versions(
+        &self,
+        meta: &LoaderMeta,
+        game_version: Option<&str>,
+    ) -> Result<Vec<LoaderVersion>, HttpError> {
+        match game_version {
+            Some(game) => {
+                let entries: Vec<LoaderEntry> = self
+                    .get_json(&meta.url(&["versions", "loader", game])?)
+                    .await?;
+                Ok(entries.into_iter().map(|e| e.loader).collect())
+            }
+            None => self.get_json(&meta.url(&["versions", "loader"])?).await,
+        }
+    }
+
+    /// Fetches the launcher profile of a loader release for a Minecraft version.
+    ///
+    /// The profile inherits from the vanilla version; resolve it with
+    /// [`crate::version::resolve_inheritance`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is not a version JSON.
+    pub async fn loader_profile(
+        &self,
+        meta: &LoaderMeta,
+        game_version: &str,
+        loader_version: &str,
+    ) -> Result<VersionJson, HttpError> {
+        let url = meta.url(&[
+            "versions",
+            "loader",
+            game_version,
+            loader_version,
+            "profile",
+            "json",
+        ])?;
+        self.get_json(&url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn builds_escaped_urls_and_detects_prereleases() {
+        let meta = LoaderMeta::new(LoaderKind::Quilt).with_base_url("https://meta.example.com/v3/");
+        assert_eq!(
+            meta.url(&["versions", "loader", "1.14 Pre-Release 5"])
+                .unwrap(),
+            "https://meta.example.com/v3/versions/loader/1.14%20Pre-Release%205"
+        );
+
+        let beta: LoaderVersion = serde_json::from_str(
+            r#"{"separator": ".", "build": 9, "maven": "org.quiltmc:quilt-loader:0.20.0-beta.9", "version": "0.20.0-beta.9"}"#,
+        )
+        .unwrap();
+        assert!(!beta.is_stable());
+        assert_eq!(beta.maven.artifact, "quilt-loader");
+    }
+
+    #[tokio::test]
+    async fn lists_loader_versions_and_fetches_profiles() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/v2/versions/loader/1.20.1");
+            then.status(200).body(
+                r#"[{"loader": {"separator": ".", "build": 9, "maven": "net.fabricmc:fabric-loader:0.16.9", "version": "0.16.9", "stable": true},
+                     "intermediary": {"maven": "net.fabricmc:intermediary:1.20.1", "version": "1.20.1", "stable": true}}]"#,
+            );
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/v2/versions/loader/1.20.1/0.16.9/profile/json");
+            then.status(200).body(
+                r#"{"id": "fabric-loader-0.16.9-1.20.1", "inheritsFrom": "1.20.1",
+                    "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                    "libraries": [{"name": "net.fabricmc:fabric-loader:0.16.9", "url": "https://maven.fabricmc.net/"}]}"#,
+            );
+        });
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+        let meta = LoaderMeta::new(LoaderKind::Fabric).with_base_url(server.url("/v2"));
+
+        let versions = client.loader_versions(&meta, Some("1.20.1")).await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert!(versions[0].is_stable());
+
+        let profile = client
+            .loader_profile(&meta, "1.20.1", &versions[0].version)
+            .await
+            .unwrap();
+        assert_eq!(profile.inherits_from.as_deref(), Some("1.20.1"));
+        assert_eq!(profile.libraries.len(), 1);
+    }
+}
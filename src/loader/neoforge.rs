@@ -0,0 +1,231 @@
+use crate::http::{HttpClient, HttpError};
+use crate::maven::{MavenCoordinate, metadata_url, parse_metadata_versions};
+
+/// The NeoForge Maven repository.
+pub const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases";
+
+/// The artifact a NeoForge build is published as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NeoForgeArtifact {
+    /// `net.neoforged:neoforge`, used from Minecraft 1.20.2.
+    NeoForge,
+    /// `net.neoforged:forge`, the Forge fork used for Minecraft 1.20.1.
+    Forge,
+}
+
+impl NeoForgeArtifact {
+    /// Returns the artifact id.
+    pub fn artifact_id(self) -> &'static str {
+        match self {
+            NeoForgeArtifact::NeoForge => "neoforge",
+            NeoForgeArtifact::Forge => "forge",
+        }
+    }
+}
+
+/// A NeoForge build.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NeoForgeVersion {
+    /// The version as published on Maven, e.g. `21.1.77` or `1.20.1-47.1.106`.
+    pub version: String,
+    /// The Minecraft version, e.g. `1.21.1`.
+    pub minecraft: String,
+    /// The artifact the build is published as.
+    pub artifact: NeoForgeArtifact,
+}
+
+impl NeoForgeVersion {
+    /// Parses a version of the `neoforge` artifact.
+    ///
+    /// Its first two components are the Minecraft minor and patch version, so
+    /// `21.1.77` targets 1.21.1 and `21.0.0-beta` targets 1.21.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.split(['.', '-']);
+        let minor: u32 = parts.next()?.parse().ok()?;
+        let patch: u32 = parts.next()?.parse().ok()?;
+        let minecraft = match patch {
+            0 => format!("1.{}", minor),
+            patch => format!("1.{}.{}", minor, patch),
+        };
+        Some(Self {
+            version: version.to_string(),
+            minecraft,
+            artifact: NeoForgeArtifact::NeoForge,
+        })
+    }
+
+    /// Parses a version of the 1.20.1 `forge` artifact, e.g. `1.20.1-47.1.106`.
+    pub fn parse_forge(version: &str) -> Option<Self> {
+        let (minecraft, build) = version.split_once('-')?;
+        if minecraft.is_empty() || build.is_empty() {
+            return None;
+        }
+        Some(Self {
+            version: version.to_string(),
+            minecraft: minecraft.to_string(),
+            artifact: NeoForgeArtifact::Forge,
+        })
+    }
+
+    /// Returns `true` unless the build is marked as a beta.
+    pub fn is_stable(&self) -> bool {
+        !self.version.contains("-beta")
+    }
+
+    /// Returns the coordinate of the installer jar.
+    pub fn installer(&self) -> MavenCoordinate {
+        MavenCoordinate::new("net.neoforged", self.artifact.artifact_id(), &self.version)
+            .with_classifier("installer")
+    }
+}
+
+/// All NeoForge builds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NeoForgeIndex {
+    /// Builds of both artifacts in Maven metadata order.
+    pub versions: Vec<NeoForgeVersion>,
+}
+
+impl NeoForgeIndex {
+    /// Builds an index from the `maven-metadata.xml` of the `neoforge` and the
+    /// `forge` artifact.
+    pub fn new(neoforge_xml: &str, forge_xml: &str) -> Self {
+        let forge = parse_metadata_versions(forge_xml)
+            .into_iter()
+            .filter_map(|v| NeoForgeVersion::parse_forge(&v));
+        let neoforge = parse_metadata_versions(neoforge_xml)
+            .into_iter()
+            .filter_map(|v| NeoForgeVersion::parse(&v));
+        Self {
+            versions: forge.chain(neoforge).collect(),
+        }
+    }
+
+    /// Returns the builds for a Minecraft version, newest first.
+    pub fn for_minecraft(&self, minecraft: &str) -> Vec<&NeoForgeVersion> {
+        let mut versions: Vec<_> = self
+            .versions
+            .iter()
+            .filter(|v| v.minecraft == minecraft)
+            .collect();
+        versions.reverse();
+        versions
+    }
+
+    /// Returns the newest build for a Minecraft version, preferring stable builds.
+    pub fn latest(&self, minecraft: &str) -> Option<&NeoForgeVersion> {
+        let versions = self.for_minecraft(minecraft);
+        versions
+            .iter()
+            .find(|v| v.is_stable())
+            .or_else(|| versions.first())
+            .copied()
+    }
+}
+
+impl HttpClient {
+    /// Fetches all NeoForge builds.
+    ///
+    /// Installers of both artifacts use the Forge installer format; read them
+    /// with [`super::forge::read_installer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if either metadata document cannot be fetched.
+    pub async fn neoforge_index(&self) -> Result<NeoForgeIndex, HttpError> {
+        let neoforge = self
+            .get_bytes(&metadata_url(NEOFORGE_MAVEN, "net.neoforged", "neoforge"))
+            .await?;
+        let forge = self
+            .get_bytes(&metadata_url(NEOFORGE_MAVEN, "net.neoforged", "forge"))
+            .await?;
+        Ok(NeoForgeIndex::new(
+            &String::from_utf8_lossy(&neoforge),
+            &String::from_utf8_lossy(&forge),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::forge::{InstallerMetadata, read_installer};
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn metadata(versions: &[&str]) -> String {
+        let versions: String = versions
+            .iter()
+            .map(|v| format!("<version>{}</version>", v))
+            .collect();
+        format!(
+            "<metadata><versioning><versions>{}</versions></versioning></metadata>",
+            versions
+        )
+    }
+
+    #[test]
+    fn maps_versions_to_minecraft_versions() {
+        let index = NeoForgeIndex::new(
+            &metadata(&[
+                "20.2.86",
+                "21.0.0-beta",
+                "21.1.76",
+                "21.1.77",
+                "21.2.0-beta",
+            ]),
+            &metadata(&["1.20.1-47.1.105", "1.20.1-47.1.106"]),
+        );
+
+        assert_eq!(index.latest("1.21.1").unwrap().version, "21.1.77");
+        assert_eq!(index.latest("1.20.2").unwrap().version, "20.2.86");
+        assert_eq!(index.latest("1.21").unwrap().version, "21.0.0-beta");
+        assert!(!index.latest("1.21.2").unwrap().is_stable());
+
+        let legacy = index.latest("1.20.1").unwrap();
+        assert_eq!(legacy.artifact, NeoForgeArtifact::Forge);
+        assert_eq!(
+            legacy.installer().url(NEOFORGE_MAVEN),
+            "https://maven.neoforged.net/releases/net/neoforged/forge/1.20.1-47.1.106/forge-1.20.1-47.1.106-installer.jar"
+        );
+    }
+
+    #[test]
+    fn reads_neoforge_installer() {
+        let dir = tempdir().unwrap();
+        let jar = dir.path().join("neoforge-21.1.77-installer.jar");
+        let mut writer = zip::ZipWriter::new(File::create(&jar).unwrap());
+        for (name, data) in [
+            (
+                "install_profile.json",
+                r#"{"spec": 1, "profile": "NeoForge", "version": "neoforge-21.1.77",
+                    "minecraft": "1.21.1", "json": "/version.json",
+                    "processors": [{"sides": ["client"], "jar": "net.neoforged.installertools:installertools:2.1.2",
+                        "args": ["--task", "PROCESS_MINECRAFT_JAR"]}]}"#,
+            ),
+            (
+                "version.json",
+                r#"{"id": "neoforge-21.1.77", "inheritsFrom": "1.21.1",
+                    "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher",
+                    "libraries": [{"name": "net.neoforged.fancymodloader:loader:4.0.31"}]}"#,
+            ),
+        ] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let metadata = read_installer(&jar).unwrap();
+
+        assert_eq!(metadata.minecraft(), "1.21.1");
+        assert_eq!(metadata.version().libraries.len(), 1);
+        assert!(matches!(
+            metadata,
+            InstallerMetadata::Modern { profile, .. } if profile.processors_for("server").count() == 0
+        ));
+    }
+}
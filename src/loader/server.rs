@@ -0,0 +1,341 @@
+use super::LoaderKind;
+use crate::maven::MavenCoordinate;
+use crate::server::{SERVER_JAR, ServerEntry};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use thiserror::Error;
+use tokio::process::Command;
+
+/// Error returned when a server installer fails.
+#[derive(Debug, Error)]
+pub enum ServerInstallError {
+    #[error("failed to run the server installer: {0}")]
+    Io(#[from] io::Error),
+    #[error("server installer exited with {status}")]
+    Failed {
+        status: ExitStatus,
+        /// Everything the installer printed.
+        log: Vec<String>,
+    },
+    #[error("server installer not found: {0}")]
+    InstallerNotFound(PathBuf),
+    #[error("server installer finished without setting up a server in {0}")]
+    NotInstalled(PathBuf),
+}
+
+/// A loader installer able to set up a dedicated server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerInstaller {
+    /// A Forge or NeoForge installer jar, run with `--installServer`.
+    Forge {
+        /// The downloaded installer jar.
+        jar: PathBuf,
+        /// The coordinate of the installer, e.g. from
+        /// [`super::forge::ForgeVersion::installer`] or
+        /// [`super::neoforge::NeoForgeVersion::installer`].
+        coordinate: MavenCoordinate,
+    },
+    /// The Fabric or Quilt installer jar, which also downloads the vanilla
+    /// server.
+    Fabric {
+        kind: LoaderKind,
+        /// The downloaded installer jar.
+        jar: PathBuf,
+        /// The Minecraft version, e.g. `1.20.1`.
+        minecraft: String,
+        /// The loader version, e.g. `0.16.9`.
+        loader: String,
+    },
+}
+
+impl ServerInstaller {
+    /// Returns the arguments passed to `java` to install into `server_dir`.
+    pub fn arguments(&self, server_dir: &Path) -> Vec<String> {
+        let dir = server_dir.display().to_string();
+        match self {
+            ServerInstaller::Forge { jar, .. } => vec![
+                "-jar".to_string(),
+                jar.display().to_string(),
+                "--installServer".to_string(),
+                dir,
+            ],
+            ServerInstaller::Fabric {
+                kind: LoaderKind::Fabric,
+                jar,
+                minecraft,
+                loader,
+            } => vec![
+                "-jar".to_string(),
+                jar.display().to_string(),
+                "server".to_string(),
+                "-dir".to_string(),
+                dir,
+                "-mcversion".to_string(),
+                minecraft.clone(),
+                "-loader".to_string(),
+                loader.clone(),
+                "-downloadMinecraft".to_string(),
+            ],
+            ServerInstaller::Fabric {
+                kind: LoaderKind::Quilt,
+                jar,
+                minecraft,
+                loader,
+            } => vec![
+                "-jar".to_string(),
+                jar.display().to_string(),
+                "install".to_string(),
+                "server".to_string(),
+                minecraft.clone(),
+                loader.clone(),
+                format!("--install-dir={}", dir),
+                "--download-server".to_string(),
+            ],
+        }
+    }
+
+    /// Returns how to start the server the installer set up in `server_dir`,
+    /// or `None` if its files are not there.
+    ///
+    /// Forge and NeoForge for 1.17 and later leave argument files below
+    /// `libraries`; older Forge builds leave a jar named after the build next
+    /// to the server. Fabric and Quilt leave a launch jar that starts the
+    /// vanilla `server.jar`, which must be there too.
+    pub fn installed_entry(&self, server_dir: &Path) -> Option<ServerEntry> {
+        match self {
+            ServerInstaller::Forge { coordinate, .. } => {
+                let dir = format!(
+                    "libraries/{}/{}/{}",
+                    coordinate.group.replace('.', "/"),
+                    coordinate.artifact,
+                    coordinate.version
+                );
+                let unix = PathBuf::from(format!("{}/unix_args.txt", dir));
+                let windows = PathBuf::from(format!("{}/win_args.txt", dir));
+                if server_dir.join(&unix).is_file() && server_dir.join(&windows).is_file() {
+                    return Some(ServerEntry::ArgsFile { unix, windows });
+                }
+                let stem = format!("{}-{}", coordinate.artifact, coordinate.version);
+                ["", "-universal"]
+                    .iter()
+                    .map(|suffix| PathBuf::from(format!("{}{}.jar", stem, suffix)))
+                    .find(|jar| server_dir.join(jar).is_file())
+                    .map(ServerEntry::Jar)
+            }
+            ServerInstaller::Fabric { kind, .. } => {
+                let launcher = PathBuf::from(match kind {
+                    LoaderKind::Fabric => "fabric-server-launch.jar",
+                    LoaderKind::Quilt => "quilt-server-launch.jar",
+                });
+                (server_dir.join(&launcher).is_file() && server_dir.join(SERVER_JAR).is_file())
+                    .then_some(ServerEntry::Jar(launcher))
+            }
+        }
+    }
+
+    fn jar(&self) -> &Path {
+        match self {
+            ServerInstaller::Forge { jar, .. } | ServerInstaller::Fabric { jar, .. } => jar,
+        }
+    }
+}
+
+/// The result of [`run_server_installer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInstall {
+    /// How to start the installed server, e.g. for
+    /// [`crate::server::ServerLaunchConfig::with_entry`].
+    pub entry: ServerEntry,
+    /// Everything the installer printed.
+    pub log: Vec<String>,
+}
+
+/// Runs a loader installer headlessly to set up a server in `server_dir` and
+/// checks that it left a startable server.
+///
+/// The installer runs in `server_dir`, which is created if needed, with its
+/// output captured. It downloads the vanilla server and libraries itself, so
+/// it needs network access.
+///
+/// # Arguments
+///
+/// * `java` - The `java` executable to run the installer with.
+/// * `installer` - The installer and what it installs.
+/// * `server_dir` - The folder of the server.
+///
+/// # Returns
+///
+/// * `ServerInstall` - How to start the server, and the installer's output.
+///
+/// # Errors
+///
+/// Returns [`ServerInstallError::Failed`] with the installer's output if it
+/// exits unsuccessfully, or [`ServerInstallError::NotInstalled`] if it
+/// succeeds without leaving the expected files.
+pub async fn run_server_installer(
+    java: &Path,
+    installer: &ServerInstaller,
+    server_dir: &Path,
+) -> Result<ServerInstall, ServerInstallError> {
+    if !installer.jar().is_file() {
+        return Err(ServerInstallError::InstallerNotFound(
+            installer.jar().to_path_buf(),
+        ));
+    }
+    std::fs::create_dir_all(server_dir)?;
+    // The installer runs in the server folder, so relative paths would
+    // resolve against it.
+    let server_dir = std::path::absolute(server_dir)?;
+    let installer = match installer.clone() {
+        ServerInstaller::Forge { jar, coordinate } => ServerInstaller::Forge {
+            jar: std::path::absolute(jar)?,
+            coordinate,
+        },
+        ServerInstaller::Fabric {
+            kind,
+            jar,
+            minecraft,
+            loader,
+        } => ServerInstaller::Fabric {
+            kind,
+            jar: std::path::absolute(jar)?,
+            minecraft,
+            loader,
+        },
+    };
+
+    let output = Command::new(java)
+        .args(installer.arguments(&server_dir))
+        .current_dir(&server_dir)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    let log: Vec<String> = [&output.stdout, &output.stderr]
+        .into_iter()
+        .flat_map(|bytes| {
+            String::from_utf8_lossy(bytes)
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if !output.status.success() {
+        return Err(ServerInstallError::Failed {
+            status: output.status,
+            log,
+        });
+    }
+    match installer.installed_entry(&server_dir) {
+        Some(entry) => Ok(ServerInstall { entry, log }),
+        None => Err(ServerInstallError::NotInstalled(server_dir)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn forge() -> MavenCoordinate {
+        MavenCoordinate::new("net.minecraftforge", "forge", "1.20.1-47.3.0")
+            .with_classifier("installer")
+    }
+
+    #[test]
+    fn finds_the_installed_entry() {
+        let dir = tempdir().unwrap();
+        let installer = ServerInstaller::Forge {
+            jar: "forge-installer.jar".into(),
+            coordinate: forge(),
+        };
+        assert_eq!(installer.installed_entry(dir.path()), None);
+        fs::write(dir.path().join("forge-1.20.1-47.3.0.jar"), b"").unwrap();
+        assert_eq!(
+            installer.installed_entry(dir.path()),
+            Some(ServerEntry::Jar("forge-1.20.1-47.3.0.jar".into()))
+        );
+        let args = dir
+            .path()
+            .join("libraries/net/minecraftforge/forge/1.20.1-47.3.0");
+        fs::create_dir_all(&args).unwrap();
+        fs::write(args.join("unix_args.txt"), b"").unwrap();
+        fs::write(args.join("win_args.txt"), b"").unwrap();
+        assert!(matches!(
+            installer.installed_entry(dir.path()),
+            Some(ServerEntry::ArgsFile { unix, .. })
+                if unix == Path::new("libraries/net/minecraftforge/forge/1.20.1-47.3.0/unix_args.txt")
+        ));
+
+        let quilt = ServerInstaller::Fabric {
+            kind: LoaderKind::Quilt,
+            jar: "quilt-installer.jar".into(),
+            minecraft: "1.20.1".into(),
+            loader: "0.26.0".into(),
+        };
+        assert_eq!(
+            quilt.arguments(Path::new("/srv/mc"))[2..],
+            [
+                "install",
+                "server",
+                "1.20.1",
+                "0.26.0",
+                "--install-dir=/srv/mc",
+                "--download-server"
+            ]
+        );
+        fs::write(dir.path().join("quilt-server-launch.jar"), b"").unwrap();
+        assert_eq!(quilt.installed_entry(dir.path()), None);
+        fs::write(dir.path().join(SERVER_JAR), b"").unwrap();
+        assert_eq!(
+            quilt.installed_entry(dir.path()),
+            Some(ServerEntry::Jar("quilt-server-launch.jar".into()))
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_the_installer_and_checks_its_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let installer_jar = dir.path().join("fabric-installer.jar");
+        fs::write(&installer_jar, b"").unwrap();
+        // Stands in for `java`, creating the files the Fabric installer would.
+        let java = dir.path().join("java");
+        fs::write(
+            &java,
+            "#!/bin/sh\necho \"installing $5 for $7\"\n[ \"$7\" = 1.20.1 ] || { echo bad version >&2; exit 3; }\ntouch fabric-server-launch.jar server.jar\n",
+        )
+        .unwrap();
+        fs::set_permissions(&java, fs::Permissions::from_mode(0o755)).unwrap();
+        let installer = |minecraft: &str| ServerInstaller::Fabric {
+            kind: LoaderKind::Fabric,
+            jar: installer_jar.clone(),
+            minecraft: minecraft.to_string(),
+            loader: "0.16.9".to_string(),
+        };
+
+        let server_dir = dir.path().join("server");
+        let install = run_server_installer(&java, &installer("1.20.1"), &server_dir)
+            .await
+            .unwrap();
+        assert_eq!(
+            install.entry,
+            ServerEntry::Jar("fabric-server-launch.jar".into())
+        );
+        assert_eq!(
+            install.log,
+            [format!("installing {} for 1.20.1", server_dir.display())]
+        );
+
+        let failed = run_server_installer(&java, &installer("1.0"), &dir.path().join("other"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            failed,
+            ServerInstallError::Failed { status, log } if status.code() == Some(3) && log.last().unwrap() == "bad version"
+        ));
+    }
+}
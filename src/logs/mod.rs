@@ -0,0 +1,4 @@
+/// Following `latest.log` while the game runs.
+pub mod tail;
+
+pub use tail::{LogEvent, TailOptions, latest_log, tail_log, tail_log_with};
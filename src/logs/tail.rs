@@ -0,0 +1,284 @@
+use futures_util::Stream;
+use futures_util::stream;
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// An event of a followed log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogEvent {
+    /// A complete line, without its line terminator.
+    Line(String),
+    /// The file shrank and is read again from the start.
+    Truncated,
+    /// The file was replaced by a new one, which is read from the start.
+    Rotated,
+}
+
+/// Options for [`tail_log_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TailOptions {
+    /// How often the file is checked for new data.
+    pub poll_interval: Duration,
+    /// Whether to emit the lines already in the file, or only new ones.
+    pub from_start: bool,
+}
+
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(250),
+            from_start: true,
+        }
+    }
+}
+
+impl TailOptions {
+    /// Sets how often the file is checked for new data.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets whether the lines already in the file are emitted.
+    pub fn with_from_start(mut self, from_start: bool) -> Self {
+        self.from_start = from_start;
+        self
+    }
+}
+
+/// Follows a growing log file such as `logs/latest.log` with default options.
+///
+/// See [`tail_log_with`].
+pub fn tail_log(path: impl Into<PathBuf>) -> impl Stream<Item = LogEvent> {
+    tail_log_with(path, TailOptions::default())
+}
+
+/// Follows a growing log file, yielding its lines as they are written.
+///
+/// The stream never ends on its own; drop it to stop following. A file that
+/// does not exist yet is waited for, so tailing can start before the game does.
+/// When the game rotates the log on startup, or the file is truncated, the
+/// stream reports it and continues with the new content. Invalid UTF-8 is
+/// replaced, since the game writes logs in the platform encoding on Windows.
+///
+/// # Arguments
+///
+/// * `path` - The log file.
+/// * `options` - Polling interval and starting position.
+///
+/// # Returns
+///
+/// * `impl Stream<Item = LogEvent>` - Lines, truncations and rotations.
+pub fn tail_log_with(
+    path: impl Into<PathBuf>,
+    options: TailOptions,
+) -> impl Stream<Item = LogEvent> {
+    let state = TailState {
+        path: path.into(),
+        options,
+        file: None,
+        identity: None,
+        offset: 0,
+        first_open: true,
+        partial: Vec::new(),
+        pending: VecDeque::new(),
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if !state.poll().await {
+                tokio::time::sleep(state.options.poll_interval).await;
+            }
+        }
+    })
+}
+
+/// Identifies a file across renames: its creation time and, on Unix, its inode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity {
+    created: Option<SystemTime>,
+    inode: u64,
+}
+
+impl FileIdentity {
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+        Self {
+            created: metadata.created().ok(),
+            inode,
+        }
+    }
+}
+
+struct TailState {
+    path: PathBuf,
+    options: TailOptions,
+    file: Option<File>,
+    identity: Option<FileIdentity>,
+    offset: u64,
+    first_open: bool,
+    partial: Vec<u8>,
+    pending: VecDeque<LogEvent>,
+}
+
+impl TailState {
+    /// Reads whatever is new and queues the events. Returns whether anything
+    /// was queued. I/O errors are treated like a missing file and retried.
+    async fn poll(&mut self) -> bool {
+        let Ok(metadata) = fs::metadata(&self.path).await else {
+            return false;
+        };
+        let identity = FileIdentity::of(&metadata);
+        if self.file.is_some() && self.identity != Some(identity) {
+            // Drain what the old file received before it was replaced.
+            self.read_new().await;
+            self.flush_partial();
+            self.pending.push_back(LogEvent::Rotated);
+            self.file = None;
+        } else if self.file.is_some() && metadata.len() < self.offset {
+            self.flush_partial();
+            self.pending.push_back(LogEvent::Truncated);
+            self.offset = 0;
+            if let Some(file) = &mut self.file
+                && file.seek(SeekFrom::Start(0)).await.is_err()
+            {
+                self.file = None;
+            }
+        }
+        if self.file.is_none() && !self.open(identity, metadata.len()).await {
+            return !self.pending.is_empty();
+        }
+        self.read_new().await;
+        !self.pending.is_empty()
+    }
+
+    async fn open(&mut self, identity: FileIdentity, len: u64) -> bool {
+        let Ok(mut file) = File::open(&self.path).await else {
+            return false;
+        };
+        let skip = self.first_open && !self.options.from_start;
+        self.offset = if skip { len } else { 0 };
+        if file.seek(SeekFrom::Start(self.offset)).await.is_err() {
+            return false;
+        }
+        self.first_open = false;
+        self.identity = Some(identity);
+        self.file = Some(file);
+        true
+    }
+
+    async fn read_new(&mut self) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let mut buffer = Vec::new();
+        let Ok(read) = file.read_to_end(&mut buffer).await else {
+            return;
+        };
+        self.offset += read as u64;
+        self.partial.extend_from_slice(&buffer);
+        while let Some(end) = self.partial.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            let line = line.strip_suffix(b"\n").unwrap_or(&line);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            self.pending
+                .push_back(LogEvent::Line(String::from_utf8_lossy(line).into_owned()));
+        }
+    }
+
+    /// Emits an unterminated last line of a file that is going away.
+    fn flush_partial(&mut self) {
+        if !self.partial.is_empty() {
+            let line = String::from_utf8_lossy(&self.partial).into_owned();
+            self.pending.push_back(LogEvent::Line(line));
+            self.partial.clear();
+        }
+    }
+}
+
+/// Returns the path of `latest.log` in a game directory.
+pub fn latest_log(game_dir: &Path) -> PathBuf {
+    game_dir.join("logs").join("latest.log")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    async fn next(stream: &mut (impl Stream<Item = LogEvent> + Unpin)) -> LogEvent {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    fn line(text: &str) -> LogEvent {
+        LogEvent::Line(text.to_string())
+    }
+
+    #[tokio::test]
+    async fn follows_growth_truncation_and_rotation() {
+        let dir = tempdir().unwrap();
+        let path = latest_log(dir.path());
+        let options = TailOptions::default().with_poll_interval(Duration::from_millis(10));
+        let mut stream = Box::pin(tail_log_with(&path, options));
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "[12:00:00] [main/INFO]: Starting\r\n[12:00:01] part").unwrap();
+        assert_eq!(
+            next(&mut stream).await,
+            line("[12:00:00] [main/INFO]: Starting")
+        );
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"ial\n").unwrap();
+        assert_eq!(next(&mut stream).await, line("[12:00:01] partial"));
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(next(&mut stream).await, LogEvent::Truncated);
+        file.write_all(b"after truncate\n").unwrap();
+        assert_eq!(next(&mut stream).await, line("after truncate"));
+
+        file.write_all(b"last words\n").unwrap();
+        drop(file);
+        std::fs::rename(&path, dir.path().join("logs/2024-03-01-1.log")).unwrap();
+        std::fs::write(&path, "new session\n").unwrap();
+        assert_eq!(next(&mut stream).await, line("last words"));
+        assert_eq!(next(&mut stream).await, LogEvent::Rotated);
+        assert_eq!(next(&mut stream).await, line("new session"));
+    }
+
+    #[tokio::test]
+    async fn skips_existing_content_when_asked() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("latest.log");
+        std::fs::write(&path, "old\n").unwrap();
+        let options = TailOptions::default()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_from_start(false);
+        let mut stream = Box::pin(tail_log_with(&path, options));
+        let idle = tokio::time::timeout(Duration::from_millis(50), stream.next()).await;
+        assert!(idle.is_err());
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"new\n").unwrap();
+        assert_eq!(next(&mut stream).await, line("new"));
+    }
+}
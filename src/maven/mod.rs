@@ -0,0 +1,323 @@
+use crate::http::{HttpClient, HttpError};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Error returned when a Maven coordinate cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MavenError {
+    #[error(
+        "invalid maven coordinate {0:?}: expected group:artifact:version[:classifier][@extension]"
+    )]
+    InvalidCoordinate(String),
+}
+
+/// A Maven artifact coordinate such as `net.fabricmc:fabric-loader:0.16.9` or
+/// `org.lwjgl:lwjgl:3.3.3:natives-linux@jar`.
+///
+/// Serializes as the coordinate string, the form used by the `name` field of
+/// version JSON libraries.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MavenCoordinate {
+    /// The group id, e.g. `net.fabricmc`.
+    pub group: String,
+    /// The artifact id, e.g. `fabric-loader`.
+    pub artifact: String,
+    /// The version.
+    pub version: String,
+    /// Optional classifier, e.g. `natives-linux`.
+    pub classifier: Option<String>,
+    /// File extension, `jar` unless given with `@`.
+    pub extension: String,
+}
+
+impl MavenCoordinate {
+    /// Creates a coordinate for a jar without classifier.
+    pub fn new(
+        group: impl Into<String>,
+        artifact: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Self {
+        Self {
+            group: group.into(),
+            artifact: artifact.into(),
+            version: version.into(),
+            classifier: None,
+            extension: "jar".to_string(),
+        }
+    }
+
+    /// Returns the same artifact with a different classifier.
+    pub fn with_classifier(mut self, classifier: impl Into<String>) -> Self {
+        self.classifier = Some(classifier.into());
+        self
+    }
+
+    /// Returns the same artifact with a different extension.
+    pub fn with_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = extension.into();
+        self
+    }
+
+    /// Returns the file name, e.g. `lwjgl-3.3.3-natives-linux.jar`.
+    pub fn file_name(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!(
+                "{}-{}-{}.{}",
+                self.artifact, self.version, classifier, self.extension
+            ),
+            None => format!("{}-{}.{}", self.artifact, self.version, self.extension),
+        }
+    }
+
+    /// Returns the path relative to a repository root, always with `/` separators,
+    /// e.g. `org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar`.
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.group.replace('.', "/"),
+            self.artifact,
+            self.version,
+            self.file_name()
+        )
+    }
+
+    /// Returns the location of the artifact below a local repository such as
+    /// the launcher's `libraries` directory.
+    pub fn local_path(&self, root: &Path) -> PathBuf {
+        self.path()
+            .split('/')
+            .fold(root.to_path_buf(), |path, part| path.join(part))
+    }
+
+    /// Returns the URL of the artifact in the repository at `repository`.
+    ///
+    /// # Arguments
+    ///
+    /// * `repository` - Base URL of the repository, with or without trailing slash.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The full artifact URL.
+    pub fn url(&self, repository: &str) -> String {
+        format!("{}/{}", repository.trim_end_matches('/'), self.path())
+    }
+
+    /// Returns the URL of the artifact in each repository, in the given order.
+    ///
+    /// Loaders list several repositories; try the URLs one after another until
+    /// one succeeds.
+    pub fn urls<S: AsRef<str>>(&self, repositories: &[S]) -> Vec<String> {
+        repositories
+            .iter()
+            .map(|repo| self.url(repo.as_ref()))
+            .collect()
+    }
+}
+
+impl FromStr for MavenCoordinate {
+    type Err = MavenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || MavenError::InvalidCoordinate(s.to_string());
+        let (coordinate, extension) = match s.split_once('@') {
+            Some((coordinate, extension)) => (coordinate, extension),
+            None => (s, "jar"),
+        };
+        let parts: Vec<&str> = coordinate.split(':').collect();
+        if !(3..=4).contains(&parts.len())
+            || extension.is_empty()
+            || parts.iter().any(|part| part.trim().is_empty())
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            group: parts[0].to_string(),
+            artifact: parts[1].to_string(),
+            version: parts[2].to_string(),
+            classifier: parts.get(3).map(|c| c.to_string()),
+            extension: extension.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for MavenCoordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{}", classifier)?;
+        }
+        if self.extension != "jar" {
+            write!(f, "@{}", self.extension)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for MavenCoordinate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MavenCoordinate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Returns the URL of `maven-metadata.xml` for an artifact.
+///
+/// # Arguments
+///
+/// * `repository` - Base URL of the repository, with or without trailing slash.
+/// * `group` - The group id, e.g. `net.minecraftforge`.
+/// * `artifact` - The artifact id, e.g. `forge`.
+pub fn metadata_url(repository: &str, group: &str, artifact: &str) -> String {
+    format!(
+        "{}/{}/{}/maven-metadata.xml",
+        repository.trim_end_matches('/'),
+        group.replace('.', "/"),
+        artifact
+    )
+}
+
+/// Extracts the published versions from a `maven-metadata.xml` document, in
+/// document order (usually oldest first).
+///
+/// # Arguments
+///
+/// * `xml` - The metadata document.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The contents of every `<version>` element.
+pub fn parse_metadata_versions(xml: &str) -> Vec<String> {
+    let versions = xml.find("<versions>").map_or(xml, |start| &xml[start..]);
+    let re = Regex::new(r"<version>\s*([^<]*?)\s*</version>").expect("valid regex");
+    re.captures_iter(versions)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+impl HttpClient {
+    /// Lists the published versions of an artifact from its `maven-metadata.xml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the metadata cannot be fetched.
+    pub async fn maven_versions(
+        &self,
+        repository: &str,
+        group: &str,
+        artifact: &str,
+    ) -> Result<Vec<String>, HttpError> {
+        let body = self
+            .get_bytes(&metadata_url(repository, group, artifact))
+            .await?;
+        Ok(parse_metadata_versions(&String::from_utf8_lossy(&body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_coordinate() {
+        let coordinate: MavenCoordinate = "net.fabricmc:fabric-loader:0.16.9".parse().unwrap();
+        assert_eq!(
+            coordinate,
+            MavenCoordinate::new("net.fabricmc", "fabric-loader", "0.16.9")
+        );
+        assert_eq!(
+            coordinate.path(),
+            "net/fabricmc/fabric-loader/0.16.9/fabric-loader-0.16.9.jar"
+        );
+        assert_eq!(
+            coordinate.url("https://maven.fabricmc.net/"),
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.16.9/fabric-loader-0.16.9.jar"
+        );
+    }
+
+    #[test]
+    fn parses_classifier_and_extension() {
+        let coordinate: MavenCoordinate = "de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412@zip"
+            .parse()
+            .unwrap();
+        assert_eq!(coordinate.extension, "zip");
+        assert_eq!(coordinate.classifier, None);
+        assert_eq!(
+            coordinate.file_name(),
+            "mcp_config-1.20.1-20230612.114412.zip"
+        );
+
+        let natives: MavenCoordinate = "org.lwjgl:lwjgl:3.3.3:natives-linux".parse().unwrap();
+        assert_eq!(natives.file_name(), "lwjgl-3.3.3-natives-linux.jar");
+        assert_eq!(natives.to_string(), "org.lwjgl:lwjgl:3.3.3:natives-linux");
+        assert_eq!(
+            natives.local_path(Path::new("libraries")),
+            Path::new("libraries/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar")
+        );
+    }
+
+    #[test]
+    fn builds_urls_for_each_repository() {
+        let coordinate = MavenCoordinate::new("a.b", "c", "1");
+        assert_eq!(
+            coordinate.urls(&["https://one.example", "https://two.example/maven/"]),
+            vec![
+                "https://one.example/a/b/c/1/c-1.jar",
+                "https://two.example/maven/a/b/c/1/c-1.jar"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_coordinates() {
+        for input in ["", "a:b", "a:b:c:d:e", "a::c", "a:b:c@"] {
+            assert!(input.parse::<MavenCoordinate>().is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn parses_metadata_versions() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>net.minecraftforge</groupId>
+  <artifactId>forge</artifactId>
+  <version>1.20.1-47.3.0</version>
+  <versioning>
+    <latest>1.20.1-47.3.0</latest>
+    <versions>
+      <version>1.7.10-10.13.4.1614-1.7.10</version>
+      <version> 1.20.1-47.3.0 </version>
+    </versions>
+  </versioning>
+</metadata>"#;
+        assert_eq!(
+            parse_metadata_versions(xml),
+            vec!["1.7.10-10.13.4.1614-1.7.10", "1.20.1-47.3.0"]
+        );
+        assert_eq!(
+            metadata_url(
+                "https://maven.minecraftforge.net/",
+                "net.minecraftforge",
+                "forge"
+            ),
+            "https://maven.minecraftforge.net/net/minecraftforge/forge/maven-metadata.xml"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_serde() {
+        let json = r#""net.minecraftforge:forge:1.20.1-47.3.0:universal""#;
+        let coordinate: MavenCoordinate = serde_json::from_str(json).unwrap();
+        assert_eq!(coordinate.classifier.as_deref(), Some("universal"));
+        assert_eq!(serde_json::to_string(&coordinate).unwrap(), json);
+    }
+}
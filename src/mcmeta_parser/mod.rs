@@ -2,6 +2,7 @@ use std::fs;
 use std::path::Path;
 use serde::Deserialize;
 use thiserror::Error;
+use crate::json::parse_json_lenient;
 
 /// Represents the contents of a `pack.mcmeta` file, which is used in Minecraft resource packs
 /// to provide metadata about the pack, such as its format version and description.
@@ -48,7 +49,8 @@ pub enum McmetaError {
 /// or if the `pack` section is missing.
 pub fn parse_resource_pack_mcmeta<P: AsRef<Path>>(path: P) -> Result<Mcmeta, McmetaError> {
     let content = fs::read_to_string(path)?;
-    let mcmeta: Mcmeta = serde_json::from_str(&content)?;
+    // The game reads pack metadata leniently, so hand-edited files may have comments.
+    let mcmeta: Mcmeta = parse_json_lenient(&content)?;
 
     // Validate that the `pack` section exists
     if mcmeta.pack.pack_format == 0 || mcmeta.pack.description.is_empty() {
@@ -63,7 +65,6 @@ mod tests {
     use super::*;
     use std::fs::File;
     use std::io::Write;
-    use tempfile::tempdir;
 
     fn write_temp_mcmeta(content: &str) -> (tempfile::TempDir, std::path::PathBuf) {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,653 @@
+use super::{
+    ModpackError, ModpackPlan, extract_overrides, override_copies, read_entry, safe_relative,
+};
+use crate::http::murmur2::Murmur2;
+use crate::http::{DownloadRequest, HashAlgorithm, HashSpec, HttpClient, HttpError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest inside a CurseForge modpack zip.
+pub const CURSEFORGE_MANIFEST: &str = "manifest.json";
+
+/// Base URL of the CurseForge API.
+///
+/// Every request needs an API key, sent with
+/// `client.with_header("x-api-key", key)`.
+pub const CURSEFORGE_API_URL: &str = "https://api.curseforge.com/v1";
+
+/// CurseForge game id of Minecraft.
+const MINECRAFT_GAME_ID: u32 = 432;
+/// CurseForge class id of resource packs.
+const CLASS_RESOURCE_PACKS: u32 = 12;
+/// CurseForge class id of shader packs.
+const CLASS_SHADER_PACKS: u32 = 6552;
+
+/// A mod loader listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurseForgeLoader {
+    /// Loader and version, e.g. `forge-47.2.0` or `fabric-0.16.9`.
+    pub id: String,
+    /// Whether this is the loader the pack runs on.
+    #[serde(default)]
+    pub primary: bool,
+}
+
+impl CurseForgeLoader {
+    /// Splits the id into the loader name and version, e.g. `("forge", "47.2.0")`.
+    pub fn loader(&self) -> Option<(&str, &str)> {
+        self.id.split_once('-')
+    }
+}
+
+/// The `minecraft` section of the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMinecraft {
+    /// The Minecraft version.
+    pub version: String,
+    /// The mod loaders, usually exactly one.
+    #[serde(default)]
+    pub mod_loaders: Vec<CurseForgeLoader>,
+}
+
+/// A file listed in the manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurseForgeManifestFile {
+    /// The project the file belongs to.
+    #[serde(rename = "projectID")]
+    pub project_id: u32,
+    /// The file.
+    #[serde(rename = "fileID")]
+    pub file_id: u32,
+    /// Whether the file must be installed.
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// The `manifest.json` of a CurseForge modpack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeManifest {
+    /// The game and loaders.
+    pub minecraft: CurseForgeMinecraft,
+    /// Always `minecraftModpack`.
+    pub manifest_type: String,
+    /// Format version, currently `1`.
+    pub manifest_version: u32,
+    /// The pack name.
+    pub name: String,
+    /// The pack version.
+    #[serde(default)]
+    pub version: String,
+    /// The pack author.
+    #[serde(default)]
+    pub author: String,
+    /// Files to download.
+    #[serde(default)]
+    pub files: Vec<CurseForgeManifestFile>,
+    /// Folder inside the zip copied over the instance, usually `overrides`.
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
+}
+
+fn default_overrides() -> String {
+    "overrides".to_string()
+}
+
+impl CurseForgeManifest {
+    /// Returns the primary mod loader, or the first one if none is marked primary.
+    pub fn primary_loader(&self) -> Option<&CurseForgeLoader> {
+        let loaders = &self.minecraft.mod_loaders;
+        loaders.iter().find(|l| l.primary).or(loaders.first())
+    }
+}
+
+/// A file hash published by CurseForge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurseForgeHash {
+    /// Hex digest.
+    pub value: String,
+    /// `1` for SHA-1, `2` for MD5.
+    pub algo: u32,
+}
+
+impl CurseForgeHash {
+    /// Returns the hash as a [`HashSpec`], if the algorithm is known.
+    pub fn spec(&self) -> Option<HashSpec> {
+        let algorithm = match self.algo {
+            1 => HashAlgorithm::Sha1,
+            2 => HashAlgorithm::Md5,
+            _ => return None,
+        };
+        Some(HashSpec::new(algorithm, &self.value))
+    }
+}
+
+/// A file returned by the CurseForge API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFile {
+    /// The file id.
+    pub id: u32,
+    /// The project the file belongs to.
+    pub mod_id: u32,
+    /// Human readable name.
+    #[serde(default)]
+    pub display_name: String,
+    /// File name, e.g. `jei-1.20.1-forge-15.2.0.27.jar`.
+    pub file_name: String,
+    /// Size in bytes.
+    #[serde(default)]
+    pub file_length: u64,
+    /// Download URL, `None` if the author does not allow third-party downloads.
+    #[serde(default)]
+    pub download_url: Option<String>,
+    /// Published hashes.
+    #[serde(default)]
+    pub hashes: Vec<CurseForgeHash>,
+    /// MurmurHash2 fingerprint of the file.
+    #[serde(default)]
+    pub file_fingerprint: u32,
+}
+
+/// A project returned by the CurseForge API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+    /// The project id.
+    pub id: u32,
+    /// Human readable name.
+    pub name: String,
+    /// URL slug.
+    #[serde(default)]
+    pub slug: String,
+    /// Project type, e.g. `6` for mods and `12` for resource packs.
+    #[serde(default)]
+    pub class_id: Option<u32>,
+}
+
+impl CurseForgeMod {
+    /// Returns the instance folder files of this project are installed to.
+    pub fn install_folder(&self) -> &'static str {
+        match self.class_id {
+            Some(CLASS_RESOURCE_PACKS) => "resourcepacks",
+            Some(CLASS_SHADER_PACKS) => "shaderpacks",
+            _ => "mods",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FingerprintResponse {
+    #[serde(default)]
+    exact_matches: Vec<ExactMatch>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExactMatch {
+    file: CurseForgeFile,
+    #[serde(default)]
+    latest_files: Vec<CurseForgeFile>,
+}
+
+/// A local file looked up by its CurseForge fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintMatch {
+    /// The local file.
+    pub path: PathBuf,
+    /// Its fingerprint, see [`curseforge_fingerprint`].
+    pub fingerprint: u32,
+    /// The matching CurseForge file, `None` if CurseForge does not know it.
+    pub file: Option<CurseForgeFile>,
+    /// The newest files of the matched project.
+    pub latest_files: Vec<CurseForgeFile>,
+}
+
+impl FingerprintMatch {
+    /// Returns the id of the matched project.
+    pub fn project_id(&self) -> Option<u32> {
+        self.file.as_ref().map(|f| f.mod_id)
+    }
+
+    /// Returns the latest files of the project that are newer than the local one.
+    ///
+    /// CurseForge file ids increase with every upload, so newer files have
+    /// greater ids.
+    pub fn updates(&self) -> Vec<&CurseForgeFile> {
+        let Some(file) = &self.file else {
+            return Vec::new();
+        };
+        self.latest_files
+            .iter()
+            .filter(|f| f.id > file.id)
+            .collect()
+    }
+}
+
+/// Computes the CurseForge fingerprint of a file.
+///
+/// This is the MurmurHash2 of the file with whitespace bytes removed, as used
+/// by the CurseForge fingerprint API.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn curseforge_fingerprint(path: &Path) -> io::Result<u32> {
+    let mut hasher = Murmur2::new();
+    hasher.update(&fs::read(path)?);
+    Ok(hasher.finalize())
+}
+
+/// The CurseForge API endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurseForgeApi {
+    /// Base URL without a trailing slash.
+    pub base_url: String,
+}
+
+impl Default for CurseForgeApi {
+    fn default() -> Self {
+        Self {
+            base_url: CURSEFORGE_API_URL.to_string(),
+        }
+    }
+}
+
+impl CurseForgeApi {
+    /// Creates a client for the official API.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses another base URL, e.g. a proxy holding the API key.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path)
+    }
+}
+
+/// An opened CurseForge modpack zip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurseForgePack {
+    /// The archive.
+    pub path: PathBuf,
+    /// The parsed manifest.
+    pub manifest: CurseForgeManifest,
+    entries: Vec<String>,
+}
+
+impl CurseForgePack {
+    /// Opens a CurseForge modpack zip and parses its manifest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be read, has no valid manifest, or
+    /// uses an unsupported manifest version.
+    pub fn open(path: &Path) -> Result<Self, ModpackError> {
+        let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+        let manifest: CurseForgeManifest =
+            serde_json::from_str(&read_entry(&mut archive, CURSEFORGE_MANIFEST)?)?;
+        if manifest.manifest_version != 1 {
+            return Err(ModpackError::UnsupportedFormat(manifest.manifest_version));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            manifest,
+            entries: archive.file_names().map(str::to_string).collect(),
+        })
+    }
+
+    /// Copies the planned overrides out of the archive.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, ModpackError>` - The number of files written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be read or a file cannot be written.
+    pub fn apply_overrides(&self, plan: &ModpackPlan) -> Result<usize, ModpackError> {
+        extract_overrides(&self.path, &plan.overrides)
+    }
+}
+
+impl HttpClient {
+    /// Fetches files by id from the CurseForge API.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is malformed.
+    pub async fn curseforge_files(
+        &self,
+        api: &CurseForgeApi,
+        file_ids: &[u32],
+    ) -> Result<Vec<CurseForgeFile>, HttpError> {
+        let body = serde_json::json!({ "fileIds": file_ids });
+        let response: ApiResponse<_> = self.post_json(&api.url("mods/files"), &body).await?;
+        Ok(response.data)
+    }
+
+    /// Fetches projects by id from the CurseForge API.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is malformed.
+    pub async fn curseforge_mods(
+        &self,
+        api: &CurseForgeApi,
+        mod_ids: &[u32],
+    ) -> Result<Vec<CurseForgeMod>, HttpError> {
+        let body = serde_json::json!({ "modIds": mod_ids });
+        let response: ApiResponse<_> = self.post_json(&api.url("mods"), &body).await?;
+        Ok(response.data)
+    }
+
+    /// Plans the installation of a CurseForge pack into `instance_dir`.
+    ///
+    /// The manifest only lists project and file ids, so the files and their
+    /// projects are resolved through the API. Files go to `mods`,
+    /// `resourcepacks` or `shaderpacks` depending on the project type. Files the
+    /// author does not allow to be downloaded by third parties have no download
+    /// URL and are reported in `skipped`, as are optional files unless
+    /// `include_optional` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - The CurseForge API.
+    /// * `pack` - The opened pack.
+    /// * `instance_dir` - The instance directory.
+    /// * `include_optional` - Whether files not marked required are installed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API cannot be reached, a listed file does not
+    /// exist, or a path would be written outside `instance_dir`.
+    pub async fn plan_curseforge_pack(
+        &self,
+        api: &CurseForgeApi,
+        pack: &CurseForgePack,
+        instance_dir: &Path,
+        include_optional: bool,
+    ) -> Result<ModpackPlan, ModpackError> {
+        let listed = &pack.manifest.files;
+        let mut plan = ModpackPlan::default();
+        if !listed.is_empty() {
+            let file_ids: Vec<u32> = listed.iter().map(|f| f.file_id).collect();
+            let mod_ids: Vec<u32> = listed.iter().map(|f| f.project_id).collect();
+            let files: HashMap<u32, CurseForgeFile> = self
+                .curseforge_files(api, &file_ids)
+                .await?
+                .into_iter()
+                .map(|f| (f.id, f))
+                .collect();
+            let mods: HashMap<u32, CurseForgeMod> = self
+                .curseforge_mods(api, &mod_ids)
+                .await?
+                .into_iter()
+                .map(|m| (m.id, m))
+                .collect();
+
+            for entry in listed {
+                let file = files.get(&entry.file_id).ok_or_else(|| {
+                    ModpackError::Invalid(format!("CurseForge file {} not found", entry.file_id))
+                })?;
+                let Some(url) = &file.download_url else {
+                    plan.skipped.push(file.file_name.clone());
+                    continue;
+                };
+                if !entry.required && !include_optional {
+                    plan.skipped.push(file.file_name.clone());
+                    continue;
+                }
+                let folder = mods
+                    .get(&entry.project_id)
+                    .map_or("mods", CurseForgeMod::install_folder);
+                let path = instance_dir
+                    .join(folder)
+                    .join(safe_relative(&file.file_name)?);
+                let mut request = DownloadRequest::new(url, path).with_size(file.file_length);
+                for hash in file.hashes.iter().filter_map(CurseForgeHash::spec) {
+                    request = request.with_hash(hash);
+                }
+                plan.downloads.push(request);
+            }
+        }
+
+        let prefix = format!("{}/", pack.manifest.overrides.trim_end_matches('/'));
+        plan.overrides = override_copies(&pack.entries, &prefix, instance_dir)?;
+        Ok(plan)
+    }
+
+    /// Identifies local files by their CurseForge fingerprint.
+    ///
+    /// Lets manually installed mods be matched to their CurseForge project and
+    /// file, so they can be checked for updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - The CurseForge API.
+    /// * `paths` - The files to identify, e.g. the jars in `mods`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<FingerprintMatch>, ModpackError>` - One match per path, in
+    ///   order. Files unknown to CurseForge have no `file`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or the API cannot be reached.
+    pub async fn identify_curseforge_files(
+        &self,
+        api: &CurseForgeApi,
+        paths: &[PathBuf],
+    ) -> Result<Vec<FingerprintMatch>, ModpackError> {
+        let fingerprints = paths
+            .iter()
+            .map(|path| curseforge_fingerprint(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut matches: HashMap<u32, ExactMatch> = HashMap::new();
+        if !fingerprints.is_empty() {
+            let body = serde_json::json!({ "fingerprints": fingerprints });
+            let url = api.url(&format!("fingerprints/{}", MINECRAFT_GAME_ID));
+            let response: ApiResponse<FingerprintResponse> = self.post_json(&url, &body).await?;
+            matches.extend(
+                response
+                    .data
+                    .exact_matches
+                    .into_iter()
+                    .map(|m| (m.file.file_fingerprint, m)),
+            );
+        }
+
+        Ok(paths
+            .iter()
+            .zip(fingerprints)
+            .map(|(path, fingerprint)| {
+                let found = matches.get(&fingerprint);
+                FingerprintMatch {
+                    path: path.clone(),
+                    fingerprint,
+                    file: found.map(|m| m.file.clone()),
+                    latest_files: found.map(|m| m.latest_files.clone()).unwrap_or_default(),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    const MANIFEST: &str = r#"{
+        "minecraft": {"version": "1.20.1", "modLoaders": [{"id": "forge-47.2.0", "primary": true}]},
+        "manifestType": "minecraftModpack", "manifestVersion": 1,
+        "name": "Example Pack", "version": "1.0.0", "author": "Alex",
+        "files": [
+            {"projectID": 238222, "fileID": 4712866, "required": true},
+            {"projectID": 322506, "fileID": 4600000, "required": true},
+            {"projectID": 400000, "fileID": 4800000, "required": false},
+            {"projectID": 500000, "fileID": 4900000, "required": true}
+        ],
+        "overrides": "overrides"
+    }"#;
+
+    fn write_pack(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn parses_manifest() {
+        let manifest: CurseForgeManifest = serde_json::from_str(MANIFEST).unwrap();
+
+        assert_eq!(manifest.minecraft.version, "1.20.1");
+        assert_eq!(
+            manifest.primary_loader().and_then(CurseForgeLoader::loader),
+            Some(("forge", "47.2.0"))
+        );
+        assert_eq!(manifest.files.len(), 4);
+        assert!(!manifest.files[2].required);
+    }
+
+    #[tokio::test]
+    async fn resolves_files_through_the_api() {
+        let server = MockServer::start();
+        let files = server.mock(|when, then| {
+            when.method(POST)
+                .path("/mods/files")
+                .header("x-api-key", "key")
+                .json_body(serde_json::json!({"fileIds": [4712866, 4600000, 4800000, 4900000]}));
+            then.status(200).json_body(serde_json::json!({"data": [
+                {"id": 4712866, "modId": 238222, "fileName": "jei-15.2.0.27.jar",
+                 "fileLength": 1000, "downloadUrl": "https://edge.forgecdn.net/files/4712/866/jei-15.2.0.27.jar",
+                 "hashes": [{"value": "aa", "algo": 1}, {"value": "bb", "algo": 2}]},
+                {"id": 4600000, "modId": 322506, "fileName": "Faithful.zip",
+                 "fileLength": 2000, "downloadUrl": "https://edge.forgecdn.net/files/4600/0/Faithful.zip"},
+                {"id": 4800000, "modId": 400000, "fileName": "extra.jar",
+                 "downloadUrl": "https://edge.forgecdn.net/files/4800/0/extra.jar"},
+                {"id": 4900000, "modId": 500000, "fileName": "optifine.jar", "downloadUrl": null}
+            ]}));
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/mods");
+            then.status(200).json_body(serde_json::json!({"data": [
+                {"id": 238222, "name": "JEI", "classId": 6},
+                {"id": 322506, "name": "Faithful", "classId": 12}
+            ]}));
+        });
+        let dir = tempdir().unwrap();
+        let pack_path = dir.path().join("pack.zip");
+        write_pack(
+            &pack_path,
+            &[
+                (CURSEFORGE_MANIFEST, MANIFEST),
+                ("overrides/config/jei.toml", "x"),
+                ("overrides/", ""),
+            ],
+        );
+        let pack = CurseForgePack::open(&pack_path).unwrap();
+        let instance = dir.path().join("instance");
+        let client = HttpClient::new(ClientOptions::default())
+            .unwrap()
+            .with_header("x-api-key", "key");
+        let api = CurseForgeApi::new().with_base_url(server.base_url());
+
+        let plan = client
+            .plan_curseforge_pack(&api, &pack, &instance, false)
+            .await
+            .unwrap();
+
+        files.assert();
+        assert_eq!(plan.downloads.len(), 2);
+        assert_eq!(
+            plan.downloads[0].path,
+            instance.join("mods/jei-15.2.0.27.jar")
+        );
+        assert_eq!(plan.downloads[0].hashes.len(), 2);
+        assert_eq!(
+            plan.downloads[1].path,
+            instance.join("resourcepacks/Faithful.zip")
+        );
+        assert_eq!(plan.skipped, vec!["extra.jar", "optifine.jar"]);
+        assert_eq!(plan.overrides.len(), 1);
+        assert_eq!(pack.apply_overrides(&plan).unwrap(), 1);
+        assert!(instance.join("config/jei.toml").is_file());
+    }
+
+    #[tokio::test]
+    async fn identifies_files_by_fingerprint() {
+        let dir = tempdir().unwrap();
+        let known = dir.path().join("jei.jar");
+        let unknown = dir.path().join("custom.jar");
+        fs::write(&known, b"jei contents").unwrap();
+        fs::write(&unknown, b"something else").unwrap();
+        let fingerprint = curseforge_fingerprint(&known).unwrap();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/fingerprints/432");
+            then.status(200).json_body(serde_json::json!({"data": {
+                "exactMatches": [{
+                    "id": 238222,
+                    "file": {"id": 100, "modId": 238222, "fileName": "jei.jar",
+                             "fileFingerprint": fingerprint},
+                    "latestFiles": [
+                        {"id": 100, "modId": 238222, "fileName": "jei.jar"},
+                        {"id": 120, "modId": 238222, "fileName": "jei-new.jar"}
+                    ]
+                }],
+                "unmatchedFingerprints": [curseforge_fingerprint(&unknown).unwrap()]
+            }}));
+        });
+        let api = CurseForgeApi::new().with_base_url(server.base_url());
+
+        let matches = HttpClient::new(ClientOptions::default())
+            .unwrap()
+            .identify_curseforge_files(&api, &[known.clone(), unknown])
+            .await
+            .unwrap();
+
+        assert_eq!(matches[0].path, known);
+        assert_eq!(matches[0].project_id(), Some(238222));
+        assert_eq!(
+            matches[0]
+                .updates()
+                .iter()
+                .map(|f| f.id)
+                .collect::<Vec<_>>(),
+            vec![120]
+        );
+        assert!(matches[1].file.is_none());
+        assert!(matches[1].updates().is_empty());
+    }
+}
@@ -0,0 +1,422 @@
+use super::ModpackError;
+use super::mrpack::{MRPACK_INDEX, ModrinthApi, MrpackFile, MrpackIndex};
+use crate::http::{HashAlgorithm, HttpClient, hash_reader};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+
+/// Paths below the game directory that are not exported by default.
+pub const DEFAULT_EXPORT_EXCLUDES: [&str; 7] = [
+    "logs",
+    "crash-reports",
+    "screenshots",
+    "natives",
+    ".cache",
+    "usercache.json",
+    "usernamecache.json",
+];
+
+/// Folders whose files may be published on Modrinth.
+const DOWNLOADABLE_FOLDERS: [&str; 3] = ["mods/", "resourcepacks/", "shaderpacks/"];
+
+/// What to put into an exported pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// The pack name.
+    pub name: String,
+    /// The pack version.
+    pub version_id: String,
+    /// Short description.
+    pub summary: Option<String>,
+    /// Versions of `minecraft` and the loader, keyed by their `.mrpack` ids
+    /// (`forge`, `neoforge`, `fabric-loader` or `quilt-loader`).
+    pub dependencies: BTreeMap<String, String>,
+    /// Paths relative to the game directory that are left out, with `/` separators.
+    pub exclude: Vec<String>,
+}
+
+impl ExportOptions {
+    /// Creates options for a pack of the given Minecraft version.
+    pub fn new(name: impl Into<String>, minecraft: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version_id: "1.0.0".to_string(),
+            summary: None,
+            dependencies: BTreeMap::from([("minecraft".to_string(), minecraft.into())]),
+            exclude: DEFAULT_EXPORT_EXCLUDES.map(str::to_string).to_vec(),
+        }
+    }
+
+    /// Sets the pack version.
+    pub fn with_version_id(mut self, version_id: impl Into<String>) -> Self {
+        self.version_id = version_id.into();
+        self
+    }
+
+    /// Sets the pack description.
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Adds the mod loader, e.g. `("fabric-loader", "0.16.9")`.
+    pub fn with_loader(mut self, id: impl Into<String>, version: impl Into<String>) -> Self {
+        self.dependencies.insert(id.into(), version.into());
+        self
+    }
+
+    /// Leaves out another path relative to the game directory.
+    pub fn with_exclude(mut self, path: impl Into<String>) -> Self {
+        self.exclude.push(path.into());
+        self
+    }
+
+    fn is_excluded(&self, relative: &str) -> bool {
+        self.exclude.iter().any(|excluded| {
+            relative == excluded
+                || relative
+                    .strip_prefix(excluded.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+/// A planned `.mrpack` export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrpackExport {
+    /// The index, listing the files available on Modrinth.
+    pub index: MrpackIndex,
+    /// Files embedded under `overrides/`, relative to the game directory.
+    pub embedded: Vec<String>,
+}
+
+/// Lists the files of a game directory, relative and with `/` separators, sorted.
+fn instance_files(game_dir: &Path, options: &ExportOptions) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![String::new()];
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(game_dir.join(&relative))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            if options.is_excluded(&path) {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn add_file<W: Write + io::Seek>(
+    writer: &mut zip::ZipWriter<W>,
+    name: &str,
+    source: &Path,
+) -> Result<(), ModpackError> {
+    writer.start_file(name, SimpleFileOptions::default())?;
+    io::copy(&mut File::open(source)?, writer)?;
+    Ok(())
+}
+
+/// Returns the MultiMC component uid of a `.mrpack` dependency id.
+fn multimc_uid(id: &str) -> Option<&'static str> {
+    match id {
+        "minecraft" => Some("net.minecraft"),
+        "forge" => Some("net.minecraftforge"),
+        "neoforge" => Some("net.neoforged"),
+        "fabric-loader" => Some("net.fabricmc.fabric-loader"),
+        "quilt-loader" => Some("org.quiltmc.quilt-loader"),
+        _ => None,
+    }
+}
+
+/// Packages a game directory as a MultiMC instance zip.
+///
+/// The zip holds one folder named after the pack with `instance.cfg`,
+/// `mmc-pack.json` and the game files under `.minecraft/`, which MultiMC and
+/// Prism Launcher import as is.
+///
+/// # Arguments
+///
+/// * `game_dir` - The game directory of the instance.
+/// * `dest` - The zip to create.
+/// * `options` - The pack name, versions and excluded paths.
+///
+/// # Returns
+///
+/// * `Result<usize, ModpackError>` - The number of game files written.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or the zip cannot be written.
+pub fn export_multimc(
+    game_dir: &Path,
+    dest: &Path,
+    options: &ExportOptions,
+) -> Result<usize, ModpackError> {
+    let folder: String = options
+        .name
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | '\\' | ':') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    // MultiMC expects Minecraft before the loader patching it.
+    let mut dependencies: Vec<_> = options.dependencies.iter().collect();
+    dependencies.sort_by_key(|(id, _)| id.as_str() != "minecraft");
+    let components: Vec<_> = dependencies
+        .into_iter()
+        .filter_map(|(id, version)| {
+            let uid = multimc_uid(id)?;
+            Some(serde_json::json!({ "uid": uid, "version": version, "important": uid == "net.minecraft" }))
+        })
+        .collect();
+    let pack = serde_json::json!({ "formatVersion": 1, "components": components });
+    let files = instance_files(game_dir, options)?;
+
+    let mut writer = zip::ZipWriter::new(File::create(dest)?);
+    writer.start_file(
+        format!("{}/instance.cfg", folder),
+        SimpleFileOptions::default(),
+    )?;
+    write!(writer, "InstanceType=OneSix\nname={}\n", options.name)?;
+    writer.start_file(
+        format!("{}/mmc-pack.json", folder),
+        SimpleFileOptions::default(),
+    )?;
+    writer.write_all(&serde_json::to_vec_pretty(&pack)?)?;
+    for file in &files {
+        add_file(
+            &mut writer,
+            &format!("{}/.minecraft/{}", folder, file),
+            &game_dir.join(file),
+        )?;
+    }
+    writer.finish()?;
+    Ok(files.len())
+}
+
+impl HttpClient {
+    /// Plans a `.mrpack` export of a game directory.
+    ///
+    /// Jars and zips in `mods`, `resourcepacks` and `shaderpacks` are looked up
+    /// on Modrinth by SHA-1; those found are listed in the index with their
+    /// Modrinth download. Everything else is embedded under `overrides/`.
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - The Modrinth API.
+    /// * `game_dir` - The game directory of the instance.
+    /// * `options` - The pack name, versions and excluded paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or the lookup fails.
+    pub async fn plan_mrpack_export(
+        &self,
+        api: &ModrinthApi,
+        game_dir: &Path,
+        options: &ExportOptions,
+    ) -> Result<MrpackExport, ModpackError> {
+        let files = instance_files(game_dir, options)?;
+        let mut candidates = Vec::new();
+        for file in &files {
+            if DOWNLOADABLE_FOLDERS.iter().any(|f| file.starts_with(f))
+                && (file.ends_with(".jar") || file.ends_with(".zip"))
+            {
+                let reader = BufReader::new(File::open(game_dir.join(file))?);
+                candidates.push((file.as_str(), hash_reader(reader, HashAlgorithm::Sha1)?));
+            }
+        }
+        let hashes: Vec<String> = candidates.iter().map(|(_, h)| h.clone()).collect();
+        let versions = if hashes.is_empty() {
+            Default::default()
+        } else {
+            self.modrinth_versions_by_sha1(api, &hashes).await?
+        };
+
+        let mut published = BTreeMap::new();
+        for (path, sha1) in &candidates {
+            let Some(file) = versions.get(sha1).and_then(|v| v.file_with_sha1(sha1)) else {
+                continue;
+            };
+            published.insert(
+                *path,
+                MrpackFile {
+                    path: path.to_string(),
+                    hashes: file.hashes.clone(),
+                    env: None,
+                    downloads: vec![file.url.clone()],
+                    file_size: file.size,
+                },
+            );
+        }
+
+        let embedded = files
+            .iter()
+            .filter(|f| !published.contains_key(f.as_str()))
+            .cloned()
+            .collect();
+        Ok(MrpackExport {
+            index: MrpackIndex {
+                format_version: 1,
+                game: "minecraft".to_string(),
+                version_id: options.version_id.clone(),
+                name: options.name.clone(),
+                summary: options.summary.clone(),
+                files: published.into_values().collect(),
+                dependencies: options.dependencies.clone(),
+            },
+            embedded,
+        })
+    }
+}
+
+/// Writes a planned `.mrpack` export.
+///
+/// # Arguments
+///
+/// * `game_dir` - The game directory the export was planned from.
+/// * `export` - The planned export.
+/// * `dest` - The `.mrpack` to create.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or the archive cannot be written.
+pub fn write_mrpack(
+    game_dir: &Path,
+    export: &MrpackExport,
+    dest: &Path,
+) -> Result<(), ModpackError> {
+    let mut writer = zip::ZipWriter::new(File::create(dest)?);
+    writer.start_file(MRPACK_INDEX, SimpleFileOptions::default())?;
+    writer.write_all(&serde_json::to_vec_pretty(&export.index)?)?;
+    for file in &export.embedded {
+        add_file(
+            &mut writer,
+            &format!("overrides/{}", file),
+            &game_dir.join(file),
+        )?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use crate::modpack::{Mrpack, MrpackPlanOptions};
+    use httpmock::prelude::*;
+    use std::io::Read;
+    use tempfile::tempdir;
+
+    fn game_dir(root: &Path) -> std::path::PathBuf {
+        let game = root.join(".minecraft");
+        for (path, contents) in [
+            ("mods/sodium.jar", "sodium"),
+            ("mods/custom.jar", "custom"),
+            ("config/sodium.json", "{}"),
+            ("options.txt", "fov:0.5"),
+            ("logs/latest.log", "log"),
+        ] {
+            let path = game.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn exports_multimc_zip() {
+        let dir = tempdir().unwrap();
+        let game = game_dir(dir.path());
+        let zip_path = dir.path().join("export.zip");
+        let options =
+            ExportOptions::new("My Pack", "1.20.1").with_loader("fabric-loader", "0.16.9");
+
+        assert_eq!(export_multimc(&game, &zip_path, &options).unwrap(), 4);
+
+        let mut archive = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        assert!(
+            archive
+                .by_name("My Pack/.minecraft/mods/sodium.jar")
+                .is_ok()
+        );
+        assert!(
+            archive
+                .by_name("My Pack/.minecraft/logs/latest.log")
+                .is_err()
+        );
+        let mut pack = String::new();
+        archive
+            .by_name("My Pack/mmc-pack.json")
+            .unwrap()
+            .read_to_string(&mut pack)
+            .unwrap();
+        let pack: serde_json::Value = serde_json::from_str(&pack).unwrap();
+        assert_eq!(pack["components"][0]["uid"], "net.minecraft");
+        assert_eq!(pack["components"][0]["version"], "1.20.1");
+        assert_eq!(pack["components"][1]["uid"], "net.fabricmc.fabric-loader");
+    }
+
+    #[tokio::test]
+    async fn exports_mrpack_with_published_files_as_downloads() {
+        let dir = tempdir().unwrap();
+        let game = game_dir(dir.path());
+        let sodium_sha1 = hash_reader(&b"sodium"[..], HashAlgorithm::Sha1).unwrap();
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/version_files");
+            then.status(200).json_body(serde_json::json!({
+                sodium_sha1.clone(): {
+                    "id": "abc", "project_id": "AANobbMI", "version_number": "0.5.11",
+                    "files": [{
+                        "hashes": {"sha1": sodium_sha1, "sha512": "ff"},
+                        "url": "https://cdn.modrinth.com/data/AANobbMI/sodium.jar",
+                        "filename": "sodium.jar", "primary": true, "size": 6
+                    }]
+                }
+            }));
+        });
+        let api = ModrinthApi::new().with_base_url(server.base_url());
+        let options = ExportOptions::new("My Pack", "1.20.1").with_exclude("options.txt");
+
+        let export = HttpClient::new(ClientOptions::default())
+            .unwrap()
+            .plan_mrpack_export(&api, &game, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(export.index.files.len(), 1);
+        assert_eq!(export.index.files[0].path, "mods/sodium.jar");
+        assert_eq!(
+            export.embedded,
+            vec!["config/sodium.json", "mods/custom.jar"]
+        );
+
+        let mrpack_path = dir.path().join("export.mrpack");
+        write_mrpack(&game, &export, &mrpack_path).unwrap();
+        let pack = Mrpack::open(&mrpack_path).unwrap();
+        let plan = pack
+            .plan(&dir.path().join("imported"), &MrpackPlanOptions::default())
+            .unwrap();
+        assert_eq!(plan.downloads.len(), 1);
+        assert_eq!(plan.overrides.len(), 2);
+    }
+}
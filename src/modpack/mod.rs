@@ -0,0 +1,138 @@
+/// CurseForge modpack zips and the CurseForge files API.
+pub mod curseforge;
+/// Export of instances as MultiMC zips and `.mrpack` archives.
+pub mod export;
+/// Modrinth `.mrpack` archives.
+pub mod mrpack;
+
+pub use curseforge::{
+    CurseForgeApi, CurseForgeFile, CurseForgeHash, CurseForgeLoader, CurseForgeManifest,
+    CurseForgeManifestFile, CurseForgeMinecraft, CurseForgeMod, CurseForgePack, FingerprintMatch,
+    curseforge_fingerprint,
+};
+pub use export::{ExportOptions, MrpackExport, export_multimc, write_mrpack};
+pub use mrpack::{
+    EnvSupport, ModrinthApi, ModrinthVersion, ModrinthVersionFile, Mrpack, MrpackEnv, MrpackFile,
+    MrpackIndex, MrpackPlanOptions,
+};
+
+use crate::http::{DownloadRequest, HttpError};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// Error returned when a modpack cannot be read or installed.
+#[derive(Debug, Error)]
+pub enum ModpackError {
+    #[error("failed to read modpack: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid modpack archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("failed to parse modpack manifest: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("modpack does not contain {0}")]
+    MissingEntry(String),
+    #[error("unsupported modpack format version {0}")]
+    UnsupportedFormat(u32),
+    #[error("modpack path {0:?} escapes the instance directory")]
+    UnsafePath(String),
+    #[error("invalid modpack: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Http(#[from] HttpError),
+}
+
+/// A file copied from the modpack archive into the instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverrideCopy {
+    /// Entry name inside the archive.
+    pub source: String,
+    /// Destination path.
+    pub target: PathBuf,
+}
+
+/// Everything needed to create an instance from a modpack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModpackPlan {
+    /// Files to download.
+    pub downloads: Vec<DownloadRequest>,
+    /// Files to copy out of the archive, in order; later copies overwrite earlier ones.
+    pub overrides: Vec<OverrideCopy>,
+    /// Files left out of the plan, e.g. optional files, files unsupported on the
+    /// side, or CurseForge files whose authors do not allow third-party downloads.
+    pub skipped: Vec<String>,
+}
+
+/// Checks that a path from a modpack stays inside the instance directory.
+pub(crate) fn safe_relative(path: &str) -> Result<&Path, ModpackError> {
+    let relative = Path::new(path);
+    let safe = !path.is_empty()
+        && relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if safe {
+        Ok(relative)
+    } else {
+        Err(ModpackError::UnsafePath(path.to_string()))
+    }
+}
+
+/// Lists the files below `prefix` in `entries` as copies into `instance_dir`.
+pub(crate) fn override_copies(
+    entries: &[String],
+    prefix: &str,
+    instance_dir: &Path,
+) -> Result<Vec<OverrideCopy>, ModpackError> {
+    let mut copies = Vec::new();
+    for entry in entries {
+        let Some(relative) = entry.strip_prefix(prefix) else {
+            continue;
+        };
+        if relative.is_empty() || relative.ends_with('/') {
+            continue;
+        }
+        copies.push(OverrideCopy {
+            source: entry.clone(),
+            target: instance_dir.join(safe_relative(relative)?),
+        });
+    }
+    Ok(copies)
+}
+
+/// Reads the archive entry `name` as text.
+pub(crate) fn read_entry<R: io::Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String, ModpackError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(ModpackError::MissingEntry(name.to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let mut text = String::new();
+    entry.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Copies `copies` out of the archive at `archive`, in order.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(archive = %archive.display(), files = copies.len()), err)
+)]
+pub(crate) fn extract_overrides(
+    archive: &Path,
+    copies: &[OverrideCopy],
+) -> Result<usize, ModpackError> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(File::open(archive)?))?;
+    for copy in copies {
+        let mut entry = archive.by_name(&copy.source)?;
+        if let Some(parent) = copy.target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&copy.target)?)?;
+    }
+    Ok(copies.len())
+}
@@ -0,0 +1,459 @@
+use super::{
+    ModpackError, ModpackPlan, extract_overrides, override_copies, read_entry, safe_relative,
+};
+use crate::http::{DownloadRequest, HashAlgorithm, HashSpec, HttpClient, HttpError};
+use crate::mods::Side;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Name of the index inside a `.mrpack` archive.
+pub const MRPACK_INDEX: &str = "modrinth.index.json";
+
+/// How a file is needed on one side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvSupport {
+    /// The file must be installed.
+    Required,
+    /// The user may choose to install the file.
+    Optional,
+    /// The file must not be installed.
+    Unsupported,
+}
+
+/// The `env` section of a modpack file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MrpackEnv {
+    /// Support on the client.
+    pub client: EnvSupport,
+    /// Support on the dedicated server.
+    pub server: EnvSupport,
+}
+
+/// A file to download listed in `modrinth.index.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackFile {
+    /// Destination relative to the instance directory, e.g. `mods/sodium.jar`.
+    pub path: String,
+    /// Hex digests keyed by algorithm, at least `sha1` and `sha512`.
+    pub hashes: BTreeMap<String, String>,
+    /// Side support. Missing means required on both sides.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<MrpackEnv>,
+    /// Download URLs, tried in order.
+    pub downloads: Vec<String>,
+    /// Size in bytes.
+    pub file_size: u64,
+}
+
+impl MrpackFile {
+    /// Returns how the file is needed on `side`. `Side::Both` takes the stronger
+    /// requirement of the two sides.
+    pub fn support(&self, side: Side) -> EnvSupport {
+        let Some(env) = self.env else {
+            return EnvSupport::Required;
+        };
+        match side {
+            Side::Client => env.client,
+            Side::Server => env.server,
+            Side::Both => match (env.client, env.server) {
+                (EnvSupport::Required, _) | (_, EnvSupport::Required) => EnvSupport::Required,
+                (EnvSupport::Optional, _) | (_, EnvSupport::Optional) => EnvSupport::Optional,
+                _ => EnvSupport::Unsupported,
+            },
+        }
+    }
+
+    /// Returns the download request for the file below `instance_dir`, verified
+    /// by size and the strongest published hash.
+    ///
+    /// A request accepts a file matching any of its hashes, so adding the
+    /// weaker `sha1` next to `sha512` would let a file matching only the
+    /// `sha1` pass. Only the first URL of [`MrpackFile::downloads`] is used;
+    /// the others are left for the caller to retry with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ModpackError::UnsafePath`] if the path escapes the instance, and
+    /// [`ModpackError::Invalid`] if the file has no download URL.
+    pub fn request(&self, instance_dir: &Path) -> Result<DownloadRequest, ModpackError> {
+        let url = self
+            .downloads
+            .first()
+            .ok_or_else(|| ModpackError::Invalid(format!("{} has no download URL", self.path)))?;
+        let mut request = DownloadRequest::new(url, instance_dir.join(safe_relative(&self.path)?))
+            .with_size(self.file_size);
+        let strongest = self
+            .hashes
+            .iter()
+            .filter_map(|(algorithm, hex)| Some((algorithm.parse::<HashAlgorithm>().ok()?, hex)))
+            .min_by_key(|(algorithm, _)| hash_strength_rank(*algorithm));
+        if let Some((algorithm, hex)) = strongest {
+            request = request.with_hash(HashSpec::new(algorithm, hex));
+        }
+        Ok(request)
+    }
+}
+
+/// Orders hash algorithms from strongest to weakest.
+fn hash_strength_rank(algorithm: HashAlgorithm) -> u8 {
+    match algorithm {
+        HashAlgorithm::Sha512 => 0,
+        HashAlgorithm::Sha256 => 1,
+        HashAlgorithm::Sha1 => 2,
+        HashAlgorithm::Md5 => 3,
+        HashAlgorithm::Crc32 => 4,
+        HashAlgorithm::Murmur2 => 5,
+    }
+}
+
+/// The `modrinth.index.json` of a `.mrpack`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackIndex {
+    /// Format version, currently `1`.
+    pub format_version: u32,
+    /// The game, always `minecraft`.
+    pub game: String,
+    /// The pack version.
+    pub version_id: String,
+    /// The pack name.
+    pub name: String,
+    /// Short description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Files to download.
+    #[serde(default)]
+    pub files: Vec<MrpackFile>,
+    /// Versions of `minecraft`, `forge`, `neoforge`, `fabric-loader` or `quilt-loader`.
+    pub dependencies: BTreeMap<String, String>,
+}
+
+impl MrpackIndex {
+    /// Returns the Minecraft version of the pack.
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.dependencies.get("minecraft").map(String::as_str)
+    }
+}
+
+/// Options for [`Mrpack::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MrpackPlanOptions {
+    /// The side the instance runs on.
+    pub side: Side,
+    /// Whether optional files are installed.
+    pub include_optional: bool,
+}
+
+impl Default for MrpackPlanOptions {
+    fn default() -> Self {
+        Self {
+            side: Side::Client,
+            include_optional: true,
+        }
+    }
+}
+
+/// An opened `.mrpack` archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mrpack {
+    /// The archive.
+    pub path: PathBuf,
+    /// The parsed index.
+    pub index: MrpackIndex,
+    entries: Vec<String>,
+}
+
+impl Mrpack {
+    /// Opens a `.mrpack` archive and parses its index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be read, has no valid index, or uses
+    /// an unsupported format version.
+    pub fn open(path: &Path) -> Result<Self, ModpackError> {
+        let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+        let index: MrpackIndex = serde_json::from_str(&read_entry(&mut archive, MRPACK_INDEX)?)?;
+        if index.format_version != 1 {
+            return Err(ModpackError::UnsupportedFormat(index.format_version));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            index,
+            entries: archive.file_names().map(str::to_string).collect(),
+        })
+    }
+
+    /// Plans the installation of the pack into `instance_dir`.
+    ///
+    /// `overrides/` is copied first, then `client-overrides/` or
+    /// `server-overrides/` for the chosen side.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ModpackError::UnsafePath`] if a file or override would be written
+    /// outside `instance_dir`.
+    pub fn plan(
+        &self,
+        instance_dir: &Path,
+        options: &MrpackPlanOptions,
+    ) -> Result<ModpackPlan, ModpackError> {
+        let mut plan = ModpackPlan::default();
+        for file in &self.index.files {
+            match file.support(options.side) {
+                EnvSupport::Required => plan.downloads.push(file.request(instance_dir)?),
+                EnvSupport::Optional if options.include_optional => {
+                    plan.downloads.push(file.request(instance_dir)?)
+                }
+                _ => plan.skipped.push(file.path.clone()),
+            }
+        }
+
+        let mut prefixes = vec!["overrides/"];
+        match options.side {
+            Side::Client => prefixes.push("client-overrides/"),
+            Side::Server => prefixes.push("server-overrides/"),
+            Side::Both => {}
+        }
+        for prefix in prefixes {
+            plan.overrides
+                .extend(override_copies(&self.entries, prefix, instance_dir)?);
+        }
+        Ok(plan)
+    }
+
+    /// Copies the planned overrides out of the archive.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, ModpackError>` - The number of files written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be read or a file cannot be written.
+    pub fn apply_overrides(&self, plan: &ModpackPlan) -> Result<usize, ModpackError> {
+        extract_overrides(&self.path, &plan.overrides)
+    }
+}
+
+/// Base URL of the Modrinth API.
+pub const MODRINTH_API_URL: &str = "https://api.modrinth.com/v2";
+
+/// The Modrinth API endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModrinthApi {
+    /// Base URL without a trailing slash.
+    pub base_url: String,
+}
+
+impl Default for ModrinthApi {
+    fn default() -> Self {
+        Self {
+            base_url: MODRINTH_API_URL.to_string(),
+        }
+    }
+}
+
+impl ModrinthApi {
+    /// Creates a client for the official API.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses another base URL, e.g. the staging API.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+}
+
+/// A file of a Modrinth version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthVersionFile {
+    /// Hex digests keyed by algorithm.
+    pub hashes: BTreeMap<String, String>,
+    /// Download URL.
+    pub url: String,
+    /// File name.
+    pub filename: String,
+    /// Whether this is the main file of the version.
+    #[serde(default)]
+    pub primary: bool,
+    /// Size in bytes.
+    pub size: u64,
+}
+
+/// A version of a Modrinth project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthVersion {
+    /// The version id.
+    pub id: String,
+    /// The project the version belongs to.
+    pub project_id: String,
+    /// The version number set by the author.
+    #[serde(default)]
+    pub version_number: String,
+    /// The files of the version.
+    pub files: Vec<ModrinthVersionFile>,
+}
+
+impl ModrinthVersion {
+    /// Returns the file with the given SHA-1.
+    pub fn file_with_sha1(&self, sha1: &str) -> Option<&ModrinthVersionFile> {
+        self.files.iter().find(|f| {
+            f.hashes
+                .get("sha1")
+                .is_some_and(|h| h.eq_ignore_ascii_case(sha1))
+        })
+    }
+}
+
+impl HttpClient {
+    /// Looks up Modrinth versions by the SHA-1 of their files.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HashMap<String, ModrinthVersion>, HttpError>` - The versions
+    ///   keyed by hash. Hashes unknown to Modrinth are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HttpError` if the request fails or the response is malformed.
+    pub async fn modrinth_versions_by_sha1(
+        &self,
+        api: &ModrinthApi,
+        hashes: &[String],
+    ) -> Result<HashMap<String, ModrinthVersion>, HttpError> {
+        let body = serde_json::json!({ "hashes": hashes, "algorithm": "sha1" });
+        self.post_json(&format!("{}/version_files", api.base_url), &body)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    const INDEX: &str = r#"{
+        "formatVersion": 1, "game": "minecraft", "versionId": "1.0.0", "name": "Example Pack",
+        "files": [
+            {"path": "mods/sodium.jar", "hashes": {"sha1": "aa", "sha512": "bb"},
+             "env": {"client": "required", "server": "unsupported"},
+             "downloads": ["https://cdn.modrinth.com/data/sodium.jar"], "fileSize": 10},
+            {"path": "mods/lithium.jar", "hashes": {"sha1": "cc", "sha512": "dd"},
+             "downloads": ["https://cdn.modrinth.com/data/lithium.jar"], "fileSize": 20},
+            {"path": "resourcepacks/extra.zip", "hashes": {"sha1": "ee"},
+             "env": {"client": "optional", "server": "unsupported"},
+             "downloads": ["https://cdn.modrinth.com/data/extra.zip"], "fileSize": 30}
+        ],
+        "dependencies": {"minecraft": "1.20.1", "fabric-loader": "0.16.9"}
+    }"#;
+
+    fn write_pack(path: &Path, index: &str, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        writer
+            .start_file(MRPACK_INDEX, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(index.as_bytes()).unwrap();
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn plans_client_and_server_installs() {
+        let dir = tempdir().unwrap();
+        let pack_path = dir.path().join("pack.mrpack");
+        write_pack(
+            &pack_path,
+            INDEX,
+            &[
+                ("overrides/config/a.toml", "base"),
+                ("client-overrides/config/a.toml", "client"),
+                ("server-overrides/server.properties", "motd=x"),
+            ],
+        );
+        let pack = Mrpack::open(&pack_path).unwrap();
+        assert_eq!(pack.index.minecraft_version(), Some("1.20.1"));
+        let instance = dir.path().join("instance");
+
+        let client = pack.plan(&instance, &MrpackPlanOptions::default()).unwrap();
+        assert_eq!(client.downloads.len(), 3);
+        assert_eq!(client.downloads[0].path, instance.join("mods/sodium.jar"));
+        assert_eq!(client.downloads[0].size, Some(10));
+        assert_eq!(
+            client.downloads[0].hashes,
+            [HashSpec::new(HashAlgorithm::Sha512, "bb")]
+        );
+        assert_eq!(client.downloads[2].hashes[0].algorithm, HashAlgorithm::Sha1);
+        assert_eq!(
+            client
+                .overrides
+                .iter()
+                .map(|o| o.source.as_str())
+                .collect::<Vec<_>>(),
+            vec!["overrides/config/a.toml", "client-overrides/config/a.toml"]
+        );
+        assert_eq!(pack.apply_overrides(&client).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(instance.join("config/a.toml")).unwrap(),
+            "client"
+        );
+
+        let server = pack
+            .plan(
+                &instance,
+                &MrpackPlanOptions {
+                    side: Side::Server,
+                    include_optional: false,
+                },
+            )
+            .unwrap();
+        assert_eq!(server.downloads.len(), 1);
+        assert_eq!(
+            server.skipped,
+            vec!["mods/sodium.jar", "resourcepacks/extra.zip"]
+        );
+        assert_eq!(
+            server.overrides[1].target,
+            instance.join("server.properties")
+        );
+    }
+
+    #[test]
+    fn rejects_paths_escaping_the_instance() {
+        let dir = tempdir().unwrap();
+        let pack_path = dir.path().join("evil.mrpack");
+        write_pack(
+            &pack_path,
+            &INDEX.replace("mods/lithium.jar", "../../lithium.jar"),
+            &[],
+        );
+        let pack = Mrpack::open(&pack_path).unwrap();
+        assert!(matches!(
+            pack.plan(dir.path(), &MrpackPlanOptions::default()),
+            Err(ModpackError::UnsafePath(path)) if path == "../../lithium.jar"
+        ));
+
+        let missing = dir.path().join("missing.mrpack");
+        zip::ZipWriter::new(File::create(&missing).unwrap())
+            .finish()
+            .unwrap();
+        assert!(matches!(
+            Mrpack::open(&missing),
+            Err(ModpackError::MissingEntry(_))
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+use super::resolver::ScannedMod;
+use super::version_range::compare_versions;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Pairs of mods that crash or break rendering when installed together, as
+/// `(kept, disabled, reason)`. The first mod is kept because it replaces the
+/// second.
+const KNOWN_INCOMPATIBLE: &[(&str, &str, &str)] = &[
+    (
+        "sodium",
+        "optifine",
+        "Sodium and OptiFine both replace the renderer",
+    ),
+    (
+        "embeddium",
+        "optifine",
+        "Embeddium and OptiFine both replace the renderer",
+    ),
+    (
+        "rubidium",
+        "optifine",
+        "Rubidium and OptiFine both replace the renderer",
+    ),
+    (
+        "embeddium",
+        "rubidium",
+        "Embeddium is a fork of Rubidium and replaces it",
+    ),
+    (
+        "iris",
+        "optifine",
+        "Iris and OptiFine both load shader packs",
+    ),
+    (
+        "oculus",
+        "optifine",
+        "Oculus and OptiFine both load shader packs",
+    ),
+    (
+        "starlight",
+        "phosphor",
+        "Starlight and Phosphor both rewrite the light engine",
+    ),
+];
+
+/// Why two or more jars cannot be loaded together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Several jars provide the same mod id.
+    Duplicate {
+        /// The mod id.
+        mod_id: String,
+    },
+    /// Two mods known not to work together are installed.
+    Incompatible {
+        /// The mod kept.
+        mod_id: String,
+        /// The mod disabled.
+        other: String,
+        /// A sentence to show the user.
+        reason: &'static str,
+    },
+}
+
+/// A conflict found by [`find_mod_conflicts`], with the suggested fix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModConflict {
+    pub kind: ConflictKind,
+    /// The jar to keep: the newest version for duplicates.
+    pub keep: PathBuf,
+    /// The jars to disable.
+    pub disable: Vec<PathBuf>,
+}
+
+/// Finds jars in a mods folder that stop the game from starting together.
+///
+/// For duplicates the newest version by [`compare_versions`] is kept, and
+/// on equal versions the first jar by path. For known incompatible pairs,
+/// such as Sodium and OptiFine, the mod replacing the other is kept. Jars
+/// providing several mods are disabled as a whole, so a fix may disable
+/// other mods too.
+///
+/// # Arguments
+///
+/// * `mods` - The mods of the instance, one entry per mod id and jar.
+///
+/// # Returns
+///
+/// * `Vec<ModConflict>` - Duplicates by mod id, then incompatible pairs.
+pub fn find_mod_conflicts(mods: &[ScannedMod]) -> Vec<ModConflict> {
+    let mut by_id: BTreeMap<&str, Vec<&ScannedMod>> = BTreeMap::new();
+    for scanned in mods {
+        let providers = by_id.entry(&scanned.mod_id).or_default();
+        // A jar declaring a mod twice is not a duplicate.
+        if !providers.iter().any(|m| m.path == scanned.path) {
+            providers.push(scanned);
+        }
+    }
+    for providers in by_id.values_mut() {
+        providers.sort_by(|a, b| {
+            compare_versions(&b.version, &a.version).then_with(|| a.path.cmp(&b.path))
+        });
+    }
+
+    let mut conflicts = Vec::new();
+    for (mod_id, providers) in &by_id {
+        if let [newest, older @ ..] = &providers[..]
+            && !older.is_empty()
+        {
+            conflicts.push(ModConflict {
+                kind: ConflictKind::Duplicate {
+                    mod_id: mod_id.to_string(),
+                },
+                keep: newest.path.clone(),
+                disable: older.iter().map(|m| m.path.clone()).collect(),
+            });
+        }
+    }
+    for (kept, disabled, reason) in KNOWN_INCOMPATIBLE {
+        if let (Some(keep), Some(disable)) = (by_id.get(kept), by_id.get(disabled)) {
+            conflicts.push(ModConflict {
+                kind: ConflictKind::Incompatible {
+                    mod_id: kept.to_string(),
+                    other: disabled.to_string(),
+                    reason,
+                },
+                keep: keep[0].path.clone(),
+                disable: disable.iter().map(|m| m.path.clone()).collect(),
+            });
+        }
+    }
+    conflicts
+}
+
+/// Returns every jar the conflicts suggest disabling, each once, in order.
+pub fn jars_to_disable(conflicts: &[ModConflict]) -> Vec<PathBuf> {
+    let mut jars: Vec<PathBuf> = Vec::new();
+    for jar in conflicts.iter().flat_map(|c| &c.disable) {
+        if !jars.contains(jar) {
+            jars.push(jar.clone());
+        }
+    }
+    jars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::Side;
+
+    fn scanned(path: &str, id: &str, version: &str) -> ScannedMod {
+        ScannedMod {
+            path: PathBuf::from(path),
+            mod_id: id.to_string(),
+            version: version.to_string(),
+            side: Side::Both,
+            dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn keeps_the_newest_duplicate_and_disables_incompatible_mods() {
+        let mods = [
+            scanned("mods/jei-15.2.0.jar", "jei", "15.2.0"),
+            scanned("mods/jei-15.10.0.jar", "jei", "15.10.0"),
+            scanned("mods/jei-15.3.0.jar", "jei", "15.3.0"),
+            scanned("mods/create.jar", "create", "0.5.1"),
+            scanned("mods/create.jar", "create", "0.5.1"),
+            scanned("mods/embeddium.jar", "embeddium", "0.3.18"),
+            scanned("mods/OptiFine_HD_U_I6.jar", "optifine", "HD_U_I6"),
+        ];
+
+        let conflicts = find_mod_conflicts(&mods);
+        assert_eq!(
+            conflicts,
+            vec![
+                ModConflict {
+                    kind: ConflictKind::Duplicate {
+                        mod_id: "jei".to_string()
+                    },
+                    keep: PathBuf::from("mods/jei-15.10.0.jar"),
+                    disable: vec![
+                        PathBuf::from("mods/jei-15.3.0.jar"),
+                        PathBuf::from("mods/jei-15.2.0.jar"),
+                    ],
+                },
+                ModConflict {
+                    kind: ConflictKind::Incompatible {
+                        mod_id: "embeddium".to_string(),
+                        other: "optifine".to_string(),
+                        reason: "Embeddium and OptiFine both replace the renderer",
+                    },
+                    keep: PathBuf::from("mods/embeddium.jar"),
+                    disable: vec![PathBuf::from("mods/OptiFine_HD_U_I6.jar")],
+                },
+            ]
+        );
+        assert_eq!(jars_to_disable(&conflicts).len(), 3);
+        assert!(find_mod_conflicts(&mods[3..6]).is_empty());
+    }
+}
@@ -0,0 +1,23 @@
+/// Duplicate and incompatible jars in a mods folder, with suggested fixes.
+pub mod conflicts;
+/// `mods.toml` and `neoforge.mods.toml` metadata of Forge and NeoForge mods.
+pub mod mods_toml;
+/// Pre-launch checks of mod dependencies.
+pub mod resolver;
+/// Enabling and disabling mods by renaming their jars.
+pub mod toggle;
+/// Maven version ranges, Fabric version constraints and version ordering.
+pub mod version_range;
+
+pub use conflicts::{ConflictKind, ModConflict, find_mod_conflicts, jars_to_disable};
+pub use mods_toml::{
+    Dependency, DependencyKind, ModEntry, ModsToml, ModsTomlError, Side, read_mods_toml,
+};
+pub use resolver::{
+    DependencyProblem, DependencyReport, ModDependency, ResolveContext, ScannedMod,
+    check_dependencies,
+};
+pub use toggle::{DISABLED_SUFFIX, ModToggleError, is_mod_enabled, set_mod_enabled};
+pub use version_range::{
+    FabricRange, MavenRange, VersionRangeError, VersionReq, compare_semver, compare_versions,
+};
@@ -0,0 +1,361 @@
+use crate::jar::{MANIFEST_PATH, Manifest};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+/// Placeholder replaced by the jar's `Implementation-Version`.
+pub const JAR_VERSION_PLACEHOLDER: &str = "${file.jarVersion}";
+
+/// Metadata files of Forge and NeoForge mods, in lookup order.
+const MODS_TOML_PATHS: [&str; 2] = ["META-INF/neoforge.mods.toml", "META-INF/mods.toml"];
+
+/// Error returned when mod metadata cannot be read.
+#[derive(Debug, Error)]
+pub enum ModsTomlError {
+    #[error("failed to read mod jar: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid mod jar: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("failed to parse mods.toml: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+/// The side a mod or dependency applies to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Side {
+    /// Client and server.
+    #[default]
+    Both,
+    /// Only the client.
+    Client,
+    /// Only the dedicated server.
+    Server,
+}
+
+/// How a dependency relates to the depending mod.
+///
+/// Read case-insensitively, as NeoForge does, so `REQUIRED` is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    /// The dependency must be present.
+    Required,
+    /// The dependency is used if present.
+    Optional,
+    /// The game refuses to start if the dependency is present.
+    Incompatible,
+    /// A warning is shown if the dependency is present.
+    Discouraged,
+}
+
+impl<'de> Deserialize<'de> for DependencyKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const VARIANTS: &[&str] = &["required", "optional", "incompatible", "discouraged"];
+        let kind = String::deserialize(deserializer)?;
+        match kind.to_ascii_lowercase().as_str() {
+            "required" => Ok(DependencyKind::Required),
+            "optional" => Ok(DependencyKind::Optional),
+            "incompatible" => Ok(DependencyKind::Incompatible),
+            "discouraged" => Ok(DependencyKind::Discouraged),
+            _ => Err(de::Error::unknown_variant(&kind, VARIANTS)),
+        }
+    }
+}
+
+/// A `[[mods]]` entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModEntry {
+    /// The mod id.
+    pub mod_id: String,
+    /// The mod version, with `${file.jarVersion}` resolved when read from a jar.
+    #[serde(default = "default_mod_version")]
+    pub version: String,
+    /// Human readable name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Authors, as free text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authors: Option<String>,
+    /// Path of the logo inside the jar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_file: Option<String>,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, toml::Value>,
+}
+
+fn default_mod_version() -> String {
+    "1".to_string()
+}
+
+/// A `[[dependencies.<modId>]]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    /// The id of the mod depended on.
+    pub mod_id: String,
+    /// Forge's required flag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mandatory: Option<bool>,
+    /// NeoForge's dependency type, replacing `mandatory`.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<DependencyKind>,
+    /// Maven version range, e.g. `[47,)`. Empty or `*` accepts any version.
+    #[serde(default)]
+    pub version_range: String,
+    /// `NONE`, `BEFORE` or `AFTER`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ordering: Option<String>,
+    /// The side the dependency applies to.
+    #[serde(default)]
+    pub side: Side,
+}
+
+impl Dependency {
+    /// Returns the dependency type, deriving it from `mandatory` for Forge mods.
+    pub fn kind(&self) -> DependencyKind {
+        match (self.kind, self.mandatory) {
+            (Some(kind), _) => kind,
+            (None, Some(false)) => DependencyKind::Optional,
+            (None, _) => DependencyKind::Required,
+        }
+    }
+}
+
+/// A parsed `mods.toml` or `neoforge.mods.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsToml {
+    /// The language loader, usually `javafml`.
+    pub mod_loader: String,
+    /// Version range of the language loader.
+    #[serde(default)]
+    pub loader_version: String,
+    /// The license of the mods in the jar.
+    #[serde(default)]
+    pub license: String,
+    /// The mods provided by the jar.
+    #[serde(default)]
+    pub mods: Vec<ModEntry>,
+    /// Dependencies keyed by the id of the depending mod.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Vec<Dependency>>,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, toml::Value>,
+}
+
+impl ModsToml {
+    /// Parses a `mods.toml` document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is not a valid `mods.toml`.
+    pub fn from_toml(text: &str) -> Result<Self, ModsTomlError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Replaces `${file.jarVersion}` in mod versions with `jar_version`.
+    pub fn resolve_jar_version(&mut self, jar_version: &str) {
+        for entry in &mut self.mods {
+            if entry.version.contains(JAR_VERSION_PLACEHOLDER) {
+                entry.version = entry.version.replace(JAR_VERSION_PLACEHOLDER, jar_version);
+            }
+        }
+    }
+
+    /// Returns the dependencies declared by `mod_id`.
+    pub fn dependencies_of(&self, mod_id: &str) -> &[Dependency] {
+        self.dependencies
+            .get(mod_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Reads the Forge or NeoForge metadata of a mod jar.
+///
+/// `META-INF/neoforge.mods.toml` is preferred over `META-INF/mods.toml`. Versions
+/// set to `${file.jarVersion}` are replaced by the `Implementation-Version` of
+/// the jar manifest, if it has one.
+///
+/// # Arguments
+///
+/// * `jar` - The mod jar.
+///
+/// # Returns
+///
+/// * `Result<Option<ModsToml>, ModsTomlError>` - The metadata, or `None` if the
+///   jar is not a Forge or NeoForge mod.
+///
+/// # Errors
+///
+/// Returns an error if the jar cannot be read or its metadata is malformed.
+pub fn read_mods_toml(jar: &Path) -> Result<Option<ModsToml>, ModsTomlError> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(File::open(jar)?))?;
+    let Some(text) = MODS_TOML_PATHS
+        .iter()
+        .find_map(|name| read_entry(&mut archive, name).transpose())
+        .transpose()?
+    else {
+        return Ok(None);
+    };
+
+    let mut metadata = ModsToml::from_toml(&text)?;
+    if let Some(manifest) = read_entry(&mut archive, MANIFEST_PATH)?
+        && let Some(version) = Manifest::parse(manifest.as_bytes()).implementation_version()
+    {
+        metadata.resolve_jar_version(version);
+    }
+    Ok(Some(metadata))
+}
+
+fn read_entry<R: io::Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, ModsTomlError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(Some(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    const MODS_TOML: &str = r#"
+modLoader = "javafml"
+loaderVersion = "[47,)"
+license = "MIT"
+issueTrackerURL = "https://example.com/issues"
+
+[[mods]]
+modId = "examplemod"
+version = "${file.jarVersion}"
+displayName = "Example Mod"
+authors = "Alex"
+description = '''
+An example.
+'''
+
+[[dependencies.examplemod]]
+modId = "forge"
+mandatory = true
+versionRange = "[47,)"
+ordering = "NONE"
+side = "BOTH"
+
+[[dependencies.examplemod]]
+modId = "jei"
+mandatory = false
+versionRange = "[15.2,)"
+side = "CLIENT"
+"#;
+
+    #[test]
+    fn parses_forge_mods_toml() {
+        let metadata = ModsToml::from_toml(MODS_TOML).unwrap();
+
+        assert_eq!(metadata.mod_loader, "javafml");
+        assert_eq!(
+            metadata.mods[0].display_name.as_deref(),
+            Some("Example Mod")
+        );
+        assert_eq!(metadata.mods[0].version, JAR_VERSION_PLACEHOLDER);
+        let dependencies = metadata.dependencies_of("examplemod");
+        assert_eq!(dependencies[0].kind(), DependencyKind::Required);
+        assert_eq!(dependencies[1].kind(), DependencyKind::Optional);
+        assert_eq!(dependencies[1].side, Side::Client);
+        assert_eq!(dependencies[1].version_range, "[15.2,)");
+        assert!(metadata.dependencies_of("other").is_empty());
+        assert!(metadata.extra.contains_key("issueTrackerURL"));
+    }
+
+    #[test]
+    fn parses_neoforge_dependency_types() {
+        let metadata = ModsToml::from_toml(
+            r#"
+modLoader = "javafml"
+loaderVersion = "[4,)"
+license = "MIT"
+[[mods]]
+modId = "neomod"
+[[dependencies.neomod]]
+modId = "badmod"
+type = "incompatible"
+versionRange = "*"
+[[dependencies.neomod]]
+modId = "neoforge"
+type = "REQUIRED"
+versionRange = "[20.4,)"
+[[dependencies.neomod]]
+modId = "jei"
+type = "Optional"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(metadata.mods[0].version, "1");
+        let kinds: Vec<DependencyKind> = metadata
+            .dependencies_of("neomod")
+            .iter()
+            .map(Dependency::kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                DependencyKind::Incompatible,
+                DependencyKind::Required,
+                DependencyKind::Optional
+            ]
+        );
+        assert!(ModsToml::from_toml(
+            "modLoader = \"javafml\"\nloaderVersion = \"[4,)\"\nlicense = \"MIT\"\n[[dependencies.a]]\nmodId = \"b\"\ntype = \"sometimes\"\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reads_metadata_from_jar_and_resolves_jar_version() {
+        let dir = tempdir().unwrap();
+        let jar = dir.path().join("examplemod.jar");
+        let mut writer = zip::ZipWriter::new(File::create(&jar).unwrap());
+        writer
+            .start_file("META-INF/MANIFEST.MF", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .write_all(b"Manifest-Version: 1.0\r\nImplementation-Version: 1.4.2\r\n")
+            .unwrap();
+        writer
+            .start_file("META-INF/mods.toml", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(MODS_TOML.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let metadata = read_mods_toml(&jar).unwrap().unwrap();
+        assert_eq!(metadata.mods[0].version, "1.4.2");
+
+        let plain = dir.path().join("plain.jar");
+        zip::ZipWriter::new(File::create(&plain).unwrap())
+            .finish()
+            .unwrap();
+        assert!(read_mods_toml(&plain).unwrap().is_none());
+    }
+}
@@ -0,0 +1,382 @@
+use super::mods_toml::{DependencyKind, ModsToml, Side};
+use super::version_range::MavenRange;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// A dependency of a scanned mod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModDependency {
+    /// The id of the mod depended on.
+    pub mod_id: String,
+    /// How the dependency relates to the depending mod.
+    pub kind: DependencyKind,
+    /// Accepted versions, as a Maven range.
+    pub version_range: String,
+    /// The side the dependency applies to.
+    pub side: Side,
+}
+
+/// A mod found in an instance, independent of the loader's metadata format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedMod {
+    /// The jar providing the mod.
+    pub path: PathBuf,
+    /// The mod id.
+    pub mod_id: String,
+    /// The mod version.
+    pub version: String,
+    /// The side the mod runs on.
+    pub side: Side,
+    /// The declared dependencies.
+    pub dependencies: Vec<ModDependency>,
+}
+
+impl ScannedMod {
+    /// Creates the mods declared by a `mods.toml` read from `path`.
+    pub fn from_mods_toml(path: &Path, metadata: &ModsToml) -> Vec<Self> {
+        metadata
+            .mods
+            .iter()
+            .map(|entry| Self {
+                path: path.to_path_buf(),
+                mod_id: entry.mod_id.clone(),
+                version: entry.version.clone(),
+                side: Side::Both,
+                dependencies: metadata
+                    .dependencies_of(&entry.mod_id)
+                    .iter()
+                    .map(|d| ModDependency {
+                        mod_id: d.mod_id.clone(),
+                        kind: d.kind(),
+                        version_range: d.version_range.clone(),
+                        side: d.side,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// The game and loader the mods are checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveContext {
+    /// The Minecraft version, provided as mod `minecraft`.
+    pub game_version: String,
+    /// The loader mod id, e.g. `forge`, `neoforge` or `fabricloader`.
+    pub loader_id: String,
+    /// The loader version.
+    pub loader_version: String,
+    /// Whether the mods are checked for a client or a dedicated server.
+    pub side: Side,
+}
+
+impl ResolveContext {
+    /// Creates a context for a client instance.
+    pub fn new(
+        game_version: impl Into<String>,
+        loader_id: impl Into<String>,
+        loader_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            game_version: game_version.into(),
+            loader_id: loader_id.into(),
+            loader_version: loader_version.into(),
+            side: Side::Client,
+        }
+    }
+
+    /// Sets the side the mods run on.
+    pub fn with_side(mut self, side: Side) -> Self {
+        self.side = side;
+        self
+    }
+}
+
+/// A problem found by [`check_dependencies`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyProblem {
+    /// A required dependency is not installed.
+    Missing {
+        /// The depending mod.
+        mod_id: String,
+        /// The missing mod.
+        dependency: String,
+        /// The accepted versions.
+        version_range: String,
+    },
+    /// A dependency is installed in a version outside the accepted range.
+    VersionMismatch {
+        /// The depending mod.
+        mod_id: String,
+        /// The dependency.
+        dependency: String,
+        /// The accepted versions.
+        version_range: String,
+        /// The installed version.
+        found: String,
+    },
+    /// A mod declared incompatible or discouraged is installed.
+    Incompatible {
+        /// The depending mod.
+        mod_id: String,
+        /// The conflicting mod.
+        dependency: String,
+        /// The installed version of the conflicting mod.
+        found: String,
+        /// `false` if the game only warns about the combination.
+        fatal: bool,
+    },
+    /// Several jars provide the same mod id.
+    Duplicate {
+        /// The mod id.
+        mod_id: String,
+        /// The jars providing it.
+        paths: Vec<PathBuf>,
+    },
+    /// A mod does not run on the side being checked.
+    WrongSide {
+        /// The mod.
+        mod_id: String,
+        /// The side the mod runs on.
+        side: Side,
+    },
+}
+
+impl DependencyProblem {
+    /// Returns `true` if the problem prevents the game from starting.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            DependencyProblem::Incompatible { fatal: false, .. }
+                | DependencyProblem::WrongSide { .. }
+        )
+    }
+}
+
+/// The result of [`check_dependencies`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyReport {
+    /// All problems: duplicates sorted by mod id, then the problems of each
+    /// mod in the order the mods were given.
+    pub problems: Vec<DependencyProblem>,
+}
+
+impl DependencyReport {
+    /// Returns `true` if no problem prevents the game from starting.
+    pub fn can_launch(&self) -> bool {
+        !self.problems.iter().any(DependencyProblem::is_fatal)
+    }
+}
+
+/// Checks the dependencies of a set of mods before launch.
+///
+/// The game and loader count as installed mods with the ids `minecraft` and
+/// `context.loader_id`. Dependencies declared for the other side are ignored.
+/// Unparsable version ranges accept any version.
+///
+/// # Arguments
+///
+/// * `mods` - The mods of the instance.
+/// * `context` - The game version, loader and side.
+///
+/// # Returns
+///
+/// * `DependencyReport` - Missing, mismatched, incompatible, duplicate and
+///   wrong-side mods.
+pub fn check_dependencies(mods: &[ScannedMod], context: &ResolveContext) -> DependencyReport {
+    let mut report = DependencyReport::default();
+
+    let mut by_id: BTreeMap<&str, Vec<&ScannedMod>> = BTreeMap::new();
+    for scanned in mods {
+        by_id.entry(&scanned.mod_id).or_default().push(scanned);
+    }
+    for (mod_id, providers) in &by_id {
+        if providers.len() > 1 {
+            report.problems.push(DependencyProblem::Duplicate {
+                mod_id: mod_id.to_string(),
+                paths: providers.iter().map(|m| m.path.clone()).collect(),
+            });
+        }
+    }
+
+    let mut installed: HashMap<&str, &str> = by_id
+        .iter()
+        .map(|(id, providers)| (*id, providers[0].version.as_str()))
+        .collect();
+    installed.insert("minecraft", &context.game_version);
+    installed.insert(&context.loader_id, &context.loader_version);
+
+    for scanned in mods {
+        if !applies(scanned.side, context.side) {
+            report.problems.push(DependencyProblem::WrongSide {
+                mod_id: scanned.mod_id.clone(),
+                side: scanned.side,
+            });
+            continue;
+        }
+        for dependency in &scanned.dependencies {
+            if !applies(dependency.side, context.side) {
+                continue;
+            }
+            if let Some(problem) = check_dependency(scanned, dependency, &installed) {
+                report.problems.push(problem);
+            }
+        }
+    }
+    report
+}
+
+fn check_dependency(
+    scanned: &ScannedMod,
+    dependency: &ModDependency,
+    installed: &HashMap<&str, &str>,
+) -> Option<DependencyProblem> {
+    let range = dependency
+        .version_range
+        .parse()
+        .unwrap_or_else(|_| MavenRange::any());
+    let found = installed.get(dependency.mod_id.as_str());
+    match (dependency.kind, found) {
+        (DependencyKind::Required, None) => Some(DependencyProblem::Missing {
+            mod_id: scanned.mod_id.clone(),
+            dependency: dependency.mod_id.clone(),
+            version_range: dependency.version_range.clone(),
+        }),
+        (DependencyKind::Required | DependencyKind::Optional, Some(found))
+            if !range.contains(found) =>
+        {
+            Some(DependencyProblem::VersionMismatch {
+                mod_id: scanned.mod_id.clone(),
+                dependency: dependency.mod_id.clone(),
+                version_range: dependency.version_range.clone(),
+                found: found.to_string(),
+            })
+        }
+        (kind @ (DependencyKind::Incompatible | DependencyKind::Discouraged), Some(found))
+            if range.contains(found) =>
+        {
+            Some(DependencyProblem::Incompatible {
+                mod_id: scanned.mod_id.clone(),
+                dependency: dependency.mod_id.clone(),
+                found: found.to_string(),
+                fatal: kind == DependencyKind::Incompatible,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn applies(side: Side, target: Side) -> bool {
+    side == Side::Both || target == Side::Both || side == target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanned(
+        id: &str,
+        version: &str,
+        dependencies: &[(&str, DependencyKind, &str)],
+    ) -> ScannedMod {
+        ScannedMod {
+            path: PathBuf::from(format!("mods/{}-{}.jar", id, version)),
+            mod_id: id.to_string(),
+            version: version.to_string(),
+            side: Side::Both,
+            dependencies: dependencies
+                .iter()
+                .map(|(id, kind, range)| ModDependency {
+                    mod_id: id.to_string(),
+                    kind: *kind,
+                    version_range: range.to_string(),
+                    side: Side::Both,
+                })
+                .collect(),
+        }
+    }
+
+    fn context() -> ResolveContext {
+        ResolveContext::new("1.20.1", "forge", "47.3.0")
+    }
+
+    #[test]
+    fn reports_missing_mismatched_and_incompatible_dependencies() {
+        let mods = [
+            scanned(
+                "create",
+                "0.5.1",
+                &[
+                    ("forge", DependencyKind::Required, "[47,)"),
+                    ("minecraft", DependencyKind::Required, "[1.20.1,1.21)"),
+                    ("flywheel", DependencyKind::Required, "[0.6.10,0.6.11)"),
+                    ("jei", DependencyKind::Optional, "[15,)"),
+                ],
+            ),
+            scanned(
+                "sodiumlike",
+                "1.0",
+                &[("optifine", DependencyKind::Incompatible, "*")],
+            ),
+            scanned("jei", "14.0.0", &[]),
+            scanned("optifine", "HD_U_I6", &[]),
+        ];
+
+        let report = check_dependencies(&mods, &context());
+
+        assert_eq!(
+            report.problems,
+            vec![
+                DependencyProblem::Missing {
+                    mod_id: "create".to_string(),
+                    dependency: "flywheel".to_string(),
+                    version_range: "[0.6.10,0.6.11)".to_string(),
+                },
+                DependencyProblem::VersionMismatch {
+                    mod_id: "create".to_string(),
+                    dependency: "jei".to_string(),
+                    version_range: "[15,)".to_string(),
+                    found: "14.0.0".to_string(),
+                },
+                DependencyProblem::Incompatible {
+                    mod_id: "sodiumlike".to_string(),
+                    dependency: "optifine".to_string(),
+                    found: "HD_U_I6".to_string(),
+                    fatal: true,
+                },
+            ]
+        );
+        assert!(!report.can_launch());
+    }
+
+    #[test]
+    fn reports_duplicates_and_side_mismatches() {
+        let mut client_only = scanned("minimap", "2.0", &[]);
+        client_only.side = Side::Client;
+        let mut client_dependency =
+            scanned("tooltips", "1.0", &[("jei", DependencyKind::Required, "")]);
+        client_dependency.dependencies[0].side = Side::Client;
+        let mods = [
+            scanned("jei", "15.2.0", &[]),
+            scanned("jei", "15.3.0", &[]),
+            client_only,
+            client_dependency,
+        ];
+
+        let server = check_dependencies(&mods[2..], &context().with_side(Side::Server));
+        assert_eq!(
+            server.problems,
+            vec![DependencyProblem::WrongSide {
+                mod_id: "minimap".to_string(),
+                side: Side::Client,
+            }]
+        );
+        assert!(server.can_launch());
+
+        let client = check_dependencies(&mods, &context());
+        assert!(matches!(
+            &client.problems[..],
+            [DependencyProblem::Duplicate { mod_id, paths }] if mod_id == "jei" && paths.len() == 2
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::cache::{CacheError, SharedStore};
+use crate::instance::{InstanceLock, InstanceLockError};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Suffix appended to the file name of a disabled mod, which loaders skip.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// Error returned when a mod cannot be enabled or disabled.
+#[derive(Debug, Error)]
+pub enum ModToggleError {
+    #[error("failed to rename mod: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("instance {0} is running")]
+    InstanceRunning(PathBuf),
+    #[error("mod not found: {0}")]
+    NotFound(PathBuf),
+    #[error("both {0} and its disabled copy exist")]
+    BothExist(PathBuf),
+}
+
+/// Returns whether a mod file is enabled, i.e. does not end in
+/// [`DISABLED_SUFFIX`].
+pub fn is_mod_enabled(path: &Path) -> bool {
+    !path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(DISABLED_SUFFIX))
+}
+
+/// Returns the enabled and the disabled path of a mod, given either.
+fn mod_paths(path: &Path) -> (PathBuf, PathBuf) {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    match name.strip_suffix(DISABLED_SUFFIX) {
+        Some(enabled) => (path.with_file_name(enabled), path.to_path_buf()),
+        None => {
+            let mut disabled = OsString::from(path.as_os_str());
+            disabled.push(DISABLED_SUFFIX);
+            (path.to_path_buf(), PathBuf::from(disabled))
+        }
+    }
+}
+
+/// Enables or disables a mod by renaming it between `name.jar` and
+/// `name.jar.disabled`.
+///
+/// The jar is expected in the mods folder of an instance, and the folder
+/// above it is locked with an [`InstanceLock`] during the rename: a running
+/// game has loaded its mods, and renaming them under it changes nothing
+/// until the next launch or breaks classes loaded later. The rename stays
+/// within the folder, so it is atomic.
+///
+/// # Arguments
+///
+/// * `jar_path` - The mod, enabled or disabled.
+/// * `enabled` - Whether the mod should be loaded.
+///
+/// # Returns
+///
+/// * `PathBuf` - The new path of the mod; unchanged if it already was in
+///   the requested state.
+///
+/// # Errors
+///
+/// Returns [`ModToggleError::InstanceRunning`] if the instance is locked by
+/// a running game, [`ModToggleError::NotFound`] if neither file exists, or
+/// [`ModToggleError::BothExist`] if both do, since renaming would replace
+/// one of them.
+pub fn set_mod_enabled(jar_path: &Path, enabled: bool) -> Result<PathBuf, ModToggleError> {
+    let (enabled_path, disabled_path) = mod_paths(jar_path);
+    let (from, to) = if enabled {
+        (disabled_path, enabled_path)
+    } else {
+        (enabled_path, disabled_path)
+    };
+    // Holding the lock keeps the instance from being launched during the
+    // rename.
+    let _lock = match from.parent().and_then(Path::parent) {
+        Some(instance_dir) => Some(InstanceLock::acquire(instance_dir).map_err(|e| match e {
+            InstanceLockError::Running(dir) => ModToggleError::InstanceRunning(dir),
+            InstanceLockError::Io(e) => ModToggleError::Io(e),
+        })?),
+        None => None,
+    };
+    match (from.is_file(), to.is_file()) {
+        (true, true) => return Err(ModToggleError::BothExist(from)),
+        (false, true) => return Ok(to),
+        (false, false) => return Err(ModToggleError::NotFound(jar_path.to_path_buf())),
+        (true, false) => {}
+    }
+    fs::rename(&from, &to)?;
+    Ok(to)
+}
+
+impl SharedStore {
+    /// Enables or disables a mod linked from the store, moving the
+    /// instance's reference along so the object is not collected.
+    ///
+    /// See [`set_mod_enabled`].
+    ///
+    /// # Arguments
+    ///
+    /// * `instance` - The id of the instance the mod was linked into.
+    /// * `jar_path` - The mod, as passed to [`SharedStore::link`], enabled
+    ///   or disabled.
+    /// * `enabled` - Whether the mod should be loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mod cannot be renamed or the reference cannot
+    /// be written.
+    pub fn set_mod_enabled(
+        &self,
+        instance: &str,
+        jar_path: &Path,
+        enabled: bool,
+    ) -> Result<PathBuf, ModToggleError> {
+        let path = set_mod_enabled(jar_path, enabled)?;
+        let (enabled_path, disabled_path) = mod_paths(&path);
+        let from = if enabled { disabled_path } else { enabled_path };
+        self.move_reference(instance, &from, &path)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn renames_mods_unless_the_instance_runs() {
+        let dir = tempdir().unwrap();
+        let instance = dir.path().join("instance");
+        let mods = instance.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        let jar = mods.join("sodium.jar");
+        let disabled = mods.join("sodium.jar.disabled");
+        fs::write(&jar, b"jar").unwrap();
+
+        assert_eq!(set_mod_enabled(&jar, false).unwrap(), disabled);
+        assert!(!jar.exists() && !is_mod_enabled(&disabled));
+        assert_eq!(set_mod_enabled(&jar, false).unwrap(), disabled);
+
+        let lock = InstanceLock::acquire(&instance).unwrap();
+        assert!(matches!(
+            set_mod_enabled(&disabled, true),
+            Err(ModToggleError::InstanceRunning(path)) if path == instance
+        ));
+        drop(lock);
+        assert_eq!(set_mod_enabled(&disabled, true).unwrap(), jar);
+
+        fs::write(&disabled, b"old").unwrap();
+        assert!(matches!(
+            set_mod_enabled(&jar, false),
+            Err(ModToggleError::BothExist(_))
+        ));
+        assert!(matches!(
+            set_mod_enabled(&mods.join("missing.jar"), true),
+            Err(ModToggleError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn moves_the_store_reference() {
+        let dir = tempdir().unwrap();
+        let store = SharedStore::new(dir.path().join("store"));
+        let sha1 = store.insert_bytes(b"jar", None).unwrap();
+        let jar = dir.path().join("instance/mods/lithium.jar");
+        store.link("a", &sha1, &jar).unwrap();
+
+        let disabled = store.set_mod_enabled("a", &jar, false).unwrap();
+        assert_eq!(store.references("a").unwrap()[&disabled], sha1);
+        assert_eq!(store.collect_garbage().unwrap().removed, 0);
+        assert!(store.contains(&sha1));
+    }
+}
@@ -0,0 +1,686 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Error returned when a version range cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VersionRangeError {
+    #[error("invalid version range {0:?}")]
+    Invalid(String),
+}
+
+/// Compares two version strings the way Maven does.
+///
+/// Versions are split into numeric and textual parts at `.`, `-` and at changes
+/// between digits and letters. Numbers compare numerically and sort after any
+/// qualifier. Pre-release qualifiers (`alpha`, `beta`, `milestone`, `rc`,
+/// `snapshot`) sort before the release, `release`, `ga` and `final` equal it,
+/// and other qualifiers sort after it, so
+/// `1.0-beta < 1.0 = 1.0.RELEASE < 1.0-forge < 1.0.1`. Missing parts count as
+/// zero next to a number and as the release next to a qualifier.
+///
+/// # Arguments
+///
+/// * `a` - The first version.
+/// * `b` - The second version.
+///
+/// # Returns
+///
+/// * `Ordering` - How `a` compares to `b`.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a = tokenize(a);
+    let b = tokenize(b);
+    for i in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(i), b.get(i)) {
+            (Some(left), Some(right)) => left.cmp_token(right),
+            (Some(left), None) => left.cmp_missing(),
+            (None, Some(right)) => right.cmp_missing().reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    Qualifier(String),
+}
+
+/// Rank of the release qualifier, to which `release`, `ga` and `final` are
+/// normalised.
+const RELEASE_RANK: u8 = 5;
+
+impl Token {
+    fn cmp_token(&self, other: &Token) -> Ordering {
+        match (self, other) {
+            (Token::Number(a), Token::Number(b)) => a.cmp(b),
+            (Token::Number(_), Token::Qualifier(_)) => Ordering::Greater,
+            (Token::Qualifier(_), Token::Number(_)) => Ordering::Less,
+            (Token::Qualifier(a), Token::Qualifier(b)) => qualifier_rank(a)
+                .cmp(&qualifier_rank(b))
+                .then_with(|| a.cmp(b)),
+        }
+    }
+
+    /// Compares the token with a part the other version does not have.
+    fn cmp_missing(&self) -> Ordering {
+        match self {
+            Token::Number(n) => n.cmp(&0),
+            Token::Qualifier(q) => qualifier_rank(q).cmp(&RELEASE_RANK),
+        }
+    }
+}
+
+/// Rank of a qualifier: pre-releases, then the release, then `sp` and
+/// unknown qualifiers.
+fn qualifier_rank(qualifier: &str) -> u8 {
+    match qualifier {
+        "alpha" | "a" => 0,
+        "beta" | "b" => 1,
+        "milestone" | "m" => 2,
+        "rc" | "cr" | "pre" => 3,
+        "snapshot" => 4,
+        "" => RELEASE_RANK,
+        "sp" => 6,
+        _ => 7,
+    }
+}
+
+fn tokenize(version: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for part in version.split(['.', '-', '+', '_']) {
+        let mut rest = part;
+        while !rest.is_empty() {
+            let digits = rest.starts_with(|c: char| c.is_ascii_digit());
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != digits)
+                .unwrap_or(rest.len());
+            let (piece, tail) = rest.split_at(end);
+            tokens.push(match piece.parse() {
+                Ok(number) if digits => Token::Number(number),
+                _ => match piece.to_ascii_lowercase().as_str() {
+                    "release" | "ga" | "final" => Token::Qualifier(String::new()),
+                    qualifier => Token::Qualifier(qualifier.to_string()),
+                },
+            });
+            rest = tail;
+        }
+    }
+    // Trailing zeros and release qualifiers do not change a version:
+    // 1.0 == 1 == 1.0.RELEASE.
+    while tokens
+        .last()
+        .is_some_and(|t| t.cmp_missing() == Ordering::Equal)
+    {
+        tokens.pop();
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bound {
+    version: String,
+    inclusive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Interval {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+impl Interval {
+    fn contains(&self, version: &str) -> bool {
+        let above = self.lower.as_ref().is_none_or(|b| {
+            let ordering = compare_versions(version, &b.version);
+            ordering == Ordering::Greater || (b.inclusive && ordering == Ordering::Equal)
+        });
+        let below = self.upper.as_ref().is_none_or(|b| {
+            let ordering = compare_versions(version, &b.version);
+            ordering == Ordering::Less || (b.inclusive && ordering == Ordering::Equal)
+        });
+        above && below
+    }
+}
+
+/// A Maven version range such as `[1.0,2.0)`, `[47,)` or `(,1.0],[1.2,)`.
+///
+/// As in Maven, a bare version (`1.0`) is only a recommendation and accepts any
+/// version, as do an empty range and `*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MavenRange {
+    source: String,
+    intervals: Vec<Interval>,
+}
+
+impl MavenRange {
+    /// A range accepting every version.
+    pub fn any() -> Self {
+        Self {
+            source: String::new(),
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the range accepts every version.
+    pub fn is_any(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns `true` if `version` lies in the range.
+    pub fn contains(&self, version: &str) -> bool {
+        self.is_any() || self.intervals.iter().any(|i| i.contains(version))
+    }
+}
+
+impl FromStr for MavenRange {
+    type Err = VersionRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || VersionRangeError::Invalid(s.to_string());
+        let trimmed = s.trim();
+        if trimmed.is_empty() || trimmed == "*" {
+            return Ok(Self::any());
+        }
+        if !trimmed.starts_with(['[', '(']) {
+            if trimmed.contains([',', ']', ')']) {
+                return Err(invalid());
+            }
+            return Ok(Self {
+                source: trimmed.to_string(),
+                intervals: Vec::new(),
+            });
+        }
+
+        let mut intervals = Vec::new();
+        let mut rest = trimmed;
+        while !rest.is_empty() {
+            let lower_inclusive = match rest.as_bytes()[0] {
+                b'[' => true,
+                b'(' => false,
+                _ => return Err(invalid()),
+            };
+            let end = rest.find([']', ')']).ok_or_else(invalid)?;
+            let upper_inclusive = rest.as_bytes()[end] == b']';
+            let body = &rest[1..end];
+            let interval = match body.split_once(',') {
+                Some((lower, upper)) => {
+                    let bound = |version: &str, inclusive| {
+                        let version = version.trim();
+                        (!version.is_empty()).then(|| Bound {
+                            version: version.to_string(),
+                            inclusive,
+                        })
+                    };
+                    Interval {
+                        lower: bound(lower, lower_inclusive),
+                        upper: bound(upper, upper_inclusive),
+                    }
+                }
+                // `[1.0]` pins an exact version.
+                None if lower_inclusive && upper_inclusive && !body.trim().is_empty() => {
+                    let exact = Bound {
+                        version: body.trim().to_string(),
+                        inclusive: true,
+                    };
+                    Interval {
+                        lower: Some(exact.clone()),
+                        upper: Some(exact),
+                    }
+                }
+                None => return Err(invalid()),
+            };
+            intervals.push(interval);
+            rest = rest[end + 1..].trim_start();
+            if let Some(next) = rest.strip_prefix(',') {
+                rest = next.trim_start();
+                if rest.is_empty() {
+                    return Err(invalid());
+                }
+            } else if !rest.is_empty() {
+                return Err(invalid());
+            }
+        }
+        Ok(Self {
+            source: trimmed.to_string(),
+            intervals,
+        })
+    }
+}
+
+impl fmt::Display for MavenRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.source.is_empty() {
+            f.write_str("*")
+        } else {
+            f.write_str(&self.source)
+        }
+    }
+}
+
+/// Compares two semantic versions the way Fabric Loader does.
+///
+/// Build metadata after `+` is ignored. The part after the first `-` is a
+/// pre-release, which sorts before the release; pre-releases compare by their
+/// dot-separated identifiers, numbers numerically and before text. The
+/// version core compares like [`compare_versions`], so `1.0 == 1.0.0`.
+///
+/// # Arguments
+///
+/// * `a` - The first version.
+/// * `b` - The second version.
+///
+/// # Returns
+///
+/// * `Ordering` - How `a` compares to `b`.
+pub fn compare_semver(a: &str, b: &str) -> Ordering {
+    let (a_core, a_pre) = split_semver(a);
+    let (b_core, b_pre) = split_semver(b);
+    compare_versions(a_core, b_core).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_pre_release(a, b),
+    })
+}
+
+/// Splits a semantic version into its core and pre-release, dropping build
+/// metadata.
+fn split_semver(version: &str) -> (&str, Option<&str>) {
+    let version = version.split('+').next().unwrap_or_default().trim();
+    match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    }
+}
+
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => x.cmp(y),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: String,
+}
+
+impl Comparator {
+    fn new(op: Op, version: impl Into<String>) -> Self {
+        Self {
+            op,
+            version: version.into(),
+        }
+    }
+
+    fn matches(&self, version: &str) -> bool {
+        let ordering = compare_semver(version, &self.version);
+        match self.op {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// A Fabric version constraint such as `>=0.14.0 <0.15`, `^1.2.0`, `~1.20.1`
+/// or `1.20.x`.
+///
+/// Space-separated predicates must all hold, and alternatives separated by
+/// `||` (or given as the entries of a `fabric.mod.json` array) are tried in
+/// turn. As in Fabric Loader, `^` keeps the major version, even when it is
+/// `0`, and `~` keeps the major and minor versions. A bare version must match
+/// exactly, while `*` and an empty constraint accept every version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FabricRange {
+    source: String,
+    alternatives: Vec<Vec<Comparator>>,
+}
+
+impl FabricRange {
+    /// A range accepting every version.
+    pub fn any() -> Self {
+        Self {
+            source: String::new(),
+            alternatives: vec![Vec::new()],
+        }
+    }
+
+    /// Parses the entries of a `fabric.mod.json` dependency array, any of
+    /// which may match.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VersionRangeError::Invalid`] if an entry cannot be parsed.
+    pub fn any_of<I, S>(constraints: I) -> Result<Self, VersionRangeError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut range = Self {
+            source: String::new(),
+            alternatives: Vec::new(),
+        };
+        for constraint in constraints {
+            let parsed: FabricRange = constraint.as_ref().parse()?;
+            if !range.source.is_empty() {
+                range.source.push_str(" || ");
+            }
+            range.source.push_str(&parsed.to_string());
+            range.alternatives.extend(parsed.alternatives);
+        }
+        // An empty array accepts nothing in Fabric Loader.
+        Ok(range)
+    }
+
+    /// Returns `true` if the range accepts every version.
+    pub fn is_any(&self) -> bool {
+        self.alternatives.iter().any(Vec::is_empty)
+    }
+
+    /// Returns `true` if `version` satisfies the range.
+    pub fn contains(&self, version: &str) -> bool {
+        self.alternatives
+            .iter()
+            .any(|all| all.iter().all(|c| c.matches(version)))
+    }
+}
+
+/// Parses one predicate of a Fabric constraint into comparators.
+fn parse_predicate(predicate: &str) -> Option<Vec<Comparator>> {
+    let (op, version) = [">=", "<=", ">", "<", "=", "^", "~"]
+        .into_iter()
+        .find_map(|op| Some((op, predicate.strip_prefix(op)?)))
+        .unwrap_or(("", predicate));
+    let version = version.trim();
+    let (core, _) = split_semver(version);
+    let parts: Vec<&str> = core.split('.').collect();
+    if let Some(wildcard) = parts.iter().position(|p| matches!(*p, "x" | "X" | "*")) {
+        // `1.20.x` accepts 1.20 and everything up to 1.21.
+        if !matches!(op, "" | "=") || core != version {
+            return None;
+        }
+        if wildcard == 0 {
+            return Some(Vec::new());
+        }
+        let fixed = &parts[..wildcard];
+        return Some(vec![
+            Comparator::new(Op::Ge, fixed.join(".")),
+            Comparator::new(Op::Lt, bump(fixed)?),
+        ]);
+    }
+    if parts.iter().any(|p| p.parse::<u64>().is_err()) {
+        return None;
+    }
+    let comparators = match op {
+        ">=" => vec![Comparator::new(Op::Ge, version)],
+        "<=" => vec![Comparator::new(Op::Le, version)],
+        ">" => vec![Comparator::new(Op::Gt, version)],
+        "<" => vec![Comparator::new(Op::Lt, version)],
+        "^" => vec![
+            Comparator::new(Op::Ge, version),
+            Comparator::new(Op::Lt, bump(&parts[..1])?),
+        ],
+        "~" => vec![
+            Comparator::new(Op::Ge, version),
+            Comparator::new(Op::Lt, bump(&parts[..parts.len().min(2)])?),
+        ],
+        _ => vec![Comparator::new(Op::Eq, version)],
+    };
+    Some(comparators)
+}
+
+/// Increments the last of `parts`: `[1, 20]` becomes `1.21`.
+fn bump(parts: &[&str]) -> Option<String> {
+    let (last, init) = parts.split_last()?;
+    let next = last.parse::<u64>().ok()? + 1;
+    Some(
+        init.iter()
+            .map(|p| p.to_string())
+            .chain([next.to_string()])
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+impl FromStr for FabricRange {
+    type Err = VersionRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() || trimmed == "*" {
+            return Ok(Self::any());
+        }
+        let alternatives = trimmed
+            .split("||")
+            .map(|alternative| {
+                let predicates: Vec<&str> = alternative.split_whitespace().collect();
+                if predicates.is_empty() {
+                    return None;
+                }
+                predicates.into_iter().try_fold(Vec::new(), |mut all, p| {
+                    all.extend(parse_predicate(p)?);
+                    Some(all)
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| VersionRangeError::Invalid(s.to_string()))?;
+        Ok(Self {
+            source: trimmed.to_string(),
+            alternatives,
+        })
+    }
+}
+
+impl fmt::Display for FabricRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.source.is_empty() && self.is_any() {
+            f.write_str("*")
+        } else {
+            f.write_str(&self.source)
+        }
+    }
+}
+
+/// A version requirement in the syntax of either mod loader family: Maven
+/// ranges for Forge and NeoForge, Fabric constraints for Fabric and Quilt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionReq {
+    Maven(MavenRange),
+    Fabric(FabricRange),
+}
+
+impl VersionReq {
+    /// Parses a Maven version range, e.g. from a `mods.toml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VersionRangeError::Invalid`] if the range cannot be parsed.
+    pub fn parse_maven(range: &str) -> Result<Self, VersionRangeError> {
+        range.parse().map(VersionReq::Maven)
+    }
+
+    /// Parses a Fabric version constraint, e.g. from a `fabric.mod.json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VersionRangeError::Invalid`] if the constraint cannot be parsed.
+    pub fn parse_fabric(constraint: &str) -> Result<Self, VersionRangeError> {
+        constraint.parse().map(VersionReq::Fabric)
+    }
+
+    /// Returns `true` if the requirement accepts every version.
+    pub fn is_any(&self) -> bool {
+        match self {
+            VersionReq::Maven(range) => range.is_any(),
+            VersionReq::Fabric(range) => range.is_any(),
+        }
+    }
+
+    /// Returns `true` if `version` satisfies the requirement.
+    pub fn matches(&self, version: &str) -> bool {
+        match self {
+            VersionReq::Maven(range) => range.contains(version),
+            VersionReq::Fabric(range) => range.contains(version),
+        }
+    }
+
+    /// Compares two versions with the ordering of the requirement's syntax:
+    /// [`compare_versions`] for Maven and [`compare_semver`] for Fabric.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            VersionReq::Maven(_) => compare_versions(a, b),
+            VersionReq::Fabric(_) => compare_semver(a, b),
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionReq::Maven(range) => range.fmt(f),
+            VersionReq::Fabric(range) => range.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions_like_maven() {
+        assert_eq!(compare_versions("1.0", "1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.1", "1.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0-beta", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-alpha2", "1.0-beta1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-rc1", "1.0-rc2"), Ordering::Less);
+        assert_eq!(compare_versions("47.3.0", "47.2.20"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0-forge", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-forge", "1.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.RELEASE", "1.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0-ga", "1.0-final"), Ordering::Equal);
+        assert_eq!(
+            compare_versions("1.0-snapshot", "1.0.RELEASE"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn parses_and_matches_ranges() {
+        let range: MavenRange = "[1.0,2.0)".parse().unwrap();
+        assert!(range.contains("1.0"));
+        assert!(range.contains("1.9.9"));
+        assert!(!range.contains("2.0"));
+        assert!(!range.contains("0.9"));
+
+        let open: MavenRange = "[47,)".parse().unwrap();
+        assert!(open.contains("47.3.0"));
+        assert!(!open.contains("46.0.14"));
+
+        let exact: MavenRange = "[1.20.1]".parse().unwrap();
+        assert!(exact.contains("1.20.1"));
+        assert!(!exact.contains("1.20.2"));
+
+        let union: MavenRange = "(,1.0],[1.2,)".parse().unwrap();
+        assert!(union.contains("0.5"));
+        assert!(!union.contains("1.1"));
+        assert!(union.contains("1.2"));
+
+        for any in ["", "*", "1.0"] {
+            assert!(
+                any.parse::<MavenRange>().unwrap().contains("0.1"),
+                "{:?}",
+                any
+            );
+        }
+        for invalid in ["[1.0", "[1.0,2.0),", "[]", "1.0,2.0"] {
+            assert!(invalid.parse::<MavenRange>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_and_matches_fabric_constraints() {
+        let range: FabricRange = ">=0.14.0 <0.15".parse().unwrap();
+        assert!(range.contains("0.14.21"));
+        assert!(range.contains("0.14.21+build.5"));
+        assert!(!range.contains("0.15.0"));
+        assert!(!range.contains("0.14.0-beta.2"));
+
+        let caret: FabricRange = "^0.92.0".parse().unwrap();
+        assert!(caret.contains("0.100.1"));
+        assert!(!caret.contains("1.0.0"));
+        let tilde: FabricRange = "~1.20.1".parse().unwrap();
+        assert!(tilde.contains("1.20.4"));
+        assert!(!tilde.contains("1.21"));
+        let wildcard: FabricRange = "1.20.x".parse().unwrap();
+        assert!(wildcard.contains("1.20"));
+        assert!(!wildcard.contains("1.19.4"));
+
+        let exact: FabricRange = "1.20.1".parse().unwrap();
+        assert!(exact.contains("1.20.1") && !exact.contains("1.20.2"));
+        let either = FabricRange::any_of(["1.20.1", "1.20.2"]).unwrap();
+        assert!(either.contains("1.20.2") && !either.contains("1.20.3"));
+        assert_eq!(either.to_string(), "1.20.1 || 1.20.2");
+        assert!("*".parse::<FabricRange>().unwrap().is_any());
+        for invalid in [">=abc", "^1.x", "1.0 ||"] {
+            assert!(invalid.parse::<FabricRange>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn unifies_maven_and_fabric_requirements() {
+        assert_eq!(
+            compare_semver("1.0.0-alpha.2", "1.0.0-alpha.10"),
+            Ordering::Less
+        );
+        assert_eq!(compare_semver("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_semver("1.0+a", "1.0.0+b"), Ordering::Equal);
+
+        let forge = VersionReq::parse_maven("[47,)").unwrap();
+        let fabric = VersionReq::parse_fabric(">=0.14.0").unwrap();
+        assert!(forge.matches("47.3.0") && !forge.matches("46.0.1"));
+        assert!(
+            !VersionReq::parse_maven("[2.3.1,)")
+                .unwrap()
+                .matches("2.3-mc1.20")
+        );
+        assert!(fabric.matches("0.16.9") && !fabric.matches("0.13.3"));
+        assert_eq!(fabric.to_string(), ">=0.14.0");
+        assert!(VersionReq::parse_maven("1.0").unwrap().is_any());
+        assert!(!VersionReq::parse_fabric("1.0").unwrap().is_any());
+    }
+}
@@ -0,0 +1,237 @@
+use super::{MAX_DEPTH, NbtCompound, NbtError, NbtValue};
+use std::io::{Read, Write};
+
+const TAG_END: u8 = 0;
+const TAG_COMPOUND: u8 = 10;
+
+/// Reads the named root tag of a document.
+pub(super) fn read_root<R: Read>(reader: &mut R) -> Result<(String, NbtValue), NbtError> {
+    let tag = read_u8(reader)?;
+    if tag != TAG_COMPOUND {
+        return Err(NbtError::RootNotCompound);
+    }
+    let name = read_string(reader)?;
+    let root = read_payload(reader, tag, 0)?;
+    Ok((name, root))
+}
+
+/// Writes `root` as the named root tag of a document.
+pub(super) fn write_root<W: Write>(
+    writer: &mut W,
+    name: &str,
+    root: &NbtValue,
+) -> Result<(), NbtError> {
+    if !matches!(root, NbtValue::Compound(_)) {
+        return Err(NbtError::RootNotCompound);
+    }
+    writer.write_all(&[TAG_COMPOUND])?;
+    write_string(writer, name)?;
+    write_payload(writer, root, 0)
+}
+
+fn read_payload<R: Read>(reader: &mut R, tag: u8, depth: usize) -> Result<NbtValue, NbtError> {
+    if depth > MAX_DEPTH {
+        return Err(NbtError::TooDeep);
+    }
+    Ok(match tag {
+        1 => NbtValue::Byte(read_u8(reader)? as i8),
+        2 => NbtValue::Short(i16::from_be_bytes(read_array(reader)?)),
+        3 => NbtValue::Int(i32::from_be_bytes(read_array(reader)?)),
+        4 => NbtValue::Long(i64::from_be_bytes(read_array(reader)?)),
+        5 => NbtValue::Float(f32::from_be_bytes(read_array(reader)?)),
+        6 => NbtValue::Double(f64::from_be_bytes(read_array(reader)?)),
+        7 => {
+            let len = read_len(reader)?;
+            // The length is untrusted, so only allocate what actually arrives.
+            let mut bytes = Vec::with_capacity(len.min(1024));
+            reader.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+            if bytes.len() != len {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            NbtValue::ByteArray(bytes.into_iter().map(|b| b as i8).collect())
+        }
+        8 => NbtValue::String(read_string(reader)?),
+        9 => {
+            let element = read_u8(reader)?;
+            let len = read_len(reader)?;
+            if element == TAG_END && len > 0 {
+                return Err(NbtError::InvalidTag(TAG_END));
+            }
+            // The length is untrusted, so grow the list as elements arrive.
+            let mut list = Vec::with_capacity(len.min(1024));
+            for _ in 0..len {
+                list.push(read_payload(reader, element, depth + 1)?);
+            }
+            NbtValue::List(list)
+        }
+        10 => {
+            let mut compound = NbtCompound::new();
+            loop {
+                let element = read_u8(reader)?;
+                if element == TAG_END {
+                    break;
+                }
+                let name = read_string(reader)?;
+                compound.insert(name, read_payload(reader, element, depth + 1)?);
+            }
+            NbtValue::Compound(compound)
+        }
+        11 => {
+            let len = read_len(reader)?;
+            let mut ints = Vec::with_capacity(len.min(1024));
+            for _ in 0..len {
+                ints.push(i32::from_be_bytes(read_array(reader)?));
+            }
+            NbtValue::IntArray(ints)
+        }
+        12 => {
+            let len = read_len(reader)?;
+            let mut longs = Vec::with_capacity(len.min(1024));
+            for _ in 0..len {
+                longs.push(i64::from_be_bytes(read_array(reader)?));
+            }
+            NbtValue::LongArray(longs)
+        }
+        other => return Err(NbtError::InvalidTag(other)),
+    })
+}
+
+fn write_payload<W: Write>(writer: &mut W, value: &NbtValue, depth: usize) -> Result<(), NbtError> {
+    if depth > MAX_DEPTH {
+        return Err(NbtError::TooDeep);
+    }
+    match value {
+        NbtValue::Byte(v) => writer.write_all(&v.to_be_bytes())?,
+        NbtValue::Short(v) => writer.write_all(&v.to_be_bytes())?,
+        NbtValue::Int(v) => writer.write_all(&v.to_be_bytes())?,
+        NbtValue::Long(v) => writer.write_all(&v.to_be_bytes())?,
+        NbtValue::Float(v) => writer.write_all(&v.to_be_bytes())?,
+        NbtValue::Double(v) => writer.write_all(&v.to_be_bytes())?,
+        NbtValue::ByteArray(bytes) => {
+            write_len(writer, bytes.len())?;
+            let bytes: Vec<u8> = bytes.iter().map(|b| *b as u8).collect();
+            writer.write_all(&bytes)?;
+        }
+        NbtValue::String(s) => write_string(writer, s)?,
+        NbtValue::List(list) => {
+            let element = list.first().map_or(TAG_END, NbtValue::tag_id);
+            if let Some(other) = list.iter().find(|v| v.tag_id() != element) {
+                return Err(NbtError::MixedList(list[0].type_name(), other.type_name()));
+            }
+            writer.write_all(&[element])?;
+            write_len(writer, list.len())?;
+            for item in list {
+                write_payload(writer, item, depth + 1)?;
+            }
+        }
+        NbtValue::Compound(compound) => {
+            for (name, item) in compound {
+                writer.write_all(&[item.tag_id()])?;
+                write_string(writer, name)?;
+                write_payload(writer, item, depth + 1)?;
+            }
+            writer.write_all(&[TAG_END])?;
+        }
+        NbtValue::IntArray(ints) => {
+            write_len(writer, ints.len())?;
+            for v in ints {
+                writer.write_all(&v.to_be_bytes())?;
+            }
+        }
+        NbtValue::LongArray(longs) => {
+            write_len(writer, longs.len())?;
+            for v in longs {
+                writer.write_all(&v.to_be_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, NbtError> {
+    Ok(read_array::<R, 1>(reader)?[0])
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], NbtError> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_len<R: Read>(reader: &mut R) -> Result<usize, NbtError> {
+    let len = i32::from_be_bytes(read_array(reader)?);
+    Ok(len.max(0) as usize)
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> Result<(), NbtError> {
+    let len = i32::try_from(len).map_err(|_| NbtError::Message("array too long".to_string()))?;
+    writer.write_all(&len.to_be_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, NbtError> {
+    let len = u16::from_be_bytes(read_array(reader)?) as usize;
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    decode_mutf8(&bytes)
+}
+
+fn write_string<W: Write>(writer: &mut W, s: &str) -> Result<(), NbtError> {
+    let bytes = encode_mutf8(s);
+    let len = u16::try_from(bytes.len())
+        .map_err(|_| NbtError::Message("string longer than 65535 bytes".to_string()))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Decodes Java's modified UTF-8: NUL is two bytes and characters outside the
+/// BMP are surrogate pairs of three bytes each.
+fn decode_mutf8(bytes: &[u8]) -> Result<String, NbtError> {
+    // Most strings are plain ASCII or BMP text, which both encodings share.
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Ok(s.to_string());
+    }
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let continuation = |j: usize| match bytes.get(j) {
+            Some(c) if c & 0xc0 == 0x80 => Ok(u16::from(c & 0x3f)),
+            _ => Err(NbtError::InvalidString),
+        };
+        let (unit, width) = if b & 0x80 == 0 && b != 0 {
+            (u16::from(b), 1)
+        } else if b & 0xe0 == 0xc0 {
+            ((u16::from(b & 0x1f) << 6) | continuation(i + 1)?, 2)
+        } else if b & 0xf0 == 0xe0 {
+            let unit =
+                (u16::from(b & 0x0f) << 12) | (continuation(i + 1)? << 6) | continuation(i + 2)?;
+            (unit, 3)
+        } else {
+            return Err(NbtError::InvalidString);
+        };
+        units.push(unit);
+        i += width;
+    }
+    String::from_utf16(&units).map_err(|_| NbtError::InvalidString)
+}
+
+fn encode_mutf8(s: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(s.len());
+    for unit in s.encode_utf16() {
+        match unit {
+            0x0001..=0x007f => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    bytes
+}
@@ -0,0 +1,215 @@
+use super::{NbtCompound, NbtError, NbtValue};
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::{Deserialize, forward_to_deserialize_any};
+use std::fmt;
+
+/// Newtype name under which [`NbtValue`] asks for typed arrays to be kept.
+pub(super) const VALUE_TOKEN: &str = "$__nbt_value";
+/// Map key marking a byte array handed to the [`NbtValue`] visitor, and the
+/// newtype name under which [`NbtValue`] serializes byte arrays.
+pub(super) const BYTE_ARRAY_TOKEN: &str = "$__nbt_byte_array";
+/// Like [`BYTE_ARRAY_TOKEN`], for int arrays.
+pub(super) const INT_ARRAY_TOKEN: &str = "$__nbt_int_array";
+/// Like [`BYTE_ARRAY_TOKEN`], for long arrays.
+pub(super) const LONG_ARRAY_TOKEN: &str = "$__nbt_long_array";
+
+/// Deserializes a Rust value from a tag tree.
+///
+/// Compounds map to structs and maps, lists and arrays to sequences, and byte
+/// tags also deserialize as `bool`. Enums are read from strings (unit variants)
+/// or single-entry compounds.
+///
+/// # Errors
+///
+/// Returns an error if the tree does not match the shape of `T`.
+pub fn from_value<T: DeserializeOwned>(value: NbtValue) -> Result<T, NbtError> {
+    T::deserialize(value)
+}
+
+impl<'de> IntoDeserializer<'de, NbtError> for NbtValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for NbtValue {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            NbtValue::Byte(v) => visitor.visit_i8(v),
+            NbtValue::Short(v) => visitor.visit_i16(v),
+            NbtValue::Int(v) => visitor.visit_i32(v),
+            NbtValue::Long(v) => visitor.visit_i64(v),
+            NbtValue::Float(v) => visitor.visit_f32(v),
+            NbtValue::Double(v) => visitor.visit_f64(v),
+            NbtValue::String(s) => visitor.visit_string(s),
+            NbtValue::ByteArray(v) => visitor.visit_seq(SeqDeserializer::new(v.into_iter())),
+            NbtValue::IntArray(v) => visitor.visit_seq(SeqDeserializer::new(v.into_iter())),
+            NbtValue::LongArray(v) => visitor.visit_seq(SeqDeserializer::new(v.into_iter())),
+            NbtValue::List(list) => visitor.visit_seq(SeqDeserializer::new(list.into_iter())),
+            NbtValue::Compound(compound) => {
+                visitor.visit_map(MapDeserializer::new(compound.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            NbtValue::Byte(v) => visitor.visit_bool(v != 0),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, NbtError> {
+        if name != VALUE_TOKEN {
+            return visitor.visit_newtype_struct(self);
+        }
+        let (token, elements): (&str, Vec<NbtValue>) = match self {
+            NbtValue::ByteArray(v) => (
+                BYTE_ARRAY_TOKEN,
+                v.into_iter().map(NbtValue::Byte).collect(),
+            ),
+            NbtValue::IntArray(v) => (INT_ARRAY_TOKEN, v.into_iter().map(NbtValue::Int).collect()),
+            NbtValue::LongArray(v) => (
+                LONG_ARRAY_TOKEN,
+                v.into_iter().map(NbtValue::Long).collect(),
+            ),
+            other => return other.deserialize_any(visitor),
+        };
+        visitor.visit_map(MapDeserializer::new(std::iter::once((
+            token,
+            NbtValue::List(elements),
+        ))))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, NbtError> {
+        match self {
+            NbtValue::String(s) => visitor.visit_enum(s.into_deserializer()),
+            NbtValue::Compound(compound) if compound.len() == 1 => {
+                let map = MapDeserializer::new(compound.into_iter());
+                visitor.visit_enum(MapAccessDeserializer::new(map))
+            }
+            other => Err(de::Error::invalid_type(
+                de::Unexpected::Other(other.type_name()),
+                &"a string or a compound with one entry",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> Deserialize<'de> for NbtValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = NbtValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an NBT value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<NbtValue, E> {
+        Ok(NbtValue::Byte(v.into()))
+    }
+
+    fn visit_i8<E>(self, v: i8) -> Result<NbtValue, E> {
+        Ok(NbtValue::Byte(v))
+    }
+
+    fn visit_i16<E>(self, v: i16) -> Result<NbtValue, E> {
+        Ok(NbtValue::Short(v))
+    }
+
+    fn visit_i32<E>(self, v: i32) -> Result<NbtValue, E> {
+        Ok(NbtValue::Int(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<NbtValue, E> {
+        Ok(NbtValue::Long(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<NbtValue, E> {
+        i64::try_from(v)
+            .map(NbtValue::Long)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<NbtValue, E> {
+        Ok(NbtValue::Float(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<NbtValue, E> {
+        Ok(NbtValue::Double(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<NbtValue, E> {
+        Ok(NbtValue::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<NbtValue, E> {
+        Ok(NbtValue::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<NbtValue, E> {
+        Ok(NbtValue::ByteArray(v.iter().map(|b| *b as i8).collect()))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<NbtValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<NbtValue, A::Error> {
+        let mut list = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            list.push(value);
+        }
+        Ok(NbtValue::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<NbtValue, A::Error> {
+        let mut compound = NbtCompound::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                BYTE_ARRAY_TOKEN => return Ok(NbtValue::ByteArray(map.next_value()?)),
+                INT_ARRAY_TOKEN => return Ok(NbtValue::IntArray(map.next_value()?)),
+                LONG_ARRAY_TOKEN => return Ok(NbtValue::LongArray(map.next_value()?)),
+                _ => {
+                    let value = map.next_value()?;
+                    compound.insert(key, value);
+                }
+            }
+        }
+        Ok(NbtValue::Compound(compound))
+    }
+}
@@ -0,0 +1,415 @@
+/// Binary encoding of tags and Java's modified UTF-8.
+mod binary;
+/// Deserialization of Rust types from [`NbtValue`] trees.
+mod de;
+/// Serialization of Rust types into [`NbtValue`] trees.
+mod ser;
+
+pub use de::from_value;
+pub use ser::to_value;
+
+use crate::filesystem::{FilesystemError, write_atomic};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// Maximum nesting of lists and compounds, as enforced by Minecraft.
+pub const MAX_DEPTH: usize = 512;
+
+/// Error returned when NBT data cannot be read, written or converted.
+#[derive(Debug, Error)]
+pub enum NbtError {
+    #[error("failed to read NBT: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write NBT: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid NBT tag type {0}")]
+    InvalidTag(u8),
+    #[error("invalid modified UTF-8 string")]
+    InvalidString,
+    #[error("NBT is nested deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+    #[error("the root tag must be a compound")]
+    RootNotCompound,
+    #[error("list mixes {0} and {1} elements")]
+    MixedList(&'static str, &'static str),
+    #[error("{0}")]
+    Message(String),
+}
+
+impl serde::ser::Error for NbtError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        NbtError::Message(msg.to_string())
+    }
+}
+
+impl serde::de::Error for NbtError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        NbtError::Message(msg.to_string())
+    }
+}
+
+/// Keys and values of a compound tag.
+pub type NbtCompound = BTreeMap<String, NbtValue>;
+
+/// A tag value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtValue {
+    /// `TAG_Byte`, also used for booleans.
+    Byte(i8),
+    /// `TAG_Short`.
+    Short(i16),
+    /// `TAG_Int`.
+    Int(i32),
+    /// `TAG_Long`.
+    Long(i64),
+    /// `TAG_Float`.
+    Float(f32),
+    /// `TAG_Double`.
+    Double(f64),
+    /// `TAG_Byte_Array`.
+    ByteArray(Vec<i8>),
+    /// `TAG_String`.
+    String(String),
+    /// `TAG_List`: elements of one type. An empty list is written with the end
+    /// tag as its element type.
+    List(Vec<NbtValue>),
+    /// `TAG_Compound`.
+    Compound(NbtCompound),
+    /// `TAG_Int_Array`.
+    IntArray(Vec<i32>),
+    /// `TAG_Long_Array`.
+    LongArray(Vec<i64>),
+}
+
+impl NbtValue {
+    /// Returns the tag type id, e.g. `10` for compounds.
+    pub fn tag_id(&self) -> u8 {
+        match self {
+            NbtValue::Byte(_) => 1,
+            NbtValue::Short(_) => 2,
+            NbtValue::Int(_) => 3,
+            NbtValue::Long(_) => 4,
+            NbtValue::Float(_) => 5,
+            NbtValue::Double(_) => 6,
+            NbtValue::ByteArray(_) => 7,
+            NbtValue::String(_) => 8,
+            NbtValue::List(_) => 9,
+            NbtValue::Compound(_) => 10,
+            NbtValue::IntArray(_) => 11,
+            NbtValue::LongArray(_) => 12,
+        }
+    }
+
+    /// Returns the name of the tag type, e.g. `TAG_Compound`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            NbtValue::Byte(_) => "TAG_Byte",
+            NbtValue::Short(_) => "TAG_Short",
+            NbtValue::Int(_) => "TAG_Int",
+            NbtValue::Long(_) => "TAG_Long",
+            NbtValue::Float(_) => "TAG_Float",
+            NbtValue::Double(_) => "TAG_Double",
+            NbtValue::ByteArray(_) => "TAG_Byte_Array",
+            NbtValue::String(_) => "TAG_String",
+            NbtValue::List(_) => "TAG_List",
+            NbtValue::Compound(_) => "TAG_Compound",
+            NbtValue::IntArray(_) => "TAG_Int_Array",
+            NbtValue::LongArray(_) => "TAG_Long_Array",
+        }
+    }
+
+    /// Returns an integer tag widened to `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            NbtValue::Byte(v) => Some(v.into()),
+            NbtValue::Short(v) => Some(v.into()),
+            NbtValue::Int(v) => Some(v.into()),
+            NbtValue::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns a numeric tag as `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            NbtValue::Float(v) => Some(v.into()),
+            NbtValue::Double(v) => Some(v),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    /// Returns a byte tag as a boolean, the way Minecraft stores flags.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            NbtValue::Byte(v) => Some(v != 0),
+            _ => None,
+        }
+    }
+
+    /// Returns the string of a string tag.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of a list tag.
+    pub fn as_list(&self) -> Option<&[NbtValue]> {
+        match self {
+            NbtValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of a list tag for modification.
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<NbtValue>> {
+        match self {
+            NbtValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a compound tag.
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            NbtValue::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a compound tag for modification.
+    pub fn as_compound_mut(&mut self) -> Option<&mut NbtCompound> {
+        match self {
+            NbtValue::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    /// Returns the entry `key` of a compound tag.
+    pub fn get(&self, key: &str) -> Option<&NbtValue> {
+        self.as_compound()?.get(key)
+    }
+
+    /// Follows a `.`-separated path of compound keys, e.g. `Data.Version.Name`.
+    pub fn get_path(&self, path: &str) -> Option<&NbtValue> {
+        path.split('.').try_fold(self, |value, key| value.get(key))
+    }
+}
+
+/// Compression of an NBT file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NbtCompression {
+    /// Gzip, used by `level.dat` and most files.
+    #[default]
+    Gzip,
+    /// Zlib, used inside region files.
+    Zlib,
+    /// No compression, used by `servers.dat`.
+    None,
+}
+
+impl NbtCompression {
+    /// Detects the compression from the first bytes of a file.
+    pub fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => NbtCompression::Gzip,
+            [0x78, second, ..] if (0x7800u16 | u16::from(*second)).is_multiple_of(31) => {
+                NbtCompression::Zlib
+            }
+            _ => NbtCompression::None,
+        }
+    }
+}
+
+/// A complete NBT document: a named root compound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtFile {
+    /// Name of the root tag, usually empty.
+    pub name: String,
+    /// The root compound.
+    pub root: NbtValue,
+    /// Compression used when the document is written.
+    pub compression: NbtCompression,
+}
+
+impl NbtFile {
+    /// Creates a document with an unnamed root compound.
+    pub fn new(root: NbtCompound, compression: NbtCompression) -> Self {
+        Self {
+            name: String::new(),
+            root: NbtValue::Compound(root),
+            compression,
+        }
+    }
+
+    /// Parses a document, detecting its compression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is truncated, malformed, or its root is not
+    /// a compound.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, NbtError> {
+        let compression = NbtCompression::detect(data);
+        let mut raw = Vec::new();
+        match compression {
+            NbtCompression::Gzip => GzDecoder::new(data).read_to_end(&mut raw)?,
+            NbtCompression::Zlib => ZlibDecoder::new(data).read_to_end(&mut raw)?,
+            NbtCompression::None => {
+                raw.extend_from_slice(data);
+                raw.len()
+            }
+        };
+        let (name, root) = binary::read_root(&mut raw.as_slice())?;
+        Ok(Self {
+            name,
+            root,
+            compression,
+        })
+    }
+
+    /// Encodes the document with its compression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root is not a compound or a list mixes types.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NbtError> {
+        let mut raw = Vec::new();
+        binary::write_root(&mut raw, &self.name, &self.root)?;
+        Ok(match self.compression {
+            NbtCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&raw)?;
+                encoder.finish()?
+            }
+            NbtCompression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&raw)?;
+                encoder.finish()?
+            }
+            NbtCompression::None => raw,
+        })
+    }
+
+    /// Reads a document from a file, detecting its compression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid NBT.
+    pub fn read(path: &Path) -> Result<Self, NbtError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Writes the document to a file atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document cannot be encoded or written.
+    pub fn write(&self, path: &Path) -> Result<(), NbtError> {
+        write_atomic(path, &self.to_bytes()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    fn sample() -> NbtCompound {
+        let mut data = NbtCompound::new();
+        data.insert(
+            "LevelName".to_string(),
+            NbtValue::String("World\0 ☃ 𝄞".to_string()),
+        );
+        data.insert("DataVersion".to_string(), NbtValue::Int(3465));
+        data.insert("hardcore".to_string(), NbtValue::Byte(1));
+        data.insert("RandomSeed".to_string(), NbtValue::Long(-42));
+        data.insert("SpawnAngle".to_string(), NbtValue::Float(0.5));
+        data.insert("BorderSize".to_string(), NbtValue::Double(6.0e7));
+        data.insert("Flags".to_string(), NbtValue::ByteArray(vec![-1, 0, 1]));
+        data.insert("UUID".to_string(), NbtValue::IntArray(vec![1, -2, 3, -4]));
+        data.insert("Heights".to_string(), NbtValue::LongArray(vec![i64::MIN]));
+        data.insert(
+            "Enabled".to_string(),
+            NbtValue::List(vec![NbtValue::String("vanilla".to_string())]),
+        );
+        data.insert("Empty".to_string(), NbtValue::List(Vec::new()));
+        let mut root = NbtCompound::new();
+        root.insert("Data".to_string(), NbtValue::Compound(data));
+        root
+    }
+
+    #[test]
+    fn round_trips_every_compression() {
+        for compression in [
+            NbtCompression::Gzip,
+            NbtCompression::Zlib,
+            NbtCompression::None,
+        ] {
+            let file = NbtFile::new(sample(), compression);
+            let bytes = file.to_bytes().unwrap();
+            assert_eq!(NbtCompression::detect(&bytes), compression);
+            let parsed = NbtFile::from_bytes(&bytes).unwrap();
+            assert_eq!(parsed, file);
+            assert_eq!(
+                parsed
+                    .root
+                    .get_path("Data.LevelName")
+                    .and_then(NbtValue::as_str),
+                Some("World\0 ☃ 𝄞")
+            );
+        }
+
+        let mixed = NbtFile::new(
+            NbtCompound::from([(
+                "x".to_string(),
+                NbtValue::List(vec![NbtValue::Byte(1), NbtValue::Int(2)]),
+            )]),
+            NbtCompression::None,
+        );
+        assert!(matches!(mixed.to_bytes(), Err(NbtError::MixedList(..))));
+        assert!(NbtFile::from_bytes(&[10, 0, 0, 1, 0]).is_err());
+        // A byte array claiming 2 GiB fails without allocating it.
+        assert!(NbtFile::from_bytes(&[10, 0, 0, 7, 0, 0, 0x7f, 0xff, 0xff, 0xff, 1, 2]).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Level {
+        level_name: String,
+        data_version: i32,
+        #[serde(rename = "hardcore")]
+        hardcore: bool,
+        random_seed: i64,
+        #[serde(rename = "UUID")]
+        uuid: Vec<i32>,
+        enabled: Vec<String>,
+        #[serde(default)]
+        missing: Option<String>,
+    }
+
+    #[test]
+    fn converts_structs_with_serde() {
+        let root = NbtValue::Compound(sample());
+        let level: Level = from_value(root.get("Data").unwrap().clone()).unwrap();
+        assert!(level.hardcore);
+        assert_eq!(level.uuid, vec![1, -2, 3, -4]);
+        assert_eq!(level.missing, None);
+
+        let value = to_value(&level).unwrap();
+        assert_eq!(value.get("hardcore"), Some(&NbtValue::Byte(1)));
+        assert_eq!(value.get("DataVersion"), Some(&NbtValue::Int(3465)));
+        assert!(value.get("Missing").is_none());
+
+        let tree: NbtValue = from_value(root.clone()).unwrap();
+        assert_eq!(tree, root);
+        assert_eq!(to_value(&tree).unwrap(), root);
+    }
+}
@@ -0,0 +1,415 @@
+use super::de::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+use super::{NbtCompound, NbtError, NbtValue};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// Serializes a Rust value into a tag tree.
+///
+/// Integers keep their width (`i8` becomes a byte tag, `i32` an int tag, and
+/// unsigned integers the signed tag of the same width), `bool` becomes a byte
+/// tag, sequences become lists, and structs and maps become compounds. `None`
+/// fields are left out of compounds. Unit enum variants become strings.
+///
+/// # Errors
+///
+/// Returns an error if the value has no NBT representation, e.g. a map with
+/// non-string keys, a list of mixed types, or a top-level `None`.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<NbtValue, NbtError> {
+    value
+        .serialize(ValueSerializer)?
+        .ok_or_else(|| NbtError::Message("value has no NBT representation".to_string()))
+}
+
+impl Serialize for NbtValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NbtValue::Byte(v) => serializer.serialize_i8(*v),
+            NbtValue::Short(v) => serializer.serialize_i16(*v),
+            NbtValue::Int(v) => serializer.serialize_i32(*v),
+            NbtValue::Long(v) => serializer.serialize_i64(*v),
+            NbtValue::Float(v) => serializer.serialize_f32(*v),
+            NbtValue::Double(v) => serializer.serialize_f64(*v),
+            NbtValue::String(s) => serializer.serialize_str(s),
+            // Other serializers see plain sequences; ours keeps the array type.
+            NbtValue::ByteArray(v) => serializer.serialize_newtype_struct(BYTE_ARRAY_TOKEN, v),
+            NbtValue::IntArray(v) => serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, v),
+            NbtValue::LongArray(v) => serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, v),
+            NbtValue::List(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for item in list {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            NbtValue::Compound(compound) => {
+                let mut map = serializer.serialize_map(Some(compound.len()))?;
+                for (key, item) in compound {
+                    map.serialize_entry(key, item)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Produces `None` for values NBT cannot store, which compounds then skip.
+struct ValueSerializer;
+
+fn unsupported(what: &str) -> NbtError {
+    NbtError::Message(format!("{} cannot be stored as NBT", what))
+}
+
+fn required(value: Option<NbtValue>) -> Result<NbtValue, NbtError> {
+    value.ok_or_else(|| NbtError::Message("lists cannot contain missing values".to_string()))
+}
+
+fn single(key: &str, value: NbtValue) -> NbtValue {
+    NbtValue::Compound(NbtCompound::from([(key.to_string(), value)]))
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = VariantSerializer<ListSerializer>;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = VariantSerializer<CompoundSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Byte(v.into())))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Byte(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Short(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Int(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Long(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Byte(v as i8)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Short(v as i16)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Int(v as i32)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Long(v as i64)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Float(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Double(v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::String(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::String(v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::ByteArray(
+            v.iter().map(|b| *b as i8).collect(),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, NbtError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, NbtError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, NbtError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, NbtError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, NbtError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, NbtError> {
+        let value = value.serialize(self)?;
+        let elements = || {
+            value
+                .clone()
+                .and_then(|v| v.as_list().map(<[NbtValue]>::to_vec))
+                .ok_or_else(|| unsupported(name))
+        };
+        Ok(Some(match name {
+            BYTE_ARRAY_TOKEN => NbtValue::ByteArray(
+                elements()?
+                    .iter()
+                    .map(|v| v.as_i64().map(|v| v as i8))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| unsupported(name))?,
+            ),
+            INT_ARRAY_TOKEN => NbtValue::IntArray(
+                elements()?
+                    .iter()
+                    .map(|v| v.as_i64().map(|v| v as i32))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| unsupported(name))?,
+            ),
+            LONG_ARRAY_TOKEN => NbtValue::LongArray(
+                elements()?
+                    .iter()
+                    .map(NbtValue::as_i64)
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| unsupported(name))?,
+            ),
+            _ => return Ok(value),
+        }))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, NbtError> {
+        Ok(value.serialize(self)?.map(|v| single(variant, v)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ListSerializer, NbtError> {
+        Ok(ListSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer, NbtError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<ListSerializer, NbtError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, NbtError> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<CompoundSerializer, NbtError> {
+        Ok(CompoundSerializer::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<CompoundSerializer, NbtError> {
+        Ok(CompoundSerializer::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, NbtError> {
+        Ok(VariantSerializer {
+            variant,
+            inner: CompoundSerializer::default(),
+        })
+    }
+}
+
+struct ListSerializer(Vec<NbtValue>);
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        let value = required(value.serialize(ValueSerializer)?)?;
+        if let Some(first) = self.0.first()
+            && first.tag_id() != value.tag_id()
+        {
+            return Err(NbtError::MixedList(first.type_name(), value.type_name()));
+        }
+        self.0.push(value);
+        Ok(())
+    }
+}
+
+impl SerializeSeq for ListSerializer {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::List(self.0)))
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        SerializeSeq::end(self)
+    }
+}
+
+#[derive(Default)]
+struct CompoundSerializer {
+    compound: NbtCompound,
+    key: Option<String>,
+}
+
+impl CompoundSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), NbtError> {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.compound.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+impl SerializeMap for CompoundSerializer {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), NbtError> {
+        match key.serialize(ValueSerializer)? {
+            Some(NbtValue::String(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(unsupported("a map key that is not a string")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| NbtError::Message("map value without a key".to_string()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        Ok(Some(NbtValue::Compound(self.compound)))
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), NbtError> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        SerializeMap::end(self)
+    }
+}
+
+/// Wraps the fields of a tuple or struct variant in a compound keyed by the variant.
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ListSerializer> {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        let list = required(SerializeSeq::end(self.inner)?)?;
+        Ok(Some(single(self.variant, list)))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<CompoundSerializer> {
+    type Ok = Option<NbtValue>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), NbtError> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, NbtError> {
+        let compound = required(SerializeMap::end(self.inner)?)?;
+        Ok(Some(single(self.variant, compound)))
+    }
+}
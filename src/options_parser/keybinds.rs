@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the `options.txt` entries holding key bindings.
+pub const KEYBIND_PREFIX: &str = "key_";
+
+/// Key name of an unbound action.
+pub const UNBOUND: &str = "key.keyboard.unknown";
+
+/// The vanilla bindings of 1.13 and later, as `(action, key)`.
+const VANILLA_KEYBINDS: &[(&str, &str)] = &[
+    ("key.attack", "key.mouse.left"),
+    ("key.use", "key.mouse.right"),
+    ("key.forward", "key.keyboard.w"),
+    ("key.left", "key.keyboard.a"),
+    ("key.back", "key.keyboard.s"),
+    ("key.right", "key.keyboard.d"),
+    ("key.jump", "key.keyboard.space"),
+    ("key.sneak", "key.keyboard.left.shift"),
+    ("key.sprint", "key.keyboard.left.control"),
+    ("key.drop", "key.keyboard.q"),
+    ("key.inventory", "key.keyboard.e"),
+    ("key.chat", "key.keyboard.t"),
+    ("key.playerlist", "key.keyboard.tab"),
+    ("key.pickItem", "key.mouse.middle"),
+    ("key.command", "key.keyboard.slash"),
+    ("key.socialInteractions", "key.keyboard.p"),
+    ("key.screenshot", "key.keyboard.f2"),
+    ("key.togglePerspective", "key.keyboard.f5"),
+    ("key.smoothCamera", UNBOUND),
+    ("key.fullscreen", "key.keyboard.f11"),
+    ("key.spectatorOutlines", UNBOUND),
+    ("key.swapOffhand", "key.keyboard.f"),
+    ("key.saveToolbarActivator", "key.keyboard.c"),
+    ("key.loadToolbarActivator", "key.keyboard.x"),
+    ("key.advancements", "key.keyboard.l"),
+    ("key.hotbar.1", "key.keyboard.1"),
+    ("key.hotbar.2", "key.keyboard.2"),
+    ("key.hotbar.3", "key.keyboard.3"),
+    ("key.hotbar.4", "key.keyboard.4"),
+    ("key.hotbar.5", "key.keyboard.5"),
+    ("key.hotbar.6", "key.keyboard.6"),
+    ("key.hotbar.7", "key.keyboard.7"),
+    ("key.hotbar.8", "key.keyboard.8"),
+    ("key.hotbar.9", "key.keyboard.9"),
+];
+
+/// A key binding read from `options.txt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keybind {
+    /// The action, e.g. `key.forward`, or a mod's own action.
+    pub action: String,
+    /// The key, e.g. `key.keyboard.w`, or a key code before 1.13.
+    pub key: String,
+}
+
+/// Reads the key bindings of an `options.txt`, in file order.
+pub fn parse_keybinds(doc: &str) -> Vec<Keybind> {
+    doc.lines()
+        .filter_map(|line| {
+            let (name, key) = line.split_once(':')?;
+            Some(Keybind {
+                action: name.strip_prefix(KEYBIND_PREFIX)?.to_string(),
+                key: key.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A named layout of the vanilla key bindings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeybindPreset {
+    /// The game's default bindings.
+    #[default]
+    Vanilla,
+    /// Movement on ESDF, which keeps the index finger on the homing key and
+    /// frees the keys left of it. Inventory moves to R, drop to W, sneak to
+    /// A and swapping hands to G.
+    Esdf,
+    /// Movement on IJKL for left-handed players with the mouse on the left.
+    /// Inventory moves to U, drop to O, sneak and sprint to the right Shift
+    /// and Control, swapping hands to H, chat to Enter and advancements to
+    /// `;`.
+    Lefty,
+    /// Vanilla movement with drop unbound, so blocks are not thrown away by
+    /// accident, pick block on Q for touchpads, and the hotbar activators on
+    /// C and V.
+    CreativeBuilder,
+}
+
+impl KeybindPreset {
+    /// All presets, in the order a launcher lists them.
+    pub const ALL: [KeybindPreset; 4] = [
+        KeybindPreset::Vanilla,
+        KeybindPreset::Esdf,
+        KeybindPreset::Lefty,
+        KeybindPreset::CreativeBuilder,
+    ];
+
+    /// Returns the id the preset is stored under, e.g. `creative-builder`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeybindPreset::Vanilla => "vanilla",
+            KeybindPreset::Esdf => "esdf",
+            KeybindPreset::Lefty => "lefty",
+            KeybindPreset::CreativeBuilder => "creative-builder",
+        }
+    }
+
+    /// Parses a preset id, ignoring case.
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.as_str().eq_ignore_ascii_case(id))
+    }
+
+    /// The bindings that differ from vanilla, as `(action, key)`.
+    fn overrides(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            KeybindPreset::Vanilla => &[],
+            KeybindPreset::Esdf => &[
+                ("key.forward", "key.keyboard.e"),
+                ("key.left", "key.keyboard.s"),
+                ("key.back", "key.keyboard.d"),
+                ("key.right", "key.keyboard.f"),
+                ("key.sneak", "key.keyboard.a"),
+                ("key.inventory", "key.keyboard.r"),
+                ("key.drop", "key.keyboard.w"),
+                ("key.swapOffhand", "key.keyboard.g"),
+            ],
+            KeybindPreset::Lefty => &[
+                ("key.forward", "key.keyboard.i"),
+                ("key.left", "key.keyboard.j"),
+                ("key.back", "key.keyboard.k"),
+                ("key.right", "key.keyboard.l"),
+                ("key.sneak", "key.keyboard.right.shift"),
+                ("key.sprint", "key.keyboard.right.control"),
+                ("key.inventory", "key.keyboard.u"),
+                ("key.drop", "key.keyboard.o"),
+                ("key.swapOffhand", "key.keyboard.h"),
+                ("key.chat", "key.keyboard.enter"),
+                ("key.advancements", "key.keyboard.semicolon"),
+            ],
+            KeybindPreset::CreativeBuilder => &[
+                ("key.drop", UNBOUND),
+                ("key.pickItem", "key.keyboard.q"),
+                ("key.saveToolbarActivator", "key.keyboard.c"),
+                ("key.loadToolbarActivator", "key.keyboard.v"),
+            ],
+        }
+    }
+
+    /// Returns every vanilla binding of the preset, as `(action, key)`.
+    pub fn keybinds(self) -> Vec<(&'static str, &'static str)> {
+        VANILLA_KEYBINDS
+            .iter()
+            .map(|&(action, key)| {
+                let key = self
+                    .overrides()
+                    .iter()
+                    .find(|(overridden, _)| *overridden == action)
+                    .map_or(key, |(_, key)| *key);
+                (action, key)
+            })
+            .collect()
+    }
+}
+
+/// Applies a key binding preset to the content of an `options.txt`.
+///
+/// Every vanilla binding is rewritten, so keys freed by the preset do not
+/// stay bound twice; bindings missing from the file are appended. Bindings
+/// of mods and all other lines are kept byte for byte, line endings
+/// included. Key names are those of 1.13 and later.
+///
+/// # Arguments
+///
+/// * `doc` - The content of `options.txt`.
+/// * `preset` - The layout to apply.
+///
+/// # Returns
+///
+/// * `String` - The new content of `options.txt`.
+pub fn apply_keybind_preset(doc: &str, preset: KeybindPreset) -> String {
+    let keybinds = preset.keybinds();
+    let mut written = vec![false; keybinds.len()];
+    let mut out = String::with_capacity(doc.len());
+    for line in doc.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let index = content
+            .split_once(':')
+            .and_then(|(name, _)| name.strip_prefix(KEYBIND_PREFIX))
+            .and_then(|action| keybinds.iter().position(|(a, _)| *a == action));
+        match index {
+            Some(i) => {
+                let (action, key) = keybinds[i];
+                out.push_str(&format!("{}{}:{}", KEYBIND_PREFIX, action, key));
+                out.push_str(&line[content.len()..]);
+                written[i] = true;
+            }
+            None => out.push_str(line),
+        }
+    }
+
+    let newline = if doc.contains("\r\n") { "\r\n" } else { "\n" };
+    for ((action, key), _) in keybinds.iter().zip(written).filter(|(_, w)| !w) {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push_str(newline);
+        }
+        out.push_str(&format!("{}{}:{}{}", KEYBIND_PREFIX, action, key, newline));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_only_vanilla_keybinds() {
+        let doc = "version:3465\r\nkey_key.forward:key.keyboard.w\r\nkey_key.left:key.keyboard.a\r\nfov:0.5\r\nkey_key.create.rotate_menu:key.keyboard.left.alt\r\n";
+
+        let esdf = apply_keybind_preset(doc, KeybindPreset::Esdf);
+        assert!(esdf.starts_with(
+            "version:3465\r\nkey_key.forward:key.keyboard.e\r\nkey_key.left:key.keyboard.s\r\nfov:0.5\r\nkey_key.create.rotate_menu:key.keyboard.left.alt\r\n"
+        ));
+        let keybinds = parse_keybinds(&esdf);
+        assert_eq!(keybinds.len(), VANILLA_KEYBINDS.len() + 1);
+        assert!(keybinds.contains(&Keybind {
+            action: "key.inventory".to_string(),
+            key: "key.keyboard.r".to_string(),
+        }));
+        assert!(esdf.ends_with("key_key.hotbar.9:key.keyboard.9\r\n"));
+
+        let back = apply_keybind_preset(&esdf, KeybindPreset::Vanilla);
+        assert!(back.starts_with("version:3465\r\nkey_key.forward:key.keyboard.w\r\n"));
+        assert_eq!(back.lines().count(), esdf.lines().count());
+        assert_eq!(apply_keybind_preset(&back, KeybindPreset::Vanilla), back);
+    }
+
+    #[test]
+    fn presets_bind_each_key_once() {
+        for preset in KeybindPreset::ALL {
+            let mut keys: Vec<&str> = preset
+                .keybinds()
+                .into_iter()
+                .map(|(_, key)| key)
+                .filter(|key| *key != UNBOUND)
+                .collect();
+            let count = keys.len();
+            keys.sort();
+            keys.dedup();
+            assert_eq!(keys.len(), count, "{}", preset.as_str());
+            assert_eq!(KeybindPreset::from_id(preset.as_str()), Some(preset));
+        }
+        assert_eq!(
+            KeybindPreset::from_id("Creative-Builder"),
+            Some(KeybindPreset::CreativeBuilder)
+        );
+    }
+}
@@ -1,3 +1,10 @@
+/// Key bindings of `options.txt` and presets rewriting them.
+pub mod keybinds;
+
+pub use keybinds::{
+    KEYBIND_PREFIX, Keybind, KeybindPreset, UNBOUND, apply_keybind_preset, parse_keybinds,
+};
+
 use std::fmt;
 
 /// Represents the possible data types that can be parsed from an options line.
@@ -166,6 +173,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn parses_float_value() {
         let parsed = parse_line("ratio: 3.14").unwrap();
         assert_eq!(parsed.key, "ratio");
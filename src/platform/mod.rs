@@ -0,0 +1,270 @@
+use crate::version::Environment;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+/// An operating system, named as version JSON rules name it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OsName {
+    Windows,
+    Osx,
+    Linux,
+    /// Any other system. No `os.name` rule matches it.
+    Unknown,
+}
+
+impl OsName {
+    /// Returns the name used in rules: `windows`, `osx` or `linux`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OsName::Windows => "windows",
+            OsName::Osx => "osx",
+            OsName::Linux => "linux",
+            OsName::Unknown => "unknown",
+        }
+    }
+
+    /// Maps a Rust OS name such as [`std::env::consts::OS`].
+    pub fn from_rust(os: &str) -> Self {
+        match os {
+            "windows" => OsName::Windows,
+            "macos" => OsName::Osx,
+            "linux" => OsName::Linux,
+            _ => OsName::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for OsName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A CPU architecture, named as version JSON rules name it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arch {
+    X86,
+    X86_64,
+    Arm32,
+    Arm64,
+    /// Any other architecture. No `os.arch` rule matches it.
+    Unknown,
+}
+
+impl Arch {
+    /// Returns the name used in rules: `x86`, `x86_64`, `arm32` or `arm64`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Arch::X86 => "x86",
+            Arch::X86_64 => "x86_64",
+            Arch::Arm32 => "arm32",
+            Arch::Arm64 => "arm64",
+            Arch::Unknown => "unknown",
+        }
+    }
+
+    /// Maps a Rust architecture name such as [`std::env::consts::ARCH`].
+    pub fn from_rust(arch: &str) -> Self {
+        match arch {
+            "x86" => Arch::X86,
+            "x86_64" => Arch::X86_64,
+            "arm" => Arch::Arm32,
+            "aarch64" => Arch::Arm64,
+            _ => Arch::Unknown,
+        }
+    }
+
+    /// Returns the pointer width substituted for `${arch}` in natives
+    /// classifiers such as `natives-windows-${arch}`: `32` or `64`.
+    pub fn bits(self) -> &'static str {
+        match self {
+            Arch::X86 | Arch::Arm32 => "32",
+            Arch::X86_64 | Arch::Arm64 | Arch::Unknown => "64",
+        }
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An operating system, its version and a CPU architecture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Platform {
+    pub os: OsName,
+    /// The version Java reports as `os.version`, which `os.version` rules
+    /// match: `10.0` on Windows, e.g. `14.4.1` on macOS and the kernel release
+    /// on Linux.
+    pub os_version: String,
+    pub arch: Arch,
+}
+
+static OVERRIDE: RwLock<Option<Platform>> = RwLock::new(None);
+
+impl Platform {
+    /// Creates a platform, e.g. to evaluate rules for another system.
+    pub fn new(os: OsName, os_version: impl Into<String>, arch: Arch) -> Self {
+        Self {
+            os,
+            os_version: os_version.into(),
+            arch,
+        }
+    }
+
+    /// Returns the platform the launcher runs on, or the one set with
+    /// [`Platform::set_override`].
+    pub fn current() -> Self {
+        let overridden = OVERRIDE.read().unwrap_or_else(|e| e.into_inner());
+        match &*overridden {
+            Some(platform) => platform.clone(),
+            None => Self::detect(),
+        }
+    }
+
+    /// Detects the platform the launcher runs on, ignoring any override.
+    ///
+    /// The architecture is the one the launcher was built for.
+    pub fn detect() -> Self {
+        let os = OsName::from_rust(std::env::consts::OS);
+        Self::new(
+            os,
+            detect_os_version(os),
+            Arch::from_rust(std::env::consts::ARCH),
+        )
+    }
+
+    /// Returns [`Platform::current`] with the architecture of the hardware,
+    /// see [`native_arch`]. An override is returned unchanged.
+    ///
+    /// Use this platform to pick the Java runtime and then evaluate the
+    /// version's rules for the runtime's architecture, which is what the game
+    /// sees.
+    pub fn native() -> Self {
+        let overridden = OVERRIDE.read().unwrap_or_else(|e| e.into_inner());
+        match &*overridden {
+            Some(platform) => platform.clone(),
+            None => Self {
+                arch: native_arch(),
+                ..Self::detect()
+            },
+        }
+    }
+
+    /// Makes [`Platform::current`] return `platform` instead of the detected
+    /// platform, until cleared with `None`.
+    ///
+    /// The override is process-wide, so tests using it must not run in
+    /// parallel with tests that depend on the current platform.
+    pub fn set_override(platform: Option<Platform>) {
+        *OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = platform;
+    }
+
+    /// Returns an [`Environment`] for evaluating version JSON rules on this
+    /// platform, without enabled features.
+    pub fn environment(&self) -> Environment {
+        Environment::new(self.os.as_str(), &self.os_version, self.arch.as_str())
+    }
+}
+
+/// Returns the architecture of the hardware, which differs from the one the
+/// launcher was built for when an x86_64 build runs on arm64 under Rosetta 2
+/// or Windows' x64 emulation.
+pub fn native_arch() -> Arch {
+    static NATIVE: OnceLock<Arch> = OnceLock::new();
+    *NATIVE.get_or_init(detect_native_arch)
+}
+
+/// Returns whether the launcher runs translated on hardware of another
+/// architecture, e.g. under Rosetta 2.
+pub fn is_translated() -> bool {
+    native_arch() != Arch::from_rust(std::env::consts::ARCH)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_native_arch() -> Arch {
+    // Set to 1 for processes translated by Rosetta 2, and missing on Intel Macs.
+    let translated = std::process::Command::new("sysctl")
+        .args(["-in", "sysctl.proc_translated"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "1");
+    if translated {
+        Arch::Arm64
+    } else {
+        Arch::from_rust(std::env::consts::ARCH)
+    }
+}
+
+#[cfg(windows)]
+fn detect_native_arch() -> Arch {
+    use windows_sys::Win32::System::SystemInformation::{
+        IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_I386,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    let mut process_machine = 0;
+    let mut native_machine = 0;
+    // SAFETY: both pointers are valid for writes, and the pseudo handle of the
+    // current process needs no closing.
+    let ok = unsafe {
+        IsWow64Process2(
+            GetCurrentProcess(),
+            &mut process_machine,
+            &mut native_machine,
+        )
+    };
+    if ok == 0 {
+        return Arch::from_rust(std::env::consts::ARCH);
+    }
+    match native_machine {
+        IMAGE_FILE_MACHINE_ARM64 => Arch::Arm64,
+        IMAGE_FILE_MACHINE_AMD64 => Arch::X86_64,
+        IMAGE_FILE_MACHINE_I386 => Arch::X86,
+        _ => Arch::from_rust(std::env::consts::ARCH),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn detect_native_arch() -> Arch {
+    Arch::from_rust(std::env::consts::ARCH)
+}
+
+fn detect_os_version(os: OsName) -> String {
+    match os {
+        // Rust supports Windows 10 and later only, and Windows 11 still
+        // reports NT version 10.0 like Java does.
+        OsName::Windows => "10.0".to_string(),
+        OsName::Osx => sysinfo::System::os_version().unwrap_or_default(),
+        OsName::Linux | OsName::Unknown => sysinfo::System::kernel_version().unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_platforms_like_version_jsons_and_honors_override() {
+        assert_eq!(OsName::from_rust("macos").as_str(), "osx");
+        assert_eq!(Arch::from_rust("aarch64").as_str(), "arm64");
+        assert_eq!(Arch::from_rust("x86").bits(), "32");
+        assert_eq!(OsName::from_rust("freebsd"), OsName::Unknown);
+
+        let detected = Platform::detect();
+        assert_eq!(detected.os, OsName::from_rust(std::env::consts::OS));
+
+        let windows = Platform::new(OsName::Windows, "10.0", Arch::X86);
+        Platform::set_override(Some(windows.clone()));
+        let current = Platform::current();
+        Platform::set_override(None);
+        assert_eq!(current, windows);
+        let env = current.environment();
+        assert_eq!(
+            (env.os_name.as_str(), env.arch.as_str()),
+            ("windows", "x86")
+        );
+        assert_eq!(Platform::current(), detected);
+        assert_eq!(Platform::native().arch == detected.arch, !is_translated());
+    }
+}
@@ -0,0 +1,331 @@
+use crate::version::LaunchArguments;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::SystemTime;
+use thiserror::Error;
+use tokio::process::{Child, Command};
+
+/// Game arguments whose value is a secret, hidden by [`LaunchCommand::command_line`].
+const SECRET_ARGUMENTS: [&str; 2] = ["--accessToken", "--session"];
+
+/// Error returned when the game cannot be started.
+#[derive(Debug, Error)]
+pub enum ProcessError {
+    #[error("Java executable not found: {0}")]
+    JavaNotFound(PathBuf),
+    #[error("working directory does not exist: {0}")]
+    WorkingDirNotFound(PathBuf),
+    #[error("failed to start the game: {0}")]
+    Spawn(#[from] io::Error),
+}
+
+/// How the game process relates to the launcher.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LaunchMode {
+    /// The game shares the launcher's console and process group, so it
+    /// receives the launcher's Ctrl+C.
+    #[default]
+    Attached,
+    /// The game runs in its own process group without the launcher's console,
+    /// so it keeps running when the launcher exits.
+    Detached,
+}
+
+/// A complete `java` command line starting the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    /// The `java` executable.
+    pub java: PathBuf,
+    /// Arguments placed before the main class.
+    pub jvm_args: Vec<String>,
+    /// The class to run, e.g. `net.minecraft.client.main.Main`.
+    pub main_class: String,
+    /// Arguments placed after the main class.
+    pub game_args: Vec<String>,
+    /// Working directory of the game, usually the instance's game directory.
+    pub working_dir: PathBuf,
+    /// Environment variables to set (`Some`) or remove (`None`) on top of the
+    /// launcher's environment.
+    pub env: BTreeMap<String, Option<String>>,
+}
+
+impl LaunchCommand {
+    /// Creates a command without arguments.
+    pub fn new(
+        java: impl Into<PathBuf>,
+        main_class: impl Into<String>,
+        working_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            java: java.into(),
+            jvm_args: Vec::new(),
+            main_class: main_class.into(),
+            game_args: Vec::new(),
+            working_dir: working_dir.into(),
+            env: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the JVM and game arguments from resolved version arguments.
+    pub fn with_arguments(mut self, arguments: LaunchArguments) -> Self {
+        self.jvm_args = arguments.jvm;
+        self.game_args = arguments.game;
+        self
+    }
+
+    /// Appends JVM arguments, e.g. memory settings such as `-Xmx4G`.
+    pub fn with_jvm_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.jvm_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Appends game arguments.
+    pub fn with_game_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.game_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable of the game.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), Some(value.into()));
+        self
+    }
+
+    /// Removes an inherited environment variable, e.g. `_JAVA_OPTIONS`, which
+    /// would override the instance's JVM arguments.
+    pub fn without_env(mut self, key: impl Into<String>) -> Self {
+        self.env.insert(key.into(), None);
+        self
+    }
+
+    /// Returns the arguments passed to `java`: JVM arguments, main class and
+    /// game arguments.
+    pub fn args(&self) -> Vec<&str> {
+        self.jvm_args
+            .iter()
+            .map(String::as_str)
+            .chain([self.main_class.as_str()])
+            .chain(self.game_args.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Returns the command line for logs, with access tokens replaced by
+    /// `********`. Arguments are joined with spaces and not quoted.
+    pub fn command_line(&self) -> String {
+        let mut line = self.java.display().to_string();
+        let mut hide_next = false;
+        for arg in self.args() {
+            line.push(' ');
+            line.push_str(if hide_next { "********" } else { arg });
+            hide_next = SECRET_ARGUMENTS.contains(&arg);
+        }
+        line
+    }
+
+    /// Builds the command without starting it, for callers that need to
+    /// configure it further, e.g. to capture its output.
+    ///
+    /// Standard input is closed. In [`LaunchMode::Detached`], standard output
+    /// and error are discarded too.
+    pub fn to_command(&self, mode: LaunchMode) -> Command {
+        let mut command = Command::new(&self.java);
+        command
+            .args(self.args())
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null());
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        if mode == LaunchMode::Detached {
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+            detach(&mut command);
+        }
+        command
+    }
+
+    /// Starts the game.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Whether the game stays attached to the launcher.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GameProcess, ProcessError>` - The running game.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProcessError::JavaNotFound`] or
+    /// [`ProcessError::WorkingDirNotFound`] if a path is missing, or
+    /// [`ProcessError::Spawn`] if the process cannot be started.
+    pub fn spawn(&self, mode: LaunchMode) -> Result<GameProcess, ProcessError> {
+        self.check_paths()?;
+        GameProcess::spawn(self.to_command(mode), &self.working_dir)
+    }
+
+    pub(crate) fn check_paths(&self) -> Result<(), ProcessError> {
+        // A bare `java` is looked up in `PATH` when spawning.
+        if self.java.components().count() > 1 && !self.java.is_file() {
+            return Err(ProcessError::JavaNotFound(self.java.clone()));
+        }
+        if !self.working_dir.is_dir() {
+            return Err(ProcessError::WorkingDirNotFound(self.working_dir.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_command: &mut Command) {}
+
+/// A running game.
+#[derive(Debug)]
+pub struct GameProcess {
+    pid: u32,
+    pub(crate) child: Child,
+    pub(crate) game_dir: PathBuf,
+    pub(crate) started: SystemTime,
+}
+
+impl GameProcess {
+    pub(crate) fn spawn(mut command: Command, game_dir: &Path) -> Result<Self, ProcessError> {
+        let started = SystemTime::now();
+        let child = command.spawn()?;
+        // The id is only missing once the child has been waited for.
+        let pid = child.id().unwrap_or_default();
+        Ok(Self {
+            pid,
+            child,
+            game_dir: game_dir.to_path_buf(),
+            started,
+        })
+    }
+
+    /// Returns the process id.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Returns when the game was started.
+    pub fn started(&self) -> SystemTime {
+        self.started
+    }
+
+    /// Waits for the game to exit.
+    ///
+    /// Dropping the future does not stop the game, and waiting again returns
+    /// the same status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status cannot be read.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.child.wait().await
+    }
+
+    /// Returns the exit status if the game has exited, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status cannot be read.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    /// Forcibly stops the game and waits for it to exit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process cannot be killed.
+    pub async fn kill(&mut self) -> io::Result<()> {
+        self.child.kill().await
+    }
+
+    /// Returns the underlying child process, e.g. to take its output pipes.
+    pub fn child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assembles_arguments_and_hides_tokens() {
+        let command = LaunchCommand::new("java", "net.minecraft.client.main.Main", "/games/a")
+            .with_arguments(LaunchArguments {
+                jvm: vec!["-cp".to_string(), "a.jar".to_string()],
+                game: vec!["--accessToken".to_string(), "secret".to_string()],
+            })
+            .with_jvm_args(["-Xmx2G"])
+            .with_game_args(["--demo"]);
+        assert_eq!(
+            command.args(),
+            vec![
+                "-cp",
+                "a.jar",
+                "-Xmx2G",
+                "net.minecraft.client.main.Main",
+                "--accessToken",
+                "secret",
+                "--demo"
+            ]
+        );
+        assert_eq!(
+            command.command_line(),
+            "java -cp a.jar -Xmx2G net.minecraft.client.main.Main --accessToken ******** --demo"
+        );
+        assert!(matches!(
+            command.spawn(LaunchMode::Attached),
+            Err(ProcessError::WorkingDirNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawns_in_working_dir_with_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        // `sh -c script name arg` stands in for `java jvm-args main-class game-args`.
+        let command = LaunchCommand::new("/bin/sh", "main", dir.path())
+            .with_jvm_args(["-c", "echo \"$1 $GAME_VAR\" > out.txt; exit 3"])
+            .with_game_args(["arg"])
+            .with_env("GAME_VAR", "set")
+            .without_env("HOME");
+        for mode in [LaunchMode::Attached, LaunchMode::Detached] {
+            let mut process = command.spawn(mode).unwrap();
+            assert_ne!(process.pid(), 0);
+            let status = process.wait().await.unwrap();
+            assert_eq!(status.code(), Some(3));
+            assert_eq!(
+                std::fs::read_to_string(dir.path().join("out.txt")).unwrap(),
+                "arg set\n"
+            );
+        }
+    }
+}
@@ -0,0 +1,7 @@
+/// Assembling the `java` command line and starting the game.
+pub mod launch;
+/// Capturing the game's output and finding out why it exited.
+pub mod output;
+
+pub use launch::{GameProcess, LaunchCommand, LaunchMode, ProcessError};
+pub use output::{CRASH_REPORTS_DIR, GameExit, OutputLine, OutputSource, find_crash_report};
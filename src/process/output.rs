@@ -0,0 +1,230 @@
+use super::launch::{GameProcess, LaunchCommand, LaunchMode, ProcessError};
+use futures_util::Stream;
+use futures_util::stream;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::mpsc;
+
+/// Directory of the game directory where crash reports are written.
+pub const CRASH_REPORTS_DIR: &str = "crash-reports";
+
+/// Slack for file systems that store modification times coarsely, such as
+/// FAT with its two-second resolution.
+const TIMESTAMP_SLACK: Duration = Duration::from_secs(2);
+
+/// The pipe a line of output came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputSource {
+    Stdout,
+    Stderr,
+}
+
+/// A line the game wrote to standard output or error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    pub source: OutputSource,
+    /// The line without its line terminator.
+    pub line: String,
+}
+
+/// How the game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameExit {
+    pub status: ExitStatus,
+    /// Crash report the game wrote during the session, if any.
+    pub crash_report: Option<PathBuf>,
+    /// `hs_err_pid<pid>.log` the JVM wrote when it crashed itself, if any.
+    pub jvm_crash_log: Option<PathBuf>,
+}
+
+impl GameExit {
+    /// Returns whether the game crashed or exited with an error, rather than
+    /// being closed by the player.
+    pub fn is_abnormal(&self) -> bool {
+        !self.status.success() || self.crash_report.is_some() || self.jvm_crash_log.is_some()
+    }
+}
+
+impl LaunchCommand {
+    /// Starts the game attached to the launcher and captures its output.
+    ///
+    /// Output is buffered until read, so the game never blocks on a full
+    /// pipe; dropping the stream discards further output.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(GameProcess, impl Stream<Item = OutputLine>), ProcessError>` -
+    ///   The running game and its standard output and error, interleaved as
+    ///   they arrive. The stream ends when the game closes both pipes.
+    ///
+    /// # Errors
+    ///
+    /// See [`LaunchCommand::spawn`].
+    pub fn spawn_captured(
+        &self,
+    ) -> Result<(GameProcess, impl Stream<Item = OutputLine> + use<>), ProcessError> {
+        self.check_paths()?;
+        let mut command = self.to_command(LaunchMode::Attached);
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut process = GameProcess::spawn(command, &self.working_dir)?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        if let Some(stdout) = process.child.stdout.take() {
+            tokio::spawn(forward_lines(stdout, OutputSource::Stdout, sender.clone()));
+        }
+        if let Some(stderr) = process.child.stderr.take() {
+            tokio::spawn(forward_lines(stderr, OutputSource::Stderr, sender));
+        }
+        let lines = stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|line| (line, receiver))
+        });
+        Ok((process, lines))
+    }
+}
+
+/// Reads a pipe to its end, sending its lines while anyone listens.
+async fn forward_lines(
+    pipe: impl AsyncRead + Unpin,
+    source: OutputSource,
+    sender: mpsc::UnboundedSender<OutputLine>,
+) {
+    let mut reader = BufReader::new(pipe);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        let line = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        // Keep draining after the receiver is gone so the game never blocks.
+        let _ = sender.send(OutputLine {
+            source,
+            line: String::from_utf8_lossy(line).into_owned(),
+        });
+    }
+}
+
+impl GameProcess {
+    /// Waits for the game to exit and looks for the crash reports of the
+    /// session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the exit status cannot be read.
+    pub async fn wait_for_exit(&mut self) -> io::Result<GameExit> {
+        let status = self.wait().await?;
+        let since = self.started - TIMESTAMP_SLACK;
+        let jvm_crash_log = self.game_dir.join(format!("hs_err_pid{}.log", self.pid()));
+        Ok(GameExit {
+            status,
+            // A missing or unreadable directory means no report to show.
+            crash_report: find_crash_report(&self.game_dir, since).unwrap_or(None),
+            jvm_crash_log: jvm_crash_log.is_file().then_some(jvm_crash_log),
+        })
+    }
+}
+
+/// Finds the newest crash report written to a game directory since `since`.
+///
+/// # Arguments
+///
+/// * `game_dir` - The game directory.
+/// * `since` - When the session started.
+///
+/// # Returns
+///
+/// * `io::Result<Option<PathBuf>>` - The crash report, or `None` if none
+///   was written since `since`.
+///
+/// # Errors
+///
+/// Returns an error if the crash reports directory cannot be read.
+pub fn find_crash_report(game_dir: &Path, since: SystemTime) -> io::Result<Option<PathBuf>> {
+    let dir = game_dir.join(CRASH_REPORTS_DIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let is_report = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("crash-") && n.ends_with(".txt"));
+        if !is_report {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if modified >= since && newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, path));
+        }
+    }
+    Ok(newest.map(|(_, path)| path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn finds_only_reports_of_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            find_crash_report(dir.path(), SystemTime::now()).unwrap(),
+            None
+        );
+
+        let reports = dir.path().join(CRASH_REPORTS_DIR);
+        fs::create_dir(&reports).unwrap();
+        let old = reports.join("crash-2024-01-01_10.00.00-client.txt");
+        fs::write(&old, "old").unwrap();
+        let since = SystemTime::now() + Duration::from_secs(60);
+        assert_eq!(find_crash_report(dir.path(), since).unwrap(), None);
+
+        let since = SystemTime::now() - Duration::from_secs(60);
+        fs::write(reports.join("notes.txt"), "").unwrap();
+        assert_eq!(find_crash_report(dir.path(), since).unwrap(), Some(old));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn captures_output_and_detects_crashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = "echo starting; echo 'bad thing' >&2; mkdir crash-reports; \
+                      echo report > crash-reports/crash-2024-03-01_12.00.00-client.txt; exit 255";
+        let command =
+            LaunchCommand::new("/bin/sh", "main", dir.path()).with_jvm_args(["-c", script]);
+        let (mut process, lines) = command.spawn_captured().unwrap();
+        let lines: Vec<OutputLine> = lines.collect().await;
+        let exit = process.wait_for_exit().await.unwrap();
+
+        assert!(lines.contains(&OutputLine {
+            source: OutputSource::Stdout,
+            line: "starting".to_string()
+        }));
+        assert!(lines.contains(&OutputLine {
+            source: OutputSource::Stderr,
+            line: "bad thing".to_string()
+        }));
+        assert_eq!(exit.status.code(), Some(255));
+        assert!(exit.is_abnormal());
+        assert_eq!(
+            exit.crash_report,
+            Some(
+                dir.path()
+                    .join("crash-reports/crash-2024-03-01_12.00.00-client.txt")
+            )
+        );
+        assert_eq!(exit.jvm_crash_log, None);
+    }
+}
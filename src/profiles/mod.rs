@@ -0,0 +1,13 @@
+/// Name to UUID lookups and public profiles from the Mojang APIs.
+pub mod mojang;
+/// Skin validation, head avatars and changing the skin and cape of an account.
+pub mod skins;
+
+pub use mojang::{
+    BULK_LOOKUP_LIMIT, MOJANG_API_URL, MojangApi, PlayerId, ProfileError, ProfileProperty,
+    ProfileTextures, PublicProfile, SESSION_SERVER_URL, Texture, TextureMetadata, TextureSet,
+    hyphenate_uuid,
+};
+pub use skins::{
+    MAX_AVATAR_SIZE, PlayerHead, SkinError, SkinImage, SkinLayout, SkinModel, render_head,
+};
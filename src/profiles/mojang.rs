@@ -0,0 +1,440 @@
+use crate::http::{HttpClient, HttpError};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Base URL of the Mojang account API.
+pub const MOJANG_API_URL: &str = "https://api.mojang.com";
+
+/// Base URL of the session server, which serves profiles with their textures.
+pub const SESSION_SERVER_URL: &str = "https://sessionserver.mojang.com";
+
+/// Most names the bulk lookup endpoint accepts per request.
+pub const BULK_LOOKUP_LIMIT: usize = 10;
+
+/// Error returned when a profile lookup fails.
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[error(transparent)]
+    Http(HttpError),
+    #[error("invalid profile response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid profile textures: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("rate limited by the Mojang API")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("invalid player name: {0:?}")]
+    InvalidName(String),
+    #[error("invalid player UUID: {0:?}")]
+    InvalidUuid(String),
+}
+
+impl From<HttpError> for ProfileError {
+    fn from(error: HttpError) -> Self {
+        match error {
+            HttpError::Status {
+                status: 429,
+                retry_after,
+                ..
+            } => ProfileError::RateLimited { retry_after },
+            other => ProfileError::Http(other),
+        }
+    }
+}
+
+/// The Mojang profile endpoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MojangApi {
+    /// Base URL of the account API, without trailing slash.
+    pub api_url: String,
+    /// Base URL of the session server, without trailing slash.
+    pub session_url: String,
+}
+
+impl Default for MojangApi {
+    fn default() -> Self {
+        Self {
+            api_url: MOJANG_API_URL.to_string(),
+            session_url: SESSION_SERVER_URL.to_string(),
+        }
+    }
+}
+
+impl MojangApi {
+    /// Creates the configuration of the official endpoints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets both base URLs to one server, e.g. a test server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        self.api_url = base_url.to_string();
+        self.session_url = base_url.to_string();
+        self
+    }
+}
+
+/// A player's UUID and the current spelling of their name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PlayerId {
+    /// UUID without hyphens.
+    pub id: String,
+    pub name: String,
+}
+
+impl PlayerId {
+    /// Returns the UUID in the hyphenated form used by server files.
+    pub fn hyphenated_id(&self) -> String {
+        hyphenate_uuid(&self.id).unwrap_or_else(|| self.id.clone())
+    }
+}
+
+/// Adds hyphens to a UUID written without them, as the Mojang APIs return it.
+///
+/// # Returns
+///
+/// * `Option<String>` - The hyphenated UUID, or `None` if `uuid` is not 32
+///   hexadecimal digits. Already hyphenated UUIDs are returned unchanged.
+pub fn hyphenate_uuid(uuid: &str) -> Option<String> {
+    let simple = simple_uuid(uuid)?;
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &simple[..8],
+        &simple[8..12],
+        &simple[12..16],
+        &simple[16..20],
+        &simple[20..]
+    ))
+}
+
+/// Returns a UUID in lowercase without hyphens, or `None` if it is malformed.
+fn simple_uuid(uuid: &str) -> Option<String> {
+    let hyphenated = uuid.contains('-');
+    if hyphenated && !crate::server::is_valid_uuid(uuid) {
+        return None;
+    }
+    let simple: String = uuid.chars().filter(|c| *c != '-').collect();
+    (simple.len() == 32 && simple.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| simple.to_ascii_lowercase())
+}
+
+/// Checks that `name` could be a player name: letters, digits and underscores,
+/// up to 25 characters since some old accounts exceed today's limit of 16.
+fn is_valid_name(name: &str) -> bool {
+    (1..=25).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A profile as served by the session server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicProfile {
+    /// UUID without hyphens.
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl PublicProfile {
+    /// Decodes the `textures` property.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<ProfileTextures>, ProfileError>` - The textures, or
+    ///   `None` if the profile has no `textures` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property is not valid base64-encoded JSON.
+    pub fn textures(&self) -> Result<Option<ProfileTextures>, ProfileError> {
+        let Some(property) = self.properties.iter().find(|p| p.name == "textures") else {
+            return Ok(None);
+        };
+        let json = STANDARD.decode(&property.value)?;
+        Ok(Some(serde_json::from_slice(&json)?))
+    }
+}
+
+/// A signed property of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileProperty {
+    pub name: String,
+    /// Base64-encoded value.
+    pub value: String,
+    /// Signature by Mojang, present when requested with `unsigned=false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// The decoded `textures` property of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileTextures {
+    /// When the property was generated, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub profile_id: String,
+    pub profile_name: String,
+    pub textures: TextureSet,
+}
+
+/// The skin and cape of a profile. Players without a skin use a default one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub struct TextureSet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skin: Option<Texture>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cape: Option<Texture>,
+}
+
+/// A texture of a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Texture {
+    /// URL of the PNG on `textures.minecraft.net`.
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<TextureMetadata>,
+}
+
+impl Texture {
+    /// Returns whether a skin uses the slim ("Alex") model.
+    pub fn is_slim(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.model.as_deref())
+            .is_some_and(|model| model == "slim")
+    }
+}
+
+/// Metadata of a texture.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextureMetadata {
+    /// `slim` for skins using the slim model; absent for the classic model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl HttpClient {
+    /// Looks up the UUID of a player name.
+    ///
+    /// Requests are subject to Mojang's rate limit. Responses with status 429
+    /// are retried according to the client's retry policy, honoring
+    /// `Retry-After`, and reported as [`ProfileError::RateLimited`] if the
+    /// retries run out.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<PlayerId>, ProfileError>` - The player, or `None` if no
+    ///   account has that name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::InvalidName`] if `name` cannot be a player name,
+    /// or another error if the request fails.
+    pub async fn lookup_player(
+        &self,
+        api: &MojangApi,
+        name: &str,
+    ) -> Result<Option<PlayerId>, ProfileError> {
+        if !is_valid_name(name) {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        let url = format!("{}/users/profiles/minecraft/{}", api.api_url, name);
+        self.get_optional_json(&url).await
+    }
+
+    /// Looks up the UUIDs of several player names, [`BULK_LOOKUP_LIMIT`] per
+    /// request.
+    ///
+    /// Rate limiting is handled as in [`HttpClient::lookup_player`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<PlayerId>, ProfileError>` - The players found. Names
+    ///   without an account are left out, so the result may be shorter than
+    ///   `names`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::InvalidName`] if a name cannot be a player name,
+    /// or another error if a request fails.
+    pub async fn lookup_players(
+        &self,
+        api: &MojangApi,
+        names: &[&str],
+    ) -> Result<Vec<PlayerId>, ProfileError> {
+        if let Some(name) = names.iter().find(|name| !is_valid_name(name)) {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        let url = format!("{}/profiles/minecraft", api.api_url);
+        let mut players = Vec::with_capacity(names.len());
+        for chunk in names.chunks(BULK_LOOKUP_LIMIT) {
+            // The lookup has no side effects, so it is safe to retry.
+            let found: Vec<PlayerId> = self.with_retry(|| self.post_json(&url, chunk)).await?;
+            players.extend(found);
+        }
+        Ok(players)
+    }
+
+    /// Fetches a profile with its signed textures from the session server.
+    ///
+    /// Rate limiting is handled as in [`HttpClient::lookup_player`].
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - The endpoints.
+    /// * `uuid` - The player's UUID, with or without hyphens.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<PublicProfile>, ProfileError>` - The profile, or `None`
+    ///   if no player has that UUID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::InvalidUuid`] if `uuid` is malformed, or another
+    /// error if the request fails.
+    pub async fn fetch_public_profile(
+        &self,
+        api: &MojangApi,
+        uuid: &str,
+    ) -> Result<Option<PublicProfile>, ProfileError> {
+        let simple =
+            simple_uuid(uuid).ok_or_else(|| ProfileError::InvalidUuid(uuid.to_string()))?;
+        let url = format!(
+            "{}/session/minecraft/profile/{}?unsigned=false",
+            api.session_url, simple
+        );
+        self.get_optional_json(&url).await
+    }
+
+    /// Fetches JSON that may not exist: both 404 and an empty 204 response
+    /// mean `None`, as the Mojang APIs have used either.
+    async fn get_optional_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<Option<T>, ProfileError> {
+        match self.get_bytes(url).await {
+            Ok(body) if body.iter().all(u8::is_ascii_whitespace) => Ok(None),
+            Ok(body) => Ok(Some(serde_json::from_slice(&body)?)),
+            Err(HttpError::Status { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{ClientOptions, RetryPolicy};
+    use httpmock::prelude::*;
+
+    fn client() -> HttpClient {
+        HttpClient::new(ClientOptions {
+            retry: RetryPolicy::none(),
+            ..ClientOptions::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn looks_up_names_and_profiles() {
+        let server = MockServer::start();
+        let api = MojangApi::new().with_base_url(&server.base_url());
+        let client = client();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/users/profiles/minecraft/Notch");
+            then.status(200).json_body(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch"
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/users/profiles/minecraft/nobody");
+            then.status(404)
+                .json_body(serde_json::json!({"errorMessage": "not found"}));
+        });
+        let notch = client.lookup_player(&api, "Notch").await.unwrap().unwrap();
+        assert_eq!(
+            notch.hyphenated_id(),
+            "069a79f4-44e9-4726-a5be-fca90e38aaf5"
+        );
+        assert_eq!(client.lookup_player(&api, "nobody").await.unwrap(), None);
+        assert!(matches!(
+            client.lookup_player(&api, "../evil").await,
+            Err(ProfileError::InvalidName(_))
+        ));
+
+        let textures = serde_json::json!({
+            "timestamp": 1700000000000u64,
+            "profileId": "069a79f444e94726a5befca90e38aaf5",
+            "profileName": "Notch",
+            "textures": {
+                "SKIN": {"url": "http://textures.minecraft.net/texture/a", "metadata": {"model": "slim"}},
+                "CAPE": {"url": "http://textures.minecraft.net/texture/b"}
+            }
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/session/minecraft/profile/069a79f444e94726a5befca90e38aaf5")
+                .query_param("unsigned", "false");
+            then.status(200).json_body(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch",
+                "properties": [{
+                    "name": "textures",
+                    "value": STANDARD.encode(textures.to_string()),
+                    "signature": "sig"
+                }]
+            }));
+        });
+        let profile = client
+            .fetch_public_profile(&api, "069a79f4-44e9-4726-a5be-fca90e38aaf5")
+            .await
+            .unwrap()
+            .unwrap();
+        let textures = profile.textures().unwrap().unwrap();
+        assert!(textures.textures.skin.unwrap().is_slim());
+        assert!(!textures.textures.cape.unwrap().is_slim());
+    }
+
+    #[tokio::test]
+    async fn bulk_lookup_chunks_names_and_reports_rate_limits() {
+        let server = MockServer::start();
+        let api = MojangApi::new().with_base_url(&server.base_url());
+        let client = client();
+        let names: Vec<String> = (0..12).map(|i| format!("player{}", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        let mut first = server.mock(|when, then| {
+            when.method(POST)
+                .path("/profiles/minecraft")
+                .json_body(serde_json::json!(names[..10]));
+            then.status(200)
+                .json_body(serde_json::json!([{"id": "0".repeat(32), "name": "Player0"}]));
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/profiles/minecraft")
+                .json_body(serde_json::json!(names[10..]));
+            then.status(200)
+                .json_body(serde_json::json!([{"id": "1".repeat(32), "name": "player11"}]));
+        });
+        let players = client.lookup_players(&api, &names).await.unwrap();
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].name, "Player0");
+        first.delete();
+
+        server.mock(|when, then| {
+            when.method(POST).path("/profiles/minecraft");
+            then.status(429).header("Retry-After", "30");
+        });
+        assert!(matches!(
+            client.lookup_players(&api, &names).await,
+            Err(ProfileError::RateLimited {
+                retry_after: Some(d)
+            }) if d == Duration::from_secs(30)
+        ));
+    }
+}
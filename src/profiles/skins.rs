@@ -0,0 +1,542 @@
+use crate::auth::{MinecraftProfile, MinecraftServicesApi, MinecraftToken};
+use crate::http::{HttpClient, HttpError, bearer};
+use reqwest::multipart::{Form, Part};
+use serde_json::json;
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+use thiserror::Error;
+
+/// Error returned when a skin is invalid or cannot be changed.
+#[derive(Debug, Error)]
+pub enum SkinError {
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error("failed to read skin: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid profile response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid PNG: {0}")]
+    Png(#[from] png::DecodingError),
+    #[error("failed to encode PNG: {0}")]
+    Encode(#[from] png::EncodingError),
+    #[error("a skin must be 64x64 or 64x32 pixels, not {width}x{height}")]
+    InvalidSize { width: u32, height: u32 },
+    #[error("avatar size must be between 1 and {MAX_AVATAR_SIZE} pixels")]
+    InvalidAvatarSize,
+}
+
+/// The arm model of a skin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SkinModel {
+    /// Four pixel wide arms ("Steve").
+    #[default]
+    Classic,
+    /// Three pixel wide arms ("Alex").
+    Slim,
+}
+
+impl SkinModel {
+    /// Returns the name the Minecraft services API uses for the model.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkinModel::Classic => "classic",
+            SkinModel::Slim => "slim",
+        }
+    }
+
+    /// Parses a `variant` of [`crate::auth::ProfileSkin`], e.g. `SLIM`.
+    pub fn from_variant(variant: &str) -> Option<Self> {
+        if variant.eq_ignore_ascii_case("classic") {
+            Some(SkinModel::Classic)
+        } else if variant.eq_ignore_ascii_case("slim") {
+            Some(SkinModel::Slim)
+        } else {
+            None
+        }
+    }
+}
+
+/// The layout of a skin texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkinLayout {
+    /// 64x64, with separate left limbs and second layers for all body parts.
+    Modern,
+    /// 64x32, from before 1.8; left limbs mirror the right ones.
+    Legacy,
+}
+
+/// A validated skin PNG.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinImage {
+    /// The PNG file.
+    pub png: Vec<u8>,
+    pub layout: SkinLayout,
+    /// The model detected from the texture. Legacy skins are always classic.
+    pub model: SkinModel,
+}
+
+impl SkinImage {
+    /// Validates a skin PNG and detects its model.
+    ///
+    /// A modern skin is slim if the pixels that only classic arms use are all
+    /// transparent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SkinError::Png`] if `png` is not a PNG, or
+    /// [`SkinError::InvalidSize`] if it has the wrong dimensions.
+    pub fn from_png(png: Vec<u8>) -> Result<Self, SkinError> {
+        let (layout, pixels) = decode_skin(&png)?;
+        let model = match layout {
+            SkinLayout::Legacy => SkinModel::Classic,
+            SkinLayout::Modern => detect_model(&pixels),
+        };
+        Ok(Self { png, layout, model })
+    }
+
+    /// Extracts the face of the skin with the hat layer drawn over it.
+    ///
+    /// Like the game, a legacy skin whose hat layer has no transparent pixel
+    /// is treated as having no hat, since many old skins filled it with a
+    /// solid color.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PNG cannot be decoded.
+    pub fn head(&self) -> Result<PlayerHead, SkinError> {
+        let (layout, pixels) = decode_skin(&self.png)?;
+        let mut face = [0; HEAD_PIXELS * 4];
+        let mut hat = [0; HEAD_PIXELS * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let i = (y * 8 + x) * 4;
+                let face_at = ((8 + y) * 64 + 8 + x) * 4;
+                let hat_at = ((8 + y) * 64 + 40 + x) * 4;
+                face[i..i + 4].copy_from_slice(&pixels[face_at..face_at + 4]);
+                hat[i..i + 4].copy_from_slice(&pixels[hat_at..hat_at + 4]);
+            }
+        }
+        let hat_is_fill = hat.chunks(4).all(|p| p[3] == 255);
+        if layout == SkinLayout::Modern || !hat_is_fill {
+            for (face, hat) in face.chunks_mut(4).zip(hat.chunks(4)) {
+                blend_over(face, hat);
+            }
+        }
+        // The face itself is opaque in game.
+        for pixel in face.chunks_mut(4) {
+            pixel[3] = 255;
+        }
+        Ok(PlayerHead { pixels: face })
+    }
+
+    /// Reads and validates a skin PNG file.
+    ///
+    /// # Errors
+    ///
+    /// See [`SkinImage::from_png`].
+    pub fn load(path: &Path) -> Result<Self, SkinError> {
+        Self::from_png(fs::read(path)?)
+    }
+
+    /// Sets the model to upload the skin with, overriding the detected one.
+    pub fn with_model(mut self, model: SkinModel) -> Self {
+        self.model = model;
+        self
+    }
+}
+
+/// Areas of the right arm that only classic arms cover, as `(x, y, width, height)`.
+const CLASSIC_ONLY_AREAS: [(usize, usize, usize, usize); 2] = [(50, 16, 2, 4), (54, 20, 2, 12)];
+
+fn detect_model(pixels: &[u8]) -> SkinModel {
+    let transparent = CLASSIC_ONLY_AREAS.iter().all(|&(x, y, w, h)| {
+        (y..y + h).all(|row| (x..x + w).all(|col| pixels[(row * 64 + col) * 4 + 3] == 0))
+    });
+    if transparent {
+        SkinModel::Slim
+    } else {
+        SkinModel::Classic
+    }
+}
+
+/// Decodes a skin to 8-bit RGBA after checking its dimensions.
+fn decode_skin(png: &[u8]) -> Result<(SkinLayout, Vec<u8>), SkinError> {
+    let mut decoder = png::Decoder::new(Cursor::new(png));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let (width, height) = reader.info().size();
+    let layout = match (width, height) {
+        (64, 64) => SkinLayout::Modern,
+        (64, 32) => SkinLayout::Legacy,
+        _ => return Err(SkinError::InvalidSize { width, height }),
+    };
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut buffer)?;
+    let (color_type, _) = reader.output_color_type();
+    let rgba = match color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        // Indexed images are expanded to RGB(A) by the transformations.
+        png::ColorType::Grayscale | png::ColorType::Indexed => {
+            buffer.iter().flat_map(|&v| [v, v, v, 255]).collect()
+        }
+    };
+    Ok((layout, rgba))
+}
+
+/// Draws the RGBA pixel `top` over `bottom`.
+fn blend_over(bottom: &mut [u8], top: &[u8]) {
+    let alpha = u32::from(top[3]);
+    let bottom_alpha = u32::from(bottom[3]) * (255 - alpha) / 255;
+    let out_alpha = alpha + bottom_alpha;
+    if out_alpha == 0 {
+        bottom.fill(0);
+        return;
+    }
+    for c in 0..3 {
+        let color = u32::from(top[c]) * alpha + u32::from(bottom[c]) * bottom_alpha;
+        bottom[c] = (color / out_alpha) as u8;
+    }
+    bottom[3] = out_alpha as u8;
+}
+
+const HEAD_PIXELS: usize = 8 * 8;
+
+/// Largest avatar [`PlayerHead`] renders, in pixels per side: 4 MiB of RGBA.
+pub const MAX_AVATAR_SIZE: u32 = 1024;
+
+/// The 8x8 face of a skin with its hat layer, for account avatars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerHead {
+    /// Opaque RGBA pixels, row by row.
+    pub pixels: [u8; HEAD_PIXELS * 4],
+}
+
+impl PlayerHead {
+    /// Scales the head to `size`x`size` pixels without smoothing, keeping
+    /// the pixel-art look.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, SkinError>` - RGBA pixels, row by row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SkinError::InvalidAvatarSize`] if `size` is 0 or larger
+    /// than [`MAX_AVATAR_SIZE`].
+    pub fn to_rgba(&self, size: u32) -> Result<Vec<u8>, SkinError> {
+        if size == 0 || size > MAX_AVATAR_SIZE {
+            return Err(SkinError::InvalidAvatarSize);
+        }
+        let size = size as usize;
+        let mut rgba = Vec::with_capacity(size * size * 4);
+        for y in 0..size {
+            for x in 0..size {
+                let i = ((y * 8 / size) * 8 + x * 8 / size) * 4;
+                rgba.extend_from_slice(&self.pixels[i..i + 4]);
+            }
+        }
+        Ok(rgba)
+    }
+
+    /// Scales the head to `size`x`size` pixels and encodes it as a PNG.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SkinError::InvalidAvatarSize`] if `size` is 0 or larger
+    /// than [`MAX_AVATAR_SIZE`].
+    pub fn to_png(&self, size: u32) -> Result<Vec<u8>, SkinError> {
+        let rgba = self.to_rgba(size)?;
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, size, size);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&rgba)?;
+        writer.finish()?;
+        Ok(png)
+    }
+}
+
+/// Renders the head of a skin PNG as a `size`x`size` PNG avatar.
+///
+/// # Errors
+///
+/// Returns an error if `png` is not a valid skin or `size` is 0 or larger
+/// than [`MAX_AVATAR_SIZE`].
+pub fn render_head(png: Vec<u8>, size: u32) -> Result<Vec<u8>, SkinError> {
+    SkinImage::from_png(png)?.head()?.to_png(size)
+}
+
+impl HttpClient {
+    /// Downloads and validates a skin, e.g. from [`crate::auth::ProfileSkin::url`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the download fails or the file is not a valid skin.
+    pub async fn download_skin(&self, url: &str) -> Result<SkinImage, SkinError> {
+        SkinImage::from_png(self.get_bytes(url).await?)
+    }
+
+    /// Uploads a skin to the account of `token`, using [`SkinImage::model`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MinecraftProfile, SkinError>` - The updated profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the upload is rejected.
+    pub async fn upload_skin(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+        skin: &SkinImage,
+    ) -> Result<MinecraftProfile, SkinError> {
+        let file = Part::bytes(skin.png.clone())
+            .file_name("skin.png")
+            .mime_str("image/png")
+            .map_err(HttpError::from)?;
+        let form = Form::new()
+            .text("variant", skin.model.as_str())
+            .part("file", file);
+        Ok(self
+            .with_bearer_token(&token.access_token)
+            .post_multipart(&api.url("minecraft/profile/skins"), form)
+            .await?)
+    }
+
+    /// Sets the skin of the account of `token` to a PNG at a public URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change is rejected.
+    pub async fn set_skin_url(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+        skin_url: &str,
+        model: SkinModel,
+    ) -> Result<MinecraftProfile, SkinError> {
+        let body = json!({"variant": model.as_str(), "url": skin_url});
+        Ok(self
+            .with_bearer_token(&token.access_token)
+            .post_json(&api.url("minecraft/profile/skins"), &body)
+            .await?)
+    }
+
+    /// Resets the skin of the account of `token` to a default skin.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change is rejected.
+    pub async fn reset_skin(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+    ) -> Result<MinecraftProfile, SkinError> {
+        self.delete_profile_part(&api.url("minecraft/profile/skins/active"), token)
+            .await
+    }
+
+    /// Shows one of the capes of the account of `token`, by
+    /// [`crate::auth::ProfileCape::id`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account does not own the cape.
+    pub async fn show_cape(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+        cape_id: &str,
+    ) -> Result<MinecraftProfile, SkinError> {
+        let body = json!({"capeId": cape_id});
+        Ok(self
+            .with_bearer_token(&token.access_token)
+            .put_json(&api.url("minecraft/profile/capes/active"), &body)
+            .await?)
+    }
+
+    /// Hides the cape of the account of `token`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change is rejected.
+    pub async fn hide_cape(
+        &self,
+        api: &MinecraftServicesApi,
+        token: &MinecraftToken,
+    ) -> Result<MinecraftProfile, SkinError> {
+        self.delete_profile_part(&api.url("minecraft/profile/capes/active"), token)
+            .await
+    }
+
+    async fn delete_profile_part(
+        &self,
+        url: &str,
+        token: &MinecraftToken,
+    ) -> Result<MinecraftProfile, SkinError> {
+        let builder = self
+            .request(reqwest::Method::DELETE, url, &[])
+            .header("Authorization", bearer(&token.access_token));
+        let (status, body) = self.send_raw(url, builder).await?;
+        if !(200..300).contains(&status) {
+            return Err(HttpError::Status {
+                url: url.to_string(),
+                status,
+                retry_after: None,
+            }
+            .into());
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use httpmock::prelude::*;
+
+    /// Encodes an opaque RGBA skin, with the classic-only arm areas cleared if
+    /// `slim`.
+    fn skin_png(width: u32, height: u32, slim: bool) -> Vec<u8> {
+        let mut pixels = vec![255u8; (width * height * 4) as usize];
+        if slim {
+            for (x, y, w, h) in CLASSIC_ONLY_AREAS {
+                for row in y..y + h {
+                    for col in x..x + w {
+                        pixels[(row * 64 + col) * 4 + 3] = 0;
+                    }
+                }
+            }
+        }
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    #[test]
+    fn validates_size_and_detects_model() {
+        let classic = SkinImage::from_png(skin_png(64, 64, false)).unwrap();
+        assert_eq!(classic.layout, SkinLayout::Modern);
+        assert_eq!(classic.model, SkinModel::Classic);
+
+        let slim = SkinImage::from_png(skin_png(64, 64, true)).unwrap();
+        assert_eq!(slim.model, SkinModel::Slim);
+
+        let legacy = SkinImage::from_png(skin_png(64, 32, false)).unwrap();
+        assert_eq!(legacy.layout, SkinLayout::Legacy);
+        assert_eq!(legacy.model, SkinModel::Classic);
+
+        assert!(matches!(
+            SkinImage::from_png(skin_png(128, 128, false)),
+            Err(SkinError::InvalidSize {
+                width: 128,
+                height: 128
+            })
+        ));
+        assert!(matches!(
+            SkinImage::from_png(b"not a png".to_vec()),
+            Err(SkinError::Png(_))
+        ));
+        assert_eq!(SkinModel::from_variant("SLIM"), Some(SkinModel::Slim));
+    }
+
+    #[test]
+    fn renders_head_with_hat_overlay() {
+        let mut pixels = vec![0u8; 64 * 64 * 4];
+        let mut set = |x: usize, y: usize, rgba: [u8; 4]| {
+            pixels[(y * 64 + x) * 4..][..4].copy_from_slice(&rgba);
+        };
+        for y in 8..16 {
+            for x in 8..16 {
+                set(x, y, [200, 0, 0, 255]);
+            }
+        }
+        set(40, 8, [0, 0, 200, 255]);
+        set(41, 8, [0, 0, 200, 128]);
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, 64, 64);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+
+        let head = SkinImage::from_png(png.clone()).unwrap().head().unwrap();
+        assert_eq!(head.pixels[..4], [0, 0, 200, 255]);
+        assert_eq!(head.pixels[4..8], [99, 0, 100, 255]);
+        assert_eq!(head.pixels[8..12], [200, 0, 0, 255]);
+
+        let avatar = head.to_rgba(32).unwrap();
+        assert_eq!(avatar.len(), 32 * 32 * 4);
+        assert_eq!(avatar[3 * 4..4 * 4], [0, 0, 200, 255]);
+        assert_eq!(avatar[4 * 4..5 * 4], [99, 0, 100, 255]);
+
+        let avatar = render_head(png, 16).unwrap();
+        let reader = png::Decoder::new(Cursor::new(avatar)).read_info().unwrap();
+        assert_eq!(reader.info().size(), (16, 16));
+        assert!(matches!(head.to_png(0), Err(SkinError::InvalidAvatarSize)));
+        assert!(matches!(
+            head.to_rgba(MAX_AVATAR_SIZE + 1),
+            Err(SkinError::InvalidAvatarSize)
+        ));
+    }
+
+    #[tokio::test]
+    async fn uploads_skins_and_changes_capes() {
+        let server = MockServer::start();
+        let api = MinecraftServicesApi::new().with_base_url(server.base_url());
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+        let token = MinecraftToken {
+            access_token: "mc".to_string(),
+            expires_at: u64::MAX,
+        };
+        let profile = serde_json::json!({"id": "uuid", "name": "Notch", "skins": [], "capes": []});
+
+        let upload = server.mock(|when, then| {
+            when.method(POST)
+                .path("/minecraft/profile/skins")
+                .header("Authorization", "Bearer mc")
+                .body_contains("name=\"variant\"\r\n\r\nslim")
+                .body_contains("filename=\"skin.png\"");
+            then.status(200).json_body(profile.clone());
+        });
+        let skin = SkinImage::from_png(skin_png(64, 64, true)).unwrap();
+        let updated = client.upload_skin(&api, &token, &skin).await.unwrap();
+        assert_eq!(updated.name, "Notch");
+        upload.assert();
+
+        server.mock(|when, then| {
+            when.method(PUT)
+                .path("/minecraft/profile/capes/active")
+                .json_body(serde_json::json!({"capeId": "cape"}));
+            then.status(400);
+        });
+        assert!(matches!(
+            client.show_cape(&api, &token, "cape").await,
+            Err(SkinError::Http(HttpError::Status { status: 400, .. }))
+        ));
+
+        let hide = server.mock(|when, then| {
+            when.method(DELETE).path("/minecraft/profile/capes/active");
+            then.status(200).json_body(profile.clone());
+        });
+        client.hide_cape(&api, &token).await.unwrap();
+        hide.assert();
+    }
+}
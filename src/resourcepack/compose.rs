@@ -0,0 +1,327 @@
+use super::ResourcePackError;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+use zip::write::SimpleFileOptions;
+
+/// The metadata file at the root of every pack.
+pub const PACK_MCMETA: &str = "pack.mcmeta";
+/// The icon shown in the pack list.
+pub const PACK_ICON: &str = "pack.png";
+
+/// Files left behind by file managers, which are never copied.
+const JUNK_FILES: [&str; 3] = [".DS_Store", "Thumbs.db", "desktop.ini"];
+
+/// Builds one resource pack out of several source packs.
+///
+/// Sources are folders or zips, applied in order: a file present in several
+/// sources is taken from the last one, the way the game stacks enabled packs.
+/// The `pack.mcmeta` of the sources is replaced by a generated one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackComposer {
+    /// The `pack_format` of the game version the pack is made for.
+    pub pack_format: u32,
+    /// The description shown in the pack list.
+    pub description: String,
+    /// Source packs, lowest priority first.
+    pub sources: Vec<PathBuf>,
+    /// PNG used as `pack.png` instead of the icon of the sources.
+    pub icon: Option<PathBuf>,
+}
+
+/// What [`PackComposer::compose`] wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComposeReport {
+    /// Number of files in the pack, including the generated `pack.mcmeta`.
+    pub files: usize,
+    /// Files provided by several sources and taken from the last of them.
+    pub overridden: Vec<String>,
+    /// The `pack_format` of each source, or `None` if it has no readable
+    /// `pack.mcmeta`. Sources made for other formats may not work.
+    pub source_formats: Vec<Option<u32>>,
+}
+
+impl PackComposer {
+    /// Creates a composer without sources.
+    pub fn new(pack_format: u32, description: impl Into<String>) -> Self {
+        Self {
+            pack_format,
+            description: description.into(),
+            sources: Vec::new(),
+            icon: None,
+        }
+    }
+
+    /// Adds a source pack, taking precedence over the sources added before.
+    pub fn with_source(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(path.into());
+        self
+    }
+
+    /// Uses a PNG file as the pack icon.
+    pub fn with_icon(mut self, path: impl Into<PathBuf>) -> Self {
+        self.icon = Some(path.into());
+        self
+    }
+
+    /// Writes the combined pack as a zip.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest` - The zip to create, e.g. in the instance's `resourcepacks`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ComposeReport, ResourcePackError>` - The files written and
+    ///   the formats of the sources.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ResourcePackError::NotAPack`] if a source is missing, or an
+    /// error if a source cannot be read or the zip cannot be written.
+    pub fn compose(&self, dest: &Path) -> Result<ComposeReport, ResourcePackError> {
+        let mut sources = self
+            .sources
+            .iter()
+            .map(|path| Source::open(path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut report = ComposeReport::default();
+        let mut chosen: BTreeMap<String, usize> = BTreeMap::new();
+        for (index, source) in sources.iter_mut().enumerate() {
+            report.source_formats.push(source.pack_format());
+            for name in source.files()? {
+                let generated = name == PACK_MCMETA || (name == PACK_ICON && self.icon.is_some());
+                if generated {
+                    continue;
+                }
+                if chosen.insert(name.clone(), index).is_some() {
+                    report.overridden.push(name);
+                }
+            }
+        }
+        report.overridden.sort();
+        report.overridden.dedup();
+
+        let mcmeta = json!({
+            "pack": { "pack_format": self.pack_format, "description": self.description }
+        });
+        let mut writer = zip::ZipWriter::new(File::create(dest)?);
+        writer.start_file(PACK_MCMETA, SimpleFileOptions::default())?;
+        writer.write_all(&serde_json::to_vec_pretty(&mcmeta)?)?;
+        report.files = 1;
+        if let Some(icon) = &self.icon {
+            writer.start_file(PACK_ICON, SimpleFileOptions::default())?;
+            io::copy(&mut File::open(icon)?, &mut writer)?;
+            report.files += 1;
+        }
+        for (name, index) in &chosen {
+            writer.start_file(name.as_str(), SimpleFileOptions::default())?;
+            sources[*index].copy_to(name, &mut writer)?;
+        }
+        report.files += chosen.len();
+        writer.finish()?;
+        Ok(report)
+    }
+}
+
+/// An opened pack folder or zip.
+pub(super) enum Source {
+    Folder(PathBuf),
+    Zip {
+        archive: ZipArchive<BufReader<File>>,
+        /// Folder wrapping the whole pack inside the zip, e.g. `MyPack/`.
+        prefix: String,
+    },
+}
+
+impl Source {
+    pub(super) fn open(path: &Path) -> Result<Self, ResourcePackError> {
+        if path.is_dir() {
+            return Ok(Source::Folder(path.to_path_buf()));
+        }
+        if !path.is_file() {
+            return Err(ResourcePackError::NotAPack(path.to_path_buf()));
+        }
+        let archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+        // Packs zipped together with their folder still load in the game.
+        let prefix = if archive.index_for_name(PACK_MCMETA).is_some() {
+            String::new()
+        } else {
+            archive
+                .file_names()
+                .find_map(|name| {
+                    let folder = name.strip_suffix(PACK_MCMETA)?;
+                    let inner = folder.strip_suffix('/')?;
+                    (!inner.is_empty() && !inner.contains('/')).then(|| folder.to_string())
+                })
+                .unwrap_or_default()
+        };
+        Ok(Source::Zip { archive, prefix })
+    }
+
+    /// Lists the files of the pack, relative and with `/` separators.
+    pub(super) fn files(&self) -> io::Result<Vec<String>> {
+        let mut files = Vec::new();
+        match self {
+            Source::Folder(root) => {
+                let mut pending = vec![String::new()];
+                while let Some(relative) = pending.pop() {
+                    for entry in fs::read_dir(root.join(&relative))? {
+                        let entry = entry?;
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        let path = if relative.is_empty() {
+                            name
+                        } else {
+                            format!("{}/{}", relative, name)
+                        };
+                        if entry.file_type()?.is_dir() {
+                            pending.push(path);
+                        } else {
+                            files.push(path);
+                        }
+                    }
+                }
+            }
+            Source::Zip { archive, prefix } => {
+                files.extend(
+                    archive
+                        .file_names()
+                        .filter(|name| !name.ends_with('/'))
+                        .filter_map(|name| name.strip_prefix(prefix.as_str()))
+                        .filter(|name| is_safe(name))
+                        .map(str::to_string),
+                );
+            }
+        }
+        files.retain(|file| {
+            let name = file.rsplit('/').next().unwrap_or(file);
+            !JUNK_FILES.contains(&name) && !file.starts_with("__MACOSX/")
+        });
+        Ok(files)
+    }
+
+    pub(super) fn read(&mut self, name: &str) -> Result<Vec<u8>, ResourcePackError> {
+        let mut data = Vec::new();
+        self.copy_to(name, &mut data)?;
+        Ok(data)
+    }
+
+    fn copy_to(&mut self, name: &str, writer: &mut impl Write) -> Result<(), ResourcePackError> {
+        match self {
+            Source::Folder(root) => {
+                io::copy(&mut File::open(root.join(name))?, writer)?;
+            }
+            Source::Zip { archive, prefix } => {
+                let mut entry = archive.by_name(&format!("{}{}", prefix, name))?;
+                io::copy(&mut entry, writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn pack_format(&mut self) -> Option<u32> {
+        let data = self.read(PACK_MCMETA).ok()?;
+        let mcmeta: Value = serde_json::from_slice(&data).ok()?;
+        mcmeta["pack"]["pack_format"]
+            .as_u64()
+            .and_then(|format| format.try_into().ok())
+    }
+}
+
+/// Returns whether a zip entry name stays inside the pack.
+fn is_safe(name: &str) -> bool {
+    !name.starts_with('/') && !name.split(['/', '\\']).any(|part| part == "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::tempdir;
+
+    fn read_entry(zip: &Path, name: &str) -> String {
+        let mut archive = ZipArchive::new(File::open(zip).unwrap()).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    #[test]
+    fn later_sources_override_earlier_files() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("base");
+        fs::create_dir_all(folder.join("assets/minecraft/lang")).unwrap();
+        fs::write(
+            folder.join(PACK_MCMETA),
+            r#"{"pack": {"pack_format": 15, "description": "base"}}"#,
+        )
+        .unwrap();
+        fs::write(folder.join("assets/minecraft/lang/en_us.json"), "base").unwrap();
+        fs::write(folder.join("only-base.txt"), "base").unwrap();
+        fs::write(folder.join(".DS_Store"), "").unwrap();
+
+        let zipped = dir.path().join("top.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zipped).unwrap());
+        for (name, content) in [
+            ("Top/pack.mcmeta", r#"{"pack": {"pack_format": 18}}"#),
+            ("Top/pack.png", "icon"),
+            ("Top/assets/minecraft/lang/en_us.json", "top"),
+            ("Top/../escape.txt", "evil"),
+        ] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let dest = dir.path().join("combined.zip");
+        let report = PackComposer::new(34, "Combined")
+            .with_source(&folder)
+            .with_source(&zipped)
+            .compose(&dest)
+            .unwrap();
+
+        assert_eq!(report.files, 4);
+        assert_eq!(report.overridden, vec!["assets/minecraft/lang/en_us.json"]);
+        assert_eq!(report.source_formats, vec![Some(15), Some(18)]);
+        assert_eq!(read_entry(&dest, "assets/minecraft/lang/en_us.json"), "top");
+        assert_eq!(read_entry(&dest, "only-base.txt"), "base");
+        assert_eq!(read_entry(&dest, PACK_ICON), "icon");
+        let mcmeta: Value = serde_json::from_str(&read_entry(&dest, PACK_MCMETA)).unwrap();
+        assert_eq!(mcmeta["pack"]["pack_format"], 34);
+        assert_eq!(mcmeta["pack"]["description"], "Combined");
+    }
+
+    #[test]
+    fn uses_given_icon_and_rejects_missing_sources() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("pack");
+        fs::create_dir(&folder).unwrap();
+        fs::write(folder.join(PACK_ICON), "source icon").unwrap();
+        let icon = dir.path().join("icon.png");
+        fs::write(&icon, "custom icon").unwrap();
+        let dest = dir.path().join("out.zip");
+
+        PackComposer::new(15, "")
+            .with_source(&folder)
+            .with_icon(&icon)
+            .compose(&dest)
+            .unwrap();
+        assert_eq!(read_entry(&dest, PACK_ICON), "custom icon");
+
+        let missing = PackComposer::new(15, "").with_source(dir.path().join("missing"));
+        assert!(matches!(
+            missing.compose(&dest),
+            Err(ResourcePackError::NotAPack(_))
+        ));
+    }
+}
@@ -0,0 +1,399 @@
+use super::ResourcePackError;
+use super::compose::Source;
+use crate::json::parse_json_lenient;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// The language every other language falls back to.
+pub const FALLBACK_LOCALE: &str = "en_us";
+
+/// Translation keys and their text, e.g. `block.minecraft.stone` to `Stone`.
+pub type LangMap = BTreeMap<String, String>;
+
+/// Parses a language file of 1.13 and later, a JSON object of strings.
+///
+/// # Errors
+///
+/// Returns an error if the text is not a JSON object of strings.
+pub fn parse_lang_json(text: &str) -> Result<LangMap, ResourcePackError> {
+    Ok(parse_json_lenient(text)?)
+}
+
+/// Parses a `.lang` file of 1.12 and earlier: `key=value` lines and `#`
+/// comments. Lines without `=` are skipped, as the game does.
+pub fn parse_lang_legacy(text: &str) -> LangMap {
+    text.lines()
+        .map(|line| line.strip_prefix('\u{feff}').unwrap_or(line))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Reads a `.json` or `.lang` language file, e.g. a language of the asset
+/// index.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed.
+pub fn read_lang_file(path: &Path) -> Result<LangMap, ResourcePackError> {
+    let text = fs::read_to_string(path)?;
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("lang"))
+    {
+        Ok(parse_lang_legacy(&text))
+    } else {
+        parse_lang_json(&text)
+    }
+}
+
+/// Reads the language files of one locale from a pack folder, a pack zip
+/// or a client jar, across all namespaces.
+///
+/// Locales are matched case-insensitively, since packs before 1.11 name
+/// them like `en_US.lang`.
+///
+/// # Errors
+///
+/// Returns an error if the pack cannot be opened or a language file of the
+/// locale is invalid.
+pub fn read_pack_lang(pack: &Path, locale: &str) -> Result<LangMap, ResourcePackError> {
+    let mut source = Source::open(pack)?;
+    let mut files: Vec<String> = source
+        .files()?
+        .into_iter()
+        .filter(|file| lang_file_locale(file).is_some_and(|l| l.eq_ignore_ascii_case(locale)))
+        .collect();
+    // Namespaces in a fixed order, so that conflicting keys resolve the same
+    // way every time.
+    files.sort();
+    let mut entries = LangMap::new();
+    for file in files {
+        let text = String::from_utf8_lossy(&source.read(&file)?).into_owned();
+        if file.ends_with(".lang") {
+            entries.extend(parse_lang_legacy(&text));
+        } else {
+            entries.extend(parse_lang_json(&text)?);
+        }
+    }
+    Ok(entries)
+}
+
+/// How completely a pack translates the game into one language, from
+/// [`translation_coverage`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LangCoverage {
+    /// The locale, in lower case.
+    pub locale: String,
+    /// Number of vanilla keys the pack translates.
+    pub translated: usize,
+    /// Vanilla keys the pack has no text for, which show in English.
+    pub missing: Vec<String>,
+    /// Keys of the pack that vanilla does not have, e.g. from a newer or
+    /// older version or mistyped.
+    pub extra: Vec<String>,
+    /// Keys whose text is the same as in English, likely not translated yet.
+    pub untranslated: Vec<String>,
+}
+
+impl LangCoverage {
+    /// Returns the share of vanilla keys the pack translates, from 0 to 1.
+    /// Keys with English text count as translated here.
+    pub fn ratio(&self) -> f64 {
+        let total = self.translated + self.missing.len();
+        if total == 0 {
+            1.0
+        } else {
+            self.translated as f64 / total as f64
+        }
+    }
+}
+
+/// Compares the languages a pack adds or changes with the English keys of a
+/// game version.
+///
+/// # Arguments
+///
+/// * `pack` - The pack folder or zip.
+/// * `vanilla` - The `en_us` keys of the target version, e.g. from
+///   [`read_pack_lang`] on its client jar.
+///
+/// # Returns
+///
+/// * `Result<Vec<LangCoverage>, ResourcePackError>` - One entry per locale of
+///   the pack other than `en_us`, sorted by locale.
+///
+/// # Errors
+///
+/// Returns an error if the pack cannot be read or a language file is
+/// invalid.
+pub fn translation_coverage(
+    pack: &Path,
+    vanilla: &LangMap,
+) -> Result<Vec<LangCoverage>, ResourcePackError> {
+    let locales: BTreeSet<String> = Source::open(pack)?
+        .files()?
+        .iter()
+        .filter_map(|file| lang_file_locale(file))
+        .map(str::to_ascii_lowercase)
+        .filter(|locale| locale != FALLBACK_LOCALE)
+        .collect();
+    let mut coverage = Vec::new();
+    for locale in locales {
+        let entries = read_pack_lang(pack, &locale)?;
+        let mut report = LangCoverage {
+            extra: entries
+                .keys()
+                .filter(|key| !vanilla.contains_key(*key))
+                .cloned()
+                .collect(),
+            locale,
+            ..LangCoverage::default()
+        };
+        for (key, english) in vanilla {
+            match entries.get(key) {
+                None => report.missing.push(key.clone()),
+                Some(text) => {
+                    report.translated += 1;
+                    if text == english && !english.is_empty() {
+                        report.untranslated.push(key.clone());
+                    }
+                }
+            }
+        }
+        coverage.push(report);
+    }
+    Ok(coverage)
+}
+
+/// Returns the locale of `assets/<namespace>/lang/<locale>.json` or `.lang`.
+fn lang_file_locale(file: &str) -> Option<&str> {
+    let mut parts = file.split('/');
+    let (Some("assets"), Some(_), Some("lang"), Some(name), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return None;
+    };
+    name.strip_suffix(".json")
+        .or_else(|| name.strip_suffix(".lang"))
+}
+
+/// Translations of one language, with [`FALLBACK_LOCALE`] for missing keys.
+///
+/// Layers are added the way the game stacks them: vanilla first, then the
+/// enabled resource packs from lowest to highest priority. Any text of the
+/// selected language wins over fallback text, even from a lower pack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Translations {
+    locale: String,
+    entries: LangMap,
+    fallback: LangMap,
+}
+
+impl Translations {
+    /// Creates empty translations for a locale such as `de_de`.
+    pub fn new(locale: impl Into<String>) -> Self {
+        Self {
+            locale: locale.into().to_ascii_lowercase(),
+            entries: LangMap::new(),
+            fallback: LangMap::new(),
+        }
+    }
+
+    /// Returns the selected locale.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Adds the entries of a language file on top of the current ones.
+    /// Entries of locales other than the selected and the fallback one are
+    /// ignored.
+    pub fn add(&mut self, locale: &str, entries: LangMap) {
+        if locale.eq_ignore_ascii_case(&self.locale) {
+            self.entries.extend(entries);
+        } else if locale.eq_ignore_ascii_case(FALLBACK_LOCALE) {
+            self.fallback.extend(entries);
+        }
+    }
+
+    /// Adds the language files of a pack folder, pack zip or client jar on
+    /// top of the current ones.
+    ///
+    /// The client jar holds `en_us` only; other vanilla languages are in the
+    /// asset index and are added with [`Translations::add`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pack cannot be read.
+    pub fn add_pack(&mut self, pack: &Path) -> Result<(), ResourcePackError> {
+        if self.locale != FALLBACK_LOCALE {
+            let entries = read_pack_lang(pack, &self.locale)?;
+            self.entries.extend(entries);
+        }
+        let fallback = read_pack_lang(pack, FALLBACK_LOCALE)?;
+        self.fallback.extend(fallback);
+        Ok(())
+    }
+
+    /// Returns the text of a key in the selected language, or else in the
+    /// fallback language.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+    }
+
+    /// Translates a key and fills in its arguments.
+    ///
+    /// Supports `%s`, positional `%1$s` and `%%`, like the game's
+    /// translatable text. A key without text is returned as is, as the game
+    /// shows it, and missing arguments are left empty.
+    pub fn translate(&self, key: &str, args: &[&str]) -> String {
+        let Some(text) = self.get(key) else {
+            return key.to_string();
+        };
+        let mut out = String::with_capacity(text.len());
+        let mut next = 0;
+        let mut rest = text;
+        while let Some(index) = rest.find('%') {
+            out.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+            if let Some(after) = rest.strip_prefix('%') {
+                out.push('%');
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('s') {
+                out.push_str(args.get(next).copied().unwrap_or_default());
+                next += 1;
+                rest = after;
+            } else if let Some((position, after)) = rest.split_once("$s")
+                && let Ok(position) = position.parse::<usize>()
+            {
+                out.push_str(
+                    args.get(position.wrapping_sub(1))
+                        .copied()
+                        .unwrap_or_default(),
+                );
+                rest = after;
+            } else {
+                out.push('%');
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn parses_json_and_legacy_files() {
+        let json = parse_lang_json(r#"{"block.minecraft.stone": "Stone"}"#).unwrap();
+        assert_eq!(json["block.minecraft.stone"], "Stone");
+        let legacy = parse_lang_legacy("# comment\ntile.stone.name=Stone\nbroken line\nempty=\n");
+        assert_eq!(legacy.len(), 2);
+        assert_eq!(legacy["tile.stone.name"], "Stone");
+        assert!(parse_lang_json("[1, 2]").is_err());
+        assert_eq!(
+            lang_file_locale("assets/minecraft/lang/en_US.lang"),
+            Some("en_US")
+        );
+        assert_eq!(
+            lang_file_locale("assets/minecraft/lang/sub/en_us.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn layers_packs_over_vanilla_with_fallback() {
+        let dir = tempdir().unwrap();
+        let client = dir.path().join("client.jar");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&client).unwrap());
+        writer
+            .start_file(
+                "assets/minecraft/lang/en_us.json",
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+        writer
+            .write_all(
+                br#"{"item.minecraft.apple": "Apple", "item.minecraft.bread": "Bread",
+                    "death.attack.arrow": "%1$s was shot by %2$s", "gui.percent": "%s%%"}"#,
+            )
+            .unwrap();
+        writer.finish().unwrap();
+
+        let pack = dir.path().join("pack");
+        fs::create_dir_all(pack.join("assets/minecraft/lang")).unwrap();
+        fs::create_dir_all(pack.join("assets/mymod/lang")).unwrap();
+        fs::write(
+            pack.join("assets/minecraft/lang/en_us.json"),
+            r#"{"item.minecraft.apple": "Red Apple"}"#,
+        )
+        .unwrap();
+        fs::write(
+            pack.join("assets/mymod/lang/de_DE.lang"),
+            "item.mymod.gem=Edelstein\n",
+        )
+        .unwrap();
+
+        let mut translations = Translations::new("de_de");
+        translations.add_pack(&client).unwrap();
+        translations.add(
+            "de_de",
+            LangMap::from([("item.minecraft.bread".to_string(), "Brot".to_string())]),
+        );
+        translations.add_pack(&pack).unwrap();
+
+        assert_eq!(translations.get("item.minecraft.bread"), Some("Brot"));
+        assert_eq!(translations.get("item.minecraft.apple"), Some("Red Apple"));
+        assert_eq!(translations.get("item.mymod.gem"), Some("Edelstein"));
+        assert_eq!(
+            translations.translate("death.attack.arrow", &["Steve", "Skeleton"]),
+            "Steve was shot by Skeleton"
+        );
+        assert_eq!(translations.translate("gui.percent", &["50"]), "50%");
+        assert_eq!(translations.translate("unknown.key", &[]), "unknown.key");
+    }
+
+    #[test]
+    fn reports_translation_coverage() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("assets/minecraft/lang")).unwrap();
+        fs::write(
+            dir.path().join("assets/minecraft/lang/en_us.json"),
+            r#"{"menu.quit": "Leave"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("assets/minecraft/lang/eo_UY.json"),
+            r#"{"menu.quit": "Eliri", "menu.options": "Options...", "menu.old": "x"}"#,
+        )
+        .unwrap();
+        let vanilla = parse_lang_json(
+            r#"{"menu.quit": "Quit Game", "menu.options": "Options...", "menu.singleplayer": "Singleplayer"}"#,
+        )
+        .unwrap();
+
+        let coverage = translation_coverage(dir.path(), &vanilla).unwrap();
+        assert_eq!(coverage.len(), 1);
+        let esperanto = &coverage[0];
+        assert_eq!(esperanto.locale, "eo_uy");
+        assert_eq!(esperanto.translated, 2);
+        assert_eq!(esperanto.missing, ["menu.singleplayer"]);
+        assert_eq!(esperanto.extra, ["menu.old"]);
+        assert_eq!(esperanto.untranslated, ["menu.options"]);
+        assert!((esperanto.ratio() - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,39 @@
+/// Merging several resource packs into one.
+pub mod compose;
+/// Language files and translations layered over vanilla.
+pub mod lang;
+/// Blockstate and model files and the validation of their references.
+pub mod models;
+/// Sound events of sounds.json and the sound files of packs.
+pub mod sounds;
+
+pub use compose::{ComposeReport, PACK_ICON, PACK_MCMETA, PackComposer};
+pub use lang::{
+    FALLBACK_LOCALE, LangCoverage, LangMap, Translations, parse_lang_json, parse_lang_legacy,
+    read_lang_file, read_pack_lang, translation_coverage,
+};
+pub use models::{
+    BlockState, BrokenReference, Model, ModelVariant, ModelVariants, MultipartCase, ReferenceKind,
+    ValidationReport, model_path, texture_path, validate_pack,
+};
+pub use sounds::{
+    MissingSound, PackSoundEvent, SoundEntry, SoundEvent, SoundInventory, SoundKind,
+    parse_sounds_json, sound_file_path, sound_inventory,
+};
+
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Error returned when a resource pack cannot be read or written.
+#[derive(Debug, Error)]
+pub enum ResourcePackError {
+    #[error("failed to access resource pack: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid resource pack archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("invalid pack metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("not a resource pack folder or zip: {0}")]
+    NotAPack(PathBuf),
+}
@@ -0,0 +1,424 @@
+use super::ResourcePackError;
+use super::compose::Source;
+use crate::json::parse_json_lenient;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// A model a blockstate picks, with its rotation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ModelVariant {
+    /// The model id, e.g. `minecraft:block/stone`.
+    pub model: String,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default)]
+    pub uvlock: bool,
+    /// The chance of picking this model when there are several.
+    #[serde(default = "weight_one")]
+    pub weight: u32,
+}
+
+fn weight_one() -> u32 {
+    1
+}
+
+/// The models of one variant or multipart case.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ModelVariants {
+    /// A single model.
+    One(ModelVariant),
+    /// Models picked at random by weight.
+    Many(Vec<ModelVariant>),
+}
+
+impl ModelVariants {
+    /// Returns the models as a slice.
+    pub fn as_slice(&self) -> &[ModelVariant] {
+        match self {
+            ModelVariants::One(variant) => std::slice::from_ref(variant),
+            ModelVariants::Many(variants) => variants,
+        }
+    }
+}
+
+/// A part of a multipart blockstate, applied when its condition holds.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MultipartCase {
+    /// The condition on the block properties, kept as JSON.
+    #[serde(default)]
+    pub when: Option<Value>,
+    pub apply: ModelVariants,
+}
+
+/// A blockstate file, mapping block properties to models.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct BlockState {
+    /// Models keyed by property values, e.g. `facing=north,lit=true`.
+    #[serde(default)]
+    pub variants: BTreeMap<String, ModelVariants>,
+    #[serde(default)]
+    pub multipart: Vec<MultipartCase>,
+}
+
+impl BlockState {
+    /// Parses a blockstate file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid blockstate.
+    pub fn parse(text: &str) -> Result<Self, ResourcePackError> {
+        Ok(parse_json_lenient(text)?)
+    }
+
+    /// Returns the ids of all models the blockstate refers to, without
+    /// duplicates.
+    pub fn models(&self) -> BTreeSet<&str> {
+        self.variants
+            .values()
+            .chain(self.multipart.iter().map(|case| &case.apply))
+            .flat_map(ModelVariants::as_slice)
+            .map(|variant| variant.model.as_str())
+            .collect()
+    }
+}
+
+/// A block or item model. Only the parts naming other files are parsed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Model {
+    /// The model this one inherits from, e.g. `minecraft:block/cube_all`.
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Texture variables to texture ids, or to `#other` variables.
+    #[serde(default)]
+    pub textures: BTreeMap<String, String>,
+}
+
+impl Model {
+    /// Parses a model file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid model.
+    pub fn parse(text: &str) -> Result<Self, ResourcePackError> {
+        Ok(parse_json_lenient(text)?)
+    }
+
+    /// Returns the texture ids of the model, skipping `#variable`
+    /// references.
+    pub fn texture_ids(&self) -> impl Iterator<Item = &str> {
+        self.textures
+            .values()
+            .map(String::as_str)
+            .filter(|texture| !texture.starts_with('#'))
+    }
+}
+
+/// Splits an id like `mymod:block/gem` into namespace and path, with
+/// `minecraft` for ids without namespace.
+fn split_id(id: &str) -> (&str, &str) {
+    id.split_once(':').unwrap_or(("minecraft", id))
+}
+
+/// Returns the path of a model file, e.g. `assets/minecraft/models/block/stone.json`.
+pub fn model_path(id: &str) -> String {
+    let (namespace, path) = split_id(id);
+    format!("assets/{}/models/{}.json", namespace, path)
+}
+
+/// Returns the path of a texture file, e.g. `assets/minecraft/textures/block/stone.png`.
+pub fn texture_path(id: &str) -> String {
+    let (namespace, path) = split_id(id);
+    format!("assets/{}/textures/{}.png", namespace, path)
+}
+
+/// What kind of file a broken reference points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    /// A model of a blockstate.
+    Model,
+    /// The parent of a model.
+    Parent,
+    /// A texture of a model.
+    Texture,
+    /// A parent that leads back to the model, which the game cannot load.
+    ParentCycle,
+}
+
+/// A reference from a pack file to something that does not exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenReference {
+    /// The blockstate or model holding the reference.
+    pub file: String,
+    /// The referenced id, e.g. `minecraft:block/stone`.
+    pub reference: String,
+    pub kind: ReferenceKind,
+}
+
+/// The validation report of a resource pack, from [`validate_pack`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of blockstate files checked.
+    pub blockstates: usize,
+    /// Number of model files checked.
+    pub models: usize,
+    /// References to models, parents and textures that cannot be found.
+    pub broken: Vec<BrokenReference>,
+    /// Blockstate and model files that could not be parsed, which the game
+    /// skips.
+    pub invalid: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Returns whether the pack has neither broken references nor invalid
+    /// files.
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty() && self.invalid.is_empty()
+    }
+}
+
+/// Checks the blockstates and models of a resource pack for references to
+/// missing models and textures and for broken parent chains.
+///
+/// A reference is fine if the file is in the pack or, given the client jar of
+/// the game version, in the game. Without the client jar, references to files
+/// outside the pack are reported only for namespaces other than `minecraft`.
+/// Forge blockstates (`forge_marker`) use their own format and are skipped.
+///
+/// # Arguments
+///
+/// * `pack` - The pack folder or zip.
+/// * `vanilla` - The client jar of the game version the pack is used with.
+///
+/// # Returns
+///
+/// * `Result<ValidationReport, ResourcePackError>` - The broken references
+///   and invalid files.
+///
+/// # Errors
+///
+/// Returns an error if the pack or the client jar cannot be read.
+pub fn validate_pack(
+    pack: &Path,
+    vanilla: Option<&Path>,
+) -> Result<ValidationReport, ResourcePackError> {
+    let mut source = Source::open(pack)?;
+    let files: BTreeSet<String> = source.files()?.into_iter().collect();
+    let vanilla_files: Option<BTreeSet<String>> = match vanilla {
+        Some(jar) => Some(Source::open(jar)?.files()?.into_iter().collect()),
+        None => None,
+    };
+    let exists = |path: &str| {
+        files.contains(path)
+            || match &vanilla_files {
+                Some(vanilla) => vanilla.contains(path),
+                None => path.starts_with("assets/minecraft/"),
+            }
+    };
+
+    let mut report = ValidationReport::default();
+    let mut models = BTreeMap::new();
+    for file in &files {
+        let mut parts = file.splitn(4, '/');
+        let (Some("assets"), Some(namespace), Some(folder), Some(name)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let Some(name) = name.strip_suffix(".json") else {
+            continue;
+        };
+        match folder {
+            "blockstates" => {
+                let text = String::from_utf8_lossy(&source.read(file)?).into_owned();
+                let Ok(value) = parse_json_lenient::<Value>(&text) else {
+                    report.invalid.push(file.clone());
+                    continue;
+                };
+                if value.get("forge_marker").is_some() {
+                    continue;
+                }
+                let Ok(state) = serde_json::from_value::<BlockState>(value) else {
+                    report.invalid.push(file.clone());
+                    continue;
+                };
+                report.blockstates += 1;
+                for model in state.models() {
+                    let id = full_id(model);
+                    // Blockstates before 1.13 name models relative to
+                    // `models/block`.
+                    let (model_namespace, path) = split_id(&id);
+                    let legacy = format!("{}:block/{}", model_namespace, path);
+                    if !exists(&model_path(&id)) && !exists(&model_path(&legacy)) {
+                        report.broken.push(BrokenReference {
+                            file: file.clone(),
+                            reference: id,
+                            kind: ReferenceKind::Model,
+                        });
+                    }
+                }
+            }
+            "models" => {
+                let text = String::from_utf8_lossy(&source.read(file)?).into_owned();
+                match Model::parse(&text) {
+                    Ok(model) => {
+                        models.insert(format!("{}:{}", namespace, name), (file.clone(), model));
+                    }
+                    Err(_) => report.invalid.push(file.clone()),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    report.models = models.len();
+    for (id, (file, model)) in &models {
+        for texture in model.texture_ids() {
+            let texture = full_id(texture);
+            if !exists(&texture_path(&texture)) {
+                report.broken.push(BrokenReference {
+                    file: file.clone(),
+                    reference: texture,
+                    kind: ReferenceKind::Texture,
+                });
+            }
+        }
+        if let Some(problem) = check_parents(id, &models, &exists) {
+            report.broken.push(BrokenReference {
+                file: file.clone(),
+                reference: problem.0,
+                kind: problem.1,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Adds the `minecraft` namespace to an id without one, as the game does.
+fn full_id(id: &str) -> String {
+    let (namespace, path) = split_id(id);
+    format!("{}:{}", namespace, path)
+}
+
+/// Follows the parents of a pack model, returning the first parent that is
+/// missing or closes a cycle. Parents outside the pack are not followed.
+fn check_parents(
+    id: &str,
+    models: &BTreeMap<String, (String, Model)>,
+    exists: &impl Fn(&str) -> bool,
+) -> Option<(String, ReferenceKind)> {
+    let mut seen = BTreeSet::from([id.to_string()]);
+    let mut current = id.to_string();
+    loop {
+        let parent = full_id(models.get(&current)?.1.parent.as_deref()?);
+        // `builtin/generated` and `builtin/entity` are made by the game.
+        if split_id(&parent).1.starts_with("builtin/") {
+            return None;
+        }
+        if !seen.insert(parent.clone()) {
+            return Some((parent, ReferenceKind::ParentCycle));
+        }
+        if !models.contains_key(&parent) {
+            return (!exists(&model_path(&parent))).then_some((parent, ReferenceKind::Parent));
+        }
+        current = parent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_variants_and_multipart() {
+        let state = BlockState::parse(
+            r#"{"variants": {"facing=north": {"model": "block/furnace", "y": 90},
+                "facing=south": [{"model": "block/furnace"}, {"model": "block/furnace_alt", "weight": 2}]},
+                "multipart": [{"when": {"up": "true"}, "apply": {"model": "mymod:block/top"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(state.variants["facing=north"].as_slice()[0].y, 90);
+        assert_eq!(state.variants["facing=south"].as_slice()[1].weight, 2);
+        assert_eq!(
+            state.models().into_iter().collect::<Vec<_>>(),
+            ["block/furnace", "block/furnace_alt", "mymod:block/top"]
+        );
+        let model = Model::parse(
+            r##"{"parent": "block/cube_all", "textures": {"all": "mymod:block/gem", "particle": "#all"}}"##,
+        )
+        .unwrap();
+        assert_eq!(model.texture_ids().collect::<Vec<_>>(), ["mymod:block/gem"]);
+        assert_eq!(
+            texture_path("mymod:block/gem"),
+            "assets/mymod/textures/block/gem.png"
+        );
+    }
+
+    #[test]
+    fn reports_broken_references() {
+        let dir = tempdir().unwrap();
+        let pack = dir.path();
+        let write = |name: &str, content: &str| {
+            let path = pack.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "assets/mymod/blockstates/gem_block.json",
+            r#"{"variants": {"": [{"model": "mymod:block/gem_block"}, {"model": "mymod:block/gone"}]}}"#,
+        );
+        write(
+            "assets/mymod/blockstates/machine.json",
+            r#"{"forge_marker": 1, "variants": {"normal": [{}]}}"#,
+        );
+        write(
+            "assets/mymod/models/block/gem_block.json",
+            r#"{"parent": "minecraft:block/cube_all", "textures": {"all": "mymod:block/gem"}}"#,
+        );
+        write(
+            "assets/mymod/models/block/loop_a.json",
+            r#"{"parent": "mymod:block/loop_b"}"#,
+        );
+        write(
+            "assets/mymod/models/block/loop_b.json",
+            r#"{"parent": "mymod:block/loop_a"}"#,
+        );
+        write(
+            "assets/mymod/models/item/gem.json",
+            r#"{"parent": "mymod:item/handheld_gem", "textures": {"layer0": "mymod:item/missing"}}"#,
+        );
+        write(
+            "assets/mymod/models/item/wand.json",
+            r#"{"parent": "builtin/generated", "textures": {"layer0": "item/stick"}}"#,
+        );
+        write("assets/mymod/models/item/broken.json", "{");
+        write("assets/mymod/textures/block/gem.png", "");
+
+        let report = validate_pack(pack, None).unwrap();
+        assert_eq!(report.blockstates, 1);
+        assert_eq!(report.models, 5);
+        assert_eq!(report.invalid, ["assets/mymod/models/item/broken.json"]);
+        let broken: Vec<_> = report
+            .broken
+            .iter()
+            .map(|b| (b.reference.as_str(), b.kind))
+            .collect();
+        assert_eq!(
+            broken,
+            [
+                ("mymod:block/gone", ReferenceKind::Model),
+                ("mymod:block/loop_a", ReferenceKind::ParentCycle),
+                ("mymod:block/loop_b", ReferenceKind::ParentCycle),
+                ("mymod:item/missing", ReferenceKind::Texture),
+                ("mymod:item/handheld_gem", ReferenceKind::Parent),
+            ]
+        );
+        assert!(!report.is_ok());
+    }
+}
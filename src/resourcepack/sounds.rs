@@ -0,0 +1,309 @@
+use super::ResourcePackError;
+use super::compose::Source;
+use crate::assets::AssetIndex;
+use crate::json::parse_json_lenient;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// What a sound entry refers to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundKind {
+    /// An `.ogg` file under `assets/<namespace>/sounds`.
+    #[default]
+    File,
+    /// Another sound event, whose sounds are played instead.
+    Event,
+}
+
+/// One of the sounds an event picks from.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "RawSound")]
+pub struct SoundEntry {
+    /// The file without `.ogg`, e.g. `minecraft:mob/cow/say1`, or an event id.
+    pub name: String,
+    pub kind: SoundKind,
+    /// Whether the file is streamed, as music and records are.
+    pub stream: bool,
+    pub volume: f32,
+    pub pitch: f32,
+    /// The chance of picking this sound relative to the others.
+    pub weight: u32,
+}
+
+/// A sound entry is either a name or an object with options.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSound {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(default, rename = "type")]
+        kind: SoundKind,
+        #[serde(default)]
+        stream: bool,
+        #[serde(default = "one")]
+        volume: f32,
+        #[serde(default = "one")]
+        pitch: f32,
+        #[serde(default = "weight_one")]
+        weight: u32,
+    },
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+fn weight_one() -> u32 {
+    1
+}
+
+impl From<RawSound> for SoundEntry {
+    fn from(raw: RawSound) -> Self {
+        match raw {
+            RawSound::Name(name) => SoundEntry {
+                name,
+                kind: SoundKind::File,
+                stream: false,
+                volume: 1.0,
+                pitch: 1.0,
+                weight: 1,
+            },
+            RawSound::Full {
+                name,
+                kind,
+                stream,
+                volume,
+                pitch,
+                weight,
+            } => SoundEntry {
+                name,
+                kind,
+                stream,
+                volume,
+                pitch,
+                weight,
+            },
+        }
+    }
+}
+
+/// A sound event of a `sounds.json`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SoundEvent {
+    /// Whether the sounds replace those of lower packs instead of being added.
+    #[serde(default)]
+    pub replace: bool,
+    /// The translation key of the subtitle.
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    #[serde(default)]
+    pub sounds: Vec<SoundEntry>,
+}
+
+/// Parses a `sounds.json`, keyed by event name without namespace.
+///
+/// # Errors
+///
+/// Returns an error if the text is not a valid `sounds.json`.
+pub fn parse_sounds_json(text: &str) -> Result<BTreeMap<String, SoundEvent>, ResourcePackError> {
+    Ok(parse_json_lenient(text)?)
+}
+
+/// Returns the path of the file a sound entry plays, e.g.
+/// `assets/minecraft/sounds/mob/cow/say1.ogg`. Names without namespace are in
+/// the namespace of their `sounds.json`.
+pub fn sound_file_path(namespace: &str, name: &str) -> String {
+    let (namespace, path) = name.split_once(':').unwrap_or((namespace, name));
+    format!("assets/{}/sounds/{}.ogg", namespace, path)
+}
+
+/// A sound event defined by a pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackSoundEvent {
+    /// The namespaced id, e.g. `minecraft:entity.cow.ambient`.
+    pub id: String,
+    /// Whether the pack replaces the event's sounds rather than adding to them.
+    pub replace: bool,
+    /// Number of files the event refers to.
+    pub files: usize,
+}
+
+/// A sound file referenced by a pack that neither the pack nor the game has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSound {
+    /// The id of the event referring to the file.
+    pub event: String,
+    /// The path of the file, e.g. `assets/minecraft/sounds/mob/cow/say1.ogg`.
+    pub file: String,
+}
+
+/// The sounds of a resource pack, from [`sound_inventory`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SoundInventory {
+    /// The events of the pack's `sounds.json` files, sorted by id.
+    pub events: Vec<PackSoundEvent>,
+    /// `.ogg` files of the pack that replace a vanilla sound with the same
+    /// path, which needs no `sounds.json` entry.
+    pub replaced_files: Vec<String>,
+    pub missing: Vec<MissingSound>,
+    /// `sounds.json` files that could not be parsed, which the game skips.
+    pub invalid: Vec<String>,
+}
+
+/// Lists the sound events a resource pack defines and the files it replaces,
+/// and finds references to missing `.ogg` files.
+///
+/// A reference is fine if the file is in the pack or, given the asset index
+/// of the game version, in the game's assets. Without an index, references
+/// to files outside the pack are reported only for namespaces other than
+/// `minecraft`.
+///
+/// # Arguments
+///
+/// * `pack` - The pack folder or zip.
+/// * `index` - The asset index of the game version the pack is used with.
+///
+/// # Returns
+///
+/// * `Result<SoundInventory, ResourcePackError>` - The events, replaced files
+///   and missing references.
+///
+/// # Errors
+///
+/// Returns an error if the pack cannot be read.
+pub fn sound_inventory(
+    pack: &Path,
+    index: Option<&AssetIndex>,
+) -> Result<SoundInventory, ResourcePackError> {
+    let mut source = Source::open(pack)?;
+    let files: BTreeSet<String> = source.files()?.into_iter().collect();
+    let in_game = |file: &str| match index {
+        Some(index) => file
+            .strip_prefix("assets/")
+            .is_some_and(|key| index.objects.contains_key(key)),
+        None => file.starts_with("assets/minecraft/"),
+    };
+
+    let mut inventory = SoundInventory::default();
+    for file in &files {
+        let mut parts = file.splitn(3, '/');
+        let (Some("assets"), Some(namespace), Some("sounds.json")) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&source.read(file)?).into_owned();
+        let Ok(events) = parse_sounds_json(&text) else {
+            inventory.invalid.push(file.clone());
+            continue;
+        };
+        for (name, event) in events {
+            let id = format!("{}:{}", namespace, name);
+            let mut count = 0;
+            for sound in event.sounds.iter().filter(|s| s.kind == SoundKind::File) {
+                count += 1;
+                let path = sound_file_path(namespace, &sound.name);
+                if !files.contains(&path) && !in_game(&path) {
+                    inventory.missing.push(MissingSound {
+                        event: id.clone(),
+                        file: path,
+                    });
+                }
+            }
+            inventory.events.push(PackSoundEvent {
+                id,
+                replace: event.replace,
+                files: count,
+            });
+        }
+    }
+    inventory.events.sort_by(|a, b| a.id.cmp(&b.id));
+    if let Some(index) = index {
+        inventory.replaced_files = files
+            .iter()
+            .filter(|file| file.ends_with(".ogg"))
+            .filter(|file| {
+                file.strip_prefix("assets/")
+                    .is_some_and(|key| index.objects.contains_key(key))
+            })
+            .cloned()
+            .collect();
+    }
+    Ok(inventory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_short_and_full_entries() {
+        let events = parse_sounds_json(
+            r#"{"music.menu": {"replace": true, "subtitle": "subtitles.music",
+                "sounds": ["music/menu1", {"name": "music/menu2", "stream": true, "weight": 3},
+                           {"name": "minecraft:music.game", "type": "event"}]}}"#,
+        )
+        .unwrap();
+        let menu = &events["music.menu"];
+        assert!(menu.replace);
+        assert_eq!(menu.sounds[0].weight, 1);
+        assert!(menu.sounds[1].stream && menu.sounds[1].weight == 3);
+        assert_eq!(menu.sounds[2].kind, SoundKind::Event);
+        assert_eq!(
+            sound_file_path("mymod", "minecraft:mob/cow/say1"),
+            "assets/minecraft/sounds/mob/cow/say1.ogg"
+        );
+    }
+
+    #[test]
+    fn lists_overrides_and_missing_files() {
+        let dir = tempdir().unwrap();
+        let pack = dir.path();
+        fs::create_dir_all(pack.join("assets/minecraft/sounds/mob/cow")).unwrap();
+        fs::create_dir_all(pack.join("assets/mymod")).unwrap();
+        fs::write(pack.join("assets/minecraft/sounds/mob/cow/say1.ogg"), "").unwrap();
+        fs::write(pack.join("assets/minecraft/sounds/mob/cow/moo.ogg"), "").unwrap();
+        fs::write(
+            pack.join("assets/minecraft/sounds.json"),
+            r#"{"entity.cow.ambient": {"replace": true, "sounds": ["mob/cow/moo", "mob/cow/say2", "mob/pig/say1"]}}"#,
+        )
+        .unwrap();
+        fs::write(pack.join("assets/mymod/sounds.json"), "not json").unwrap();
+
+        let index = AssetIndex::from_json(
+            r#"{"objects": {
+                "minecraft/sounds/mob/cow/say1.ogg": {"hash": "aa", "size": 1},
+                "minecraft/sounds/mob/pig/say1.ogg": {"hash": "bb", "size": 1}}}"#,
+        )
+        .unwrap();
+        let inventory = sound_inventory(pack, Some(&index)).unwrap();
+        assert_eq!(
+            inventory.events,
+            [PackSoundEvent {
+                id: "minecraft:entity.cow.ambient".to_string(),
+                replace: true,
+                files: 3,
+            }]
+        );
+        assert_eq!(
+            inventory.missing,
+            [MissingSound {
+                event: "minecraft:entity.cow.ambient".to_string(),
+                file: "assets/minecraft/sounds/mob/cow/say2.ogg".to_string(),
+            }]
+        );
+        assert_eq!(
+            inventory.replaced_files,
+            ["assets/minecraft/sounds/mob/cow/say1.ogg"]
+        );
+        assert_eq!(inventory.invalid, ["assets/mymod/sounds.json"]);
+
+        let without_index = sound_inventory(pack, None).unwrap();
+        assert!(without_index.missing.is_empty() && without_index.replaced_files.is_empty());
+    }
+}
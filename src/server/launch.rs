@@ -0,0 +1,312 @@
+use super::setup::SERVER_JAR;
+use crate::filesystem::{FilesystemError, write_atomic};
+use crate::java::JvmPreset;
+use crate::platform::{OsName, Platform};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// What the server runs, relative to its folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEntry {
+    /// An executable jar, e.g. `server.jar` or a Fabric `fabric-server-launch.jar`.
+    Jar(PathBuf),
+    /// The argument files the Forge and NeoForge installers write since 1.17,
+    /// e.g. `libraries/net/minecraftforge/forge/1.20.1-47.2.0/unix_args.txt`.
+    ArgsFile {
+        /// The file used on Linux and macOS.
+        unix: PathBuf,
+        /// The file used on Windows, with `;` as classpath separator.
+        windows: PathBuf,
+    },
+}
+
+impl Default for ServerEntry {
+    fn default() -> Self {
+        ServerEntry::Jar(PathBuf::from(SERVER_JAR))
+    }
+}
+
+/// How a managed server is started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerLaunchConfig {
+    /// The `java` executable.
+    pub java: PathBuf,
+    pub entry: ServerEntry,
+    /// The garbage collector flags; [`JvmPreset::Aikar`] unless changed.
+    pub preset: JvmPreset,
+    /// The maximum heap in MiB.
+    pub max_mb: u64,
+    /// The major version of the Java runtime, e.g. `17`.
+    pub java_major: u32,
+    /// JVM arguments placed after the preset's.
+    pub jvm_args: Vec<String>,
+    /// Whether to pass `nogui`, which keeps the server from opening its own
+    /// window. On by default.
+    pub nogui: bool,
+}
+
+impl ServerLaunchConfig {
+    /// Creates a configuration running `server.jar` with Aikar's flags.
+    pub fn new(java: impl Into<PathBuf>, max_mb: u64, java_major: u32) -> Self {
+        Self {
+            java: java.into(),
+            entry: ServerEntry::default(),
+            preset: JvmPreset::Aikar,
+            max_mb,
+            java_major,
+            jvm_args: Vec::new(),
+            nogui: true,
+        }
+    }
+
+    pub fn with_entry(mut self, entry: ServerEntry) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    pub fn with_preset(mut self, preset: JvmPreset) -> Self {
+        self.preset = preset;
+        self
+    }
+
+    pub fn with_jvm_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.jvm_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_nogui(mut self, nogui: bool) -> Self {
+        self.nogui = nogui;
+        self
+    }
+
+    /// Returns the arguments passed to `java` on a system: the preset and
+    /// extra JVM arguments, the entry and `nogui`.
+    pub fn arguments(&self, os: OsName) -> Vec<String> {
+        let mut args = self.preset.arguments(self.max_mb, self.java_major);
+        args.extend(self.jvm_args.iter().cloned());
+        match &self.entry {
+            ServerEntry::Jar(jar) => {
+                args.push("-jar".to_string());
+                args.push(jar.display().to_string());
+            }
+            ServerEntry::ArgsFile { unix, windows } => {
+                let file = if os == OsName::Windows { windows } else { unix };
+                args.push(format!("@{}", file.display()));
+            }
+        }
+        if self.nogui {
+            args.push("nogui".to_string());
+        }
+        args
+    }
+
+    /// Builds the command starting the server in `server_dir` on the current
+    /// system.
+    ///
+    /// Standard input is piped so the launcher can send console commands such
+    /// as `stop`.
+    pub fn to_command(&self, server_dir: &Path) -> Command {
+        let mut command = Command::new(&self.java);
+        command
+            .args(self.arguments(Platform::current().os))
+            .current_dir(server_dir)
+            .stdin(Stdio::piped());
+        command
+    }
+}
+
+/// A start script for a server folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartScript {
+    /// `start.bat` on Windows, `start.command` on macOS so it opens in a
+    /// terminal on double click, `start.sh` elsewhere.
+    pub file_name: &'static str,
+    pub content: String,
+}
+
+/// Returns the start script of a server for a system.
+///
+/// The script changes into its own folder first, so it works wherever it is
+/// started from, and passes its own arguments on to the server. The Windows
+/// script pauses when the server stops so its window stays open.
+///
+/// # Arguments
+///
+/// * `config` - How the server is started.
+/// * `os` - The system the script runs on.
+///
+/// # Returns
+///
+/// * `StartScript` - The file name and content of the script.
+pub fn start_script(config: &ServerLaunchConfig, os: OsName) -> StartScript {
+    let java = config.java.display().to_string();
+    let args = config.arguments(os);
+    if os == OsName::Windows {
+        let line: Vec<String> = [java]
+            .iter()
+            .chain(&args)
+            .map(|arg| quote_batch(arg))
+            .collect();
+        return StartScript {
+            file_name: "start.bat",
+            content: format!(
+                "@echo off\r\ncd /d \"%~dp0\"\r\n{} %*\r\npause\r\n",
+                line.join(" ")
+            ),
+        };
+    }
+    let line: Vec<String> = [java]
+        .iter()
+        .chain(&args)
+        .map(|arg| quote_sh(arg))
+        .collect();
+    StartScript {
+        file_name: if os == OsName::Osx {
+            "start.command"
+        } else {
+            "start.sh"
+        },
+        content: format!(
+            "#!/bin/sh\ncd \"$(dirname \"$0\")\" || exit 1\nexec {} \"$@\"\n",
+            line.join(" ")
+        ),
+    }
+}
+
+/// Writes the start script of a server for the current system into
+/// `server_dir`.
+///
+/// See [`write_start_script_with`].
+pub fn write_start_script(
+    server_dir: &Path,
+    config: &ServerLaunchConfig,
+) -> Result<PathBuf, FilesystemError> {
+    write_start_script_with(server_dir, config, Platform::current().os)
+}
+
+/// Writes the start script of a server for a system into `server_dir`,
+/// replacing an existing one.
+///
+/// On Unix the script is made executable.
+///
+/// # Returns
+///
+/// * `PathBuf` - The path of the script.
+///
+/// # Errors
+///
+/// Returns an error if the script cannot be written.
+pub fn write_start_script_with(
+    server_dir: &Path,
+    config: &ServerLaunchConfig,
+    os: OsName,
+) -> Result<PathBuf, FilesystemError> {
+    let script = start_script(config, os);
+    let path = server_dir.join(script.file_name);
+    write_atomic(&path, script.content.as_bytes())?;
+    #[cfg(unix)]
+    {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = fs::metadata(&path)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o755);
+        fs::set_permissions(&path, permissions)?;
+    }
+    Ok(path)
+}
+
+/// Quotes an argument for `sh` unless it only has safe characters.
+fn quote_sh(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_=+.,/:@%".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Quotes an argument for a batch file, where `%` must also be doubled.
+fn quote_batch(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if arg.is_empty() || arg.contains([' ', '&', '|', '<', '>', '^', '(', ')', '"']) {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        arg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn builds_scripts_for_each_system() {
+        let config = ServerLaunchConfig::new("/opt/java 17/bin/java", 4096, 17)
+            .with_preset(JvmPreset::Vanilla)
+            .with_jvm_args(["-Dlog4j2.formatMsgNoLookups=true"]);
+        assert_eq!(
+            config.arguments(OsName::Linux)[..2],
+            ["-Xms2048M", "-Xmx4096M"]
+        );
+        assert!(config.arguments(OsName::Linux).ends_with(&[
+            "-Dlog4j2.formatMsgNoLookups=true".to_string(),
+            "-jar".to_string(),
+            "server.jar".to_string(),
+            "nogui".to_string(),
+        ]));
+
+        let sh = start_script(&config, OsName::Linux);
+        assert_eq!(sh.file_name, "start.sh");
+        assert!(sh.content.starts_with("#!/bin/sh\n"));
+        assert!(
+            sh.content
+                .contains("exec '/opt/java 17/bin/java' -Xms2048M")
+        );
+        assert!(sh.content.contains("-jar server.jar nogui \"$@\"\n"));
+        assert_eq!(
+            start_script(&config, OsName::Osx).file_name,
+            "start.command"
+        );
+
+        let forge = config.with_nogui(false).with_entry(ServerEntry::ArgsFile {
+            unix: "libraries/net/minecraftforge/forge/1.20.1-47.2.0/unix_args.txt".into(),
+            windows: "libraries/net/minecraftforge/forge/1.20.1-47.2.0/win_args.txt".into(),
+        });
+        let bat = start_script(&forge, OsName::Windows);
+        assert_eq!(bat.file_name, "start.bat");
+        assert!(bat.content.contains("\"/opt/java 17/bin/java\" -Xms2048M"));
+        assert!(
+            bat.content
+                .contains("@libraries/net/minecraftforge/forge/1.20.1-47.2.0/win_args.txt %*\r\n")
+        );
+        assert!(!bat.content.contains("nogui"));
+    }
+
+    #[test]
+    fn writes_an_executable_script() {
+        let dir = tempdir().unwrap();
+        let config = ServerLaunchConfig::new("java", 2048, 21);
+        let path = write_start_script_with(dir.path(), &config, OsName::Linux).unwrap();
+        assert_eq!(path, dir.path().join("start.sh"));
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("-XX:+AlwaysPreTouch")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_ne!(fs::metadata(&path).unwrap().permissions().mode() & 0o111, 0);
+        }
+    }
+}
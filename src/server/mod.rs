@@ -0,0 +1,24 @@
+/// Start scripts and commands of managed servers.
+pub mod launch;
+/// `whitelist.json`, `ops.json` and `banned-players.json`.
+pub mod player_lists;
+/// The UDP Query protocol of servers with `enable-query` on.
+pub mod query;
+/// Setting up a dedicated server: its jar and `eula.txt`.
+pub mod setup;
+
+pub use launch::{
+    ServerEntry, ServerLaunchConfig, StartScript, start_script, write_start_script,
+    write_start_script_with,
+};
+pub use player_lists::{
+    BAN_FOREVER, BanEntry, BannedPlayers, MAX_OP_LEVEL, OpEntry, OpList, PlayerList,
+    PlayerListEntry, PlayerListError, Whitelist, WhitelistEntry, format_ban_date, is_valid_uuid,
+};
+pub use query::{
+    BasicStat, FullStat, QueryError, parse_basic_stat, parse_full_stat, query_basic, query_full,
+};
+pub use setup::{
+    EULA_FILE, EULA_URL, SERVER_JAR, ServerSetupError, accept_eula, eula_accepted,
+    server_jar_request,
+};
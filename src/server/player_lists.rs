@@ -0,0 +1,427 @@
+use crate::filesystem::{FilesystemError, write_atomic};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Value of [`BanEntry::expires`] for permanent bans.
+pub const BAN_FOREVER: &str = "forever";
+
+/// Highest operator permission level.
+pub const MAX_OP_LEVEL: u8 = 4;
+
+/// Error returned when a player list cannot be read, written or edited.
+#[derive(Debug, Error)]
+pub enum PlayerListError {
+    #[error("failed to read player list: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write player list: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error("invalid player list: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid player UUID: {0}")]
+    InvalidUuid(String),
+    #[error("invalid operator level {0}, expected 0 to 4")]
+    InvalidOpLevel(u8),
+}
+
+/// Checks that `uuid` is a hyphenated UUID such as
+/// `069a79f4-44e9-4726-a5be-fca90e38aaf5`, the only form the server accepts.
+pub fn is_valid_uuid(uuid: &str) -> bool {
+    let groups: Vec<&str> = uuid.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// An entry of one of the server's player list files.
+pub trait PlayerListEntry: Serialize + DeserializeOwned {
+    /// File name of the list inside the server directory.
+    const FILE_NAME: &'static str;
+
+    /// Returns the player's UUID.
+    fn uuid(&self) -> &str;
+
+    /// Returns the player's name as last seen by the server.
+    fn name(&self) -> &str;
+
+    /// Checks the entry before it is added or after it is loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayerListError::InvalidUuid`] if the UUID is malformed.
+    fn validate(&self) -> Result<(), PlayerListError> {
+        if is_valid_uuid(self.uuid()) {
+            Ok(())
+        } else {
+            Err(PlayerListError::InvalidUuid(self.uuid().to_string()))
+        }
+    }
+}
+
+/// An entry of `whitelist.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+    /// Fields this crate does not know, written back unchanged.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl WhitelistEntry {
+    /// Creates an entry for a player.
+    pub fn new(uuid: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            uuid: uuid.into(),
+            name: name.into(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl PlayerListEntry for WhitelistEntry {
+    const FILE_NAME: &'static str = "whitelist.json";
+
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// An entry of `ops.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    pub uuid: String,
+    pub name: String,
+    /// Permission level, `0` to [`MAX_OP_LEVEL`].
+    pub level: u8,
+    /// Whether the player may join when the server is full.
+    #[serde(default)]
+    pub bypasses_player_limit: bool,
+    /// Fields this crate does not know, written back unchanged.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl OpEntry {
+    /// Creates an entry with the highest permission level.
+    pub fn new(uuid: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            uuid: uuid.into(),
+            name: name.into(),
+            level: MAX_OP_LEVEL,
+            bypasses_player_limit: false,
+            extra: Map::new(),
+        }
+    }
+
+    /// Sets the permission level.
+    pub fn with_level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl PlayerListEntry for OpEntry {
+    const FILE_NAME: &'static str = "ops.json";
+
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<(), PlayerListError> {
+        if self.level > MAX_OP_LEVEL {
+            return Err(PlayerListError::InvalidOpLevel(self.level));
+        }
+        if !is_valid_uuid(&self.uuid) {
+            return Err(PlayerListError::InvalidUuid(self.uuid.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// An entry of `banned-players.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BanEntry {
+    pub uuid: String,
+    pub name: String,
+    /// When the ban was issued, as `yyyy-MM-dd HH:mm:ss Z`.
+    pub created: String,
+    /// Who issued the ban, e.g. `Server` or an operator's name.
+    pub source: String,
+    /// When the ban ends in the format of `created`, or [`BAN_FOREVER`].
+    pub expires: String,
+    pub reason: String,
+    /// Fields this crate does not know, written back unchanged.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl BanEntry {
+    /// Creates a permanent ban issued now by `Server`, with the game's default reason.
+    pub fn new(uuid: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            uuid: uuid.into(),
+            name: name.into(),
+            created: format_ban_date(SystemTime::now()),
+            source: "Server".to_string(),
+            expires: BAN_FOREVER.to_string(),
+            reason: "Banned by an operator.".to_string(),
+            extra: Map::new(),
+        }
+    }
+
+    /// Sets the reason shown to the player.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = reason.into();
+        self
+    }
+
+    /// Sets who issued the ban.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Makes the ban end at `expires`.
+    pub fn with_expiry(mut self, expires: SystemTime) -> Self {
+        self.expires = format_ban_date(expires);
+        self
+    }
+
+    /// Returns whether the ban never ends.
+    pub fn is_permanent(&self) -> bool {
+        self.expires == BAN_FOREVER
+    }
+}
+
+impl PlayerListEntry for BanEntry {
+    const FILE_NAME: &'static str = "banned-players.json";
+
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Formats `time` in UTC as the server writes ban dates, e.g.
+/// `2024-03-01 17:05:09 +0000`.
+pub fn format_ban_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // Days to civil date, from Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} +0000",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// One of the server's player list files, in file order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerList<T> {
+    pub entries: Vec<T>,
+}
+
+impl<T> Default for PlayerList<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// The list of players allowed to join when the whitelist is enabled.
+pub type Whitelist = PlayerList<WhitelistEntry>;
+/// The list of server operators.
+pub type OpList = PlayerList<OpEntry>;
+/// The list of banned players.
+pub type BannedPlayers = PlayerList<BanEntry>;
+
+impl<T: PlayerListEntry> PlayerList<T> {
+    /// Reads a list file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PlayerList<T>, PlayerListError>` - The list, or an empty list if
+    ///   the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not valid JSON, or holds
+    /// an invalid entry.
+    pub fn load(path: &Path) -> Result<Self, PlayerListError> {
+        let entries: Vec<T> = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        entries.iter().try_for_each(T::validate)?;
+        Ok(Self { entries })
+    }
+
+    /// Reads the list from its usual file inside a server directory.
+    ///
+    /// # Errors
+    ///
+    /// See [`PlayerList::load`].
+    pub fn load_from_dir(server_dir: &Path) -> Result<Self, PlayerListError> {
+        Self::load(&server_dir.join(T::FILE_NAME))
+    }
+
+    /// Writes the list to `path`, replacing the file atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), PlayerListError> {
+        write_atomic(path, &serde_json::to_vec_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// Returns the entry of the player with the given UUID.
+    pub fn get(&self, uuid: &str) -> Option<&T> {
+        self.entries
+            .iter()
+            .find(|e| e.uuid().eq_ignore_ascii_case(uuid))
+    }
+
+    /// Returns the entry of the player with the given name, ignoring case.
+    pub fn get_by_name(&self, name: &str) -> Option<&T> {
+        self.entries
+            .iter()
+            .find(|e| e.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Adds an entry, replacing the entry of the same player.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<T>, PlayerListError>` - The replaced entry, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry is invalid; the list is left unchanged.
+    pub fn insert(&mut self, entry: T) -> Result<Option<T>, PlayerListError> {
+        entry.validate()?;
+        match self
+            .entries
+            .iter()
+            .position(|e| e.uuid().eq_ignore_ascii_case(entry.uuid()))
+        {
+            Some(index) => Ok(Some(std::mem::replace(&mut self.entries[index], entry))),
+            None => {
+                self.entries.push(entry);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Removes the entry of the player with the given UUID, returning it.
+    pub fn remove(&mut self, uuid: &str) -> Option<T> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.uuid().eq_ignore_ascii_case(uuid))?;
+        Some(self.entries.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    const NOTCH: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+    const JEB: &str = "853c80ef-3c37-49fd-aa49-938b674adae6";
+
+    #[test]
+    fn edits_player_lists_with_validation() {
+        let dir = tempdir().unwrap();
+        let mut ops = OpList::load_from_dir(dir.path()).unwrap();
+        assert!(ops.insert(OpEntry::new(NOTCH, "Notch")).unwrap().is_none());
+        let replaced = ops
+            .insert(OpEntry::new(NOTCH, "Notch").with_level(2))
+            .unwrap();
+        assert_eq!(replaced.unwrap().level, 4);
+        assert!(matches!(
+            ops.insert(OpEntry::new(JEB, "jeb_").with_level(5)),
+            Err(PlayerListError::InvalidOpLevel(5))
+        ));
+        assert!(matches!(
+            ops.insert(OpEntry::new("853c80ef3c3749fdaa49938b674adae6", "jeb_")),
+            Err(PlayerListError::InvalidUuid(_))
+        ));
+        assert_eq!(ops.entries.len(), 1);
+        ops.save(&dir.path().join(OpEntry::FILE_NAME)).unwrap();
+        let loaded = OpList::load_from_dir(dir.path()).unwrap();
+        assert_eq!(loaded, ops);
+        assert_eq!(loaded.get_by_name("notch").unwrap().level, 2);
+
+        let path = dir.path().join(WhitelistEntry::FILE_NAME);
+        fs::write(
+            &path,
+            format!(
+                r#"[{{"uuid": "{}", "name": "jeb_", "note": "builder"}}]"#,
+                JEB
+            ),
+        )
+        .unwrap();
+        let mut whitelist = Whitelist::load(&path).unwrap();
+        assert_eq!(
+            whitelist.get(&JEB.to_uppercase()).unwrap().extra["note"],
+            "builder"
+        );
+        assert!(whitelist.remove(JEB).is_some());
+        assert!(whitelist.remove(JEB).is_none());
+
+        fs::write(&path, r#"[{"uuid": "not-a-uuid", "name": "x"}]"#).unwrap();
+        assert!(matches!(
+            Whitelist::load(&path),
+            Err(PlayerListError::InvalidUuid(_))
+        ));
+    }
+
+    #[test]
+    fn formats_ban_dates() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_312_709);
+        assert_eq!(format_ban_date(time), "2024-03-01 17:05:09 +0000");
+        let ban = BanEntry::new(NOTCH, "Notch").with_reason("Griefing");
+        assert!(ban.is_permanent());
+        assert!(!ban.with_expiry(time).is_permanent());
+    }
+}
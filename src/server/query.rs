@@ -0,0 +1,393 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::net::{ToSocketAddrs, UdpSocket, lookup_host};
+
+/// Port servers answer queries on unless `query.port` is set: the game port.
+pub const DEFAULT_QUERY_PORT: u16 = 25565;
+
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+const TYPE_HANDSHAKE: u8 = 9;
+const TYPE_STAT: u8 = 0;
+/// Constant bytes before the key/value section of a full stat.
+const FULL_STAT_KV_PADDING: usize = 11;
+/// Constant bytes between the key/value section and the players.
+const FULL_STAT_PLAYER_PADDING: usize = 10;
+/// Largest response a server sends.
+const MAX_RESPONSE: usize = 64 * 1024;
+
+/// Error returned when a server cannot be queried.
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("failed to query server: {0}")]
+    Io(#[from] io::Error),
+    #[error("server did not answer the query in time; is enable-query on?")]
+    Timeout,
+    #[error("invalid query response: {0}")]
+    InvalidResponse(String),
+}
+
+/// The short status of a server, from [`query_basic`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BasicStat {
+    pub motd: String,
+    /// Always `SMP`.
+    pub game_type: String,
+    /// The name of the main world.
+    pub map: String,
+    pub players: u32,
+    pub max_players: u32,
+    pub host_port: u16,
+    pub host_ip: String,
+}
+
+/// The full status of a server, from [`query_full`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FullStat {
+    pub motd: String,
+    pub game_type: String,
+    /// Always `MINECRAFT`.
+    pub game_id: String,
+    /// The game version, e.g. `1.20.1`.
+    pub version: String,
+    /// The server software, e.g. `Paper on Bukkit 1.20.1-R0.1-SNAPSHOT`, if it
+    /// reports its plugins. Vanilla servers report none.
+    pub server_mod: Option<String>,
+    /// The plugins with their versions, e.g. `WorldEdit 7.2.15`.
+    pub plugins: Vec<String>,
+    pub map: String,
+    pub players: u32,
+    pub max_players: u32,
+    pub host_port: u16,
+    pub host_ip: String,
+    /// The names of the players online.
+    pub player_names: Vec<String>,
+    /// Other keys, added by some server software.
+    pub extra: BTreeMap<String, String>,
+}
+
+/// Asks a server for its basic status over the Query protocol.
+///
+/// The server must have `enable-query=true` in `server.properties`.
+///
+/// # Arguments
+///
+/// * `addr` - The host and query port, e.g. `("example.com", 25565)`.
+/// * `timeout` - How long to wait for each answer.
+///
+/// # Errors
+///
+/// Returns [`QueryError::Timeout`] if the server does not answer, or an error
+/// if the answer is malformed.
+pub async fn query_basic(
+    addr: impl ToSocketAddrs,
+    timeout: Duration,
+) -> Result<BasicStat, QueryError> {
+    let response = query(addr, timeout, false).await?;
+    parse_basic_stat(&response)
+}
+
+/// Asks a server for its full status, with plugins and player names, over the
+/// Query protocol.
+///
+/// # Errors
+///
+/// Returns [`QueryError::Timeout`] if the server does not answer, or an error
+/// if the answer is malformed.
+pub async fn query_full(
+    addr: impl ToSocketAddrs,
+    timeout: Duration,
+) -> Result<FullStat, QueryError> {
+    let response = query(addr, timeout, true).await?;
+    parse_full_stat(&response)
+}
+
+/// Runs the handshake and a stat request, returning the stat payload after
+/// the type and session id.
+async fn query(
+    addr: impl ToSocketAddrs,
+    timeout: Duration,
+    full: bool,
+) -> Result<Vec<u8>, QueryError> {
+    let addr = lookup_host(addr)
+        .await?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host has no address"))?;
+    // The local socket must be of the server's family, or sending fails.
+    let local: SocketAddr = if addr.is_ipv6() {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(addr).await?;
+    // Servers only look at the low four bits of each byte.
+    let session = session_id() & 0x0F0F_0F0F;
+
+    let handshake = request(TYPE_HANDSHAKE, session);
+    let challenge = exchange(&socket, &handshake, session, TYPE_HANDSHAKE, timeout).await?;
+    let token: i32 = read_string(&mut challenge.as_slice())?
+        .trim()
+        .parse()
+        .map_err(|_| QueryError::InvalidResponse("invalid challenge token".to_string()))?;
+
+    let mut stat = request(TYPE_STAT, session);
+    stat.extend_from_slice(&token.to_be_bytes());
+    if full {
+        stat.extend_from_slice(&[0; 4]);
+    }
+    exchange(&socket, &stat, session, TYPE_STAT, timeout).await
+}
+
+fn request(kind: u8, session: u32) -> Vec<u8> {
+    let mut packet = MAGIC.to_vec();
+    packet.push(kind);
+    packet.extend_from_slice(&session.to_be_bytes());
+    packet
+}
+
+/// Sends a packet and waits for the answer of the same type and session.
+async fn exchange(
+    socket: &UdpSocket,
+    packet: &[u8],
+    session: u32,
+    kind: u8,
+    timeout: Duration,
+) -> Result<Vec<u8>, QueryError> {
+    socket.send(packet).await?;
+    let mut buf = vec![0; MAX_RESPONSE];
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let len = tokio::time::timeout_at(deadline, socket.recv(&mut buf))
+            .await
+            .map_err(|_| QueryError::Timeout)??;
+        // Late answers to earlier packets are skipped.
+        if len >= 5 && buf[0] == kind && buf[1..5] == session.to_be_bytes() {
+            return Ok(buf[5..len].to_vec());
+        }
+    }
+}
+
+fn session_id() -> u32 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    nanos ^ std::process::id()
+}
+
+/// Parses the payload of a basic stat answer.
+///
+/// # Errors
+///
+/// Returns an error if the payload is truncated or a number is invalid.
+pub fn parse_basic_stat(mut payload: &[u8]) -> Result<BasicStat, QueryError> {
+    let payload = &mut payload;
+    let motd = read_string(payload)?;
+    let game_type = read_string(payload)?;
+    let map = read_string(payload)?;
+    let players = parse_number(&read_string(payload)?)?;
+    let max_players = parse_number(&read_string(payload)?)?;
+    let Some((port, rest)) = payload.split_first_chunk::<2>() else {
+        return Err(truncated());
+    };
+    // The only little-endian field of the protocol.
+    let host_port = u16::from_le_bytes(*port);
+    *payload = rest;
+    Ok(BasicStat {
+        motd,
+        game_type,
+        map,
+        players,
+        max_players,
+        host_port,
+        host_ip: read_string(payload)?,
+    })
+}
+
+/// Parses the payload of a full stat answer.
+///
+/// # Errors
+///
+/// Returns an error if the payload is truncated or a number is invalid.
+pub fn parse_full_stat(payload: &[u8]) -> Result<FullStat, QueryError> {
+    let mut payload = payload.get(FULL_STAT_KV_PADDING..).ok_or_else(truncated)?;
+    let payload = &mut payload;
+    let mut values = BTreeMap::new();
+    loop {
+        let key = read_string(payload)?;
+        if key.is_empty() {
+            break;
+        }
+        values.insert(key, read_string(payload)?);
+    }
+    *payload = payload
+        .get(FULL_STAT_PLAYER_PADDING..)
+        .ok_or_else(truncated)?;
+    let mut player_names = Vec::new();
+    while !payload.is_empty() {
+        let name = read_string(payload)?;
+        if name.is_empty() {
+            break;
+        }
+        player_names.push(name);
+    }
+
+    let mut take = |key: &str| values.remove(key).unwrap_or_default();
+    let (server_mod, plugins) = parse_plugins(&take("plugins"));
+    let mut stat = FullStat {
+        motd: take("hostname"),
+        game_type: take("gametype"),
+        game_id: take("game_id"),
+        version: take("version"),
+        server_mod,
+        plugins,
+        map: take("map"),
+        players: 0,
+        max_players: 0,
+        host_port: 0,
+        host_ip: take("hostip"),
+        player_names,
+        extra: BTreeMap::new(),
+    };
+    stat.players = parse_number(&take("numplayers"))?;
+    stat.max_players = parse_number(&take("maxplayers"))?;
+    let port = take("hostport");
+    stat.host_port = port
+        .parse()
+        .map_err(|_| QueryError::InvalidResponse(format!("invalid port {:?}", port)))?;
+    stat.extra = values;
+    Ok(stat)
+}
+
+/// Splits `Paper on Bukkit 1.20.1: WorldEdit 7.2.15; LuckPerms 5.4.102` into
+/// the server software and its plugins.
+fn parse_plugins(value: &str) -> (Option<String>, Vec<String>) {
+    if value.trim().is_empty() {
+        return (None, Vec::new());
+    }
+    let (server_mod, plugins) = value.split_once(':').unwrap_or((value, ""));
+    let plugins = plugins
+        .split(';')
+        .map(str::trim)
+        .filter(|plugin| !plugin.is_empty())
+        .map(str::to_string)
+        .collect();
+    (Some(server_mod.trim().to_string()), plugins)
+}
+
+/// Reads a null-terminated string and advances past it.
+fn read_string(payload: &mut &[u8]) -> Result<String, QueryError> {
+    let end = payload.iter().position(|b| *b == 0).ok_or_else(truncated)?;
+    let text = String::from_utf8_lossy(&payload[..end]).into_owned();
+    *payload = &payload[end + 1..];
+    Ok(text)
+}
+
+fn parse_number(text: &str) -> Result<u32, QueryError> {
+    text.trim()
+        .parse()
+        .map_err(|_| QueryError::InvalidResponse(format!("invalid number {:?}", text)))
+}
+
+fn truncated() -> QueryError {
+    QueryError::InvalidResponse("truncated response".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_stat_payload() -> Vec<u8> {
+        let mut payload = b"splitnum\0\x80\0".to_vec();
+        for (key, value) in [
+            ("hostname", "A Minecraft Server"),
+            ("gametype", "SMP"),
+            ("game_id", "MINECRAFT"),
+            ("version", "1.20.1"),
+            (
+                "plugins",
+                "Paper on Bukkit 1.20.1: WorldEdit 7.2.15; LuckPerms 5.4.102",
+            ),
+            ("map", "world"),
+            ("numplayers", "2"),
+            ("maxplayers", "20"),
+            ("hostport", "25565"),
+            ("hostip", "127.0.0.1"),
+        ] {
+            payload.extend_from_slice(key.as_bytes());
+            payload.push(0);
+            payload.extend_from_slice(value.as_bytes());
+            payload.push(0);
+        }
+        payload.extend_from_slice(b"\0\x01player_\0\0Notch\0jeb_\0\0");
+        payload
+    }
+
+    #[test]
+    fn parses_basic_and_full_stats() {
+        let basic =
+            parse_basic_stat(b"A Minecraft Server\0SMP\0world\x002\x0020\0\xdd\x63127.0.0.1\0")
+                .unwrap();
+        assert_eq!(basic.motd, "A Minecraft Server");
+        assert_eq!((basic.players, basic.max_players), (2, 20));
+        assert_eq!(basic.host_port, 25565);
+        assert_eq!(basic.host_ip, "127.0.0.1");
+        assert!(parse_basic_stat(b"motd\0SMP\0").is_err());
+
+        let full = parse_full_stat(&full_stat_payload()).unwrap();
+        assert_eq!(full.version, "1.20.1");
+        assert_eq!(full.server_mod.as_deref(), Some("Paper on Bukkit 1.20.1"));
+        assert_eq!(full.plugins, ["WorldEdit 7.2.15", "LuckPerms 5.4.102"]);
+        assert_eq!(full.player_names, ["Notch", "jeb_"]);
+        assert_eq!(full.host_port, 25565);
+        assert!(full.extra.is_empty());
+    }
+
+    /// Answers queries on `server` with [`full_stat_payload`].
+    fn serve(server: UdpSocket) {
+        tokio::spawn(async move {
+            let mut buf = [0; 64];
+            loop {
+                let (len, peer) = server.recv_from(&mut buf).await.unwrap();
+                let session = &buf[3..7];
+                let mut reply = vec![buf[2]];
+                reply.extend_from_slice(session);
+                if buf[2] == TYPE_HANDSHAKE {
+                    reply.extend_from_slice(b"9513307\0");
+                } else {
+                    assert_eq!(&buf[7..11], &9513307i32.to_be_bytes());
+                    assert_eq!(len, 15);
+                    reply.extend_from_slice(&full_stat_payload());
+                }
+                server.send_to(&reply, peer).await.unwrap();
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn queries_a_server() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        serve(server);
+
+        let full = query_full(addr, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(full.motd, "A Minecraft Server");
+        assert_eq!(full.players, 2);
+
+        let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        assert!(matches!(
+            query_basic(silent.local_addr().unwrap(), Duration::from_millis(100)).await,
+            Err(QueryError::Timeout)
+        ));
+
+        // Hosts without IPv6 cannot run the server side.
+        if let Ok(server) = UdpSocket::bind("[::1]:0").await {
+            let addr = server.local_addr().unwrap();
+            serve(server);
+            let full = query_full(addr, Duration::from_secs(5)).await.unwrap();
+            assert_eq!(full.motd, "A Minecraft Server");
+        }
+    }
+}
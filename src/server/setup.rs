@@ -0,0 +1,175 @@
+use crate::filesystem::{FilesystemError, write_atomic};
+use crate::http::{DownloadRequest, HttpClient, HttpError};
+use crate::version::VersionJson;
+use crate::version::install::artifact_request;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// File name the server jar is saved as.
+pub const SERVER_JAR: &str = "server.jar";
+
+/// Name of the file recording that the EULA was accepted.
+pub const EULA_FILE: &str = "eula.txt";
+
+/// Where the Minecraft EULA can be read.
+pub const EULA_URL: &str = "https://aka.ms/MinecraftEULA";
+
+/// Error returned when a server cannot be set up.
+#[derive(Debug, Error)]
+pub enum ServerSetupError {
+    #[error("failed to read server files: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to write server files: {0}")]
+    Filesystem(#[from] FilesystemError),
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error("version {0} has no server jar")]
+    NoServerJar(String),
+    #[error("the EULA must be confirmed by the user before it is accepted")]
+    EulaNotConfirmed,
+}
+
+/// Returns the download of the server jar of a version into `server_dir`,
+/// checked against the size and sha1 of `downloads.server`.
+///
+/// # Errors
+///
+/// Returns [`ServerSetupError::NoServerJar`] if the version has no server
+/// jar, as for versions before 1.2.5.
+pub fn server_jar_request(
+    version: &VersionJson,
+    server_dir: &Path,
+) -> Result<DownloadRequest, ServerSetupError> {
+    match version.downloads.get("server") {
+        Some(server) if !server.url.is_empty() => {
+            Ok(artifact_request(server, server_dir.join(SERVER_JAR)))
+        }
+        _ => Err(ServerSetupError::NoServerJar(version.id.clone())),
+    }
+}
+
+impl HttpClient {
+    /// Downloads the server jar of a version into `server_dir` as
+    /// [`SERVER_JAR`].
+    ///
+    /// An existing jar that passes the check is kept.
+    ///
+    /// # Returns
+    ///
+    /// * `PathBuf` - The path of the verified jar.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version has no server jar, or if the download
+    /// fails or does not match the size and sha1 of the version JSON.
+    pub async fn download_server_jar(
+        &self,
+        version: &VersionJson,
+        server_dir: &Path,
+    ) -> Result<PathBuf, ServerSetupError> {
+        let request = server_jar_request(version, server_dir)?;
+        self.download(&request, false).await?;
+        Ok(request.path)
+    }
+}
+
+/// Returns whether `eula.txt` in `server_dir` accepts the EULA.
+///
+/// Like the server, `eula=true` is compared case-insensitively and a missing
+/// file means the EULA was not accepted.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn eula_accepted(server_dir: &Path) -> io::Result<bool> {
+    let text = match fs::read_to_string(server_dir.join(EULA_FILE)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    Ok(text.lines().any(|line| {
+        line.split_once('=').is_some_and(|(key, value)| {
+            key.trim() == "eula" && value.trim().eq_ignore_ascii_case("true")
+        })
+    }))
+}
+
+/// Writes `eula.txt` accepting the EULA into `server_dir`.
+///
+/// The launcher must show the user [`EULA_URL`] and pass their answer as
+/// `confirmed`; without it nothing is written, so the EULA is never accepted
+/// on the user's behalf.
+///
+/// # Errors
+///
+/// Returns [`ServerSetupError::EulaNotConfirmed`] if `confirmed` is false, or
+/// an error if the file cannot be written.
+pub fn accept_eula(server_dir: &Path, confirmed: bool) -> Result<(), ServerSetupError> {
+    if !confirmed {
+        return Err(ServerSetupError::EulaNotConfirmed);
+    }
+    fs::create_dir_all(server_dir)?;
+    let content = format!(
+        "#By changing the setting below to TRUE you are indicating your agreement to our EULA ({}).\neula=true\n",
+        EULA_URL
+    );
+    write_atomic(server_dir.join(EULA_FILE), content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::ClientOptions;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn downloads_and_verifies_the_server_jar() {
+        let dir = tempdir().unwrap();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/server.jar");
+            then.status(200).body("hello world");
+        });
+        let version = VersionJson::from_json(&format!(
+            r#"{{"id": "1.20.1", "downloads": {{"server": {{"url": "{}",
+                "sha1": "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed", "size": 11}}}}}}"#,
+            server.url("/server.jar")
+        ))
+        .unwrap();
+        let client = HttpClient::new(ClientOptions::default()).unwrap();
+
+        let jar = client
+            .download_server_jar(&version, dir.path())
+            .await
+            .unwrap();
+        assert_eq!(jar, dir.path().join(SERVER_JAR));
+        assert_eq!(fs::read(&jar).unwrap(), b"hello world");
+
+        let old = VersionJson::from_json(r#"{"id": "1.0"}"#).unwrap();
+        assert!(matches!(
+            server_jar_request(&old, dir.path()),
+            Err(ServerSetupError::NoServerJar(id)) if id == "1.0"
+        ));
+    }
+
+    #[test]
+    fn accepts_the_eula_only_when_confirmed() {
+        let dir = tempdir().unwrap();
+        assert!(!eula_accepted(dir.path()).unwrap());
+        assert!(matches!(
+            accept_eula(dir.path(), false),
+            Err(ServerSetupError::EulaNotConfirmed)
+        ));
+        assert!(!dir.path().join(EULA_FILE).exists());
+
+        accept_eula(dir.path(), true).unwrap();
+        assert!(eula_accepted(dir.path()).unwrap());
+        fs::write(dir.path().join(EULA_FILE), "#comment\neula = TRUE\n").unwrap();
+        assert!(eula_accepted(dir.path()).unwrap());
+        fs::write(dir.path().join(EULA_FILE), "eula=false\n").unwrap();
+        assert!(!eula_accepted(dir.path()).unwrap());
+    }
+}
@@ -0,0 +1,209 @@
+use super::json::{Argument, VersionJson};
+use super::rules::{Environment, rules_allow};
+use std::collections::HashMap;
+
+/// JVM arguments the vanilla launcher adds for versions without `arguments.jvm`.
+const LEGACY_JVM_ARGUMENTS: [&str; 3] = [
+    "-Djava.library.path=${natives_directory}",
+    "-cp",
+    "${classpath}",
+];
+
+/// Values substituted for `${...}` placeholders in launch arguments.
+///
+/// Common keys are `auth_player_name`, `auth_uuid`, `auth_access_token`,
+/// `user_type`, `version_name`, `version_type`, `game_directory`, `assets_root`,
+/// `assets_index_name`, `natives_directory`, `classpath`, `launcher_name` and
+/// `launcher_version`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArgumentValues {
+    values: HashMap<String, String>,
+}
+
+impl ArgumentValues {
+    /// Creates an empty set of values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of the placeholder `${key}`.
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    /// Sets the value of the placeholder `${key}`.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(key.into(), value.into());
+    }
+
+    /// Returns the value of the placeholder `${key}`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Replaces every known `${key}` in `template`. Unknown placeholders are kept.
+    pub fn substitute(&self, template: &str) -> String {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            match after.find('}') {
+                Some(end) => {
+                    let key = &after[..end];
+                    match self.values.get(key) {
+                        Some(value) => result.push_str(value),
+                        None => result.push_str(&rest[start..start + 2 + end + 1]),
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    result.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// The resolved command line arguments of a version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchArguments {
+    /// Arguments placed before the main class.
+    pub jvm: Vec<String>,
+    /// Arguments placed after the main class.
+    pub game: Vec<String>,
+}
+
+/// Resolves the JVM and game arguments of a version.
+///
+/// Conditional arguments are kept if their rules allow them in `env`, so features
+/// such as `is_demo_user` or `has_custom_resolution` are controlled through
+/// [`Environment::with_feature`]. Versions with only `minecraftArguments` get the
+/// vanilla default JVM arguments.
+///
+/// # Arguments
+///
+/// * `version` - The (merged) version JSON.
+/// * `env` - The platform and features to evaluate rules against.
+/// * `values` - Values for the `${...}` placeholders.
+///
+/// # Returns
+///
+/// * `LaunchArguments` - The substituted JVM and game arguments.
+pub fn resolve_arguments(
+    version: &VersionJson,
+    env: &Environment,
+    values: &ArgumentValues,
+) -> LaunchArguments {
+    let (jvm, game) = match &version.arguments {
+        Some(arguments) => (select(&arguments.jvm, env), select(&arguments.game, env)),
+        None => (Vec::new(), Vec::new()),
+    };
+    let jvm = if jvm.is_empty() {
+        LEGACY_JVM_ARGUMENTS.iter().map(|s| s.to_string()).collect()
+    } else {
+        jvm
+    };
+    let game = match (&version.minecraft_arguments, game.is_empty()) {
+        (Some(legacy), true) => legacy.split_whitespace().map(str::to_string).collect(),
+        _ => game,
+    };
+
+    LaunchArguments {
+        jvm: jvm.iter().map(|a| values.substitute(a)).collect(),
+        game: game.iter().map(|a| values.substitute(a)).collect(),
+    }
+}
+
+fn select(arguments: &[Argument], env: &Environment) -> Vec<String> {
+    let mut selected = Vec::new();
+    for argument in arguments {
+        match argument {
+            Argument::Plain(value) => selected.push(value.clone()),
+            Argument::Conditional { rules, value } if rules_allow(rules, env) => {
+                selected.extend(value.as_slice().iter().cloned())
+            }
+            Argument::Conditional { .. } => {}
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> ArgumentValues {
+        ArgumentValues::new()
+            .with("auth_player_name", "Steve")
+            .with("version_name", "1.20.1")
+            .with("natives_directory", "/tmp/natives")
+            .with("classpath", "a.jar:b.jar")
+    }
+
+    #[test]
+    fn substitutes_known_placeholders_and_keeps_unknown_ones() {
+        let values = values();
+        assert_eq!(
+            values.substitute("-Djava.library.path=${natives_directory}"),
+            "-Djava.library.path=/tmp/natives"
+        );
+        assert_eq!(values.substitute("${clientid}"), "${clientid}");
+        assert_eq!(
+            values.substitute("${auth_player_name}-${version_name}"),
+            "Steve-1.20.1"
+        );
+        assert_eq!(values.substitute("broken ${"), "broken ${");
+    }
+
+    #[test]
+    fn resolves_modern_arguments_with_rules() {
+        let version = VersionJson::from_json(
+            r#"{
+                "id": "1.20.1",
+                "arguments": {
+                    "game": ["--username", "${auth_player_name}",
+                        {"rules": [{"action": "allow", "features": {"is_demo_user": true}}], "value": "--demo"},
+                        {"rules": [{"action": "allow", "features": {"has_custom_resolution": true}}],
+                         "value": ["--width", "${resolution_width}"]}],
+                    "jvm": [{"rules": [{"action": "allow", "os": {"name": "osx"}}], "value": ["-XstartOnFirstThread"]},
+                        "-cp", "${classpath}"]
+                }
+            }"#,
+        )
+        .unwrap();
+        let env =
+            Environment::new("linux", "", "x86_64").with_feature("has_custom_resolution", true);
+        let values = values().with("resolution_width", "1280");
+
+        let arguments = resolve_arguments(&version, &env, &values);
+
+        assert_eq!(arguments.jvm, vec!["-cp", "a.jar:b.jar"]);
+        assert_eq!(
+            arguments.game,
+            vec!["--username", "Steve", "--width", "1280"]
+        );
+    }
+
+    #[test]
+    fn resolves_legacy_minecraft_arguments() {
+        let version = VersionJson::from_json(
+            r#"{"id": "1.7.10", "minecraftArguments": "--username ${auth_player_name} --version ${version_name}"}"#,
+        )
+        .unwrap();
+        let arguments = resolve_arguments(&version, &Environment::current(), &values());
+
+        assert_eq!(
+            arguments.jvm,
+            vec!["-Djava.library.path=/tmp/natives", "-cp", "a.jar:b.jar"]
+        );
+        assert_eq!(
+            arguments.game,
+            vec!["--username", "Steve", "--version", "1.20.1"]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use super::json::{Arguments, Library, VersionError, VersionJson};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Merges a child version into the version it inherits from.
+///
+/// Follows the vanilla launcher: scalar fields of the child win, child libraries
+/// come first and replace parent libraries with the same group, artifact and
+/// classifier, argument lists are concatenated parent first, and `downloads`,
+/// `logging` and unknown fields are merged with the child's entries winning. The
+/// result has the child's id and no `inheritsFrom`.
+///
+/// # Arguments
+///
+/// * `child` - The inheriting version, e.g. a Fabric profile.
+/// * `parent` - The version named by the child's `inheritsFrom`.
+///
+/// # Returns
+///
+/// * `VersionJson` - The merged version.
+pub fn merge_versions(child: &VersionJson, parent: &VersionJson) -> VersionJson {
+    let child_libraries: HashSet<_> = child.libraries.iter().map(library_key).collect();
+    let libraries = child
+        .libraries
+        .iter()
+        .chain(
+            parent
+                .libraries
+                .iter()
+                .filter(|library| !child_libraries.contains(&library_key(library))),
+        )
+        .cloned()
+        .collect();
+
+    let arguments = match (&parent.arguments, &child.arguments) {
+        (Some(parent), Some(child)) => Some(Arguments {
+            game: [parent.game.as_slice(), &child.game].concat(),
+            jvm: [parent.jvm.as_slice(), &child.jvm].concat(),
+        }),
+        (parent, child) => child.clone().or_else(|| parent.clone()),
+    };
+
+    let mut downloads = parent.downloads.clone();
+    downloads.extend(child.downloads.clone());
+    let mut logging = parent.logging.clone();
+    logging.extend(child.logging.clone());
+    let mut extra = parent.extra.clone();
+    extra.extend(child.extra.clone());
+
+    VersionJson {
+        id: child.id.clone(),
+        inherits_from: None,
+        kind: child.kind.clone().or_else(|| parent.kind.clone()),
+        main_class: child
+            .main_class
+            .clone()
+            .or_else(|| parent.main_class.clone()),
+        minecraft_arguments: child
+            .minecraft_arguments
+            .clone()
+            .or_else(|| parent.minecraft_arguments.clone()),
+        arguments,
+        libraries,
+        assets: child.assets.clone().or_else(|| parent.assets.clone()),
+        asset_index: child
+            .asset_index
+            .clone()
+            .or_else(|| parent.asset_index.clone()),
+        downloads,
+        java_version: child
+            .java_version
+            .clone()
+            .or_else(|| parent.java_version.clone()),
+        logging,
+        extra,
+    }
+}
+
+/// Resolves the `inheritsFrom` chain of a version into a single version.
+///
+/// # Arguments
+///
+/// * `version` - The version to resolve.
+/// * `load` - Loads a version by id.
+///
+/// # Returns
+///
+/// * `Result<VersionJson, VersionError>` - The fully merged version.
+///
+/// # Errors
+///
+/// Returns the error of `load`, or [`VersionError::InheritanceCycle`] if a
+/// version inherits from itself directly or indirectly.
+pub fn resolve_inheritance<F>(
+    version: VersionJson,
+    mut load: F,
+) -> Result<VersionJson, VersionError>
+where
+    F: FnMut(&str) -> Result<VersionJson, VersionError>,
+{
+    let mut chain = vec![version];
+    let mut seen = HashSet::from([chain[0].id.clone()]);
+    while let Some(parent_id) = chain.last().and_then(|v| v.inherits_from.clone()) {
+        if !seen.insert(parent_id.clone()) {
+            return Err(VersionError::InheritanceCycle(parent_id));
+        }
+        chain.push(load(&parent_id)?);
+    }
+
+    let mut merged = chain.pop().expect("chain is never empty");
+    while let Some(child) = chain.pop() {
+        merged = merge_versions(&child, &merged);
+    }
+    Ok(merged)
+}
+
+/// Loads `versions/<id>/<id>.json` and resolves its `inheritsFrom` chain from the
+/// same directory.
+///
+/// # Errors
+///
+/// Returns an error if a version in the chain cannot be read or parsed, or the
+/// chain contains a cycle.
+pub fn load_resolved_version(versions_dir: &Path, id: &str) -> Result<VersionJson, VersionError> {
+    let load =
+        |id: &str| VersionJson::from_file(versions_dir.join(id).join(format!("{}.json", id)));
+    resolve_inheritance(load(id)?, load)
+}
+
+fn library_key(library: &Library) -> (&str, &str, Option<&str>) {
+    (
+        &library.name.group,
+        &library.name.artifact,
+        library.name.classifier.as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn vanilla() -> VersionJson {
+        VersionJson::from_json(
+            r#"{
+                "id": "1.20.1", "type": "release",
+                "mainClass": "net.minecraft.client.main.Main",
+                "arguments": {"game": ["--username", "${auth_player_name}"], "jvm": ["-cp", "${classpath}"]},
+                "libraries": [
+                    {"name": "org.ow2.asm:asm:9.3"},
+                    {"name": "com.mojang:brigadier:1.1.8"}
+                ],
+                "assets": "5",
+                "downloads": {"client": {"url": "https://example.com/client.jar"}},
+                "complianceLevel": 1
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn fabric() -> VersionJson {
+        VersionJson::from_json(
+            r#"{
+                "id": "fabric-loader-0.16.9-1.20.1", "inheritsFrom": "1.20.1",
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                "arguments": {"game": [], "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "]},
+                "libraries": [
+                    {"name": "org.ow2.asm:asm:9.6"},
+                    {"name": "net.fabricmc:fabric-loader:0.16.9"}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn merges_child_over_parent() {
+        let merged = merge_versions(&fabric(), &vanilla());
+
+        assert_eq!(merged.id, "fabric-loader-0.16.9-1.20.1");
+        assert_eq!(merged.inherits_from, None);
+        assert_eq!(
+            merged.main_class.as_deref(),
+            Some("net.fabricmc.loader.impl.launch.knot.KnotClient")
+        );
+        assert_eq!(merged.kind.as_deref(), Some("release"));
+        assert_eq!(merged.assets.as_deref(), Some("5"));
+        let libraries: Vec<_> = merged
+            .libraries
+            .iter()
+            .map(|l| l.name.to_string())
+            .collect();
+        assert_eq!(
+            libraries,
+            vec![
+                "org.ow2.asm:asm:9.6",
+                "net.fabricmc:fabric-loader:0.16.9",
+                "com.mojang:brigadier:1.1.8"
+            ]
+        );
+        let arguments = merged.arguments.unwrap();
+        assert_eq!(arguments.game.len(), 2);
+        assert_eq!(arguments.jvm.len(), 3);
+        assert!(merged.downloads.contains_key("client"));
+        assert_eq!(merged.extra["complianceLevel"], 1);
+    }
+
+    #[test]
+    fn loads_chain_from_versions_directory_and_detects_cycles() {
+        let dir = tempdir().unwrap();
+        for version in [vanilla(), fabric()] {
+            let folder = dir.path().join(&version.id);
+            fs::create_dir_all(&folder).unwrap();
+            fs::write(
+                folder.join(format!("{}.json", version.id)),
+                serde_json::to_string(&version).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let merged = load_resolved_version(dir.path(), "fabric-loader-0.16.9-1.20.1").unwrap();
+        assert_eq!(merged.libraries.len(), 3);
+
+        let mut looping = vanilla();
+        looping.inherits_from = Some("1.20.1".to_string());
+        assert!(matches!(
+            resolve_inheritance(looping.clone(), |_| Ok(looping.clone())),
+            Err(VersionError::InheritanceCycle(id)) if id == "1.20.1"
+        ));
+    }
+}
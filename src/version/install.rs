@@ -0,0 +1,370 @@
+use super::json::{Artifact, Library, VersionJson};
+use super::logging::CLIENT_LOGGING;
+use super::natives::{NativeJar, native_classifier};
+use super::rules::rules_allow;
+use crate::assets::AssetIndex;
+use crate::http::{DownloadRequest, HashAlgorithm, HashSpec, Priority};
+use crate::platform::Platform;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Repository of libraries that name no repository of their own.
+const MOJANG_LIBRARIES: &str = "https://libraries.minecraft.net/";
+
+/// The shared folders a version is installed into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallPaths {
+    /// The `libraries` folder, a local Maven repository.
+    pub libraries: PathBuf,
+    /// The `assets` folder with `indexes`, `objects` and `log_configs`.
+    pub assets: PathBuf,
+    /// The `versions` folder holding `<id>/<id>.jar`.
+    pub versions: PathBuf,
+}
+
+impl InstallPaths {
+    /// Returns the vanilla layout below a launcher folder such as
+    /// `.minecraft`.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            libraries: root.join("libraries"),
+            assets: root.join("assets"),
+            versions: root.join("versions"),
+        }
+    }
+}
+
+/// What a file of an [`InstallPlan`] is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InstallFileKind {
+    /// The game jar in the `versions` folder.
+    ClientJar,
+    /// A library on the classpath.
+    Library,
+    /// A jar of native libraries, extracted before launch.
+    Native,
+    /// The index listing the assets of the version.
+    AssetIndex,
+    /// An object of the asset index, stored by its hash.
+    Asset,
+    /// The log4j configuration of the `logging` section.
+    LogConfig,
+}
+
+/// A file to download for an install.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallFile {
+    /// What the file is for.
+    pub kind: InstallFileKind,
+    /// Where to download the file from and to, with its size and hash.
+    pub request: DownloadRequest,
+}
+
+/// Every file a version needs, from [`plan_install`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallPlan {
+    /// The files to download, each destination once.
+    pub files: Vec<InstallFile>,
+    /// The native jars to extract with
+    /// [`super::natives::extract_natives`] once downloaded.
+    pub natives: Vec<NativeJar>,
+    /// Libraries without a download URL, which an installer must provide,
+    /// e.g. the patched client of Forge.
+    pub unavailable: Vec<String>,
+}
+
+impl InstallPlan {
+    /// Returns the download requests, ready for the download manager.
+    pub fn requests(&self) -> Vec<DownloadRequest> {
+        self.files.iter().map(|file| file.request.clone()).collect()
+    }
+
+    /// Returns the total size in bytes of the files with a known size.
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().filter_map(|file| file.request.size).sum()
+    }
+
+    /// Returns the number of files and their total size for one kind.
+    pub fn totals(&self, kind: InstallFileKind) -> (usize, u64) {
+        self.files
+            .iter()
+            .filter(|file| file.kind == kind)
+            .fold((0, 0), |(count, size), file| {
+                (count + 1, size + file.request.size.unwrap_or(0))
+            })
+    }
+}
+
+/// Plans the install of a version on this computer.
+///
+/// See [`plan_install_with`].
+pub fn plan_install(
+    version: &VersionJson,
+    index: &AssetIndex,
+    paths: &InstallPaths,
+) -> InstallPlan {
+    plan_install_with(version, index, paths, &Platform::current())
+}
+
+/// Plans the download of every file a version needs: the client jar, the
+/// libraries and natives its rules allow on `platform`, the asset index and
+/// objects, and the log configuration.
+///
+/// The client jar goes to `versions/<jar>/<jar>.jar`, where `<jar>` is the
+/// version's `jar` field or else its id. Client jar and libraries are
+/// [`Priority::Critical`], assets [`Priority::Low`]. Files that are already
+/// present are skipped by the download manager when their hash matches.
+///
+/// # Arguments
+///
+/// * `version` - The resolved version JSON, see
+///   [`super::inherit::load_resolved_version`].
+/// * `index` - The asset index named by the version.
+/// * `paths` - The shared folders.
+/// * `platform` - The platform to evaluate library rules for.
+///
+/// # Returns
+///
+/// * `InstallPlan` - The downloads with URL, size and hash, and the native
+///   jars to extract.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(version = %version.id))
+)]
+pub fn plan_install_with(
+    version: &VersionJson,
+    index: &AssetIndex,
+    paths: &InstallPaths,
+    platform: &Platform,
+) -> InstallPlan {
+    let mut plan = InstallPlan::default();
+    let mut seen = HashSet::new();
+    let mut add = |plan: &mut InstallPlan, kind, request: DownloadRequest| {
+        if seen.insert(request.path.clone()) {
+            plan.files.push(InstallFile { kind, request });
+        }
+    };
+
+    if let Some(client) = version.downloads.get("client") {
+        let jar = version
+            .extra
+            .get("jar")
+            .and_then(|jar| jar.as_str())
+            .unwrap_or(&version.id);
+        let path = paths.versions.join(jar).join(format!("{}.jar", jar));
+        add(
+            &mut plan,
+            InstallFileKind::ClientJar,
+            artifact_request(client, path).with_priority(Priority::Critical),
+        );
+    }
+
+    let env = platform.environment();
+    for library in &version.libraries {
+        if !rules_allow(&library.rules, &env) {
+            continue;
+        }
+        let artifact = library
+            .downloads
+            .as_ref()
+            .and_then(|downloads| downloads.artifact.as_ref());
+        // Native-only libraries before 1.19 have no main artifact.
+        if artifact.is_some() || library.natives.is_none() {
+            match library_request(library, artifact, None, &paths.libraries) {
+                Some(request) => add(&mut plan, InstallFileKind::Library, request),
+                None => plan.unavailable.push(library.name.to_string()),
+            }
+        }
+        if let Some(classifier) = native_classifier(library, platform) {
+            let artifact = library
+                .downloads
+                .as_ref()
+                .and_then(|downloads| downloads.classifiers.get(&classifier));
+            match library_request(library, artifact, Some(&classifier), &paths.libraries) {
+                Some(request) => {
+                    plan.natives.push(
+                        NativeJar::new(&request.path)
+                            .with_rules(library.extract.clone().unwrap_or_default()),
+                    );
+                    add(&mut plan, InstallFileKind::Native, request);
+                }
+                None => plan
+                    .unavailable
+                    .push(format!("{}:{}", library.name, classifier)),
+            }
+        }
+    }
+
+    if let Some(info) = &version.asset_index {
+        let mut request = DownloadRequest::new(
+            &info.url,
+            paths
+                .assets
+                .join("indexes")
+                .join(format!("{}.json", info.id)),
+        );
+        if let Some(size) = info.size {
+            request = request.with_size(size);
+        }
+        if let Some(sha1) = &info.sha1 {
+            request = request.with_hash(HashSpec::new(HashAlgorithm::Sha1, sha1));
+        }
+        add(&mut plan, InstallFileKind::AssetIndex, request);
+    }
+    let objects = paths.assets.join("objects");
+    for object in index.objects.values() {
+        let request = DownloadRequest::new(object.url(), objects.join(object.object_path()))
+            .with_size(object.size)
+            .with_hash(HashSpec::new(HashAlgorithm::Sha1, &object.hash))
+            .with_priority(Priority::Low);
+        add(&mut plan, InstallFileKind::Asset, request);
+    }
+
+    if let Some(logging) = version.logging.get(CLIENT_LOGGING) {
+        let path = paths.assets.join("log_configs").join(&logging.file.id);
+        add(
+            &mut plan,
+            InstallFileKind::LogConfig,
+            DownloadRequest::new(&logging.file.url, path).with_check(logging.file.check()),
+        );
+    }
+    #[cfg(feature = "tracing")]
+    {
+        for library in &plan.unavailable {
+            tracing::warn!(%library, "library has no download");
+        }
+        tracing::debug!(
+            files = plan.files.len(),
+            bytes = plan.total_size(),
+            "planned install"
+        );
+    }
+    plan
+}
+
+/// Builds the request of a library jar from its artifact, or else from its
+/// Maven coordinate and repository. Returns `None` if it has no URL.
+fn library_request(
+    library: &Library,
+    artifact: Option<&Artifact>,
+    classifier: Option<&str>,
+    libraries: &Path,
+) -> Option<DownloadRequest> {
+    let mut coordinate = library.name.clone();
+    if let Some(classifier) = classifier {
+        coordinate = coordinate.with_classifier(classifier);
+    }
+    let request = match artifact {
+        Some(artifact) => {
+            if artifact.url.is_empty() {
+                return None;
+            }
+            let path = match &artifact.path {
+                Some(path) => path
+                    .split('/')
+                    .fold(libraries.to_path_buf(), |dir, part| dir.join(part)),
+                None => coordinate.local_path(libraries),
+            };
+            artifact_request(artifact, path)
+        }
+        None => DownloadRequest::new(
+            coordinate.url(library.url.as_deref().unwrap_or(MOJANG_LIBRARIES)),
+            coordinate.local_path(libraries),
+        ),
+    };
+    Some(request.with_priority(Priority::Critical))
+}
+
+/// Builds the request of an artifact, checked against its size and sha1 if given.
+pub(crate) fn artifact_request(artifact: &Artifact, path: PathBuf) -> DownloadRequest {
+    let mut request = DownloadRequest::new(&artifact.url, path);
+    if let Some(size) = artifact.size {
+        request = request.with_size(size);
+    }
+    if let Some(sha1) = &artifact.sha1 {
+        request = request.with_hash(HashSpec::new(HashAlgorithm::Sha1, sha1));
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{Arch, OsName};
+
+    const VERSION: &str = r#"{
+        "id": "1.16.5",
+        "downloads": {"client": {"sha1": "37fd3c903861eeff3bc24b71eed48f828b5269c8", "size": 17547153,
+            "url": "https://launcher.mojang.com/client.jar"}},
+        "assetIndex": {"id": "1.16", "sha1": "f8e11ca03b475dd655755b945334c7a0ac2c3b43", "size": 295421,
+            "url": "https://launchermeta.mojang.com/1.16.json"},
+        "logging": {"client": {"argument": "-Dlog4j.configurationFile=${path}", "type": "log4j2-xml",
+            "file": {"id": "client-1.12.xml", "sha1": "bd65e7d2e3c237be76cfbef4c2405033d7f91521",
+                "size": 888, "url": "https://launcher.mojang.com/client-1.12.xml"}}},
+        "libraries": [
+            {"name": "com.mojang:brigadier:1.0.17", "downloads": {"artifact": {
+                "path": "com/mojang/brigadier/1.0.17/brigadier-1.0.17.jar",
+                "sha1": "c6b7dc51dd44379cc751b7504816006e9be4b1e6", "size": 77392,
+                "url": "https://libraries.minecraft.net/com/mojang/brigadier/1.0.17/brigadier-1.0.17.jar"}}},
+            {"name": "org.lwjgl:lwjgl-platform:2.9.4", "natives": {"linux": "natives-linux", "windows": "natives-windows"},
+                "extract": {"exclude": ["META-INF/"]},
+                "downloads": {"classifiers": {"natives-linux": {
+                    "sha1": "931074f46c795d2f7b30ed6395df5715cfd7675b", "size": 578680,
+                    "url": "https://libraries.minecraft.net/lwjgl-platform-natives-linux.jar",
+                    "path": "org/lwjgl/lwjgl-platform/2.9.4/lwjgl-platform-2.9.4-natives-linux.jar"}}}},
+            {"name": "ca.weblite:java-objc-bridge:1.0.0", "rules": [{"action": "allow", "os": {"name": "osx"}}]},
+            {"name": "net.fabricmc:fabric-loader:0.16.9", "url": "https://maven.fabricmc.net/"},
+            {"name": "net.minecraftforge:forge:1.16.5-36.2.39:client", "downloads": {"artifact": {"url": ""}}}
+        ]
+    }"#;
+
+    #[test]
+    fn plans_every_file_of_a_version() {
+        let version = VersionJson::from_json(VERSION).unwrap();
+        let index = AssetIndex::from_json(
+            r#"{"objects": {
+                "icons/icon_16x16.png": {"hash": "bdf48ef6b5d0d23bbb02e17d04865216179f510a", "size": 3665},
+                "minecraft/icons/icon_16x16.png": {"hash": "bdf48ef6b5d0d23bbb02e17d04865216179f510a", "size": 3665},
+                "minecraft/sounds/ambient/cave/cave1.ogg": {"hash": "aa", "size": 100}}}"#,
+        )
+        .unwrap();
+        let paths = InstallPaths::new(Path::new("/mc"));
+        let linux = Platform::new(OsName::Linux, "6.1", Arch::X86_64);
+        let plan = plan_install_with(&version, &index, &paths, &linux);
+
+        let kinds: Vec<_> = plan.files.iter().map(|file| file.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                InstallFileKind::ClientJar,
+                InstallFileKind::Library,
+                InstallFileKind::Native,
+                InstallFileKind::Library,
+                InstallFileKind::AssetIndex,
+                InstallFileKind::Asset,
+                InstallFileKind::Asset,
+                InstallFileKind::LogConfig
+            ]
+        );
+        assert_eq!(
+            plan.files[0].request.path,
+            Path::new("/mc/versions/1.16.5/1.16.5.jar")
+        );
+        assert_eq!(
+            plan.files[3].request.url,
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.16.9/fabric-loader-0.16.9.jar"
+        );
+        assert_eq!(plan.natives.len(), 1);
+        assert_eq!(plan.natives[0].rules.exclude, ["META-INF/"]);
+        assert_eq!(
+            plan.unavailable,
+            ["net.minecraftforge:forge:1.16.5-36.2.39:client"]
+        );
+        assert_eq!(plan.totals(InstallFileKind::Asset), (2, 3765));
+        assert_eq!(
+            plan.total_size(),
+            17547153 + 77392 + 578680 + 295421 + 3765 + 888
+        );
+        assert_eq!(plan.requests().len(), 8);
+    }
+}
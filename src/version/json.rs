@@ -0,0 +1,259 @@
+use super::logging::LoggingConfig;
+use super::natives::ExtractRules;
+use super::rules::Rule;
+use crate::maven::MavenCoordinate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Error returned when a version JSON cannot be loaded.
+#[derive(Debug, Error)]
+pub enum VersionError {
+    #[error("failed to read version JSON: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse version JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("version {0} inherits from itself")]
+    InheritanceCycle(String),
+}
+
+/// A version JSON as found in `versions/<id>/<id>.json`.
+///
+/// Fields this crate does not model are kept in `extra` and written back unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionJson {
+    /// The version id, e.g. `1.20.1` or `fabric-loader-0.16.9-1.20.1`.
+    pub id: String,
+    /// Id of the version this one extends, used by loader profiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherits_from: Option<String>,
+    /// `release`, `snapshot`, `old_beta` or `old_alpha`.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// The class to launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_class: Option<String>,
+    /// Space-separated game arguments of versions before 1.13.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minecraft_arguments: Option<String>,
+    /// Structured JVM and game arguments of 1.13 and later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Arguments>,
+    /// Libraries on the classpath, including natives.
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+    /// Id of the asset index, e.g. `5`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assets: Option<String>,
+    /// Where to download the asset index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_index: Option<AssetIndexInfo>,
+    /// Client and server jars, keyed by `client`, `server`, `client_mappings`, ...
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub downloads: BTreeMap<String, Artifact>,
+    /// The Java runtime the version was built for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub java_version: Option<JavaVersion>,
+    /// Log4j configurations keyed by side, usually only `client`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub logging: BTreeMap<String, LoggingConfig>,
+    /// All other fields.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl VersionJson {
+    /// Parses a version JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a valid version JSON.
+    pub fn from_json(json: &str) -> Result<Self, VersionError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads a version JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, VersionError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+/// The `arguments` section of a version JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arguments {
+    /// Arguments passed to the main class.
+    #[serde(default)]
+    pub game: Vec<Argument>,
+    /// Arguments passed to the JVM.
+    #[serde(default)]
+    pub jvm: Vec<Argument>,
+}
+
+/// A single entry of an argument list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Argument {
+    /// An argument that always applies.
+    Plain(String),
+    /// Arguments that apply only if their rules allow them.
+    Conditional {
+        /// The rules deciding whether `value` applies.
+        rules: Vec<Rule>,
+        /// One or several arguments.
+        value: ArgumentValue,
+    },
+}
+
+/// The value of a conditional argument.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ArgumentValue {
+    /// A single argument.
+    One(String),
+    /// Several arguments.
+    Many(Vec<String>),
+}
+
+impl ArgumentValue {
+    /// Returns the arguments as a slice.
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            ArgumentValue::One(value) => std::slice::from_ref(value),
+            ArgumentValue::Many(values) => values,
+        }
+    }
+}
+
+/// A downloadable file with its expected size and sha1.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Path relative to the libraries directory, for library artifacts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Hex sha1 of the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    /// Size in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Download URL. Empty for files that must be provided otherwise.
+    #[serde(default)]
+    pub url: String,
+}
+
+/// A library of a version JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Library {
+    /// The Maven coordinate of the library.
+    pub name: MavenCoordinate,
+    /// Explicit downloads. Loader profiles often omit them and give `url` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<LibraryDownloads>,
+    /// Maven repository the library is downloaded from when `downloads` is missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Rules deciding whether the library applies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// Native classifier per OS name, for versions before 1.19. `${arch}` is
+    /// replaced by `32` or `64`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub natives: Option<BTreeMap<String, String>>,
+    /// Entries to skip when extracting the natives.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract: Option<ExtractRules>,
+}
+
+/// The `downloads` section of a library.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryDownloads {
+    /// The main jar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+    /// Jars per classifier, e.g. `natives-windows`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub classifiers: BTreeMap<String, Artifact>,
+}
+
+/// The `assetIndex` section of a version JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetIndexInfo {
+    /// The index id, e.g. `5` or `legacy`.
+    pub id: String,
+    /// Hex sha1 of the index file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    /// Size of the index file in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Total size of all assets in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_size: Option<u64>,
+    /// Download URL of the index.
+    #[serde(default)]
+    pub url: String,
+}
+
+/// The `javaVersion` section of a version JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaVersion {
+    /// Mojang runtime component, e.g. `java-runtime-gamma`.
+    pub component: String,
+    /// Required major Java version, e.g. `17`.
+    pub major_version: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modern_version_json_and_keeps_unknown_fields() {
+        let json = r#"{
+            "id": "1.20.1",
+            "type": "release",
+            "mainClass": "net.minecraft.client.main.Main",
+            "arguments": {
+                "game": ["--username", "${auth_player_name}",
+                    {"rules": [{"action": "allow", "features": {"is_demo_user": true}}], "value": "--demo"}],
+                "jvm": [{"rules": [{"action": "allow", "os": {"name": "osx"}}], "value": ["-XstartOnFirstThread"]}]
+            },
+            "libraries": [{
+                "name": "com.mojang:brigadier:1.1.8",
+                "downloads": {"artifact": {
+                    "path": "com/mojang/brigadier/1.1.8/brigadier-1.1.8.jar",
+                    "sha1": "5244ce82c3337bba4a196a3ce858bfaecc74404a",
+                    "size": 77392,
+                    "url": "https://libraries.minecraft.net/com/mojang/brigadier/1.1.8/brigadier-1.1.8.jar"
+                }}
+            }],
+            "javaVersion": {"component": "java-runtime-gamma", "majorVersion": 17},
+            "complianceLevel": 1
+        }"#;
+        let version = VersionJson::from_json(json).unwrap();
+
+        assert_eq!(version.kind.as_deref(), Some("release"));
+        let arguments = version.arguments.as_ref().unwrap();
+        assert_eq!(arguments.game.len(), 3);
+        assert!(
+            matches!(&arguments.jvm[0], Argument::Conditional { value, .. }
+            if value.as_slice() == ["-XstartOnFirstThread"])
+        );
+        assert_eq!(version.libraries[0].name.artifact, "brigadier");
+        assert_eq!(version.java_version.as_ref().unwrap().major_version, 17);
+        assert_eq!(version.extra["complianceLevel"], 1);
+
+        let written = serde_json::to_value(&version).unwrap();
+        assert_eq!(written["complianceLevel"], 1);
+        assert_eq!(written["mainClass"], "net.minecraft.client.main.Main");
+    }
+}
@@ -0,0 +1,291 @@
+use super::json::VersionJson;
+use crate::http::{DownloadRequest, FileCheck, HashAlgorithm, HashSpec};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Key of the client configuration in the `logging` section.
+pub const CLIENT_LOGGING: &str = "client";
+
+/// Argument used when a version has no `logging` section of its own.
+const DEFAULT_LOGGING_ARGUMENT: &str = "-Dlog4j.configurationFile=${path}";
+
+/// Disables message lookups in log4j 2.10 and later.
+pub const NO_LOOKUPS_ARGUMENT: &str = "-Dlog4j2.formatMsgNoLookups=true";
+
+/// An entry of the `logging` section of a version JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// JVM argument pointing log4j at the file, with a `${path}` placeholder.
+    pub argument: String,
+    /// The configuration file.
+    pub file: LoggingFile,
+    /// Format of the file, `log4j2-xml` for all released versions.
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// The configuration file of a [`LoggingConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggingFile {
+    /// File name, e.g. `client-1.12.xml`.
+    pub id: String,
+    /// Hex sha1 of the file.
+    pub sha1: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Download URL.
+    pub url: String,
+}
+
+impl LoggingFile {
+    /// Returns the size and sha1 as a [`FileCheck`].
+    pub fn check(&self) -> FileCheck {
+        FileCheck::new(self.size, HashSpec::new(HashAlgorithm::Sha1, &self.sha1))
+    }
+}
+
+/// The log4j 2 configurations the launcher writes itself.
+///
+/// Both log to `logs/latest.log` like the vanilla configurations, with message
+/// lookups disabled against CVE-2021-44228 (Log4Shell).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Log4jConfig {
+    /// For 1.7 to 1.11.2, whose log4j 2.0-beta9 lacks `%msg{nolookups}`:
+    /// messages containing `${` are dropped.
+    Legacy,
+    /// For 1.12 to 1.16.5, with `%msg{nolookups}` and XML console output.
+    Modern,
+}
+
+impl Log4jConfig {
+    /// File name under which the configuration is stored.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Log4jConfig::Legacy => "log4j2_17-111.xml",
+            Log4jConfig::Modern => "log4j2_112-116.xml",
+        }
+    }
+
+    /// Returns the XML document.
+    pub fn xml(self) -> String {
+        let (layout, message, lookup_filter) = match self {
+            Log4jConfig::Legacy => (
+                "LegacyXMLLayout",
+                "%msg",
+                "\n                <RegexFilter regex=\"(?s).*\\$\\{[^}]*\\}.*\" onMatch=\"DENY\" onMismatch=\"NEUTRAL\"/>",
+            ),
+            Log4jConfig::Modern => ("XMLLayout", "%msg{nolookups}", ""),
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Configuration status="WARN">
+    <Appenders>
+        <Console name="SysOut" target="SYSTEM_OUT">
+            <{layout} />
+        </Console>
+        <RollingRandomAccessFile name="File" fileName="logs/latest.log" filePattern="logs/%d{{yyyy-MM-dd}}-%i.log.gz">
+            <PatternLayout pattern="[%d{{HH:mm:ss}}] [%t/%level]: {message}%n" />
+            <Policies>
+                <TimeBasedTriggeringPolicy />
+                <OnStartupTriggeringPolicy />
+            </Policies>
+        </RollingRandomAccessFile>
+    </Appenders>
+    <Loggers>
+        <Root level="info">
+            <filters>{lookup_filter}
+                <MarkerFilter marker="NETWORK_PACKETS" onMatch="DENY" onMismatch="NEUTRAL" />
+            </filters>
+            <AppenderRef ref="SysOut"/>
+            <AppenderRef ref="File"/>
+        </Root>
+    </Loggers>
+</Configuration>
+"#
+        )
+    }
+}
+
+/// How a game version is protected against Log4Shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Log4jMitigation {
+    /// The version does not use log4j, or ships a fixed log4j (1.18.1 and later).
+    None,
+    /// The version's configuration is replaced by a patched one.
+    PatchedConfig(Log4jConfig),
+    /// The version's log4j honours [`NO_LOOKUPS_ARGUMENT`] (1.17 to 1.18).
+    NoLookupsProperty,
+}
+
+impl Log4jMitigation {
+    /// Returns the mitigation for a game version such as `1.16.5`.
+    ///
+    /// Only release ids and their pre-releases (`1.18-pre1`) are recognised;
+    /// snapshots and other ids get [`Log4jMitigation::None`].
+    pub fn for_game_version(game_version: &str) -> Self {
+        let release = game_version.split(['-', ' ']).next().unwrap_or_default();
+        let mut parts = release.split('.').map(str::parse::<u32>);
+        let (Some(Ok(1)), Some(Ok(minor))) = (parts.next(), parts.next()) else {
+            return Log4jMitigation::None;
+        };
+        let patch = match parts.next() {
+            Some(Ok(patch)) => patch,
+            Some(Err(_)) => return Log4jMitigation::None,
+            None => 0,
+        };
+        match (minor, patch) {
+            (7..=11, _) => Log4jMitigation::PatchedConfig(Log4jConfig::Legacy),
+            (12..=16, _) => Log4jMitigation::PatchedConfig(Log4jConfig::Modern),
+            (17, _) | (18, 0) => Log4jMitigation::NoLookupsProperty,
+            _ => Log4jMitigation::None,
+        }
+    }
+}
+
+/// What to download, write and pass to the JVM to configure logging.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoggingSetup {
+    /// The version's configuration file to download, unless it is replaced.
+    pub download: Option<DownloadRequest>,
+    /// A generated configuration to write to [`LoggingSetup::config_path`].
+    pub generated: Option<String>,
+    /// Where the configuration lives.
+    pub config_path: PathBuf,
+    /// Arguments to add to the JVM arguments.
+    pub jvm_arguments: Vec<String>,
+}
+
+impl LoggingSetup {
+    /// Writes the generated configuration, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write_generated(&self) -> io::Result<()> {
+        let Some(xml) = &self.generated else {
+            return Ok(());
+        };
+        if let Some(parent) = self.config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.config_path, xml)
+    }
+}
+
+/// Plans the logging configuration of a client launch.
+///
+/// The version's `logging.client` file is downloaded into `log_configs_dir`,
+/// except for versions before 1.17, where a patched configuration is generated
+/// instead. Versions from 1.17 to 1.18 also get [`NO_LOOKUPS_ARGUMENT`].
+///
+/// # Arguments
+///
+/// * `version` - The resolved version JSON.
+/// * `game_version` - The Minecraft version, e.g. `1.16.5`. Differs from the
+///   version id for loader profiles.
+/// * `log_configs_dir` - Usually `assets/log_configs`.
+///
+/// # Returns
+///
+/// * `Option<LoggingSetup>` - The setup, or `None` if the version configures no
+///   logging and needs no mitigation.
+pub fn plan_logging(
+    version: &VersionJson,
+    game_version: &str,
+    log_configs_dir: &Path,
+) -> Option<LoggingSetup> {
+    let config = version.logging.get(CLIENT_LOGGING);
+    let argument = config.map_or(DEFAULT_LOGGING_ARGUMENT, |c| c.argument.as_str());
+    let mut setup = LoggingSetup::default();
+    match Log4jMitigation::for_game_version(game_version) {
+        Log4jMitigation::PatchedConfig(patched) => {
+            setup.config_path = log_configs_dir.join(patched.file_name());
+            setup.generated = Some(patched.xml());
+        }
+        mitigation => {
+            let config = config?;
+            setup.config_path = log_configs_dir.join(&config.file.id);
+            setup.download = Some(
+                DownloadRequest::new(&config.file.url, &setup.config_path)
+                    .with_check(config.file.check()),
+            );
+            if mitigation == Log4jMitigation::NoLookupsProperty {
+                setup.jvm_arguments.push(NO_LOOKUPS_ARGUMENT.to_string());
+            }
+        }
+    }
+    setup
+        .jvm_arguments
+        .push(argument.replace("${path}", &setup.config_path.to_string_lossy()));
+    Some(setup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn version_with_logging(id: &str) -> VersionJson {
+        VersionJson::from_json(&format!(
+            r#"{{
+                "id": "{id}",
+                "logging": {{"client": {{
+                    "argument": "-Dlog4j.configurationFile=${{path}}",
+                    "file": {{"id": "client-1.12.xml", "sha1": "bd65e7d2e3c237be76cfbef4c2405033d7f91521",
+                        "size": 888, "url": "https://example.com/client-1.12.xml"}},
+                    "type": "log4j2-xml"
+                }}}}
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn picks_mitigation_by_game_version() {
+        use Log4jMitigation::*;
+        assert_eq!(Log4jMitigation::for_game_version("1.6.4"), None);
+        assert_eq!(
+            Log4jMitigation::for_game_version("1.7.10"),
+            PatchedConfig(Log4jConfig::Legacy)
+        );
+        assert_eq!(
+            Log4jMitigation::for_game_version("1.16.5"),
+            PatchedConfig(Log4jConfig::Modern)
+        );
+        assert_eq!(
+            Log4jMitigation::for_game_version("1.18-pre1"),
+            NoLookupsProperty
+        );
+        assert_eq!(Log4jMitigation::for_game_version("1.18.1"), None);
+        assert_eq!(Log4jMitigation::for_game_version("21w37a"), None);
+        assert!(Log4jConfig::Legacy.xml().contains("RegexFilter"));
+        assert!(Log4jConfig::Modern.xml().contains("%msg{nolookups}%n"));
+    }
+
+    #[test]
+    fn plans_download_or_generated_config() {
+        let dir = tempdir().unwrap();
+        let version = version_with_logging("1.17.1");
+        let setup = plan_logging(&version, "1.17.1", dir.path()).unwrap();
+        let download = setup.download.as_ref().unwrap();
+        assert_eq!(download.path, dir.path().join("client-1.12.xml"));
+        assert_eq!(download.size, Some(888));
+        assert_eq!(setup.jvm_arguments[0], NO_LOOKUPS_ARGUMENT);
+        assert_eq!(
+            setup.jvm_arguments[1],
+            format!("-Dlog4j.configurationFile={}", download.path.display())
+        );
+
+        let setup = plan_logging(&version, "1.12.2", dir.path()).unwrap();
+        assert!(setup.download.is_none());
+        setup.write_generated().unwrap();
+        let written = fs::read_to_string(dir.path().join("log4j2_112-116.xml")).unwrap();
+        assert_eq!(written, Log4jConfig::Modern.xml());
+        assert_eq!(setup.jvm_arguments.len(), 1);
+
+        assert!(plan_logging(&VersionJson::default(), "1.20.1", dir.path()).is_none());
+        assert!(plan_logging(&VersionJson::default(), "1.8.9", dir.path()).is_some());
+    }
+}
@@ -0,0 +1,328 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// First weekly snapshot of each release cycle, with the release it led to.
+///
+/// A weekly snapshot belongs to the last cycle that started before it.
+const SNAPSHOT_CYCLES: &[(u32, u32, &str)] = &[
+    (11, 47, "1.1"),
+    (12, 1, "1.2.1"),
+    (12, 15, "1.3.1"),
+    (12, 32, "1.4.2"),
+    (12, 49, "1.4.6"),
+    (13, 1, "1.5"),
+    (13, 11, "1.5.1"),
+    (13, 16, "1.6.1"),
+    (13, 36, "1.7.2"),
+    (13, 47, "1.7.4"),
+    (14, 2, "1.8"),
+    (15, 31, "1.9"),
+    (16, 14, "1.9.3"),
+    (16, 20, "1.10"),
+    (16, 32, "1.11"),
+    (16, 50, "1.11.1"),
+    (17, 6, "1.12"),
+    (17, 31, "1.12.1"),
+    (17, 43, "1.13"),
+    (18, 30, "1.13.1"),
+    (18, 43, "1.14"),
+    (19, 34, "1.15"),
+    (20, 6, "1.16"),
+    (20, 27, "1.16.2"),
+    (20, 45, "1.17"),
+    (21, 37, "1.18"),
+    (22, 3, "1.18.2"),
+    (22, 11, "1.19"),
+    (22, 24, "1.19.1"),
+    (22, 42, "1.19.3"),
+    (23, 3, "1.19.4"),
+    (23, 12, "1.20"),
+    (23, 31, "1.20.2"),
+    (23, 40, "1.20.3"),
+    (23, 51, "1.20.5"),
+    (24, 18, "1.21"),
+    (24, 33, "1.21.2"),
+    (24, 44, "1.21.4"),
+    (25, 2, "1.21.5"),
+    (25, 15, "1.21.6"),
+    (25, 31, "1.21.9"),
+    (25, 41, "1.21.11"),
+];
+
+/// The last weekly snapshot of [`SNAPSHOT_CYCLES`], 25w46a.
+const LAST_KNOWN_WEEK: (u32, u32) = (25, 46);
+
+/// Error returned when a string is not a Minecraft version id.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum McVersionError {
+    #[error("invalid Minecraft version {0:?}")]
+    Invalid(String),
+}
+
+/// The kind of a Minecraft version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum McVersionKind {
+    Release,
+    Snapshot,
+    PreRelease,
+    ReleaseCandidate,
+}
+
+/// Development stage of a version within its release cycle, in order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+    /// Weekly snapshot: year, week and suffix, e.g. `23w31a`.
+    Weekly(u32, u32, String),
+    /// Numbered snapshot of year-based versions, e.g. `26.1-snapshot-1`.
+    Snapshot(u32),
+    PreRelease(u32),
+    ReleaseCandidate(u32),
+    Release,
+    /// Weekly snapshot newer than the last known cycle, which sorts after that
+    /// cycle's release.
+    Unplanned(u32, u32, String),
+}
+
+/// A Minecraft version id such as `1.20.1`, `23w31a`, `1.20.2-pre1`,
+/// `1.16-rc1` or `26.1-snapshot-1`, ordered by when it was released.
+///
+/// Snapshots sort after the previous release and before the pre-releases of
+/// the release they lead to, pre-releases before release candidates, and
+/// release candidates before the release. Trailing zeros are ignored, so
+/// `1.20` equals `1.20.0`.
+///
+/// Old alpha and beta versions, April Fools' snapshots with non-ASCII ids
+/// and other experimental builds are not supported.
+#[derive(Debug, Clone)]
+pub struct McVersion {
+    id: String,
+    /// The release, or the release the snapshot leads to.
+    release: Vec<u32>,
+    stage: Stage,
+}
+
+impl McVersion {
+    /// Parses a version id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McVersionError::Invalid`] if `id` is not a release, weekly
+    /// snapshot, pre-release or release candidate id.
+    pub fn parse(id: &str) -> Result<Self, McVersionError> {
+        let invalid = || McVersionError::Invalid(id.to_string());
+        if let Some((year, week, suffix)) = parse_weekly(id) {
+            let cycle = SNAPSHOT_CYCLES
+                .iter()
+                .rev()
+                .find(|(y, w, _)| (*y, *w) <= (year, week))
+                .ok_or_else(invalid)?;
+            let stage = if (year, week) > LAST_KNOWN_WEEK {
+                Stage::Unplanned(year, week, suffix)
+            } else {
+                Stage::Weekly(year, week, suffix)
+            };
+            return Ok(Self {
+                id: id.to_string(),
+                release: parse_release(cycle.2).ok_or_else(invalid)?,
+                stage,
+            });
+        }
+
+        let end = id
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(id.len());
+        let release = parse_release(&id[..end]).ok_or_else(invalid)?;
+        let suffix = id[end..]
+            .trim_start_matches(['-', ' '])
+            .to_ascii_lowercase();
+        let stage = if suffix.is_empty() {
+            Stage::Release
+        } else {
+            let (prefix, number) = ["pre-release", "pre", "rc", "snapshot"]
+                .into_iter()
+                .find_map(|prefix| Some((prefix, suffix.strip_prefix(prefix)?)))
+                .ok_or_else(invalid)?;
+            let number = number.trim_start_matches(['-', ' ']);
+            let number = if number.is_empty() {
+                0
+            } else {
+                number.parse().map_err(|_| invalid())?
+            };
+            match prefix {
+                "rc" => Stage::ReleaseCandidate(number),
+                "snapshot" => Stage::Snapshot(number),
+                _ => Stage::PreRelease(number),
+            }
+        };
+        Ok(Self {
+            id: id.to_string(),
+            release,
+            stage,
+        })
+    }
+
+    /// Returns the id as given.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the kind of the version.
+    pub fn kind(&self) -> McVersionKind {
+        match self.stage {
+            Stage::Weekly(..) | Stage::Snapshot(_) | Stage::Unplanned(..) => {
+                McVersionKind::Snapshot
+            }
+            Stage::PreRelease(_) => McVersionKind::PreRelease,
+            Stage::ReleaseCandidate(_) => McVersionKind::ReleaseCandidate,
+            Stage::Release => McVersionKind::Release,
+        }
+    }
+
+    /// Returns whether the version is a release.
+    pub fn is_release(&self) -> bool {
+        self.stage == Stage::Release
+    }
+
+    /// Returns whether the version is `other` or newer, e.g.
+    /// `is_at_least("1.13")`. An invalid `other` gives `false`.
+    pub fn is_at_least(&self, other: &str) -> bool {
+        McVersion::parse(other).is_ok_and(|other| *self >= other)
+    }
+
+    /// Returns whether the version is older than `other`. An invalid `other`
+    /// gives `false`.
+    pub fn is_before(&self, other: &str) -> bool {
+        McVersion::parse(other).is_ok_and(|other| *self < other)
+    }
+
+    fn key(&self) -> (&[u32], &Stage) {
+        let len = self
+            .release
+            .iter()
+            .rposition(|part| *part != 0)
+            .map_or(0, |i| i + 1);
+        (&self.release[..len], &self.stage)
+    }
+}
+
+/// Parses `YYwWW` followed by a suffix such as `a` or `potato`.
+fn parse_weekly(id: &str) -> Option<(u32, u32, String)> {
+    let (year, rest) = id.split_once('w')?;
+    if year.len() != 2 || rest.len() < 3 {
+        return None;
+    }
+    let (week, suffix) = rest.split_at_checked(2)?;
+    let valid_suffix = suffix
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid_suffix || !suffix.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return None;
+    }
+    Some((year.parse().ok()?, week.parse().ok()?, suffix.to_string()))
+}
+
+/// Parses `major.minor[.patch...]`.
+fn parse_release(release: &str) -> Option<Vec<u32>> {
+    let parts = release
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    (parts.len() >= 2).then_some(parts)
+}
+
+impl FromStr for McVersion {
+    type Err = McVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        McVersion::parse(s)
+    }
+}
+
+impl fmt::Display for McVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl PartialEq for McVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for McVersion {}
+
+impl PartialOrd for McVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for McVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(id: &str) -> McVersion {
+        McVersion::parse(id).unwrap()
+    }
+
+    #[test]
+    fn orders_releases_snapshots_and_pre_releases() {
+        let ordered = [
+            "1.9.4",
+            "1.10",
+            "1.12.2",
+            "17w43a",
+            "1.13-pre1",
+            "1.13",
+            "1.20.1",
+            "23w31a",
+            "23w33a",
+            "1.20.2-pre1",
+            "1.20.2-rc1",
+            "1.20.2",
+            "23w40a",
+            "1.20.3",
+            "1.21.11",
+            "26.1-snapshot-1",
+            "26.1-snapshot-2",
+            "26.1-pre-1",
+            "26.1-rc-1",
+            "26.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("1.20"), v("1.20.0"));
+        assert_eq!(v("1.14 Pre-Release 2"), v("1.14-pre2"));
+        assert_eq!(v("23w31a").kind(), McVersionKind::Snapshot);
+        assert_eq!(v("1.16-rc1").kind(), McVersionKind::ReleaseCandidate);
+        assert!(v("1.20.1").is_release());
+    }
+
+    #[test]
+    fn checks_ranges_and_rejects_other_ids() {
+        assert!(v("1.13").is_at_least("1.13"));
+        assert!(v("18w22c").is_before("1.13"));
+        assert!(v("18w30a").is_at_least("1.13"));
+        assert!(!v("1.12.2").is_at_least("1.13"));
+        assert!(!v("1.20").is_at_least("not a version"));
+        assert!(v("25w50a") > v("1.21.11"));
+        assert_eq!(v("1.20.2-pre1").to_string(), "1.20.2-pre1");
+
+        for id in ["b1.7.3", "1", "1.x", "1.20-beta", "abc", "11w01a"] {
+            assert_eq!(
+                McVersion::parse(id).unwrap_err(),
+                McVersionError::Invalid(id.to_string())
+            );
+        }
+    }
+}
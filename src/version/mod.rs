@@ -0,0 +1,35 @@
+/// Placeholder substitution for JVM and game arguments.
+pub mod arguments;
+/// Resolution of `inheritsFrom` chains.
+pub mod inherit;
+/// Planning the downloads of a full version install.
+pub mod install;
+/// The version JSON document model.
+pub mod json;
+/// The `logging` section and the log4j configurations the launcher provides.
+pub mod logging;
+/// Minecraft version ids and their release order.
+pub mod mc_version;
+/// Extraction of native library jars.
+pub mod natives;
+/// Evaluation of `rules` guarding libraries and arguments.
+pub mod rules;
+
+pub use arguments::{ArgumentValues, LaunchArguments, resolve_arguments};
+pub use inherit::{load_resolved_version, merge_versions, resolve_inheritance};
+pub use install::{
+    InstallFile, InstallFileKind, InstallPaths, InstallPlan, plan_install, plan_install_with,
+};
+pub use json::{
+    Argument, ArgumentValue, Arguments, Artifact, AssetIndexInfo, JavaVersion, Library,
+    LibraryDownloads, VersionError, VersionJson,
+};
+pub use logging::{
+    CLIENT_LOGGING, Log4jConfig, Log4jMitigation, LoggingConfig, LoggingFile, LoggingSetup,
+    NO_LOOKUPS_ARGUMENT, plan_logging,
+};
+pub use mc_version::{McVersion, McVersionError, McVersionKind};
+pub use natives::{
+    ExtractRules, NativeJar, NativesReport, extract_natives, has_arm64_natives, native_classifier,
+};
+pub use rules::{Environment, OsRule, Rule, RuleAction, rules_allow};
@@ -0,0 +1,263 @@
+use super::json::{Library, VersionJson};
+use super::rules::rules_allow;
+use crate::platform::{Arch, Platform};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+/// The `extract` section of a native library.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractRules {
+    /// Path prefixes inside the jar that are not extracted, usually `META-INF/`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl ExtractRules {
+    /// Returns `true` if the entry `name` is excluded.
+    pub fn is_excluded(&self, name: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|prefix| name.starts_with(prefix.as_str()))
+    }
+}
+
+/// A resolved native library jar together with its extraction rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeJar {
+    /// Path of the downloaded jar.
+    pub path: PathBuf,
+    /// Entries to skip.
+    pub rules: ExtractRules,
+}
+
+impl NativeJar {
+    /// Creates a native jar that is extracted completely.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            rules: ExtractRules::default(),
+        }
+    }
+
+    /// Sets the extraction rules.
+    pub fn with_rules(mut self, rules: ExtractRules) -> Self {
+        self.rules = rules;
+        self
+    }
+}
+
+/// Files written by [`extract_natives`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NativesReport {
+    /// Extracted files, relative to the natives directory.
+    pub extracted: Vec<PathBuf>,
+    /// Entries skipped because an earlier jar already provided a file with the same path.
+    pub duplicates: Vec<PathBuf>,
+}
+
+/// Returns the natives classifier a library before 1.19 uses on `platform`,
+/// e.g. `natives-windows-64` for `natives-windows-${arch}`.
+///
+/// # Returns
+///
+/// * `Option<String>` - The classifier, or `None` if the library has no
+///   natives for the platform's OS.
+pub fn native_classifier(library: &Library, platform: &Platform) -> Option<String> {
+    let classifier = library.natives.as_ref()?.get(platform.os.as_str())?;
+    Some(classifier.replace("${arch}", platform.arch.bits()))
+}
+
+/// Returns whether a version ships arm64 natives for the OS of `platform`.
+///
+/// Versions before 1.19 only have x86 natives on macOS and Windows, so they
+/// must run on an x86_64 Java under Rosetta 2 or Windows' emulation there.
+/// A library counts if its rules allow it on arm64 and one of its
+/// classifiers names `arm64` or `aarch64`.
+pub fn has_arm64_natives(version: &VersionJson, platform: &Platform) -> bool {
+    let arm64 = Platform {
+        arch: Arch::Arm64,
+        ..platform.clone()
+    };
+    let env = arm64.environment();
+    let is_arm64 =
+        |classifier: &str| classifier.contains("arm64") || classifier.contains("aarch64");
+    version
+        .libraries
+        .iter()
+        .filter(|library| rules_allow(&library.rules, &env))
+        .any(|library| {
+            library.name.classifier.as_deref().is_some_and(is_arm64)
+                || native_classifier(library, &arm64).is_some_and(|c| is_arm64(&c))
+                || library
+                    .downloads
+                    .as_ref()
+                    .is_some_and(|d| d.classifiers.keys().any(|c| is_arm64(c)))
+        })
+}
+
+/// Extracts native library jars into `natives_dir`, ready to be passed as
+/// `-Djava.library.path`.
+///
+/// Jars are processed in order. Excluded entries and directories are skipped, and
+/// when several jars contain the same path the first one wins. Existing files in
+/// `natives_dir` are overwritten.
+///
+/// # Arguments
+///
+/// * `jars` - The native jars for the current platform.
+/// * `natives_dir` - The directory to extract into. It is created if needed.
+///
+/// # Returns
+///
+/// * `io::Result<NativesReport>` - The extracted and duplicate entries.
+///
+/// # Errors
+///
+/// Returns an error if a jar cannot be read or a file cannot be written. Entries
+/// with paths escaping `natives_dir` are rejected as `InvalidData`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip(jars), fields(natives_dir = %natives_dir.display(), jars = jars.len()), err)
+)]
+pub fn extract_natives(jars: &[NativeJar], natives_dir: &Path) -> io::Result<NativesReport> {
+    fs::create_dir_all(natives_dir)?;
+    let mut seen = HashSet::new();
+    let mut report = NativesReport::default();
+
+    for jar in jars {
+        let mut archive = zip::ZipArchive::new(BufReader::new(File::open(&jar.path)?))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if entry.is_dir() || jar.rules.is_excluded(entry.name()) {
+                continue;
+            }
+            let relative = entry.enclosed_name().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "unsafe entry name {:?} in {}",
+                        entry.name(),
+                        jar.path.display()
+                    ),
+                )
+            })?;
+            if !seen.insert(relative.clone()) {
+                report.duplicates.push(relative);
+                continue;
+            }
+
+            let target = natives_dir.join(&relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut entry, &mut File::create(&target)?)?;
+            report.extracted.push(relative);
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::OsName;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_jar(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn extracts_honoring_excludes_and_deduplicating() {
+        let dir = tempdir().unwrap();
+        let lwjgl = dir.path().join("lwjgl-natives.jar");
+        let openal = dir.path().join("openal-natives.jar");
+        write_jar(
+            &lwjgl,
+            &[
+                ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0"),
+                ("liblwjgl.so", b"lwjgl"),
+                ("libshared.so", b"first"),
+            ],
+        );
+        write_jar(
+            &openal,
+            &[("libopenal.so", b"openal"), ("libshared.so", b"second")],
+        );
+        let rules = ExtractRules {
+            exclude: vec!["META-INF/".to_string()],
+        };
+        let natives = dir.path().join("natives");
+
+        let report = extract_natives(
+            &[
+                NativeJar::new(&lwjgl).with_rules(rules.clone()),
+                NativeJar::new(&openal).with_rules(rules),
+            ],
+            &natives,
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.extracted,
+            vec![
+                PathBuf::from("liblwjgl.so"),
+                PathBuf::from("libshared.so"),
+                PathBuf::from("libopenal.so")
+            ]
+        );
+        assert_eq!(report.duplicates, vec![PathBuf::from("libshared.so")]);
+        assert_eq!(fs::read(natives.join("libshared.so")).unwrap(), b"first");
+        assert!(!natives.join("META-INF").exists());
+    }
+
+    #[test]
+    fn finds_classifiers_and_arm64_natives() {
+        let legacy = VersionJson::from_json(
+            r#"{"id": "1.12.2", "libraries": [{
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                "natives": {"linux": "natives-linux", "osx": "natives-osx",
+                    "windows": "natives-windows-${arch}"}
+            }]}"#,
+        )
+        .unwrap();
+        let modern = VersionJson::from_json(
+            r#"{"id": "1.20.1", "libraries": [
+                {"name": "org.lwjgl:lwjgl:3.3.1:natives-macos"},
+                {"name": "org.lwjgl:lwjgl:3.3.1:natives-macos-arm64",
+                    "rules": [{"action": "allow", "os": {"name": "osx"}}]}
+            ]}"#,
+        )
+        .unwrap();
+        let windows = Platform::new(OsName::Windows, "10.0", Arch::X86);
+        let mac = Platform::new(OsName::Osx, "14.4", Arch::Arm64);
+
+        let lwjgl = &legacy.libraries[0];
+        assert_eq!(
+            native_classifier(lwjgl, &windows).as_deref(),
+            Some("natives-windows-32")
+        );
+        assert_eq!(
+            native_classifier(lwjgl, &mac).as_deref(),
+            Some("natives-osx")
+        );
+        assert!(!has_arm64_natives(&legacy, &mac));
+        assert!(has_arm64_natives(&modern, &mac));
+        assert!(!has_arm64_natives(&modern, &windows));
+    }
+}
@@ -0,0 +1,184 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Whether a matching rule allows or disallows its library or argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// The element applies.
+    Allow,
+    /// The element does not apply.
+    Disallow,
+}
+
+/// The `os` condition of a rule. Unset fields match any value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsRule {
+    /// Operating system name: `windows`, `osx` or `linux`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Regular expression matched against the OS version, e.g. `^10\\.`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Architecture, e.g. `x86`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+}
+
+/// A single entry of a version JSON `rules` list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    /// What to do if the rule matches.
+    pub action: RuleAction,
+    /// Operating system condition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<OsRule>,
+    /// Launcher feature condition, e.g. `{"is_demo_user": true}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<BTreeMap<String, bool>>,
+}
+
+impl Rule {
+    /// Returns `true` if all conditions of the rule hold in `env`.
+    ///
+    /// Features missing from `env` count as disabled. An invalid version
+    /// expression never matches.
+    pub fn matches(&self, env: &Environment) -> bool {
+        if let Some(os) = &self.os {
+            if os.name.as_ref().is_some_and(|name| *name != env.os_name) {
+                return false;
+            }
+            if os.arch.as_ref().is_some_and(|arch| *arch != env.arch) {
+                return false;
+            }
+            if let Some(pattern) = &os.version {
+                let matched = Regex::new(pattern).is_ok_and(|re| re.is_match(&env.os_version));
+                if !matched {
+                    return false;
+                }
+            }
+        }
+        if let Some(features) = &self.features {
+            return features
+                .iter()
+                .all(|(name, expected)| env.feature(name) == *expected);
+        }
+        true
+    }
+}
+
+/// The platform and launcher features rules are evaluated against.
+///
+/// Construct one with [`Environment::new`] to evaluate rules for another platform,
+/// e.g. in tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment {
+    /// Operating system name in version JSON terms: `windows`, `osx` or `linux`.
+    pub os_name: String,
+    /// Operating system version, matched by `os.version` patterns.
+    pub os_version: String,
+    /// Architecture in version JSON terms, e.g. `x86` or `x86_64`.
+    pub arch: String,
+    /// Launcher features such as `is_demo_user` or `has_custom_resolution`.
+    pub features: HashMap<String, bool>,
+}
+
+impl Environment {
+    /// Creates an environment without enabled features.
+    pub fn new(
+        os_name: impl Into<String>,
+        os_version: impl Into<String>,
+        arch: impl Into<String>,
+    ) -> Self {
+        Self {
+            os_name: os_name.into(),
+            os_version: os_version.into(),
+            arch: arch.into(),
+            features: HashMap::new(),
+        }
+    }
+
+    /// Returns the environment of the running process, see
+    /// [`crate::platform::Platform::current`].
+    pub fn current() -> Self {
+        crate::platform::Platform::current().environment()
+    }
+
+    /// Enables or disables a launcher feature.
+    pub fn with_feature(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.features.insert(name.into(), enabled);
+        self
+    }
+
+    /// Returns whether a launcher feature is enabled.
+    pub fn feature(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+}
+
+/// Decides whether an element guarded by `rules` applies in `env`.
+///
+/// An empty list allows the element. Otherwise the element is disallowed unless a
+/// rule matches, and the last matching rule decides.
+///
+/// # Arguments
+///
+/// * `rules` - The `rules` list of a library or argument.
+/// * `env` - The platform and features to evaluate against.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the element applies.
+pub fn rules_allow(rules: &[Rule], env: &Environment) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(env))
+        .is_some_and(|rule| rule.action == RuleAction::Allow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(json: &str) -> Vec<Rule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        // The classic "everything but macOS" library rule.
+        let rules =
+            rules(r#"[{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]"#);
+        let linux = Environment::new("linux", "6.1", "x86_64");
+        let mac = Environment::new("osx", "14.0", "arm64");
+        assert!(rules_allow(&rules, &linux));
+        assert!(!rules_allow(&rules, &mac));
+        assert!(rules_allow(&[], &mac));
+    }
+
+    #[test]
+    fn os_version_and_arch_conditions() {
+        let rules = rules(
+            r#"[{"action": "allow", "os": {"name": "windows", "version": "^10\\.", "arch": "x86"}}]"#,
+        );
+        let win10 = Environment::new("windows", "10.0.19045", "x86");
+        let win7 = Environment::new("windows", "6.1", "x86");
+        let win10_64 = Environment::new("windows", "10.0", "x86_64");
+        assert!(rules_allow(&rules, &win10));
+        assert!(!rules_allow(&rules, &win7));
+        assert!(!rules_allow(&rules, &win10_64));
+    }
+
+    #[test]
+    fn feature_conditions_default_to_disabled() {
+        let rules = rules(r#"[{"action": "allow", "features": {"is_demo_user": true}}]"#);
+        let env = Environment::new("linux", "", "x86_64");
+        assert!(!rules_allow(&rules, &env));
+        assert!(rules_allow(&rules, &env.with_feature("is_demo_user", true)));
+    }
+}